@@ -0,0 +1,177 @@
+//! Authorization server issuing authorization codes and access tokens.
+//!
+//! Every authorization request is approved without user interaction. The
+//! `cli_loopback` example can be used as a client:
+//!
+//! ```sh
+//! cargo run --example axum_server --features axum
+//! cargo run --example cli_loopback --features examples,reqwest -- \
+//!     http://127.0.0.1:3000/authorize http://127.0.0.1:3000/token cli
+//! ```
+use std::{
+	borrow::Cow,
+	collections::HashMap,
+	sync::{Arc, Mutex},
+	time::Duration,
+};
+
+use iref::{Uri, UriBuf, uri};
+use open_auth2::{
+	AccessTokenBuf, ClientId, ClientIdBuf, CodeBuf, IssuerBuf, Stateful,
+	endpoints::token::TokenResponse,
+	ext::pkce::{PkceCodeChallengeAndMethod, PkceCodeVerifierBuf},
+	grant::authorization_code::{
+		AuthorizationCodeAuthorizationRequest, AuthorizationCodeTokenRequest,
+	},
+	server::{
+		AuthorizationContext, AuthorizationServerMetadata, AuthorizeInterrupt, OAuth2Router,
+		OAuth2Server, OAuth2ServerError,
+	},
+	util::NoExtension,
+};
+use serde::Deserialize;
+
+const ISSUER: &Uri = uri!("http://127.0.0.1:3000");
+
+/// Authorization request, with a mandatory PKCE challenge.
+#[derive(Deserialize)]
+struct AuthorizationRequest {
+	#[serde(flatten)]
+	request: AuthorizationCodeAuthorizationRequest,
+
+	#[serde(flatten)]
+	pkce: PkceCodeChallengeAndMethod,
+}
+
+/// Authorization code token request, with a mandatory PKCE verifier.
+#[derive(Deserialize)]
+struct TokenRequest {
+	#[serde(flatten)]
+	request: AuthorizationCodeTokenRequest,
+
+	code_verifier: PkceCodeVerifierBuf,
+}
+
+/// Authorization code issued to a client.
+struct Grant {
+	client_id: ClientIdBuf,
+	redirect_uri: Option<UriBuf>,
+	pkce: PkceCodeChallengeAndMethod,
+}
+
+struct Server {
+	metadata: AuthorizationServerMetadata,
+
+	/// Redirection URIs registered by each client.
+	clients: HashMap<ClientIdBuf, Vec<UriBuf>>,
+
+	/// Pending authorization codes.
+	codes: Mutex<HashMap<CodeBuf, Grant>>,
+}
+
+impl Server {
+	fn new() -> Self {
+		let mut metadata =
+			AuthorizationServerMetadata::new(IssuerBuf::new(ISSUER.to_string()).unwrap());
+		metadata.authorization_endpoint =
+			Some(UriBuf::new(format!("{ISSUER}/authorize").into_bytes()).unwrap());
+		metadata.token_endpoint =
+			Some(UriBuf::new(format!("{ISSUER}/token").into_bytes()).unwrap());
+
+		let clients = HashMap::from([(
+			ClientIdBuf::new("cli".to_owned()).unwrap(),
+			vec![uri!("http://127.0.0.1/callback").to_owned()],
+		)]);
+
+		Self {
+			metadata,
+			clients,
+			codes: Mutex::new(HashMap::new()),
+		}
+	}
+}
+
+impl OAuth2Server for Server {
+	type Metadata = NoExtension;
+	type AuthorizationRequest = AuthorizationRequest;
+	type TokenRequest = TokenRequest;
+	type TokenResponse = TokenResponse;
+
+	async fn redirect_uris(&self, client_id: &ClientId) -> Option<Vec<UriBuf>> {
+		self.clients.get(client_id).cloned()
+	}
+
+	async fn metadata(
+		&self,
+	) -> Result<Cow<'_, AuthorizationServerMetadata<Self::Metadata>>, OAuth2ServerError> {
+		Ok(Cow::Borrowed(&self.metadata))
+	}
+
+	async fn authenticate_user(
+		&self,
+		_context: &AuthorizationContext,
+		_request: &Stateful<Self::AuthorizationRequest>,
+	) -> Result<String, AuthorizeInterrupt> {
+		Ok("alice".to_owned())
+	}
+
+	async fn issue_code(
+		&self,
+		_subject: &str,
+		context: &AuthorizationContext,
+		request: &Stateful<Self::AuthorizationRequest>,
+	) -> Result<CodeBuf, AuthorizeInterrupt> {
+		let code = CodeBuf::new_random();
+		self.codes.lock().unwrap().insert(
+			code.clone(),
+			Grant {
+				client_id: context.client_id.clone(),
+				redirect_uri: request.value.request.redirect_uri.clone(),
+				pkce: request.value.pkce.clone(),
+			},
+		);
+
+		Ok(code)
+	}
+
+	async fn token(
+		&self,
+		TokenRequest {
+			request,
+			code_verifier,
+		}: Self::TokenRequest,
+	) -> Result<Self::TokenResponse, OAuth2ServerError> {
+		let grant = self
+			.codes
+			.lock()
+			.unwrap()
+			.remove(&request.code)
+			.ok_or(OAuth2ServerError::InvalidGrant)?;
+
+		let challenge = grant.pkce.method.transform(&code_verifier);
+		if request.client_id.as_ref() != Some(&grant.client_id)
+			|| request.redirect_uri != grant.redirect_uri
+			|| *challenge != *grant.pkce.challenge
+		{
+			return Err(OAuth2ServerError::InvalidGrant);
+		}
+
+		Ok(TokenResponse::new(
+			AccessTokenBuf::new_random(),
+			"Bearer".to_owned(),
+			NoExtension {},
+		)
+		.with_expires_in(Duration::from_secs(3600)))
+	}
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> std::io::Result<()> {
+	let app = axum::Router::new()
+		.oauth2_routes()
+		.with_state(Arc::new(Server::new()));
+
+	let listener = tokio::net::TcpListener::bind("127.0.0.1:3000").await?;
+	println!("Listening on {ISSUER}");
+	axum::serve(listener, app).await
+}
@@ -0,0 +1,84 @@
+//! Command-line client obtaining an access token with the Authorization Code
+//! Grant, using PKCE and a loopback redirection endpoint.
+//!
+//! ```sh
+//! cargo run --example cli_loopback --features examples,reqwest -- \
+//!     https://auth.example.com/authorize https://auth.example.com/token my-client-id
+//! ```
+use iref::UriBuf;
+use open_auth2::{
+	AddState, ClientId, ClientIdBuf, StateBuf, Stateful,
+	client::{ExternalUserAgent, OAuth2Client, SystemBrowser},
+	endpoints::{authorization::AuthorizationEndpoint, token::TokenEndpoint},
+	examples::LoopbackRedirect,
+	ext::pkce::{AddPkceChallenge, AddPkceVerifier, PkceCodeChallengeAndMethod},
+	grant::authorization_code::AuthorizationCodeAuthorizationResponse,
+	server::ServerResult,
+};
+
+struct Client(ClientIdBuf);
+
+impl OAuth2Client for Client {
+	type TokenResponse = open_auth2::endpoints::token::TokenResponse;
+
+	fn client_id(&self) -> &ClientId {
+		&self.0
+	}
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+	let mut args = std::env::args().skip(1);
+	let (Some(authorization_uri), Some(token_uri), Some(client_id)) =
+		(args.next(), args.next(), args.next())
+	else {
+		eprintln!("usage: cli_loopback <authorization-endpoint> <token-endpoint> <client-id>");
+		std::process::exit(1)
+	};
+
+	let authorization_uri = UriBuf::new(authorization_uri.into_bytes())
+		.map_err(|_| "invalid authorization endpoint")?;
+	let token_uri = UriBuf::new(token_uri.into_bytes()).map_err(|_| "invalid token endpoint")?;
+	let client = Client(ClientIdBuf::new(client_id).map_err(|_| "invalid client identifier")?);
+	let http_client = reqwest::Client::new();
+
+	let redirect = LoopbackRedirect::bind("/callback")?;
+	let redirect_uri = redirect.redirect_uri().to_owned();
+
+	let (pkce_challenge, pkce_verifier) = PkceCodeChallengeAndMethod::new_random_sha256();
+	let state = StateBuf::new_random();
+
+	let authorize_url = AuthorizationEndpoint::new(&client, &authorization_uri)
+		.authorize_url(Some(redirect_uri.clone()), None)
+		.with_state(Some(state.clone()))
+		.with_pkce_challenge(pkce_challenge)
+		.into_redirect_uri()?;
+
+	println!("Opening {authorize_url}");
+	if SystemBrowser
+		.open(&authorize_url, &redirect_uri)
+		.await
+		.is_err()
+	{
+		println!("Please open the above URL in your browser.");
+	}
+
+	let response: Stateful<ServerResult<AuthorizationCodeAuthorizationResponse>> =
+		redirect.receive(Some(&state))?;
+
+	let code = match response.value {
+		ServerResult::Ok(response) => response.code,
+		ServerResult::Err(error) => {
+			return Err(format!("authorization denied: {}", error.error).into());
+		}
+	};
+
+	let token = TokenEndpoint::new(&client, &token_uri)
+		.exchange_code(code, Some(redirect_uri))
+		.with_pkce_verifier(&pkce_verifier)
+		.send(&http_client)
+		.await?;
+
+	println!("Access token: {}", token.access_token);
+	Ok(())
+}
@@ -0,0 +1,58 @@
+//! Wallet obtaining an access token from an OpenID4VCI credential offer with
+//! the Pre-Authorized Code Grant.
+//!
+//! ```sh
+//! cargo run --example oid4vci_wallet --features reqwest -- \
+//!     'openid-credential-offer://?credential_offer=...' [tx-code]
+//! ```
+use iref::UriBuf;
+use open_auth2::{
+	endpoints::token::{PublicTokenEndpoint, TokenResponse},
+	grant::pre_authorized_code::{CredentialIssuerMetadata, CredentialOffer},
+	server::AuthorizationServerMetadata,
+	util::Discoverable,
+};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct CredentialOfferQuery {
+	credential_offer: String,
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+	let mut args = std::env::args().skip(1);
+	let Some(offer_uri) = args.next() else {
+		eprintln!("usage: oid4vci_wallet <credential-offer-uri> [tx-code]");
+		std::process::exit(1)
+	};
+	let tx_code = args.next();
+
+	let offer_uri = UriBuf::new(offer_uri.into_bytes()).map_err(|_| "invalid offer URI")?;
+	let query: CredentialOfferQuery =
+		serde_html_form::from_str(offer_uri.query().map(|q| q.as_str()).unwrap_or_default())?;
+	let offer: CredentialOffer = serde_json::from_str(&query.credential_offer)?;
+
+	let http_client = reqwest::Client::new();
+	let issuer: CredentialIssuerMetadata =
+		CredentialIssuerMetadata::discover(&http_client, &offer.credential_issuer).await?;
+	let metadata: AuthorizationServerMetadata = offer
+		.discover_authorization_server(&http_client, &issuer)
+		.await?;
+
+	// The wallet is not registered with the issuer: the token request is
+	// anonymous.
+	let offered = PublicTokenEndpoint::from_credential_offer(&offer, &metadata)?;
+	if let (Some(expected), None) = (offered.tx_code(), &tx_code) {
+		eprintln!(
+			"transaction code required: {}",
+			expected.description.as_deref().unwrap_or("no description")
+		);
+		std::process::exit(1)
+	}
+
+	let token: TokenResponse = offered.exchange(tx_code)?.send(&http_client).await?;
+
+	println!("Access token: {}", token.access_token);
+	Ok(())
+}
@@ -0,0 +1,81 @@
+//! Runs the conformance self-test against an authorization server, printing
+//! the JSON report on the standard output.
+//!
+//! ```sh
+//! cargo run --bin conformance --features conformance,reqwest -- \
+//!     <issuer> <client-id> <redirect-uri> [scope]
+//! ```
+//!
+//! The client must be registered as a public client, whose authorization
+//! requests are approved without user interaction. Exits with a non-zero
+//! status if any check failed.
+use iref::UriBuf;
+use open_auth2::{
+	ClientId, ClientIdBuf, IssuerBuf, ScopeBuf,
+	client::OAuth2Client,
+	conformance::{AutoApprove, ConformanceTest},
+	endpoints::token::TokenResponse,
+	server::AuthorizationServerMetadata,
+	transport::TransportConfig,
+	util::Discoverable,
+};
+
+/// Public client under which the checks are performed.
+struct Client(ClientIdBuf);
+
+impl OAuth2Client for Client {
+	type TokenResponse = TokenResponse;
+
+	fn client_id(&self) -> &ClientId {
+		&self.0
+	}
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+	let mut args = std::env::args().skip(1);
+	let (Some(issuer), Some(client_id), Some(redirect_uri)) =
+		(args.next(), args.next(), args.next())
+	else {
+		eprintln!("usage: conformance <issuer> <client-id> <redirect-uri> [scope]");
+		std::process::exit(2)
+	};
+
+	let issuer = IssuerBuf::new(issuer).map_err(|_| "invalid issuer identifier")?;
+	let client_id = ClientIdBuf::new(client_id).map_err(|_| "invalid client identifier")?;
+	let redirect_uri =
+		UriBuf::new(redirect_uri.into_bytes()).map_err(|_| "invalid redirect URI")?;
+	let scope = args
+		.next()
+		.map(ScopeBuf::new)
+		.transpose()
+		.map_err(|_| "invalid scope")?;
+
+	let http_client = TransportConfig::new().build_reqwest()?;
+
+	let report = tokio::runtime::Builder::new_current_thread()
+		.enable_all()
+		.build()?
+		.block_on(async {
+			let metadata: AuthorizationServerMetadata =
+				AuthorizationServerMetadata::discover(&http_client, issuer.as_uri()).await?;
+
+			let test = ConformanceTest::new(
+				Client(client_id),
+				metadata,
+				redirect_uri,
+				&http_client,
+				AutoApprove(&http_client),
+			)
+			.with_scope(scope);
+
+			Ok::<_, Box<dyn std::error::Error>>(test.run().await)
+		})?;
+
+	println!("{}", serde_json::to_string_pretty(&report)?);
+
+	if !report.passed() {
+		std::process::exit(1)
+	}
+
+	Ok(())
+}
@@ -0,0 +1,217 @@
+use iref::{Uri, UriBuf};
+use serde::de::DeserializeOwned;
+
+use crate::{RedirectUriKind, State};
+
+/// Authorization response parameters, which must not be delivered in the
+/// fragment of the redirection URI when the response is expected in its
+/// query.
+const RESPONSE_PARAMETERS: [&str; 9] = [
+	"code",
+	"state",
+	"iss",
+	"error",
+	"error_description",
+	"error_uri",
+	"access_token",
+	"token_type",
+	"id_token",
+];
+
+/// Error returned when an authorization response delivered to the
+/// redirection endpoint of the client is rejected.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CallbackError {
+	/// The user-agent was redirected to another URI than the redirection URI
+	/// of the authorization request, possibly by an open redirector.
+	///
+	/// See: <https://www.rfc-editor.org/rfc/rfc9700.html#section-4.1>
+	#[error("authorization response delivered to `{0}` instead of the redirect URI")]
+	RedirectUriMismatch(UriBuf),
+
+	/// An authorization response parameter was found in the fragment of the
+	/// redirection URI, where it may have been smuggled in, instead of its
+	/// query.
+	#[error("authorization response parameter `{0}` in the URI fragment")]
+	FragmentParameter(String),
+
+	/// An authorization response parameter is included more than once, which
+	/// is forbidden by
+	/// [RFC 6749 Section 3.1](https://datatracker.ietf.org/doc/html/rfc6749#section-3.1).
+	#[error("duplicate authorization response parameter `{0}`")]
+	DuplicateParameter(String),
+
+	/// The `state` of the response does not match the one of the request,
+	/// possibly because the response was forged (CSRF).
+	///
+	/// See: <https://datatracker.ietf.org/doc/html/rfc6749#section-10.12>
+	#[error("state mismatch")]
+	StateMismatch,
+
+	/// The authorization response could not be decoded.
+	#[error("invalid authorization response: {0}")]
+	Invalid(String),
+}
+
+/// Parses the authorization response delivered to the redirection endpoint,
+/// in the query of the `callback` URI the user-agent was redirected to.
+///
+/// Before decoding the response, this checks that:
+/// - the callback was delivered to the `redirect_uri` of the authorization
+///   request, comparing their scheme, authority and path exactly, except for
+///   the port of loopback redirection URIs (see [`RedirectUriKind::matches`]);
+/// - no response parameter is included in the URI fragment, so that a
+///   parameter cannot be smuggled in by confusing the fragment with the
+///   query;
+/// - no parameter is included more than once;
+/// - the `state` parameter is the `state` of the authorization request,
+///   and is absent if, and only if, the request had none.
+pub fn parse_callback<T>(
+	redirect_uri: &Uri,
+	callback: &Uri,
+	state: Option<&State>,
+) -> Result<T, CallbackError>
+where
+	T: DeserializeOwned,
+{
+	if !RedirectUriKind::matches(&endpoint(redirect_uri), &endpoint(callback)) {
+		return Err(CallbackError::RedirectUriMismatch(endpoint(callback)));
+	}
+
+	if let Some(fragment) = callback.fragment() {
+		let parameters: Vec<(String, String)> =
+			serde_html_form::from_str(fragment.as_str()).unwrap_or_default();
+		if let Some((name, _)) = parameters
+			.into_iter()
+			.find(|(name, _)| RESPONSE_PARAMETERS.contains(&name.as_str()))
+		{
+			return Err(CallbackError::FragmentParameter(name));
+		}
+	}
+
+	let query = callback.query().map(|q| q.as_str()).unwrap_or_default();
+	let parameters: Vec<(String, String)> =
+		serde_html_form::from_str(query).map_err(|e| CallbackError::Invalid(e.to_string()))?;
+	for (i, (name, _)) in parameters.iter().enumerate() {
+		if parameters[..i].iter().any(|(other, _)| other == name) {
+			return Err(CallbackError::DuplicateParameter(name.clone()));
+		}
+	}
+
+	let received_state = parameters
+		.iter()
+		.find(|(name, _)| name == "state")
+		.map(|(_, value)| value.as_str());
+	if received_state != state.map(State::as_str) {
+		return Err(CallbackError::StateMismatch);
+	}
+
+	serde_html_form::from_str(query).map_err(|e| CallbackError::Invalid(e.to_string()))
+}
+
+/// Returns the given URI without query and fragment.
+fn endpoint(uri: &Uri) -> UriBuf {
+	let mut uri = uri.to_owned();
+	uri.set_query(None);
+	uri.set_fragment(None);
+	uri
+}
+
+#[cfg(test)]
+mod tests {
+	use iref::uri;
+
+	use crate::{
+		Stateful, grant::authorization_code::AuthorizationCodeAuthorizationResponse,
+		server::ServerResult, state,
+	};
+
+	use super::*;
+
+	type Response = Stateful<ServerResult<AuthorizationCodeAuthorizationResponse>>;
+
+	#[test]
+	fn callback() {
+		let response: Response = parse_callback(
+			uri!("https://client.example.com/cb?tenant=a"),
+			uri!("https://client.example.com/cb?tenant=a&code=SplxlOBeZQQYbYS6WxSbIA&state=xyz"),
+			Some(state!("xyz")),
+		)
+		.unwrap();
+		assert_eq!(response.state.as_deref().map(|s| s.as_str()), Some("xyz"));
+		assert!(matches!(
+			response.value,
+			ServerResult::Ok(response) if response.code.as_str() == "SplxlOBeZQQYbYS6WxSbIA"
+		));
+
+		let response: Response = parse_callback(
+			uri!("http://127.0.0.1/cb"),
+			uri!("http://127.0.0.1:51004/cb?error=access_denied&state=xyz"),
+			Some(state!("xyz")),
+		)
+		.unwrap();
+		assert!(matches!(response.value, ServerResult::Err(_)));
+	}
+
+	#[test]
+	fn rejected_callback() {
+		let redirect_uri = uri!("https://client.example.com/cb");
+
+		assert_eq!(
+			parse_callback::<Response>(
+				redirect_uri,
+				uri!("https://attacker.example.com/cb?code=SplxlOBeZQQYbYS6WxSbIA"),
+				None
+			)
+			.err(),
+			Some(CallbackError::RedirectUriMismatch(
+				uri!("https://attacker.example.com/cb").to_owned()
+			))
+		);
+		assert!(matches!(
+			parse_callback::<Response>(
+				redirect_uri,
+				uri!("https://client.example.com/cb/../redirect?code=SplxlOBeZQQYbYS6WxSbIA"),
+				None
+			),
+			Err(CallbackError::RedirectUriMismatch(_))
+		));
+		assert_eq!(
+			parse_callback::<Response>(
+				redirect_uri,
+				uri!("https://client.example.com/cb?state=xyz#code=SplxlOBeZQQYbYS6WxSbIA"),
+				None
+			)
+			.err(),
+			Some(CallbackError::FragmentParameter("code".to_owned()))
+		);
+		assert_eq!(
+			parse_callback::<Response>(
+				redirect_uri,
+				uri!("https://client.example.com/cb?code=SplxlOBeZQQYbYS6WxSbIA&code=other"),
+				None
+			)
+			.err(),
+			Some(CallbackError::DuplicateParameter("code".to_owned()))
+		);
+
+		let callback = uri!("https://client.example.com/cb?code=SplxlOBeZQQYbYS6WxSbIA&state=xyz");
+		assert_eq!(
+			parse_callback::<Response>(redirect_uri, callback, Some(state!("abc"))).err(),
+			Some(CallbackError::StateMismatch)
+		);
+		assert_eq!(
+			parse_callback::<Response>(redirect_uri, callback, None).err(),
+			Some(CallbackError::StateMismatch)
+		);
+		assert_eq!(
+			parse_callback::<Response>(
+				redirect_uri,
+				uri!("https://client.example.com/cb?code=SplxlOBeZQQYbYS6WxSbIA"),
+				Some(state!("xyz"))
+			)
+			.err(),
+			Some(CallbackError::StateMismatch)
+		);
+	}
+}
@@ -0,0 +1,386 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	ClientId, ClientIdBuf, endpoints::token::TokenResponse, transport::REDACTED, util::AnyExtension,
+};
+
+#[cfg(feature = "client-secret")]
+use crate::endpoints::RequestBuilder;
+
+#[cfg(feature = "client-secret")]
+use super::{AddClientSecret, WithClientSecret};
+use super::{ClientType, OAuth2Client, OAuth2ClientError};
+
+/// Client authentication method at the token endpoint.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc7591#section-2>
+#[derive(
+	Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientAuthMethod {
+	/// The client is public and does not authenticate.
+	#[default]
+	None,
+
+	/// HTTP Basic authentication with the client secret.
+	///
+	/// See: <https://datatracker.ietf.org/doc/html/rfc6749#section-2.3.1>
+	ClientSecretBasic,
+
+	/// Client secret included in the request body.
+	///
+	/// The request must also include the `client_id` parameter.
+	///
+	/// See: <https://datatracker.ietf.org/doc/html/rfc6749#section-2.3.1>
+	ClientSecretPost,
+
+	/// HTTP Basic authentication with the client secret, without
+	/// form-urlencoding the client identifier and secret first.
+	///
+	/// This violates [RFC 6749 Section 2.3.1], but is expected by some
+	/// authorization servers, which then reject secrets containing reserved
+	/// characters encoded with [`ClientSecretBasic`](Self::ClientSecretBasic).
+	/// Only use it for such servers: credentials containing a `:` in the
+	/// client identifier cannot be sent this way.
+	///
+	/// [RFC 6749 Section 2.3.1]: https://datatracker.ietf.org/doc/html/rfc6749#section-2.3.1
+	ClientSecretBasicUnencoded,
+
+	/// JWT signed with the client secret.
+	///
+	/// See: <https://openid.net/specs/openid-connect-core-1_0.html#ClientAuthentication>
+	ClientSecretJwt,
+
+	/// JWT signed with a private key of the client.
+	///
+	/// See: <https://openid.net/specs/openid-connect-core-1_0.html#ClientAuthentication>
+	PrivateKeyJwt,
+
+	/// Mutual TLS with a PKI-bound certificate.
+	///
+	/// See: <https://www.rfc-editor.org/rfc/rfc8705.html#section-2.1>
+	TlsClientAuth,
+
+	/// Mutual TLS with a self-signed certificate.
+	///
+	/// See: <https://www.rfc-editor.org/rfc/rfc8705.html#section-2.2>
+	SelfSignedTlsClientAuth,
+}
+
+impl ClientAuthMethod {
+	/// Returns the registered name of this method.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Self::None => "none",
+			Self::ClientSecretBasic => "client_secret_basic",
+			Self::ClientSecretPost => "client_secret_post",
+			Self::ClientSecretBasicUnencoded => "client_secret_basic_unencoded",
+			Self::ClientSecretJwt => "client_secret_jwt",
+			Self::PrivateKeyJwt => "private_key_jwt",
+			Self::TlsClientAuth => "tls_client_auth",
+			Self::SelfSignedTlsClientAuth => "self_signed_tls_client_auth",
+		}
+	}
+
+	/// Checks whether this method sends the client secret itself.
+	pub fn uses_client_secret(&self) -> bool {
+		matches!(
+			self,
+			Self::ClientSecretBasic | Self::ClientSecretPost | Self::ClientSecretBasicUnencoded
+		)
+	}
+
+	/// Checks whether requests can be authenticated with this method by
+	/// this crate.
+	///
+	/// Methods using the client secret require the `client-secret` feature,
+	/// while the JWT and mutual TLS methods are not supported.
+	pub fn is_supported(&self) -> bool {
+		match self {
+			Self::None => true,
+			_ => cfg!(feature = "client-secret") && self.uses_client_secret(),
+		}
+	}
+}
+
+impl fmt::Display for ClientAuthMethod {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.as_str().fmt(f)
+	}
+}
+
+/// Client authentication settings of a [`DynamicClient`] that cannot be
+/// honored.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ClientAuthError {
+	/// The authentication method is not supported.
+	#[error("unsupported client authentication method `{0}`")]
+	Unsupported(ClientAuthMethod),
+
+	/// The authentication method requires a client secret, but none is
+	/// given.
+	#[error("missing client secret for the `{0}` authentication method")]
+	MissingSecret(ClientAuthMethod),
+
+	/// A client secret is given, but the authentication method does not use
+	/// it.
+	#[error("client secret given for the `{0}` authentication method")]
+	UnusedSecret(ClientAuthMethod),
+}
+
+impl From<ClientAuthError> for OAuth2ClientError {
+	fn from(value: ClientAuthError) -> Self {
+		Self::request(value)
+	}
+}
+
+/// OAuth 2.0 client configured at runtime.
+///
+/// Applications managing many clients in one process, such as services with
+/// per-tenant OAuth 2.0 applications, can build a [`DynamicClient`] for each
+/// of them, typically from configuration, instead of defining a type per
+/// client. It deserializes from the client registration fields of
+/// [RFC 7591](https://datatracker.ietf.org/doc/html/rfc7591#section-2).
+///
+/// Extension fields of token responses are kept in an [`AnyExtension`] map,
+/// to be inspected by the application.
+///
+/// With the `client-secret` feature, requests to the token endpoint are
+/// authenticated with [`authenticate`](Self::authenticate), which fails if
+/// the configured authentication method cannot be honored.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DynamicClient {
+	/// Client identifier.
+	pub client_id: ClientIdBuf,
+
+	/// Client secret, if any.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub client_secret: Option<String>,
+
+	/// Authentication method at the token endpoint.
+	#[serde(default, rename = "token_endpoint_auth_method")]
+	pub auth_method: ClientAuthMethod,
+}
+
+impl DynamicClient {
+	/// Creates a new public client.
+	pub fn new(client_id: ClientIdBuf) -> Self {
+		Self {
+			client_id,
+			client_secret: None,
+			auth_method: ClientAuthMethod::None,
+		}
+	}
+
+	/// Sets the client secret and authentication method.
+	pub fn with_secret(self, client_secret: String, auth_method: ClientAuthMethod) -> Self {
+		Self {
+			client_secret: Some(client_secret),
+			auth_method,
+			..self
+		}
+	}
+
+	/// Sets the authentication method.
+	pub fn with_auth_method(self, auth_method: ClientAuthMethod) -> Self {
+		Self {
+			auth_method,
+			..self
+		}
+	}
+
+	/// Checks that the authentication method is supported, and consistent
+	/// with the presence of a client secret.
+	pub fn check_auth_method(&self) -> Result<(), ClientAuthError> {
+		let method = self.auth_method;
+		if !method.is_supported() {
+			return Err(ClientAuthError::Unsupported(method));
+		}
+
+		match (method.uses_client_secret(), &self.client_secret) {
+			(true, None) => Err(ClientAuthError::MissingSecret(method)),
+			(false, Some(_)) => Err(ClientAuthError::UnusedSecret(method)),
+			_ => Ok(()),
+		}
+	}
+
+	/// Authenticates the request of the given builder with the configured
+	/// method and client secret.
+	///
+	/// Fails if the authentication method cannot be honored (see
+	/// [`check_auth_method`](Self::check_auth_method)).
+	#[cfg(feature = "client-secret")]
+	pub fn authenticate<E, T>(
+		&self,
+		builder: RequestBuilder<E, T>,
+	) -> Result<RequestBuilder<E, WithClientSecret<'_, str, T>>, ClientAuthError> {
+		self.check_auth_method()?;
+		Ok(builder.with_client_secret(
+			self.client_secret.as_deref().unwrap_or_default(),
+			self.auth_method,
+		))
+	}
+}
+
+impl fmt::Debug for DynamicClient {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("DynamicClient")
+			.field("client_id", &self.client_id)
+			.field(
+				"client_secret",
+				&self.client_secret.as_ref().map(|_| REDACTED),
+			)
+			.field("auth_method", &self.auth_method)
+			.finish()
+	}
+}
+
+impl OAuth2Client for DynamicClient {
+	type TokenResponse = TokenResponse<String, AnyExtension>;
+
+	fn client_id(&self) -> &ClientId {
+		&self.client_id
+	}
+
+	/// Returns [`ClientType::Confidential`] unless the authentication method
+	/// is [`ClientAuthMethod::None`].
+	fn client_type(&self) -> ClientType {
+		match self.auth_method {
+			ClientAuthMethod::None => ClientType::Public,
+			_ => ClientType::Confidential,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn from_configuration() {
+		let clients: Vec<DynamicClient> = serde_json::from_value(serde_json::json!([
+			{ "client_id": "tenant-a" },
+			{
+				"client_id": "tenant-b",
+				"client_secret": "s3cr3t",
+				"token_endpoint_auth_method": "client_secret_post"
+			}
+		]))
+		.unwrap();
+
+		assert_eq!(
+			clients[0],
+			DynamicClient::new(crate::client_id!("tenant-a").to_owned())
+		);
+		assert_eq!(clients[0].client_type(), ClientType::Public);
+
+		assert_eq!(clients[1].client_id().as_str(), "tenant-b");
+		assert_eq!(clients[1].auth_method, ClientAuthMethod::ClientSecretPost);
+		assert_eq!(clients[1].client_type(), ClientType::Confidential);
+		assert!(!format!("{:?}", clients[1]).contains("s3cr3t"));
+	}
+
+	#[test]
+	fn token_response_extension() {
+		let response: <DynamicClient as OAuth2Client>::TokenResponse =
+			serde_json::from_value(serde_json::json!({
+				"access_token": "2YotnFZFEjr1zCsicMWpAA",
+				"token_type": "Bearer",
+				"id_token": "eyJhbGciOiJSUzI1NiJ9"
+			}))
+			.unwrap();
+
+		assert_eq!(
+			response
+				.ext
+				.get_as::<String>("id_token")
+				.unwrap()
+				.as_deref(),
+			Some("eyJhbGciOiJSUzI1NiJ9")
+		);
+	}
+
+	#[test]
+	fn auth_method_check() {
+		let client = DynamicClient::new(crate::client_id!("s6BhdRkqt3").to_owned());
+		assert_eq!(client.check_auth_method(), Ok(()));
+		assert_eq!(
+			client
+				.clone()
+				.with_auth_method(ClientAuthMethod::PrivateKeyJwt)
+				.check_auth_method(),
+			Err(ClientAuthError::Unsupported(
+				ClientAuthMethod::PrivateKeyJwt
+			))
+		);
+		assert_eq!(
+			client
+				.clone()
+				.with_secret("s3cr3t".to_owned(), ClientAuthMethod::None)
+				.check_auth_method(),
+			Err(ClientAuthError::UnusedSecret(ClientAuthMethod::None))
+		);
+
+		#[cfg(feature = "client-secret")]
+		assert_eq!(
+			client
+				.with_auth_method(ClientAuthMethod::ClientSecretBasic)
+				.check_auth_method(),
+			Err(ClientAuthError::MissingSecret(
+				ClientAuthMethod::ClientSecretBasic
+			))
+		);
+	}
+
+	#[cfg(feature = "client-secret")]
+	#[tokio::test]
+	async fn authenticated_token_request() {
+		use iref::uri;
+
+		use crate::{
+			code,
+			endpoints::{HttpRequest, token::TokenEndpoint},
+			transport::HttpClient,
+		};
+
+		/// HTTP client that does not expect any request.
+		struct NoHttpClient;
+
+		impl HttpClient for NoHttpClient {
+			async fn send(
+				&self,
+				_request: http::Request<Vec<u8>>,
+			) -> Result<http::Response<Vec<u8>>, OAuth2ClientError> {
+				unreachable!()
+			}
+		}
+
+		let client = DynamicClient::new(crate::client_id!("s6BhdRkqt3").to_owned())
+			.with_secret("s3cr3t".to_owned(), ClientAuthMethod::ClientSecretPost);
+		let builder = client
+			.authenticate(
+				TokenEndpoint::new(&client, uri!("https://server.example.com/token"))
+					.exchange_code(code!("SplxlOBeZQQYbYS6WxSbIA").to_owned(), None),
+			)
+			.unwrap();
+		let request = builder
+			.request
+			.build_request(&builder.endpoint, &NoHttpClient)
+			.await
+			.unwrap();
+		assert_eq!(request.body().client_secret.as_deref(), Some("s3cr3t"));
+
+		let client = client.with_auth_method(ClientAuthMethod::ClientSecretJwt);
+		assert!(
+			client
+				.authenticate(
+					TokenEndpoint::new(&client, uri!("https://server.example.com/token"))
+						.exchange_code(code!("SplxlOBeZQQYbYS6WxSbIA").to_owned(), None),
+				)
+				.is_err()
+		);
+	}
+}
@@ -1,7 +1,66 @@
 //! OAuth 2.0 client trait and error types.
+//!
+//! Clients known at compile time implement [`OAuth2Client`], while clients
+//! configured at runtime can use a [`DynamicClient`].
+//!
+//! Long-lived clients can keep their token up to date with a
+//! [`TokenManager`], holding a [`TokenSet`] that can be stored and restored
+//! across restarts. With the `tokio` feature, a `SharedTokenManager` can be
+//! used concurrently by many tasks, and a `TokenRefresher` can refresh the
+//! token in the background. With the `browser` feature, browser-based
+//! applications can use a `SpaTokenSession` instead.
+//!
+//! Authorization responses delivered to the redirection endpoint of the
+//! client are parsed with [`parse_callback`], which rejects responses
+//! delivered elsewhere or not carrying the `state` of the request.
+//!
+//! Native apps open authorization requests in an [`ExternalUserAgent`], such
+//! as the `SystemBrowser` provided with the `system-browser` feature.
+use std::time::Duration;
+
+use http::header::RETRY_AFTER;
 use serde::de::DeserializeOwned;
 
-use crate::ClientId;
+use crate::{
+	ClientId,
+	server::ErrorResponse,
+	time::{Clock, SystemClock},
+};
+
+mod callback;
+pub use callback::*;
+
+mod dynamic;
+pub use dynamic::*;
+
+#[cfg(feature = "client-secret")]
+mod secret;
+#[cfg(feature = "client-secret")]
+pub use secret::*;
+
+mod token_manager;
+pub use token_manager::*;
+
+mod token_set;
+pub use token_set::*;
+
+mod user_agent;
+pub use user_agent::*;
+
+#[cfg(feature = "tokio")]
+mod refresher;
+#[cfg(feature = "tokio")]
+pub use refresher::*;
+
+#[cfg(feature = "tokio")]
+mod shared;
+#[cfg(feature = "tokio")]
+pub use shared::*;
+
+#[cfg(feature = "browser")]
+mod spa;
+#[cfg(feature = "browser")]
+pub use spa::*;
 
 /// An OAuth 2.0 client.
 ///
@@ -16,10 +75,51 @@ pub trait OAuth2Client {
 
 	/// Returns the client identifier.
 	fn client_id(&self) -> &ClientId;
+
+	/// Returns the client type.
+	///
+	/// Defaults to [`ClientType::Public`], so that clients which do not
+	/// authenticate with the authorization server are held to the stricter
+	/// requirements.
+	fn client_type(&self) -> ClientType {
+		ClientType::Public
+	}
+}
+
+/// OAuth 2.0 client type.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc6749#section-2.1>
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ClientType {
+	/// Client capable of maintaining the confidentiality of its credentials,
+	/// authenticating with the authorization server.
+	Confidential,
+
+	/// Client incapable of maintaining the confidentiality of its
+	/// credentials, such as native or browser-based applications.
+	#[default]
+	Public,
+}
+
+impl ClientType {
+	/// Checks whether this is a public client.
+	pub fn is_public(&self) -> bool {
+		matches!(self, Self::Public)
+	}
+}
+
+/// Classification of an [`OAuth2ClientError`], for retry logic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ErrorClass {
+	/// The request may succeed if attempted again, possibly after a delay.
+	Transient,
+
+	/// The request will fail again if attempted with the same parameters.
+	Permanent,
 }
 
 /// Errors that can occur during an OAuth 2.0 HTTP exchange.
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, Clone, thiserror::Error)]
 pub enum OAuth2ClientError {
 	/// The HTTP request could not be sent.
 	#[error("unable to send request: {0}")]
@@ -32,6 +132,25 @@ pub enum OAuth2ClientError {
 	/// The server responded with an unexpected HTTP status code.
 	#[error("server responded with status code: {0}")]
 	ServerError(http::StatusCode),
+
+	/// The server responded with an OAuth 2.0 error.
+	///
+	/// See: <https://datatracker.ietf.org/doc/html/rfc6749#section-5.2>
+	#[error("server responded with error: {}", .0.error)]
+	ErrorResponse(ErrorResponse),
+
+	/// The server responded with `429 Too Many Requests`.
+	///
+	/// The request should not be attempted again before `retry_after`, if
+	/// given by the server.
+	///
+	/// See: <https://www.rfc-editor.org/rfc/rfc6585.html#section-4>
+	#[error("rate limited by the server")]
+	RateLimited {
+		/// Delay before the request may be attempted again, from the
+		/// `Retry-After` header.
+		retry_after: Option<Duration>,
+	},
 }
 
 impl OAuth2ClientError {
@@ -57,4 +176,191 @@ impl OAuth2ClientError {
 		log::error!("unexpected server response status: {status}");
 		Self::ServerError(status)
 	}
+
+	/// Creates an error from a response with an unexpected status code.
+	///
+	/// Returns a [`RateLimited`](Self::RateLimited) error for
+	/// `429 Too Many Requests` responses, or a
+	/// [`ServerError`](Self::ServerError) error otherwise.
+	pub fn unexpected_status<T>(response: &http::Response<T>) -> Self {
+		let status = response.status();
+		if status == http::StatusCode::TOO_MANY_REQUESTS {
+			let retry_after = response
+				.headers()
+				.get(RETRY_AFTER)
+				.and_then(|value| value.to_str().ok())
+				.and_then(parse_retry_after);
+			log::error!("rate limited by the server, retry after: {retry_after:?}");
+			Self::RateLimited { retry_after }
+		} else {
+			Self::server(status)
+		}
+	}
+
+	/// Creates an error from an unsuccessful response of the token endpoint.
+	///
+	/// Returns an [`ErrorResponse`](Self::ErrorResponse) error if the
+	/// response body is a JSON error response, or a
+	/// [`unexpected_status`](Self::unexpected_status) error otherwise.
+	pub fn token_error(response: &http::Response<Vec<u8>>) -> Self {
+		let status = response.status();
+		match serde_json::from_slice::<ErrorResponse>(response.body()) {
+			Ok(error) if status.is_client_error() => {
+				log::error!("token endpoint error: {}", error.error);
+				Self::ErrorResponse(error)
+			}
+			_ => Self::unexpected_status(response),
+		}
+	}
+
+	/// Returns the OAuth 2.0 error code of the server response, if any.
+	pub fn error_code(&self) -> Option<&str> {
+		match self {
+			Self::ErrorResponse(error) => Some(&error.error),
+			_ => None,
+		}
+	}
+
+	/// Returns the delay before the request may be attempted again, if the
+	/// server rate limited it.
+	pub fn retry_after(&self) -> Option<Duration> {
+		match self {
+			Self::RateLimited { retry_after } => *retry_after,
+			_ => None,
+		}
+	}
+
+	/// Classifies this error for retry logic.
+	///
+	/// The following errors are [transient](ErrorClass::Transient):
+	///
+	/// - the request could not be sent, e.g. because of a timeout;
+	/// - the server failed with a `5xx` status code, or `408 Request
+	///   Timeout`;
+	/// - the request was rate limited, in which case it should not be
+	///   attempted again before [`retry_after`](Self::retry_after);
+	/// - the server responded with the `temporarily_unavailable`,
+	///   `server_error`, `slow_down` or `authorization_pending` error codes,
+	///   or with `use_dpop_nonce` after the nonce retries were exhausted.
+	///
+	/// All other errors, such as `invalid_grant` or `invalid_client` error
+	/// responses and malformed responses, are
+	/// [permanent](ErrorClass::Permanent).
+	pub fn classification(&self) -> ErrorClass {
+		let transient = match self {
+			Self::Request(_) => true,
+			Self::Response(_) => false,
+			Self::ServerError(status) => {
+				status.is_server_error() || *status == http::StatusCode::REQUEST_TIMEOUT
+			}
+			Self::ErrorResponse(error) => TRANSIENT_ERROR_CODES.contains(&error.error.as_str()),
+			Self::RateLimited { .. } => true,
+		};
+
+		if transient {
+			ErrorClass::Transient
+		} else {
+			ErrorClass::Permanent
+		}
+	}
+
+	/// Checks whether this error is [transient](ErrorClass::Transient).
+	pub fn is_transient(&self) -> bool {
+		self.classification() == ErrorClass::Transient
+	}
+}
+
+/// OAuth 2.0 error codes indicating a transient failure.
+const TRANSIENT_ERROR_CODES: &[&str] = &[
+	"temporarily_unavailable",
+	"server_error",
+	"slow_down",
+	"authorization_pending",
+	"use_dpop_nonce",
+];
+
+/// Parses the value of a `Retry-After` header, either a number of seconds or
+/// an HTTP date.
+///
+/// See: <https://www.rfc-editor.org/rfc/rfc9110.html#section-10.2.3>
+fn parse_retry_after(value: &str) -> Option<Duration> {
+	let value = value.trim();
+	match value.parse() {
+		Ok(secs) => Some(Duration::from_secs(secs)),
+		Err(_) => {
+			let date = httpdate::parse_http_date(value).ok()?;
+			Some(date.duration_since(SystemClock.now()).unwrap_or_default())
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn response(status: http::StatusCode, retry_after: Option<&str>) -> http::Response<Vec<u8>> {
+		let mut response = http::Response::builder().status(status);
+		if let Some(retry_after) = retry_after {
+			response = response.header(RETRY_AFTER, retry_after);
+		}
+
+		response.body(Vec::new()).unwrap()
+	}
+
+	#[test]
+	fn rate_limited() {
+		let error = OAuth2ClientError::token_error(&response(
+			http::StatusCode::TOO_MANY_REQUESTS,
+			Some("120"),
+		));
+		assert_eq!(error.retry_after(), Some(Duration::from_secs(120)));
+
+		let error = OAuth2ClientError::unexpected_status(&response(
+			http::StatusCode::TOO_MANY_REQUESTS,
+			Some("Wed, 21 Oct 2015 07:28:00 GMT"),
+		));
+		assert_eq!(error.retry_after(), Some(Duration::ZERO));
+
+		let error = OAuth2ClientError::unexpected_status(&response(
+			http::StatusCode::TOO_MANY_REQUESTS,
+			None,
+		));
+		assert!(matches!(
+			error,
+			OAuth2ClientError::RateLimited { retry_after: None }
+		));
+
+		let error = OAuth2ClientError::unexpected_status(&response(
+			http::StatusCode::SERVICE_UNAVAILABLE,
+			Some("120"),
+		));
+		assert!(matches!(error, OAuth2ClientError::ServerError(_)));
+	}
+
+	#[test]
+	fn classification() {
+		let error_response = |code: &str| {
+			OAuth2ClientError::ErrorResponse(ErrorResponse::new(code.to_owned(), None, None))
+		};
+
+		for error in [
+			OAuth2ClientError::Request("timeout".to_owned()),
+			OAuth2ClientError::ServerError(http::StatusCode::BAD_GATEWAY),
+			OAuth2ClientError::ServerError(http::StatusCode::REQUEST_TIMEOUT),
+			OAuth2ClientError::RateLimited { retry_after: None },
+			error_response("temporarily_unavailable"),
+			error_response("use_dpop_nonce"),
+		] {
+			assert_eq!(error.classification(), ErrorClass::Transient, "{error}");
+		}
+
+		for error in [
+			OAuth2ClientError::Response("invalid JSON".to_owned()),
+			OAuth2ClientError::ServerError(http::StatusCode::NOT_FOUND),
+			error_response("invalid_grant"),
+			error_response("invalid_client"),
+		] {
+			assert_eq!(error.classification(), ErrorClass::Permanent, "{error}");
+		}
+	}
 }
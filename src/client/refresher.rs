@@ -0,0 +1,237 @@
+use std::time::Duration;
+
+use tokio::sync::watch;
+
+use crate::{
+	AccessTokenBuf,
+	client::{IntoTokenSet, OAuth2Client, TokenManager, TokenRefreshError},
+	endpoints::token::{TokenResponse, TokenType},
+	time::{Clock, LeewayConfig, SystemClock},
+	transport::HttpClient,
+};
+
+/// Background task refreshing the token of a [`TokenManager`] ahead of its
+/// expiration.
+///
+/// Consumers [`subscribe`](Self::subscribe) to the access token instead of
+/// refreshing it themselves, so a single refresh request is sent no matter
+/// how many tasks use the token.
+///
+/// The refresher does nothing until [`run`](Self::run), typically with
+/// `tokio::spawn(refresher.run())`. It stops when every receiver is dropped,
+/// when the token has no expiration time, when no refresh token is
+/// available, or when a refresh fails permanently (e.g. with an
+/// `invalid_grant` error once the refresh token is revoked). Receivers then
+/// observe the closed channel.
+///
+/// [Transient](TokenRefreshError::is_transient) failures are retried.
+///
+/// Requires the `tokio` feature.
+pub struct TokenRefresher<C: OAuth2Client, H, K = SystemClock>
+where
+	C::TokenResponse: IntoTokenSet,
+{
+	manager: TokenManager<C, K>,
+	http_client: H,
+	margin: Duration,
+	retry_delay: Duration,
+	max_retry_after: Duration,
+	sender: watch::Sender<AccessTokenBuf>,
+}
+
+impl<C, T, E, H, K> TokenRefresher<C, H, K>
+where
+	C: OAuth2Client<TokenResponse = TokenResponse<T, E>>,
+	T: TokenType,
+	H: HttpClient,
+	K: Clock,
+{
+	/// Default delay before retrying a failed refresh.
+	pub const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(10);
+
+	/// Default longest delay requested by the server that is waited for
+	/// before retrying a failed refresh.
+	pub const DEFAULT_MAX_RETRY_AFTER: Duration = Duration::from_secs(300);
+
+	/// Creates a new refresher for the token held by `manager`.
+	///
+	/// By default, the token is refreshed [`LeewayConfig::DEFAULT_LEEWAY`]
+	/// before it expires.
+	pub fn new(manager: TokenManager<C, K>, http_client: H) -> Self {
+		let (sender, _) = watch::channel(manager.access_token().to_owned());
+		Self {
+			manager,
+			http_client,
+			margin: LeewayConfig::DEFAULT_LEEWAY,
+			retry_delay: Self::DEFAULT_RETRY_DELAY,
+			max_retry_after: Self::DEFAULT_MAX_RETRY_AFTER,
+			sender,
+		}
+	}
+
+	/// Sets how long before its expiration the access token is refreshed.
+	pub fn with_margin(self, margin: Duration) -> Self {
+		Self { margin, ..self }
+	}
+
+	/// Sets the delay before retrying a failed refresh.
+	///
+	/// A longer delay requested by a rate limiting server is honored.
+	pub fn with_retry_delay(self, retry_delay: Duration) -> Self {
+		Self {
+			retry_delay,
+			..self
+		}
+	}
+
+	/// Sets the longest delay requested by the server that is waited for
+	/// before retrying a failed refresh.
+	///
+	/// Longer delays are shortened to this value, so that a misbehaving
+	/// server cannot stall the refresher indefinitely.
+	pub fn with_max_retry_after(self, max_retry_after: Duration) -> Self {
+		Self {
+			max_retry_after,
+			..self
+		}
+	}
+
+	/// Returns a receiver always holding the latest access token.
+	pub fn subscribe(&self) -> watch::Receiver<AccessTokenBuf> {
+		self.sender.subscribe()
+	}
+
+	/// Refreshes the access token ahead of its expiration until it is no
+	/// longer needed.
+	///
+	/// Returns the token manager, holding the latest token, or the error of
+	/// the refresh that failed permanently. In both cases, the channel is
+	/// closed when this returns.
+	pub async fn run(mut self) -> Result<TokenManager<C, K>, TokenRefreshError> {
+		while let Some(expires_at) = self.manager.expires_at() {
+			let delay = expires_at
+				.checked_sub(self.margin)
+				.and_then(|t| t.duration_since(self.manager.clock().now()).ok())
+				.unwrap_or_default();
+
+			if !self.sleep(delay).await {
+				break;
+			}
+
+			match self.manager.refresh(&self.http_client).await {
+				Ok(access_token) => {
+					self.sender.send_replace(access_token.to_owned());
+				}
+				Err(TokenRefreshError::NoRefreshToken) => {
+					log::warn!("no refresh token, stopping token refresher");
+					break;
+				}
+				Err(e) if !e.is_transient() => {
+					log::warn!("token refresh failed permanently, stopping token refresher: {e}");
+					return Err(e);
+				}
+				Err(e) => {
+					log::warn!("token refresh failed: {e}");
+					let retry_delay = e.retry_after().map_or(self.retry_delay, |retry_after| {
+						retry_after.min(self.max_retry_after).max(self.retry_delay)
+					});
+					if !self.sleep(retry_delay).await {
+						break;
+					}
+				}
+			}
+		}
+
+		Ok(self.manager)
+	}
+
+	/// Sleeps for the given duration, returning `false` if every receiver was
+	/// dropped in the meantime.
+	async fn sleep(&self, duration: Duration) -> bool {
+		tokio::time::timeout(duration, self.sender.closed())
+			.await
+			.is_err()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{
+		sync::atomic::{AtomicUsize, Ordering},
+		time::Duration,
+	};
+
+	use crate::client::{
+		OAuth2ClientError,
+		token_manager::tests::{TestServer, manager},
+	};
+
+	use super::*;
+
+	#[tokio::test(start_paused = true)]
+	async fn refresh_ahead() {
+		let refresher = TokenRefresher::new(
+			manager(Some("tGzv3JOkF0XG5Qx2TlKWIA")),
+			TestServer::default(),
+		)
+		.with_margin(Duration::from_secs(300));
+
+		let mut receiver = refresher.subscribe();
+		assert_eq!(receiver.borrow_and_update().as_str(), "token-0");
+
+		let task = tokio::spawn(refresher.run());
+		let start = tokio::time::Instant::now();
+
+		receiver.changed().await.unwrap();
+		assert_eq!(receiver.borrow_and_update().as_str(), "token-1");
+		assert_eq!(start.elapsed(), Duration::from_secs(3300));
+
+		receiver.changed().await.unwrap();
+		assert_eq!(receiver.borrow_and_update().as_str(), "token-2");
+
+		drop(receiver);
+		let manager = task.await.unwrap().unwrap();
+		assert_eq!(manager.access_token().as_str(), "token-2");
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn no_refresh_token() {
+		let server = TestServer::default();
+		let refresher = TokenRefresher::new(manager(None), &server);
+		let _receiver = refresher.subscribe();
+
+		assert!(refresher.run().await.is_ok());
+		assert_eq!(server.0.load(Ordering::SeqCst), 0);
+	}
+
+	/// Token endpoint rejecting the refresh token.
+	#[derive(Default)]
+	struct RevokedServer(AtomicUsize);
+
+	impl HttpClient for RevokedServer {
+		async fn send(
+			&self,
+			_request: http::Request<Vec<u8>>,
+		) -> Result<http::Response<Vec<u8>>, OAuth2ClientError> {
+			self.0.fetch_add(1, Ordering::SeqCst);
+			Ok(http::Response::builder()
+				.status(http::StatusCode::BAD_REQUEST)
+				.header(http::header::CONTENT_TYPE, "application/json")
+				.body(br#"{"error":"invalid_grant"}"#.to_vec())
+				.unwrap())
+		}
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn permanent_failure() {
+		let server = RevokedServer::default();
+		let refresher = TokenRefresher::new(manager(Some("tGzv3JOkF0XG5Qx2TlKWIA")), &server);
+		let mut receiver = refresher.subscribe();
+
+		let e = refresher.run().await.err().unwrap();
+		assert!(!e.is_transient());
+		assert_eq!(server.0.load(Ordering::SeqCst), 1);
+		assert!(receiver.changed().await.is_err());
+		assert_eq!(receiver.borrow().as_str(), "token-0");
+	}
+}
@@ -0,0 +1,403 @@
+use std::{fmt, future::Future, ops::Deref};
+
+use base64::{Engine, prelude::BASE64_STANDARD};
+use http::{HeaderValue, header::AUTHORIZATION};
+use serde::Serialize;
+
+use crate::{
+	endpoints::{Endpoint, HttpRequest, RequestBuilder},
+	transport::{HttpClient, REDACTED},
+};
+
+use super::{ClientAuthMethod, OAuth2Client, OAuth2ClientError};
+
+/// Provider of a client secret.
+///
+/// The secret is resolved every time a request is built, so that it can be
+/// kept in a vault or key management service and rotated without rebuilding
+/// the client.
+pub trait SecretProvider {
+	/// Returns the current secret.
+	fn secret(&self) -> impl Future<Output = Result<String, OAuth2ClientError>>;
+}
+
+impl SecretProvider for str {
+	async fn secret(&self) -> Result<String, OAuth2ClientError> {
+		Ok(self.to_owned())
+	}
+}
+
+impl SecretProvider for String {
+	async fn secret(&self) -> Result<String, OAuth2ClientError> {
+		Ok(self.clone())
+	}
+}
+
+impl<S: ?Sized + SecretProvider> SecretProvider for &S {
+	async fn secret(&self) -> Result<String, OAuth2ClientError> {
+		S::secret(self).await
+	}
+}
+
+/// Secret read from an environment variable when the request is built.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EnvSecret(pub String);
+
+impl EnvSecret {
+	/// Creates a provider reading the given environment variable.
+	pub fn new(name: impl Into<String>) -> Self {
+		Self(name.into())
+	}
+}
+
+impl SecretProvider for EnvSecret {
+	async fn secret(&self) -> Result<String, OAuth2ClientError> {
+		std::env::var(&self.0).map_err(|_| {
+			OAuth2ClientError::request(format!("missing environment variable `{}`", self.0))
+		})
+	}
+}
+
+/// Secret returned by a callback when the request is built.
+#[derive(Debug, Clone, Copy)]
+pub struct SecretFn<F>(pub F);
+
+impl<F, Fut> SecretProvider for SecretFn<F>
+where
+	F: Fn() -> Fut,
+	Fut: Future<Output = Result<String, OAuth2ClientError>>,
+{
+	async fn secret(&self) -> Result<String, OAuth2ClientError> {
+		(self.0)().await
+	}
+}
+
+/// Body of a request authenticated with the `client_secret_post` method.
+#[derive(Clone, Serialize)]
+pub struct WithClientSecretBody<T> {
+	/// The client secret, if sent in the body.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub client_secret: Option<String>,
+
+	/// The inner request body.
+	#[serde(flatten)]
+	pub value: T,
+}
+
+impl<T: fmt::Debug> fmt::Debug for WithClientSecretBody<T> {
+	/// Formats the body, redacting the client secret.
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("WithClientSecretBody")
+			.field(
+				"client_secret",
+				&self.client_secret.as_ref().map(|_| REDACTED),
+			)
+			.field("value", &self.value)
+			.finish()
+	}
+}
+
+/// Wrapper authenticating a request with a client secret.
+///
+/// The secret is resolved from the [`SecretProvider`] when the request is
+/// built. The request is sent unauthenticated with
+/// [`ClientAuthMethod::None`], and fails to build with the methods that do
+/// not use the client secret directly.
+pub struct WithClientSecret<'a, S: ?Sized, T> {
+	/// The secret provider.
+	pub secret: &'a S,
+
+	/// How the secret is sent.
+	pub method: ClientAuthMethod,
+
+	/// The inner request being extended.
+	pub value: T,
+}
+
+impl<'a, S: ?Sized, T> WithClientSecret<'a, S, T> {
+	/// Creates a new [`WithClientSecret`] wrapping the given request.
+	pub fn new(value: T, secret: &'a S, method: ClientAuthMethod) -> Self {
+		Self {
+			secret,
+			method,
+			value,
+		}
+	}
+}
+
+impl<'a, S: ?Sized, T> Deref for WithClientSecret<'a, S, T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		&self.value
+	}
+}
+
+impl<'a, E, S, T> HttpRequest<E> for WithClientSecret<'a, S, T>
+where
+	E: Endpoint,
+	S: ?Sized + SecretProvider,
+	T: HttpRequest<E>,
+{
+	type ContentType = T::ContentType;
+	type RequestBody<'b>
+		= WithClientSecretBody<T::RequestBody<'b>>
+	where
+		Self: 'b;
+	type Response = T::Response;
+	type ResponsePayload = T::ResponsePayload;
+
+	async fn build_request(
+		&self,
+		endpoint: &E,
+		http_client: &impl HttpClient,
+	) -> Result<http::Request<Self::RequestBody<'_>>, OAuth2ClientError> {
+		let method = self.method;
+		let secret = match method {
+			ClientAuthMethod::None => None,
+			ClientAuthMethod::ClientSecretBasic
+			| ClientAuthMethod::ClientSecretBasicUnencoded
+			| ClientAuthMethod::ClientSecretPost => Some(self.secret.secret().await?),
+			_ => {
+				return Err(OAuth2ClientError::request(format!(
+					"unsupported client authentication method `{method}`"
+				)));
+			}
+		};
+		let request = self.value.build_request(endpoint, http_client).await?;
+
+		Ok(match secret {
+			Some(secret) if method == ClientAuthMethod::ClientSecretPost => {
+				request.map(|value| WithClientSecretBody {
+					client_secret: Some(secret),
+					value,
+				})
+			}
+			Some(secret) => {
+				let mut request = request.map(|value| WithClientSecretBody {
+					client_secret: None,
+					value,
+				});
+				request.headers_mut().insert(
+					AUTHORIZATION,
+					basic_authorization(
+						endpoint.client().client_id().as_str(),
+						&secret,
+						method == ClientAuthMethod::ClientSecretBasic,
+					),
+				);
+				request
+			}
+			None => request.map(|value| WithClientSecretBody {
+				client_secret: None,
+				value,
+			}),
+		})
+	}
+
+	fn decode_response(
+		&self,
+		endpoint: &E,
+		response: http::Response<Vec<u8>>,
+	) -> Result<http::Response<Self::ResponsePayload>, OAuth2ClientError> {
+		self.value.decode_response(endpoint, response)
+	}
+
+	async fn process_response(
+		&self,
+		endpoint: &E,
+		http_client: &impl HttpClient,
+		response: http::Response<Self::ResponsePayload>,
+	) -> Result<Self::Response, OAuth2ClientError> {
+		self.value
+			.process_response(endpoint, http_client, response)
+			.await
+	}
+}
+
+/// Builds the value of the `Authorization` header for the HTTP Basic
+/// authentication scheme.
+///
+/// Unless `form_encode` is `false`, the client identifier and secret are
+/// form-urlencoded before being combined, as required by
+/// [RFC 6749 Section 2.3.1](https://datatracker.ietf.org/doc/html/rfc6749#section-2.3.1).
+fn basic_authorization(client_id: &str, secret: &str, form_encode: bool) -> HeaderValue {
+	let credentials = if form_encode {
+		format!("{}:{}", form_urlencode(client_id), form_urlencode(secret))
+	} else {
+		format!("{client_id}:{secret}")
+	};
+	let mut value = HeaderValue::try_from(format!("Basic {}", BASE64_STANDARD.encode(credentials)))
+		// UNWRAP SAFETY: base64 is a valid header value.
+		.unwrap();
+	value.set_sensitive(true);
+	value
+}
+
+fn form_urlencode(s: &str) -> String {
+	let mut result = String::with_capacity(s.len());
+
+	for b in s.bytes() {
+		match b {
+			b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'*' => {
+				result.push(b as char)
+			}
+			b' ' => result.push('+'),
+			_ => result.push_str(&format!("%{b:02X}")),
+		}
+	}
+
+	result
+}
+
+/// Extension trait for authenticating the request of a [`RequestBuilder`]
+/// with a client secret.
+pub trait AddClientSecret<'a, S: ?Sized> {
+	/// The resulting type after adding the client secret.
+	type Output;
+
+	/// Wraps the current request so that it is authenticated with the secret
+	/// returned by `secret`, sent with the given method.
+	fn with_client_secret(self, secret: &'a S, method: ClientAuthMethod) -> Self::Output;
+}
+
+impl<'a, S, E, T> AddClientSecret<'a, S> for RequestBuilder<E, T>
+where
+	S: 'a + ?Sized,
+{
+	type Output = RequestBuilder<E, WithClientSecret<'a, S, T>>;
+
+	fn with_client_secret(self, secret: &'a S, method: ClientAuthMethod) -> Self::Output {
+		self.map(|value| WithClientSecret::new(value, secret, method))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::atomic::{AtomicUsize, Ordering};
+
+	use iref::uri;
+
+	use crate::{
+		ClientId, client_id, code,
+		endpoints::token::{TokenEndpoint, TokenResponse},
+		transport::{ContentType, WwwFormUrlEncoded},
+	};
+
+	use super::*;
+
+	struct Client;
+
+	impl OAuth2Client for Client {
+		type TokenResponse = TokenResponse;
+
+		fn client_id(&self) -> &ClientId {
+			client_id!("s6BhdRkqt3")
+		}
+	}
+
+	/// HTTP client that does not expect any request.
+	struct NoHttpClient;
+
+	impl HttpClient for NoHttpClient {
+		async fn send(
+			&self,
+			_request: http::Request<Vec<u8>>,
+		) -> Result<http::Response<Vec<u8>>, OAuth2ClientError> {
+			unreachable!()
+		}
+	}
+
+	#[tokio::test]
+	async fn client_secret_basic() {
+		let builder = TokenEndpoint::new(&Client, uri!("https://server.example.com/token"))
+			.exchange_code(code!("SplxlOBeZQQYbYS6WxSbIA").to_owned(), None)
+			.with_client_secret(
+				"7Fjfp0ZBr1KtDRbnfVdmIw",
+				ClientAuthMethod::ClientSecretBasic,
+			);
+		let request = builder
+			.request
+			.build_request(&builder.endpoint, &NoHttpClient)
+			.await
+			.unwrap();
+
+		assert_eq!(
+			request.headers()[AUTHORIZATION],
+			"Basic czZCaGRSa3F0Mzo3RmpmcDBaQnIxS3REUmJuZlZkbUl3"
+		);
+		assert!(!WwwFormUrlEncoded::encode(request.body()).starts_with(b"client_secret"));
+	}
+
+	#[tokio::test]
+	async fn client_secret_post() {
+		let builder = TokenEndpoint::new(&Client, uri!("https://server.example.com/token"))
+			.exchange_code(code!("SplxlOBeZQQYbYS6WxSbIA").to_owned(), None)
+			.with_client_secret("7Fjfp0ZBr1KtDRbnfVdmIw", ClientAuthMethod::ClientSecretPost);
+		let request = builder
+			.request
+			.build_request(&builder.endpoint, &NoHttpClient)
+			.await
+			.unwrap();
+
+		assert!(!request.headers().contains_key(AUTHORIZATION));
+		assert!(!format!("{:?}", request.body()).contains("7Fjfp0ZBr1KtDRbnfVdmIw"));
+		assert_eq!(
+			String::from_utf8(WwwFormUrlEncoded::encode(request.body())).unwrap(),
+			"client_secret=7Fjfp0ZBr1KtDRbnfVdmIw&grant_type=authorization_code&client_id=s6BhdRkqt3&code=SplxlOBeZQQYbYS6WxSbIA"
+		);
+	}
+
+	#[tokio::test]
+	async fn rotated_secret() {
+		let calls = AtomicUsize::new(0);
+		let secret = SecretFn(|| {
+			let n = calls.fetch_add(1, Ordering::SeqCst);
+			async move { Ok(format!("secret-{n}")) }
+		});
+
+		for expected in ["secret-0", "secret-1"] {
+			let builder = TokenEndpoint::new(&Client, uri!("https://server.example.com/token"))
+				.exchange_code(code!("SplxlOBeZQQYbYS6WxSbIA").to_owned(), None)
+				.with_client_secret(&secret, ClientAuthMethod::ClientSecretPost);
+			let request = builder
+				.request
+				.build_request(&builder.endpoint, &NoHttpClient)
+				.await
+				.unwrap();
+			assert_eq!(request.body().client_secret.as_deref(), Some(expected));
+		}
+	}
+
+	#[tokio::test]
+	async fn basic_credentials_encoding() {
+		assert_eq!(form_urlencode("a b:c/é"), "a+b%3Ac%2F%C3%A9");
+
+		let decode = async |method| {
+			let builder = TokenEndpoint::new(&Client, uri!("https://server.example.com/token"))
+				.exchange_code(code!("SplxlOBeZQQYbYS6WxSbIA").to_owned(), None)
+				.with_client_secret("p@ss+w%rd", method);
+			let request = builder
+				.request
+				.build_request(&builder.endpoint, &NoHttpClient)
+				.await
+				.unwrap();
+			let credentials = request.headers()[AUTHORIZATION]
+				.to_str()
+				.unwrap()
+				.strip_prefix("Basic ")
+				.unwrap()
+				.to_owned();
+			String::from_utf8(BASE64_STANDARD.decode(credentials).unwrap()).unwrap()
+		};
+
+		assert_eq!(
+			decode(ClientAuthMethod::ClientSecretBasic).await,
+			"s6BhdRkqt3:p%40ss%2Bw%25rd"
+		);
+		assert_eq!(
+			decode(ClientAuthMethod::ClientSecretBasicUnencoded).await,
+			"s6BhdRkqt3:p@ss+w%rd"
+		);
+	}
+}
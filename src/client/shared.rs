@@ -0,0 +1,311 @@
+use std::{
+	sync::atomic::{AtomicU64, Ordering},
+	time::Duration,
+};
+
+use tokio::sync::Mutex;
+
+use crate::{
+	AccessTokenBuf,
+	client::{IntoTokenSet, OAuth2Client, TokenManager, TokenRefreshError},
+	endpoints::token::{TokenResponse, TokenType},
+	time::{Clock, SystemClock},
+	transport::HttpClient,
+};
+
+/// [`TokenManager`] shared between concurrent tasks.
+///
+/// Refreshes are deduplicated: when many tasks need a new access token at
+/// the same time, the first one performs the refresh while the others wait
+/// for its result, success or failure, instead of sending their own
+/// request.
+///
+/// Requires the `tokio` feature.
+pub struct SharedTokenManager<C: OAuth2Client, K = SystemClock>
+where
+	C::TokenResponse: IntoTokenSet,
+{
+	state: Mutex<State<C, K>>,
+
+	/// Number of refreshes performed so far, used by waiting tasks to detect
+	/// that a refresh completed while they were waiting.
+	generation: AtomicU64,
+
+	max_attempts: u32,
+
+	max_retry_after: Duration,
+}
+
+struct State<C: OAuth2Client, K>
+where
+	C::TokenResponse: IntoTokenSet,
+{
+	manager: TokenManager<C, K>,
+	last_refresh: Result<(), TokenRefreshError>,
+}
+
+impl<C, T, E, K> SharedTokenManager<C, K>
+where
+	C: OAuth2Client<TokenResponse = TokenResponse<T, E>>,
+	T: TokenType,
+	K: Clock,
+{
+	/// Default maximum number of attempts of a single refresh.
+	pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+	/// Default longest delay requested by the server that is waited for
+	/// before retrying a refresh.
+	pub const DEFAULT_MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+	/// Creates a new shared token manager.
+	pub fn new(manager: TokenManager<C, K>) -> Self {
+		Self {
+			state: Mutex::new(State {
+				manager,
+				last_refresh: Ok(()),
+			}),
+			generation: AtomicU64::new(0),
+			max_attempts: Self::DEFAULT_MAX_ATTEMPTS,
+			max_retry_after: Self::DEFAULT_MAX_RETRY_AFTER,
+		}
+	}
+
+	/// Sets the maximum number of attempts of a single refresh.
+	///
+	/// Only [transient](TokenRefreshError::is_transient) failures are
+	/// retried, after the [delay](TokenRefreshError::retry_after) requested
+	/// by the server, if any. At least one attempt is always made.
+	pub fn with_max_attempts(self, max_attempts: u32) -> Self {
+		Self {
+			max_attempts,
+			..self
+		}
+	}
+
+	/// Sets the longest delay requested by the server that is waited for
+	/// before retrying a refresh.
+	///
+	/// Other tasks needing the token wait for the refresh, including its
+	/// retries. If the server requests a longer delay, the transient error is
+	/// returned instead of retrying.
+	pub fn with_max_retry_after(self, max_retry_after: Duration) -> Self {
+		Self {
+			max_retry_after,
+			..self
+		}
+	}
+
+	/// Returns the current access token, refreshing it first if it expires
+	/// soon.
+	pub async fn valid_access_token(
+		&self,
+		http_client: &impl HttpClient,
+	) -> Result<AccessTokenBuf, TokenRefreshError> {
+		self.refresh_if(http_client, TokenManager::expires_soon)
+			.await
+	}
+
+	/// Refreshes the access token, typically after it was rejected by a
+	/// resource server.
+	///
+	/// If another task refreshed the token while this one was waiting, the
+	/// result of that refresh is returned instead.
+	pub async fn refresh(
+		&self,
+		http_client: &impl HttpClient,
+	) -> Result<AccessTokenBuf, TokenRefreshError> {
+		self.refresh_if(http_client, |_| true).await
+	}
+
+	async fn refresh_if(
+		&self,
+		http_client: &impl HttpClient,
+		needs_refresh: impl FnOnce(&TokenManager<C, K>) -> bool,
+	) -> Result<AccessTokenBuf, TokenRefreshError> {
+		let generation = self.generation.load(Ordering::Acquire);
+		let mut state = self.state.lock().await;
+
+		if self.generation.load(Ordering::Acquire) != generation {
+			// Another task refreshed the token while we were waiting.
+			state.last_refresh.clone()?;
+			return Ok(state.manager.access_token().to_owned());
+		}
+
+		if needs_refresh(&state.manager) {
+			let mut attempts = 0;
+			let result = loop {
+				attempts += 1;
+				match state.manager.refresh(http_client).await {
+					Ok(_) => break Ok(()),
+					Err(e)
+						if e.is_transient()
+							&& attempts < self.max_attempts
+							&& e.retry_after()
+								.is_none_or(|retry_after| retry_after <= self.max_retry_after) =>
+					{
+						log::warn!("token refresh failed, retrying: {e}");
+						if let Some(retry_after) = e.retry_after() {
+							tokio::time::sleep(retry_after).await;
+						}
+					}
+					Err(e) => break Err(e),
+				}
+			};
+
+			state.last_refresh = result.clone();
+			self.generation.fetch_add(1, Ordering::Release);
+			result?;
+		}
+
+		Ok(state.manager.access_token().to_owned())
+	}
+
+	/// Returns the inner token manager.
+	pub fn into_inner(self) -> TokenManager<C, K> {
+		self.state.into_inner().manager
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::atomic::{AtomicUsize, Ordering};
+
+	use crate::{
+		client::{
+			OAuth2ClientError,
+			token_manager::tests::{TestClient, TestServer, manager},
+		},
+		time::MockClock,
+	};
+
+	use super::*;
+
+	/// Token endpoint answering after one second, or failing with the given
+	/// status code.
+	#[derive(Default)]
+	struct SlowServer {
+		inner: TestServer,
+		error: Option<http::StatusCode>,
+		requests: AtomicUsize,
+	}
+
+	impl HttpClient for SlowServer {
+		async fn send(
+			&self,
+			request: http::Request<Vec<u8>>,
+		) -> Result<http::Response<Vec<u8>>, OAuth2ClientError> {
+			self.requests.fetch_add(1, Ordering::SeqCst);
+			tokio::time::sleep(Duration::from_secs(1)).await;
+			match self.error {
+				Some(status) => Ok(http::Response::builder()
+					.status(status)
+					.body(Vec::new())
+					.unwrap()),
+				None => self.inner.send(request).await,
+			}
+		}
+	}
+
+	fn expired_manager() -> SharedTokenManager<TestClient, MockClock> {
+		let manager = manager(Some("tGzv3JOkF0XG5Qx2TlKWIA"));
+		manager.clock().advance(Duration::from_secs(3600));
+		SharedTokenManager::new(manager)
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn single_flight() {
+		let manager = expired_manager();
+		let server = SlowServer::default();
+
+		let (a, b, c) = tokio::join!(
+			manager.valid_access_token(&server),
+			manager.valid_access_token(&server),
+			manager.refresh(&server),
+		);
+
+		assert_eq!(a.unwrap().as_str(), "token-1");
+		assert_eq!(b.unwrap().as_str(), "token-1");
+		assert_eq!(c.unwrap().as_str(), "token-1");
+		assert_eq!(server.requests.load(Ordering::SeqCst), 1);
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn shared_failure() {
+		let manager = expired_manager().with_max_attempts(2);
+		let server = SlowServer {
+			error: Some(http::StatusCode::SERVICE_UNAVAILABLE),
+			..Default::default()
+		};
+
+		let (a, b) = tokio::join!(
+			manager.valid_access_token(&server),
+			manager.valid_access_token(&server),
+		);
+
+		assert!(a.unwrap_err().is_transient());
+		assert!(b.unwrap_err().is_transient());
+		assert_eq!(server.requests.load(Ordering::SeqCst), 2);
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn permanent_failure_is_not_retried() {
+		let manager = expired_manager();
+		let server = SlowServer {
+			error: Some(http::StatusCode::BAD_REQUEST),
+			..Default::default()
+		};
+
+		assert!(manager.valid_access_token(&server).await.is_err());
+		assert_eq!(server.requests.load(Ordering::SeqCst), 1);
+	}
+
+	/// Token endpoint rate limiting the first request.
+	#[derive(Default)]
+	struct RateLimitingServer {
+		inner: TestServer,
+		requests: AtomicUsize,
+	}
+
+	impl HttpClient for RateLimitingServer {
+		async fn send(
+			&self,
+			request: http::Request<Vec<u8>>,
+		) -> Result<http::Response<Vec<u8>>, OAuth2ClientError> {
+			if self.requests.fetch_add(1, Ordering::SeqCst) == 0 {
+				Ok(http::Response::builder()
+					.status(http::StatusCode::TOO_MANY_REQUESTS)
+					.header(http::header::RETRY_AFTER, "30")
+					.body(Vec::new())
+					.unwrap())
+			} else {
+				self.inner.send(request).await
+			}
+		}
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn rate_limited_retry() {
+		let manager = expired_manager();
+		let server = RateLimitingServer::default();
+
+		let start = tokio::time::Instant::now();
+		assert_eq!(
+			manager.valid_access_token(&server).await.unwrap().as_str(),
+			"token-1"
+		);
+		assert_eq!(start.elapsed(), Duration::from_secs(30));
+		assert_eq!(server.requests.load(Ordering::SeqCst), 2);
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn rate_limited_beyond_max_retry_after() {
+		let manager = expired_manager().with_max_retry_after(Duration::from_secs(10));
+		let server = RateLimitingServer::default();
+
+		let start = tokio::time::Instant::now();
+		let e = manager.valid_access_token(&server).await.unwrap_err();
+		assert_eq!(e.retry_after(), Some(Duration::from_secs(30)));
+		assert_eq!(start.elapsed(), Duration::ZERO);
+		assert_eq!(server.requests.load(Ordering::SeqCst), 1);
+	}
+}
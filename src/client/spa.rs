@@ -0,0 +1,350 @@
+use std::time::SystemTime;
+
+use iref::UriBuf;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	AccessToken, AccessTokenBuf,
+	client::{OAuth2Client, OAuth2ClientError},
+	endpoints::token::{TokenEndpoint, TokenResponse, TokenType},
+	time::{Clock, LeewayConfig, SystemClock},
+	transport::HttpClient,
+};
+
+/// Token session of a browser-based application.
+///
+/// See: <https://datatracker.ietf.org/doc/html/draft-ietf-oauth-browser-based-apps>
+///
+/// The session follows the recommendations for browser-based apps:
+/// - the access token is only kept in memory, and is never part of the
+///   [stored session](Self::stored);
+/// - refresh tokens are expected to be rotated on every use, and the stored
+///   session must be updated accordingly (see
+///   [`take_rotated`](Self::take_rotated));
+/// - when the refresh token is rejected with `invalid_grant`, the session is
+///   cleared and [`SpaSessionError::ReauthenticationRequired`] is returned,
+///   signaling the application to start a new (possibly silent)
+///   authorization request.
+///
+/// Requires the `browser` feature.
+pub struct SpaTokenSession<C, K = SystemClock> {
+	client: C,
+	token_uri: UriBuf,
+	access_token: Option<AccessTokenBuf>,
+	expires_at: Option<SystemTime>,
+	refresh_token: Option<String>,
+	rotated: bool,
+	leeway: LeewayConfig,
+	clock: K,
+}
+
+impl<C: OAuth2Client> SpaTokenSession<C> {
+	/// Creates a new, unauthenticated session for the given client.
+	///
+	/// Tokens are refreshed at the `token_uri` token endpoint.
+	pub fn new(client: C, token_uri: UriBuf) -> Self {
+		Self {
+			client,
+			token_uri,
+			access_token: None,
+			expires_at: None,
+			refresh_token: None,
+			rotated: false,
+			leeway: LeewayConfig::default(),
+			clock: SystemClock,
+		}
+	}
+
+	/// Restores a session from its stored state.
+	///
+	/// No access token is available until the session is refreshed, which
+	/// [`valid_access_token`](Self::valid_access_token) does automatically.
+	pub fn restore(client: C, token_uri: UriBuf, stored: StoredSpaSession) -> Self {
+		Self {
+			refresh_token: Some(stored.refresh_token),
+			..Self::new(client, token_uri)
+		}
+	}
+}
+
+impl<C: OAuth2Client, K> SpaTokenSession<C, K> {
+	/// Sets the clock used to expire the access token.
+	pub fn with_clock<L: Clock>(self, clock: L) -> SpaTokenSession<C, L> {
+		SpaTokenSession {
+			client: self.client,
+			token_uri: self.token_uri,
+			access_token: self.access_token,
+			expires_at: self.expires_at,
+			refresh_token: self.refresh_token,
+			rotated: self.rotated,
+			leeway: self.leeway,
+			clock,
+		}
+	}
+
+	/// Sets the leeway with which the access token is considered expired
+	/// before its actual expiration time.
+	pub fn with_leeway(self, leeway: LeewayConfig) -> Self {
+		Self { leeway, ..self }
+	}
+
+	/// Returns the OAuth 2.0 client.
+	pub fn client(&self) -> &C {
+		&self.client
+	}
+
+	/// Returns the current access token, even if expired.
+	pub fn access_token(&self) -> Option<&AccessToken> {
+		self.access_token.as_deref()
+	}
+
+	/// Checks whether the session holds a refresh token, and can hence
+	/// obtain access tokens without user interaction.
+	pub fn is_authenticated(&self) -> bool {
+		self.refresh_token.is_some()
+	}
+
+	/// Returns the state of the session to store, if authenticated.
+	///
+	/// Only the refresh token is stored. Since it is rotated on every
+	/// refresh, the stored state must be replaced after each refresh.
+	pub fn stored(&self) -> Option<StoredSpaSession> {
+		self.refresh_token.clone().map(StoredSpaSession::new)
+	}
+
+	/// Returns the new state of the session to store if the refresh token
+	/// changed since the last call.
+	pub fn take_rotated(&mut self) -> Option<StoredSpaSession> {
+		if std::mem::take(&mut self.rotated) {
+			self.stored()
+		} else {
+			None
+		}
+	}
+
+	/// Clears the session, for instance when the user logs out.
+	///
+	/// The stored state of the session, if any, must be removed.
+	pub fn clear(&mut self) {
+		self.access_token = None;
+		self.expires_at = None;
+		self.refresh_token = None;
+		self.rotated = false;
+	}
+}
+
+impl<C, T, E, K> SpaTokenSession<C, K>
+where
+	C: OAuth2Client<TokenResponse = TokenResponse<T, E>>,
+	T: TokenType,
+	K: Clock,
+{
+	/// Updates the session with a token response, typically received from
+	/// an authorization code exchange.
+	///
+	/// If the response does not include a refresh token, the current one is
+	/// kept.
+	pub fn set_token(&mut self, token: TokenResponse<T, E>) {
+		self.expires_at = token.expires_in().map(|d| self.clock.deadline_in(d));
+		self.access_token = Some(token.access_token);
+
+		if let Some(refresh_token) = token.refresh_token
+			&& self.refresh_token.as_ref() != Some(&refresh_token)
+		{
+			self.refresh_token = Some(refresh_token);
+			self.rotated = true;
+		}
+	}
+
+	/// Returns the current access token, refreshing it first if it is
+	/// missing or expires soon.
+	pub async fn valid_access_token(
+		&mut self,
+		http_client: &impl HttpClient,
+	) -> Result<&AccessToken, SpaSessionError> {
+		let expires_soon = self
+			.expires_at
+			.is_some_and(|expires_at| self.leeway.expires_soon(&self.clock, expires_at));
+
+		if self.access_token.is_none() || expires_soon {
+			self.refresh(http_client).await
+		} else {
+			Ok(self.access_token.as_deref().unwrap())
+		}
+	}
+
+	/// Refreshes the access token, regardless of its expiration time.
+	pub async fn refresh(
+		&mut self,
+		http_client: &impl HttpClient,
+	) -> Result<&AccessToken, SpaSessionError> {
+		let refresh_token = self
+			.refresh_token
+			.clone()
+			.ok_or(SpaSessionError::ReauthenticationRequired)?;
+
+		let result = TokenEndpoint::new(&self.client, &self.token_uri)
+			.refresh(refresh_token, None)
+			.send(http_client)
+			.await;
+
+		match result {
+			Ok(token) => {
+				if token.refresh_token.is_none() {
+					log::warn!("refresh token was not rotated");
+				}
+
+				self.set_token(token);
+				// UNWRAP SAFETY: the access token was just set.
+				Ok(self.access_token.as_deref().unwrap())
+			}
+			Err(e) if e.error_code() == Some("invalid_grant") => {
+				self.clear();
+				Err(SpaSessionError::ReauthenticationRequired)
+			}
+			Err(e) => Err(e.into()),
+		}
+	}
+}
+
+/// Stored state of a [`SpaTokenSession`].
+///
+/// Converts to and from JSON strings, which can be passed as is across the
+/// `wasm-bindgen` boundary to be kept in the browser storage.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredSpaSession {
+	/// The refresh token.
+	pub refresh_token: String,
+}
+
+impl StoredSpaSession {
+	/// Creates a new stored session.
+	pub fn new(refresh_token: String) -> Self {
+		Self { refresh_token }
+	}
+
+	/// Decodes a stored session from JSON.
+	pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+		serde_json::from_str(json)
+	}
+
+	/// Encodes the stored session to JSON.
+	pub fn to_json(&self) -> String {
+		// UNWRAP SAFETY: serializing a struct of strings cannot fail.
+		serde_json::to_string(self).unwrap()
+	}
+}
+
+/// Error that can occur while refreshing the access token of a
+/// [`SpaTokenSession`].
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum SpaSessionError {
+	/// The session has no valid refresh token: the user must be
+	/// authenticated again, and the stored state of the session, if any,
+	/// must be removed.
+	#[error("reauthentication required")]
+	ReauthenticationRequired,
+
+	/// The refresh token request failed.
+	#[error(transparent)]
+	Client(#[from] OAuth2ClientError),
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{
+		sync::atomic::{AtomicUsize, Ordering},
+		time::Duration,
+	};
+
+	use crate::{
+		client::token_manager::tests::{TestClient, token},
+		time::MockClock,
+	};
+
+	use super::*;
+
+	/// Token endpoint rotating refresh tokens, rejecting reused ones.
+	#[derive(Default)]
+	struct RotatingServer(AtomicUsize);
+
+	impl HttpClient for RotatingServer {
+		async fn send(
+			&self,
+			request: http::Request<Vec<u8>>,
+		) -> Result<http::Response<Vec<u8>>, OAuth2ClientError> {
+			let body = String::from_utf8(request.into_body()).unwrap();
+			let n = self.0.fetch_add(1, Ordering::SeqCst);
+
+			let (status, body) = if body.contains(&format!("refresh_token=rt-{n}")) {
+				(
+					http::StatusCode::OK,
+					serde_json::json!({
+						"access_token": format!("token-{}", n + 1),
+						"token_type": "Bearer",
+						"expires_in": 3600,
+						"refresh_token": format!("rt-{}", n + 1)
+					}),
+				)
+			} else {
+				(
+					http::StatusCode::BAD_REQUEST,
+					serde_json::json!({ "error": "invalid_grant" }),
+				)
+			};
+
+			Ok(http::Response::builder()
+				.status(status)
+				.header(http::header::CONTENT_TYPE, "application/json")
+				.body(serde_json::to_vec(&body).unwrap())
+				.unwrap())
+		}
+	}
+
+	fn session(stored: &str) -> SpaTokenSession<TestClient, MockClock> {
+		SpaTokenSession::restore(
+			TestClient,
+			UriBuf::new(b"https://server.example.com/token".to_vec()).unwrap(),
+			StoredSpaSession::from_json(stored).unwrap(),
+		)
+		.with_clock(MockClock::from_unix_timestamp(0))
+	}
+
+	#[tokio::test]
+	async fn rotation() {
+		let mut session = session(r#"{"refresh_token":"rt-0"}"#);
+		let server = RotatingServer::default();
+		assert!(session.access_token().is_none());
+
+		let access_token = session.valid_access_token(&server).await.unwrap();
+		assert_eq!(access_token.as_str(), "token-1");
+		assert_eq!(
+			session.take_rotated().unwrap().to_json(),
+			r#"{"refresh_token":"rt-1"}"#
+		);
+		assert_eq!(session.take_rotated(), None);
+
+		session.clock.advance(Duration::from_secs(3600));
+		let access_token = session.valid_access_token(&server).await.unwrap();
+		assert_eq!(access_token.as_str(), "token-2");
+		assert_eq!(session.stored().unwrap().refresh_token, "rt-2");
+	}
+
+	#[tokio::test]
+	async fn reauthentication_required() {
+		let mut session = session(r#"{"refresh_token":"stolen"}"#);
+		let server = RotatingServer::default();
+
+		assert!(matches!(
+			session.refresh(&server).await,
+			Err(SpaSessionError::ReauthenticationRequired)
+		));
+		assert!(!session.is_authenticated());
+		assert_eq!(session.take_rotated(), None);
+		assert!(session.stored().is_none());
+
+		session.set_token(token(Some("rt-1")));
+		assert!(session.is_authenticated());
+		assert_eq!(session.access_token().unwrap().as_str(), "token-0");
+	}
+}
@@ -0,0 +1,368 @@
+use std::time::{Duration, SystemTime};
+
+use iref::UriBuf;
+
+use crate::{
+	AccessToken,
+	client::{IntoTokenSet, OAuth2Client, OAuth2ClientError, TokenSet},
+	endpoints::token::{TokenEndpoint, TokenResponse, TokenType, TokenTypePolicy},
+	time::{Clock, LeewayConfig, SystemClock},
+	transport::HttpClient,
+};
+
+/// Holds the current [`TokenSet`] of a client, refreshing it when it
+/// expires.
+///
+/// The expiration time of the access token is computed from the `expires_in`
+/// value of the token response upon receipt. Tokens without `expires_in` are
+/// considered valid until explicitly refreshed.
+pub struct TokenManager<C: OAuth2Client, K = SystemClock>
+where
+	C::TokenResponse: IntoTokenSet,
+{
+	client: C,
+	token_uri: UriBuf,
+	tokens: TokenSetOf<C>,
+	leeway: LeewayConfig,
+	token_type_policy: TokenTypePolicy,
+	clock: K,
+}
+
+/// Token set of the given client.
+pub type TokenSetOf<C> = TokenSet<
+	<<C as OAuth2Client>::TokenResponse as IntoTokenSet>::TokenType,
+	<<C as OAuth2Client>::TokenResponse as IntoTokenSet>::Extension,
+>;
+
+impl<C, T, E> TokenManager<C>
+where
+	C: OAuth2Client<TokenResponse = TokenResponse<T, E>>,
+	T: TokenType,
+{
+	/// Creates a new token manager for the given client, holding `token`.
+	///
+	/// The token is refreshed at the `token_uri` token endpoint.
+	pub fn new(client: C, token_uri: UriBuf, token: TokenResponse<T, E>) -> Self {
+		Self::new_with_clock(client, token_uri, token, SystemClock)
+	}
+
+	/// Creates a new token manager for the given client, holding previously
+	/// stored `tokens`.
+	pub fn from_token_set(client: C, token_uri: UriBuf, tokens: TokenSet<T, E>) -> Self {
+		Self::from_token_set_with_clock(client, token_uri, tokens, SystemClock)
+	}
+}
+
+impl<C, T, E, K> TokenManager<C, K>
+where
+	C: OAuth2Client<TokenResponse = TokenResponse<T, E>>,
+	T: TokenType,
+	K: Clock,
+{
+	/// Creates a new token manager for the given client, holding `token`,
+	/// using the given clock to expire the access token.
+	///
+	/// The token is considered issued at the current time of this clock.
+	pub fn new_with_clock(
+		client: C,
+		token_uri: UriBuf,
+		token: TokenResponse<T, E>,
+		clock: K,
+	) -> Self {
+		let tokens = TokenSet::new(token, clock.now());
+		Self::from_token_set_with_clock(client, token_uri, tokens, clock)
+	}
+
+	/// Creates a new token manager for the given client, holding previously
+	/// stored `tokens`, using the given clock to expire the access token.
+	pub fn from_token_set_with_clock(
+		client: C,
+		token_uri: UriBuf,
+		tokens: TokenSet<T, E>,
+		clock: K,
+	) -> Self {
+		Self {
+			client,
+			token_uri,
+			tokens,
+			leeway: LeewayConfig::default(),
+			token_type_policy: TokenTypePolicy::Strict,
+			clock,
+		}
+	}
+}
+
+impl<C, T, E, K> TokenManager<C, K>
+where
+	C: OAuth2Client<TokenResponse = TokenResponse<T, E>>,
+	T: TokenType,
+{
+	/// Sets the clock used to expire the access token.
+	///
+	/// The issue time of the current tokens is left unchanged. Use
+	/// [`new_with_clock`](Self::new_with_clock) to consider a new token
+	/// issued at the current time of the clock.
+	pub fn with_clock<L: Clock>(self, clock: L) -> TokenManager<C, L> {
+		TokenManager {
+			client: self.client,
+			token_uri: self.token_uri,
+			tokens: self.tokens,
+			leeway: self.leeway,
+			token_type_policy: self.token_type_policy,
+			clock,
+		}
+	}
+
+	/// Sets the leeway with which the access token is considered expired
+	/// before its actual expiration time.
+	pub fn with_leeway(self, leeway: LeewayConfig) -> Self {
+		Self { leeway, ..self }
+	}
+
+	/// Sets the tolerance to non-standard `token_type` values in the
+	/// responses of the token endpoint.
+	pub fn with_token_type_policy(self, token_type_policy: TokenTypePolicy) -> Self {
+		Self {
+			token_type_policy,
+			..self
+		}
+	}
+
+	/// Returns the OAuth 2.0 client.
+	pub fn client(&self) -> &C {
+		&self.client
+	}
+
+	/// Returns the current tokens.
+	pub fn tokens(&self) -> &TokenSet<T, E> {
+		&self.tokens
+	}
+
+	/// Returns the current access token, even if expired.
+	pub fn access_token(&self) -> &AccessToken {
+		&self.tokens.access_token
+	}
+
+	/// Returns the expiration time of the current access token, if known.
+	pub fn expires_at(&self) -> Option<SystemTime> {
+		self.tokens.expires_at()
+	}
+}
+
+impl<C, T, E, K> TokenManager<C, K>
+where
+	C: OAuth2Client<TokenResponse = TokenResponse<T, E>>,
+	T: TokenType,
+	K: Clock,
+{
+	/// Returns the clock used to expire the access token.
+	pub fn clock(&self) -> &K {
+		&self.clock
+	}
+
+	/// Checks whether the current access token expires within the configured
+	/// leeway.
+	pub fn expires_soon(&self) -> bool {
+		self.tokens.expires_soon(&self.clock, &self.leeway)
+	}
+
+	/// Merges a new token response into the current tokens, returning
+	/// whether the refresh token was rotated.
+	///
+	/// See [`TokenSet::apply`].
+	pub fn set_token(&mut self, token: TokenResponse<T, E>) -> bool {
+		self.tokens.apply(token, self.clock.now())
+	}
+
+	/// Refreshes the access token, regardless of its expiration time.
+	pub async fn refresh(
+		&mut self,
+		http_client: &impl HttpClient,
+	) -> Result<&AccessToken, TokenRefreshError> {
+		let refresh_token = self
+			.tokens
+			.refresh_token
+			.clone()
+			.ok_or(TokenRefreshError::NoRefreshToken)?;
+
+		let token = TokenEndpoint::new(&self.client, &self.token_uri)
+			.with_token_type_policy(self.token_type_policy)
+			.refresh(refresh_token, None)
+			.send(http_client)
+			.await?;
+
+		self.set_token(token);
+		Ok(self.access_token())
+	}
+
+	/// Returns the current access token, refreshing it first if it expires
+	/// soon.
+	pub async fn valid_access_token(
+		&mut self,
+		http_client: &impl HttpClient,
+	) -> Result<&AccessToken, TokenRefreshError> {
+		if self.expires_soon() {
+			self.refresh(http_client).await
+		} else {
+			Ok(self.access_token())
+		}
+	}
+}
+
+/// Error that can occur while refreshing an access token.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum TokenRefreshError {
+	/// The authorization server did not issue a refresh token.
+	#[error("no refresh token")]
+	NoRefreshToken,
+
+	/// The refresh token request failed.
+	#[error(transparent)]
+	Client(#[from] OAuth2ClientError),
+}
+
+impl TokenRefreshError {
+	/// Checks whether the refresh may succeed if attempted again.
+	///
+	/// See [`OAuth2ClientError::classification`]. Rate limited refreshes
+	/// should not be attempted again before
+	/// [`retry_after`](Self::retry_after).
+	pub fn is_transient(&self) -> bool {
+		match self {
+			Self::NoRefreshToken => false,
+			Self::Client(e) => e.is_transient(),
+		}
+	}
+
+	/// Returns the delay before the refresh may be attempted again, if the
+	/// server rate limited it.
+	pub fn retry_after(&self) -> Option<Duration> {
+		match self {
+			Self::Client(e) => e.retry_after(),
+			Self::NoRefreshToken => None,
+		}
+	}
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+	use std::{
+		sync::atomic::{AtomicUsize, Ordering},
+		time::Duration,
+	};
+
+	use crate::{AccessTokenBuf, ClientId, client_id, time::MockClock, util::NoExtension};
+
+	use super::*;
+
+	pub struct TestClient;
+
+	impl OAuth2Client for TestClient {
+		type TokenResponse = TokenResponse;
+
+		fn client_id(&self) -> &ClientId {
+			client_id!("s6BhdRkqt3")
+		}
+	}
+
+	/// Token endpoint issuing `token-1`, `token-2`, etc. without rotating
+	/// the refresh token.
+	#[derive(Default)]
+	pub struct TestServer(pub AtomicUsize);
+
+	impl HttpClient for TestServer {
+		async fn send(
+			&self,
+			request: http::Request<Vec<u8>>,
+		) -> Result<http::Response<Vec<u8>>, OAuth2ClientError> {
+			let body = String::from_utf8(request.into_body()).unwrap();
+			assert!(body.contains("grant_type=refresh_token"));
+			assert!(body.contains("refresh_token=tGzv3JOkF0XG5Qx2TlKWIA"));
+
+			let n = self.0.fetch_add(1, Ordering::SeqCst) + 1;
+			Ok(http::Response::builder()
+				.header(http::header::CONTENT_TYPE, "application/json")
+				.body(
+					serde_json::to_vec(&serde_json::json!({
+						"access_token": format!("token-{n}"),
+						"token_type": "Bearer",
+						"expires_in": 3600
+					}))
+					.unwrap(),
+				)
+				.unwrap())
+		}
+	}
+
+	pub fn token(refresh_token: Option<&str>) -> TokenResponse {
+		TokenResponse {
+			refresh_token: refresh_token.map(ToOwned::to_owned),
+			..TokenResponse::new(
+				AccessTokenBuf::new("token-0".to_owned()).unwrap(),
+				"Bearer".to_owned(),
+				NoExtension {},
+			)
+			.with_expires_in(Duration::from_secs(3600))
+		}
+	}
+
+	pub fn manager(refresh_token: Option<&str>) -> TokenManager<TestClient, MockClock> {
+		TokenManager::new_with_clock(
+			TestClient,
+			UriBuf::new(b"https://server.example.com/token".to_vec()).unwrap(),
+			token(refresh_token),
+			MockClock::from_unix_timestamp(0),
+		)
+	}
+
+	#[tokio::test]
+	async fn refresh_when_expiring() {
+		let mut manager = manager(Some("tGzv3JOkF0XG5Qx2TlKWIA"));
+		let server = TestServer::default();
+
+		let token = manager.valid_access_token(&server).await.unwrap();
+		assert_eq!(token.as_str(), "token-0");
+
+		manager.clock().advance(Duration::from_secs(3540));
+		let token = manager.valid_access_token(&server).await.unwrap();
+		assert_eq!(token.as_str(), "token-1");
+
+		// The refresh token is kept when not rotated.
+		assert_eq!(
+			manager.tokens().refresh_token.as_deref(),
+			Some("tGzv3JOkF0XG5Qx2TlKWIA")
+		);
+		assert_eq!(
+			manager.expires_at(),
+			Some(SystemTime::UNIX_EPOCH + Duration::from_secs(3540 + 3600))
+		);
+	}
+
+	#[test]
+	fn restored_tokens_keep_issue_time() {
+		let tokens = TokenSet::new(
+			token(Some("tGzv3JOkF0XG5Qx2TlKWIA")),
+			SystemTime::UNIX_EPOCH + Duration::from_secs(100),
+		);
+		let manager = TokenManager::from_token_set(
+			TestClient,
+			UriBuf::new(b"https://server.example.com/token".to_vec()).unwrap(),
+			tokens,
+		)
+		.with_clock(MockClock::from_unix_timestamp(1000));
+
+		assert_eq!(
+			manager.expires_at(),
+			Some(SystemTime::UNIX_EPOCH + Duration::from_secs(100 + 3600))
+		);
+	}
+
+	#[tokio::test]
+	async fn no_refresh_token() {
+		let mut manager = manager(None);
+		assert!(matches!(
+			manager.refresh(&TestServer::default()).await,
+			Err(TokenRefreshError::NoRefreshToken)
+		));
+	}
+}
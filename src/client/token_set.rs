@@ -0,0 +1,207 @@
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use serde_with::{TimestampSeconds, serde_as, skip_serializing_none};
+
+use crate::{
+	AccessToken, AccessTokenBuf, ScopeBuf,
+	endpoints::token::{TokenResponse, TokenType},
+	time::{Clock, LeewayConfig},
+	util::{NoExtension, RawJson},
+};
+
+/// Tokens held by a client, as the unit of storage of a [`TokenManager`].
+///
+/// Unlike a [`TokenResponse`], a token set remembers when it was issued, so
+/// that the expiration time of the access token can be computed after being
+/// stored and loaded again.
+///
+/// [`TokenManager`]: super::TokenManager
+#[serde_as]
+#[skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound(
+	serialize = "T: TokenType, E: Serialize",
+	deserialize = "T: TokenType, E: Deserialize<'de>"
+))]
+pub struct TokenSet<T: TokenType = String, E = NoExtension> {
+	/// The access token.
+	pub access_token: AccessTokenBuf,
+
+	/// The type of the access token.
+	pub token_type: T,
+
+	/// The refresh token, if any.
+	pub refresh_token: Option<String>,
+
+	/// The scope of the access token, if provided by the server.
+	pub scope: Option<ScopeBuf>,
+
+	/// Time at which the access token was received, anchoring its lifetime.
+	#[serde_as(as = "TimestampSeconds<i64>")]
+	pub issued_at: SystemTime,
+
+	/// Lifetime in seconds of the access token, from
+	/// [`issued_at`](Self::issued_at).
+	pub expires_in: Option<u64>,
+
+	/// Extension fields of the last token response.
+	#[serde(flatten)]
+	pub ext: E,
+}
+
+impl<T, E> TokenSet<T, E>
+where
+	T: TokenType,
+{
+	/// Creates a token set from the given token response, received at
+	/// `issued_at`.
+	pub fn new(token: TokenResponse<T, E>, issued_at: SystemTime) -> Self {
+		Self {
+			access_token: token.access_token,
+			token_type: token.token_type,
+			refresh_token: token.refresh_token,
+			scope: token.scope,
+			issued_at,
+			expires_in: token.expires_in,
+			ext: token.ext,
+		}
+	}
+
+	/// Returns the access token, even if expired.
+	pub fn access_token(&self) -> &AccessToken {
+		&self.access_token
+	}
+
+	/// Returns the lifetime of the access token, if known.
+	pub fn expires_in(&self) -> Option<Duration> {
+		self.expires_in.map(Duration::from_secs)
+	}
+
+	/// Returns the expiration time of the access token, if known.
+	pub fn expires_at(&self) -> Option<SystemTime> {
+		self.expires_in().map(|d| self.issued_at + d)
+	}
+
+	/// Checks whether the access token expires within the given leeway.
+	///
+	/// Access tokens without a known lifetime never expire.
+	pub fn expires_soon(&self, clock: &impl Clock, leeway: &LeewayConfig) -> bool {
+		self.expires_at()
+			.is_some_and(|expires_at| leeway.expires_soon(clock, expires_at))
+	}
+
+	/// Merges a token response received at `issued_at`, typically after a
+	/// refresh, and returns whether the refresh token was rotated.
+	///
+	/// The access token and its lifetime are always replaced. If the response
+	/// does not include a refresh token, the current one is kept, as allowed
+	/// by [RFC 6749 Section 6](https://datatracker.ietf.org/doc/html/rfc6749#section-6).
+	/// Likewise, an omitted scope is identical to the one previously granted.
+	pub fn apply(&mut self, token: TokenResponse<T, E>, issued_at: SystemTime) -> bool {
+		let rotated = match token.refresh_token {
+			Some(refresh_token) => {
+				let rotated = self.refresh_token.as_ref() != Some(&refresh_token);
+				self.refresh_token = Some(refresh_token);
+				rotated
+			}
+			None => false,
+		};
+
+		self.access_token = token.access_token;
+		self.token_type = token.token_type;
+		self.scope = token.scope.or(self.scope.take());
+		self.issued_at = issued_at;
+		self.expires_in = token.expires_in;
+		self.ext = token.ext;
+
+		rotated
+	}
+}
+
+/// Token response that can be held in a [`TokenSet`].
+///
+/// Implemented by [`TokenResponse`] and [`RawJson`] token responses, so that
+/// a [`TokenManager`] can name the token set of its client.
+///
+/// [`TokenManager`]: super::TokenManager
+pub trait IntoTokenSet {
+	/// Token type of the access token.
+	type TokenType: TokenType;
+
+	/// Extension fields of the response.
+	type Extension;
+
+	/// Converts this response, received at `issued_at`, into a token set.
+	fn into_token_set(self, issued_at: SystemTime) -> TokenSet<Self::TokenType, Self::Extension>;
+}
+
+impl<T, E> IntoTokenSet for TokenResponse<T, E>
+where
+	T: TokenType,
+{
+	type TokenType = T;
+	type Extension = E;
+
+	fn into_token_set(self, issued_at: SystemTime) -> TokenSet<T, E> {
+		TokenSet::new(self, issued_at)
+	}
+}
+
+impl<R> IntoTokenSet for RawJson<R>
+where
+	R: IntoTokenSet,
+{
+	type TokenType = R::TokenType;
+	type Extension = R::Extension;
+
+	fn into_token_set(self, issued_at: SystemTime) -> TokenSet<R::TokenType, R::Extension> {
+		self.into_value().into_token_set(issued_at)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{client::token_manager::tests::token, time::MockClock};
+
+	use super::*;
+
+	#[test]
+	fn apply() {
+		let epoch = SystemTime::UNIX_EPOCH;
+		let mut tokens = token(Some("refresh-0")).into_token_set(epoch);
+		assert_eq!(tokens.expires_at(), Some(epoch + Duration::from_secs(3600)));
+
+		let clock = MockClock::from_unix_timestamp(3540);
+		assert!(tokens.expires_soon(&clock, &LeewayConfig::default()));
+
+		// Refresh token kept when omitted.
+		let now = clock.now();
+		assert!(!tokens.apply(token(None), now));
+		assert_eq!(tokens.refresh_token.as_deref(), Some("refresh-0"));
+		assert_eq!(tokens.expires_at(), Some(now + Duration::from_secs(3600)));
+
+		// Refresh token replaced when rotated.
+		assert!(tokens.apply(token(Some("refresh-1")), now));
+		assert_eq!(tokens.refresh_token.as_deref(), Some("refresh-1"));
+		assert!(!tokens.apply(token(Some("refresh-1")), now));
+	}
+
+	#[test]
+	fn serialization() {
+		let tokens = token(Some("refresh-0"))
+			.into_token_set(SystemTime::UNIX_EPOCH + Duration::from_secs(1000));
+		let json = serde_json::to_value(&tokens).unwrap();
+		assert_eq!(
+			json,
+			serde_json::json!({
+				"access_token": "token-0",
+				"token_type": "Bearer",
+				"refresh_token": "refresh-0",
+				"issued_at": 1000,
+				"expires_in": 3600
+			})
+		);
+		assert_eq!(serde_json::from_value::<TokenSet>(json).unwrap(), tokens);
+	}
+}
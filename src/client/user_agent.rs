@@ -0,0 +1,124 @@
+use std::io;
+
+use iref::{Uri, UriBuf};
+
+/// External user-agent in which native apps perform authorization requests.
+///
+/// See: <https://www.rfc-editor.org/rfc/rfc8252.html#section-6>
+///
+/// Native apps must not use embedded user-agents, but open the authorization
+/// request in the system browser, or in an in-app browser tab sharing its
+/// state (e.g. Android Custom Tabs, or `ASWebAuthenticationSession` on
+/// Apple platforms). With the `system-browser` feature, [`SystemBrowser`]
+/// opens the default browser of the user.
+pub trait ExternalUserAgent {
+	/// Opens the authorization request URI, whose response is delivered to
+	/// `redirect_uri`.
+	///
+	/// User-agents capturing the redirection to `redirect_uri` themselves,
+	/// such as `ASWebAuthenticationSession`, return the callback URI the
+	/// user-agent was redirected to, to be parsed with
+	/// [`parse_callback`](super::parse_callback). Otherwise, the response is
+	/// delivered to the redirection endpoint of the app (e.g. a loopback
+	/// redirection endpoint) and `None` is returned.
+	#[allow(async_fn_in_trait)]
+	async fn open(&self, uri: &Uri, redirect_uri: &Uri) -> Result<Option<UriBuf>, UserAgentError>;
+}
+
+impl<T> ExternalUserAgent for &T
+where
+	T: ?Sized + ExternalUserAgent,
+{
+	async fn open(&self, uri: &Uri, redirect_uri: &Uri) -> Result<Option<UriBuf>, UserAgentError> {
+		T::open(*self, uri, redirect_uri).await
+	}
+}
+
+/// Error returned by an [`ExternalUserAgent`].
+#[derive(Debug, thiserror::Error)]
+pub enum UserAgentError {
+	/// The user closed the user-agent before the authorization completed.
+	#[error("authorization cancelled by the user")]
+	Cancelled,
+
+	/// The user-agent could not be opened.
+	#[error("unable to open the user-agent: {0}")]
+	Io(#[from] io::Error),
+}
+
+/// Default web browser of the user, opened with [`open::that`].
+///
+/// The authorization response is delivered to the redirection endpoint of
+/// the app, so [`open`](ExternalUserAgent::open) always returns `None`.
+///
+/// Requires the `system-browser` feature.
+#[cfg(feature = "system-browser")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SystemBrowser;
+
+#[cfg(feature = "system-browser")]
+impl ExternalUserAgent for SystemBrowser {
+	async fn open(&self, uri: &Uri, _redirect_uri: &Uri) -> Result<Option<UriBuf>, UserAgentError> {
+		open::that(uri.as_str())?;
+		Ok(None)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use iref::uri;
+
+	use crate::{
+		Stateful, client::parse_callback,
+		grant::authorization_code::AuthorizationCodeAuthorizationResponse, server::ServerResult,
+		state,
+	};
+
+	use super::*;
+
+	/// In-app browser tab capturing the redirection to the app.
+	struct AuthenticationSession {
+		cancelled: bool,
+	}
+
+	impl ExternalUserAgent for AuthenticationSession {
+		async fn open(
+			&self,
+			uri: &Uri,
+			redirect_uri: &Uri,
+		) -> Result<Option<UriBuf>, UserAgentError> {
+			assert_eq!(
+				uri,
+				"https://server.example.com/authorize?client_id=s6BhdRkqt3"
+			);
+			if self.cancelled {
+				return Err(UserAgentError::Cancelled);
+			}
+
+			Ok(Some(
+				UriBuf::new(
+					format!("{redirect_uri}?code=SplxlOBeZQQYbYS6WxSbIA&state=xyz").into_bytes(),
+				)
+				.unwrap(),
+			))
+		}
+	}
+
+	#[tokio::test]
+	async fn external_user_agent() {
+		let uri = uri!("https://server.example.com/authorize?client_id=s6BhdRkqt3");
+		let redirect_uri = uri!("com.example.app:/oauth2redirect");
+
+		let session = AuthenticationSession { cancelled: false };
+		let callback = session.open(uri, redirect_uri).await.unwrap().unwrap();
+		let response: Stateful<ServerResult<AuthorizationCodeAuthorizationResponse>> =
+			parse_callback(redirect_uri, &callback, Some(state!("xyz"))).unwrap();
+		assert!(matches!(response.value, ServerResult::Ok(_)));
+
+		let session = AuthenticationSession { cancelled: true };
+		assert!(matches!(
+			session.open(uri, redirect_uri).await,
+			Err(UserAgentError::Cancelled)
+		));
+	}
+}
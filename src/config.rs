@@ -0,0 +1,555 @@
+//! Client configuration.
+//!
+//! [`ClientConfig`] fully describes a client and the authorization server it
+//! talks to, described by a [`ProviderConfig`]. Configurations can be loaded
+//! from TOML or JSON documents, or from environment variables, and turned
+//! into a [`DynamicClient`] and [`AuthorizationServerMetadata`], from which
+//! endpoints are built:
+//!
+//! ```toml
+//! client_id = "s6BhdRkqt3"
+//! client_secret = { env = "TENANT_A_CLIENT_SECRET" }
+//! token_endpoint_auth_method = "client_secret_basic"
+//! scope = "read write"
+//! redirect_uris = ["https://client.example.com/cb"]
+//!
+//! [provider]
+//! issuer = "https://server.example.com"
+//! ```
+//!
+//! Requires the `config` feature.
+use std::{
+	collections::HashMap,
+	fmt,
+	path::{Path, PathBuf},
+};
+
+use iref::UriBuf;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	ClientIdBuf, EndpointUriPolicy, InsecureEndpointUri, IssuerBuf, ScopeBuf,
+	client::{ClientAuthError, ClientAuthMethod, DynamicClient, OAuth2ClientError},
+	server::AuthorizationServerMetadata,
+	transport::{HttpClient, REDACTED},
+	util::Discoverable,
+};
+
+/// Configuration error.
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+	/// The configuration file could not be read.
+	#[error("unable to read configuration file: {0}")]
+	Io(#[from] std::io::Error),
+
+	/// The configuration file extension is neither `toml` nor `json`.
+	#[error("unsupported configuration file format: {}", .0.display())]
+	UnsupportedFormat(PathBuf),
+
+	/// The TOML configuration is invalid.
+	#[error("invalid TOML configuration: {0}")]
+	Toml(#[from] toml::de::Error),
+
+	/// The JSON configuration, or the configuration built from environment
+	/// variables, is invalid.
+	#[error("invalid configuration: {0}")]
+	Json(#[from] serde_json::Error),
+
+	/// The environment variable holding a secret is not set.
+	#[error("missing environment variable `{0}`")]
+	MissingVariable(String),
+
+	/// The authorization server metadata could not be discovered.
+	#[error(transparent)]
+	Discovery(#[from] OAuth2ClientError),
+
+	/// A configured endpoint is not an `https` URI.
+	#[error(transparent)]
+	InsecureEndpoint(#[from] InsecureEndpointUri),
+
+	/// The client authentication method cannot be honored.
+	#[error(transparent)]
+	ClientAuth(#[from] ClientAuthError),
+}
+
+/// Reference to a secret value.
+///
+/// Secrets can be given inline, but should preferably be read from an
+/// environment variable or a file, so that they do not appear in the
+/// configuration itself.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SecretRef {
+	/// Inline secret value.
+	Value(String),
+
+	/// Secret stored in an environment variable.
+	Env {
+		/// Name of the environment variable.
+		env: String,
+	},
+
+	/// Secret stored in a file, with surrounding whitespace trimmed.
+	File {
+		/// Path to the file.
+		file: PathBuf,
+	},
+}
+
+impl SecretRef {
+	/// Reads the referenced secret.
+	pub fn resolve(&self) -> Result<String, ConfigError> {
+		match self {
+			Self::Value(value) => Ok(value.clone()),
+			Self::Env { env } => {
+				std::env::var(env).map_err(|_| ConfigError::MissingVariable(env.clone()))
+			}
+			Self::File { file } => Ok(std::fs::read_to_string(file)?.trim().to_owned()),
+		}
+	}
+}
+
+impl fmt::Debug for SecretRef {
+	/// Formats the reference, redacting inline secrets.
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::Value(_) => f.debug_tuple("Value").field(&REDACTED).finish(),
+			Self::Env { env } => f.debug_struct("Env").field("env", env).finish(),
+			Self::File { file } => f.debug_struct("File").field("file", file).finish(),
+		}
+	}
+}
+
+#[cfg(feature = "client-secret")]
+impl crate::client::SecretProvider for SecretRef {
+	async fn secret(&self) -> Result<String, OAuth2ClientError> {
+		self.resolve()
+			.map_err(|e| OAuth2ClientError::request(e.to_string()))
+	}
+}
+
+/// Authorization server configuration.
+///
+/// If no `token_endpoint` is configured, the authorization server metadata
+/// is discovered from the `issuer`. Configured endpoints always take
+/// precedence over discovered ones.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderConfig {
+	/// Issuer identifier of the authorization server.
+	pub issuer: IssuerBuf,
+
+	/// Authorization endpoint.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub authorization_endpoint: Option<UriBuf>,
+
+	/// Token endpoint.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub token_endpoint: Option<UriBuf>,
+
+	/// Token revocation endpoint.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub revocation_endpoint: Option<UriBuf>,
+
+	/// Token introspection endpoint.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub introspection_endpoint: Option<UriBuf>,
+
+	/// Pushed Authorization Request endpoint.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub pushed_authorization_request_endpoint: Option<UriBuf>,
+
+	/// Device Authorization endpoint.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub device_authorization_endpoint: Option<UriBuf>,
+
+	/// OpenID Connect end session endpoint.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub end_session_endpoint: Option<UriBuf>,
+
+	/// Whether to accept plain `http` endpoints on other hosts than
+	/// loopback addresses.
+	///
+	/// This must never be enabled in production.
+	#[serde(default, skip_serializing_if = "std::ops::Not::not")]
+	pub allow_insecure_http: bool,
+}
+
+impl ProviderConfig {
+	/// Creates a new configuration for the given issuer, relying on
+	/// discovery.
+	pub fn new(issuer: IssuerBuf) -> Self {
+		Self {
+			issuer,
+			authorization_endpoint: None,
+			token_endpoint: None,
+			revocation_endpoint: None,
+			introspection_endpoint: None,
+			pushed_authorization_request_endpoint: None,
+			device_authorization_endpoint: None,
+			end_session_endpoint: None,
+			allow_insecure_http: false,
+		}
+	}
+
+	/// Returns the policy applied to the endpoint URIs.
+	pub fn endpoint_uri_policy(&self) -> EndpointUriPolicy {
+		EndpointUriPolicy::new(self.allow_insecure_http)
+	}
+
+	/// Checks whether the metadata must be discovered, because no token
+	/// endpoint is configured.
+	pub fn requires_discovery(&self) -> bool {
+		self.token_endpoint.is_none()
+	}
+
+	/// Builds the authorization server metadata from the configured
+	/// endpoints only, without discovery.
+	pub fn to_metadata(&self) -> AuthorizationServerMetadata {
+		self.apply(AuthorizationServerMetadata::new(self.issuer.clone()))
+	}
+
+	/// Returns the authorization server metadata, discovering it from the
+	/// issuer if [required](Self::requires_discovery).
+	///
+	/// Fails if an endpoint is not an `https` URI, except on a loopback
+	/// address or if [`allow_insecure_http`](Self::allow_insecure_http) is
+	/// set.
+	pub async fn metadata(
+		&self,
+		http_client: &impl HttpClient,
+	) -> Result<AuthorizationServerMetadata, ConfigError> {
+		let policy = self.endpoint_uri_policy();
+		let metadata = if self.requires_discovery() {
+			let metadata = AuthorizationServerMetadata::discover_with_policy(
+				http_client,
+				self.issuer.as_uri(),
+				policy,
+			)
+			.await?;
+			self.apply(metadata)
+		} else {
+			self.to_metadata()
+		};
+
+		metadata.check_endpoint_uris(policy)?;
+		Ok(metadata)
+	}
+
+	/// Overrides the endpoints of `metadata` with the configured ones.
+	fn apply(&self, mut metadata: AuthorizationServerMetadata) -> AuthorizationServerMetadata {
+		fn set(target: &mut Option<UriBuf>, value: &Option<UriBuf>) {
+			if value.is_some() {
+				target.clone_from(value)
+			}
+		}
+
+		set(
+			&mut metadata.authorization_endpoint,
+			&self.authorization_endpoint,
+		);
+		set(&mut metadata.token_endpoint, &self.token_endpoint);
+		set(&mut metadata.revocation_endpoint, &self.revocation_endpoint);
+		set(
+			&mut metadata.introspection_endpoint,
+			&self.introspection_endpoint,
+		);
+		set(
+			&mut metadata.pushed_authorization_request_endpoint,
+			&self.pushed_authorization_request_endpoint,
+		);
+		set(
+			&mut metadata.device_authorization_endpoint,
+			&self.device_authorization_endpoint,
+		);
+		set(
+			&mut metadata.end_session_endpoint,
+			&self.end_session_endpoint,
+		);
+		metadata
+	}
+}
+
+/// Client configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientConfig {
+	/// Client identifier.
+	pub client_id: ClientIdBuf,
+
+	/// Client secret, if any.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub client_secret: Option<SecretRef>,
+
+	/// Authentication method at the token endpoint.
+	#[serde(default)]
+	pub token_endpoint_auth_method: ClientAuthMethod,
+
+	/// Scope requested by default.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub scope: Option<ScopeBuf>,
+
+	/// Registered redirection URIs.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub redirect_uris: Vec<UriBuf>,
+
+	/// Authorization server.
+	pub provider: ProviderConfig,
+}
+
+impl ClientConfig {
+	/// Parses a TOML configuration.
+	pub fn from_toml(document: &str) -> Result<Self, ConfigError> {
+		Ok(toml::from_str(document)?)
+	}
+
+	/// Parses a JSON configuration.
+	pub fn from_json(document: &str) -> Result<Self, ConfigError> {
+		Ok(serde_json::from_str(document)?)
+	}
+
+	/// Reads a configuration file, whose format is given by its `toml` or
+	/// `json` extension.
+	pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+		let path = path.as_ref();
+		match path.extension().and_then(|e| e.to_str()) {
+			Some("toml") => Self::from_toml(&std::fs::read_to_string(path)?),
+			Some("json") => Self::from_json(&std::fs::read_to_string(path)?),
+			_ => Err(ConfigError::UnsupportedFormat(path.to_owned())),
+		}
+	}
+
+	/// Reads the configuration from the environment variables starting with
+	/// `prefix`.
+	///
+	/// See [`from_vars`](Self::from_vars).
+	pub fn from_env(prefix: &str) -> Result<Self, ConfigError> {
+		Self::from_vars(prefix, std::env::vars())
+	}
+
+	/// Reads the configuration from the given variables starting with
+	/// `prefix`.
+	///
+	/// Each field is read from the upper-case variable named after it, such
+	/// as `{prefix}CLIENT_ID`, and each provider field from the variable
+	/// named after it with a `PROVIDER_` prefix, such as
+	/// `{prefix}PROVIDER_ISSUER`. Redirection URIs are separated by
+	/// whitespace. The client secret is given inline by
+	/// `{prefix}CLIENT_SECRET`.
+	pub fn from_vars(
+		prefix: &str,
+		vars: impl IntoIterator<Item = (String, String)>,
+	) -> Result<Self, ConfigError> {
+		let vars: HashMap<_, _> = vars
+			.into_iter()
+			.filter_map(|(name, value)| Some((name.strip_prefix(prefix)?.to_owned(), value)))
+			.collect();
+
+		let mut provider = serde_json::Map::new();
+		let mut client = serde_json::Map::new();
+		for (name, value) in vars {
+			let (target, name) = match name.strip_prefix("PROVIDER_") {
+				Some(name) => (&mut provider, name),
+				None => (&mut client, name.as_str()),
+			};
+
+			let name = name.to_ascii_lowercase();
+			let value = if name == "redirect_uris" {
+				serde_json::Value::Array(value.split_whitespace().map(Into::into).collect())
+			} else if name == "allow_insecure_http" {
+				value
+					.parse()
+					.map_or_else(|_| value.into(), serde_json::Value::Bool)
+			} else {
+				value.into()
+			};
+
+			target.insert(name, value);
+		}
+
+		client.insert("provider".to_owned(), provider.into());
+		Ok(serde_json::from_value(client.into())?)
+	}
+
+	/// Builds the configured client, reading its secret.
+	///
+	/// Fails if the authentication method is not supported, or is not
+	/// consistent with the presence of a client secret (see
+	/// [`DynamicClient::check_auth_method`]).
+	pub fn client(&self) -> Result<DynamicClient, ConfigError> {
+		let client = DynamicClient::new(self.client_id.clone());
+		let client = match &self.client_secret {
+			Some(secret) => client.with_secret(secret.resolve()?, self.token_endpoint_auth_method),
+			None => client.with_auth_method(self.token_endpoint_auth_method),
+		};
+
+		client.check_auth_method()?;
+		Ok(client)
+	}
+
+	/// Returns the default redirection URI, the first registered one.
+	pub fn redirect_uri(&self) -> Option<&iref::Uri> {
+		self.redirect_uris.first().map(UriBuf::as_uri)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use iref::uri;
+
+	use crate::{client::OAuth2Client, endpoints::token::TokenEndpoint, issuer, scope};
+
+	use super::*;
+
+	#[test]
+	fn toml_configuration() {
+		let config = ClientConfig::from_toml(
+			r#"
+			client_id = "s6BhdRkqt3"
+			client_secret = "7Fjfp0ZBr1KtDRbnfVdmIw"
+			token_endpoint_auth_method = "client_secret_basic"
+			scope = "read write"
+			redirect_uris = ["https://client.example.com/cb"]
+
+			[provider]
+			issuer = "https://server.example.com"
+			token_endpoint = "https://server.example.com/token"
+			"#,
+		)
+		.unwrap();
+
+		assert_eq!(config.scope.as_deref(), Some(scope!("read write")));
+		assert_eq!(
+			config.redirect_uri(),
+			Some(uri!("https://client.example.com/cb"))
+		);
+		assert!(!config.provider.requires_discovery());
+
+		let client = config.client().unwrap();
+		assert_eq!(client.client_id().as_str(), "s6BhdRkqt3");
+		assert_eq!(
+			client.client_secret.as_deref(),
+			Some("7Fjfp0ZBr1KtDRbnfVdmIw")
+		);
+		assert_eq!(client.auth_method, ClientAuthMethod::ClientSecretBasic);
+
+		let metadata = config.provider.to_metadata();
+		let endpoint = TokenEndpoint::from_metadata(&client, &metadata).unwrap();
+		assert_eq!(*endpoint.uri, *uri!("https://server.example.com/token"));
+	}
+
+	#[test]
+	fn json_configuration() {
+		let config = ClientConfig::from_json(
+			r#"{
+				"client_id": "s6BhdRkqt3",
+				"client_secret": { "env": "OPEN_AUTH2_TEST_UNSET_SECRET" },
+				"provider": { "issuer": "https://server.example.com" }
+			}"#,
+		)
+		.unwrap();
+
+		assert!(config.provider.requires_discovery());
+		assert!(matches!(
+			config.client(),
+			Err(ConfigError::MissingVariable(name)) if name == "OPEN_AUTH2_TEST_UNSET_SECRET"
+		));
+	}
+
+	#[test]
+	fn unsupported_auth_method() {
+		let config = ClientConfig::from_json(
+			r#"{
+				"client_id": "s6BhdRkqt3",
+				"token_endpoint_auth_method": "private_key_jwt",
+				"provider": { "issuer": "https://server.example.com" }
+			}"#,
+		)
+		.unwrap();
+
+		assert!(matches!(
+			config.client(),
+			Err(ConfigError::ClientAuth(ClientAuthError::Unsupported(
+				ClientAuthMethod::PrivateKeyJwt
+			)))
+		));
+	}
+
+	#[test]
+	fn env_configuration() {
+		let vars = [
+			("APP_CLIENT_ID", "s6BhdRkqt3"),
+			("APP_CLIENT_SECRET", "7Fjfp0ZBr1KtDRbnfVdmIw"),
+			("APP_TOKEN_ENDPOINT_AUTH_METHOD", "client_secret_post"),
+			(
+				"APP_REDIRECT_URIS",
+				"https://client.example.com/cb https://client.example.com/cb2",
+			),
+			("APP_PROVIDER_ISSUER", "https://server.example.com"),
+			("OTHER_CLIENT_ID", "other"),
+		];
+
+		let config = ClientConfig::from_vars(
+			"APP_",
+			vars.map(|(name, value)| (name.to_owned(), value.to_owned())),
+		)
+		.unwrap();
+
+		assert_eq!(config.client_id.as_str(), "s6BhdRkqt3");
+		assert_eq!(
+			config.client_secret,
+			Some(SecretRef::Value("7Fjfp0ZBr1KtDRbnfVdmIw".to_owned()))
+		);
+		assert_eq!(
+			config.token_endpoint_auth_method,
+			ClientAuthMethod::ClientSecretPost
+		);
+		assert_eq!(config.redirect_uris.len(), 2);
+		assert!(!format!("{config:?}").contains("7Fjfp0ZBr1KtDRbnfVdmIw"));
+		assert!(!format!("{:?}", config.client().unwrap()).contains("7Fjfp0ZBr1KtDRbnfVdmIw"));
+		assert_eq!(
+			config.provider,
+			ProviderConfig::new(issuer!("https://server.example.com").to_owned())
+		);
+	}
+
+	/// HTTP client failing every request.
+	struct Offline;
+
+	impl HttpClient for Offline {
+		async fn send(
+			&self,
+			_request: http::Request<Vec<u8>>,
+		) -> Result<http::Response<Vec<u8>>, OAuth2ClientError> {
+			Err(OAuth2ClientError::request("offline"))
+		}
+	}
+
+	#[tokio::test]
+	async fn insecure_endpoint() {
+		let mut provider = ProviderConfig::new(issuer!("https://server.example.com").to_owned());
+		provider.token_endpoint = Some(uri!("http://server.example.com/token").to_owned());
+		assert!(matches!(
+			provider.metadata(&Offline).await,
+			Err(ConfigError::InsecureEndpoint(_))
+		));
+
+		provider.token_endpoint = Some(uri!("http://127.0.0.1:8080/token").to_owned());
+		assert!(provider.metadata(&Offline).await.is_ok());
+
+		let config = ClientConfig::from_vars(
+			"APP_",
+			[
+				("APP_CLIENT_ID", "s6BhdRkqt3"),
+				("APP_PROVIDER_ISSUER", "https://server.example.com"),
+				(
+					"APP_PROVIDER_TOKEN_ENDPOINT",
+					"http://server.example.com/token",
+				),
+				("APP_PROVIDER_ALLOW_INSECURE_HTTP", "true"),
+			]
+			.map(|(name, value)| (name.to_owned(), value.to_owned())),
+		)
+		.unwrap();
+		assert!(config.provider.allow_insecure_http);
+		assert!(config.provider.metadata(&Offline).await.is_ok());
+	}
+}
@@ -0,0 +1,612 @@
+//! Conformance self-test of authorization servers.
+//!
+//! A [`ConformanceTest`] plays a scripted set of client interactions against
+//! an authorization server, using the client machinery of this crate, and
+//! checks that the server rejects the requests it must reject:
+//!
+//! - a token request with a code verifier not matching the PKCE challenge
+//!   ([RFC 7636 Section 4.6](https://datatracker.ietf.org/doc/html/rfc7636#section-4.6));
+//! - a token request replaying an authorization code
+//!   ([RFC 6749 Section 4.1.2](https://datatracker.ietf.org/doc/html/rfc6749#section-4.1.2));
+//! - a token request with another `redirect_uri` than the authorization
+//!   request
+//!   ([RFC 6749 Section 4.1.3](https://datatracker.ietf.org/doc/html/rfc6749#section-4.1.3));
+//! - an authorization request referencing an expired `request_uri`
+//!   ([RFC 9126 Section 4](https://www.rfc-editor.org/rfc/rfc9126.html#section-4)).
+//!
+//! The outcome of every check is gathered in a [`ConformanceReport`], which
+//! serializes as JSON so that it can be consumed by CI pipelines. The
+//! `conformance` binary runs the test against a remote authorization server.
+//!
+//! Authorization codes are obtained through a [`UserAgent`]. Test
+//! deployments approving the requests of the test client without user
+//! interaction can use [`AutoApprove`].
+//!
+//! Requires the `conformance` feature.
+use std::{future::Future, time::Duration};
+
+use http::header::LOCATION;
+use iref::{Uri, UriBuf, uri::Segment};
+use serde::Serialize;
+
+use crate::{
+	AddState, CodeBuf, IssuerBuf, ScopeBuf, StateBuf, Stateful,
+	client::{OAuth2Client, OAuth2ClientError, parse_callback},
+	endpoints::{
+		RequestBuilder,
+		authorization::{AnyAuthorizationEndpoint, AuthorizationEndpoint},
+		pushed_authorization::PushedAuthorizationEndpoint,
+		token::TokenEndpoint,
+	},
+	ext::pkce::{
+		AddPkceChallenge, AddPkceVerifier, PkceCodeChallengeAndMethod, PkceCodeVerifier,
+		PkceCodeVerifierBuf,
+	},
+	grant::authorization_code::AuthorizationCodeAuthorizationResponse,
+	server::{AuthorizationServerMetadata, ServerResult},
+	transport::HttpClient,
+};
+
+/// Check performed by a [`ConformanceTest`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Check {
+	/// Authorization codes are not exchanged with a code verifier that does
+	/// not match their PKCE challenge.
+	InvalidCodeVerifier,
+
+	/// Authorization codes are not exchanged twice.
+	ReplayedCode,
+
+	/// Authorization codes are not exchanged with another redirection URI
+	/// than the one of the authorization request.
+	RedirectUriMismatch,
+
+	/// Expired pushed authorization requests are not processed.
+	ExpiredRequestUri,
+}
+
+impl Check {
+	/// All the checks, in the order they are performed.
+	pub const ALL: [Self; 4] = [
+		Self::InvalidCodeVerifier,
+		Self::ReplayedCode,
+		Self::RedirectUriMismatch,
+		Self::ExpiredRequestUri,
+	];
+}
+
+/// Outcome of a [`Check`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "outcome", content = "reason", rename_all = "snake_case")]
+pub enum Outcome {
+	/// The server behaved as expected.
+	Passed,
+
+	/// The server did not behave as expected, or the check could not be
+	/// completed.
+	Failed(String),
+
+	/// The check does not apply to the server (e.g. it has no PAR endpoint).
+	Skipped(String),
+}
+
+impl Outcome {
+	/// Checks whether this outcome is a failure.
+	pub fn is_failed(&self) -> bool {
+		matches!(self, Self::Failed(_))
+	}
+}
+
+/// Outcome of a [`Check`], as listed in a [`ConformanceReport`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CheckResult {
+	/// The check.
+	pub check: Check,
+
+	/// Its outcome.
+	#[serde(flatten)]
+	pub outcome: Outcome,
+}
+
+/// Outcomes of all the checks performed against an authorization server.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConformanceReport {
+	/// Issuer identifier of the authorization server.
+	pub issuer: IssuerBuf,
+
+	/// Outcome of each check.
+	pub results: Vec<CheckResult>,
+}
+
+impl ConformanceReport {
+	/// Checks whether no check failed.
+	pub fn passed(&self) -> bool {
+		!self.results.iter().any(|result| result.outcome.is_failed())
+	}
+}
+
+/// User-agent completing authorization requests on behalf of the resource
+/// owner.
+pub trait UserAgent {
+	/// Visits the given authorization request URI, returning the URI the
+	/// user-agent is redirected back to, or `None` if the authorization
+	/// server did not redirect it.
+	fn authorize(
+		&self,
+		uri: &Uri,
+	) -> impl Future<Output = Result<Option<UriBuf>, OAuth2ClientError>>;
+}
+
+/// User-agent following the authorization request URI with an HTTP client,
+/// for authorization servers redirecting back to the client without user
+/// interaction.
+///
+/// The HTTP client must not follow redirects itself. For instance, a
+/// [`reqwest::Client`] must be built with
+/// `redirect::Policy::none()`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AutoApprove<H>(pub H);
+
+impl<H: HttpClient> UserAgent for AutoApprove<H> {
+	async fn authorize(&self, uri: &Uri) -> Result<Option<UriBuf>, OAuth2ClientError> {
+		let request = http::Request::get(uri.as_str())
+			.body(Vec::new())
+			.map_err(OAuth2ClientError::request)?;
+		let response = self.0.send(request).await?;
+
+		if !response.status().is_redirection() {
+			log::debug!("authorization request answered with {}", response.status());
+			return Ok(None);
+		}
+
+		let location = response
+			.headers()
+			.get(LOCATION)
+			.and_then(|value| value.to_str().ok())
+			.ok_or_else(|| OAuth2ClientError::response("redirect without location"))?;
+
+		UriBuf::new(location.as_bytes().to_vec())
+			.map(Some)
+			.map_err(|_| OAuth2ClientError::response("invalid redirect location"))
+	}
+}
+
+/// Conformance test of an authorization server.
+///
+/// The test client must be registered by the authorization server with the
+/// given redirection URI, and allowed to use the authorization code grant
+/// without client authentication.
+pub struct ConformanceTest<C, H, U> {
+	client: C,
+	metadata: AuthorizationServerMetadata,
+	redirect_uri: UriBuf,
+	scope: Option<ScopeBuf>,
+	http_client: H,
+	user_agent: U,
+}
+
+impl<C, H, U> ConformanceTest<C, H, U> {
+	/// Creates a new conformance test of the authorization server described
+	/// by `metadata`, for the given client.
+	pub fn new(
+		client: C,
+		metadata: AuthorizationServerMetadata,
+		redirect_uri: UriBuf,
+		http_client: H,
+		user_agent: U,
+	) -> Self {
+		Self {
+			client,
+			metadata,
+			redirect_uri,
+			scope: None,
+			http_client,
+			user_agent,
+		}
+	}
+
+	/// Sets the scope of the authorization requests.
+	pub fn with_scope(self, scope: Option<ScopeBuf>) -> Self {
+		Self { scope, ..self }
+	}
+}
+
+impl<C, H, U> ConformanceTest<C, H, U>
+where
+	C: OAuth2Client,
+	H: HttpClient,
+	U: UserAgent,
+{
+	/// Performs all the checks.
+	pub async fn run(&self) -> ConformanceReport {
+		let mut results = Vec::with_capacity(Check::ALL.len());
+
+		for check in Check::ALL {
+			let outcome = self.check(check).await;
+			log::info!("{check:?}: {outcome:?}");
+			results.push(CheckResult { check, outcome });
+		}
+
+		ConformanceReport {
+			issuer: self.metadata.issuer.clone(),
+			results,
+		}
+	}
+
+	/// Performs the given check.
+	pub async fn check(&self, check: Check) -> Outcome {
+		let outcome = match check {
+			Check::InvalidCodeVerifier => self.invalid_code_verifier().await,
+			Check::ReplayedCode => self.replayed_code().await,
+			Check::RedirectUriMismatch => self.redirect_uri_mismatch().await,
+			Check::ExpiredRequestUri => self.expired_request_uri().await,
+		};
+
+		outcome.unwrap_or_else(Outcome::Failed)
+	}
+
+	async fn invalid_code_verifier(&self) -> Result<Outcome, String> {
+		let (code, _) = self.authorization_code().await?;
+		let (_, other_verifier) = PkceCodeChallengeAndMethod::new_random_sha256();
+
+		let result = self
+			.exchange(code, self.redirect_uri.clone(), &other_verifier)
+			.await?;
+		Ok(expect_invalid_grant(result, "an invalid code verifier"))
+	}
+
+	async fn replayed_code(&self) -> Result<Outcome, String> {
+		let (code, verifier) = self.authorization_code().await?;
+
+		self.exchange(code.clone(), self.redirect_uri.clone(), &verifier)
+			.await?
+			.map_err(|e| format!("unable to exchange the authorization code: {e}"))?;
+
+		let result = self
+			.exchange(code, self.redirect_uri.clone(), &verifier)
+			.await?;
+		Ok(expect_invalid_grant(
+			result,
+			"a replayed authorization code",
+		))
+	}
+
+	async fn redirect_uri_mismatch(&self) -> Result<Outcome, String> {
+		let (code, verifier) = self.authorization_code().await?;
+
+		let mut other_redirect_uri = self.redirect_uri.clone();
+		// UNWRAP SAFETY: the segment is made of unreserved characters.
+		other_redirect_uri
+			.path_mut()
+			.push(Segment::new("conformance").unwrap());
+
+		let result = self.exchange(code, other_redirect_uri, &verifier).await?;
+		Ok(expect_invalid_grant(result, "another redirect URI"))
+	}
+
+	async fn expired_request_uri(&self) -> Result<Outcome, String> {
+		let Ok(par_endpoint) =
+			PushedAuthorizationEndpoint::from_metadata(&self.client, &self.metadata)
+		else {
+			return Ok(Outcome::Skipped(
+				"no pushed authorization request endpoint".to_owned(),
+			));
+		};
+
+		let endpoint = AuthorizationEndpoint::from_metadata(&self.client, &self.metadata)
+			.map_err(|e| e.to_string())?;
+		let (pkce, _) = PkceCodeChallengeAndMethod::new_random_sha256();
+		let state = StateBuf::new_random();
+		let request = endpoint
+			.authorize_url(Some(self.redirect_uri.clone()), self.scope.clone())
+			.with_state(Some(state.clone()))
+			.with_pkce_challenge(pkce)
+			.request;
+
+		let response = RequestBuilder::new(
+			par_endpoint,
+			PushedAuthorizationEndpoint::<C>::build_authorization_request(request),
+		)
+		.send(&self.http_client)
+		.await
+		.map_err(|e| format!("unable to push the authorization request: {e}"))?;
+
+		tokio::time::sleep(response.expires_in() + Duration::from_secs(1)).await;
+
+		let uri = response
+			.authorize_url(endpoint)
+			.into_pushed_redirect_uri()
+			.map_err(|e| e.to_string())?;
+		match self.follow(&uri, &state).await? {
+			Some(_) => Ok(Outcome::Failed(
+				"authorization code issued for an expired request URI".to_owned(),
+			)),
+			None => Ok(Outcome::Passed),
+		}
+	}
+
+	/// Obtains an authorization code bound to a PKCE challenge, returning it
+	/// along with the code verifier.
+	async fn authorization_code(&self) -> Result<(CodeBuf, PkceCodeVerifierBuf), String> {
+		let endpoint = AuthorizationEndpoint::from_metadata(&self.client, &self.metadata)
+			.map_err(|e| e.to_string())?;
+		let (pkce, verifier) = PkceCodeChallengeAndMethod::new_random_sha256();
+		let state = StateBuf::new_random();
+
+		let uri = endpoint
+			.authorize_url(Some(self.redirect_uri.clone()), self.scope.clone())
+			.with_state(Some(state.clone()))
+			.with_pkce_challenge(pkce)
+			.into_redirect_uri_or_pushed(&self.metadata, &self.http_client)
+			.await
+			.map_err(|e| e.to_string())?;
+
+		let code = self
+			.follow(&uri, &state)
+			.await?
+			.ok_or("authorization request rejected")?;
+		Ok((code, verifier))
+	}
+
+	/// Follows the given authorization request URI with the user-agent,
+	/// returning the issued authorization code, or `None` if the request was
+	/// rejected.
+	async fn follow(&self, uri: &Uri, state: &StateBuf) -> Result<Option<CodeBuf>, String> {
+		let Some(redirect_uri) = self
+			.user_agent
+			.authorize(uri)
+			.await
+			.map_err(|e| e.to_string())?
+		else {
+			return Ok(None);
+		};
+
+		let response: Stateful<ServerResult<AuthorizationCodeAuthorizationResponse>> =
+			parse_callback(&self.redirect_uri, &redirect_uri, Some(state))
+				.map_err(|e| e.to_string())?;
+
+		match response.value {
+			ServerResult::Ok(response) => Ok(Some(response.code)),
+			ServerResult::Err(error) => {
+				log::debug!("authorization request rejected with `{}`", error.error);
+				Ok(None)
+			}
+		}
+	}
+
+	/// Exchanges an authorization code at the token endpoint.
+	///
+	/// The outer error reports that the exchange could not be attempted, the
+	/// inner result is the response of the server.
+	async fn exchange(
+		&self,
+		code: CodeBuf,
+		redirect_uri: UriBuf,
+		verifier: &PkceCodeVerifier,
+	) -> Result<Result<(), OAuth2ClientError>, String> {
+		let endpoint = TokenEndpoint::from_metadata(&self.client, &self.metadata)
+			.map_err(|e| e.to_string())?;
+
+		Ok(endpoint
+			.exchange_code(code, Some(redirect_uri))
+			.with_pkce_verifier(verifier)
+			.send(&self.http_client)
+			.await
+			.map(|_| ()))
+	}
+}
+
+/// Expects the given token request to be rejected with an `invalid_grant`
+/// error.
+fn expect_invalid_grant(result: Result<(), OAuth2ClientError>, accepted: &str) -> Outcome {
+	match result {
+		Ok(()) => Outcome::Failed(format!("token issued for {accepted}")),
+		Err(e) if e.error_code() == Some("invalid_grant") => Outcome::Passed,
+		Err(e) => Outcome::Failed(format!("expected an `invalid_grant` error: {e}")),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use http::StatusCode;
+	use iref::uri;
+	use serde::Deserialize;
+
+	use crate::{
+		ClientId, ClientIdBuf, client_id,
+		endpoints::token::TokenResponse,
+		grant::authorization_code::AuthorizationCodeTokenRequest,
+		issuer,
+		server::{
+			code::{AuthorizationCodeStore, CodeBinding, CodeExchange},
+			par::PushedRequestStore,
+		},
+		transport::APPLICATION_JSON,
+	};
+
+	use super::*;
+
+	struct Client;
+
+	impl OAuth2Client for Client {
+		type TokenResponse = TokenResponse;
+
+		fn client_id(&self) -> &ClientId {
+			client_id!("s6BhdRkqt3")
+		}
+	}
+
+	#[derive(Deserialize)]
+	struct PushedQuery {
+		client_id: ClientIdBuf,
+		request_uri: UriBuf,
+	}
+
+	#[derive(Deserialize)]
+	struct AuthorizeQuery {
+		client_id: ClientIdBuf,
+		redirect_uri: UriBuf,
+		state: StateBuf,
+		#[serde(flatten)]
+		pkce: PkceCodeChallengeAndMethod,
+	}
+
+	#[derive(Deserialize)]
+	struct TokenForm {
+		#[serde(flatten)]
+		request: AuthorizationCodeTokenRequest,
+		code_verifier: Option<PkceCodeVerifierBuf>,
+	}
+
+	/// Conformant authorization server, approving all the authorization
+	/// requests, and expiring pushed requests immediately.
+	struct Server {
+		codes: AuthorizationCodeStore<()>,
+		pushed: PushedRequestStore<String>,
+	}
+
+	impl Server {
+		fn new() -> Self {
+			Self {
+				codes: AuthorizationCodeStore::new(),
+				pushed: PushedRequestStore::new().with_lifetime(Duration::ZERO),
+			}
+		}
+
+		fn authorize(&self, query: &str) -> http::Response<Vec<u8>> {
+			let query = match serde_html_form::from_str::<PushedQuery>(query) {
+				Ok(pushed) => match self.pushed.take(&pushed.request_uri, &pushed.client_id) {
+					Some(query) => query,
+					None => return json(StatusCode::BAD_REQUEST, r#"{"error":"invalid_request"}"#),
+				},
+				Err(_) => query.to_owned(),
+			};
+
+			let request: AuthorizeQuery = serde_html_form::from_str(&query).unwrap();
+			let binding = CodeBinding::new(request.client_id)
+				.with_redirect_uri(Some(request.redirect_uri.clone()))
+				.with_pkce(Some(request.pkce));
+			let code = self.codes.issue(binding, ());
+
+			http::Response::builder()
+				.status(StatusCode::FOUND)
+				.header(
+					LOCATION,
+					format!(
+						"{}?code={code}&state={}",
+						request.redirect_uri, request.state
+					),
+				)
+				.body(Vec::new())
+				.unwrap()
+		}
+
+		fn token(&self, body: &[u8]) -> http::Response<Vec<u8>> {
+			let form: TokenForm = serde_html_form::from_bytes(body).unwrap();
+			let client_id = form.request.client_id.as_deref().unwrap();
+			let exchange = CodeExchange::new(client_id, &form.request)
+				.with_code_verifier(form.code_verifier.as_deref());
+
+			match self.codes.redeem(exchange) {
+				Ok(_) => json(
+					StatusCode::OK,
+					r#"{"access_token":"2YotnFZFEjr1zCsicMWpAA","token_type":"Bearer"}"#,
+				),
+				Err(_) => json(StatusCode::BAD_REQUEST, r#"{"error":"invalid_grant"}"#),
+			}
+		}
+
+		fn par(&self, body: &[u8]) -> http::Response<Vec<u8>> {
+			let body = String::from_utf8(body.to_vec()).unwrap();
+			let request: AuthorizeQuery = serde_html_form::from_str(&body).unwrap();
+			let response = self.pushed.push(&request.client_id, body);
+			json(
+				StatusCode::CREATED,
+				&serde_json::to_string(&response).unwrap(),
+			)
+		}
+	}
+
+	fn json(status: StatusCode, body: &str) -> http::Response<Vec<u8>> {
+		http::Response::builder()
+			.status(status)
+			.header(http::header::CONTENT_TYPE, APPLICATION_JSON)
+			.body(body.as_bytes().to_vec())
+			.unwrap()
+	}
+
+	impl HttpClient for Server {
+		async fn send(
+			&self,
+			request: http::Request<Vec<u8>>,
+		) -> Result<http::Response<Vec<u8>>, OAuth2ClientError> {
+			Ok(match request.uri().path() {
+				"/authorize" => self.authorize(request.uri().query().unwrap_or_default()),
+				"/token" => self.token(request.body()),
+				"/par" => self.par(request.body()),
+				path => panic!("unexpected request to {path}"),
+			})
+		}
+	}
+
+	fn metadata() -> AuthorizationServerMetadata {
+		AuthorizationServerMetadata::new(issuer!("https://server.example.com").to_owned())
+			.with_authorization_endpoint(uri!("https://server.example.com/authorize").to_owned())
+			.with_token_endpoint(uri!("https://server.example.com/token").to_owned())
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn conformant_server() {
+		let server = Server::new();
+		let test = ConformanceTest::new(
+			Client,
+			metadata().with_pushed_authorization_request_endpoint(
+				uri!("https://server.example.com/par").to_owned(),
+			),
+			uri!("https://client.example.com/cb").to_owned(),
+			&server,
+			AutoApprove(&server),
+		);
+
+		let report = test.run().await;
+		assert!(report.passed());
+		assert!(
+			report
+				.results
+				.iter()
+				.all(|result| result.outcome == Outcome::Passed)
+		);
+		assert_eq!(
+			serde_json::to_value(&report.results[0]).unwrap(),
+			serde_json::json!({ "check": "invalid_code_verifier", "outcome": "passed" })
+		);
+	}
+
+	#[tokio::test]
+	async fn skipped_check() {
+		let server = Server::new();
+		let test = ConformanceTest::new(
+			Client,
+			metadata(),
+			uri!("https://client.example.com/cb").to_owned(),
+			&server,
+			AutoApprove(&server),
+		);
+
+		let outcome = test.check(Check::ExpiredRequestUri).await;
+		assert!(matches!(outcome, Outcome::Skipped(_)));
+		assert_eq!(
+			serde_json::to_value(CheckResult {
+				check: Check::ExpiredRequestUri,
+				outcome
+			})
+			.unwrap(),
+			serde_json::json!({
+				"check": "expired_request_uri",
+				"outcome": "skipped",
+				"reason": "no pushed authorization request endpoint"
+			})
+		);
+	}
+}
@@ -5,7 +5,11 @@
 use iref::Uri;
 use serde::{Deserialize, Serialize};
 
-use crate::{client::OAuth2Client, endpoints::Endpoint};
+use crate::{
+	client::OAuth2Client,
+	endpoints::Endpoint,
+	server::metadata::{AuthorizationServerMetadata, MissingEndpoint},
+};
 
 /// The OAuth 2.0 authorization endpoint.
 ///
@@ -25,6 +29,35 @@ impl<'a, C> AuthorizationEndpoint<'a, C> {
 	pub fn new(client: &'a C, uri: &'a Uri) -> Self {
 		Self { client, uri }
 	}
+
+	/// Creates a new authorization endpoint for the given client and URL.
+	///
+	/// Fails if the URL is not a valid URI.
+	#[cfg(feature = "url")]
+	pub fn from_url(
+		client: &'a C,
+		url: &'a url::Url,
+	) -> Result<Self, crate::util::UrlConversionError> {
+		use crate::util::UrlExt;
+		Ok(Self::new(client, url.as_uri()?))
+	}
+
+	/// Creates a new authorization endpoint for the given client, using the
+	/// `authorization_endpoint` advertised by the authorization server
+	/// metadata.
+	///
+	/// Fails if the authorization server does not advertise an authorization
+	/// endpoint.
+	pub fn from_metadata<P>(
+		client: &'a C,
+		metadata: &'a AuthorizationServerMetadata<P>,
+	) -> Result<Self, MissingEndpoint> {
+		metadata
+			.authorization_endpoint
+			.as_deref()
+			.map(|uri| Self::new(client, uri))
+			.ok_or(MissingEndpoint("authorization_endpoint"))
+	}
 }
 
 impl<'a, C> Clone for AuthorizationEndpoint<'a, C> {
@@ -74,6 +107,7 @@ where
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum AuthorizationErrorCode {
 	/// The request is missing a required parameter, includes an invalid parameter value,
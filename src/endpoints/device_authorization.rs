@@ -0,0 +1,284 @@
+//! [RFC 8628]: OAuth 2.0 Device Authorization Grant.
+//!
+//! [RFC 8628]: <https://datatracker.ietf.org/doc/html/rfc8628>
+use std::time::Duration;
+
+use http::StatusCode;
+use iref::{Uri, UriBuf};
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+use crate::{
+	ClientIdBuf, IntoScope, ScopeBuf,
+	client::{OAuth2Client, OAuth2ClientError},
+	endpoints::{Endpoint, HttpRequest, RequestBuilder},
+	grant::polling::{PollingConfig, PollingState},
+	server::metadata::{AuthorizationServerMetadata, MissingEndpoint},
+	time::Clock,
+	transport::{HttpClient, ResponseDecoder, WwwFormUrlEncoded},
+};
+
+/// The OAuth 2.0 Device Authorization endpoint.
+///
+/// This endpoint allows input-constrained devices to obtain a device code
+/// and a user code, the latter being entered by the end user on a secondary
+/// device to authorize the request.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc8628#section-3.1>
+pub struct DeviceAuthorizationEndpoint<'a, C> {
+	/// The OAuth 2.0 client.
+	pub client: &'a C,
+
+	/// The device authorization endpoint URI.
+	pub uri: &'a Uri,
+}
+
+impl<'a, C> DeviceAuthorizationEndpoint<'a, C> {
+	/// Creates a new device authorization endpoint for the given client and
+	/// URI.
+	pub fn new(client: &'a C, uri: &'a Uri) -> Self {
+		Self { client, uri }
+	}
+
+	/// Creates a new device authorization endpoint for the given client and URL.
+	///
+	/// Fails if the URL is not a valid URI.
+	#[cfg(feature = "url")]
+	pub fn from_url(
+		client: &'a C,
+		url: &'a url::Url,
+	) -> Result<Self, crate::util::UrlConversionError> {
+		use crate::util::UrlExt;
+		Ok(Self::new(client, url.as_uri()?))
+	}
+
+	/// Creates a new device authorization endpoint for the given client,
+	/// using the `device_authorization_endpoint` advertised by the
+	/// authorization server metadata.
+	///
+	/// Fails if the authorization server does not advertise a device
+	/// authorization endpoint.
+	pub fn from_metadata<P>(
+		client: &'a C,
+		metadata: &'a AuthorizationServerMetadata<P>,
+	) -> Result<Self, MissingEndpoint> {
+		metadata
+			.device_authorization_endpoint
+			.as_deref()
+			.map(|uri| Self::new(client, uri))
+			.ok_or(MissingEndpoint("device_authorization_endpoint"))
+	}
+}
+
+impl<'a, C> DeviceAuthorizationEndpoint<'a, C>
+where
+	C: OAuth2Client,
+{
+	/// Begins a Device Authorization Request.
+	///
+	/// Returns a [`RequestBuilder`] that can be further extended before
+	/// being sent.
+	pub fn authorize_device(
+		self,
+		scope: impl IntoScope,
+	) -> RequestBuilder<Self, DeviceAuthorizationRequest> {
+		RequestBuilder::new(
+			self,
+			DeviceAuthorizationRequest::new(self.client.client_id().to_owned(), scope),
+		)
+	}
+}
+
+impl<'a, C> Clone for DeviceAuthorizationEndpoint<'a, C> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+
+impl<'a, C> Copy for DeviceAuthorizationEndpoint<'a, C> {}
+
+impl<'a, C> Endpoint for DeviceAuthorizationEndpoint<'a, C>
+where
+	C: OAuth2Client,
+{
+	type Client = C;
+
+	fn client(&self) -> &Self::Client {
+		self.client
+	}
+
+	fn uri(&self) -> &Uri {
+		self.uri
+	}
+}
+
+/// Device Authorization Request.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc8628#section-3.1>
+#[skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DeviceAuthorizationRequest {
+	/// Client identifier.
+	pub client_id: ClientIdBuf,
+
+	/// Scope of the access request.
+	pub scope: Option<ScopeBuf>,
+}
+
+impl DeviceAuthorizationRequest {
+	/// Creates a new device authorization request.
+	pub fn new(client_id: ClientIdBuf, scope: impl IntoScope) -> Self {
+		Self {
+			client_id,
+			scope: scope.into_scope(),
+		}
+	}
+}
+
+impl<'a, C> HttpRequest<DeviceAuthorizationEndpoint<'a, C>> for DeviceAuthorizationRequest
+where
+	C: OAuth2Client,
+{
+	type ContentType = WwwFormUrlEncoded;
+	type RequestBody<'b>
+		= &'b Self
+	where
+		Self: 'b;
+	type ResponsePayload = DeviceAuthorizationResponse;
+	type Response = DeviceAuthorizationResponse;
+
+	async fn build_request(
+		&self,
+		endpoint: &DeviceAuthorizationEndpoint<'a, C>,
+		_http_client: &impl HttpClient,
+	) -> Result<http::Request<Self::RequestBody<'_>>, OAuth2ClientError> {
+		Ok(http::Request::builder()
+			.method(http::Method::POST)
+			.uri(endpoint.uri.as_str())
+			.body(self)
+			.unwrap())
+	}
+
+	fn decode_response(
+		&self,
+		_endpoint: &DeviceAuthorizationEndpoint<'a, C>,
+		response: http::Response<Vec<u8>>,
+	) -> Result<http::Response<Self::ResponsePayload>, OAuth2ClientError> {
+		ResponseDecoder::json(StatusCode::OK)
+			.with_error_responses()
+			.decode(response)
+	}
+
+	async fn process_response(
+		&self,
+		_endpoint: &DeviceAuthorizationEndpoint<'a, C>,
+		_http_client: &impl HttpClient,
+		response: http::Response<Self::ResponsePayload>,
+	) -> Result<Self::Response, OAuth2ClientError> {
+		Ok(response.into_body())
+	}
+}
+
+/// Successful response from the device authorization endpoint.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc8628#section-3.2>
+#[skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DeviceAuthorizationResponse {
+	/// The device verification code, exchanged at the token endpoint.
+	pub device_code: String,
+
+	/// The end-user verification code.
+	pub user_code: String,
+
+	/// The end-user verification URI on the authorization server.
+	#[cfg_attr(feature = "schemars", schemars(with = "String"))]
+	pub verification_uri: UriBuf,
+
+	/// A verification URI that includes the `user_code`, designed for
+	/// non-textual transmission (e.g. QR codes).
+	#[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
+	pub verification_uri_complete: Option<UriBuf>,
+
+	/// Lifetime in seconds of the `device_code` and `user_code`.
+	pub expires_in: u64,
+
+	/// Minimum amount of time in seconds that the client should wait
+	/// between polling requests to the token endpoint.
+	pub interval: Option<u64>,
+}
+
+impl DeviceAuthorizationResponse {
+	/// Default polling interval, in seconds, when the authorization server
+	/// does not specify one.
+	///
+	/// See: <https://datatracker.ietf.org/doc/html/rfc8628#section-3.2>
+	pub const DEFAULT_INTERVAL: u64 = 5;
+
+	/// Creates a new response with the required fields.
+	///
+	/// Optional fields (`verification_uri_complete`, `interval`) default to
+	/// `None`. Durations are serialized in whole seconds, sub-second
+	/// precision is discarded.
+	pub fn new(
+		device_code: String,
+		user_code: String,
+		verification_uri: UriBuf,
+		expires_in: Duration,
+	) -> Self {
+		Self {
+			device_code,
+			user_code,
+			verification_uri,
+			verification_uri_complete: None,
+			expires_in: expires_in.as_secs(),
+			interval: None,
+		}
+	}
+
+	/// Sets the minimum polling interval.
+	pub fn with_interval(self, interval: Duration) -> Self {
+		Self {
+			interval: Some(interval.as_secs()),
+			..self
+		}
+	}
+
+	/// Returns the lifetime of the `device_code` and `user_code`.
+	pub fn expires_in(&self) -> Duration {
+		Duration::from_secs(self.expires_in)
+	}
+
+	/// Returns the polling interval, defaulting to
+	/// [`Self::DEFAULT_INTERVAL`] seconds.
+	pub fn interval(&self) -> Duration {
+		Duration::from_secs(self.interval.unwrap_or(Self::DEFAULT_INTERVAL))
+	}
+
+	/// Starts polling the token endpoint for the result of this device
+	/// authorization, following the given configuration.
+	pub fn polling(&self, config: &PollingConfig, clock: &impl Clock) -> PollingState {
+		config.start(
+			self.interval.map(Duration::from_secs),
+			self.expires_in(),
+			clock,
+		)
+	}
+}
+
+#[cfg(feature = "axum")]
+mod axum {
+	use ::axum::response::{IntoResponse, Response};
+
+	use crate::server::SecureJson;
+
+	use super::*;
+
+	impl IntoResponse for DeviceAuthorizationResponse {
+		fn into_response(self) -> Response {
+			SecureJson(self).into_response()
+		}
+	}
+}
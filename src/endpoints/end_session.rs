@@ -0,0 +1,281 @@
+//! OpenID Connect end session endpoint.
+//!
+//! See: <https://openid.net/specs/openid-connect-rpinitiated-1_0.html>
+//!
+//! Relying parties log the end-user out of the OpenID provider by
+//! redirecting them to the end session endpoint with a [`LogoutRequest`],
+//! built with [`EndSessionEndpoint::logout`]. The provider then redirects
+//! the user-agent back to the `post_logout_redirect_uri`, where the
+//! callback is checked with [`parse_logout_callback`].
+use iref::{Uri, UriBuf};
+use serde::{Deserialize, Serialize, de::IgnoredAny};
+use serde_with::skip_serializing_none;
+
+use crate::{
+	ClientIdBuf, State,
+	client::{CallbackError, OAuth2Client, parse_callback},
+	endpoints::{Endpoint, RedirectRequest, RequestBuilder},
+	server::metadata::{AuthorizationServerMetadata, MissingEndpoint},
+};
+
+/// The OpenID Connect end session endpoint.
+///
+/// See: <https://openid.net/specs/openid-connect-rpinitiated-1_0.html#RPLogout>
+pub struct EndSessionEndpoint<'a, C> {
+	/// The OAuth 2.0 client.
+	pub client: &'a C,
+
+	/// The end session endpoint URI.
+	pub uri: &'a Uri,
+}
+
+impl<'a, C> EndSessionEndpoint<'a, C> {
+	/// Creates a new end session endpoint for the given client and URI.
+	pub fn new(client: &'a C, uri: &'a Uri) -> Self {
+		Self { client, uri }
+	}
+
+	/// Creates a new end session endpoint for the given client, using the
+	/// `end_session_endpoint` advertised by the OpenID provider metadata.
+	///
+	/// Fails if the OpenID provider does not advertise an end session
+	/// endpoint.
+	pub fn from_metadata<P>(
+		client: &'a C,
+		metadata: &'a AuthorizationServerMetadata<P>,
+	) -> Result<Self, MissingEndpoint> {
+		metadata
+			.end_session_endpoint
+			.as_deref()
+			.map(|uri| Self::new(client, uri))
+			.ok_or(MissingEndpoint("end_session_endpoint"))
+	}
+}
+
+impl<'a, C> Clone for EndSessionEndpoint<'a, C> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+
+impl<'a, C> Copy for EndSessionEndpoint<'a, C> {}
+
+impl<'a, C> Endpoint for EndSessionEndpoint<'a, C>
+where
+	C: OAuth2Client,
+{
+	type Client = C;
+
+	fn client(&self) -> &Self::Client {
+		self.client
+	}
+
+	fn uri(&self) -> &Uri {
+		self.uri
+	}
+}
+
+impl<'a, C> EndSessionEndpoint<'a, C>
+where
+	C: OAuth2Client,
+{
+	/// Begins a logout request.
+	///
+	/// The `id_token_hint` is the ID token previously issued to the client,
+	/// identifying the session to end. The end-user is redirected to the
+	/// `post_logout_redirect_uri` after logging out, if it is registered by
+	/// the client.
+	///
+	/// Use [`with_state`](crate::AddState::with_state) to maintain state
+	/// between the logout request and the callback.
+	pub fn logout(
+		self,
+		id_token_hint: Option<String>,
+		post_logout_redirect_uri: Option<UriBuf>,
+	) -> RequestBuilder<Self, LogoutRequest> {
+		RequestBuilder::new(
+			self,
+			LogoutRequest::new(
+				self.client.client_id().to_owned(),
+				id_token_hint,
+				post_logout_redirect_uri,
+			),
+		)
+	}
+}
+
+/// RP-Initiated Logout request.
+///
+/// See: <https://openid.net/specs/openid-connect-rpinitiated-1_0.html#RPLogout>
+#[skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogoutRequest {
+	/// ID token previously issued to the client, as a hint about the
+	/// end-user's session.
+	pub id_token_hint: Option<String>,
+
+	/// Hint about the end-user that is logging out, such as their login
+	/// identifier.
+	pub logout_hint: Option<String>,
+
+	/// The client identifier, required by the OpenID provider to validate
+	/// the `post_logout_redirect_uri` in the absence of `id_token_hint`.
+	pub client_id: Option<ClientIdBuf>,
+
+	/// URI to which the user-agent is redirected after logout.
+	pub post_logout_redirect_uri: Option<UriBuf>,
+
+	/// Preferred languages for the user interface, as a space-separated list
+	/// of BCP 47 language tags.
+	pub ui_locales: Option<String>,
+}
+
+impl LogoutRequest {
+	/// Creates a new logout request.
+	pub fn new(
+		client_id: ClientIdBuf,
+		id_token_hint: Option<String>,
+		post_logout_redirect_uri: Option<UriBuf>,
+	) -> Self {
+		Self {
+			id_token_hint,
+			logout_hint: None,
+			client_id: Some(client_id),
+			post_logout_redirect_uri,
+			ui_locales: None,
+		}
+	}
+
+	/// Sets the hint about the end-user that is logging out.
+	pub fn with_logout_hint(self, logout_hint: String) -> Self {
+		Self {
+			logout_hint: Some(logout_hint),
+			..self
+		}
+	}
+
+	/// Sets the preferred languages for the user interface.
+	pub fn with_ui_locales(self, ui_locales: String) -> Self {
+		Self {
+			ui_locales: Some(ui_locales),
+			..self
+		}
+	}
+}
+
+impl RedirectRequest for LogoutRequest {
+	type RequestBody<'b>
+		= &'b Self
+	where
+		Self: 'b;
+
+	fn build_query(&self) -> Self::RequestBody<'_> {
+		self
+	}
+}
+
+/// Checks the redirection of the user-agent to the
+/// `post_logout_redirect_uri` after logout, at the given `callback` URI.
+///
+/// The callback must be delivered to the `post_logout_redirect_uri` of the
+/// logout request (see [`parse_callback`]), with the `state` of the logout
+/// request, if any.
+pub fn parse_logout_callback(
+	post_logout_redirect_uri: &Uri,
+	callback: &Uri,
+	state: Option<&State>,
+) -> Result<(), CallbackError> {
+	parse_callback::<IgnoredAny>(post_logout_redirect_uri, callback, state)?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use iref::uri;
+
+	use crate::{AddState, ClientId, client_id, endpoints::token::TokenResponse, state};
+
+	use super::*;
+
+	struct TestClient;
+
+	impl OAuth2Client for TestClient {
+		type TokenResponse = TokenResponse;
+
+		fn client_id(&self) -> &ClientId {
+			client_id!("s6BhdRkqt3")
+		}
+	}
+
+	#[test]
+	fn logout_request() {
+		let metadata: AuthorizationServerMetadata = serde_json::from_value(serde_json::json!({
+			"issuer": "https://server.example.com",
+			"end_session_endpoint": "https://server.example.com/logout"
+		}))
+		.unwrap();
+		let endpoint = EndSessionEndpoint::from_metadata(&TestClient, &metadata).unwrap();
+
+		let uri = endpoint
+			.logout(
+				Some("eyJhbGciOiJSUzI1NiJ9.e30.c2ln".to_owned()),
+				Some(uri!("https://client.example.org/logged-out").to_owned()),
+			)
+			.with_state(Some(state!("af0ifjsldkj").to_owned()))
+			.into_redirect_uri()
+			.unwrap();
+
+		assert_eq!(
+			uri,
+			"https://server.example.com/logout?state=af0ifjsldkj&id_token_hint=eyJhbGciOiJSUzI1NiJ9.e30.c2ln&client_id=s6BhdRkqt3&post_logout_redirect_uri=https%3A%2F%2Fclient.example.org%2Flogged-out"
+		);
+
+		let metadata: AuthorizationServerMetadata = serde_json::from_value(serde_json::json!({
+			"issuer": "https://server.example.com"
+		}))
+		.unwrap();
+		assert_eq!(
+			EndSessionEndpoint::from_metadata(&TestClient, &metadata).err(),
+			Some(MissingEndpoint("end_session_endpoint"))
+		);
+	}
+
+	#[test]
+	fn logout_callback() {
+		let redirect_uri = uri!("https://client.example.org/logged-out");
+		let state = Some(state!("af0ifjsldkj"));
+
+		assert_eq!(
+			parse_logout_callback(
+				redirect_uri,
+				uri!("https://client.example.org/logged-out?state=af0ifjsldkj"),
+				state
+			),
+			Ok(())
+		);
+		assert_eq!(
+			parse_logout_callback(
+				redirect_uri,
+				uri!("https://client.example.org/logged-out?state=forged"),
+				state
+			),
+			Err(CallbackError::StateMismatch)
+		);
+		assert_eq!(
+			parse_logout_callback(
+				redirect_uri,
+				uri!("https://client.example.org/logged-out"),
+				state
+			),
+			Err(CallbackError::StateMismatch)
+		);
+		assert!(matches!(
+			parse_logout_callback(
+				redirect_uri,
+				uri!("https://attacker.example.com/logged-out?state=af0ifjsldkj"),
+				state
+			),
+			Err(CallbackError::RedirectUriMismatch(_))
+		));
+	}
+}
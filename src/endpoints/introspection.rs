@@ -0,0 +1,328 @@
+//! [RFC 7662]: OAuth 2.0 Token Introspection.
+//!
+//! [RFC 7662]: <https://datatracker.ietf.org/doc/html/rfc7662>
+use std::collections::BTreeMap;
+
+use http::StatusCode;
+use iref::Uri;
+use serde::{Deserialize, Serialize};
+use serde_with::{OneOrMany, formats::PreferOne, serde_as, skip_serializing_none};
+
+use crate::{
+	AudienceBuf, ClientIdBuf, ScopeBuf,
+	client::{OAuth2Client, OAuth2ClientError},
+	endpoints::{Endpoint, HttpRequest, RequestBuilder, token::TokenTypeHint},
+	server::metadata::{AuthorizationServerMetadata, MissingEndpoint},
+	transport::{HttpClient, ResponseDecoder, WwwFormUrlEncoded},
+};
+
+/// The OAuth 2.0 Token Introspection endpoint.
+///
+/// This endpoint allows protected resources to query the authorization
+/// server about the state of a token.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc7662#section-2>
+pub struct IntrospectionEndpoint<'a, C> {
+	/// The OAuth 2.0 client.
+	pub client: &'a C,
+
+	/// The introspection endpoint URI.
+	pub uri: &'a Uri,
+}
+
+impl<'a, C> IntrospectionEndpoint<'a, C> {
+	/// Creates a new introspection endpoint for the given client and URI.
+	pub fn new(client: &'a C, uri: &'a Uri) -> Self {
+		Self { client, uri }
+	}
+
+	/// Creates a new introspection endpoint for the given client and URL.
+	///
+	/// Fails if the URL is not a valid URI.
+	#[cfg(feature = "url")]
+	pub fn from_url(
+		client: &'a C,
+		url: &'a url::Url,
+	) -> Result<Self, crate::util::UrlConversionError> {
+		use crate::util::UrlExt;
+		Ok(Self::new(client, url.as_uri()?))
+	}
+
+	/// Creates a new introspection endpoint for the given client, using the
+	/// `introspection_endpoint` advertised by the authorization server
+	/// metadata.
+	///
+	/// Fails if the authorization server does not advertise an
+	/// introspection endpoint.
+	pub fn from_metadata<P>(
+		client: &'a C,
+		metadata: &'a AuthorizationServerMetadata<P>,
+	) -> Result<Self, MissingEndpoint> {
+		metadata
+			.introspection_endpoint
+			.as_deref()
+			.map(|uri| Self::new(client, uri))
+			.ok_or(MissingEndpoint("introspection_endpoint"))
+	}
+}
+
+impl<'a, C> IntrospectionEndpoint<'a, C>
+where
+	C: OAuth2Client,
+{
+	/// Begins a Token Introspection Request.
+	///
+	/// The protected resource usually needs to authenticate itself, which
+	/// can be done by extending the returned [`RequestBuilder`].
+	pub fn introspect(
+		self,
+		token: String,
+		token_type_hint: Option<TokenTypeHint>,
+	) -> RequestBuilder<Self, IntrospectionRequest> {
+		RequestBuilder::new(self, IntrospectionRequest::new(token, token_type_hint))
+	}
+}
+
+impl<'a, C> Clone for IntrospectionEndpoint<'a, C> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+
+impl<'a, C> Copy for IntrospectionEndpoint<'a, C> {}
+
+impl<'a, C> Endpoint for IntrospectionEndpoint<'a, C>
+where
+	C: OAuth2Client,
+{
+	type Client = C;
+
+	fn client(&self) -> &Self::Client {
+		self.client
+	}
+
+	fn uri(&self) -> &Uri {
+		self.uri
+	}
+}
+
+/// Token Introspection Request.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc7662#section-2.1>
+#[skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct IntrospectionRequest {
+	/// The string value of the token.
+	pub token: String,
+
+	/// Hint about the type of the submitted token.
+	pub token_type_hint: Option<TokenTypeHint>,
+}
+
+impl IntrospectionRequest {
+	/// Creates a new introspection request.
+	pub fn new(token: String, token_type_hint: Option<TokenTypeHint>) -> Self {
+		Self {
+			token,
+			token_type_hint,
+		}
+	}
+}
+
+impl<'a, C> HttpRequest<IntrospectionEndpoint<'a, C>> for IntrospectionRequest
+where
+	C: OAuth2Client,
+{
+	type ContentType = WwwFormUrlEncoded;
+	type RequestBody<'b>
+		= &'b Self
+	where
+		Self: 'b;
+	type ResponsePayload = IntrospectionResponse;
+	type Response = IntrospectionResponse;
+
+	async fn build_request(
+		&self,
+		endpoint: &IntrospectionEndpoint<'a, C>,
+		_http_client: &impl HttpClient,
+	) -> Result<http::Request<Self::RequestBody<'_>>, OAuth2ClientError> {
+		Ok(http::Request::builder()
+			.method(http::Method::POST)
+			.uri(endpoint.uri.as_str())
+			.body(self)
+			.unwrap())
+	}
+
+	fn decode_response(
+		&self,
+		_endpoint: &IntrospectionEndpoint<'a, C>,
+		response: http::Response<Vec<u8>>,
+	) -> Result<http::Response<Self::ResponsePayload>, OAuth2ClientError> {
+		ResponseDecoder::json(StatusCode::OK).decode(response)
+	}
+
+	async fn process_response(
+		&self,
+		_endpoint: &IntrospectionEndpoint<'a, C>,
+		_http_client: &impl HttpClient,
+		response: http::Response<Self::ResponsePayload>,
+	) -> Result<Self::Response, OAuth2ClientError> {
+		Ok(response.into_body())
+	}
+}
+
+/// Token Introspection Response.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc7662#section-2.2>
+///
+/// Only [`active`](Self::active) is required. Inactive tokens should not
+/// carry any other information.
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct IntrospectionResponse<E = BTreeMap<String, serde_json::Value>> {
+	/// Whether or not the presented token is currently active.
+	pub active: bool,
+
+	/// Scope associated with the token.
+	pub scope: Option<ScopeBuf>,
+
+	/// Client identifier of the client that requested the token.
+	pub client_id: Option<ClientIdBuf>,
+
+	/// Human-readable identifier of the resource owner who authorized the
+	/// token.
+	pub username: Option<String>,
+
+	/// Type of the token.
+	pub token_type: Option<String>,
+
+	/// Expiration time of the token, in seconds since the Unix epoch.
+	pub exp: Option<u64>,
+
+	/// Issuance time of the token, in seconds since the Unix epoch.
+	pub iat: Option<u64>,
+
+	/// Time before which the token must not be used, in seconds since the
+	/// Unix epoch.
+	pub nbf: Option<u64>,
+
+	/// Subject of the token.
+	pub sub: Option<String>,
+
+	/// Intended audience of the token.
+	#[serde_as(as = "Option<OneOrMany<_, PreferOne>>")]
+	#[serde(default)]
+	pub aud: Option<Vec<AudienceBuf>>,
+
+	/// Issuer of the token.
+	pub iss: Option<String>,
+
+	/// Identifier of the token.
+	pub jti: Option<String>,
+
+	/// Extension fields.
+	#[serde(flatten)]
+	pub ext: E,
+}
+
+impl<E> IntrospectionResponse<E> {
+	/// Creates a new response for an active token, with no other
+	/// information.
+	pub fn active(ext: E) -> Self {
+		Self {
+			active: true,
+			scope: None,
+			client_id: None,
+			username: None,
+			token_type: None,
+			exp: None,
+			iat: None,
+			nbf: None,
+			sub: None,
+			aud: None,
+			iss: None,
+			jti: None,
+			ext,
+		}
+	}
+
+	/// Creates a new response for an inactive token.
+	pub fn inactive() -> Self
+	where
+		E: Default,
+	{
+		Self {
+			active: false,
+			..Self::active(E::default())
+		}
+	}
+}
+
+#[cfg(feature = "axum")]
+mod axum {
+	use ::axum::response::{IntoResponse, Response};
+
+	use crate::server::SecureJson;
+
+	use super::*;
+
+	impl<E> IntoResponse for IntrospectionResponse<E>
+	where
+		E: Serialize,
+	{
+		fn into_response(self) -> Response {
+			SecureJson(self).into_response()
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::util::NoExtension;
+
+	#[test]
+	fn inactive_response() {
+		assert_eq!(
+			serde_json::to_value(IntrospectionResponse::<NoExtension>::inactive()).unwrap(),
+			serde_json::json!({ "active": false })
+		);
+	}
+
+	#[test]
+	fn active_response() {
+		let response: IntrospectionResponse = serde_json::from_value(serde_json::json!({
+			"active": true,
+			"client_id": "l238j323ds-23ij4",
+			"username": "jdoe",
+			"scope": "read write dolphin",
+			"sub": "Z5O3upPC88QrAjx00dis",
+			"aud": "https://protected.example.net/resource",
+			"iss": "https://server.example.com/",
+			"exp": 1419356238,
+			"iat": 1419350238,
+			"extension_field": "twenty-seven"
+		}))
+		.unwrap();
+
+		assert!(response.active);
+		assert_eq!(
+			response.aud.as_deref(),
+			Some(
+				[crate::audience!("https://protected.example.net/resource").to_owned()].as_slice()
+			)
+		);
+		assert_eq!(response.ext["extension_field"], "twenty-seven");
+	}
+
+	#[test]
+	fn introspection_request() {
+		let request: IntrospectionRequest =
+			serde_html_form::from_str("token=mF_9.B5f-4.1JqM&token_type_hint=access_token")
+				.unwrap();
+		assert_eq!(request.token_type_hint, Some(TokenTypeHint::AccessToken));
+	}
+}
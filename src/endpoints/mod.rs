@@ -8,24 +8,45 @@
 //! - [`RedirectRequest`] — builds query parameters for redirect-based flows.
 //! - [`RequestBuilder`] — fluent builder for composing requests with
 //!   extensions.
-use std::collections::BTreeMap;
+//! - [`Telemetry`] — measurements of a request, for monitoring.
+//! - [`RetryOnResponse`] — hook resending a request after inspecting its
+//!   response.
+//! - [`PaginatedRequest`] — list request whose items are fetched page by
+//!   page with a [`Paginator`].
+use std::{marker::PhantomData, ops::Deref};
 
-use http::header::CONTENT_TYPE;
-use iref::{
-	Uri, UriBuf,
-	uri::{Query, QueryBuf},
-};
-use serde::Serialize;
+use http::header::{ACCEPT, CONTENT_TYPE};
+use iref::{Uri, UriBuf, uri::QueryBuf};
+use serde::{Serialize, de::DeserializeOwned};
 
 use crate::{
 	client::{OAuth2Client, OAuth2ClientError},
-	transport::{ContentType, HttpClient, WwwFormUrlEncoded},
+	transport::{
+		ContentType, HttpClient, ResponseBodyLimit, ResponseContentType, WwwFormUrlEncoded,
+		expect_content_type,
+	},
+	util::ExistingQuery,
 };
 
 pub mod authorization;
+#[cfg(feature = "device")]
+pub mod device_authorization;
+#[cfg(feature = "oidc")]
+pub mod end_session;
+pub mod introspection;
+pub mod nonce;
+mod pagination;
+#[cfg(feature = "par")]
 pub mod pushed_authorization;
+mod retry;
+pub mod revocation;
+mod telemetry;
 pub mod token;
 
+pub use pagination::*;
+pub use retry::*;
+pub use telemetry::*;
+
 /// An OAuth 2.0 endpoint bound to a specific client.
 pub trait Endpoint {
 	/// The client type associated with this endpoint.
@@ -52,7 +73,25 @@ pub trait RedirectRequest {
 	///
 	/// The request parameters are serialized as query parameters and appended
 	/// to the authorization endpoint URI.
-	fn redirect_uri<E>(&self, endpoint: &E) -> UriBuf
+	///
+	/// Fails if the resulting URI is longer than
+	/// [`DEFAULT_MAX_REDIRECT_URI_LEN`]. Use
+	/// [`redirect_uri_with_max_len`](Self::redirect_uri_with_max_len) to
+	/// configure the limit.
+	fn redirect_uri<E>(&self, endpoint: &E) -> Result<UriBuf, RedirectUriTooLong>
+	where
+		E: Endpoint,
+	{
+		self.redirect_uri_with_max_len(endpoint, DEFAULT_MAX_REDIRECT_URI_LEN)
+	}
+
+	/// Converts this request builder into a complete redirect URI, failing
+	/// if it is longer than `max_len` bytes.
+	fn redirect_uri_with_max_len<E>(
+		&self,
+		endpoint: &E,
+		max_len: usize,
+	) -> Result<UriBuf, RedirectUriTooLong>
 	where
 		E: Endpoint,
 	{
@@ -61,15 +100,14 @@ pub trait RedirectRequest {
 		#[derive(Serialize)]
 		struct WithAuthorizationRequest<T> {
 			#[serde(flatten)]
-			args: BTreeMap<String, String>,
+			args: ExistingQuery,
 
 			#[serde(flatten)]
 			authorization_params: T,
 		}
 
 		let query = QueryBuf::new(WwwFormUrlEncoded::encode(&WithAuthorizationRequest {
-			args: serde_html_form::from_str(uri.query().map(Query::as_str).unwrap_or_default())
-				.unwrap(),
+			args: ExistingQuery::of(&uri),
 			authorization_params: self.build_query(),
 		}))
 		.unwrap();
@@ -78,10 +116,55 @@ pub trait RedirectRequest {
 			uri.set_query(Some(&query));
 		}
 
-		uri
+		if uri.len() > max_len {
+			return Err(RedirectUriTooLong {
+				len: uri.len(),
+				max_len,
+			});
+		}
+
+		Ok(uri)
 	}
 }
 
+/// Default maximum length, in bytes, of redirect URIs built with
+/// [`RedirectRequest::redirect_uri`].
+///
+/// Browsers, proxies and servers commonly truncate or reject longer URIs.
+pub const DEFAULT_MAX_REDIRECT_URI_LEN: usize = 8192;
+
+/// Error returned when a redirect URI exceeds the maximum length.
+///
+/// Large requests, such as those carrying rich authorization details, should
+/// be sent with a Pushed Authorization Request
+/// ([RFC 9126](https://www.rfc-editor.org/rfc/rfc9126.html)) or a JWT-Secured
+/// Authorization Request
+/// ([RFC 9101](https://www.rfc-editor.org/rfc/rfc9101.html)) instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error(
+	"redirect URI is {len} bytes long, exceeding the limit of {max_len} bytes; use a pushed (PAR) or JWT-secured (JAR) authorization request instead"
+)]
+pub struct RedirectUriTooLong {
+	/// Length of the redirect URI.
+	pub len: usize,
+
+	/// Maximum length of the redirect URI.
+	pub max_len: usize,
+}
+
+/// Error returned by [`RequestBuilder::into_redirect_url`].
+#[cfg(feature = "url")]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RedirectUrlError {
+	/// The redirect URI is too long.
+	#[error(transparent)]
+	TooLong(#[from] RedirectUriTooLong),
+
+	/// The redirect URI is not a valid URL.
+	#[error(transparent)]
+	Conversion(#[from] crate::util::UrlConversionError),
+}
+
 /// A request that can be sent to an endpoint over HTTP.
 ///
 /// This trait handles the full lifecycle of an HTTP request: building the
@@ -121,7 +204,9 @@ pub trait HttpRequest<E>: Sized {
 	/// Post-processes the decoded response into the final response type.
 	///
 	/// This step may perform additional HTTP exchanges (e.g. DPoP nonce
-	/// retry).
+	/// retry), which layers should request through a [`RetryOnResponse`]
+	/// hook. The extensions of the built request are available through
+	/// [`RequestExtensions`].
 	#[allow(async_fn_in_trait)]
 	async fn process_response(
 		&self,
@@ -143,13 +228,38 @@ pub trait HttpRequest<E>: Sized {
 			request.headers_mut().insert(CONTENT_TYPE, content_type);
 		}
 		let encoded_request = request.map(|body| Self::ContentType::encode(&body));
-		let response = http_client.send(encoded_request).await?;
+		let extensions = encoded_request.extensions().clone();
+		let mut response = http_client.send(encoded_request).await?;
+		response
+			.extensions_mut()
+			.insert(RequestExtensions(extensions));
 		let decoded_response = self.decode_response(endpoint, response)?;
 		self.process_response(endpoint, http_client, decoded_response)
 			.await
 	}
 }
 
+/// Extensions of a built request, attached to its response.
+///
+/// [`HttpRequest::send`] copies the [`http::Extensions`] of the built request
+/// into the extensions of the response, wrapped in this type. Layers can
+/// stash data in the request when building it (e.g. a key handle or a retry
+/// policy) and read it back when decoding or processing the response,
+/// without changing the types of the other layers.
+#[derive(Debug, Clone, Default)]
+pub struct RequestExtensions(pub http::Extensions);
+
+impl RequestExtensions {
+	/// Returns the request extension of type `T` attached to the given
+	/// response, if any.
+	pub fn get<T, B>(response: &http::Response<B>) -> Option<&T>
+	where
+		T: Send + Sync + 'static,
+	{
+		response.extensions().get::<Self>()?.0.get()
+	}
+}
+
 /// Fluent builder for composing an endpoint request with extensions.
 ///
 /// Extension traits (such as [`AddState`](crate::AddState),
@@ -178,6 +288,49 @@ impl<E, T> RequestBuilder<E, T> {
 		RequestBuilder::new(self.endpoint, f(self.request))
 	}
 
+	/// Encodes the request body with the given content type instead of the
+	/// one defined by the request.
+	pub fn with_content_type<C>(self) -> RequestBuilder<E, WithContentType<C, T>>
+	where
+		C: ContentType,
+	{
+		self.map(WithContentType::new)
+	}
+
+	/// Decodes successful responses with the given content type instead of
+	/// the one expected by the request.
+	///
+	/// The expected content type is sent in the `Accept` header. Error
+	/// responses are still decoded by the inner request.
+	pub fn with_response_content_type<D>(self) -> RequestBuilder<E, WithResponseContentType<D, T>>
+	where
+		D: ResponseContentType,
+	{
+		self.map(WithResponseContentType::new)
+	}
+
+	/// Returns the response along with the [`Telemetry`] of the request.
+	pub fn with_telemetry(self) -> RequestBuilder<E, WithTelemetry<T>> {
+		self.map(WithTelemetry::new)
+	}
+
+	/// Rebuilds and resends the request when requested by the given hook,
+	/// at most [`DEFAULT_MAX_RETRIES`] times.
+	///
+	/// See [`WithRetry::with_max_retries`] to change the bound.
+	pub fn with_retry<R>(self, hook: R) -> RequestBuilder<E, WithRetry<R, T>> {
+		self.map(|request| WithRetry::new(request, hook))
+	}
+
+	/// Returns a [`Paginator`] fetching the pages of the built list request,
+	/// starting with this one.
+	pub fn paginate(self) -> Paginator<E, T>
+	where
+		T: PaginatedRequest<E>,
+	{
+		Paginator::new(self.endpoint, self.request)
+	}
+
 	/// Sends the built request using the provided HTTP client.
 	pub async fn send(self, http_client: &impl HttpClient) -> Result<T::Response, OAuth2ClientError>
 	where
@@ -190,8 +343,9 @@ impl<E, T> RequestBuilder<E, T> {
 	/// Converts this request builder into a redirect URI.
 	///
 	/// The request parameters are serialized as query parameters and appended
-	/// to the authorization endpoint URI.
-	pub fn into_redirect_uri(self) -> UriBuf
+	/// to the authorization endpoint URI. Fails if the resulting URI is longer
+	/// than [`DEFAULT_MAX_REDIRECT_URI_LEN`].
+	pub fn into_redirect_uri(self) -> Result<UriBuf, RedirectUriTooLong>
 	where
 		E: Endpoint,
 		T: RedirectRequest,
@@ -199,4 +353,549 @@ impl<E, T> RequestBuilder<E, T> {
 		let endpoint = self.endpoint;
 		self.request.redirect_uri(&endpoint)
 	}
+
+	/// Converts this request builder into a redirect URI, failing if it is
+	/// longer than `max_len` bytes.
+	pub fn into_redirect_uri_with_max_len(
+		self,
+		max_len: usize,
+	) -> Result<UriBuf, RedirectUriTooLong>
+	where
+		E: Endpoint,
+		T: RedirectRequest,
+	{
+		let endpoint = self.endpoint;
+		self.request.redirect_uri_with_max_len(&endpoint, max_len)
+	}
+
+	/// Converts this request builder into a redirect URL.
+	///
+	/// Same as [`into_redirect_uri`](Self::into_redirect_uri), returning a
+	/// [`url::Url`].
+	#[cfg(feature = "url")]
+	pub fn into_redirect_url(self) -> Result<url::Url, RedirectUrlError>
+	where
+		E: Endpoint,
+		T: RedirectRequest,
+	{
+		use crate::util::UriExt;
+		Ok(self.into_redirect_uri()?.to_url()?)
+	}
+}
+
+/// Wrapper encoding the body of a request with a [`ContentType`].
+///
+/// See [`RequestBuilder::with_content_type`].
+pub struct WithContentType<C, T> {
+	/// The inner request.
+	pub value: T,
+
+	content_type: PhantomData<C>,
+}
+
+impl<C, T> WithContentType<C, T> {
+	/// Creates a new [`WithContentType`] wrapping the given request.
+	pub fn new(value: T) -> Self {
+		Self {
+			value,
+			content_type: PhantomData,
+		}
+	}
+}
+
+impl<C, T> Deref for WithContentType<C, T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		&self.value
+	}
+}
+
+impl<E, C, T> HttpRequest<E> for WithContentType<C, T>
+where
+	T: HttpRequest<E>,
+	C: ContentType,
+{
+	type ContentType = C;
+	type RequestBody<'b>
+		= T::RequestBody<'b>
+	where
+		Self: 'b;
+	type Response = T::Response;
+	type ResponsePayload = T::ResponsePayload;
+
+	async fn build_request(
+		&self,
+		endpoint: &E,
+		http_client: &impl HttpClient,
+	) -> Result<http::Request<Self::RequestBody<'_>>, OAuth2ClientError> {
+		self.value.build_request(endpoint, http_client).await
+	}
+
+	fn decode_response(
+		&self,
+		endpoint: &E,
+		response: http::Response<Vec<u8>>,
+	) -> Result<http::Response<Self::ResponsePayload>, OAuth2ClientError> {
+		self.value.decode_response(endpoint, response)
+	}
+
+	async fn process_response(
+		&self,
+		endpoint: &E,
+		http_client: &impl HttpClient,
+		response: http::Response<Self::ResponsePayload>,
+	) -> Result<Self::Response, OAuth2ClientError> {
+		self.value
+			.process_response(endpoint, http_client, response)
+			.await
+	}
+}
+
+/// Wrapper decoding the successful responses of a request with a
+/// [`ResponseContentType`].
+///
+/// See [`RequestBuilder::with_response_content_type`].
+pub struct WithResponseContentType<D, T> {
+	/// The inner request.
+	pub value: T,
+
+	content_type: PhantomData<D>,
+}
+
+impl<D, T> WithResponseContentType<D, T> {
+	/// Creates a new [`WithResponseContentType`] wrapping the given request.
+	pub fn new(value: T) -> Self {
+		Self {
+			value,
+			content_type: PhantomData,
+		}
+	}
+}
+
+impl<D, T> Deref for WithResponseContentType<D, T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		&self.value
+	}
+}
+
+impl<E, D, T> HttpRequest<E> for WithResponseContentType<D, T>
+where
+	T: HttpRequest<E>,
+	T::ResponsePayload: DeserializeOwned,
+	D: ResponseContentType,
+{
+	type ContentType = T::ContentType;
+	type RequestBody<'b>
+		= T::RequestBody<'b>
+	where
+		Self: 'b;
+	type Response = T::Response;
+	type ResponsePayload = T::ResponsePayload;
+
+	async fn build_request(
+		&self,
+		endpoint: &E,
+		http_client: &impl HttpClient,
+	) -> Result<http::Request<Self::RequestBody<'_>>, OAuth2ClientError> {
+		let mut request = self.value.build_request(endpoint, http_client).await?;
+		request.headers_mut().insert(ACCEPT, D::VALUE);
+		Ok(request)
+	}
+
+	fn decode_response(
+		&self,
+		endpoint: &E,
+		response: http::Response<Vec<u8>>,
+	) -> Result<http::Response<Self::ResponsePayload>, OAuth2ClientError> {
+		if !response.status().is_success() {
+			return self.value.decode_response(endpoint, response);
+		}
+
+		ResponseBodyLimit::DEFAULT.check(response.body())?;
+		expect_content_type(response.headers(), &D::VALUE)?;
+		let body = D::decode(response.body())?;
+		Ok(response.map(|_| body))
+	}
+
+	async fn process_response(
+		&self,
+		endpoint: &E,
+		http_client: &impl HttpClient,
+		response: http::Response<Self::ResponsePayload>,
+	) -> Result<Self::Response, OAuth2ClientError> {
+		self.value
+			.process_response(endpoint, http_client, response)
+			.await
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use iref::uri;
+
+	use crate::{
+		ClientId, client_id,
+		endpoints::token::TokenResponse,
+		grant::authorization_code::{AuthorizationCodeAuthorizationRequest, ExchangeCode},
+		issuer, response_type,
+		server::metadata::{AuthorizationServerMetadata, UnsupportedResponseType},
+		util::NoExtension,
+	};
+
+	use super::*;
+
+	struct Client;
+
+	impl OAuth2Client for Client {
+		type TokenResponse = TokenResponse;
+
+		fn client_id(&self) -> &ClientId {
+			client_id!("s6BhdRkqt3")
+		}
+	}
+
+	#[test]
+	fn redirect_uri_max_len() {
+		let endpoint = authorization::AuthorizationEndpoint::new(
+			&Client,
+			uri!("https://server.example.com/authorize"),
+		);
+		let request = AuthorizationCodeAuthorizationRequest::new(
+			client_id!("s6BhdRkqt3").to_owned(),
+			None,
+			None,
+		);
+
+		let uri = request.redirect_uri(&endpoint).unwrap();
+		assert_eq!(
+			uri.as_str(),
+			"https://server.example.com/authorize?response_type=code&client_id=s6BhdRkqt3"
+		);
+
+		assert_eq!(
+			request.redirect_uri_with_max_len(&endpoint, 64),
+			Err(RedirectUriTooLong {
+				len: uri.len(),
+				max_len: 64
+			})
+		);
+	}
+
+	#[test]
+	fn loopback_redirect_uri() {
+		let endpoint = authorization::AuthorizationEndpoint::new(
+			&Client,
+			uri!("https://server.example.com/authorize"),
+		);
+
+		let uri = endpoint
+			.authorize_url(Some(uri!("http://127.0.0.1/cb").to_owned()), None)
+			.with_loopback_port(51004)
+			.unwrap()
+			.into_redirect_uri()
+			.unwrap();
+		assert_eq!(
+			uri.as_str(),
+			"https://server.example.com/authorize?response_type=code&client_id=s6BhdRkqt3&redirect_uri=http%3A%2F%2F127.0.0.1%3A51004%2Fcb"
+		);
+
+		assert!(
+			endpoint
+				.authorize_url(Some(uri!("com.example.app:/cb").to_owned()), None)
+				.with_loopback_port(51004)
+				.is_err()
+		);
+	}
+
+	#[test]
+	fn existing_query() {
+		let endpoint = authorization::AuthorizationEndpoint::new(
+			&Client,
+			uri!("https://server.example.com/authorize?tenant=b&acr=1&acr=2"),
+		);
+		let request = AuthorizationCodeAuthorizationRequest::new(
+			client_id!("s6BhdRkqt3").to_owned(),
+			None,
+			None,
+		);
+
+		assert_eq!(
+			request.redirect_uri(&endpoint).unwrap().as_str(),
+			"https://server.example.com/authorize?tenant=b&acr=1&acr=2&response_type=code&client_id=s6BhdRkqt3"
+		);
+
+		#[cfg(feature = "par")]
+		{
+			let response = pushed_authorization::PushedAuthorizationResponse::new(
+				UriBuf::new(b"urn:example:bwc4JK-ESC0w8acc191e-Y1LTC2".to_vec()).unwrap(),
+				std::time::Duration::from_secs(60),
+			);
+			let uri = response
+				.authorize_url(endpoint)
+				.into_pushed_redirect_uri()
+				.unwrap();
+			assert_eq!(
+				uri.as_str(),
+				"https://server.example.com/authorize?tenant=b&acr=1&acr=2&client_id=s6BhdRkqt3&request_uri=urn%3Aexample%3Abwc4JK-ESC0w8acc191e-Y1LTC2"
+			);
+
+			let request: pushed_authorization::PushedAuthorizationRequest =
+				serde_html_form::from_str(uri.query().unwrap().as_str()).unwrap();
+			assert_eq!(
+				request.ext,
+				ExistingQuery(vec![
+					("tenant".to_owned(), "b".to_owned()),
+					("acr".to_owned(), "1".to_owned()),
+					("acr".to_owned(), "2".to_owned()),
+				])
+			);
+		}
+	}
+
+	#[cfg(feature = "url")]
+	#[test]
+	fn redirect_url() {
+		let url = url::Url::parse("https://server.example.com/authorize?tenant=b").unwrap();
+		let endpoint = authorization::AuthorizationEndpoint::from_url(&Client, &url).unwrap();
+
+		let redirect_url = endpoint
+			.authorize_url(None, None)
+			.into_redirect_url()
+			.unwrap();
+		assert_eq!(
+			redirect_url.as_str(),
+			"https://server.example.com/authorize?tenant=b&response_type=code&client_id=s6BhdRkqt3"
+		);
+		assert_eq!(redirect_url.query_pairs().last().unwrap().1, "s6BhdRkqt3");
+	}
+
+	#[test]
+	fn checked_authorize_url() {
+		let mut metadata = AuthorizationServerMetadata::<NoExtension>::new(
+			issuer!("https://server.example.com").to_owned(),
+		)
+		.with_authorization_endpoint(uri!("https://server.example.com/authorize").to_owned());
+		let endpoint =
+			authorization::AuthorizationEndpoint::from_metadata(&Client, &metadata).unwrap();
+		assert_eq!(endpoint.uri, uri!("https://server.example.com/authorize"));
+		assert!(
+			endpoint
+				.checked_authorize_url(&metadata, None, None)
+				.is_ok()
+		);
+
+		let endpoint = authorization::AuthorizationEndpoint::new(
+			&Client,
+			uri!("https://server.example.com/authorize"),
+		);
+		metadata.response_types_supported = Some(vec![
+			response_type!("id_token token").to_owned(),
+			response_type!("token").to_owned(),
+		]);
+		assert!(metadata.supports_response_type(response_type!("token id_token")));
+		assert_eq!(
+			endpoint
+				.checked_authorize_url(&metadata, None, None)
+				.err()
+				.unwrap(),
+			UnsupportedResponseType(response_type!("code").to_owned())
+		);
+
+		metadata
+			.response_types_supported
+			.as_mut()
+			.unwrap()
+			.push(response_type!("code").to_owned());
+		assert!(
+			endpoint
+				.checked_authorize_url(&metadata, None, None)
+				.is_ok()
+		);
+	}
+
+	/// Token endpoint responding with a form-encoded token, as some legacy
+	/// authorization servers do.
+	struct FormTokenServer;
+
+	impl HttpClient for FormTokenServer {
+		async fn send(
+			&self,
+			_request: http::Request<Vec<u8>>,
+		) -> Result<http::Response<Vec<u8>>, OAuth2ClientError> {
+			Ok(http::Response::builder()
+				.header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+				.body(
+					b"access_token=2YotnFZFEjr1zCsicMWpAA&token_type=bearer&expires_in=3600"
+						.to_vec(),
+				)
+				.unwrap())
+		}
+	}
+
+	#[tokio::test]
+	async fn form_encoded_token_response() {
+		let endpoint = token::TokenEndpoint::new(&Client, uri!("https://server.example.com/token"));
+
+		let builder = endpoint
+			.clone()
+			.exchange_code(crate::code!("SplxlOBeZQQYbYS6WxSbIA").to_owned(), None)
+			.with_response_content_type::<WwwFormUrlEncoded>();
+		let request = builder
+			.request
+			.build_request(&builder.endpoint, &FormTokenServer)
+			.await
+			.unwrap();
+		assert_eq!(
+			request.headers()[ACCEPT],
+			crate::transport::APPLICATION_X_WWW_FORM_URLENCODED
+		);
+
+		let response = builder.send(&FormTokenServer).await.unwrap();
+		assert_eq!(response.access_token.as_str(), "2YotnFZFEjr1zCsicMWpAA");
+		assert_eq!(
+			response.expires_in(),
+			Some(std::time::Duration::from_secs(3600))
+		);
+
+		assert!(matches!(
+			endpoint
+				.exchange_code(crate::code!("SplxlOBeZQQYbYS6WxSbIA").to_owned(), None)
+				.send(&FormTokenServer)
+				.await,
+			Err(OAuth2ClientError::Response(_))
+		));
+	}
+
+	/// Layer stashing a marker in the request extensions.
+	struct WithMarker<T>(T);
+
+	#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+	struct Marker(u32);
+
+	impl<E, T> HttpRequest<E> for WithMarker<T>
+	where
+		T: HttpRequest<E>,
+	{
+		type ContentType = T::ContentType;
+		type RequestBody<'b>
+			= T::RequestBody<'b>
+		where
+			Self: 'b;
+		type Response = (T::Response, Option<Marker>);
+		type ResponsePayload = T::ResponsePayload;
+
+		async fn build_request(
+			&self,
+			endpoint: &E,
+			http_client: &impl HttpClient,
+		) -> Result<http::Request<Self::RequestBody<'_>>, OAuth2ClientError> {
+			let mut request = self.0.build_request(endpoint, http_client).await?;
+			request.extensions_mut().insert(Marker(42));
+			Ok(request)
+		}
+
+		fn decode_response(
+			&self,
+			endpoint: &E,
+			response: http::Response<Vec<u8>>,
+		) -> Result<http::Response<Self::ResponsePayload>, OAuth2ClientError> {
+			self.0.decode_response(endpoint, response)
+		}
+
+		async fn process_response(
+			&self,
+			endpoint: &E,
+			http_client: &impl HttpClient,
+			response: http::Response<Self::ResponsePayload>,
+		) -> Result<Self::Response, OAuth2ClientError> {
+			let marker = RequestExtensions::get::<Marker, _>(&response).copied();
+			let response = self
+				.0
+				.process_response(endpoint, http_client, response)
+				.await?;
+			Ok((response, marker))
+		}
+	}
+
+	#[tokio::test]
+	async fn request_extensions() {
+		let endpoint = token::TokenEndpoint::new(&Client, uri!("https://server.example.com/token"));
+		let (response, marker) = endpoint
+			.exchange_code(crate::code!("SplxlOBeZQQYbYS6WxSbIA").to_owned(), None)
+			.with_response_content_type::<WwwFormUrlEncoded>()
+			.map(WithMarker)
+			.send(&FormTokenServer)
+			.await
+			.unwrap();
+
+		assert_eq!(response.access_token.as_str(), "2YotnFZFEjr1zCsicMWpAA");
+		assert_eq!(marker, Some(Marker(42)));
+	}
+
+	#[tokio::test]
+	async fn telemetry() {
+		let endpoint = token::TokenEndpoint::new(&Client, uri!("https://server.example.com/token"));
+		let response = endpoint
+			.exchange_code(crate::code!("SplxlOBeZQQYbYS6WxSbIA").to_owned(), None)
+			.with_response_content_type::<WwwFormUrlEncoded>()
+			.with_telemetry()
+			.send(&FormTokenServer)
+			.await
+			.unwrap();
+
+		assert_eq!(response.access_token.as_str(), "2YotnFZFEjr1zCsicMWpAA");
+		assert_eq!(response.telemetry.status, http::StatusCode::OK);
+		assert_eq!(response.telemetry.retries, 0);
+		assert!(!response.telemetry.nonce_retry);
+	}
+
+	#[cfg(feature = "cbor")]
+	#[tokio::test]
+	async fn cbor_token_request() {
+		use crate::transport::{APPLICATION_CBOR, Cbor};
+
+		/// Token endpoint only speaking CBOR.
+		struct CborTokenServer;
+
+		impl HttpClient for CborTokenServer {
+			async fn send(
+				&self,
+				request: http::Request<Vec<u8>>,
+			) -> Result<http::Response<Vec<u8>>, OAuth2ClientError> {
+				assert_eq!(request.headers()[CONTENT_TYPE], APPLICATION_CBOR);
+				let body: serde_json::Value =
+					ciborium::from_reader(request.body().as_slice()).unwrap();
+				assert_eq!(
+					body,
+					serde_json::json!({
+						"grant_type": "authorization_code",
+						"client_id": "s6BhdRkqt3",
+						"code": "SplxlOBeZQQYbYS6WxSbIA"
+					})
+				);
+
+				Ok(http::Response::builder()
+					.header(CONTENT_TYPE, APPLICATION_CBOR)
+					.body(Cbor::encode(&serde_json::json!({
+						"access_token": "2YotnFZFEjr1zCsicMWpAA",
+						"token_type": "Bearer",
+						"expires_in": 3600
+					})))
+					.unwrap())
+			}
+		}
+
+		let endpoint = token::TokenEndpoint::new(&Client, uri!("https://server.example.com/token"));
+		let response = endpoint
+			.exchange_code(crate::code!("SplxlOBeZQQYbYS6WxSbIA").to_owned(), None)
+			.with_content_type::<Cbor>()
+			.with_response_content_type::<Cbor>()
+			.send(&CborTokenServer)
+			.await
+			.unwrap();
+		assert_eq!(response.access_token.as_str(), "2YotnFZFEjr1zCsicMWpAA");
+	}
 }
@@ -0,0 +1,277 @@
+//! OpenID4VCI nonce endpoint.
+//!
+//! See: <https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0.html#name-nonce-endpoint>
+//!
+//! Wallets retrieve from the credential issuer a fresh `c_nonce`, to include
+//! in the proofs of possession of the keys the credentials are bound to. The
+//! nonce endpoint may also provide a DPoP nonce, in the `DPoP-Nonce` response
+//! header.
+use std::time::Duration;
+
+use iref::Uri;
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+use crate::{
+	client::OAuth2ClientError,
+	endpoints::{HttpRequest, RequestBuilder},
+	grant::pre_authorized_code::CredentialIssuerMetadata,
+	server::metadata::MissingEndpoint,
+	transport::{HttpClient, NoContent, ResponseDecoder},
+};
+
+/// The OpenID4VCI nonce endpoint.
+///
+/// Requests to this endpoint are not authenticated.
+pub struct NonceEndpoint<'a> {
+	/// The nonce endpoint URI.
+	pub uri: &'a Uri,
+}
+
+impl<'a> NonceEndpoint<'a> {
+	/// Creates a new nonce endpoint for the given URI.
+	pub fn new(uri: &'a Uri) -> Self {
+		Self { uri }
+	}
+
+	/// Creates a new nonce endpoint, using the `nonce_endpoint` advertised by
+	/// the credential issuer metadata.
+	///
+	/// Fails if the credential issuer does not advertise a nonce endpoint.
+	pub fn from_metadata<E>(
+		metadata: &'a CredentialIssuerMetadata<E>,
+	) -> Result<Self, MissingEndpoint> {
+		metadata
+			.nonce_endpoint
+			.as_deref()
+			.map(Self::new)
+			.ok_or(MissingEndpoint("nonce_endpoint"))
+	}
+
+	/// Begins a nonce request.
+	pub fn request_nonce(self) -> RequestBuilder<Self, NonceRequest> {
+		RequestBuilder::new(self, NonceRequest)
+	}
+}
+
+impl<'a> Clone for NonceEndpoint<'a> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+
+impl<'a> Copy for NonceEndpoint<'a> {}
+
+/// Nonce request, without any parameter.
+///
+/// See: <https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0.html#name-nonce-request>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct NonceRequest;
+
+impl<'a> HttpRequest<NonceEndpoint<'a>> for NonceRequest {
+	type ContentType = NoContent;
+	type RequestBody<'b>
+		= &'b Self
+	where
+		Self: 'b;
+	type ResponsePayload = NonceResponse;
+	type Response = NonceResponse;
+
+	async fn build_request(
+		&self,
+		endpoint: &NonceEndpoint<'a>,
+		_http_client: &impl HttpClient,
+	) -> Result<http::Request<Self::RequestBody<'_>>, OAuth2ClientError> {
+		Ok(http::Request::builder()
+			.method(http::Method::POST)
+			.uri(endpoint.uri.as_str())
+			.body(self)
+			.unwrap())
+	}
+
+	fn decode_response(
+		&self,
+		_endpoint: &NonceEndpoint<'a>,
+		response: http::Response<Vec<u8>>,
+	) -> Result<http::Response<Self::ResponsePayload>, OAuth2ClientError> {
+		ResponseDecoder::json(http::StatusCode::OK)
+			.with_error_responses()
+			.decode(response)
+	}
+
+	async fn process_response(
+		&self,
+		_endpoint: &NonceEndpoint<'a>,
+		_http_client: &impl HttpClient,
+		response: http::Response<Self::ResponsePayload>,
+	) -> Result<Self::Response, OAuth2ClientError> {
+		#[cfg(feature = "dpop")]
+		{
+			let dpop_nonce = response
+				.headers()
+				.get(crate::ext::dpop::DPOP_NONCE)
+				.and_then(|value| value.to_str().ok())
+				.map(ToOwned::to_owned);
+			let mut response = response.into_body();
+			response.dpop_nonce = dpop_nonce;
+			Ok(response)
+		}
+
+		#[cfg(not(feature = "dpop"))]
+		Ok(response.into_body())
+	}
+}
+
+/// Nonce response.
+///
+/// See: <https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0.html#name-nonce-response>
+#[skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct NonceResponse {
+	/// Nonce to include in the proofs of possession of the credential
+	/// request.
+	pub c_nonce: String,
+
+	/// Lifetime in seconds of the nonce, returned by credential issuers
+	/// implementing earlier drafts of OpenID4VCI.
+	pub c_nonce_expires_in: Option<u64>,
+
+	/// DPoP nonce provided in the `DPoP-Nonce` response header, if any.
+	#[cfg(feature = "dpop")]
+	#[serde(skip)]
+	pub dpop_nonce: Option<String>,
+}
+
+impl NonceResponse {
+	/// Creates a new nonce response.
+	pub fn new(c_nonce: String) -> Self {
+		Self {
+			c_nonce,
+			c_nonce_expires_in: None,
+			#[cfg(feature = "dpop")]
+			dpop_nonce: None,
+		}
+	}
+
+	/// Sets the DPoP nonce sent in the `DPoP-Nonce` response header.
+	#[cfg(feature = "dpop")]
+	pub fn with_dpop_nonce(self, dpop_nonce: String) -> Self {
+		Self {
+			dpop_nonce: Some(dpop_nonce),
+			..self
+		}
+	}
+
+	/// Returns the lifetime of the nonce, if known.
+	pub fn expires_in(&self) -> Option<Duration> {
+		self.c_nonce_expires_in.map(Duration::from_secs)
+	}
+}
+
+#[cfg(feature = "axum")]
+mod axum {
+	use ::axum::response::{IntoResponse, Response};
+
+	use crate::server::SecureJson;
+
+	use super::*;
+
+	/// Responds with the `Cache-Control: no-store` header, and the
+	/// `DPoP-Nonce` header if a DPoP nonce is set.
+	impl IntoResponse for NonceResponse {
+		fn into_response(self) -> Response {
+			#[cfg(feature = "dpop")]
+			if let Some(dpop_nonce) = &self.dpop_nonce
+				&& let Ok(value) = ::axum::http::HeaderValue::from_str(dpop_nonce)
+			{
+				return ([(crate::ext::dpop::DPOP_NONCE, value)], SecureJson(self)).into_response();
+			}
+
+			SecureJson(self).into_response()
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use iref::uri;
+
+	use crate::transport::APPLICATION_JSON;
+
+	use super::*;
+
+	/// Credential issuer returning a nonce.
+	struct Issuer;
+
+	impl HttpClient for Issuer {
+		async fn send(
+			&self,
+			request: http::Request<Vec<u8>>,
+		) -> Result<http::Response<Vec<u8>>, OAuth2ClientError> {
+			assert_eq!(request.method(), http::Method::POST);
+			assert_eq!(request.uri(), "https://issuer.example.com/nonce");
+			assert!(request.body().is_empty());
+
+			Ok(http::Response::builder()
+				.header(http::header::CONTENT_TYPE, &APPLICATION_JSON)
+				.header(http::header::CACHE_CONTROL, "no-store")
+				.header("DPoP-Nonce", "eyJ7S_zG.eyJH0-Z.HX4w-7v")
+				.body(br#"{"c_nonce":"wKI4LT17ac15ES9bw8ac4","c_nonce_expires_in":86400}"#.to_vec())
+				.unwrap())
+		}
+	}
+
+	#[tokio::test]
+	async fn nonce_request() {
+		let metadata: CredentialIssuerMetadata = serde_json::from_value(serde_json::json!({
+			"credential_issuer": "https://issuer.example.com",
+			"credential_endpoint": "https://issuer.example.com/credential",
+			"nonce_endpoint": "https://issuer.example.com/nonce"
+		}))
+		.unwrap();
+		let endpoint = NonceEndpoint::from_metadata(&metadata).unwrap();
+		assert_eq!(endpoint.uri, uri!("https://issuer.example.com/nonce"));
+
+		let response = endpoint.request_nonce().send(&Issuer).await.unwrap();
+		assert_eq!(response.c_nonce, "wKI4LT17ac15ES9bw8ac4");
+		assert_eq!(response.expires_in(), Some(Duration::from_secs(86400)));
+		#[cfg(feature = "dpop")]
+		assert_eq!(
+			response.dpop_nonce.as_deref(),
+			Some("eyJ7S_zG.eyJH0-Z.HX4w-7v")
+		);
+	}
+
+	#[test]
+	fn missing_nonce_endpoint() {
+		let metadata: CredentialIssuerMetadata = serde_json::from_value(serde_json::json!({
+			"credential_issuer": "https://issuer.example.com",
+			"credential_endpoint": "https://issuer.example.com/credential"
+		}))
+		.unwrap();
+		assert_eq!(
+			NonceEndpoint::from_metadata(&metadata).err(),
+			Some(MissingEndpoint("nonce_endpoint"))
+		);
+	}
+
+	#[cfg(feature = "axum")]
+	#[test]
+	fn nonce_response() {
+		use ::axum::response::IntoResponse;
+
+		let response = NonceResponse::new("wKI4LT17ac15ES9bw8ac4".to_owned()).into_response();
+		assert_eq!(response.status(), http::StatusCode::OK);
+		assert_eq!(response.headers()["cache-control"], "no-store");
+
+		#[cfg(feature = "dpop")]
+		{
+			let response = NonceResponse::new("wKI4LT17ac15ES9bw8ac4".to_owned())
+				.with_dpop_nonce("eyJ7S_zG.eyJH0-Z.HX4w-7v".to_owned())
+				.into_response();
+			assert_eq!(response.headers()["dpop-nonce"], "eyJ7S_zG.eyJH0-Z.HX4w-7v");
+		}
+	}
+}
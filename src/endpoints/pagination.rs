@@ -0,0 +1,377 @@
+use http::{HeaderMap, header::LINK};
+use iref::{Uri, UriBuf, UriRef};
+
+use crate::{client::OAuth2ClientError, transport::HttpClient};
+
+use super::HttpRequest;
+
+/// Default maximum number of pages fetched by a [`Paginator`].
+pub const DEFAULT_MAX_PAGES: usize = 100;
+
+/// Position of the next page of a paginated list.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PageCursor {
+	/// URI of the next page, e.g. the target of a `Link` header with the
+	/// `next` relation type.
+	///
+	/// See: <https://www.rfc-editor.org/rfc/rfc8288.html>
+	Uri(UriBuf),
+
+	/// Opaque cursor returned in the response body, to be sent back with the
+	/// request for the next page.
+	Token(String),
+}
+
+impl PageCursor {
+	/// Returns the target of the `next` link of the given response headers,
+	/// resolved against `base`, if any.
+	pub fn from_link_header(base: &Uri, headers: &HeaderMap) -> Option<Self> {
+		headers
+			.get_all(LINK)
+			.iter()
+			.filter_map(|value| value.to_str().ok())
+			.find_map(next_link)
+			.and_then(|target| UriRef::new(target.as_bytes()).ok())
+			.and_then(|target| UriBuf::new(target.resolved(base).into_bytes()).ok())
+			.map(Self::Uri)
+	}
+}
+
+/// Returns the target of the first link with the `next` relation type in
+/// the given `Link` header value.
+fn next_link(value: &str) -> Option<&str> {
+	let mut rest = value;
+	while let Some(start) = rest.find('<') {
+		let end = start + rest[start..].find('>')?;
+		let target = &rest[start + 1..end];
+		rest = &rest[end + 1..];
+
+		let params_end = rest.find('<').unwrap_or(rest.len());
+		let is_next = rest[..params_end].split(';').any(|param| {
+			param.split_once('=').is_some_and(|(name, value)| {
+				name.trim().eq_ignore_ascii_case("rel")
+					&& value
+						.trim()
+						.trim_end_matches(',')
+						.trim_matches('"')
+						.split_whitespace()
+						.any(|rel| rel.eq_ignore_ascii_case("next"))
+			})
+		});
+
+		if is_next {
+			return Some(target);
+		}
+
+		rest = &rest[params_end..];
+	}
+
+	None
+}
+
+/// Page of items returned by a paginated list endpoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page<T> {
+	/// Items of this page.
+	pub items: Vec<T>,
+
+	/// Position of the next page, if this is not the last one.
+	pub next: Option<PageCursor>,
+}
+
+impl<T> Page<T> {
+	/// Creates a new page.
+	pub fn new(items: Vec<T>, next: Option<PageCursor>) -> Self {
+		Self { items, next }
+	}
+
+	/// Checks whether this is the last page.
+	pub fn is_last(&self) -> bool {
+		self.next.is_none()
+	}
+}
+
+/// Request to a list endpoint returning its items one [`Page`] at a time.
+///
+/// Implementations decode the items of a page, and its [`PageCursor`] from
+/// either the `Link` header (see [`PageCursor::from_link_header`]) or the
+/// response body, leaving the iteration over the pages to a [`Paginator`].
+pub trait PaginatedRequest<E>: HttpRequest<E, Response = Page<Self::Item>> {
+	/// Type of the listed items.
+	type Item;
+
+	/// Returns the request for the page at the given cursor.
+	fn with_cursor(&self, cursor: PageCursor) -> Self;
+}
+
+/// Iterator over the pages of a [`PaginatedRequest`].
+///
+/// At most [`DEFAULT_MAX_PAGES`] pages are fetched by default, so that a
+/// misbehaving server cannot make the client loop forever.
+///
+/// See [`RequestBuilder::paginate`](super::RequestBuilder::paginate).
+pub struct Paginator<E, T> {
+	endpoint: E,
+	request: Option<T>,
+	cursor: Option<PageCursor>,
+	pages: usize,
+	max_pages: usize,
+}
+
+impl<E, T> Paginator<E, T> {
+	/// Creates a new paginator starting with the given request.
+	pub fn new(endpoint: E, request: T) -> Self {
+		Self {
+			endpoint,
+			request: Some(request),
+			cursor: None,
+			pages: 0,
+			max_pages: DEFAULT_MAX_PAGES,
+		}
+	}
+
+	/// Sets the maximum number of pages fetched.
+	pub fn with_max_pages(self, max_pages: usize) -> Self {
+		Self { max_pages, ..self }
+	}
+
+	/// Returns the number of pages fetched so far.
+	pub fn pages(&self) -> usize {
+		self.pages
+	}
+
+	/// Checks whether the last page has been fetched.
+	pub fn is_done(&self) -> bool {
+		self.request.is_none()
+	}
+
+	/// Fetches the items of the next page, or returns `None` once the last
+	/// page has been fetched.
+	///
+	/// Fails if more than the maximum number of pages are requested, or if
+	/// the server returns the cursor of the current page as the next one.
+	pub async fn next_page(
+		&mut self,
+		http_client: &impl HttpClient,
+	) -> Result<Option<Vec<T::Item>>, OAuth2ClientError>
+	where
+		T: PaginatedRequest<E>,
+	{
+		let Some(request) = &self.request else {
+			return Ok(None);
+		};
+
+		if self.pages >= self.max_pages {
+			return Err(OAuth2ClientError::response(format!(
+				"list exceeds the maximum of {} pages",
+				self.max_pages
+			)));
+		}
+
+		let page = request.send(&self.endpoint, http_client).await?;
+		self.pages += 1;
+
+		match page.next {
+			Some(next) if self.cursor.as_ref() == Some(&next) => {
+				self.request = None;
+				return Err(OAuth2ClientError::response(
+					"server returned the same page cursor twice",
+				));
+			}
+			Some(next) => {
+				self.request = Some(request.with_cursor(next.clone()));
+				self.cursor = Some(next);
+			}
+			None => self.request = None,
+		}
+
+		Ok(Some(page.items))
+	}
+
+	/// Fetches all the remaining pages, and returns their items.
+	pub async fn collect(
+		mut self,
+		http_client: &impl HttpClient,
+	) -> Result<Vec<T::Item>, OAuth2ClientError>
+	where
+		T: PaginatedRequest<E>,
+	{
+		let mut items = Vec::new();
+		while let Some(page) = self.next_page(http_client).await? {
+			items.extend(page);
+		}
+
+		Ok(items)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use iref::uri;
+	use serde::Deserialize;
+
+	use crate::transport::{APPLICATION_JSON, NoContent, ResponseDecoder};
+
+	use super::*;
+
+	/// Client registration listing endpoint.
+	struct ListEndpoint {
+		uri: &'static Uri,
+	}
+
+	/// Client listing request, following cursors of both kinds.
+	struct ListClients {
+		cursor: Option<PageCursor>,
+	}
+
+	impl ListClients {
+		fn uri(&self, endpoint: &ListEndpoint) -> UriBuf {
+			match &self.cursor {
+				Some(PageCursor::Uri(uri)) => uri.clone(),
+				Some(PageCursor::Token(token)) => {
+					UriBuf::new(format!("{}?cursor={token}", endpoint.uri).into_bytes()).unwrap()
+				}
+				None => endpoint.uri.to_owned(),
+			}
+		}
+	}
+
+	#[derive(Deserialize)]
+	struct ListPayload {
+		clients: Vec<String>,
+		next_cursor: Option<String>,
+	}
+
+	impl HttpRequest<ListEndpoint> for ListClients {
+		type ContentType = NoContent;
+		type RequestBody<'b> = ();
+		type ResponsePayload = ListPayload;
+		type Response = Page<String>;
+
+		async fn build_request(
+			&self,
+			endpoint: &ListEndpoint,
+			_http_client: &impl HttpClient,
+		) -> Result<http::Request<()>, OAuth2ClientError> {
+			Ok(http::Request::builder()
+				.uri(self.uri(endpoint).as_str())
+				.body(())
+				.unwrap())
+		}
+
+		fn decode_response(
+			&self,
+			_endpoint: &ListEndpoint,
+			response: http::Response<Vec<u8>>,
+		) -> Result<http::Response<ListPayload>, OAuth2ClientError> {
+			ResponseDecoder::json(http::StatusCode::OK).decode(response)
+		}
+
+		async fn process_response(
+			&self,
+			endpoint: &ListEndpoint,
+			_http_client: &impl HttpClient,
+			response: http::Response<ListPayload>,
+		) -> Result<Page<String>, OAuth2ClientError> {
+			let next = PageCursor::from_link_header(&self.uri(endpoint), response.headers());
+			let payload = response.into_body();
+			Ok(Page::new(
+				payload.clients,
+				next.or(payload.next_cursor.map(PageCursor::Token)),
+			))
+		}
+	}
+
+	impl PaginatedRequest<ListEndpoint> for ListClients {
+		type Item = String;
+
+		fn with_cursor(&self, cursor: PageCursor) -> Self {
+			Self {
+				cursor: Some(cursor),
+			}
+		}
+	}
+
+	/// Server paginating with a `Link` header, then with a body cursor.
+	struct Server;
+
+	impl HttpClient for Server {
+		async fn send(
+			&self,
+			request: http::Request<Vec<u8>>,
+		) -> Result<http::Response<Vec<u8>>, OAuth2ClientError> {
+			let response =
+				http::Response::builder().header(http::header::CONTENT_TYPE, APPLICATION_JSON);
+			let (response, body) = match request.uri().path_and_query().unwrap().as_str() {
+				"/clients" => (
+					response.header(LINK, r#"</clients?page=2>; rel="next""#),
+					r#"{"clients":["s6BhdRkqt3","7gRsWc7nPb"]}"#,
+				),
+				"/clients?page=2" => (response, r#"{"clients":["xZ2m4C1a"],"next_cursor":"a3f9"}"#),
+				"/clients?cursor=a3f9" => (response, r#"{"clients":["Lq8dNw0k"]}"#),
+				"/loop" => (
+					response.header(LINK, r#"</loop>; rel="next""#),
+					r#"{"clients":[]}"#,
+				),
+				_ => return Err(OAuth2ClientError::request("not found")),
+			};
+
+			Ok(response.body(body.as_bytes().to_vec()).unwrap())
+		}
+	}
+
+	#[test]
+	fn link_header() {
+		let base = uri!("https://server.example.com/clients?page=2");
+		let mut headers = HeaderMap::new();
+		assert_eq!(PageCursor::from_link_header(base, &headers), None);
+
+		headers.insert(
+			LINK,
+			r#"<https://server.example.com/clients?page=1>; rel="prev", </clients?page=3>; title="Next, please"; REL="last next""#
+				.parse()
+				.unwrap(),
+		);
+		assert_eq!(
+			PageCursor::from_link_header(base, &headers),
+			Some(PageCursor::Uri(
+				uri!("https://server.example.com/clients?page=3").to_owned()
+			))
+		);
+	}
+
+	#[tokio::test]
+	async fn paginate() {
+		let endpoint = || ListEndpoint {
+			uri: uri!("https://server.example.com/clients"),
+		};
+
+		let mut paginator = Paginator::new(endpoint(), ListClients { cursor: None });
+		assert_eq!(
+			paginator.next_page(&Server).await.unwrap().unwrap(),
+			["s6BhdRkqt3", "7gRsWc7nPb"]
+		);
+		assert!(!paginator.is_done());
+
+		let clients = Paginator::new(endpoint(), ListClients { cursor: None })
+			.collect(&Server)
+			.await
+			.unwrap();
+		assert_eq!(
+			clients,
+			["s6BhdRkqt3", "7gRsWc7nPb", "xZ2m4C1a", "Lq8dNw0k"]
+		);
+
+		let paginator = Paginator::new(endpoint(), ListClients { cursor: None }).with_max_pages(2);
+		assert!(paginator.collect(&Server).await.is_err());
+
+		let looping = ListEndpoint {
+			uri: uri!("https://server.example.com/loop"),
+		};
+		let mut paginator = Paginator::new(looping, ListClients { cursor: None });
+		assert!(paginator.next_page(&Server).await.unwrap().is_some());
+		assert!(paginator.next_page(&Server).await.is_err());
+		assert!(paginator.is_done());
+		assert_eq!(paginator.pages(), 2);
+	}
+}
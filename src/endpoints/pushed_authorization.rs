@@ -1,23 +1,24 @@
 //! [RFC 9126]: OAuth 2.0 Pushed Authorization Requests.
 //!
 //! [RFC 9126]: <https://www.rfc-editor.org/rfc/rfc9126.html>
-use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime};
 
 use http::StatusCode;
-use iref::{
-	Uri, UriBuf,
-	uri::{Query, QueryBuf},
-};
+use iref::{Uri, UriBuf};
 use serde::{Deserialize, Serialize};
 
 use crate::{
 	ClientIdBuf,
 	client::{OAuth2Client, OAuth2ClientError},
 	endpoints::{
-		Endpoint, HttpRequest, RedirectRequest,
+		DEFAULT_MAX_REDIRECT_URI_LEN, Endpoint, HttpRequest, RedirectRequest, RedirectUriTooLong,
+		RequestBuilder,
 		authorization::{AnyAuthorizationEndpoint, AuthorizationEndpoint},
 	},
-	transport::{APPLICATION_JSON, HttpClient, WwwFormUrlEncoded, expect_content_type},
+	server::metadata::{AuthorizationServerMetadata, MissingEndpoint},
+	time::{Clock, LeewayConfig},
+	transport::{ContentType, HttpClient, ResponseDecoder, WwwFormUrlEncoded},
+	util::{ExistingQuery, NoExtension},
 };
 
 /// The OAuth 2.0 Pushed Authorization Request (PAR) endpoint.
@@ -33,12 +34,80 @@ pub struct PushedAuthorizationEndpoint<'a, C> {
 
 	/// The PAR endpoint URI.
 	pub uri: &'a Uri,
+
+	/// Statuses accepted for successful responses.
+	pub status_policy: ParStatusPolicy,
 }
 
 impl<'a, C> PushedAuthorizationEndpoint<'a, C> {
 	/// Creates a new PAR endpoint for the given client and URI.
 	pub fn new(client: &'a C, uri: &'a Uri) -> Self {
-		Self { client, uri }
+		Self {
+			client,
+			uri,
+			status_policy: ParStatusPolicy::Strict,
+		}
+	}
+
+	/// Creates a new PAR endpoint for the given client and URL.
+	///
+	/// Fails if the URL is not a valid URI.
+	#[cfg(feature = "url")]
+	pub fn from_url(
+		client: &'a C,
+		url: &'a url::Url,
+	) -> Result<Self, crate::util::UrlConversionError> {
+		use crate::util::UrlExt;
+		Ok(Self::new(client, url.as_uri()?))
+	}
+
+	/// Sets the statuses accepted for successful responses.
+	pub fn with_status_policy(self, status_policy: ParStatusPolicy) -> Self {
+		Self {
+			status_policy,
+			..self
+		}
+	}
+
+	/// Creates a new PAR endpoint for the given client, using the
+	/// `pushed_authorization_request_endpoint` advertised by the
+	/// authorization server metadata.
+	///
+	/// Fails if the authorization server does not advertise a PAR endpoint.
+	pub fn from_metadata<P>(
+		client: &'a C,
+		metadata: &'a AuthorizationServerMetadata<P>,
+	) -> Result<Self, MissingEndpoint> {
+		metadata
+			.pushed_authorization_request_endpoint
+			.as_deref()
+			.map(|uri| Self::new(client, uri))
+			.ok_or(MissingEndpoint("pushed_authorization_request_endpoint"))
+	}
+}
+
+/// Builder of a request sent to a [`PushedAuthorizationEndpoint`].
+pub type ParRequestBuilder<'a, C, T> = RequestBuilder<PushedAuthorizationEndpoint<'a, C>, T>;
+
+/// Statuses accepted for successful PAR responses.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ParStatusPolicy {
+	/// Only accept `201 Created`, as required by
+	/// [RFC 9126 Section 2.2](https://www.rfc-editor.org/rfc/rfc9126.html#section-2.2).
+	#[default]
+	Strict,
+
+	/// Also accept `200 OK`, returned by some authorization servers.
+	Lenient,
+}
+
+impl ParStatusPolicy {
+	/// Checks if the given status is accepted for a successful response.
+	pub fn accepts(self, status: StatusCode) -> bool {
+		match self {
+			Self::Strict => status == StatusCode::CREATED,
+			Self::Lenient => status == StatusCode::CREATED || status == StatusCode::OK,
+		}
 	}
 }
 
@@ -82,6 +151,7 @@ where
 /// parameters are POSTed as `application/x-www-form-urlencoded` instead of
 /// being appended to a redirect URI.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(transparent)]
 pub struct Pushed<T>(T);
 
@@ -111,18 +181,26 @@ where
 
 	fn decode_response(
 		&self,
-		_endpoint: &PushedAuthorizationEndpoint<'a, C>,
+		endpoint: &PushedAuthorizationEndpoint<'a, C>,
 		response: http::Response<Vec<u8>>,
 	) -> Result<http::Response<Self::ResponsePayload>, OAuth2ClientError> {
-		if response.status() != StatusCode::CREATED {
-			return Err(OAuth2ClientError::server(response.status()));
+		let status = response.status();
+		let expected = if endpoint.status_policy.accepts(status) {
+			status
+		} else {
+			StatusCode::CREATED
+		};
+
+		if expected != StatusCode::CREATED {
+			log::warn!("accepting PAR response with non-standard status: {status}");
 		}
 
-		expect_content_type(response.headers(), &APPLICATION_JSON)?;
-
-		let body = serde_json::from_slice(response.body()).map_err(OAuth2ClientError::response)?;
-
-		Ok(response.map(|_| body))
+		let mut response: http::Response<PushedAuthorizationResponse> =
+			ResponseDecoder::json(expected)
+				.with_error_responses()
+				.decode(response)?;
+		response.body_mut().status = status;
+		Ok(response)
 	}
 
 	async fn process_response(
@@ -140,44 +218,356 @@ where
 /// Contains an opaque `request_uri` that the client uses at the
 /// authorization endpoint, along with an expiration time.
 #[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct PushedAuthorizationResponse {
 	/// Opaque URI referencing the pushed authorization request.
+	#[cfg_attr(feature = "schemars", schemars(with = "String"))]
 	pub request_uri: UriBuf,
 
 	/// Lifetime in seconds of the `request_uri`.
 	pub expires_in: u64,
+
+	/// HTTP status of the response.
+	///
+	/// Always `201 Created`, unless the response was accepted from a lenient
+	/// server with [`ParStatusPolicy::Lenient`].
+	#[serde(skip, default = "created")]
+	pub status: StatusCode,
+}
+
+fn created() -> StatusCode {
+	StatusCode::CREATED
 }
 
 impl PushedAuthorizationResponse {
-	/// Builds the authorization URI for the given authorization endpoint.
+	/// Creates a new response for the given `request_uri`, valid for
+	/// `expires_in`.
 	///
-	/// The returned URI contains the `client_id` and `request_uri` as query
-	/// parameters, ready to redirect the user-agent to.
-	pub fn for_endpoint<'a, C>(&self, endpoint: &AuthorizationEndpoint<'a, C>) -> UriBuf
+	/// The lifetime is serialized in whole seconds, sub-second precision is
+	/// discarded.
+	pub fn new(request_uri: UriBuf, expires_in: Duration) -> Self {
+		Self {
+			request_uri,
+			expires_in: expires_in.as_secs(),
+			status: StatusCode::CREATED,
+		}
+	}
+
+	/// Returns the lifetime of the `request_uri`.
+	pub fn expires_in(&self) -> Duration {
+		Duration::from_secs(self.expires_in)
+	}
+
+	/// Starts building the authorization request referencing this pushed
+	/// request, for the given authorization endpoint.
+	///
+	/// The request contains the `client_id` and `request_uri` parameters. It
+	/// can be extended with additional parameters, as long as they are
+	/// explicitly allowed with
+	/// [`RequestBuilder::with_allowed_parameters`]. The redirect URI is built
+	/// with [`RequestBuilder::into_pushed_redirect_uri`].
+	pub fn authorize_url<'a, C>(
+		&self,
+		endpoint: AuthorizationEndpoint<'a, C>,
+	) -> RequestBuilder<PushedAuthorizationRedirect<'a, C>, PushedAuthorizationRequest<NoExtension>>
 	where
 		C: OAuth2Client,
 	{
-		let mut uri = endpoint.uri.to_owned();
-
-		let query = QueryBuf::new(
-			serde_html_form::to_string(PushedAuthorizationRequest {
+		RequestBuilder::new(
+			PushedAuthorizationRedirect::new(endpoint),
+			PushedAuthorizationRequest {
 				client_id: endpoint.client.client_id().to_owned(),
 				request_uri: self.request_uri.clone(),
-				ext: serde_html_form::from_str::<BTreeMap<String, String>>(
-					uri.query().map(Query::as_str).unwrap_or_default(),
-				)
-				.unwrap(),
-			})
-			.unwrap()
-			.into_bytes(),
+				ext: NoExtension {},
+			},
+		)
+	}
+}
+
+impl<'a, C, T> ParRequestBuilder<'a, C, Pushed<T>>
+where
+	C: OAuth2Client,
+	T: RedirectRequest,
+{
+	/// Pushes the request, remembering when the returned `request_uri`
+	/// expires according to the given clock.
+	pub async fn send_expiring(
+		self,
+		http_client: &impl HttpClient,
+		clock: &impl Clock,
+	) -> Result<PushedAuthorization<T>, OAuth2ClientError> {
+		let response = self.request.send(&self.endpoint, http_client).await?;
+		Ok(PushedAuthorization::new(self.request, response, clock))
+	}
+}
+
+/// Pushed authorization request, along with the expiration time of its
+/// `request_uri`.
+///
+/// The `request_uri` is only valid for a short time (typically a minute).
+/// Redirecting the user-agent to the authorization endpoint after it expired
+/// only shows an error to the end-user, so the redirect URI is not built past
+/// the expiration time. The request can be pushed again with
+/// [`repush`](Self::repush).
+#[derive(Debug)]
+pub struct PushedAuthorization<T> {
+	request: Pushed<T>,
+	response: PushedAuthorizationResponse,
+	expires_at: SystemTime,
+}
+
+impl<T> PushedAuthorization<T> {
+	/// Creates a new pushed authorization from the given request and the
+	/// response of the PAR endpoint, received now according to the given
+	/// clock.
+	pub fn new(
+		request: Pushed<T>,
+		response: PushedAuthorizationResponse,
+		clock: &impl Clock,
+	) -> Self {
+		let expires_at = clock.deadline_in(response.expires_in());
+		Self {
+			request,
+			response,
+			expires_at,
+		}
+	}
+
+	/// Returns the pushed request.
+	pub fn request(&self) -> &T {
+		&self.request.0
+	}
+
+	/// Returns the response of the PAR endpoint.
+	pub fn response(&self) -> &PushedAuthorizationResponse {
+		&self.response
+	}
+
+	/// Returns the expiration time of the `request_uri`.
+	pub fn expires_at(&self) -> SystemTime {
+		self.expires_at
+	}
+
+	/// Checks whether the `request_uri` expires within the given leeway.
+	pub fn is_expired(&self, clock: &impl Clock, leeway: &LeewayConfig) -> bool {
+		leeway.expires_soon(clock, self.expires_at)
+	}
+
+	/// Starts building the authorization request referencing the pushed
+	/// request, for the given authorization endpoint.
+	///
+	/// Fails if the `request_uri` expires within the given leeway. See
+	/// [`PushedAuthorizationResponse::authorize_url`].
+	pub fn authorize_url<'a, C>(
+		&self,
+		endpoint: AuthorizationEndpoint<'a, C>,
+		clock: &impl Clock,
+		leeway: &LeewayConfig,
+	) -> Result<
+		RequestBuilder<PushedAuthorizationRedirect<'a, C>, PushedAuthorizationRequest<NoExtension>>,
+		RequestUriExpired,
+	>
+	where
+		C: OAuth2Client,
+	{
+		if self.is_expired(clock, leeway) {
+			return Err(RequestUriExpired);
+		}
+
+		Ok(self.response.authorize_url(endpoint))
+	}
+
+	/// Pushes the request again to the given PAR endpoint, replacing the
+	/// expired `request_uri` with a fresh one.
+	pub async fn repush<C>(
+		&mut self,
+		endpoint: &PushedAuthorizationEndpoint<'_, C>,
+		http_client: &impl HttpClient,
+		clock: &impl Clock,
+	) -> Result<(), OAuth2ClientError>
+	where
+		C: OAuth2Client,
+		T: RedirectRequest,
+	{
+		let response = self.request.send(endpoint, http_client).await?;
+		self.expires_at = clock.deadline_in(response.expires_in());
+		self.response = response;
+		Ok(())
+	}
+}
+
+/// Error returned when building the authorization request referencing an
+/// expired `request_uri`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("pushed authorization request expired")]
+pub struct RequestUriExpired;
+
+/// Authorization endpoint, reached after a successful PAR exchange.
+///
+/// [RFC 9126 Section 4](https://www.rfc-editor.org/rfc/rfc9126.html#section-4)
+/// requires the authorization request to carry only the `client_id` and
+/// `request_uri` parameters, all the other parameters being pushed. Some
+/// authorization servers accept a few more (e.g. `prompt`), which must be
+/// explicitly allowed.
+///
+/// This is not an [`Endpoint`], so that the redirect URI can only be built
+/// with [`RequestBuilder::into_pushed_redirect_uri`], enforcing this policy.
+pub struct PushedAuthorizationRedirect<'a, C> {
+	/// The authorization endpoint.
+	pub endpoint: AuthorizationEndpoint<'a, C>,
+
+	/// Parameters allowed in addition to `client_id` and `request_uri`.
+	pub allowed_parameters: Vec<String>,
+}
+
+impl<'a, C> PushedAuthorizationRedirect<'a, C> {
+	/// Creates a new redirection to the given authorization endpoint, only
+	/// allowing the `client_id` and `request_uri` parameters.
+	pub fn new(endpoint: AuthorizationEndpoint<'a, C>) -> Self {
+		Self {
+			endpoint,
+			allowed_parameters: Vec::new(),
+		}
+	}
+
+	/// Checks that the given parameter is allowed.
+	pub fn allows(&self, name: &str) -> bool {
+		PUSHED_AUTHORIZATION_PARAMETERS.contains(&name)
+			|| self.allowed_parameters.iter().any(|p| p == name)
+	}
+}
+
+/// Parameters of an authorization request referencing a pushed request.
+const PUSHED_AUTHORIZATION_PARAMETERS: [&str; 2] = ["client_id", "request_uri"];
+
+/// Error returned when building the redirect URI of an authorization request
+/// referencing a pushed request.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PushedRedirectError {
+	/// A parameter is not allowed alongside `request_uri`.
+	#[error("parameter `{0}` is not allowed in a pushed authorization request redirect")]
+	ForbiddenParameter(String),
+
+	/// The redirect URI is too long.
+	#[error(transparent)]
+	TooLong(#[from] RedirectUriTooLong),
+}
+
+impl<'a, C, T> RequestBuilder<PushedAuthorizationRedirect<'a, C>, T> {
+	/// Allows the given parameters in addition to `client_id` and
+	/// `request_uri`.
+	pub fn with_allowed_parameters(
+		mut self,
+		names: impl IntoIterator<Item = impl Into<String>>,
+	) -> Self {
+		self.endpoint
+			.allowed_parameters
+			.extend(names.into_iter().map(Into::into));
+		self
+	}
+
+	/// Converts this request builder into a redirect URI.
+	///
+	/// Fails if the request contains a parameter that is not allowed, or if
+	/// the resulting URI is longer than [`DEFAULT_MAX_REDIRECT_URI_LEN`].
+	/// Query parameters of the authorization endpoint URI are preserved.
+	pub fn into_pushed_redirect_uri(self) -> Result<UriBuf, PushedRedirectError>
+	where
+		C: OAuth2Client,
+		T: RedirectRequest,
+	{
+		let query = WwwFormUrlEncoded::encode(&self.request.build_query());
+		let params: Vec<(String, String)> = serde_html_form::from_bytes(&query)
+			// UNWRAP SAFETY: `query` was just encoded as a form.
+			.unwrap();
+
+		if let Some((name, _)) = params
+			.into_iter()
+			.find(|(name, _)| !self.endpoint.allows(name))
+		{
+			return Err(PushedRedirectError::ForbiddenParameter(name));
+		}
+
+		Ok(self
+			.request
+			.redirect_uri_with_max_len(&self.endpoint.endpoint, DEFAULT_MAX_REDIRECT_URI_LEN)?)
+	}
+}
+
+/// Error returned by
+/// [`RequestBuilder::into_redirect_uri_or_pushed`].
+#[derive(Debug, thiserror::Error)]
+pub enum ParFallbackError {
+	/// The request must be pushed, but the authorization server does not
+	/// advertise a PAR endpoint.
+	#[error(transparent)]
+	MissingEndpoint(#[from] MissingEndpoint),
+
+	/// The pushed authorization request failed.
+	#[error(transparent)]
+	Push(#[from] OAuth2ClientError),
+
+	/// The redirect URI referencing the pushed request could not be built.
+	#[error(transparent)]
+	Redirect(#[from] PushedRedirectError),
+}
+
+impl<'a, C, T> RequestBuilder<AuthorizationEndpoint<'a, C>, T>
+where
+	C: OAuth2Client,
+	T: RedirectRequest,
+{
+	/// Converts this request builder into a redirect URI, pushing the request
+	/// to the PAR endpoint of the authorization server when needed.
+	///
+	/// The request is pushed if the authorization server requires it
+	/// (`require_pushed_authorization_requests`), or if the redirect URI
+	/// carrying its parameters (e.g. a long `scope` or rich authorization
+	/// details) would be longer than [`DEFAULT_MAX_REDIRECT_URI_LEN`]. The
+	/// returned URI then only references the pushed request.
+	pub async fn into_redirect_uri_or_pushed<P>(
+		self,
+		metadata: &AuthorizationServerMetadata<P>,
+		http_client: &impl HttpClient,
+	) -> Result<UriBuf, ParFallbackError> {
+		self.into_redirect_uri_or_pushed_with_max_len(
+			metadata,
+			http_client,
+			DEFAULT_MAX_REDIRECT_URI_LEN,
 		)
-		.unwrap();
+		.await
+	}
 
-		if !query.is_empty() {
-			uri.set_query(Some(&query));
+	/// Converts this request builder into a redirect URI, pushing the request
+	/// to the PAR endpoint of the authorization server if it is required, or
+	/// if the redirect URI would be longer than `max_len` bytes.
+	///
+	/// See
+	/// [`into_redirect_uri_or_pushed`](Self::into_redirect_uri_or_pushed).
+	pub async fn into_redirect_uri_or_pushed_with_max_len<P>(
+		self,
+		metadata: &AuthorizationServerMetadata<P>,
+		http_client: &impl HttpClient,
+		max_len: usize,
+	) -> Result<UriBuf, ParFallbackError> {
+		if !metadata.require_pushed_authorization_requests {
+			match self
+				.request
+				.redirect_uri_with_max_len(&self.endpoint, max_len)
+			{
+				Ok(uri) => return Ok(uri),
+				Err(e) => log::debug!("pushing authorization request: {e}"),
+			}
 		}
 
-		uri
+		let par_endpoint =
+			PushedAuthorizationEndpoint::from_metadata(self.endpoint.client, metadata)?;
+		let response = Pushed(self.request)
+			.send(&par_endpoint, http_client)
+			.await?;
+		Ok(response
+			.authorize_url(self.endpoint)
+			.into_pushed_redirect_uri()?)
 	}
 }
 
@@ -186,11 +576,13 @@ impl PushedAuthorizationResponse {
 /// This is the query sent to the authorization endpoint after a successful
 /// PAR exchange, containing the `client_id` and the opaque `request_uri`.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
-pub struct PushedAuthorizationRequest<E = BTreeMap<String, String>> {
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct PushedAuthorizationRequest<E = ExistingQuery> {
 	/// The client identifier.
 	pub client_id: ClientIdBuf,
 
 	/// The opaque URI returned by the PAR endpoint.
+	#[cfg_attr(feature = "schemars", schemars(with = "String"))]
 	pub request_uri: UriBuf,
 
 	/// Additional extension parameters.
@@ -198,23 +590,237 @@ pub struct PushedAuthorizationRequest<E = BTreeMap<String, String>> {
 	pub ext: E,
 }
 
+impl RedirectRequest for PushedAuthorizationRequest<NoExtension> {
+	type RequestBody<'b>
+		= &'b Self
+	where
+		Self: 'b;
+
+	fn build_query(&self) -> Self::RequestBody<'_> {
+		self
+	}
+}
+
 #[cfg(feature = "axum")]
 mod axum {
-	use ::axum::{
-		body::Body,
-		http::header,
-		response::{IntoResponse, Response},
-	};
+	use ::axum::response::{IntoResponse, Response};
+
+	use crate::server::json_response;
 
 	use super::*;
 
 	impl IntoResponse for PushedAuthorizationResponse {
 		fn into_response(self) -> Response {
-			Response::builder()
-				.status(StatusCode::CREATED)
-				.header(header::CONTENT_TYPE, APPLICATION_JSON)
-				.body(Body::from(serde_json::to_vec(&self).unwrap()))
+			json_response(Response::builder().status(self.status), &self)
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use iref::uri;
+
+	use crate::{
+		AddAudience, ClientId, ScopeBuf, audience, client_id, endpoints::token::TokenResponse,
+		grant::authorization_code::ExchangeCode, issuer, time::MockClock,
+		transport::APPLICATION_JSON,
+	};
+
+	use super::*;
+
+	struct Client;
+
+	impl OAuth2Client for Client {
+		type TokenResponse = TokenResponse;
+
+		fn client_id(&self) -> &ClientId {
+			client_id!("s6BhdRkqt3")
+		}
+	}
+
+	fn response() -> PushedAuthorizationResponse {
+		PushedAuthorizationResponse::new(
+			UriBuf::new(b"urn:example:bwc4JK-ESC0w8acc191e-Y1LTC2".to_vec()).unwrap(),
+			Duration::from_secs(60),
+		)
+	}
+
+	#[test]
+	fn forbidden_parameter() {
+		let endpoint =
+			AuthorizationEndpoint::new(&Client, uri!("https://server.example.com/authorize"));
+
+		assert_eq!(
+			response()
+				.authorize_url(endpoint)
+				.with_audience([audience!("https://api.example.com/").to_owned()])
+				.into_pushed_redirect_uri(),
+			Err(PushedRedirectError::ForbiddenParameter(
+				"audience".to_owned()
+			))
+		);
+	}
+
+	#[test]
+	fn allowed_parameter() {
+		let endpoint =
+			AuthorizationEndpoint::new(&Client, uri!("https://server.example.com/authorize"));
+
+		assert_eq!(
+			response()
+				.authorize_url(endpoint)
+				.with_audience([audience!("https://api.example.com/").to_owned()])
+				.with_allowed_parameters(["audience"])
+				.into_pushed_redirect_uri()
 				.unwrap()
+				.as_str(),
+			"https://server.example.com/authorize?audience=https%3A%2F%2Fapi.example.com%2F&client_id=s6BhdRkqt3&request_uri=urn%3Aexample%3Abwc4JK-ESC0w8acc191e-Y1LTC2"
+		);
+	}
+
+	/// Authorization server answering PAR requests with the given status.
+	struct Server(StatusCode);
+
+	impl HttpClient for Server {
+		async fn send(
+			&self,
+			_request: http::Request<Vec<u8>>,
+		) -> Result<http::Response<Vec<u8>>, OAuth2ClientError> {
+			Ok(http::Response::builder()
+				.status(self.0)
+				.header(http::header::CONTENT_TYPE, APPLICATION_JSON)
+				.body(
+					serde_json::to_vec(&serde_json::json!({
+						"request_uri": "urn:example:bwc4JK-ESC0w8acc191e-Y1LTC2",
+						"expires_in": 60
+					}))
+					.unwrap(),
+				)
+				.unwrap())
 		}
 	}
+
+	#[tokio::test]
+	async fn par_fallback() {
+		let mut metadata = AuthorizationServerMetadata::<NoExtension>::new(
+			issuer!("https://server.example.com").to_owned(),
+		)
+		.with_pushed_authorization_request_endpoint(
+			UriBuf::new(b"https://server.example.com/par".to_vec()).unwrap(),
+		);
+		let endpoint =
+			AuthorizationEndpoint::new(&Client, uri!("https://server.example.com/authorize"));
+		let request = || {
+			endpoint.authorize_url(
+				None,
+				Some(ScopeBuf::new("openid profile".to_owned()).unwrap()),
+			)
+		};
+		let server = Server(StatusCode::CREATED);
+		let pushed = "https://server.example.com/authorize?client_id=s6BhdRkqt3&request_uri=urn%3Aexample%3Abwc4JK-ESC0w8acc191e-Y1LTC2";
+
+		let uri = request()
+			.into_redirect_uri_or_pushed(&metadata, &server)
+			.await
+			.unwrap();
+		assert_eq!(
+			uri.as_str(),
+			"https://server.example.com/authorize?response_type=code&client_id=s6BhdRkqt3&scope=openid+profile"
+		);
+
+		let uri = request()
+			.into_redirect_uri_or_pushed_with_max_len(&metadata, &server, 64)
+			.await
+			.unwrap();
+		assert_eq!(uri.as_str(), pushed);
+
+		metadata.require_pushed_authorization_requests = true;
+		let uri = request()
+			.into_redirect_uri_or_pushed(&metadata, &server)
+			.await
+			.unwrap();
+		assert_eq!(uri.as_str(), pushed);
+
+		metadata.pushed_authorization_request_endpoint = None;
+		assert!(matches!(
+			request()
+				.into_redirect_uri_or_pushed(&metadata, &server)
+				.await,
+			Err(ParFallbackError::MissingEndpoint(_))
+		));
+	}
+
+	#[tokio::test]
+	async fn status_policy() {
+		let uri = uri!("https://server.example.com/par");
+		let endpoint = PushedAuthorizationEndpoint::new(&Client, uri);
+		let request = Pushed(PushedAuthorizationRequest {
+			client_id: client_id!("s6BhdRkqt3").to_owned(),
+			request_uri: response().request_uri,
+			ext: NoExtension {},
+		});
+
+		let response = request
+			.send(&endpoint, &Server(StatusCode::CREATED))
+			.await
+			.unwrap();
+		assert_eq!(response.status, StatusCode::CREATED);
+
+		assert!(matches!(
+			request.send(&endpoint, &Server(StatusCode::OK)).await,
+			Err(OAuth2ClientError::ServerError(StatusCode::OK))
+		));
+
+		let endpoint = endpoint.with_status_policy(ParStatusPolicy::Lenient);
+		let response = request
+			.send(&endpoint, &Server(StatusCode::OK))
+			.await
+			.unwrap();
+		assert_eq!(response.status, StatusCode::OK);
+		assert_eq!(response.expires_in(), Duration::from_secs(60));
+	}
+
+	#[tokio::test]
+	async fn expiring_request_uri() {
+		let clock = MockClock::from_unix_timestamp(1000);
+		let leeway = LeewayConfig::new(Duration::from_secs(10));
+		let par_endpoint =
+			PushedAuthorizationEndpoint::new(&Client, uri!("https://server.example.com/par"));
+		let endpoint =
+			AuthorizationEndpoint::new(&Client, uri!("https://server.example.com/authorize"));
+		let server = Server(StatusCode::CREATED);
+
+		let mut pushed = par_endpoint
+			.authorize_url(None, None)
+			.send_expiring(&server, &clock)
+			.await
+			.unwrap();
+		assert_eq!(
+			pushed.expires_at(),
+			SystemTime::UNIX_EPOCH + Duration::from_secs(1060)
+		);
+		assert_eq!(
+			pushed
+				.authorize_url(endpoint, &clock, &leeway)
+				.unwrap()
+				.into_pushed_redirect_uri()
+				.unwrap()
+				.as_str(),
+			"https://server.example.com/authorize?client_id=s6BhdRkqt3&request_uri=urn%3Aexample%3Abwc4JK-ESC0w8acc191e-Y1LTC2"
+		);
+
+		clock.advance(Duration::from_secs(55));
+		assert!(pushed.is_expired(&clock, &leeway));
+		assert_eq!(
+			pushed.authorize_url(endpoint, &clock, &leeway).err(),
+			Some(RequestUriExpired)
+		);
+
+		pushed.repush(&par_endpoint, &server, &clock).await.unwrap();
+		assert_eq!(
+			pushed.expires_at(),
+			SystemTime::UNIX_EPOCH + Duration::from_secs(1115)
+		);
+		assert!(pushed.authorize_url(endpoint, &clock, &leeway).is_ok());
+	}
 }
@@ -0,0 +1,358 @@
+use std::ops::Deref;
+
+use http::header::CONTENT_TYPE;
+
+use crate::{
+	client::OAuth2ClientError,
+	transport::{ContentType, HttpClient},
+};
+
+use super::{HttpRequest, RequestExtensions, TelemetryRecorder};
+
+/// Default maximum number of times a request is resent by [`WithRetry`].
+pub const DEFAULT_MAX_RETRIES: u32 = 1;
+
+/// Decision of a [`RetryOnResponse`] hook.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RetryDecision {
+	/// The response is processed as is.
+	#[default]
+	Proceed,
+
+	/// The request is rebuilt and sent again.
+	Retry,
+
+	/// The request is rebuilt and sent again with a server-provided nonce
+	/// (e.g. DPoP).
+	NonceRetry,
+}
+
+impl RetryDecision {
+	/// Checks whether the request must be sent again.
+	pub fn is_retry(&self) -> bool {
+		!matches!(self, Self::Proceed)
+	}
+}
+
+/// Hook deciding whether a request must be rebuilt and sent again after
+/// receiving a response.
+///
+/// Extensions requiring an additional HTTP exchange, such as DPoP nonces,
+/// step-up authentication or rotating client attestations, inspect the raw
+/// response before it is decoded, update their own state (e.g. store the
+/// nonce provided by the server) and request a retry. The request is then
+/// rebuilt with [`HttpRequest::build_request`], picking up the updated
+/// state, and sent again by [`WithRetry`], up to a bounded number of times.
+///
+/// Implemented by closures, and by pairs of hooks which are both consulted
+/// for every response.
+pub trait RetryOnResponse<E> {
+	/// Inspects a raw response of `endpoint` and decides whether the request
+	/// must be sent again.
+	fn retry_on_response(&self, endpoint: &E, response: &http::Response<Vec<u8>>) -> RetryDecision;
+}
+
+impl<E, F> RetryOnResponse<E> for F
+where
+	F: Fn(&E, &http::Response<Vec<u8>>) -> RetryDecision,
+{
+	fn retry_on_response(&self, endpoint: &E, response: &http::Response<Vec<u8>>) -> RetryDecision {
+		self(endpoint, response)
+	}
+}
+
+impl<E, A, B> RetryOnResponse<E> for (A, B)
+where
+	A: RetryOnResponse<E>,
+	B: RetryOnResponse<E>,
+{
+	fn retry_on_response(&self, endpoint: &E, response: &http::Response<Vec<u8>>) -> RetryDecision {
+		let a = self.0.retry_on_response(endpoint, response);
+		let b = self.1.retry_on_response(endpoint, response);
+		a.max(b)
+	}
+}
+
+/// Response payload of a [`WithRetry`] request.
+pub enum RetryPayload<P> {
+	/// The response was decoded by the inner request.
+	Decoded(P),
+
+	/// The response must be sent again, as decided by the retry hook.
+	Retry(Vec<u8>, RetryDecision),
+}
+
+/// Wrapper resending a request when requested by a [`RetryOnResponse`] hook.
+///
+/// The request is rebuilt by the inner request and the layers below this
+/// one. Layers applied on top of this one are not applied again, except for
+/// the request extensions which are carried over to the new request, so
+/// that a [`WithTelemetry`](super::WithTelemetry) layer counts the retries.
+///
+/// See [`RequestBuilder::with_retry`](super::RequestBuilder::with_retry).
+pub struct WithRetry<R, T> {
+	/// The inner request.
+	pub value: T,
+
+	/// The retry hook.
+	pub hook: R,
+
+	max_retries: u32,
+}
+
+impl<R, T> WithRetry<R, T> {
+	/// Creates a new [`WithRetry`] wrapping the given request, resending it
+	/// at most [`DEFAULT_MAX_RETRIES`] times.
+	pub fn new(value: T, hook: R) -> Self {
+		Self {
+			value,
+			hook,
+			max_retries: DEFAULT_MAX_RETRIES,
+		}
+	}
+
+	/// Sets the maximum number of times the request is resent.
+	pub fn with_max_retries(self, max_retries: u32) -> Self {
+		Self {
+			max_retries,
+			..self
+		}
+	}
+
+	/// Returns the maximum number of times the request is resent.
+	pub fn max_retries(&self) -> u32 {
+		self.max_retries
+	}
+}
+
+impl<R, T> Deref for WithRetry<R, T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		&self.value
+	}
+}
+
+impl<E, R, T> HttpRequest<E> for WithRetry<R, T>
+where
+	T: HttpRequest<E>,
+	R: RetryOnResponse<E>,
+{
+	type ContentType = T::ContentType;
+	type RequestBody<'b>
+		= T::RequestBody<'b>
+	where
+		Self: 'b;
+	type Response = T::Response;
+	type ResponsePayload = RetryPayload<T::ResponsePayload>;
+
+	async fn build_request(
+		&self,
+		endpoint: &E,
+		http_client: &impl HttpClient,
+	) -> Result<http::Request<Self::RequestBody<'_>>, OAuth2ClientError> {
+		self.value.build_request(endpoint, http_client).await
+	}
+
+	fn decode_response(
+		&self,
+		endpoint: &E,
+		response: http::Response<Vec<u8>>,
+	) -> Result<http::Response<Self::ResponsePayload>, OAuth2ClientError> {
+		match self.hook.retry_on_response(endpoint, &response) {
+			RetryDecision::Proceed => Ok(self
+				.value
+				.decode_response(endpoint, response)?
+				.map(RetryPayload::Decoded)),
+			decision => Ok(response.map(|body| RetryPayload::Retry(body, decision))),
+		}
+	}
+
+	async fn process_response(
+		&self,
+		endpoint: &E,
+		http_client: &impl HttpClient,
+		response: http::Response<Self::ResponsePayload>,
+	) -> Result<Self::Response, OAuth2ClientError> {
+		let mut retries = 0;
+		let mut response = response;
+
+		loop {
+			let (parts, payload) = response.into_parts();
+			let payload = match payload {
+				RetryPayload::Decoded(payload) => payload,
+				RetryPayload::Retry(body, decision) => {
+					let raw = http::Response::from_parts(parts, body);
+
+					if retries >= self.max_retries {
+						log::warn!("giving up after {retries} retries");
+						let decoded = self.value.decode_response(endpoint, raw)?;
+						return self
+							.value
+							.process_response(endpoint, http_client, decoded)
+							.await;
+					}
+
+					retries += 1;
+					if let Some(recorder) = TelemetryRecorder::of(&raw) {
+						match decision {
+							RetryDecision::NonceRetry => recorder.record_nonce_retry(),
+							_ => recorder.record_retry(),
+						}
+					}
+
+					let extensions = raw
+						.extensions()
+						.get::<RequestExtensions>()
+						.cloned()
+						.unwrap_or_default();
+					response = self.resend(endpoint, http_client, extensions).await?;
+					continue;
+				}
+			};
+
+			return self
+				.value
+				.process_response(
+					endpoint,
+					http_client,
+					http::Response::from_parts(parts, payload),
+				)
+				.await;
+		}
+	}
+}
+
+impl<R, T> WithRetry<R, T> {
+	/// Rebuilds and sends the request, with the extensions of the original
+	/// request.
+	async fn resend<E>(
+		&self,
+		endpoint: &E,
+		http_client: &impl HttpClient,
+		RequestExtensions(extensions): RequestExtensions,
+	) -> Result<http::Response<RetryPayload<T::ResponsePayload>>, OAuth2ClientError>
+	where
+		T: HttpRequest<E>,
+		R: RetryOnResponse<E>,
+	{
+		let mut request = self.value.build_request(endpoint, http_client).await?;
+		if let Some(content_type) = T::ContentType::VALUE {
+			request.headers_mut().insert(CONTENT_TYPE, content_type);
+		}
+		let mut encoded_request = request.map(|body| T::ContentType::encode(&body));
+
+		// Extensions of the rebuilt request take precedence.
+		let mut extensions = extensions;
+		extensions.extend(encoded_request.extensions().clone());
+		*encoded_request.extensions_mut() = extensions.clone();
+
+		let mut response = http_client.send(encoded_request).await?;
+		response
+			.extensions_mut()
+			.insert(RequestExtensions(extensions));
+		self.decode_response(endpoint, response)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::atomic::{AtomicUsize, Ordering};
+
+	use http::StatusCode;
+	use iref::uri;
+
+	use crate::{
+		ClientId,
+		client::OAuth2Client,
+		client_id,
+		endpoints::{
+			RequestBuilder,
+			token::{TokenEndpoint, TokenResponse},
+		},
+		grant::refresh::RefreshTokenRequest,
+	};
+
+	use super::*;
+
+	struct Client;
+
+	impl OAuth2Client for Client {
+		type TokenResponse = TokenResponse;
+
+		fn client_id(&self) -> &ClientId {
+			client_id!("s6BhdRkqt3")
+		}
+	}
+
+	/// Token endpoint rejecting the first `n` requests with a
+	/// `use_dpop_nonce` error.
+	struct NonceServer {
+		rejections: usize,
+		requests: AtomicUsize,
+	}
+
+	impl HttpClient for NonceServer {
+		async fn send(
+			&self,
+			_request: http::Request<Vec<u8>>,
+		) -> Result<http::Response<Vec<u8>>, OAuth2ClientError> {
+			let n = self.requests.fetch_add(1, Ordering::SeqCst);
+			let (status, body) = if n < self.rejections {
+				(
+					StatusCode::BAD_REQUEST,
+					serde_json::json!({ "error": "use_dpop_nonce" }),
+				)
+			} else {
+				(
+					StatusCode::OK,
+					serde_json::json!({ "access_token": "token", "token_type": "DPoP" }),
+				)
+			};
+
+			Ok(http::Response::builder()
+				.status(status)
+				.header(CONTENT_TYPE, "application/json")
+				.body(serde_json::to_vec(&body).unwrap())
+				.unwrap())
+		}
+	}
+
+	fn hook<E>(_: &E, response: &http::Response<Vec<u8>>) -> RetryDecision {
+		if response.status() == StatusCode::BAD_REQUEST {
+			RetryDecision::NonceRetry
+		} else {
+			RetryDecision::Proceed
+		}
+	}
+
+	#[tokio::test]
+	async fn bounded_retries() {
+		let request = || {
+			RequestBuilder::new(
+				TokenEndpoint::new(&Client, uri!("https://server.example.com/token")),
+				RefreshTokenRequest::new(None, "tGzv3JOkF0XG5Qx2TlKWIA".to_owned(), None),
+			)
+			.with_retry(hook)
+			.with_telemetry()
+		};
+
+		let server = NonceServer {
+			rejections: 1,
+			requests: AtomicUsize::new(0),
+		};
+		let response = request().send(&server).await.unwrap();
+		assert_eq!(response.access_token.as_str(), "token");
+		assert_eq!(response.telemetry.retries, 1);
+		assert!(response.telemetry.nonce_retry);
+		assert_eq!(server.requests.load(Ordering::SeqCst), 2);
+
+		let server = NonceServer {
+			rejections: 2,
+			requests: AtomicUsize::new(0),
+		};
+		let error = request().send(&server).await.unwrap_err();
+		assert_eq!(error.error_code(), Some("use_dpop_nonce"));
+		assert_eq!(server.requests.load(Ordering::SeqCst), 2);
+	}
+}
@@ -0,0 +1,209 @@
+//! [RFC 7009]: OAuth 2.0 Token Revocation.
+//!
+//! [RFC 7009]: <https://datatracker.ietf.org/doc/html/rfc7009>
+use http::StatusCode;
+use iref::Uri;
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+use crate::{
+	client::{OAuth2Client, OAuth2ClientError},
+	endpoints::{Endpoint, HttpRequest, RequestBuilder, token::TokenTypeHint},
+	server::metadata::{AuthorizationServerMetadata, MissingEndpoint},
+	transport::{HttpClient, WwwFormUrlEncoded},
+};
+
+/// The OAuth 2.0 Token Revocation endpoint.
+///
+/// This endpoint allows clients to notify the authorization server that a
+/// previously obtained refresh or access token is no longer needed.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc7009#section-2>
+pub struct RevocationEndpoint<'a, C> {
+	/// The OAuth 2.0 client.
+	pub client: &'a C,
+
+	/// The revocation endpoint URI.
+	pub uri: &'a Uri,
+}
+
+impl<'a, C> RevocationEndpoint<'a, C> {
+	/// Creates a new revocation endpoint for the given client and URI.
+	pub fn new(client: &'a C, uri: &'a Uri) -> Self {
+		Self { client, uri }
+	}
+
+	/// Creates a new revocation endpoint for the given client and URL.
+	///
+	/// Fails if the URL is not a valid URI.
+	#[cfg(feature = "url")]
+	pub fn from_url(
+		client: &'a C,
+		url: &'a url::Url,
+	) -> Result<Self, crate::util::UrlConversionError> {
+		use crate::util::UrlExt;
+		Ok(Self::new(client, url.as_uri()?))
+	}
+
+	/// Creates a new revocation endpoint for the given client, using the
+	/// `revocation_endpoint` advertised by the authorization server
+	/// metadata.
+	///
+	/// Fails if the authorization server does not advertise a revocation
+	/// endpoint.
+	pub fn from_metadata<P>(
+		client: &'a C,
+		metadata: &'a AuthorizationServerMetadata<P>,
+	) -> Result<Self, MissingEndpoint> {
+		metadata
+			.revocation_endpoint
+			.as_deref()
+			.map(|uri| Self::new(client, uri))
+			.ok_or(MissingEndpoint("revocation_endpoint"))
+	}
+}
+
+impl<'a, C> RevocationEndpoint<'a, C>
+where
+	C: OAuth2Client,
+{
+	/// Begins a Token Revocation Request.
+	///
+	/// Returns a [`RequestBuilder`] that can be further extended with client
+	/// authentication before being sent.
+	pub fn revoke(
+		self,
+		token: String,
+		token_type_hint: Option<TokenTypeHint>,
+	) -> RequestBuilder<Self, RevocationRequest> {
+		RequestBuilder::new(self, RevocationRequest::new(token, token_type_hint))
+	}
+}
+
+impl<'a, C> Clone for RevocationEndpoint<'a, C> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+
+impl<'a, C> Copy for RevocationEndpoint<'a, C> {}
+
+impl<'a, C> Endpoint for RevocationEndpoint<'a, C>
+where
+	C: OAuth2Client,
+{
+	type Client = C;
+
+	fn client(&self) -> &Self::Client {
+		self.client
+	}
+
+	fn uri(&self) -> &Uri {
+		self.uri
+	}
+}
+
+/// Token Revocation Request.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc7009#section-2.1>
+#[skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RevocationRequest {
+	/// The token that the client wants to get revoked.
+	pub token: String,
+
+	/// Hint about the type of the submitted token.
+	pub token_type_hint: Option<TokenTypeHint>,
+}
+
+impl RevocationRequest {
+	/// Creates a new revocation request.
+	pub fn new(token: String, token_type_hint: Option<TokenTypeHint>) -> Self {
+		Self {
+			token,
+			token_type_hint,
+		}
+	}
+}
+
+impl<'a, C> HttpRequest<RevocationEndpoint<'a, C>> for RevocationRequest
+where
+	C: OAuth2Client,
+{
+	type ContentType = WwwFormUrlEncoded;
+	type RequestBody<'b>
+		= &'b Self
+	where
+		Self: 'b;
+	type ResponsePayload = ();
+	type Response = ();
+
+	async fn build_request(
+		&self,
+		endpoint: &RevocationEndpoint<'a, C>,
+		_http_client: &impl HttpClient,
+	) -> Result<http::Request<Self::RequestBody<'_>>, OAuth2ClientError> {
+		Ok(http::Request::builder()
+			.method(http::Method::POST)
+			.uri(endpoint.uri.as_str())
+			.body(self)
+			.unwrap())
+	}
+
+	fn decode_response(
+		&self,
+		_endpoint: &RevocationEndpoint<'a, C>,
+		response: http::Response<Vec<u8>>,
+	) -> Result<http::Response<Self::ResponsePayload>, OAuth2ClientError> {
+		// The content of the response body is ignored by the client.
+		if response.status() != StatusCode::OK {
+			return Err(OAuth2ClientError::unexpected_status(&response));
+		}
+
+		Ok(response.map(|_| ()))
+	}
+
+	async fn process_response(
+		&self,
+		_endpoint: &RevocationEndpoint<'a, C>,
+		_http_client: &impl HttpClient,
+		_response: http::Response<Self::ResponsePayload>,
+	) -> Result<Self::Response, OAuth2ClientError> {
+		Ok(())
+	}
+}
+
+/// Outcome of a token revocation, on the server side.
+///
+/// Both outcomes result in a `200 OK` response: invalid tokens do not cause
+/// an error response since the client cannot handle such an error in a
+/// reasonable way, and the purpose of the revocation request is already
+/// achieved.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc7009#section-2.2>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TokenRevocation {
+	/// The token was found and revoked.
+	Revoked,
+
+	/// The token is unknown, already expired or already revoked.
+	Unknown,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn revocation_request() {
+		let request = RevocationRequest::new(
+			"45ghiukldjahdnhzdauz".to_owned(),
+			Some(TokenTypeHint::RefreshToken),
+		);
+		assert_eq!(
+			serde_html_form::to_string(&request).unwrap(),
+			"token=45ghiukldjahdnhzdauz&token_type_hint=refresh_token"
+		);
+	}
+}
@@ -0,0 +1,186 @@
+use std::{
+	ops::Deref,
+	sync::{
+		Arc,
+		atomic::{AtomicBool, AtomicU32, Ordering},
+	},
+	time::{Duration, Instant},
+};
+
+use http::StatusCode;
+
+use crate::{client::OAuth2ClientError, transport::HttpClient};
+
+use super::{HttpRequest, RequestExtensions};
+
+/// Measurements of a request, for monitoring authorization server
+/// dependencies.
+///
+/// See [`RequestBuilder::with_telemetry`](super::RequestBuilder::with_telemetry).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Telemetry {
+	/// Time elapsed between building the request and processing its
+	/// response.
+	pub elapsed: Duration,
+
+	/// Number of additional HTTP exchanges performed by the request layers.
+	pub retries: u32,
+
+	/// Whether a layer retried the request with a server-provided nonce
+	/// (e.g. DPoP).
+	pub nonce_retry: bool,
+
+	/// Status of the response.
+	pub status: StatusCode,
+}
+
+/// Recorder of the [`Telemetry`] of a request.
+///
+/// Attached to the request extensions by [`WithTelemetry`]. Layers performing
+/// additional HTTP exchanges while processing the response should report
+/// them with [`record_retry`](Self::record_retry).
+#[derive(Debug)]
+pub struct TelemetryRecorder {
+	start: Instant,
+	retries: AtomicU32,
+	nonce_retry: AtomicBool,
+}
+
+impl TelemetryRecorder {
+	/// Creates a recorder, starting the clock now.
+	pub fn new() -> Self {
+		Self {
+			start: Instant::now(),
+			retries: AtomicU32::new(0),
+			nonce_retry: AtomicBool::new(false),
+		}
+	}
+
+	/// Returns the recorder attached to the request of the given response, if
+	/// any.
+	pub fn of<B>(response: &http::Response<B>) -> Option<&Self> {
+		RequestExtensions::get::<Arc<Self>, _>(response).map(Arc::as_ref)
+	}
+
+	/// Records an additional HTTP exchange.
+	pub fn record_retry(&self) {
+		self.retries.fetch_add(1, Ordering::Relaxed);
+	}
+
+	/// Records a retry with a server-provided nonce.
+	pub fn record_nonce_retry(&self) {
+		self.record_retry();
+		self.nonce_retry.store(true, Ordering::Relaxed);
+	}
+
+	/// Returns the measurements so far, for a response with the given status.
+	pub fn telemetry(&self, status: StatusCode) -> Telemetry {
+		Telemetry {
+			elapsed: self.start.elapsed(),
+			retries: self.retries.load(Ordering::Relaxed),
+			nonce_retry: self.nonce_retry.load(Ordering::Relaxed),
+			status,
+		}
+	}
+}
+
+impl Default for TelemetryRecorder {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Response returned along with its [`Telemetry`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Measured<R> {
+	/// The response.
+	pub response: R,
+
+	/// Measurements of the request.
+	pub telemetry: Telemetry,
+}
+
+impl<R> Deref for Measured<R> {
+	type Target = R;
+
+	fn deref(&self) -> &Self::Target {
+		&self.response
+	}
+}
+
+/// Wrapper measuring a request.
+///
+/// See [`RequestBuilder::with_telemetry`](super::RequestBuilder::with_telemetry).
+pub struct WithTelemetry<T> {
+	/// The inner request.
+	pub value: T,
+}
+
+impl<T> WithTelemetry<T> {
+	/// Creates a new [`WithTelemetry`] wrapping the given request.
+	pub fn new(value: T) -> Self {
+		Self { value }
+	}
+}
+
+impl<T> Deref for WithTelemetry<T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		&self.value
+	}
+}
+
+impl<E, T> HttpRequest<E> for WithTelemetry<T>
+where
+	T: HttpRequest<E>,
+{
+	type ContentType = T::ContentType;
+	type RequestBody<'b>
+		= T::RequestBody<'b>
+	where
+		Self: 'b;
+	type Response = Measured<T::Response>;
+	type ResponsePayload = T::ResponsePayload;
+
+	async fn build_request(
+		&self,
+		endpoint: &E,
+		http_client: &impl HttpClient,
+	) -> Result<http::Request<Self::RequestBody<'_>>, OAuth2ClientError> {
+		let recorder = Arc::new(TelemetryRecorder::new());
+		let mut request = self.value.build_request(endpoint, http_client).await?;
+		request.extensions_mut().insert(recorder);
+		Ok(request)
+	}
+
+	fn decode_response(
+		&self,
+		endpoint: &E,
+		response: http::Response<Vec<u8>>,
+	) -> Result<http::Response<Self::ResponsePayload>, OAuth2ClientError> {
+		self.value.decode_response(endpoint, response)
+	}
+
+	async fn process_response(
+		&self,
+		endpoint: &E,
+		http_client: &impl HttpClient,
+		response: http::Response<Self::ResponsePayload>,
+	) -> Result<Self::Response, OAuth2ClientError> {
+		let status = response.status();
+		let recorder = RequestExtensions::get::<Arc<TelemetryRecorder>, _>(&response)
+			.cloned()
+			.unwrap_or_default();
+
+		let response = self
+			.value
+			.process_response(endpoint, http_client, response)
+			.await?;
+
+		Ok(Measured {
+			response,
+			telemetry: recorder.telemetry(status),
+		})
+	}
+}
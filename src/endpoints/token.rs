@@ -1,14 +1,19 @@
 //! OAuth 2.0 token endpoint.
 //!
 //! See: <https://datatracker.ietf.org/doc/html/rfc6749#section-3.2>
-use std::fmt::Display;
+use std::{borrow::Cow, fmt::Display, marker::PhantomData, time::Duration};
 
-use iref::Uri;
+use iref::{Uri, UriBuf};
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use serde_with::skip_serializing_none;
 
 use crate::{
-	AccessTokenBuf, ScopeBuf, client::OAuth2Client, endpoints::Endpoint, util::NoExtension,
+	AccessTokenBuf, Scope, ScopeBuf,
+	client::{OAuth2Client, OAuth2ClientError},
+	endpoints::Endpoint,
+	server::metadata::{AuthorizationServerMetadata, MissingEndpoint},
+	transport::ResponseDecoder,
+	util::NoExtension,
 };
 
 /// The OAuth 2.0 token endpoint.
@@ -21,21 +26,97 @@ pub struct TokenEndpoint<'a, C> {
 	pub client: &'a C,
 
 	/// The token endpoint URI.
-	pub uri: &'a Uri,
+	pub uri: Cow<'a, Uri>,
+
+	/// Tolerance to non-standard `token_type` values in token responses.
+	pub token_type_policy: TokenTypePolicy,
 }
 
 impl<'a, C> Clone for TokenEndpoint<'a, C> {
 	fn clone(&self) -> Self {
-		*self
+		Self {
+			client: self.client,
+			uri: self.uri.clone(),
+			token_type_policy: self.token_type_policy,
+		}
 	}
 }
 
-impl<'a, C> Copy for TokenEndpoint<'a, C> {}
-
 impl<'a, C> TokenEndpoint<'a, C> {
 	/// Creates a new token endpoint for the given client and URI.
 	pub fn new(client: &'a C, uri: &'a Uri) -> Self {
-		Self { client, uri }
+		Self {
+			client,
+			uri: Cow::Borrowed(uri),
+			token_type_policy: TokenTypePolicy::Strict,
+		}
+	}
+
+	/// Creates a new token endpoint for the given client and URL.
+	///
+	/// Fails if the URL is not a valid URI.
+	#[cfg(feature = "url")]
+	pub fn from_url(
+		client: &'a C,
+		url: &'a url::Url,
+	) -> Result<Self, crate::util::UrlConversionError> {
+		use crate::util::UrlExt;
+		Ok(Self::new(client, url.as_uri()?))
+	}
+
+	/// Creates a new token endpoint for the given client, owning its URI.
+	pub fn new_owned(client: &'a C, uri: UriBuf) -> Self {
+		Self {
+			client,
+			uri: Cow::Owned(uri),
+			token_type_policy: TokenTypePolicy::Strict,
+		}
+	}
+
+	/// Sets the tolerance to non-standard `token_type` values in token
+	/// responses.
+	pub fn with_token_type_policy(self, token_type_policy: TokenTypePolicy) -> Self {
+		Self {
+			token_type_policy,
+			..self
+		}
+	}
+
+	/// Decodes a token response, after normalizing its `token_type`
+	/// according to the [`token_type_policy`](Self::token_type_policy).
+	///
+	/// Unsuccessful responses are parsed as OAuth 2.0 error responses.
+	pub fn decode_token_response<T: DeserializeOwned>(
+		&self,
+		response: http::Response<Vec<u8>>,
+	) -> Result<http::Response<T>, OAuth2ClientError> {
+		ResponseDecoder::json(http::StatusCode::OK)
+			.with_error_responses()
+			.decode(self.token_type_policy.normalize(response))
+	}
+
+	/// Creates a new token endpoint for the given client, parsing its URI.
+	pub fn parse<U>(client: &'a C, uri: U) -> Result<Self, U::Error>
+	where
+		U: TryInto<UriBuf>,
+	{
+		Ok(Self::new_owned(client, uri.try_into()?))
+	}
+
+	/// Creates a new token endpoint for the given client, using the
+	/// `token_endpoint` advertised by the authorization server metadata.
+	///
+	/// Fails if the authorization server does not advertise a token
+	/// endpoint.
+	pub fn from_metadata<P>(
+		client: &'a C,
+		metadata: &'a AuthorizationServerMetadata<P>,
+	) -> Result<Self, MissingEndpoint> {
+		metadata
+			.token_endpoint
+			.as_deref()
+			.map(|uri| Self::new(client, uri))
+			.ok_or(MissingEndpoint("token_endpoint"))
 	}
 
 	// pub fn begin<T>(self, request: T) -> TokenRequestBuilder<'a, C, T> {
@@ -54,7 +135,168 @@ where
 	}
 
 	fn uri(&self) -> &Uri {
-		self.uri
+		&self.uri
+	}
+}
+
+/// The OAuth 2.0 token endpoint, used without a registered client.
+///
+/// Some authorization servers issue tokens to unregistered clients, such as
+/// OpenID4VCI credential issuers accepting pre-authorized codes from any
+/// wallet. Requests sent to this endpoint do not include any `client_id`.
+/// Successful responses are decoded as `R`.
+pub struct PublicTokenEndpoint<'a, R = TokenResponse> {
+	/// The token endpoint URI.
+	pub uri: Cow<'a, Uri>,
+
+	/// Tolerance to non-standard `token_type` values in token responses.
+	pub token_type_policy: TokenTypePolicy,
+
+	response: PhantomData<fn() -> R>,
+}
+
+impl<'a, R> Clone for PublicTokenEndpoint<'a, R> {
+	fn clone(&self) -> Self {
+		Self {
+			uri: self.uri.clone(),
+			token_type_policy: self.token_type_policy,
+			response: PhantomData,
+		}
+	}
+}
+
+impl<'a, R> PublicTokenEndpoint<'a, R> {
+	/// Creates a new public token endpoint for the given URI.
+	pub fn new(uri: &'a Uri) -> Self {
+		Self {
+			uri: Cow::Borrowed(uri),
+			token_type_policy: TokenTypePolicy::Strict,
+			response: PhantomData,
+		}
+	}
+
+	/// Creates a new public token endpoint for the given URL.
+	///
+	/// Fails if the URL is not a valid URI.
+	#[cfg(feature = "url")]
+	pub fn from_url(url: &'a url::Url) -> Result<Self, crate::util::UrlConversionError> {
+		use crate::util::UrlExt;
+		Ok(Self::new(url.as_uri()?))
+	}
+
+	/// Creates a new public token endpoint, owning its URI.
+	pub fn new_owned(uri: UriBuf) -> Self {
+		Self {
+			uri: Cow::Owned(uri),
+			token_type_policy: TokenTypePolicy::Strict,
+			response: PhantomData,
+		}
+	}
+
+	/// Sets the tolerance to non-standard `token_type` values in token
+	/// responses.
+	pub fn with_token_type_policy(self, token_type_policy: TokenTypePolicy) -> Self {
+		Self {
+			token_type_policy,
+			..self
+		}
+	}
+
+	/// Decodes a token response, after normalizing its `token_type`
+	/// according to the [`token_type_policy`](Self::token_type_policy).
+	///
+	/// Unsuccessful responses are parsed as OAuth 2.0 error responses.
+	pub fn decode_token_response(
+		&self,
+		response: http::Response<Vec<u8>>,
+	) -> Result<http::Response<R>, OAuth2ClientError>
+	where
+		R: DeserializeOwned,
+	{
+		ResponseDecoder::json(http::StatusCode::OK)
+			.with_error_responses()
+			.decode(self.token_type_policy.normalize(response))
+	}
+
+	/// Creates a new public token endpoint, parsing its URI.
+	pub fn parse<U>(uri: U) -> Result<Self, U::Error>
+	where
+		U: TryInto<UriBuf>,
+	{
+		Ok(Self::new_owned(uri.try_into()?))
+	}
+
+	/// Creates a new public token endpoint, using the `token_endpoint`
+	/// advertised by the authorization server metadata.
+	///
+	/// Fails if the authorization server does not advertise a token
+	/// endpoint.
+	pub fn from_metadata<P>(
+		metadata: &'a AuthorizationServerMetadata<P>,
+	) -> Result<Self, MissingEndpoint> {
+		metadata
+			.token_endpoint
+			.as_deref()
+			.map(Self::new)
+			.ok_or(MissingEndpoint("token_endpoint"))
+	}
+}
+
+/// Tolerance of a [`TokenEndpoint`] or [`PublicTokenEndpoint`] to
+/// non-standard `token_type` values.
+///
+/// The `token_type` parameter of token responses is required, and its value
+/// case-insensitive, as described in
+/// [RFC 6749 Section 5.1](https://datatracker.ietf.org/doc/html/rfc6749#section-5.1).
+/// Some authorization servers omit it, or return `bearer`, which resource
+/// servers comparing the `Authorization` scheme case-sensitively may reject.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TokenTypePolicy {
+	/// Decode the `token_type` as returned, failing if it is missing.
+	#[default]
+	Strict,
+
+	/// Normalize the case of the `Bearer` and `DPoP` token types.
+	Normalize,
+
+	/// Normalize the case of the `Bearer` and `DPoP` token types, and
+	/// default to `Bearer` if the token type is missing.
+	DefaultBearer,
+}
+
+impl TokenTypePolicy {
+	/// Normalizes the `token_type` of the given successful JSON token
+	/// response according to this policy.
+	///
+	/// Other responses are returned unchanged.
+	pub fn normalize(self, response: http::Response<Vec<u8>>) -> http::Response<Vec<u8>> {
+		if self == Self::Strict || response.status() != http::StatusCode::OK {
+			return response;
+		}
+
+		let Ok(mut body) =
+			serde_json::from_slice::<serde_json::Map<String, serde_json::Value>>(response.body())
+		else {
+			return response;
+		};
+
+		let normalized = match body.get("token_type") {
+			Some(serde_json::Value::String(token_type)) => ["Bearer", "DPoP"]
+				.into_iter()
+				.find(|t| t.eq_ignore_ascii_case(token_type) && t != token_type),
+			None if self == Self::DefaultBearer => Some("Bearer"),
+			_ => None,
+		};
+
+		match normalized {
+			Some(token_type) => {
+				log::warn!("normalizing non-standard token type to `{token_type}`");
+				body.insert("token_type".to_owned(), token_type.into());
+				// UNWRAP SAFETY: a JSON object always serializes.
+				response.map(|_| serde_json::to_vec(&body).unwrap())
+			}
+			None => response,
+		}
 	}
 }
 
@@ -72,9 +314,12 @@ impl TokenType for String {}
 ///
 /// The type parameters allow customizing the token type string and any
 /// extension fields returned by the authorization server. Use
-/// [`NoExtension`] when no extra fields are expected.
+/// [`NoExtension`] when no extra fields are expected, and
+/// [`TryExt`](crate::util::TryExt) to keep the response when the extension
+/// fields fail to decode.
 #[skip_serializing_none]
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(bound(
 	serialize = "T: TokenType, E: Serialize",
 	deserialize = "T: TokenType, E: Deserialize<'de>"
@@ -133,4 +378,315 @@ where
 			ext,
 		}
 	}
+
+	/// Sets the lifetime of the access token.
+	///
+	/// The lifetime is serialized in whole seconds, sub-second precision is
+	/// discarded.
+	pub fn with_expires_in(self, expires_in: Duration) -> Self {
+		Self {
+			expires_in: Some(expires_in.as_secs()),
+			..self
+		}
+	}
+
+	/// Returns the lifetime of the access token, if provided by the server.
+	pub fn expires_in(&self) -> Option<Duration> {
+		self.expires_in.map(Duration::from_secs)
+	}
+
+	/// Returns the scope of the access token.
+	///
+	/// If the server did not include a scope in the response, the granted
+	/// scope is the `requested` one.
+	pub fn granted_scope<'s>(&'s self, requested: Option<&'s Scope>) -> Option<&'s Scope> {
+		self.scope.as_deref().or(requested)
+	}
+
+	/// Compares the granted scope with the `requested` one.
+	///
+	/// The authorization server may issue an access token with a different
+	/// scope than requested, as described in
+	/// [RFC 6749 Section 3.3](https://datatracker.ietf.org/doc/html/rfc6749#section-3.3).
+	pub fn granted_scope_diff(&self, requested: &Scope) -> ScopeDiff {
+		let granted = self.granted_scope(Some(requested));
+		ScopeDiff {
+			added: ScopeBuf::from_tokens(
+				granted
+					.into_iter()
+					.flatten()
+					.filter(|t| !requested.contains(t)),
+			),
+			missing: ScopeBuf::from_tokens(
+				requested
+					.iter()
+					.filter(|t| !granted.is_some_and(|g| g.contains(t))),
+			),
+		}
+	}
+
+	/// Ensures that every token of the `required` scope was granted.
+	///
+	/// Clients that cannot operate with a reduced scope should call this
+	/// method on every token response.
+	pub fn ensure_granted_scope(&self, required: &Scope) -> Result<(), ScopeNotGranted> {
+		match self.granted_scope_diff(required).missing {
+			Some(missing) => Err(ScopeNotGranted(missing)),
+			None => Ok(()),
+		}
+	}
+}
+
+/// Difference between the requested and granted scopes of an access token.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScopeDiff {
+	/// Scope tokens granted without being requested.
+	pub added: Option<ScopeBuf>,
+
+	/// Scope tokens requested but not granted.
+	pub missing: Option<ScopeBuf>,
+}
+
+impl ScopeDiff {
+	/// Returns `true` if the granted scope is the requested one.
+	pub fn is_empty(&self) -> bool {
+		self.added.is_none() && self.missing.is_none()
+	}
+}
+
+/// Error returned when some required scope tokens were not granted.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("scope `{0}` was not granted")]
+pub struct ScopeNotGranted(pub ScopeBuf);
+
+/// Hint about the type of a token submitted for introspection or
+/// revocation.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc7009#section-2.1>
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum TokenTypeHint {
+	AccessToken,
+	RefreshToken,
+	#[serde(untagged)]
+	Extension(String),
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::util::{AnyExtension, TryExt};
+
+	use super::*;
+
+	fn response(scope: Option<&str>) -> TokenResponse {
+		let mut response = TokenResponse::new(
+			AccessTokenBuf::new("2YotnFZFEjr1zCsicMWpAA".to_owned()).unwrap(),
+			"Bearer".to_owned(),
+			NoExtension {},
+		);
+		response.scope = scope.map(|s| ScopeBuf::new(s.to_owned()).unwrap());
+		response
+	}
+
+	fn token_response(body: &str) -> http::Response<Vec<u8>> {
+		http::Response::builder()
+			.header(http::header::CONTENT_TYPE, "application/json")
+			.body(body.as_bytes().to_vec())
+			.unwrap()
+	}
+
+	#[test]
+	fn token_type_policy() {
+		let endpoint = TokenEndpoint::new(&(), iref::uri!("https://server.example.com/token"));
+		let lowercase = r#"{"access_token":"2YotnFZFEjr1zCsicMWpAA","token_type":"bearer"}"#;
+		let missing = r#"{"access_token":"2YotnFZFEjr1zCsicMWpAA"}"#;
+
+		let response: http::Response<TokenResponse> = endpoint
+			.decode_token_response(token_response(lowercase))
+			.unwrap();
+		assert_eq!(response.body().token_type, "bearer");
+		assert!(
+			endpoint
+				.decode_token_response::<TokenResponse>(token_response(missing))
+				.is_err()
+		);
+
+		let endpoint = endpoint.with_token_type_policy(TokenTypePolicy::Normalize);
+		let response: http::Response<TokenResponse> = endpoint
+			.decode_token_response(token_response(lowercase))
+			.unwrap();
+		assert_eq!(response.body().token_type, "Bearer");
+		let response: http::Response<TokenResponse> = endpoint
+			.decode_token_response(token_response(
+				r#"{"access_token":"2YotnFZFEjr1zCsicMWpAA","token_type":"DPOP"}"#,
+			))
+			.unwrap();
+		assert_eq!(response.body().token_type, "DPoP");
+		assert!(
+			endpoint
+				.decode_token_response::<TokenResponse>(token_response(missing))
+				.is_err()
+		);
+
+		let endpoint = endpoint.with_token_type_policy(TokenTypePolicy::DefaultBearer);
+		let response: http::Response<TokenResponse> = endpoint
+			.decode_token_response(token_response(missing))
+			.unwrap();
+		assert_eq!(response.body().token_type, "Bearer");
+
+		let error = http::Response::builder()
+			.status(http::StatusCode::BAD_REQUEST)
+			.body(br#"{"error":"invalid_grant"}"#.to_vec())
+			.unwrap();
+		assert_eq!(
+			TokenTypePolicy::DefaultBearer.normalize(error).body(),
+			br#"{"error":"invalid_grant"}"#
+		);
+	}
+
+	#[test]
+	fn public_token_type_policy() {
+		let endpoint: PublicTokenEndpoint<TokenResponse> =
+			PublicTokenEndpoint::new(iref::uri!("https://issuer.example.com/token"));
+		let missing = r#"{"access_token":"2YotnFZFEjr1zCsicMWpAA"}"#;
+
+		assert!(
+			endpoint
+				.decode_token_response(token_response(missing))
+				.is_err()
+		);
+
+		let endpoint = endpoint.with_token_type_policy(TokenTypePolicy::DefaultBearer);
+		let response = endpoint
+			.clone()
+			.decode_token_response(token_response(missing))
+			.unwrap();
+		assert_eq!(response.body().token_type, "Bearer");
+	}
+
+	#[test]
+	fn granted_scope_diff() {
+		let requested = Scope::new("openid profile email").unwrap();
+
+		assert!(response(None).granted_scope_diff(requested).is_empty());
+		assert!(
+			response(Some("email openid profile"))
+				.granted_scope_diff(requested)
+				.is_empty()
+		);
+
+		let diff = response(Some("openid offline_access")).granted_scope_diff(requested);
+		assert_eq!(
+			diff.added.as_deref().map(Scope::as_str),
+			Some("offline_access")
+		);
+		assert_eq!(
+			diff.missing.as_deref().map(Scope::as_str),
+			Some("profile email")
+		);
+	}
+
+	#[test]
+	fn ensure_granted_scope() {
+		let response = response(Some("openid profile"));
+		assert!(
+			response
+				.ensure_granted_scope(Scope::new("openid").unwrap())
+				.is_ok()
+		);
+		assert_eq!(
+			response.ensure_granted_scope(Scope::new("openid email").unwrap()),
+			Err(ScopeNotGranted(ScopeBuf::new("email".to_owned()).unwrap()))
+		);
+	}
+
+	#[test]
+	fn any_extension() {
+		let json = serde_json::json!({
+			"access_token": "2YotnFZFEjr1zCsicMWpAA",
+			"token_type": "Bearer",
+			"id_token": "eyJhbGciOiJSUzI1NiJ9.e30.c2ln",
+			"x_provider": { "region": "eu" }
+		});
+
+		let response: TokenResponse<String, AnyExtension> =
+			serde_json::from_value(json.clone()).unwrap();
+		assert_eq!(
+			response
+				.ext
+				.get_as::<String>("id_token")
+				.unwrap()
+				.as_deref(),
+			Some("eyJhbGciOiJSUzI1NiJ9.e30.c2ln")
+		);
+		assert_eq!(response.ext["x_provider"]["region"], "eu");
+		assert_eq!(
+			response.ext.get_as::<String>("refresh_token").unwrap(),
+			None
+		);
+		assert_eq!(serde_json::to_value(&response).unwrap(), json);
+	}
+
+	#[test]
+	fn expires_in() {
+		assert_eq!(response(None).expires_in(), None);
+
+		let response = response(None).with_expires_in(Duration::from_millis(3_600_500));
+		assert_eq!(response.expires_in, Some(3600));
+		assert_eq!(response.expires_in(), Some(Duration::from_secs(3600)));
+		assert!(
+			serde_json::to_string(&response)
+				.unwrap()
+				.contains(r#""expires_in":3600"#)
+		);
+	}
+
+	#[test]
+	fn try_extension() {
+		#[derive(Debug, PartialEq, Serialize, Deserialize)]
+		struct IdToken {
+			id_token: String,
+		}
+
+		let json = serde_json::json!({
+			"access_token": "2YotnFZFEjr1zCsicMWpAA",
+			"token_type": "Bearer",
+			"id_token": "eyJhbGciOiJSUzI1NiJ9.e30.c2ln"
+		});
+		let response: TokenResponse<String, TryExt<IdToken>> =
+			serde_json::from_value(json.clone()).unwrap();
+		assert_eq!(
+			response.ext.ok().map(|ext| ext.id_token.as_str()),
+			Some("eyJhbGciOiJSUzI1NiJ9.e30.c2ln")
+		);
+		assert_eq!(serde_json::to_value(&response).unwrap(), json);
+
+		let json = serde_json::json!({
+			"access_token": "2YotnFZFEjr1zCsicMWpAA",
+			"token_type": "Bearer",
+			"x_provider": { "region": "eu" }
+		});
+		let response: TokenResponse<String, TryExt<IdToken>> =
+			serde_json::from_value(json.clone()).unwrap();
+		assert_eq!(response.access_token.as_str(), "2YotnFZFEjr1zCsicMWpAA");
+		let error = response.ext.error().unwrap();
+		assert_eq!(error.fields["x_provider"]["region"], "eu");
+		assert_eq!(serde_json::to_value(&response).unwrap(), json);
+	}
+
+	#[cfg(feature = "schemars")]
+	#[test]
+	fn json_schema() {
+		let schema = schemars::schema_for!(TokenResponse);
+		assert_eq!(
+			schema.get("required"),
+			Some(&serde_json::json!(["access_token", "token_type"]))
+		);
+		assert_eq!(schema.get("type"), Some(&serde_json::json!("object")));
+		assert_eq!(
+			schema.pointer("/properties/access_token/type"),
+			Some(&serde_json::json!("string"))
+		);
+	}
 }
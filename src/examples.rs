@@ -0,0 +1,147 @@
+//! Helpers for command-line clients, used by the examples of this crate.
+//!
+//! Authorization requests are opened with the
+//! [`SystemBrowser`](crate::client::SystemBrowser) user-agent, enabled along
+//! with this module. No QR code renderer is provided: the examples never show
+//! a URI meant to be opened on another device.
+//!
+//! Requires the `examples` feature.
+use std::{
+	io::{self, BufRead, BufReader, Write},
+	net::{Ipv4Addr, TcpListener},
+};
+
+use iref::{Uri, UriBuf};
+use serde::de::DeserializeOwned;
+
+use crate::{State, client::parse_callback};
+
+/// Loopback redirection endpoint of a native app.
+///
+/// See: <https://www.rfc-editor.org/rfc/rfc8252.html#section-7.3>
+///
+/// Listens on an ephemeral port of the `127.0.0.1` loopback interface for
+/// the authorization response.
+pub struct LoopbackRedirect {
+	listener: TcpListener,
+	redirect_uri: UriBuf,
+}
+
+impl LoopbackRedirect {
+	/// Binds a new loopback redirection endpoint at the given path.
+	pub fn bind(path: &str) -> io::Result<Self> {
+		let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+		let port = listener.local_addr()?.port();
+		let redirect_uri = UriBuf::new(format!("http://127.0.0.1:{port}{path}").into_bytes())
+			.map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid path"))?;
+
+		Ok(Self {
+			listener,
+			redirect_uri,
+		})
+	}
+
+	/// Returns the redirection URI to include in the authorization request.
+	pub fn redirect_uri(&self) -> &Uri {
+		&self.redirect_uri
+	}
+
+	/// Waits for the user-agent to be redirected, returning the raw query of
+	/// the authorization response.
+	///
+	/// Blocks the current thread.
+	pub fn receive_query(self) -> io::Result<String> {
+		let target = self.receive_target()?;
+		Ok(target
+			.split_once('?')
+			.map(|(_, query)| query)
+			.unwrap_or_default()
+			.to_owned())
+	}
+
+	/// Waits for the user-agent to be redirected, returning the decoded
+	/// authorization response.
+	///
+	/// The response is parsed with [`parse_callback`], so that responses
+	/// delivered to another path than the one of the redirection URI, or
+	/// without the given `state`, are rejected.
+	///
+	/// Blocks the current thread.
+	pub fn receive<T: DeserializeOwned>(self, state: Option<&State>) -> io::Result<T> {
+		let redirect_uri = self.redirect_uri.clone();
+		let target = self.receive_target()?;
+
+		// UNWRAP SAFETY: the loopback redirection URI has an authority.
+		let authority = redirect_uri.authority().unwrap();
+		let callback = UriBuf::new(format!("http://{authority}{target}").into_bytes())
+			.map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid request target"))?;
+
+		parse_callback(&redirect_uri, &callback, state)
+			.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+	}
+
+	/// Waits for the user-agent to be redirected, returning the target of its
+	/// request.
+	fn receive_target(self) -> io::Result<String> {
+		let (mut stream, _) = self.listener.accept()?;
+
+		let mut request_line = String::new();
+		BufReader::new(&stream).read_line(&mut request_line)?;
+
+		let target = request_line
+			.split(' ')
+			.nth(1)
+			.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid request"))?
+			.to_owned();
+
+		stream.write_all(
+			b"HTTP/1.1 200 OK\r\n\
+			content-type: text/plain\r\n\
+			connection: close\r\n\r\n\
+			Authorization complete, you can close this window.",
+		)?;
+
+		Ok(target)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::net::TcpStream;
+
+	use crate::{
+		CodeBuf, Stateful, grant::authorization_code::AuthorizationCodeAuthorizationResponse,
+		server::ServerResult, state,
+	};
+
+	use super::*;
+
+	#[test]
+	fn loopback_redirect() {
+		let redirect = LoopbackRedirect::bind("/cb").unwrap();
+		let uri = redirect.redirect_uri().to_owned();
+		assert!(uri.as_str().starts_with("http://127.0.0.1:"));
+
+		let authority = uri.authority().unwrap().as_str().to_owned();
+		let user_agent = std::thread::spawn(move || {
+			let mut stream = TcpStream::connect(authority).unwrap();
+			stream
+				.write_all(b"GET /cb?code=SplxlOBeZQQYbYS6WxSbIA&state=xyz HTTP/1.1\r\n\r\n")
+				.unwrap();
+			io::read_to_string(stream).unwrap()
+		});
+
+		let response: Stateful<ServerResult<AuthorizationCodeAuthorizationResponse>> =
+			redirect.receive(Some(state!("xyz"))).unwrap();
+		assert_eq!(response.state.as_deref().map(|s| s.as_str()), Some("xyz"));
+		match response.value {
+			ServerResult::Ok(response) => assert_eq!(
+				response.code,
+				CodeBuf::new("SplxlOBeZQQYbYS6WxSbIA".to_owned()).unwrap()
+			),
+			ServerResult::Err(_) => panic!("unexpected error response"),
+		}
+
+		assert!(user_agent.join().unwrap().starts_with("HTTP/1.1 200 OK"));
+	}
+}
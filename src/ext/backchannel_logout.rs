@@ -0,0 +1,794 @@
+//! OpenID Connect Back-Channel Logout.
+//!
+//! See: <https://openid.net/specs/openid-connect-backchannel-1_0.html>
+//!
+//! When an end-user logs out, the OpenID provider notifies every relying
+//! party holding a session for them by posting a signed [`LogoutToken`] to
+//! the `backchannel_logout_uri` they registered. OpenID providers build the
+//! token with [`LogoutToken::new`], sign it with
+//! [`sign`](LogoutToken::sign) and deliver it with [`send_logout_token`].
+//! Relying parties check it with a [`LogoutTokenDecoder`], or, with the
+//! `axum` feature, receive it at a route added by
+//! [`BackchannelLogoutRouter::backchannel_logout_route`].
+//!
+//! This crate does not implement any cryptographic algorithm: signing and
+//! signature verification are delegated to the [`LogoutTokenSigner`] and
+//! [`JwsSignatureVerifier`] traits.
+use std::{collections::BTreeMap, future::Future, time::Duration};
+
+use http::header::CONTENT_TYPE;
+use iref::Uri;
+use serde::{Deserialize, Serialize};
+use serde_with::{OneOrMany, formats::PreferOne, serde_as, skip_serializing_none};
+
+use crate::{
+	ClientId, ClientIdBuf, Issuer, IssuerBuf,
+	client::OAuth2ClientError,
+	ext::oidc::IdTokenClaims,
+	time::{Clock, LeewayConfig, SystemClock},
+	transport::{APPLICATION_X_WWW_FORM_URLENCODED, ContentType, HttpClient, WwwFormUrlEncoded},
+	util::{JwsSignatureVerifier, decode_compact_jws, unix_time, unix_timestamp},
+};
+
+/// Member of the `events` claim identifying a logout token.
+///
+/// See: <https://openid.net/specs/openid-connect-backchannel-1_0.html#LogoutToken>
+pub const BACKCHANNEL_LOGOUT_EVENT: &str = "http://schemas.openid.net/event/backchannel-logout";
+
+/// Media type of logout tokens, set in their `typ` header.
+///
+/// See: <https://openid.net/specs/openid-connect-backchannel-1_0.html#LogoutToken>
+pub const LOGOUT_TOKEN_TYPE: &str = "logout+jwt";
+
+/// Default lifetime of the logout tokens built with [`LogoutToken::new`].
+///
+/// The specification recommends an expiration of at most two minutes.
+pub const DEFAULT_LOGOUT_TOKEN_LIFETIME: Duration = Duration::from_secs(120);
+
+/// Claims of a logout token.
+///
+/// See: <https://openid.net/specs/openid-connect-backchannel-1_0.html#LogoutToken>
+#[serde_as]
+#[skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogoutToken {
+	/// Issuer identifier of the OpenID provider.
+	pub iss: IssuerBuf,
+
+	/// Client identifiers of the relying parties the token is intended for.
+	#[serde_as(as = "OneOrMany<_, PreferOne>")]
+	pub aud: Vec<ClientIdBuf>,
+
+	/// Time at which the token was issued, as a Unix timestamp.
+	pub iat: u64,
+
+	/// Expiration time of the token, as a Unix timestamp.
+	pub exp: u64,
+
+	/// Unique identifier of the token, used by relying parties to detect
+	/// replays.
+	pub jti: String,
+
+	/// Subject identifier of the end-user logging out.
+	pub sub: Option<String>,
+
+	/// Identifier of the session being ended.
+	pub sid: Option<String>,
+
+	/// Events, including [`BACKCHANNEL_LOGOUT_EVENT`].
+	pub events: BTreeMap<String, serde_json::Value>,
+
+	/// Other claims.
+	#[serde(flatten)]
+	pub ext: BTreeMap<String, serde_json::Value>,
+}
+
+impl LogoutToken {
+	/// Creates a new logout token issued by `iss` to the relying party
+	/// `aud`, expiring after [`DEFAULT_LOGOUT_TOKEN_LIFETIME`].
+	///
+	/// The end-user or session being logged out must be set with
+	/// [`with_subject`](Self::with_subject) or
+	/// [`with_session_id`](Self::with_session_id).
+	pub fn new(iss: IssuerBuf, aud: ClientIdBuf, jti: String, clock: &impl Clock) -> Self {
+		let iat = unix_timestamp(clock.now());
+		Self {
+			iss,
+			aud: vec![aud],
+			iat,
+			exp: iat.saturating_add(DEFAULT_LOGOUT_TOKEN_LIFETIME.as_secs()),
+			jti,
+			sub: None,
+			sid: None,
+			events: BTreeMap::from([(
+				BACKCHANNEL_LOGOUT_EVENT.to_owned(),
+				serde_json::Value::Object(Default::default()),
+			)]),
+			ext: BTreeMap::new(),
+		}
+	}
+
+	/// Sets the subject identifier of the end-user logging out.
+	pub fn with_subject(self, sub: String) -> Self {
+		Self {
+			sub: Some(sub),
+			..self
+		}
+	}
+
+	/// Sets the identifier of the session being ended.
+	pub fn with_session_id(self, sid: String) -> Self {
+		Self {
+			sid: Some(sid),
+			..self
+		}
+	}
+
+	/// Sets the lifetime of the token, from its issuance time.
+	pub fn with_expires_in(self, expires_in: Duration) -> Self {
+		Self {
+			exp: self.iat.saturating_add(expires_in.as_secs()),
+			..self
+		}
+	}
+
+	/// Signs the token with the given signer, returning a compact JWS.
+	pub async fn sign(&self, signer: &impl LogoutTokenSigner) -> Result<String, OAuth2ClientError> {
+		let claims = serde_json::to_vec(self).map_err(OAuth2ClientError::request)?;
+		signer.sign_logout_token(&claims).await
+	}
+
+	/// Checks the claims of the token, as received by the relying party
+	/// `client_id` from the OpenID provider `issuer`.
+	///
+	/// See: <https://openid.net/specs/openid-connect-backchannel-1_0.html#Validation>
+	pub fn validate(
+		&self,
+		issuer: &Issuer,
+		client_id: &ClientId,
+		leeway: &LeewayConfig,
+		clock: &impl Clock,
+	) -> Result<(), LogoutTokenError> {
+		if self.iss.as_str() != issuer.as_str() {
+			return Err(LogoutTokenError::Issuer);
+		}
+
+		if !self
+			.aud
+			.iter()
+			.any(|aud| aud.as_str() == client_id.as_str())
+		{
+			return Err(LogoutTokenError::Audience);
+		}
+
+		if !self
+			.events
+			.get(BACKCHANNEL_LOGOUT_EVENT)
+			.is_some_and(serde_json::Value::is_object)
+		{
+			return Err(LogoutTokenError::MissingEvent);
+		}
+
+		if self.sub.is_none() && self.sid.is_none() {
+			return Err(LogoutTokenError::MissingSubject);
+		}
+
+		if self.ext.contains_key("nonce") {
+			return Err(LogoutTokenError::Nonce);
+		}
+
+		let exp = unix_time(self.exp)
+			.ok_or_else(|| LogoutTokenError::Claims("`exp` out of range".to_owned()))?;
+		if leeway.is_expired(clock, exp) {
+			return Err(LogoutTokenError::Expired);
+		}
+
+		let iat = unix_time(self.iat)
+			.ok_or_else(|| LogoutTokenError::Claims("`iat` out of range".to_owned()))?;
+		if leeway.is_not_yet_valid(clock, iat) {
+			return Err(LogoutTokenError::IssuedInFuture);
+		}
+
+		Ok(())
+	}
+
+	/// Checks whether the token ends the session established with the given
+	/// ID token.
+	///
+	/// The issuer must match, along with the `sid` and `sub` claims present
+	/// in the logout token.
+	pub fn ends_session<E>(&self, id_token: &IdTokenClaims<E>) -> bool {
+		self.iss == id_token.iss
+			&& self
+				.sid
+				.as_ref()
+				.is_none_or(|sid| id_token.sid.as_ref() == Some(sid))
+			&& self.sub.as_ref().is_none_or(|sub| *sub == id_token.sub)
+	}
+}
+
+/// Logout token signer, typically implemented with the keys of the OpenID
+/// provider.
+pub trait LogoutTokenSigner {
+	/// Signs the given JSON claims, returning a compact JWS whose `typ`
+	/// header is [`LOGOUT_TOKEN_TYPE`].
+	fn sign_logout_token(
+		&self,
+		claims: &[u8],
+	) -> impl Future<Output = Result<String, OAuth2ClientError>>;
+}
+
+impl<T: LogoutTokenSigner> LogoutTokenSigner for &T {
+	fn sign_logout_token(
+		&self,
+		claims: &[u8],
+	) -> impl Future<Output = Result<String, OAuth2ClientError>> {
+		T::sign_logout_token(*self, claims)
+	}
+}
+
+/// Back-channel logout request, posted by the OpenID provider to the
+/// `backchannel_logout_uri` of the relying party.
+///
+/// See: <https://openid.net/specs/openid-connect-backchannel-1_0.html#BCRequest>
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct BackchannelLogoutRequest {
+	/// The signed logout token.
+	pub logout_token: String,
+}
+
+/// Posts the given signed logout token to the `backchannel_logout_uri` of a
+/// relying party.
+///
+/// Fails if the relying party does not respond with a `2xx` status, with an
+/// [`ErrorResponse`](OAuth2ClientError::ErrorResponse) error if it included
+/// one.
+pub async fn send_logout_token(
+	http_client: &impl HttpClient,
+	backchannel_logout_uri: &Uri,
+	logout_token: &str,
+) -> Result<(), OAuth2ClientError> {
+	let request = http::Request::builder()
+		.method(http::Method::POST)
+		.uri(backchannel_logout_uri.as_str())
+		.header(CONTENT_TYPE, APPLICATION_X_WWW_FORM_URLENCODED)
+		.body(WwwFormUrlEncoded::encode(&BackchannelLogoutRequest {
+			logout_token: logout_token.to_owned(),
+		}))
+		.map_err(OAuth2ClientError::request)?;
+
+	let response = http_client.send(request).await?;
+	if response.status().is_success() {
+		Ok(())
+	} else {
+		Err(OAuth2ClientError::token_error(&response))
+	}
+}
+
+/// Error returned when a logout token is rejected.
+///
+/// See: <https://openid.net/specs/openid-connect-backchannel-1_0.html#Validation>
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum LogoutTokenError {
+	/// The logout token is not a well-formed JWS.
+	#[error("malformed logout token")]
+	Malformed,
+
+	/// The logout token is not signed.
+	#[error("unsecured logout token")]
+	Unsecured,
+
+	/// The `typ` header of the logout token is neither
+	/// [`LOGOUT_TOKEN_TYPE`] nor `JWT`.
+	#[error("unexpected logout token type `{0}`")]
+	Type(String),
+
+	/// The signature of the logout token is invalid.
+	#[error("invalid logout token signature")]
+	Signature,
+
+	/// The claims of the logout token could not be decoded.
+	#[error("invalid logout token claims: {0}")]
+	Claims(String),
+
+	/// The `iss` claim is not the expected OpenID provider.
+	#[error("unexpected logout token issuer")]
+	Issuer,
+
+	/// The `aud` claim does not contain the client identifier of the
+	/// relying party.
+	#[error("unexpected logout token audience")]
+	Audience,
+
+	/// The `events` claim does not contain the [`BACKCHANNEL_LOGOUT_EVENT`]
+	/// member.
+	#[error("missing back-channel logout event")]
+	MissingEvent,
+
+	/// Neither the `sub` nor the `sid` claim is present.
+	#[error("missing `sub` or `sid` claim")]
+	MissingSubject,
+
+	/// The logout token contains a `nonce` claim, as ID tokens do.
+	#[error("logout token must not contain a `nonce` claim")]
+	Nonce,
+
+	/// The logout token expired.
+	#[error("logout token expired")]
+	Expired,
+
+	/// The logout token is issued in the future.
+	#[error("logout token issued in the future")]
+	IssuedInFuture,
+
+	/// The relying party could not end the session, e.g. because the token
+	/// was already received (replay).
+	#[error("logout failed: {0}")]
+	Rejected(String),
+}
+
+/// Decoder of the logout tokens received by a relying party.
+///
+/// The signature is checked with the given [`JwsSignatureVerifier`],
+/// typically implemented with the keys published by the OpenID provider,
+/// then the claims with [`LogoutToken::validate`], tolerating the
+/// configured clock skew.
+///
+/// Relying parties should also reject the tokens whose `jti` they already
+/// received.
+pub struct LogoutTokenDecoder<V, K = SystemClock> {
+	signature_verifier: V,
+	issuer: IssuerBuf,
+	client_id: ClientIdBuf,
+	clock: K,
+	leeway: LeewayConfig,
+}
+
+impl<V> LogoutTokenDecoder<V> {
+	/// Creates a new decoder for the logout tokens issued by `issuer` to
+	/// the relying party `client_id`.
+	pub fn new(signature_verifier: V, issuer: IssuerBuf, client_id: ClientIdBuf) -> Self {
+		Self {
+			signature_verifier,
+			issuer,
+			client_id,
+			clock: SystemClock,
+			leeway: LeewayConfig::default(),
+		}
+	}
+}
+
+impl<V, K> LogoutTokenDecoder<V, K> {
+	/// Sets the clock skew tolerance.
+	pub fn with_leeway(self, leeway: LeewayConfig) -> Self {
+		Self { leeway, ..self }
+	}
+
+	/// Sets the clock used to check the token expiration.
+	pub fn with_clock<L>(self, clock: L) -> LogoutTokenDecoder<V, L> {
+		LogoutTokenDecoder {
+			signature_verifier: self.signature_verifier,
+			issuer: self.issuer,
+			client_id: self.client_id,
+			clock,
+			leeway: self.leeway,
+		}
+	}
+}
+
+impl<V, K> LogoutTokenDecoder<V, K>
+where
+	V: JwsSignatureVerifier,
+	K: Clock,
+{
+	/// Decodes and checks the given logout token.
+	pub async fn decode(&self, logout_token: &str) -> Result<LogoutToken, LogoutTokenError> {
+		let jws = decode_compact_jws(logout_token).ok_or(LogoutTokenError::Malformed)?;
+		if jws.header.alg == "none" {
+			return Err(LogoutTokenError::Unsecured);
+		}
+
+		if let Some(typ) = jws.header.typ.as_deref()
+			&& !is_logout_token_type(typ)
+		{
+			return Err(LogoutTokenError::Type(typ.to_owned()));
+		}
+
+		if !jws.verify_signature(&self.signature_verifier).await {
+			return Err(LogoutTokenError::Signature);
+		}
+
+		let claims: serde_json::Value = jws.claims().ok_or(LogoutTokenError::Malformed)?;
+		let token: LogoutToken =
+			serde_json::from_value(claims).map_err(|e| LogoutTokenError::Claims(e.to_string()))?;
+		token.validate(&self.issuer, &self.client_id, &self.leeway, &self.clock)?;
+
+		Ok(token)
+	}
+}
+
+/// Checks the `typ` header of a logout token.
+///
+/// Logout tokens should be explicitly typed, but OpenID providers predating
+/// explicit typing use `JWT`.
+fn is_logout_token_type(typ: &str) -> bool {
+	let typ = typ.strip_prefix("application/").unwrap_or(typ);
+	typ.eq_ignore_ascii_case(LOGOUT_TOKEN_TYPE) || typ.eq_ignore_ascii_case("JWT")
+}
+
+#[cfg(feature = "axum")]
+pub use self::axum::*;
+
+#[cfg(feature = "axum")]
+mod axum {
+	use std::sync::Arc;
+
+	use ::axum::{
+		extract::{RawForm, State},
+		http::{StatusCode, header::CACHE_CONTROL},
+		response::{IntoResponse, Response},
+		routing::post,
+	};
+
+	use crate::server::{ErrorCode, ErrorResponse, SecureJson};
+
+	use super::*;
+
+	/// Relying party receiving back-channel logout requests.
+	pub trait BackchannelLogoutReceiver: Send + Sync + 'static {
+		/// Signature verifier of the logout tokens.
+		type SignatureVerifier: JwsSignatureVerifier;
+
+		/// Clock used to check the expiration of the logout tokens.
+		type Clock: Send + Sync + Clock;
+
+		/// Returns the decoder of the received logout tokens.
+		fn logout_token_decoder(&self)
+		-> &LogoutTokenDecoder<Self::SignatureVerifier, Self::Clock>;
+
+		/// Ends the sessions identified by the given verified logout token.
+		///
+		/// Should fail with [`LogoutTokenError::Rejected`] if the token was
+		/// already received, or if the sessions could not be ended.
+		fn logout(
+			&self,
+			logout_token: LogoutToken,
+		) -> impl Send + Future<Output = Result<(), LogoutTokenError>>;
+	}
+
+	pub trait BackchannelLogoutRouter<S> {
+		/// Receives back-channel logout requests at the given path, which is
+		/// the `backchannel_logout_uri` registered by the relying party.
+		fn backchannel_logout_route(self, path: &str) -> Self;
+	}
+
+	impl<S: BackchannelLogoutReceiver> BackchannelLogoutRouter<S> for ::axum::Router<Arc<S>> {
+		fn backchannel_logout_route(self, path: &str) -> Self {
+			self.route(path, post(receive_logout_token::<S>))
+		}
+	}
+
+	/// Back-channel logout endpoint.
+	///
+	/// See: <https://openid.net/specs/openid-connect-backchannel-1_0.html#BCResponse>
+	pub(super) async fn receive_logout_token<S>(
+		State(receiver): State<Arc<S>>,
+		RawForm(body): RawForm,
+	) -> Response
+	where
+		S: BackchannelLogoutReceiver,
+	{
+		let request = match serde_html_form::from_bytes::<BackchannelLogoutRequest>(&body) {
+			Ok(request) => request,
+			Err(_) => return LogoutTokenError::Malformed.into_response(),
+		};
+
+		let logout_token = match receiver
+			.logout_token_decoder()
+			.decode(&request.logout_token)
+			.await
+		{
+			Ok(logout_token) => logout_token,
+			Err(e) => return e.into_response(),
+		};
+
+		match receiver.logout(logout_token).await {
+			Ok(()) => (StatusCode::OK, [(CACHE_CONTROL, "no-store")]).into_response(),
+			Err(e) => e.into_response(),
+		}
+	}
+
+	impl IntoResponse for LogoutTokenError {
+		fn into_response(self) -> Response {
+			(
+				StatusCode::BAD_REQUEST,
+				SecureJson(ErrorResponse::<_>::new(
+					ErrorCode::InvalidRequest,
+					Some(self.to_string()),
+					None,
+				)),
+			)
+				.into_response()
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		client_id, issuer,
+		time::MockClock,
+		util::tests::{TestVerifier, jws},
+	};
+
+	use super::*;
+
+	/// Signs with `HS256`, the signature being `signed`.
+	struct TestSigner;
+
+	impl LogoutTokenSigner for TestSigner {
+		async fn sign_logout_token(&self, claims: &[u8]) -> Result<String, OAuth2ClientError> {
+			Ok(jws(
+				serde_json::json!({ "typ": "logout+jwt", "alg": "HS256" }),
+				claims,
+				b"signed",
+			))
+		}
+	}
+
+	fn decoder() -> LogoutTokenDecoder<TestVerifier, MockClock> {
+		LogoutTokenDecoder::new(
+			TestVerifier,
+			issuer!("https://server.example.com").to_owned(),
+			client_id!("s6BhdRkqt3").to_owned(),
+		)
+		.with_clock(MockClock::from_unix_timestamp(1000))
+	}
+
+	fn logout_token() -> LogoutToken {
+		LogoutToken::new(
+			issuer!("https://server.example.com").to_owned(),
+			client_id!("s6BhdRkqt3").to_owned(),
+			"bWJq".to_owned(),
+			&MockClock::from_unix_timestamp(1000),
+		)
+		.with_subject("248289761001".to_owned())
+		.with_session_id("08a5019c-17e1-4977-8f42-65a12843ea02".to_owned())
+	}
+
+	async fn decode(claims: &serde_json::Value) -> Result<LogoutToken, LogoutTokenError> {
+		let token = jws(
+			serde_json::json!({ "typ": "logout+jwt", "alg": "HS256" }),
+			claims.to_string().as_bytes(),
+			b"signed",
+		);
+		decoder().decode(&token).await
+	}
+
+	#[tokio::test]
+	async fn valid_logout_token() {
+		let token = logout_token().sign(&TestSigner).await.unwrap();
+		assert_eq!(decoder().decode(&token).await, Ok(logout_token()));
+
+		let claims = serde_json::json!({
+			"iss": "https://server.example.com",
+			"aud": ["s6BhdRkqt3", "other"],
+			"iat": 1000,
+			"exp": 1120,
+			"jti": "bWJq",
+			"sid": "08a5019c-17e1-4977-8f42-65a12843ea02",
+			"events": { "http://schemas.openid.net/event/backchannel-logout": {} }
+		});
+		let token = decode(&claims).await.unwrap();
+		assert_eq!(token.sub, None);
+
+		let id_token: IdTokenClaims = serde_json::from_value(serde_json::json!({
+			"iss": "https://server.example.com",
+			"sub": "248289761001",
+			"aud": "s6BhdRkqt3",
+			"exp": 1120,
+			"iat": 1000,
+			"sid": "08a5019c-17e1-4977-8f42-65a12843ea02"
+		}))
+		.unwrap();
+		assert!(token.ends_session(&id_token));
+		assert!(logout_token().ends_session(&id_token));
+		assert!(
+			!logout_token()
+				.with_session_id("other".to_owned())
+				.ends_session(&id_token)
+		);
+		assert_eq!(
+			token.sid.as_deref(),
+			Some("08a5019c-17e1-4977-8f42-65a12843ea02")
+		);
+	}
+
+	#[test]
+	fn expires_in() {
+		let token = logout_token().with_expires_in(Duration::from_secs(60));
+		assert_eq!(token.exp, 1060);
+
+		let token = logout_token().with_expires_in(Duration::MAX);
+		assert_eq!(token.exp, u64::MAX);
+	}
+
+	#[tokio::test]
+	async fn invalid_logout_token() {
+		let claims = serde_json::to_value(logout_token()).unwrap();
+		let with = |key: &str, value: serde_json::Value| {
+			let mut claims = claims.clone();
+			claims[key] = value;
+			claims
+		};
+		let without = |keys: &[&str]| {
+			let mut claims = claims.clone();
+			for key in keys {
+				claims.as_object_mut().unwrap().remove(*key);
+			}
+			claims
+		};
+
+		assert_eq!(
+			decode(&with("nonce", "n-0S6_WzA2Mj".into())).await,
+			Err(LogoutTokenError::Nonce)
+		);
+		assert_eq!(
+			decode(&with("events", serde_json::json!({}))).await,
+			Err(LogoutTokenError::MissingEvent)
+		);
+		assert_eq!(
+			decode(&without(&["sub", "sid"])).await,
+			Err(LogoutTokenError::MissingSubject)
+		);
+		assert_eq!(
+			decode(&with("aud", "other".into())).await,
+			Err(LogoutTokenError::Audience)
+		);
+		assert_eq!(
+			decode(&with("iss", "https://attacker.example.com".into())).await,
+			Err(LogoutTokenError::Issuer)
+		);
+		assert_eq!(
+			decode(&with("exp", 900.into())).await,
+			Err(LogoutTokenError::Expired)
+		);
+		assert!(matches!(
+			decode(&without(&["jti"])).await,
+			Err(LogoutTokenError::Claims(_))
+		));
+		assert!(matches!(
+			decode(&with("exp", u64::MAX.into())).await,
+			Err(LogoutTokenError::Claims(_))
+		));
+		assert!(matches!(
+			decode(&with("iat", u64::MAX.into())).await,
+			Err(LogoutTokenError::Claims(_))
+		));
+
+		// Wrong signature.
+		let token = jws(
+			serde_json::json!({ "typ": "logout+jwt", "alg": "HS256" }),
+			claims.to_string().as_bytes(),
+			b"forged",
+		);
+		assert_eq!(
+			decoder().decode(&token).await,
+			Err(LogoutTokenError::Signature)
+		);
+
+		// Access token instead of logout token.
+		let token = jws(
+			serde_json::json!({ "typ": "at+jwt", "alg": "HS256" }),
+			claims.to_string().as_bytes(),
+			b"signed",
+		);
+		assert_eq!(
+			decoder().decode(&token).await,
+			Err(LogoutTokenError::Type("at+jwt".to_owned()))
+		);
+	}
+
+	#[tokio::test]
+	async fn send() {
+		/// Relying party accepting the logout token `token`.
+		struct TestRelyingParty;
+
+		impl HttpClient for TestRelyingParty {
+			async fn send(
+				&self,
+				request: http::Request<Vec<u8>>,
+			) -> Result<http::Response<Vec<u8>>, OAuth2ClientError> {
+				assert_eq!(request.uri(), "https://rp.example.org/backchannel_logout");
+				let status = if request.body() == b"logout_token=token" {
+					http::StatusCode::OK
+				} else {
+					http::StatusCode::BAD_REQUEST
+				};
+
+				Ok(http::Response::builder()
+					.status(status)
+					.header(CONTENT_TYPE, "application/json")
+					.body(br#"{"error":"invalid_request"}"#.to_vec())
+					.unwrap())
+			}
+		}
+
+		let uri = iref::uri!("https://rp.example.org/backchannel_logout");
+		assert!(
+			send_logout_token(&TestRelyingParty, uri, "token")
+				.await
+				.is_ok()
+		);
+		assert_eq!(
+			send_logout_token(&TestRelyingParty, uri, "forged")
+				.await
+				.unwrap_err()
+				.error_code(),
+			Some("invalid_request")
+		);
+	}
+
+	#[cfg(feature = "axum")]
+	#[tokio::test]
+	async fn receive() {
+		use std::sync::{Arc, Mutex};
+
+		use ::axum::{
+			extract::{RawForm, State},
+			http::StatusCode,
+		};
+
+		struct TestReceiver {
+			decoder: LogoutTokenDecoder<TestVerifier, MockClock>,
+			received: Mutex<Vec<String>>,
+		}
+
+		impl BackchannelLogoutReceiver for TestReceiver {
+			type SignatureVerifier = TestVerifier;
+			type Clock = MockClock;
+
+			fn logout_token_decoder(&self) -> &LogoutTokenDecoder<TestVerifier, MockClock> {
+				&self.decoder
+			}
+
+			async fn logout(&self, logout_token: LogoutToken) -> Result<(), LogoutTokenError> {
+				let mut received = self.received.lock().unwrap();
+				if received.contains(&logout_token.jti) {
+					return Err(LogoutTokenError::Rejected(
+						"replayed logout token".to_owned(),
+					));
+				}
+
+				received.push(logout_token.jti);
+				Ok(())
+			}
+		}
+
+		let receiver = Arc::new(TestReceiver {
+			decoder: decoder(),
+			received: Mutex::default(),
+		});
+		let token = logout_token().sign(&TestSigner).await.unwrap();
+		let body = serde_html_form::to_string(BackchannelLogoutRequest {
+			logout_token: token,
+		})
+		.unwrap();
+		let receive = async |body: String| {
+			receive_logout_token(State(receiver.clone()), RawForm(body.into())).await
+		};
+
+		let response = receive(body.clone()).await;
+		assert_eq!(response.status(), StatusCode::OK);
+		assert_eq!(response.headers()["cache-control"], "no-store");
+
+		// Replayed.
+		assert_eq!(receive(body).await.status(), StatusCode::BAD_REQUEST);
+
+		assert_eq!(
+			receive("logout_token=forged".to_owned()).await.status(),
+			StatusCode::BAD_REQUEST
+		);
+	}
+}
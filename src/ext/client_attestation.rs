@@ -0,0 +1,200 @@
+//! OAuth 2.0 Attestation-Based Client Authentication
+//!
+//! See: <https://datatracker.ietf.org/doc/html/draft-ietf-oauth-attestation-based-client-auth>
+//!
+//! Client instances, such as OpenID4VCI wallets, authenticate with a client
+//! attestation issued by a client attester (e.g. the wallet provider),
+//! along with a proof of possession of the key it is bound to. Both are
+//! JWTs, sent in the `OAuth-Client-Attestation` and
+//! `OAuth-Client-Attestation-PoP` headers of requests wrapped with
+//! [`with_client_attestation`](AddClientAttestation::with_client_attestation).
+//!
+//! Wallets pushing their authorization requests, as required by several
+//! national eID profiles, attach the attestation to the PAR request with
+//! [`ParRequestBuilder::with_wallet_attestation`].
+use std::{future::Future, ops::Deref};
+
+use http::{HeaderName, HeaderValue};
+use iref::{Uri, UriBuf};
+
+use crate::{
+	client::OAuth2ClientError,
+	endpoints::{HttpRequest, RequestBuilder},
+	transport::HttpClient,
+};
+
+#[cfg(feature = "par")]
+use crate::endpoints::pushed_authorization::ParRequestBuilder;
+
+/// `OAuth-Client-Attestation` header name.
+///
+/// See: <https://datatracker.ietf.org/doc/html/draft-ietf-oauth-attestation-based-client-auth#section-6.1>
+pub const OAUTH_CLIENT_ATTESTATION: HeaderName =
+	HeaderName::from_static("oauth-client-attestation");
+
+/// `OAuth-Client-Attestation-PoP` header name.
+///
+/// See: <https://datatracker.ietf.org/doc/html/draft-ietf-oauth-attestation-based-client-auth#section-6.1>
+pub const OAUTH_CLIENT_ATTESTATION_POP: HeaderName =
+	HeaderName::from_static("oauth-client-attestation-pop");
+
+/// Provider of client attestations and their proofs of possession.
+///
+/// This crate does not implement any cryptographic algorithm: the
+/// attestation is obtained from the client attester, and the proof of
+/// possession signed with the key it is bound to, by implementors of this
+/// trait.
+pub trait ClientAttester {
+	/// Returns the client attestation JWT.
+	fn attestation(&self) -> impl Future<Output = Result<String, OAuth2ClientError>>;
+
+	/// Returns a new client attestation proof of possession JWT, whose `aud`
+	/// claim is the given issuer identifier of the authorization server.
+	fn proof_of_possession(
+		&self,
+		audience: &Uri,
+	) -> impl Future<Output = Result<String, OAuth2ClientError>>;
+}
+
+impl<T: ClientAttester> ClientAttester for &T {
+	fn attestation(&self) -> impl Future<Output = Result<String, OAuth2ClientError>> {
+		T::attestation(*self)
+	}
+
+	fn proof_of_possession(
+		&self,
+		audience: &Uri,
+	) -> impl Future<Output = Result<String, OAuth2ClientError>> {
+		T::proof_of_possession(*self, audience)
+	}
+}
+
+/// Extension wrapper that authenticates a request with a client
+/// attestation.
+///
+/// A new proof of possession is requested from the [`ClientAttester`] every
+/// time the request is built.
+pub struct WithClientAttestation<A, T> {
+	/// The inner request.
+	pub value: T,
+
+	/// The client attester.
+	pub attester: A,
+
+	/// Issuer identifier of the authorization server, audience of the proof
+	/// of possession.
+	pub audience: UriBuf,
+}
+
+impl<A, T> WithClientAttestation<A, T> {
+	/// Creates a new [`WithClientAttestation`] wrapping the given request.
+	pub fn new(value: T, attester: A, audience: UriBuf) -> Self {
+		Self {
+			value,
+			attester,
+			audience,
+		}
+	}
+}
+
+impl<A, T> Deref for WithClientAttestation<A, T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		&self.value
+	}
+}
+
+impl<E, A, T> HttpRequest<E> for WithClientAttestation<A, T>
+where
+	T: HttpRequest<E>,
+	A: ClientAttester,
+{
+	type ContentType = T::ContentType;
+	type RequestBody<'b>
+		= T::RequestBody<'b>
+	where
+		Self: 'b;
+	type Response = T::Response;
+	type ResponsePayload = T::ResponsePayload;
+
+	async fn build_request(
+		&self,
+		endpoint: &E,
+		http_client: &impl HttpClient,
+	) -> Result<http::Request<Self::RequestBody<'_>>, OAuth2ClientError> {
+		let mut request = self.value.build_request(endpoint, http_client).await?;
+		let attestation = self.attester.attestation().await?;
+		let pop = self.attester.proof_of_possession(&self.audience).await?;
+
+		let headers = request.headers_mut();
+		headers.insert(OAUTH_CLIENT_ATTESTATION, header_value(attestation)?);
+		headers.insert(OAUTH_CLIENT_ATTESTATION_POP, header_value(pop)?);
+		Ok(request)
+	}
+
+	fn decode_response(
+		&self,
+		endpoint: &E,
+		response: http::Response<Vec<u8>>,
+	) -> Result<http::Response<Self::ResponsePayload>, OAuth2ClientError> {
+		self.value.decode_response(endpoint, response)
+	}
+
+	async fn process_response(
+		&self,
+		endpoint: &E,
+		http_client: &impl HttpClient,
+		response: http::Response<Self::ResponsePayload>,
+	) -> Result<Self::Response, OAuth2ClientError> {
+		self.value
+			.process_response(endpoint, http_client, response)
+			.await
+	}
+}
+
+fn header_value(jwt: String) -> Result<HeaderValue, OAuth2ClientError> {
+	HeaderValue::try_from(jwt).map_err(OAuth2ClientError::request)
+}
+
+/// Extension trait for authenticating a request with a client attestation.
+pub trait AddClientAttestation<A> {
+	/// The resulting type after adding the client attestation.
+	type Output;
+
+	/// Wraps the current request so that it carries the client attestation
+	/// of `attester`, with a proof of possession for the authorization server
+	/// identified by `audience`.
+	fn with_client_attestation(self, attester: A, audience: &Uri) -> Self::Output;
+}
+
+impl<A, E, T> AddClientAttestation<A> for RequestBuilder<E, T> {
+	type Output = RequestBuilder<E, WithClientAttestation<A, T>>;
+
+	fn with_client_attestation(self, attester: A, audience: &Uri) -> Self::Output {
+		self.map(|value| WithClientAttestation::new(value, attester, audience.to_owned()))
+	}
+}
+
+#[cfg(feature = "par")]
+impl<'a, C, T> ParRequestBuilder<'a, C, T> {
+	/// Authenticates the pushed authorization request with the wallet
+	/// attestation of `attester`, with a proof of possession for the
+	/// authorization server identified by `audience`.
+	///
+	/// Same as
+	/// [`with_client_attestation`](AddClientAttestation::with_client_attestation),
+	/// for wallets acting as OAuth 2.0 clients of a credential issuer.
+	///
+	/// See: <https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0.html#name-wallet-attestation>
+	pub fn with_wallet_attestation<A>(
+		self,
+		attester: A,
+		audience: &Uri,
+	) -> ParRequestBuilder<'a, C, WithClientAttestation<A, T>>
+	where
+		A: ClientAttester,
+	{
+		self.with_client_attestation(attester, audience)
+	}
+}
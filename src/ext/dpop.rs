@@ -0,0 +1,214 @@
+//! OAuth 2.0 Demonstrating Proof of Possession (DPoP)
+//!
+//! See: <https://www.rfc-editor.org/rfc/rfc9449.html>
+//!
+//! Authorization and resource servers can require DPoP proofs to include a
+//! server-provided nonce, sent in the `DPoP-Nonce` response header. Each
+//! server issues its own nonces, which a client keeps in a
+//! [`DpopNonceCache`], keyed by the origin of the server, so that they can
+//! be used across requests to the token endpoint and resources.
+use std::{
+	collections::HashMap,
+	sync::Mutex,
+	time::{Duration, SystemTime},
+};
+
+use http::HeaderName;
+use iref::Uri;
+
+use crate::time::{Clock, SystemClock};
+
+/// `DPoP` header name.
+///
+/// See: <https://www.rfc-editor.org/rfc/rfc9449.html#section-4.1>
+pub const DPOP: HeaderName = HeaderName::from_static("dpop");
+
+/// `DPoP-Nonce` header name.
+///
+/// See: <https://www.rfc-editor.org/rfc/rfc9449.html#section-8>
+pub const DPOP_NONCE: HeaderName = HeaderName::from_static("dpop-nonce");
+
+/// Error code returned by servers requiring a DPoP proof with a nonce.
+///
+/// See: <https://www.rfc-editor.org/rfc/rfc9449.html#section-8>
+pub const USE_DPOP_NONCE: &str = "use_dpop_nonce";
+
+/// Default lifetime of the nonces held by a [`DpopNonceCache`].
+pub const DEFAULT_DPOP_NONCE_LIFETIME: Duration = Duration::from_secs(300);
+
+/// Cache of the DPoP nonces provided by servers, keyed by origin.
+///
+/// Nonces are replaced every time a server provides a new one, and are
+/// forgotten after the configured lifetime, after which a new nonce will be
+/// requested by the server. The cache can be shared between concurrent
+/// requests.
+pub struct DpopNonceCache<K = SystemClock> {
+	nonces: Mutex<HashMap<String, Nonce>>,
+	lifetime: Duration,
+	clock: K,
+}
+
+struct Nonce {
+	value: String,
+	expires_at: SystemTime,
+}
+
+impl DpopNonceCache {
+	/// Creates a new cache with the default lifetime of
+	/// [`DEFAULT_DPOP_NONCE_LIFETIME`].
+	pub fn new() -> Self {
+		Self {
+			nonces: Mutex::new(HashMap::new()),
+			lifetime: DEFAULT_DPOP_NONCE_LIFETIME,
+			clock: SystemClock,
+		}
+	}
+}
+
+impl Default for DpopNonceCache {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<K> DpopNonceCache<K> {
+	/// Sets the lifetime of the nonces.
+	pub fn with_lifetime(self, lifetime: Duration) -> Self {
+		Self { lifetime, ..self }
+	}
+
+	/// Sets the clock used to expire the nonces.
+	pub fn with_clock<L>(self, clock: L) -> DpopNonceCache<L> {
+		DpopNonceCache {
+			nonces: self.nonces,
+			lifetime: self.lifetime,
+			clock,
+		}
+	}
+
+	/// Forgets the nonce of the origin of `uri`, if any.
+	pub fn remove(&self, uri: &Uri) {
+		if let Some(origin) = origin(uri) {
+			self.nonces.lock().unwrap().remove(&origin);
+		}
+	}
+}
+
+impl<K: Clock> DpopNonceCache<K> {
+	/// Returns the current nonce of the origin of `uri`, to include in the
+	/// DPoP proof of a request to `uri`.
+	pub fn get(&self, uri: &Uri) -> Option<String> {
+		let origin = origin(uri)?;
+		let nonces = self.nonces.lock().unwrap();
+		nonces
+			.get(&origin)
+			.filter(|nonce| nonce.expires_at > self.clock.now())
+			.map(|nonce| nonce.value.clone())
+	}
+
+	/// Stores the nonce provided by the origin of `uri`.
+	///
+	/// Expired nonces are purged from the cache.
+	pub fn insert(&self, uri: &Uri, nonce: String) {
+		let Some(origin) = origin(uri) else {
+			return;
+		};
+
+		let now = self.clock.now();
+		let mut nonces = self.nonces.lock().unwrap();
+		nonces.retain(|_, nonce| nonce.expires_at > now);
+		nonces.insert(
+			origin,
+			Nonce {
+				value: nonce,
+				expires_at: now + self.lifetime,
+			},
+		);
+	}
+
+	/// Stores the nonce of the `DPoP-Nonce` header of a response to a request
+	/// sent to `uri`, if any.
+	///
+	/// Returns `true` if a nonce was stored.
+	pub fn update<B>(&self, uri: &Uri, response: &http::Response<B>) -> bool {
+		match response
+			.headers()
+			.get(DPOP_NONCE)
+			.and_then(|value| value.to_str().ok())
+		{
+			Some(nonce) => {
+				self.insert(uri, nonce.to_owned());
+				true
+			}
+			None => false,
+		}
+	}
+}
+
+/// Returns the origin of the given URI, normalized so that URIs with an
+/// explicit default port share the same origin.
+fn origin(uri: &Uri) -> Option<String> {
+	let scheme = uri.scheme().as_str().to_ascii_lowercase();
+	let authority = uri.authority()?.as_str().to_ascii_lowercase();
+
+	let default_port = match scheme.as_str() {
+		"https" => Some(":443"),
+		"http" => Some(":80"),
+		_ => None,
+	};
+
+	let authority = default_port
+		.and_then(|port| authority.strip_suffix(port))
+		.unwrap_or(&authority);
+
+	Some(format!("{scheme}://{authority}"))
+}
+
+#[cfg(test)]
+mod tests {
+	use iref::uri;
+
+	use crate::time::MockClock;
+
+	use super::*;
+
+	#[test]
+	fn nonces_per_origin() {
+		let cache = DpopNonceCache::new().with_clock(MockClock::from_unix_timestamp(0));
+		let token_uri = uri!("https://server.example.com/token");
+		let resource_uri = uri!("https://resource.example.com/protected");
+
+		cache.insert(token_uri, "eyJ7S_zG.eyJH0-Z.HX4w-7v".to_owned());
+		assert_eq!(
+			cache
+				.get(uri!("https://SERVER.example.com:443/par"))
+				.as_deref(),
+			Some("eyJ7S_zG.eyJH0-Z.HX4w-7v")
+		);
+		assert_eq!(cache.get(resource_uri), None);
+		assert_eq!(cache.get(uri!("http://server.example.com/token")), None);
+
+		let response = http::Response::builder()
+			.header(DPOP_NONCE, "resource-nonce")
+			.body(())
+			.unwrap();
+		assert!(cache.update(resource_uri, &response));
+		assert_eq!(cache.get(resource_uri).as_deref(), Some("resource-nonce"));
+		assert!(!cache.update(resource_uri, &http::Response::new(())));
+
+		cache.remove(token_uri);
+		assert_eq!(cache.get(token_uri), None);
+	}
+
+	#[test]
+	fn expired_nonce() {
+		let cache = DpopNonceCache::new()
+			.with_lifetime(Duration::from_secs(10))
+			.with_clock(MockClock::from_unix_timestamp(0));
+		let uri = uri!("https://server.example.com/token");
+
+		cache.insert(uri, "nonce".to_owned());
+		cache.clock.advance(Duration::from_secs(10));
+		assert_eq!(cache.get(uri), None);
+	}
+}
@@ -0,0 +1,531 @@
+//! HTTP Message Signatures
+//!
+//! See: <https://www.rfc-editor.org/rfc/rfc9421.html>
+//!
+//! Requests are signed by wrapping them with
+//! [`with_http_signature`](AddHttpSignature::with_http_signature). Once the
+//! inner request is built and its body encoded, the configured
+//! [components](SignatureComponent) are collected into a signature base,
+//! signed by an [`HttpMessageSigner`], and the result is attached with the
+//! `Signature-Input` and `Signature` headers. If the `content-digest` header
+//! is covered, it is first computed from the encoded body
+//! ([RFC 9530](https://www.rfc-editor.org/rfc/rfc9530.html)).
+use std::{fmt, future::Future, time::UNIX_EPOCH};
+
+use base64::{Engine, prelude::BASE64_STANDARD};
+use http::{HeaderName, HeaderValue, header::CONTENT_TYPE};
+use sha2::{Digest, Sha256};
+
+use crate::{
+	client::OAuth2ClientError,
+	endpoints::{HttpRequest, RequestBuilder},
+	time::{Clock, SystemClock},
+	transport::{ContentType, HttpClient},
+};
+
+/// `Content-Digest` header name.
+///
+/// See: <https://www.rfc-editor.org/rfc/rfc9530.html#section-2>
+pub const CONTENT_DIGEST: HeaderName = HeaderName::from_static("content-digest");
+
+/// `Signature-Input` header name.
+///
+/// See: <https://www.rfc-editor.org/rfc/rfc9421.html#section-4.1>
+pub const SIGNATURE_INPUT: HeaderName = HeaderName::from_static("signature-input");
+
+/// `Signature` header name.
+///
+/// See: <https://www.rfc-editor.org/rfc/rfc9421.html#section-4.2>
+pub const SIGNATURE: HeaderName = HeaderName::from_static("signature");
+
+/// Signer producing the signature of an HTTP message.
+///
+/// This crate does not implement any cryptographic algorithm: the signature
+/// is delegated to this trait.
+pub trait HttpMessageSigner {
+	/// Key identifier, included as the `keyid` signature parameter.
+	fn key_id(&self) -> Option<&str> {
+		None
+	}
+
+	/// Signature algorithm, included as the `alg` signature parameter.
+	///
+	/// This parameter is optional, since the algorithm is usually derived
+	/// from the key.
+	fn algorithm(&self) -> Option<&str> {
+		None
+	}
+
+	/// Signs the given signature base.
+	fn sign(
+		&self,
+		signature_base: &[u8],
+	) -> impl Future<Output = Result<Vec<u8>, OAuth2ClientError>>;
+}
+
+impl<T: HttpMessageSigner> HttpMessageSigner for &T {
+	fn key_id(&self) -> Option<&str> {
+		T::key_id(*self)
+	}
+
+	fn algorithm(&self) -> Option<&str> {
+		T::algorithm(*self)
+	}
+
+	fn sign(
+		&self,
+		signature_base: &[u8],
+	) -> impl Future<Output = Result<Vec<u8>, OAuth2ClientError>> {
+		T::sign(*self, signature_base)
+	}
+}
+
+/// HTTP message component covered by a signature.
+///
+/// See: <https://www.rfc-editor.org/rfc/rfc9421.html#section-2>
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SignatureComponent {
+	/// `@method` derived component.
+	Method,
+
+	/// `@target-uri` derived component.
+	TargetUri,
+
+	/// `@authority` derived component.
+	Authority,
+
+	/// `@scheme` derived component.
+	Scheme,
+
+	/// `@path` derived component.
+	Path,
+
+	/// `@query` derived component.
+	Query,
+
+	/// HTTP field.
+	Header(HeaderName),
+}
+
+impl SignatureComponent {
+	/// Returns the component identifier, without quotes.
+	pub fn name(&self) -> &str {
+		match self {
+			Self::Method => "@method",
+			Self::TargetUri => "@target-uri",
+			Self::Authority => "@authority",
+			Self::Scheme => "@scheme",
+			Self::Path => "@path",
+			Self::Query => "@query",
+			Self::Header(name) => name.as_str(),
+		}
+	}
+
+	/// Returns the value of this component in the given request.
+	///
+	/// Fails if the component is a header missing from the request.
+	pub fn value<B>(&self, request: &http::Request<B>) -> Result<String, OAuth2ClientError> {
+		let uri = request.uri();
+		match self {
+			Self::Method => Ok(request.method().as_str().to_owned()),
+			Self::TargetUri => Ok(uri.to_string()),
+			Self::Authority => uri
+				.authority()
+				.map(|authority| authority.as_str().to_ascii_lowercase())
+				.ok_or_else(|| OAuth2ClientError::request("missing request authority")),
+			Self::Scheme => uri
+				.scheme_str()
+				.map(str::to_ascii_lowercase)
+				.ok_or_else(|| OAuth2ClientError::request("missing request scheme")),
+			Self::Path => Ok(match uri.path() {
+				"" => "/".to_owned(),
+				path => path.to_owned(),
+			}),
+			Self::Query => Ok(format!("?{}", uri.query().unwrap_or_default())),
+			Self::Header(name) => {
+				let values = request
+					.headers()
+					.get_all(name)
+					.iter()
+					.map(|value| {
+						value.to_str().map(str::trim).map_err(|_| {
+							OAuth2ClientError::request(format!("invalid `{name}` header"))
+						})
+					})
+					.collect::<Result<Vec<_>, _>>()?;
+
+				if values.is_empty() {
+					Err(OAuth2ClientError::request(format!(
+						"missing signed `{name}` header"
+					)))
+				} else {
+					Ok(values.join(", "))
+				}
+			}
+		}
+	}
+}
+
+impl fmt::Display for SignatureComponent {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "\"{}\"", self.name())
+	}
+}
+
+/// HTTP Message Signature configuration.
+///
+/// By default, the signature is labeled `sig`, covers the method, target URI
+/// and content digest of the request, and includes its creation time.
+pub struct HttpSignature<S, K = SystemClock> {
+	signer: S,
+	label: String,
+	components: Vec<SignatureComponent>,
+	clock: K,
+}
+
+impl<S> HttpSignature<S> {
+	/// Creates a new signature configuration with the given signer.
+	pub fn new(signer: S) -> Self {
+		Self {
+			signer,
+			label: "sig".to_owned(),
+			components: vec![
+				SignatureComponent::Method,
+				SignatureComponent::TargetUri,
+				SignatureComponent::Header(CONTENT_DIGEST),
+			],
+			clock: SystemClock,
+		}
+	}
+}
+
+impl<S, K> HttpSignature<S, K> {
+	/// Sets the label of the signature in the `Signature-Input` and
+	/// `Signature` dictionaries.
+	pub fn with_label(self, label: impl Into<String>) -> Self {
+		Self {
+			label: label.into(),
+			..self
+		}
+	}
+
+	/// Sets the components covered by the signature, in order.
+	pub fn with_components(self, components: Vec<SignatureComponent>) -> Self {
+		Self { components, ..self }
+	}
+
+	/// Sets the clock used for the `created` signature parameter.
+	pub fn with_clock<L: Clock>(self, clock: L) -> HttpSignature<S, L> {
+		HttpSignature {
+			signer: self.signer,
+			label: self.label,
+			components: self.components,
+			clock,
+		}
+	}
+
+	/// Returns the components covered by the signature.
+	pub fn components(&self) -> &[SignatureComponent] {
+		&self.components
+	}
+}
+
+impl<S, K> HttpSignature<S, K>
+where
+	S: HttpMessageSigner,
+	K: Clock,
+{
+	/// Signs the given request, whose body encodes to `body`.
+	///
+	/// The `Content-Digest` header is added first if it is covered by the
+	/// signature and missing from the request.
+	pub async fn sign<B>(
+		&self,
+		request: &mut http::Request<B>,
+		body: &[u8],
+	) -> Result<(), OAuth2ClientError> {
+		if self
+			.components
+			.contains(&SignatureComponent::Header(CONTENT_DIGEST))
+			&& !request.headers().contains_key(CONTENT_DIGEST)
+		{
+			request
+				.headers_mut()
+				.insert(CONTENT_DIGEST, content_digest(body));
+		}
+
+		let mut signature_base = String::new();
+		for component in &self.components {
+			let value = component.value(request)?;
+			signature_base.push_str(&format!("{component}: {value}\n"));
+		}
+
+		let params = self.signature_params();
+		signature_base.push_str(&format!("\"@signature-params\": {params}"));
+
+		let signature = self.signer.sign(signature_base.as_bytes()).await?;
+
+		let label = &self.label;
+		let headers = request.headers_mut();
+		headers.insert(SIGNATURE_INPUT, header_value(format!("{label}={params}"))?);
+		headers.insert(
+			SIGNATURE,
+			header_value(format!("{label}=:{}:", BASE64_STANDARD.encode(signature)))?,
+		);
+
+		Ok(())
+	}
+
+	/// Returns the serialized signature parameters.
+	fn signature_params(&self) -> String {
+		let components: Vec<_> = self.components.iter().map(ToString::to_string).collect();
+		let mut params = format!("({})", components.join(" "));
+
+		if let Ok(created) = self.clock.now().duration_since(UNIX_EPOCH) {
+			params.push_str(&format!(";created={}", created.as_secs()));
+		}
+
+		if let Some(key_id) = self.signer.key_id() {
+			params.push_str(&format!(";keyid=\"{key_id}\""));
+		}
+
+		if let Some(algorithm) = self.signer.algorithm() {
+			params.push_str(&format!(";alg=\"{algorithm}\""));
+		}
+
+		params
+	}
+}
+
+/// Computes the SHA-256 `Content-Digest` header value of the given body.
+///
+/// See: <https://www.rfc-editor.org/rfc/rfc9530.html#section-2>
+pub fn content_digest(body: &[u8]) -> HeaderValue {
+	let digest = BASE64_STANDARD.encode(Sha256::digest(body));
+	// UNWRAP SAFETY: base64 is a valid header value.
+	HeaderValue::from_str(&format!("sha-256=:{digest}:")).unwrap()
+}
+
+fn header_value(value: String) -> Result<HeaderValue, OAuth2ClientError> {
+	HeaderValue::try_from(value).map_err(OAuth2ClientError::request)
+}
+
+/// Extension wrapper signing a request with HTTP Message Signatures.
+pub struct WithHttpSignature<'a, S, K, T> {
+	/// The signature configuration.
+	pub signature: &'a HttpSignature<S, K>,
+
+	/// The inner request being extended.
+	pub value: T,
+}
+
+impl<'a, S, K, T> WithHttpSignature<'a, S, K, T> {
+	/// Creates a new [`WithHttpSignature`] wrapping the given request.
+	pub fn new(value: T, signature: &'a HttpSignature<S, K>) -> Self {
+		Self { signature, value }
+	}
+}
+
+impl<'a, S, K, T> std::ops::Deref for WithHttpSignature<'a, S, K, T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		&self.value
+	}
+}
+
+impl<'a, E, S, K, T> HttpRequest<E> for WithHttpSignature<'a, S, K, T>
+where
+	T: HttpRequest<E>,
+	S: HttpMessageSigner,
+	K: Clock,
+{
+	type ContentType = T::ContentType;
+	type RequestBody<'b>
+		= T::RequestBody<'b>
+	where
+		Self: 'b;
+	type Response = T::Response;
+	type ResponsePayload = T::ResponsePayload;
+
+	async fn build_request(
+		&self,
+		endpoint: &E,
+		http_client: &impl HttpClient,
+	) -> Result<http::Request<Self::RequestBody<'_>>, OAuth2ClientError> {
+		let mut request = self.value.build_request(endpoint, http_client).await?;
+
+		// The content type is set when sending the request, but it must be
+		// known beforehand to be signed.
+		if let Some(content_type) = Self::ContentType::VALUE {
+			request.headers_mut().insert(CONTENT_TYPE, content_type);
+		}
+
+		let body = Self::ContentType::encode(request.body());
+		self.signature.sign(&mut request, &body).await?;
+		Ok(request)
+	}
+
+	fn decode_response(
+		&self,
+		endpoint: &E,
+		response: http::Response<Vec<u8>>,
+	) -> Result<http::Response<Self::ResponsePayload>, OAuth2ClientError> {
+		self.value.decode_response(endpoint, response)
+	}
+
+	async fn process_response(
+		&self,
+		endpoint: &E,
+		http_client: &impl HttpClient,
+		response: http::Response<Self::ResponsePayload>,
+	) -> Result<Self::Response, OAuth2ClientError> {
+		self.value
+			.process_response(endpoint, http_client, response)
+			.await
+	}
+}
+
+/// Extension trait for signing the request of a [`RequestBuilder`] with
+/// HTTP Message Signatures.
+pub trait AddHttpSignature<'a, S, K> {
+	/// The resulting type after adding the signature.
+	type Output;
+
+	/// Wraps the current request so that it is signed with the given
+	/// configuration once built.
+	fn with_http_signature(self, signature: &'a HttpSignature<S, K>) -> Self::Output;
+}
+
+impl<'a, S, K, E, T> AddHttpSignature<'a, S, K> for RequestBuilder<E, T>
+where
+	S: 'a,
+	K: 'a,
+{
+	type Output = RequestBuilder<E, WithHttpSignature<'a, S, K, T>>;
+
+	fn with_http_signature(self, signature: &'a HttpSignature<S, K>) -> Self::Output {
+		self.map(|value| WithHttpSignature::new(value, signature))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Mutex;
+
+	use iref::uri;
+
+	use crate::{
+		ClientId,
+		client::OAuth2Client,
+		client_id, code,
+		endpoints::token::{TokenEndpoint, TokenResponse},
+		time::MockClock,
+		transport::WwwFormUrlEncoded,
+	};
+
+	use super::*;
+
+	struct Client;
+
+	impl OAuth2Client for Client {
+		type TokenResponse = TokenResponse;
+
+		fn client_id(&self) -> &ClientId {
+			client_id!("s6BhdRkqt3")
+		}
+	}
+
+	/// Signer recording the signature base, and returning it reversed.
+	#[derive(Default)]
+	struct TestSigner(Mutex<Option<String>>);
+
+	impl HttpMessageSigner for TestSigner {
+		fn key_id(&self) -> Option<&str> {
+			Some("test-key")
+		}
+
+		async fn sign(&self, signature_base: &[u8]) -> Result<Vec<u8>, OAuth2ClientError> {
+			*self.0.lock().unwrap() = Some(String::from_utf8(signature_base.to_vec()).unwrap());
+			Ok(signature_base.iter().rev().copied().collect())
+		}
+	}
+
+	/// HTTP client that does not expect any request.
+	struct NoHttpClient;
+
+	impl HttpClient for NoHttpClient {
+		async fn send(
+			&self,
+			_request: http::Request<Vec<u8>>,
+		) -> Result<http::Response<Vec<u8>>, OAuth2ClientError> {
+			unreachable!()
+		}
+	}
+
+	#[tokio::test]
+	async fn signed_token_request() {
+		let signer = TestSigner::default();
+		let signature = HttpSignature::new(&signer)
+			.with_label("sig1")
+			.with_components(vec![
+				SignatureComponent::Method,
+				SignatureComponent::Authority,
+				SignatureComponent::Path,
+				SignatureComponent::Header(CONTENT_TYPE),
+				SignatureComponent::Header(CONTENT_DIGEST),
+			])
+			.with_clock(MockClock::from_unix_timestamp(1618884473));
+
+		let endpoint = TokenEndpoint::new(&Client, uri!("https://server.example.com/token"));
+		let request = endpoint
+			.exchange_code(code!("SplxlOBeZQQYbYS6WxSbIA").to_owned(), None)
+			.with_http_signature(&signature);
+
+		let request = request
+			.request
+			.build_request(&request.endpoint, &NoHttpClient)
+			.await
+			.unwrap();
+		let body = "grant_type=authorization_code&client_id=s6BhdRkqt3&code=SplxlOBeZQQYbYS6WxSbIA";
+		assert_eq!(WwwFormUrlEncoded::encode(request.body()), body.as_bytes());
+
+		let digest = format!("sha-256=:{}:", BASE64_STANDARD.encode(Sha256::digest(body)));
+		assert_eq!(request.headers()[CONTENT_DIGEST], digest);
+
+		let params = "(\"@method\" \"@authority\" \"@path\" \"content-type\" \"content-digest\");created=1618884473;keyid=\"test-key\"";
+		assert_eq!(request.headers()[SIGNATURE_INPUT], format!("sig1={params}"));
+
+		let signature_base = signer.0.lock().unwrap().take().unwrap();
+		assert_eq!(
+			signature_base,
+			format!(
+				"\"@method\": POST\n\
+				\"@authority\": server.example.com\n\
+				\"@path\": /token\n\
+				\"content-type\": application/x-www-form-urlencoded\n\
+				\"content-digest\": {digest}\n\
+				\"@signature-params\": {params}"
+			)
+		);
+
+		let reversed: Vec<u8> = signature_base.bytes().rev().collect();
+		assert_eq!(
+			request.headers()[SIGNATURE],
+			format!("sig1=:{}:", BASE64_STANDARD.encode(reversed))
+		);
+	}
+
+	#[tokio::test]
+	async fn missing_header() {
+		let signature = HttpSignature::new(TestSigner::default()).with_components(vec![
+			SignatureComponent::Header(HeaderName::from_static("dpop")),
+		]);
+
+		let mut request = http::Request::new(());
+		assert!(matches!(
+			signature.sign(&mut request, b"").await,
+			Err(OAuth2ClientError::Request(_))
+		));
+	}
+}
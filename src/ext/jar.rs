@@ -0,0 +1,815 @@
+//! JWT-Secured Authorization Request (JAR)
+//!
+//! See: <https://www.rfc-editor.org/rfc/rfc9101.html>
+//!
+//! The parameters of an authorization request are passed in a signed
+//! request object, optionally encrypted to the authorization server as a
+//! nested JWT, instead of the query of the authorization endpoint URI.
+//! Clients build it with
+//! [`into_request_object`](RequestBuilder::into_request_object) or
+//! [`into_encrypted_request_object`](RequestBuilder::into_encrypted_request_object),
+//! choosing the encryption algorithms among those advertised by the
+//! authorization server with [`RequestObjectEncryption::negotiate`].
+//! Authorization servers decode it with a [`RequestObjectDecoder`].
+//!
+//! This crate does not implement any cryptographic algorithm: signing,
+//! encryption, signature verification and decryption are delegated to the
+//! [`RequestObjectSigner`], [`RequestObjectEncrypter`],
+//! [`JwsSignatureVerifier`] and [`RequestObjectDecrypter`] traits, so that
+//! any JOSE backend can be plugged in.
+use std::future::Future;
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::{
+	ClientId, ClientIdBuf, Issuer, IssuerBuf,
+	client::{OAuth2Client, OAuth2ClientError},
+	endpoints::{Endpoint, RedirectRequest, RequestBuilder},
+	server::metadata::{AuthorizationServerMetadata, UnsupportedCapability},
+	time::{Clock, LeewayConfig, SystemClock},
+	util::{decode_compact_jws, unix_time},
+};
+
+pub use crate::util::{JwsHeader, JwsSignatureVerifier};
+
+/// Media type of request objects, set in their `typ` header.
+///
+/// See: <https://www.rfc-editor.org/rfc/rfc9101.html#section-10.8>
+pub const REQUEST_OBJECT_TYPE: &str = "oauth-authz-req+jwt";
+
+/// Request object signer.
+pub trait RequestObjectSigner {
+	/// Signs the given JSON claims, returning a compact JWS whose `typ`
+	/// header is [`REQUEST_OBJECT_TYPE`].
+	fn sign_request_object(
+		&self,
+		claims: &[u8],
+	) -> impl Future<Output = Result<String, OAuth2ClientError>>;
+}
+
+impl<T: RequestObjectSigner> RequestObjectSigner for &T {
+	fn sign_request_object(
+		&self,
+		claims: &[u8],
+	) -> impl Future<Output = Result<String, OAuth2ClientError>> {
+		T::sign_request_object(*self, claims)
+	}
+}
+
+/// Request object encrypter, typically implemented with the keys published
+/// by the authorization server.
+pub trait RequestObjectEncrypter {
+	/// Checks whether the given JWE key management (`alg`) and content
+	/// encryption (`enc`) algorithms are supported.
+	fn supports(&self, alg: &str, enc: &str) -> bool;
+
+	/// Encrypts the given signed request object, returning a compact JWE
+	/// whose `cty` header is `JWT`.
+	fn encrypt_request_object(
+		&self,
+		encryption: &RequestObjectEncryption,
+		jws: &str,
+	) -> impl Future<Output = Result<String, OAuth2ClientError>>;
+}
+
+impl<T: RequestObjectEncrypter> RequestObjectEncrypter for &T {
+	fn supports(&self, alg: &str, enc: &str) -> bool {
+		T::supports(*self, alg, enc)
+	}
+
+	fn encrypt_request_object(
+		&self,
+		encryption: &RequestObjectEncryption,
+		jws: &str,
+	) -> impl Future<Output = Result<String, OAuth2ClientError>> {
+		T::encrypt_request_object(*self, encryption, jws)
+	}
+}
+
+/// JWE algorithms used to encrypt a request object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestObjectEncryption {
+	/// Key management algorithm.
+	pub alg: String,
+
+	/// Content encryption algorithm.
+	pub enc: String,
+}
+
+impl RequestObjectEncryption {
+	/// Creates a new request object encryption with the given algorithms.
+	pub fn new(alg: String, enc: String) -> Self {
+		Self { alg, enc }
+	}
+
+	/// Chooses the first algorithms advertised in the
+	/// `request_object_encryption_alg_values_supported` and
+	/// `request_object_encryption_enc_values_supported` authorization server
+	/// metadata that are supported by the given encrypter.
+	///
+	/// Fails if the authorization server does not advertise any of them,
+	/// since it then does not accept encrypted request objects.
+	pub fn negotiate<P>(
+		metadata: &AuthorizationServerMetadata<P>,
+		encrypter: &impl RequestObjectEncrypter,
+	) -> Result<Self, UnsupportedCapability> {
+		let algs = metadata
+			.request_object_encryption_alg_values_supported
+			.iter()
+			.flatten();
+		let encs = metadata
+			.request_object_encryption_enc_values_supported
+			.as_deref()
+			.unwrap_or_default();
+
+		algs.flat_map(|alg| encs.iter().map(move |enc| (alg, enc)))
+			.find(|(alg, enc)| encrypter.supports(alg, enc))
+			.map(|(alg, enc)| Self::new(alg.clone(), enc.clone()))
+			.ok_or(UnsupportedCapability::RequestObjectEncryption)
+	}
+}
+
+/// Authorization request passing its parameters in a request object.
+///
+/// See: <https://www.rfc-editor.org/rfc/rfc9101.html#section-5>
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SecuredAuthorizationRequest {
+	/// The client identifier, which must match the one of the request
+	/// object.
+	pub client_id: ClientIdBuf,
+
+	/// The request object, a signed or nested JWT.
+	pub request: String,
+}
+
+impl RedirectRequest for SecuredAuthorizationRequest {
+	type RequestBody<'b>
+		= &'b Self
+	where
+		Self: 'b;
+
+	fn build_query(&self) -> Self::RequestBody<'_> {
+		self
+	}
+}
+
+/// Claims of a request object.
+///
+/// See: <https://www.rfc-editor.org/rfc/rfc9101.html#section-4>
+#[derive(Serialize)]
+struct RequestObjectClaims<'a, T> {
+	iss: &'a ClientId,
+	aud: &'a Issuer,
+
+	#[serde(flatten)]
+	params: T,
+}
+
+impl<E, T> RequestBuilder<E, T>
+where
+	E: Endpoint,
+	T: RedirectRequest,
+{
+	/// Replaces the authorization request parameters with a request object
+	/// signed by `signer`, intended for the authorization server identified
+	/// by `audience`.
+	///
+	/// The resulting request can be sent to the authorization endpoint, or
+	/// pushed.
+	pub async fn into_request_object<S>(
+		self,
+		audience: &Issuer,
+		signer: &S,
+	) -> Result<RequestBuilder<E, SecuredAuthorizationRequest>, OAuth2ClientError>
+	where
+		S: RequestObjectSigner,
+	{
+		let jws = self.sign_request_object(audience, signer).await?;
+		Ok(self.into_secured_request(jws))
+	}
+
+	/// Replaces the authorization request parameters with a request object
+	/// signed by `signer`, then encrypted by `encrypter` to the authorization
+	/// server identified by `audience`.
+	///
+	/// See [`RequestObjectEncryption::negotiate`] to choose the encryption
+	/// algorithms.
+	pub async fn into_encrypted_request_object<S, X>(
+		self,
+		audience: &Issuer,
+		signer: &S,
+		encrypter: &X,
+		encryption: &RequestObjectEncryption,
+	) -> Result<RequestBuilder<E, SecuredAuthorizationRequest>, OAuth2ClientError>
+	where
+		S: RequestObjectSigner,
+		X: RequestObjectEncrypter,
+	{
+		let jws = self.sign_request_object(audience, signer).await?;
+		let jwe = encrypter.encrypt_request_object(encryption, &jws).await?;
+		Ok(self.into_secured_request(jwe))
+	}
+
+	async fn sign_request_object(
+		&self,
+		audience: &Issuer,
+		signer: &impl RequestObjectSigner,
+	) -> Result<String, OAuth2ClientError> {
+		let claims = serde_json::to_vec(&RequestObjectClaims {
+			iss: self.endpoint.client().client_id(),
+			aud: audience,
+			params: self.request.build_query(),
+		})
+		.map_err(OAuth2ClientError::request)?;
+
+		signer.sign_request_object(&claims).await
+	}
+
+	fn into_secured_request(
+		self,
+		request: String,
+	) -> RequestBuilder<E, SecuredAuthorizationRequest> {
+		let client_id = self.endpoint.client().client_id().to_owned();
+		RequestBuilder::new(
+			self.endpoint,
+			SecuredAuthorizationRequest { client_id, request },
+		)
+	}
+}
+
+/// Request object decrypter, typically implemented with the keys of the
+/// authorization server.
+pub trait RequestObjectDecrypter: Send + Sync {
+	/// Decrypts the given compact JWE, returning its plaintext.
+	///
+	/// Implementations must reject the algorithms they do not expect, and
+	/// return `None` if decryption fails.
+	fn decrypt(&self, jwe: &str) -> impl Send + Future<Output = Option<Vec<u8>>>;
+}
+
+/// Rejects every encrypted request object.
+impl RequestObjectDecrypter for () {
+	async fn decrypt(&self, _jwe: &str) -> Option<Vec<u8>> {
+		None
+	}
+}
+
+/// Error returned when a request object is rejected.
+///
+/// Should be reported with the `invalid_request_object` error code.
+///
+/// See: <https://www.rfc-editor.org/rfc/rfc9101.html#section-6.3>
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RequestObjectError {
+	/// The request object is not a well-formed JWS or JWE.
+	#[error("malformed request object")]
+	Malformed,
+
+	/// The request object is not encrypted, although encryption is
+	/// required.
+	#[error("request object must be encrypted")]
+	EncryptionRequired,
+
+	/// The request object could not be decrypted.
+	#[error("request object could not be decrypted")]
+	Decryption,
+
+	/// The request object is not signed.
+	#[error("unsecured request object")]
+	Unsecured,
+
+	/// The `typ` header of the request object is not
+	/// [`REQUEST_OBJECT_TYPE`].
+	#[error("unexpected request object type `{0}`")]
+	Type(String),
+
+	/// The signature of the request object is invalid.
+	#[error("invalid request object signature")]
+	Signature,
+
+	/// The `client_id` of the request object does not match the one of the
+	/// authorization request.
+	#[error("request object `client_id` mismatch")]
+	ClientIdMismatch,
+
+	/// The `iss` claim of the request object is not the client identifier.
+	#[error("unexpected request object issuer")]
+	Issuer,
+
+	/// The `aud` claim of the request object does not identify the
+	/// authorization server.
+	#[error("unexpected request object audience")]
+	Audience,
+
+	/// The request object expired.
+	#[error("request object expired")]
+	Expired,
+
+	/// The claims of the request object could not be decoded.
+	#[error("invalid request object claims: {0}")]
+	Claims(String),
+}
+
+/// Decoder of the request objects received by an authorization server.
+///
+/// Encrypted request objects are decrypted with the configured
+/// [`RequestObjectDecrypter`], and rejected when none is configured. The
+/// signature is then checked with the [`JwsSignatureVerifier`], and the
+/// claims are checked:
+/// - `client_id` and `iss` must be the client identifier of the
+///   authorization request;
+/// - `aud` must identify the authorization server by its issuer identifier;
+/// - `exp`, if present, must not have passed.
+///
+/// See: <https://www.rfc-editor.org/rfc/rfc9101.html#section-6.3>
+pub struct RequestObjectDecoder<V, D = (), K = SystemClock> {
+	verifier: V,
+	decrypter: D,
+	issuer: IssuerBuf,
+	require_encryption: bool,
+	clock: K,
+	leeway: LeewayConfig,
+}
+
+impl<V> RequestObjectDecoder<V> {
+	/// Creates a new decoder for the authorization server identified by
+	/// `issuer`, checking signatures with the given verifier, and rejecting
+	/// encrypted request objects.
+	pub fn new(verifier: V, issuer: IssuerBuf) -> Self {
+		Self {
+			verifier,
+			decrypter: (),
+			issuer,
+			require_encryption: false,
+			clock: SystemClock,
+			leeway: LeewayConfig::default(),
+		}
+	}
+}
+
+impl<V, D, K> RequestObjectDecoder<V, D, K> {
+	/// Decrypts encrypted request objects with the given decrypter.
+	pub fn with_decrypter<U>(self, decrypter: U) -> RequestObjectDecoder<V, U, K> {
+		RequestObjectDecoder {
+			verifier: self.verifier,
+			decrypter,
+			issuer: self.issuer,
+			require_encryption: self.require_encryption,
+			clock: self.clock,
+			leeway: self.leeway,
+		}
+	}
+
+	/// Sets the clock skew tolerance.
+	pub fn with_leeway(self, leeway: LeewayConfig) -> Self {
+		Self { leeway, ..self }
+	}
+
+	/// Sets the clock used to check the request object expiration.
+	pub fn with_clock<L>(self, clock: L) -> RequestObjectDecoder<V, D, L> {
+		RequestObjectDecoder {
+			verifier: self.verifier,
+			decrypter: self.decrypter,
+			issuer: self.issuer,
+			require_encryption: self.require_encryption,
+			clock,
+			leeway: self.leeway,
+		}
+	}
+
+	/// Rejects request objects that are not encrypted.
+	pub fn with_required_encryption(self, require_encryption: bool) -> Self {
+		Self {
+			require_encryption,
+			..self
+		}
+	}
+}
+
+impl<V, D, K> RequestObjectDecoder<V, D, K>
+where
+	V: JwsSignatureVerifier,
+	D: RequestObjectDecrypter,
+	K: Clock,
+{
+	/// Decodes the request object of the given authorization request into
+	/// its parameters.
+	pub async fn decode<T>(
+		&self,
+		request: &SecuredAuthorizationRequest,
+	) -> Result<T, RequestObjectError>
+	where
+		T: DeserializeOwned,
+	{
+		let jws = match request.request.split('.').count() {
+			3 if !self.require_encryption => request.request.clone(),
+			3 => return Err(RequestObjectError::EncryptionRequired),
+			5 => {
+				let plaintext = self
+					.decrypter
+					.decrypt(&request.request)
+					.await
+					.ok_or(RequestObjectError::Decryption)?;
+				String::from_utf8(plaintext).map_err(|_| RequestObjectError::Malformed)?
+			}
+			_ => return Err(RequestObjectError::Malformed),
+		};
+
+		let claims = self.verify(&jws).await?;
+
+		if claims.get("client_id").and_then(serde_json::Value::as_str)
+			!= Some(request.client_id.as_str())
+		{
+			return Err(RequestObjectError::ClientIdMismatch);
+		}
+
+		self.validate(&claims, &request.client_id)?;
+
+		serde_json::from_value(claims).map_err(|e| RequestObjectError::Claims(e.to_string()))
+	}
+
+	/// Checks the `iss`, `aud` and `exp` claims of a request object sent by
+	/// the given client.
+	fn validate(
+		&self,
+		claims: &serde_json::Value,
+		client_id: &ClientId,
+	) -> Result<(), RequestObjectError> {
+		if claims.get("iss").and_then(serde_json::Value::as_str) != Some(client_id.as_str()) {
+			return Err(RequestObjectError::Issuer);
+		}
+
+		let audience_is_issuer =
+			|aud: &serde_json::Value| aud.as_str() == Some(self.issuer.as_str());
+		let audience_ok = match claims.get("aud") {
+			Some(serde_json::Value::Array(aud)) => aud.iter().any(audience_is_issuer),
+			Some(aud) => audience_is_issuer(aud),
+			None => false,
+		};
+		if !audience_ok {
+			return Err(RequestObjectError::Audience);
+		}
+
+		if let Some(exp) = claims.get("exp") {
+			let exp = exp
+				.as_u64()
+				.and_then(unix_time)
+				.ok_or_else(|| RequestObjectError::Claims("invalid `exp` claim".to_owned()))?;
+			if self.leeway.is_expired(&self.clock, exp) {
+				return Err(RequestObjectError::Expired);
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Verifies the given signed request object, returning its claims.
+	async fn verify(&self, jws: &str) -> Result<serde_json::Value, RequestObjectError> {
+		let jws = decode_compact_jws(jws).ok_or(RequestObjectError::Malformed)?;
+		if jws.header.alg == "none" {
+			return Err(RequestObjectError::Unsecured);
+		}
+
+		if let Some(typ) = jws.header.typ.as_deref()
+			&& !is_request_object_type(typ)
+		{
+			return Err(RequestObjectError::Type(typ.to_owned()));
+		}
+
+		if !jws.verify_signature(&self.verifier).await {
+			return Err(RequestObjectError::Signature);
+		}
+
+		jws.claims().ok_or(RequestObjectError::Malformed)
+	}
+}
+
+/// Checks the `typ` header of a request object.
+///
+/// See: <https://www.rfc-editor.org/rfc/rfc9101.html#section-10.8>
+fn is_request_object_type(typ: &str) -> bool {
+	let typ = typ.strip_prefix("application/").unwrap_or(typ);
+	typ.eq_ignore_ascii_case(REQUEST_OBJECT_TYPE)
+}
+
+#[cfg(test)]
+mod tests {
+	use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+	use iref::uri;
+
+	use crate::{
+		client_id,
+		endpoints::{authorization::AuthorizationEndpoint, token::TokenResponse},
+		grant::authorization_code::AuthorizationCodeAuthorizationRequest,
+		issuer, scope,
+		time::MockClock,
+		util::{
+			decode_json,
+			tests::{TestVerifier, jws},
+		},
+	};
+
+	use super::*;
+
+	struct TestClient;
+
+	impl OAuth2Client for TestClient {
+		type TokenResponse = TokenResponse;
+
+		fn client_id(&self) -> &ClientId {
+			client_id!("s6BhdRkqt3")
+		}
+	}
+
+	fn encode_json(value: serde_json::Value) -> String {
+		BASE64_URL_SAFE_NO_PAD.encode(serde_json::to_vec(&value).unwrap())
+	}
+
+	/// Produces `HS256` signatures equal to `signed`, accepted by
+	/// [`TestVerifier`].
+	struct TestSigner;
+
+	impl RequestObjectSigner for TestSigner {
+		async fn sign_request_object(&self, claims: &[u8]) -> Result<String, OAuth2ClientError> {
+			Ok(jws(
+				serde_json::json!({ "alg": "HS256", "typ": REQUEST_OBJECT_TYPE }),
+				claims,
+				b"signed",
+			))
+		}
+	}
+
+	/// Stores the plaintext in the clear in the ciphertext part of the JWE.
+	struct TestEncrypter;
+
+	impl RequestObjectEncrypter for TestEncrypter {
+		fn supports(&self, alg: &str, enc: &str) -> bool {
+			alg == "RSA-OAEP-256" && enc == "A256GCM"
+		}
+
+		async fn encrypt_request_object(
+			&self,
+			encryption: &RequestObjectEncryption,
+			jws: &str,
+		) -> Result<String, OAuth2ClientError> {
+			Ok(format!(
+				"{}.a2V5.aXY.{}.dGFn",
+				encode_json(serde_json::json!({
+					"alg": encryption.alg,
+					"enc": encryption.enc,
+					"cty": "JWT"
+				})),
+				BASE64_URL_SAFE_NO_PAD.encode(jws)
+			))
+		}
+	}
+
+	impl RequestObjectDecrypter for TestEncrypter {
+		async fn decrypt(&self, jwe: &str) -> Option<Vec<u8>> {
+			let mut parts = jwe.split('.');
+			let header: serde_json::Value = decode_json(parts.next()?)?;
+			if header["alg"] != "RSA-OAEP-256" {
+				return None;
+			}
+
+			BASE64_URL_SAFE_NO_PAD.decode(parts.nth(2)?).ok()
+		}
+	}
+
+	fn issuer() -> IssuerBuf {
+		issuer!("https://server.example.com").to_owned()
+	}
+
+	fn metadata() -> AuthorizationServerMetadata {
+		serde_json::from_value(serde_json::json!({
+			"issuer": "https://server.example.com",
+			"authorization_endpoint": "https://server.example.com/authorize",
+			"request_object_encryption_alg_values_supported": ["ECDH-ES", "RSA-OAEP-256"],
+			"request_object_encryption_enc_values_supported": ["A128CBC-HS256", "A256GCM"]
+		}))
+		.unwrap()
+	}
+
+	fn request(
+		endpoint: AuthorizationEndpoint<'_, TestClient>,
+	) -> RequestBuilder<AuthorizationEndpoint<'_, TestClient>, AuthorizationCodeAuthorizationRequest>
+	{
+		endpoint.authorize_url(
+			Some(uri!("https://client.example.org/cb").to_owned()),
+			Some(scope!("openid").to_owned()),
+		)
+	}
+
+	#[test]
+	fn negotiate() {
+		let encryption = RequestObjectEncryption::negotiate(&metadata(), &TestEncrypter).unwrap();
+		assert_eq!(
+			encryption,
+			RequestObjectEncryption::new("RSA-OAEP-256".to_owned(), "A256GCM".to_owned())
+		);
+
+		let metadata = AuthorizationServerMetadata::<crate::util::NoExtension>::new(
+			issuer!("https://server.example.com").to_owned(),
+		);
+		assert_eq!(
+			RequestObjectEncryption::negotiate(&metadata, &TestEncrypter),
+			Err(UnsupportedCapability::RequestObjectEncryption)
+		);
+	}
+
+	#[tokio::test]
+	async fn signed_request_object() {
+		let metadata = metadata();
+		let endpoint = AuthorizationEndpoint::from_metadata(&TestClient, &metadata).unwrap();
+		let uri = request(endpoint)
+			.into_request_object(&metadata.issuer, &TestSigner)
+			.await
+			.unwrap()
+			.into_redirect_uri()
+			.unwrap();
+
+		let request: SecuredAuthorizationRequest =
+			serde_html_form::from_str(uri.query().unwrap().as_str()).unwrap();
+		assert_eq!(request.client_id.as_str(), "s6BhdRkqt3");
+
+		let decoder = RequestObjectDecoder::new(TestVerifier, issuer());
+		let params: AuthorizationCodeAuthorizationRequest = decoder.decode(&request).await.unwrap();
+		assert_eq!(params.client_id.as_str(), "s6BhdRkqt3");
+		assert_eq!(
+			params.redirect_uri.as_deref(),
+			Some(uri!("https://client.example.org/cb"))
+		);
+
+		let claims: serde_json::Value = decoder.decode(&request).await.unwrap();
+		assert_eq!(claims["iss"], "s6BhdRkqt3");
+		assert_eq!(claims["aud"], "https://server.example.com");
+
+		assert_eq!(
+			decoder
+				.with_required_encryption(true)
+				.decode::<serde_json::Value>(&request)
+				.await,
+			Err(RequestObjectError::EncryptionRequired)
+		);
+	}
+
+	#[tokio::test]
+	async fn encrypted_request_object() {
+		let metadata = metadata();
+		let encryption = RequestObjectEncryption::negotiate(&metadata, &TestEncrypter).unwrap();
+		let endpoint = AuthorizationEndpoint::from_metadata(&TestClient, &metadata).unwrap();
+		let request = request(endpoint)
+			.into_encrypted_request_object(
+				&metadata.issuer,
+				&TestSigner,
+				&TestEncrypter,
+				&encryption,
+			)
+			.await
+			.unwrap()
+			.request;
+		assert_eq!(request.request.split('.').count(), 5);
+
+		assert_eq!(
+			RequestObjectDecoder::new(TestVerifier, issuer())
+				.decode::<serde_json::Value>(&request)
+				.await,
+			Err(RequestObjectError::Decryption)
+		);
+
+		let params: AuthorizationCodeAuthorizationRequest =
+			RequestObjectDecoder::new(TestVerifier, issuer())
+				.with_decrypter(TestEncrypter)
+				.with_required_encryption(true)
+				.decode(&request)
+				.await
+				.unwrap();
+		assert_eq!(params.client_id.as_str(), "s6BhdRkqt3");
+	}
+
+	#[tokio::test]
+	async fn rejected_request_object() {
+		let decoder = RequestObjectDecoder::new(TestVerifier, issuer());
+		let jws = TestSigner
+			.sign_request_object(br#"{"client_id":"s6BhdRkqt3"}"#)
+			.await
+			.unwrap();
+
+		let request = SecuredAuthorizationRequest {
+			client_id: client_id!("other").to_owned(),
+			request: jws.clone(),
+		};
+		assert_eq!(
+			decoder.decode::<serde_json::Value>(&request).await,
+			Err(RequestObjectError::ClientIdMismatch)
+		);
+
+		let (_, rest) = jws.split_once('.').unwrap();
+		let request = SecuredAuthorizationRequest {
+			client_id: client_id!("s6BhdRkqt3").to_owned(),
+			request: format!(
+				"{}.{rest}",
+				encode_json(serde_json::json!({ "alg": "none" }))
+			),
+		};
+		assert_eq!(
+			decoder.decode::<serde_json::Value>(&request).await,
+			Err(RequestObjectError::Unsecured)
+		);
+
+		let request = SecuredAuthorizationRequest {
+			client_id: client_id!("s6BhdRkqt3").to_owned(),
+			request: format!(
+				"{}.{rest}",
+				encode_json(serde_json::json!({ "alg": "HS256", "typ": "JWT" }))
+			),
+		};
+		assert_eq!(
+			decoder.decode::<serde_json::Value>(&request).await,
+			Err(RequestObjectError::Type("JWT".to_owned()))
+		);
+
+		let request = SecuredAuthorizationRequest {
+			client_id: client_id!("s6BhdRkqt3").to_owned(),
+			request: "not a JWT".to_owned(),
+		};
+		assert_eq!(
+			decoder.decode::<serde_json::Value>(&request).await,
+			Err(RequestObjectError::Malformed)
+		);
+	}
+
+	#[tokio::test]
+	async fn request_object_claims() {
+		let decoder = RequestObjectDecoder::new(TestVerifier, issuer())
+			.with_clock(MockClock::from_unix_timestamp(1000));
+		let decode = async |claims: serde_json::Value| {
+			let request = SecuredAuthorizationRequest {
+				client_id: client_id!("s6BhdRkqt3").to_owned(),
+				request: TestSigner
+					.sign_request_object(claims.to_string().as_bytes())
+					.await
+					.unwrap(),
+			};
+			decoder.decode::<serde_json::Value>(&request).await
+		};
+
+		assert!(
+			decode(serde_json::json!({
+				"client_id": "s6BhdRkqt3",
+				"iss": "s6BhdRkqt3",
+				"aud": ["https://server.example.com", "https://other.example.com"],
+				"exp": 1060
+			}))
+			.await
+			.is_ok()
+		);
+		assert_eq!(
+			decode(serde_json::json!({
+				"client_id": "s6BhdRkqt3",
+				"aud": "https://server.example.com"
+			}))
+			.await,
+			Err(RequestObjectError::Issuer)
+		);
+		assert_eq!(
+			decode(serde_json::json!({
+				"client_id": "s6BhdRkqt3",
+				"iss": "other",
+				"aud": "https://server.example.com"
+			}))
+			.await,
+			Err(RequestObjectError::Issuer)
+		);
+		assert_eq!(
+			decode(serde_json::json!({
+				"client_id": "s6BhdRkqt3",
+				"iss": "s6BhdRkqt3",
+				"aud": "https://other.example.com"
+			}))
+			.await,
+			Err(RequestObjectError::Audience)
+		);
+		assert_eq!(
+			decode(serde_json::json!({
+				"client_id": "s6BhdRkqt3",
+				"iss": "s6BhdRkqt3",
+				"aud": "https://server.example.com",
+				"exp": 900
+			}))
+			.await,
+			Err(RequestObjectError::Expired)
+		);
+		assert!(matches!(
+			decode(serde_json::json!({
+				"client_id": "s6BhdRkqt3",
+				"iss": "s6BhdRkqt3",
+				"aud": "https://server.example.com",
+				"exp": u64::MAX
+			}))
+			.await,
+			Err(RequestObjectError::Claims(_))
+		));
+	}
+}
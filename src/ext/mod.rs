@@ -1,8 +1,40 @@
 //! OAuth 2.0 protocol extensions.
 //!
+//! - [`backchannel_logout`] — OpenID Connect Back-Channel Logout tokens
+//!   ([OpenID Connect Back-Channel Logout 1.0](https://openid.net/specs/openid-connect-backchannel-1_0.html)).
+//! - [`client_attestation`] — Attestation-Based Client Authentication
+//!   ([draft-ietf-oauth-attestation-based-client-auth](https://datatracker.ietf.org/doc/html/draft-ietf-oauth-attestation-based-client-auth)).
+//! - [`dpop`] — Demonstrating Proof of Possession nonces
+//!   ([RFC 9449](https://www.rfc-editor.org/rfc/rfc9449.html)).
+//! - [`http_sig`] — HTTP Message Signatures
+//!   ([RFC 9421](https://www.rfc-editor.org/rfc/rfc9421.html)).
+//! - [`jar`] — JWT-Secured Authorization Requests, optionally encrypted
+//!   ([RFC 9101](https://www.rfc-editor.org/rfc/rfc9101.html)).
+//! - [`oidc`] — OpenID Connect ID token hashes and authentication request
+//!   parameters
+//!   ([OpenID Connect Core 1.0](https://openid.net/specs/openid-connect-core-1_0.html)).
+//! - [`openid_credential`] — OpenID4VCI `openid_credential` authorization
+//!   details, for mDL and eIDAS PID issuance
+//!   ([OpenID4VCI](https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0.html)).
 //! - [`pkce`] — Proof Key for Code Exchange
 //!   ([RFC 7636](https://datatracker.ietf.org/doc/html/rfc7636)).
 //! - [`rar`] — Rich Authorization Requests
 //!   ([RFC 9396](https://www.rfc-editor.org/rfc/rfc9396.html)).
+#[cfg(feature = "oidc")]
+pub mod backchannel_logout;
+#[cfg(feature = "client-attestation")]
+pub mod client_attestation;
+#[cfg(feature = "dpop")]
+pub mod dpop;
+#[cfg(feature = "http-sig")]
+pub mod http_sig;
+#[cfg(feature = "jar")]
+pub mod jar;
+#[cfg(feature = "oidc")]
+pub mod oidc;
+#[cfg(feature = "openid-credential")]
+pub mod openid_credential;
+#[cfg(feature = "pkce")]
 pub mod pkce;
+#[cfg(feature = "rar")]
 pub mod rar;
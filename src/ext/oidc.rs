@@ -0,0 +1,848 @@
+//! OpenID Connect ID token helpers.
+//!
+//! See: <https://openid.net/specs/openid-connect-core-1_0.html>
+//!
+//! ID tokens returned alongside an access token or authorization code (in
+//! the implicit and hybrid flows, or in JARM responses) bind them with the
+//! `at_hash` and `c_hash` claims. This module computes and checks those
+//! values. Verifying the ID token signature is left to the caller.
+//!
+//! It also provides request wrappers for the OpenID Connect authentication
+//! request parameters `max_age`, `acr_values` and `claims`, and the
+//! [`SessionExtension`] carrying the session of the end-user at the OpenID
+//! provider.
+use std::{
+	collections::BTreeMap,
+	ops::{Deref, DerefMut},
+};
+
+use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+use serde::{Deserialize, Serialize};
+use serde_with::{
+	DisplayFromStr, OneOrMany, PickFirst, StringWithSeparator,
+	formats::{PreferOne, SpaceSeparator},
+	serde_as, skip_serializing_none,
+};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+
+use crate::{
+	AccessToken, Code, IssuerBuf,
+	client::OAuth2ClientError,
+	endpoints::{HttpRequest, RedirectRequest, RequestBuilder},
+	transport::HttpClient,
+};
+
+/// Error returned when the hash algorithm of a JWS algorithm is unknown.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("unsupported JWS algorithm `{0}`")]
+pub struct UnsupportedAlgorithm(pub String);
+
+/// Computes the left-most half of the hash of `value`, base64url-encoded.
+///
+/// See: <https://openid.net/specs/openid-connect-core-1_0.html#CodeIDToken>
+///
+/// The hash function is the one used by the JWS algorithm `alg` of the ID
+/// token header: SHA-256 for `*256` algorithms (e.g. `RS256`), SHA-384 for
+/// `*384`, and SHA-512 for `*512` and `EdDSA`.
+pub fn token_hash(alg: &str, value: &str) -> Result<String, UnsupportedAlgorithm> {
+	let digest = match alg {
+		"EdDSA" | "Ed25519" => Sha512::digest(value).to_vec(),
+		"none" => return Err(UnsupportedAlgorithm(alg.to_owned())),
+		_ if alg.ends_with("256") || alg == "ES256K" => Sha256::digest(value).to_vec(),
+		_ if alg.ends_with("384") => Sha384::digest(value).to_vec(),
+		_ if alg.ends_with("512") => Sha512::digest(value).to_vec(),
+		_ => return Err(UnsupportedAlgorithm(alg.to_owned())),
+	};
+
+	Ok(BASE64_URL_SAFE_NO_PAD.encode(&digest[..digest.len() / 2]))
+}
+
+/// Computes the `at_hash` value of the given access token.
+pub fn at_hash(alg: &str, access_token: &AccessToken) -> Result<String, UnsupportedAlgorithm> {
+	token_hash(alg, access_token.as_str())
+}
+
+/// Computes the `c_hash` value of the given authorization code.
+pub fn c_hash(alg: &str, code: &Code) -> Result<String, UnsupportedAlgorithm> {
+	token_hash(alg, code.as_str())
+}
+
+/// ID token validation error.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum IdTokenError {
+	/// The hash function of the ID token algorithm is unknown.
+	#[error(transparent)]
+	UnsupportedAlgorithm(#[from] UnsupportedAlgorithm),
+
+	/// A required hash claim is absent.
+	#[error("missing `{0}` claim")]
+	MissingClaim(&'static str),
+
+	/// A hash claim does not match the access token or code.
+	#[error("`{0}` claim mismatch")]
+	Mismatch(&'static str),
+}
+
+/// ID token claims.
+///
+/// See: <https://openid.net/specs/openid-connect-core-1_0.html#IDToken>
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdTokenClaims<E = BTreeMap<String, serde_json::Value>> {
+	/// Issuer identifier.
+	pub iss: IssuerBuf,
+
+	/// Subject identifier.
+	pub sub: String,
+
+	/// Audiences of the ID token.
+	#[serde_as(as = "OneOrMany<_, PreferOne>")]
+	pub aud: Vec<String>,
+
+	/// Expiration time, in seconds since the Unix epoch.
+	pub exp: u64,
+
+	/// Issuance time, in seconds since the Unix epoch.
+	pub iat: u64,
+
+	/// Time of the end-user authentication, in seconds since the Unix epoch.
+	pub auth_time: Option<u64>,
+
+	/// Value passed in the authentication request to mitigate replay
+	/// attacks.
+	pub nonce: Option<String>,
+
+	/// Authentication context class reference.
+	pub acr: Option<String>,
+
+	/// Authentication methods references.
+	pub amr: Option<Vec<String>>,
+
+	/// Authorized party, the client to which the ID token was issued.
+	pub azp: Option<String>,
+
+	/// Access token hash.
+	pub at_hash: Option<String>,
+
+	/// Authorization code hash.
+	pub c_hash: Option<String>,
+
+	/// Identifier of the session of the end-user at the OpenID provider,
+	/// also carried by the logout tokens ending it.
+	///
+	/// See: <https://openid.net/specs/openid-connect-frontchannel-1_0.html#ClaimsContents>
+	pub sid: Option<String>,
+
+	/// Other claims.
+	#[serde(flatten)]
+	pub ext: E,
+}
+
+impl<E> IdTokenClaims<E> {
+	/// Checks the `at_hash` claim against the access token issued with this
+	/// ID token, signed with the JWS algorithm `alg`.
+	///
+	/// If `required` is `false`, an absent `at_hash` claim is accepted.
+	pub fn verify_at_hash(
+		&self,
+		alg: &str,
+		access_token: &AccessToken,
+		required: bool,
+	) -> Result<(), IdTokenError> {
+		verify_hash(
+			"at_hash",
+			self.at_hash.as_deref(),
+			|| at_hash(alg, access_token),
+			required,
+		)
+	}
+
+	/// Checks the `c_hash` claim against the authorization code issued with
+	/// this ID token, signed with the JWS algorithm `alg`.
+	///
+	/// If `required` is `false`, an absent `c_hash` claim is accepted.
+	pub fn verify_c_hash(
+		&self,
+		alg: &str,
+		code: &Code,
+		required: bool,
+	) -> Result<(), IdTokenError> {
+		verify_hash(
+			"c_hash",
+			self.c_hash.as_deref(),
+			|| c_hash(alg, code),
+			required,
+		)
+	}
+
+	/// Checks the token and code binding of an ID token returned from the
+	/// authorization endpoint.
+	///
+	/// The `c_hash` claim is required when an authorization code is issued
+	/// (hybrid flow), and the `at_hash` claim when an access token is
+	/// issued (implicit and hybrid flows).
+	pub fn verify_token_hashes(
+		&self,
+		alg: &str,
+		access_token: Option<&AccessToken>,
+		code: Option<&Code>,
+	) -> Result<(), IdTokenError> {
+		if let Some(code) = code {
+			self.verify_c_hash(alg, code, true)?;
+		}
+
+		if let Some(access_token) = access_token {
+			self.verify_at_hash(alg, access_token, true)?;
+		}
+
+		Ok(())
+	}
+}
+
+fn verify_hash(
+	claim: &'static str,
+	value: Option<&str>,
+	expected: impl FnOnce() -> Result<String, UnsupportedAlgorithm>,
+	required: bool,
+) -> Result<(), IdTokenError> {
+	match value {
+		Some(value) if value == expected()? => Ok(()),
+		Some(_) => Err(IdTokenError::Mismatch(claim)),
+		None if required => Err(IdTokenError::MissingClaim(claim)),
+		None => Ok(()),
+	}
+}
+
+/// OpenID Connect session fields of authentication and token responses.
+///
+/// Flattened into the authorization callback parameters, or used as the
+/// extension of a [`TokenResponse`](crate::endpoints::token::TokenResponse),
+/// it carries the ID token, whose `sid` claim identifies the session of the
+/// end-user at the OpenID provider, and the `session_state` used by relying
+/// parties monitoring that session with the `check_session_iframe`.
+///
+/// See: <https://openid.net/specs/openid-connect-session-1_0.html#CreatingUpdatingSessions>
+#[skip_serializing_none]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SessionExtension {
+	/// ID token.
+	pub id_token: Option<String>,
+
+	/// State of the end-user session at the OpenID provider, returned in the
+	/// authentication response.
+	pub session_state: Option<String>,
+}
+
+/// Implements [`Deref`], [`DerefMut`], [`RedirectRequest`] and
+/// [`HttpRequest`] for a request wrapper with a single parameter field,
+/// serialized as the given request body type.
+///
+/// The wrapper must provide a `body` method borrowing its parameter.
+macro_rules! request_wrapper {
+	($ty:ident, $body:ident) => {
+		impl<T> Deref for $ty<T> {
+			type Target = T;
+
+			fn deref(&self) -> &Self::Target {
+				&self.value
+			}
+		}
+
+		impl<T> DerefMut for $ty<T> {
+			fn deref_mut(&mut self) -> &mut Self::Target {
+				&mut self.value
+			}
+		}
+
+		impl<T> RedirectRequest for $ty<T>
+		where
+			T: RedirectRequest,
+		{
+			type RequestBody<'b>
+				= $body<'b, T::RequestBody<'b>>
+			where
+				Self: 'b;
+
+			fn build_query(&self) -> Self::RequestBody<'_> {
+				self.body(self.value.build_query())
+			}
+		}
+
+		impl<E, T> HttpRequest<E> for $ty<T>
+		where
+			T: HttpRequest<E>,
+		{
+			type ContentType = T::ContentType;
+			type RequestBody<'b>
+				= $body<'b, T::RequestBody<'b>>
+			where
+				Self: 'b;
+			type Response = T::Response;
+			type ResponsePayload = T::ResponsePayload;
+
+			async fn build_request(
+				&self,
+				endpoint: &E,
+				http_client: &impl HttpClient,
+			) -> Result<http::Request<Self::RequestBody<'_>>, OAuth2ClientError> {
+				self.value
+					.build_request(endpoint, http_client)
+					.await
+					.map(|request| request.map(|value| self.body(value)))
+			}
+
+			fn decode_response(
+				&self,
+				endpoint: &E,
+				response: http::Response<Vec<u8>>,
+			) -> Result<http::Response<Self::ResponsePayload>, OAuth2ClientError> {
+				self.value.decode_response(endpoint, response)
+			}
+
+			async fn process_response(
+				&self,
+				endpoint: &E,
+				http_client: &impl HttpClient,
+				response: http::Response<Self::ResponsePayload>,
+			) -> Result<Self::Response, OAuth2ClientError> {
+				self.value
+					.process_response(endpoint, http_client, response)
+					.await
+			}
+		}
+	};
+}
+
+/// Wrapper that attaches the `max_age` parameter to an authentication
+/// request.
+///
+/// See: <https://openid.net/specs/openid-connect-core-1_0.html#AuthRequest>
+///
+/// Maximum authentication age, in seconds. If the end-user authenticated
+/// longer ago, the authorization server must actively re-authenticate them,
+/// and the ID token must include the `auth_time` claim.
+#[serde_as]
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct WithMaxAge<T> {
+	/// Maximum authentication age, in seconds.
+	#[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+	pub max_age: u64,
+
+	/// The inner request being extended.
+	#[serde(flatten)]
+	pub value: T,
+}
+
+impl<T> WithMaxAge<T> {
+	/// Creates a new [`WithMaxAge`] wrapping the given request.
+	pub fn new(value: T, max_age: u64) -> Self {
+		Self { max_age, value }
+	}
+
+	/// Wraps the given request body of the inner request, borrowing the
+	/// maximum authentication age.
+	fn body<B>(&self, value: B) -> WithMaxAgeBody<'_, B> {
+		WithMaxAgeBody {
+			max_age: &self.max_age,
+			value,
+		}
+	}
+}
+
+/// Request body of a [`WithMaxAge`] request, borrowing its maximum
+/// authentication age.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct WithMaxAgeBody<'b, T> {
+	/// Maximum authentication age, in seconds.
+	pub max_age: &'b u64,
+
+	/// The inner request body.
+	#[serde(flatten)]
+	pub value: T,
+}
+
+request_wrapper!(WithMaxAge, WithMaxAgeBody);
+
+/// Wrapper that attaches the `acr_values` parameter to an authentication
+/// request.
+///
+/// See: <https://openid.net/specs/openid-connect-core-1_0.html#AuthRequest>
+///
+/// Requested Authentication Context Class Reference values, in order of
+/// preference, serialized as a space-separated string. The value satisfied
+/// by the authentication is returned in the `acr` claim of the ID token.
+#[serde_as]
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct WithAcrValues<T> {
+	/// Requested ACR values, in order of preference.
+	#[serde_as(as = "StringWithSeparator::<SpaceSeparator, String>")]
+	pub acr_values: Vec<String>,
+
+	/// The inner request being extended.
+	#[serde(flatten)]
+	pub value: T,
+}
+
+impl<T> WithAcrValues<T> {
+	/// Creates a new [`WithAcrValues`] wrapping the given request.
+	pub fn new(value: T, acr_values: Vec<String>) -> Self {
+		Self { acr_values, value }
+	}
+
+	/// Wraps the given request body of the inner request, borrowing the ACR
+	/// values.
+	fn body<B>(&self, value: B) -> WithAcrValuesBody<'_, B> {
+		WithAcrValuesBody {
+			acr_values: &self.acr_values,
+			value,
+		}
+	}
+}
+
+/// Request body of a [`WithAcrValues`] request, borrowing its ACR values.
+#[serde_as]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct WithAcrValuesBody<'b, T> {
+	/// Requested ACR values, in order of preference.
+	#[serde_as(as = "&StringWithSeparator::<SpaceSeparator, String>")]
+	pub acr_values: &'b Vec<String>,
+
+	/// The inner request body.
+	#[serde(flatten)]
+	pub value: T,
+}
+
+request_wrapper!(WithAcrValues, WithAcrValuesBody);
+
+/// Request for an individual claim.
+///
+/// See: <https://openid.net/specs/openid-connect-core-1_0.html#IndividualClaimsRequests>
+#[skip_serializing_none]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ClaimRequest {
+	/// Whether the claim is essential to the authorization requested by the
+	/// end-user.
+	pub essential: Option<bool>,
+
+	/// Specific value requested for the claim.
+	pub value: Option<serde_json::Value>,
+
+	/// Set of values requested for the claim, in order of preference.
+	pub values: Option<Vec<serde_json::Value>>,
+}
+
+impl ClaimRequest {
+	/// Requests the claim as essential.
+	pub fn essential() -> Self {
+		Self {
+			essential: Some(true),
+			..Default::default()
+		}
+	}
+
+	/// Requests the claim as essential, with the given value.
+	pub fn essential_value(value: impl Into<serde_json::Value>) -> Self {
+		Self {
+			value: Some(value.into()),
+			..Self::essential()
+		}
+	}
+
+	/// Requests the claim as essential, with one of the given values.
+	pub fn essential_values(values: impl IntoIterator<Item = serde_json::Value>) -> Self {
+		Self {
+			values: Some(values.into_iter().collect()),
+			..Self::essential()
+		}
+	}
+}
+
+/// Claims requested for the UserInfo response or the ID token.
+///
+/// Each claim is mapped to its [`ClaimRequest`], or `None` to request it in
+/// the default manner (serialized as `null`).
+pub type RequestedClaims = BTreeMap<String, Option<ClaimRequest>>;
+
+/// Value of the `claims` request parameter.
+///
+/// See: <https://openid.net/specs/openid-connect-core-1_0.html#ClaimsParameter>
+///
+/// The requested claims are serialized as a JSON object, itself carried as a
+/// string in the query or form body.
+#[skip_serializing_none]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ClaimsRequest {
+	/// Claims to be returned from the UserInfo endpoint.
+	pub userinfo: Option<RequestedClaims>,
+
+	/// Claims to be returned in the ID token.
+	pub id_token: Option<RequestedClaims>,
+}
+
+impl ClaimsRequest {
+	/// Requests the given claim from the UserInfo endpoint.
+	pub fn userinfo(mut self, name: impl Into<String>, request: Option<ClaimRequest>) -> Self {
+		self.userinfo
+			.get_or_insert_default()
+			.insert(name.into(), request);
+		self
+	}
+
+	/// Requests the given claim in the ID token.
+	pub fn id_token(mut self, name: impl Into<String>, request: Option<ClaimRequest>) -> Self {
+		self.id_token
+			.get_or_insert_default()
+			.insert(name.into(), request);
+		self
+	}
+}
+
+/// Wrapper that attaches the `claims` parameter to an authentication
+/// request.
+///
+/// See: <https://openid.net/specs/openid-connect-core-1_0.html#ClaimsParameter>
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct WithClaims<T> {
+	/// Requested claims.
+	#[serde(with = "claims_as_json")]
+	#[cfg_attr(feature = "schemars", schemars(with = "String"))]
+	pub claims: ClaimsRequest,
+
+	/// The inner request being extended.
+	#[serde(flatten)]
+	pub value: T,
+}
+
+impl<T> WithClaims<T> {
+	/// Creates a new [`WithClaims`] wrapping the given request.
+	pub fn new(value: T, claims: ClaimsRequest) -> Self {
+		Self { claims, value }
+	}
+
+	/// Wraps the given request body of the inner request, borrowing the
+	/// requested claims.
+	fn body<B>(&self, value: B) -> WithClaimsBody<'_, B> {
+		WithClaimsBody {
+			claims: &self.claims,
+			value,
+		}
+	}
+}
+
+/// Request body of a [`WithClaims`] request, borrowing its requested
+/// claims.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct WithClaimsBody<'b, T> {
+	/// Requested claims.
+	#[serde(serialize_with = "claims_as_json::serialize")]
+	pub claims: &'b ClaimsRequest,
+
+	/// The inner request body.
+	#[serde(flatten)]
+	pub value: T,
+}
+
+request_wrapper!(WithClaims, WithClaimsBody);
+
+mod claims_as_json {
+	use std::borrow::Borrow;
+
+	use serde::Deserialize;
+
+	use super::ClaimsRequest;
+
+	pub fn serialize<S>(
+		value: &impl Borrow<ClaimsRequest>,
+		serializer: S,
+	) -> Result<S::Ok, S::Error>
+	where
+		S: serde::Serializer,
+	{
+		serializer.serialize_str(
+			&serde_json::to_string(value.borrow())
+				// UNWRAP SAFETY: `ClaimsRequest` can always be serialized as
+				//                JSON.
+				.unwrap(),
+		)
+	}
+
+	pub fn deserialize<'de, D>(deserializer: D) -> Result<ClaimsRequest, D::Error>
+	where
+		D: serde::Deserializer<'de>,
+	{
+		let string = String::deserialize(deserializer)?;
+		serde_json::from_str(&string).map_err(serde::de::Error::custom)
+	}
+}
+
+/// Extension trait for attaching OpenID Connect authentication request
+/// parameters to a [`RequestBuilder`].
+pub trait AddOidcParameters: Sized {
+	/// The resulting type after adding `max_age`.
+	type WithMaxAge;
+
+	/// The resulting type after adding `acr_values`.
+	type WithAcrValues;
+
+	/// The resulting type after adding `claims`.
+	type WithClaims;
+
+	/// Wraps the current request in a [`WithMaxAge`] with the given maximum
+	/// authentication age, in seconds.
+	fn with_max_age(self, max_age: u64) -> Self::WithMaxAge;
+
+	/// Wraps the current request in a [`WithAcrValues`] with the given ACR
+	/// values, in order of preference.
+	fn with_acr_values(
+		self,
+		acr_values: impl IntoIterator<Item = impl Into<String>>,
+	) -> Self::WithAcrValues;
+
+	/// Wraps the current request in a [`WithClaims`] with the given claims
+	/// request.
+	fn with_claims(self, claims: ClaimsRequest) -> Self::WithClaims;
+}
+
+impl<E, T> AddOidcParameters for RequestBuilder<E, T> {
+	type WithMaxAge = RequestBuilder<E, WithMaxAge<T>>;
+	type WithAcrValues = RequestBuilder<E, WithAcrValues<T>>;
+	type WithClaims = RequestBuilder<E, WithClaims<T>>;
+
+	fn with_max_age(self, max_age: u64) -> Self::WithMaxAge {
+		self.map(|value| WithMaxAge::new(value, max_age))
+	}
+
+	fn with_acr_values(
+		self,
+		acr_values: impl IntoIterator<Item = impl Into<String>>,
+	) -> Self::WithAcrValues {
+		self.map(|value| {
+			WithAcrValues::new(value, acr_values.into_iter().map(Into::into).collect())
+		})
+	}
+
+	fn with_claims(self, claims: ClaimsRequest) -> Self::WithClaims {
+		self.map(|value| WithClaims::new(value, claims))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const ACCESS_TOKEN: &str = "jHkWEdUXMU1BwAsC4vtUsZwnNKTSHHbn7UiO4QTeHP8";
+	// From OpenID Connect Core 1.0, Appendix A.4.
+	const CODE: &str = "Qcb0Orv1zh30vL1MPRsbm-diHiMwcLyZvn1arpZv-Jxf_11jnpEX3Tgfvk";
+
+	fn claims(at_hash: Option<&str>, c_hash: Option<&str>) -> IdTokenClaims {
+		serde_json::from_value(serde_json::json!({
+			"iss": "https://server.example.com",
+			"sub": "248289761001",
+			"aud": "s6BhdRkqt3",
+			"exp": 1311281970,
+			"iat": 1311280970,
+			"at_hash": at_hash,
+			"c_hash": c_hash
+		}))
+		.unwrap()
+	}
+
+	#[test]
+	fn token_hashes() {
+		let access_token = AccessToken::new(ACCESS_TOKEN).unwrap();
+		let code = Code::new(CODE).unwrap();
+
+		assert_eq!(
+			at_hash("RS256", access_token).unwrap(),
+			"lM6bHjWjYGv_6jrjY4e-pw"
+		);
+		assert_eq!(c_hash("RS256", code).unwrap(), "LDktKdoQak3Pk0cnXxCltA");
+		assert_eq!(at_hash("ES384", access_token).unwrap().len(), 32);
+		assert!(at_hash("none", access_token).is_err());
+	}
+
+	#[test]
+	fn verify_token_hashes() {
+		let access_token = AccessToken::new(ACCESS_TOKEN).unwrap();
+		let code = Code::new(CODE).unwrap();
+
+		let claims = claims(
+			Some("lM6bHjWjYGv_6jrjY4e-pw"),
+			Some("LDktKdoQak3Pk0cnXxCltA"),
+		);
+		assert_eq!(
+			claims.verify_token_hashes("RS256", Some(access_token), Some(code)),
+			Ok(())
+		);
+		assert_eq!(
+			claims.verify_token_hashes("RS512", Some(access_token), None),
+			Err(IdTokenError::Mismatch("at_hash"))
+		);
+	}
+
+	#[test]
+	fn missing_token_hashes() {
+		let code = Code::new(CODE).unwrap();
+		let claims = claims(None, None);
+
+		assert_eq!(claims.verify_c_hash("RS256", code, false), Ok(()));
+		assert_eq!(
+			claims.verify_token_hashes("RS256", None, Some(code)),
+			Err(IdTokenError::MissingClaim("c_hash"))
+		);
+	}
+
+	#[test]
+	fn session() {
+		let mut claims = claims(None, None);
+		assert_eq!(claims.sid, None);
+		claims = serde_json::from_value(serde_json::json!({
+			"iss": "https://server.example.com",
+			"sub": "248289761001",
+			"aud": "s6BhdRkqt3",
+			"exp": 1311281970,
+			"iat": 1311280970,
+			"sid": "08a5019c-17e1-4977-8f42-65a12843ea02"
+		}))
+		.unwrap();
+		assert_eq!(
+			claims.sid.as_deref(),
+			Some("08a5019c-17e1-4977-8f42-65a12843ea02")
+		);
+		assert!(claims.ext.is_empty());
+
+		#[derive(Deserialize)]
+		struct Callback {
+			code: crate::CodeBuf,
+
+			#[serde(flatten)]
+			session: SessionExtension,
+		}
+
+		let callback: Callback = crate::client::parse_callback(
+			iref::uri!("https://client.example.org/cb"),
+			iref::uri!(
+				"https://client.example.org/cb?code=SplxlOBeZQQYbYS6WxSbIA&session_state=b8f1a2.salt"
+			),
+			None,
+		)
+		.unwrap();
+		assert_eq!(callback.code.as_str(), "SplxlOBeZQQYbYS6WxSbIA");
+		assert_eq!(
+			callback.session.session_state.as_deref(),
+			Some("b8f1a2.salt")
+		);
+
+		let response: crate::endpoints::token::TokenResponse<String, SessionExtension> =
+			serde_json::from_value(serde_json::json!({
+				"access_token": "2YotnFZFEjr1zCsicMWpAA",
+				"token_type": "Bearer",
+				"id_token": "eyJhbGciOiJSUzI1NiJ9.e30.c2ln"
+			}))
+			.unwrap();
+		assert_eq!(
+			response.ext.id_token.as_deref(),
+			Some("eyJhbGciOiJSUzI1NiJ9.e30.c2ln")
+		);
+		assert_eq!(response.ext.session_state, None);
+	}
+
+	#[test]
+	fn authentication_parameters() {
+		let request = WithMaxAge::new(
+			WithAcrValues::new(
+				crate::util::NoExtension {},
+				vec![
+					"urn:eidas:loa:high".to_owned(),
+					"urn:eidas:loa:substantial".to_owned(),
+				],
+			),
+			300,
+		);
+
+		let query = serde_html_form::to_string(&request).unwrap();
+		assert_eq!(
+			query,
+			"max_age=300&acr_values=urn%3Aeidas%3Aloa%3Ahigh+urn%3Aeidas%3Aloa%3Asubstantial"
+		);
+
+		let decoded: WithMaxAge<WithAcrValues<crate::util::NoExtension>> =
+			serde_html_form::from_str(&query).unwrap();
+		assert_eq!(decoded, request);
+	}
+
+	#[test]
+	fn claims_parameter() {
+		let claims = ClaimsRequest::default()
+			.userinfo("given_name", Some(ClaimRequest::essential()))
+			.userinfo("email", None)
+			.id_token("auth_time", Some(ClaimRequest::essential()))
+			.id_token(
+				"acr",
+				Some(ClaimRequest::essential_values([
+					"urn:mace:incommon:iap:silver".into(),
+				])),
+			);
+
+		assert_eq!(
+			serde_json::to_value(&claims).unwrap(),
+			serde_json::json!({
+				"userinfo": {
+					"given_name": { "essential": true },
+					"email": null
+				},
+				"id_token": {
+					"auth_time": { "essential": true },
+					"acr": {
+						"essential": true,
+						"values": ["urn:mace:incommon:iap:silver"]
+					}
+				}
+			})
+		);
+
+		let request = WithClaims::new(crate::util::NoExtension {}, claims);
+		let query = serde_html_form::to_string(&request).unwrap();
+		let decoded: WithClaims<crate::util::NoExtension> =
+			serde_html_form::from_str(&query).unwrap();
+		assert_eq!(decoded, request);
+	}
+
+	#[test]
+	fn borrowed_request_body() {
+		use crate::{
+			StateBuf, Stateful, client_id,
+			grant::authorization_code::AuthorizationCodeAuthorizationRequest,
+		};
+
+		let request = WithClaims::new(
+			WithAcrValues::new(
+				WithMaxAge::new(
+					Stateful::new(
+						AuthorizationCodeAuthorizationRequest::new(
+							client_id!("s6BhdRkqt3").to_owned(),
+							None,
+							None,
+						),
+						Some(StateBuf::new("xyz".to_owned()).unwrap()),
+					),
+					300,
+				),
+				vec!["urn:eidas:loa:high".to_owned()],
+			),
+			ClaimsRequest::default().userinfo("email", None),
+		);
+
+		assert_eq!(
+			serde_html_form::to_string(request.build_query()).unwrap(),
+			serde_html_form::to_string(&request).unwrap()
+		);
+	}
+}
@@ -0,0 +1,339 @@
+//! OpenID4VCI authorization details.
+//!
+//! See: <https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0.html#name-using-authorization-details>
+//!
+//! Wallets request the issuance of credentials with Rich Authorization
+//! Requests of type [`OPENID_CREDENTIAL`], referencing a credential
+//! configuration of the credential issuer, or, as in the mDL and eIDAS PID
+//! profiles predating OpenID4VCI 1.0, a credential format (`mso_mdoc` with
+//! its doctype, or SD-JWT VC with its `vct`). The authorization server
+//! returns the same objects in the token response, along with the
+//! identifiers of the credentials that can be issued.
+//!
+//! ```
+//! # use open_auth2::ext::openid_credential::{ClaimsDescription, OpenIdCredential, MDL_DOCTYPE, MDL_NAMESPACE};
+//! let details = OpenIdCredential::mso_mdoc(MDL_DOCTYPE)
+//!     .with_claim(ClaimsDescription::mdoc(MDL_NAMESPACE, "given_name"))
+//!     .with_claim(ClaimsDescription::mdoc(MDL_NAMESPACE, "family_name"));
+//!
+//! assert_eq!(details.doctype.as_deref(), Some("org.iso.18013.5.1.mDL"));
+//! ```
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+use crate::ext::rar::AuthorizationDetailsObject;
+
+/// Authorization details type of credential issuance requests.
+pub const OPENID_CREDENTIAL: &str = "openid_credential";
+
+/// ISO mdoc credential format (ISO/IEC 18013-5).
+pub const FORMAT_MSO_MDOC: &str = "mso_mdoc";
+
+/// IETF SD-JWT VC credential format.
+pub const FORMAT_SD_JWT_VC: &str = "dc+sd-jwt";
+
+/// Doctype of the ISO/IEC 18013-5 mobile driving licence.
+pub const MDL_DOCTYPE: &str = "org.iso.18013.5.1.mDL";
+
+/// Namespace of the ISO/IEC 18013-5 mobile driving licence data elements.
+pub const MDL_NAMESPACE: &str = "org.iso.18013.5.1";
+
+/// Doctype of the EUDI Wallet Person Identification Data, in the `mso_mdoc`
+/// format.
+pub const EUDI_PID_DOCTYPE: &str = "eu.europa.ec.eudi.pid.1";
+
+/// Namespace of the EUDI Wallet Person Identification Data elements, in the
+/// `mso_mdoc` format.
+pub const EUDI_PID_NAMESPACE: &str = "eu.europa.ec.eudi.pid.1";
+
+/// Verifiable credential type of the EUDI Wallet Person Identification
+/// Data, in the SD-JWT VC format.
+pub const EUDI_PID_VCT: &str = "urn:eudi:pid:1";
+
+/// Authorization details object of type [`OPENID_CREDENTIAL`].
+///
+/// See: <https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0.html#name-using-authorization-details>
+#[skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct OpenIdCredential {
+	r#type: OpenIdCredentialType,
+
+	/// Identifier of the requested credential configuration, from the
+	/// `credential_configurations_supported` credential issuer metadata.
+	pub credential_configuration_id: Option<String>,
+
+	/// Format of the requested credential, when it is not identified by its
+	/// credential configuration.
+	pub format: Option<String>,
+
+	/// Doctype of the requested credential, in the [`FORMAT_MSO_MDOC`]
+	/// format.
+	pub doctype: Option<String>,
+
+	/// Type of the requested credential, in the [`FORMAT_SD_JWT_VC`] format.
+	pub vct: Option<String>,
+
+	/// Claims of the credential requested by the wallet, all of them by
+	/// default.
+	pub claims: Option<Vec<ClaimsDescription>>,
+
+	/// Identifiers of the credential issuers, when the authorization server
+	/// serves several of them.
+	pub locations: Option<Vec<String>>,
+
+	/// Identifiers of the issuable credentials, set by the authorization
+	/// server in the token response.
+	pub credential_identifiers: Option<Vec<String>>,
+
+	/// Other fields.
+	#[serde(flatten)]
+	pub ext: BTreeMap<String, serde_json::Value>,
+}
+
+impl OpenIdCredential {
+	fn new() -> Self {
+		Self {
+			r#type: OpenIdCredentialType,
+			credential_configuration_id: None,
+			format: None,
+			doctype: None,
+			vct: None,
+			claims: None,
+			locations: None,
+			credential_identifiers: None,
+			ext: BTreeMap::new(),
+		}
+	}
+
+	/// Requests a credential of the given configuration.
+	pub fn configuration(credential_configuration_id: impl Into<String>) -> Self {
+		Self {
+			credential_configuration_id: Some(credential_configuration_id.into()),
+			..Self::new()
+		}
+	}
+
+	/// Requests an ISO mdoc credential of the given doctype, such as
+	/// [`MDL_DOCTYPE`] or [`EUDI_PID_DOCTYPE`].
+	pub fn mso_mdoc(doctype: impl Into<String>) -> Self {
+		Self {
+			format: Some(FORMAT_MSO_MDOC.to_owned()),
+			doctype: Some(doctype.into()),
+			..Self::new()
+		}
+	}
+
+	/// Requests an SD-JWT VC credential of the given type, such as
+	/// [`EUDI_PID_VCT`].
+	pub fn sd_jwt_vc(vct: impl Into<String>) -> Self {
+		Self {
+			format: Some(FORMAT_SD_JWT_VC.to_owned()),
+			vct: Some(vct.into()),
+			..Self::new()
+		}
+	}
+
+	/// Restricts the request to the given claim, in addition to the
+	/// previously added ones.
+	pub fn with_claim(mut self, claim: ClaimsDescription) -> Self {
+		self.claims.get_or_insert_with(Vec::new).push(claim);
+		self
+	}
+
+	/// Adds the identifier of the credential issuer.
+	pub fn with_location(mut self, location: impl Into<String>) -> Self {
+		self.locations
+			.get_or_insert_with(Vec::new)
+			.push(location.into());
+		self
+	}
+
+	/// Sets the identifiers of the issuable credentials, in the token
+	/// response.
+	pub fn with_credential_identifiers(self, credential_identifiers: Vec<String>) -> Self {
+		Self {
+			credential_identifiers: Some(credential_identifiers),
+			..self
+		}
+	}
+}
+
+impl AuthorizationDetailsObject for OpenIdCredential {
+	fn r#type(&self) -> &str {
+		OPENID_CREDENTIAL
+	}
+}
+
+/// The `openid_credential` type field, rejecting any other type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct OpenIdCredentialType;
+
+impl Serialize for OpenIdCredentialType {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: serde::Serializer,
+	{
+		serializer.serialize_str(OPENID_CREDENTIAL)
+	}
+}
+
+impl<'de> Deserialize<'de> for OpenIdCredentialType {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: serde::Deserializer<'de>,
+	{
+		let r#type = String::deserialize(deserializer)?;
+		if r#type == OPENID_CREDENTIAL {
+			Ok(Self)
+		} else {
+			Err(serde::de::Error::invalid_value(
+				serde::de::Unexpected::Str(&r#type),
+				&OPENID_CREDENTIAL,
+			))
+		}
+	}
+}
+
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for OpenIdCredentialType {
+	fn inline_schema() -> bool {
+		true
+	}
+
+	fn schema_name() -> std::borrow::Cow<'static, str> {
+		"OpenIdCredentialType".into()
+	}
+
+	fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+		schemars::json_schema!({
+			"type": "string",
+			"const": OPENID_CREDENTIAL
+		})
+	}
+}
+
+/// Description of a requested credential claim.
+///
+/// See: <https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0.html#name-claims-description>
+#[skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ClaimsDescription {
+	/// Path to the claim in the credential: names of object properties,
+	/// indices of array elements, or `null` for every array element.
+	pub path: Vec<serde_json::Value>,
+
+	/// Whether the credential issuer must include the claim.
+	pub mandatory: Option<bool>,
+}
+
+impl ClaimsDescription {
+	/// Describes the claim at the given path.
+	pub fn new(path: Vec<serde_json::Value>) -> Self {
+		Self {
+			path,
+			mandatory: None,
+		}
+	}
+
+	/// Describes the given top-level claim of a JSON-based credential, such
+	/// as an SD-JWT VC.
+	pub fn claim(name: impl Into<String>) -> Self {
+		Self::new(vec![serde_json::Value::String(name.into())])
+	}
+
+	/// Describes the given data element of an ISO mdoc credential.
+	pub fn mdoc(namespace: impl Into<String>, element: impl Into<String>) -> Self {
+		Self::new(vec![
+			serde_json::Value::String(namespace.into()),
+			serde_json::Value::String(element.into()),
+		])
+	}
+
+	/// Requires the credential issuer to include the claim.
+	pub fn mandatory(self) -> Self {
+		Self {
+			mandatory: Some(true),
+			..self
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{ext::rar::AuthorizationDetails, server::metadata::AuthorizationServerMetadata};
+
+	use super::*;
+
+	#[test]
+	fn mso_mdoc() {
+		let details = OpenIdCredential::mso_mdoc(MDL_DOCTYPE)
+			.with_claim(ClaimsDescription::mdoc(MDL_NAMESPACE, "given_name").mandatory())
+			.with_claim(ClaimsDescription::mdoc(MDL_NAMESPACE, "family_name"));
+
+		assert_eq!(
+			serde_json::to_value(&details).unwrap(),
+			serde_json::json!({
+				"type": "openid_credential",
+				"format": "mso_mdoc",
+				"doctype": "org.iso.18013.5.1.mDL",
+				"claims": [
+					{ "path": ["org.iso.18013.5.1", "given_name"], "mandatory": true },
+					{ "path": ["org.iso.18013.5.1", "family_name"] }
+				]
+			})
+		);
+	}
+
+	#[test]
+	fn token_response_details() {
+		let details: Vec<OpenIdCredential> = serde_json::from_value(serde_json::json!([{
+			"type": "openid_credential",
+			"credential_configuration_id": "eu.europa.ec.eudi.pid_vc_sd_jwt",
+			"credential_identifiers": ["PID-1", "PID-2"],
+			"x-vendor": true
+		}]))
+		.unwrap();
+
+		assert_eq!(
+			details,
+			[
+				OpenIdCredential::configuration("eu.europa.ec.eudi.pid_vc_sd_jwt")
+					.with_credential_identifiers(vec!["PID-1".to_owned(), "PID-2".to_owned()])
+			]
+			.map(|mut details| {
+				details.ext.insert("x-vendor".to_owned(), true.into());
+				details
+			})
+		);
+
+		assert!(
+			serde_json::from_value::<OpenIdCredential>(serde_json::json!({
+				"type": "payment_initiation"
+			}))
+			.is_err()
+		);
+	}
+
+	#[test]
+	fn authorization_request() {
+		let metadata: AuthorizationServerMetadata = serde_json::from_value(serde_json::json!({
+			"issuer": "https://server.example.com",
+			"authorization_details_types_supported": ["openid_credential"]
+		}))
+		.unwrap();
+		let details = [OpenIdCredential::sd_jwt_vc(EUDI_PID_VCT)
+			.with_claim(ClaimsDescription::claim("birthdate"))
+			.with_location("https://credential-issuer.example.com")];
+		metadata.check_authorization_details(&details).unwrap();
+
+		let form =
+			serde_html_form::to_string(AuthorizationDetails::from(details.to_vec())).unwrap();
+		let decoded: AuthorizationDetails<OpenIdCredential> =
+			serde_html_form::from_str(&form).unwrap();
+		assert_eq!(*decoded, details);
+	}
+}
@@ -10,10 +10,45 @@ use sha2::{Digest, Sha256};
 use str_newtype::StrNewType;
 
 use crate::{
+	client::ClientType,
 	endpoints::{HttpRequest, RedirectRequest, RequestBuilder},
 	transport::HttpClient,
 };
 
+/// Policy deciding which clients must use PKCE with the Authorization Code
+/// Grant.
+///
+/// The default policy follows the
+/// [OAuth 2.0 Security Best Current Practice](https://www.rfc-editor.org/rfc/rfc9700.html#section-2.1.1):
+/// public clients must use PKCE, while confidential clients may rely on
+/// client authentication instead.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PkcePolicy {
+	/// Every client must use PKCE.
+	Required,
+
+	/// Public clients must use PKCE.
+	#[default]
+	RequiredForPublicClients,
+
+	/// PKCE is never required.
+	///
+	/// This is not recommended, and only meant for interoperability with
+	/// legacy clients.
+	Optional,
+}
+
+impl PkcePolicy {
+	/// Checks whether clients of the given type must use PKCE.
+	pub fn is_required_for(&self, client_type: ClientType) -> bool {
+		match self {
+			Self::Required => true,
+			Self::RequiredForPublicClients => client_type.is_public(),
+			Self::Optional => false,
+		}
+	}
+}
+
 /// Extension wrapper that attaches a PKCE code challenge and method to a
 /// request.
 ///
@@ -35,6 +70,28 @@ impl<T> WithPkceChallenge<T> {
 	pub fn new(value: T, pkce: PkceCodeChallengeAndMethod) -> Self {
 		Self { value, pkce }
 	}
+
+	/// Wraps the given request body of the inner request, borrowing the
+	/// code challenge.
+	fn body<B>(&self, value: B) -> WithPkceChallengeBody<'_, B> {
+		WithPkceChallengeBody {
+			pkce: &self.pkce,
+			value,
+		}
+	}
+}
+
+/// Request body of a [`WithPkceChallenge`] request, borrowing its code
+/// challenge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct WithPkceChallengeBody<'b, T> {
+	/// The PKCE code challenge and method.
+	#[serde(flatten)]
+	pub pkce: &'b PkceCodeChallengeAndMethod,
+
+	/// The inner request body.
+	#[serde(flatten)]
+	pub value: T,
 }
 
 impl<T> std::ops::Deref for WithPkceChallenge<T> {
@@ -56,12 +113,12 @@ where
 	T: RedirectRequest,
 {
 	type RequestBody<'b>
-		= WithPkceChallenge<T::RequestBody<'b>>
+		= WithPkceChallengeBody<'b, T::RequestBody<'b>>
 	where
 		Self: 'b;
 
 	fn build_query(&self) -> Self::RequestBody<'_> {
-		WithPkceChallenge::new(self.value.build_query(), self.pkce.clone())
+		self.body(self.value.build_query())
 	}
 }
 
@@ -71,7 +128,7 @@ where
 {
 	type ContentType = T::ContentType;
 	type RequestBody<'b>
-		= WithPkceChallenge<T::RequestBody<'b>>
+		= WithPkceChallengeBody<'b, T::RequestBody<'b>>
 	where
 		Self: 'b;
 	type Response = T::Response;
@@ -85,7 +142,7 @@ where
 		self.value
 			.build_request(endpoint, http_client)
 			.await
-			.map(|request| request.map(|value| WithPkceChallenge::new(value, self.pkce.clone())))
+			.map(|request| request.map(|value| self.body(value)))
 	}
 
 	fn decode_response(
@@ -243,6 +300,7 @@ impl<'a, E, T> AddPkceVerifier<'a> for RequestBuilder<E, T> {
 /// Code Challenge used for [PKCE](https://tools.ietf.org/html/rfc7636) protection via the
 /// `code_challenge` parameter.
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct PkceCodeChallengeAndMethod {
 	#[serde(rename = "code_challenge")]
 	pub challenge: PkceCodeChallengeBuf,
@@ -428,6 +486,13 @@ impl<'de> Deserialize<'de> for PkceCodeChallengeMethod {
 	}
 }
 
+#[cfg(feature = "schemars")]
+crate::types::json_schema_string!(
+	PkceCodeChallengeMethod,
+	PkceCodeChallenge,
+	PkceCodeChallengeBuf
+);
+
 /// Code Verifier.
 ///
 /// See: <https://datatracker.ietf.org/doc/html/rfc7636#section-4.1>
@@ -626,4 +691,14 @@ mod tests {
 		);
 		assert_eq!(challenge.as_str(), verifier.as_str());
 	}
+
+	// --- PkcePolicy ---
+
+	#[test]
+	fn pkce_policy() {
+		assert!(PkcePolicy::default().is_required_for(ClientType::Public));
+		assert!(!PkcePolicy::default().is_required_for(ClientType::Confidential));
+		assert!(PkcePolicy::Required.is_required_for(ClientType::Confidential));
+		assert!(!PkcePolicy::Optional.is_required_for(ClientType::Public));
+	}
 }
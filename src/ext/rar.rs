@@ -3,13 +3,16 @@
 //! See: <https://www.rfc-editor.org/rfc/rfc9396.html>
 
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_with::skip_serializing_none;
 use std::{
 	borrow::Borrow,
+	collections::BTreeMap,
 	ops::{Deref, DerefMut},
 };
 
 use crate::{
 	endpoints::{HttpRequest, RedirectRequest, RequestBuilder},
+	server::metadata::{AuthorizationServerMetadata, UnsupportedCapability},
 	transport::HttpClient,
 };
 
@@ -23,11 +26,157 @@ pub trait AuthorizationDetailsObject: Serialize + DeserializeOwned {
 	fn r#type(&self) -> &str;
 }
 
+/// Authorization details object with the common data fields.
+///
+/// See: <https://www.rfc-editor.org/rfc/rfc9396.html#section-2.2>
+///
+/// Type-specific fields are stored in [`ext`](Self::ext). Use
+/// [`CommonAuthorizationDetails::of_type`] to build a value.
+#[skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CommonAuthorizationDetails {
+	/// Authorization details type identifier.
+	pub r#type: String,
+
+	/// Locations of the resources or resource servers.
+	pub locations: Option<Vec<String>>,
+
+	/// Kinds of actions to be taken at the resource.
+	pub actions: Option<Vec<String>>,
+
+	/// Kinds of data being requested from the resource.
+	pub datatypes: Option<Vec<String>>,
+
+	/// Identifier of a specific resource.
+	pub identifier: Option<String>,
+
+	/// Types or levels of privilege being requested at the resource.
+	pub privileges: Option<Vec<String>>,
+
+	/// Type-specific fields.
+	#[serde(flatten)]
+	pub ext: BTreeMap<String, serde_json::Value>,
+}
+
+impl CommonAuthorizationDetails {
+	/// Starts building authorization details of the given type.
+	pub fn of_type(r#type: impl Into<String>) -> AuthorizationDetailsBuilder {
+		AuthorizationDetailsBuilder::of_type(r#type)
+	}
+}
+
+impl AuthorizationDetailsObject for CommonAuthorizationDetails {
+	fn r#type(&self) -> &str {
+		&self.r#type
+	}
+}
+
+/// Builder for [`CommonAuthorizationDetails`].
+///
+/// ```
+/// # use open_auth2::ext::rar::AuthorizationDetailsBuilder;
+/// let details = AuthorizationDetailsBuilder::of_type("payment_initiation")
+///     .location("https://example.com/payments")
+///     .action("initiate")
+///     .action("status")
+///     .field("instructedAmount", serde_json::json!({ "currency": "EUR", "amount": "123.50" }))
+///     .build();
+///
+/// assert_eq!(details.actions.unwrap(), ["initiate", "status"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct AuthorizationDetailsBuilder(CommonAuthorizationDetails);
+
+impl AuthorizationDetailsBuilder {
+	/// Starts building authorization details of the given type.
+	pub fn of_type(r#type: impl Into<String>) -> Self {
+		Self(CommonAuthorizationDetails {
+			r#type: r#type.into(),
+			locations: None,
+			actions: None,
+			datatypes: None,
+			identifier: None,
+			privileges: None,
+			ext: BTreeMap::new(),
+		})
+	}
+
+	/// Adds a resource location.
+	pub fn location(mut self, location: impl Into<String>) -> Self {
+		push(&mut self.0.locations, location.into());
+		self
+	}
+
+	/// Adds an action.
+	pub fn action(mut self, action: impl Into<String>) -> Self {
+		push(&mut self.0.actions, action.into());
+		self
+	}
+
+	/// Adds a data type.
+	pub fn datatype(mut self, datatype: impl Into<String>) -> Self {
+		push(&mut self.0.datatypes, datatype.into());
+		self
+	}
+
+	/// Sets the resource identifier.
+	pub fn identifier(mut self, identifier: impl Into<String>) -> Self {
+		self.0.identifier = Some(identifier.into());
+		self
+	}
+
+	/// Adds a privilege.
+	pub fn privilege(mut self, privilege: impl Into<String>) -> Self {
+		push(&mut self.0.privileges, privilege.into());
+		self
+	}
+
+	/// Sets a type-specific field.
+	///
+	/// Common data fields (e.g. `locations`) cannot be set with this method,
+	/// and are ignored.
+	pub fn field(mut self, name: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+		let name = name.into();
+		if !COMMON_FIELDS.contains(&name.as_str()) {
+			self.0.ext.insert(name, value.into());
+		}
+
+		self
+	}
+
+	/// Builds the authorization details.
+	pub fn build(self) -> CommonAuthorizationDetails {
+		self.0
+	}
+}
+
+impl From<AuthorizationDetailsBuilder> for CommonAuthorizationDetails {
+	fn from(value: AuthorizationDetailsBuilder) -> Self {
+		value.build()
+	}
+}
+
+/// Fields of [`CommonAuthorizationDetails`] that are not extension fields.
+const COMMON_FIELDS: [&str; 6] = [
+	"type",
+	"locations",
+	"actions",
+	"datatypes",
+	"identifier",
+	"privileges",
+];
+
+fn push(list: &mut Option<Vec<String>>, value: String) {
+	list.get_or_insert_with(Vec::new).push(value)
+}
+
 /// Collection of authorization detail objects.
 ///
 /// When serialized as part of a form-encoded request, the objects are first
 /// serialized as a JSON array string in the `authorization_details` field.
 #[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(bound = "D: AuthorizationDetailsObject")]
 pub struct AuthorizationDetails<D> {
 	#[serde(
@@ -35,6 +184,7 @@ pub struct AuthorizationDetails<D> {
 		with = "as_json",
 		skip_serializing_if = "Vec::is_empty"
 	)]
+	#[cfg_attr(feature = "schemars", schemars(with = "String"))]
 	objects: Vec<D>,
 }
 
@@ -167,6 +317,21 @@ pub trait AddAuthorizationDetails<'a, D> {
 
 	/// Wraps the current request with the given authorization details.
 	fn with_authorization_details(self, authorization_details: &'a [D]) -> Self::Output;
+
+	/// Wraps the current request with the given authorization details, after
+	/// checking that their types are advertised by the authorization server.
+	fn with_checked_authorization_details<P>(
+		self,
+		metadata: &AuthorizationServerMetadata<P>,
+		authorization_details: &'a [D],
+	) -> Result<Self::Output, UnsupportedCapability>
+	where
+		Self: Sized,
+		D: AuthorizationDetailsObject,
+	{
+		metadata.check_authorization_details(authorization_details)?;
+		Ok(self.with_authorization_details(authorization_details))
+	}
 }
 
 impl<'a, D, E, T> AddAuthorizationDetails<'a, D> for RequestBuilder<E, T>
@@ -207,3 +372,86 @@ mod as_json {
 		serde_json::from_str(&string).map_err(serde::de::Error::custom)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn payment_initiation() -> CommonAuthorizationDetails {
+		CommonAuthorizationDetails::of_type("payment_initiation")
+			.location("https://example.com/payments")
+			.action("initiate")
+			.action("status")
+			.datatype("account")
+			.identifier("XYZ")
+			.field(
+				"instructedAmount",
+				serde_json::json!({ "currency": "EUR", "amount": "123.50" }),
+			)
+			.field("type", "ignored")
+			.build()
+	}
+
+	#[test]
+	fn build_authorization_details() {
+		assert_eq!(
+			serde_json::to_value(payment_initiation()).unwrap(),
+			serde_json::json!({
+				"type": "payment_initiation",
+				"locations": ["https://example.com/payments"],
+				"actions": ["initiate", "status"],
+				"datatypes": ["account"],
+				"identifier": "XYZ",
+				"instructedAmount": { "currency": "EUR", "amount": "123.50" }
+			})
+		);
+	}
+
+	#[test]
+	fn authorization_details_round_trip() {
+		let details = AuthorizationDetails::from(vec![
+			payment_initiation(),
+			CommonAuthorizationDetails::of_type("account_information").build(),
+		]);
+
+		let json = serde_json::to_string(&*details).unwrap();
+		let decoded: Vec<CommonAuthorizationDetails> = serde_json::from_str(&json).unwrap();
+		assert_eq!(decoded, *details);
+
+		let form = serde_html_form::to_string(&details).unwrap();
+		let decoded: AuthorizationDetails<CommonAuthorizationDetails> =
+			serde_html_form::from_str(&form).unwrap();
+		assert_eq!(decoded, details);
+	}
+
+	#[test]
+	fn checked_authorization_details() {
+		let metadata: AuthorizationServerMetadata = serde_json::from_value(serde_json::json!({
+			"issuer": "https://server.example.com",
+			"authorization_details_types_supported": ["payment_initiation"]
+		}))
+		.unwrap();
+		let endpoint = crate::endpoints::authorization::AuthorizationEndpoint::new(
+			&(),
+			iref::uri!("https://server.example.com/authorize"),
+		);
+		let builder = || RequestBuilder::new(endpoint, crate::util::NoExtension {});
+
+		let details = [payment_initiation()];
+		assert!(
+			builder()
+				.with_checked_authorization_details(&metadata, &details)
+				.is_ok()
+		);
+
+		let details = [CommonAuthorizationDetails::of_type("account_information").build()];
+		assert_eq!(
+			builder()
+				.with_checked_authorization_details(&metadata, &details)
+				.err(),
+			Some(UnsupportedCapability::AuthorizationDetailsType(
+				"account_information".to_owned()
+			))
+		);
+	}
+}
@@ -1,20 +1,28 @@
 //! Authorization Code Grant.
 //!
 //! See: <https://datatracker.ietf.org/doc/html/rfc6749#section-4.1>
+use std::marker::PhantomData;
+
 use iref::{Uri, UriBuf};
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 
 use crate::{
-	ClientIdBuf, CodeBuf, IntoScope, ScopeBuf, StateBuf, Stateful,
+	ClientIdBuf, CodeBuf, IntoScope, InvalidRedirectUri, RedirectUriKind, ResponseType, ScopeBuf,
+	StateBuf, Stateful,
 	client::{OAuth2Client, OAuth2ClientError},
 	endpoints::{
 		HttpRequest, RedirectRequest, RequestBuilder,
 		authorization::{AnyAuthorizationEndpoint, AuthorizationEndpoint},
 		token::TokenEndpoint,
 	},
-	server::ErrorResponse,
-	transport::{APPLICATION_JSON, HttpClient, WwwFormUrlEncoded, expect_content_type},
+	grant::{GrantType, TokenGrant, grant_type_param},
+	server::{
+		ErrorResponse,
+		metadata::{AuthorizationServerMetadata, UnsupportedResponseType},
+	},
+	set_loopback_port,
+	transport::{HttpClient, WwwFormUrlEncoded},
 	util::extend_uri_query,
 };
 
@@ -51,6 +59,21 @@ pub trait ExchangeCode: Sized + AnyAuthorizationEndpoint {
 		redirect_uri: Option<UriBuf>,
 		scope: impl IntoScope,
 	) -> RequestBuilder<Self, Self::Request<AuthorizationCodeAuthorizationRequest>>;
+
+	/// Begins an Authorization Code authorization request, after checking
+	/// that the authorization server advertises the `code` response type.
+	fn checked_authorize_url<P>(
+		self,
+		metadata: &AuthorizationServerMetadata<P>,
+		redirect_uri: Option<UriBuf>,
+		scope: impl IntoScope,
+	) -> Result<
+		RequestBuilder<Self, Self::Request<AuthorizationCodeAuthorizationRequest>>,
+		UnsupportedResponseType,
+	> {
+		metadata.check_response_type(ResponseType::CODE)?;
+		Ok(self.authorize_url(redirect_uri, scope))
+	}
 }
 
 impl<T> ExchangeCode for T
@@ -79,6 +102,7 @@ where
 /// See: <https://datatracker.ietf.org/doc/html/rfc6749#section-4.1.1>
 #[skip_serializing_none]
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(tag = "response_type", rename = "code")]
 pub struct AuthorizationCodeAuthorizationRequest {
 	/// Client identifier.
@@ -89,6 +113,7 @@ pub struct AuthorizationCodeAuthorizationRequest {
 	/// Redirect URI.
 	///
 	/// See: <https://datatracker.ietf.org/doc/html/rfc6749#section-3.1.2>
+	#[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
 	pub redirect_uri: Option<UriBuf>,
 
 	/// Scope of the access request.
@@ -111,6 +136,30 @@ impl AuthorizationCodeAuthorizationRequest {
 		}
 	}
 
+	/// Returns the kind of the redirect URI, if any, checking it against the
+	/// rules of [RFC 8252](https://www.rfc-editor.org/rfc/rfc8252.html).
+	pub fn redirect_uri_kind(&self) -> Result<Option<RedirectUriKind>, InvalidRedirectUri> {
+		self.redirect_uri
+			.as_deref()
+			.map(RedirectUriKind::of)
+			.transpose()
+	}
+
+	/// Sets the port of the loopback redirect URI, typically once the client
+	/// has bound its local HTTP listener.
+	///
+	/// Fails if the redirect URI is missing or is not a loopback URI.
+	///
+	/// See: <https://www.rfc-editor.org/rfc/rfc8252.html#section-7.3>
+	pub fn with_loopback_port(mut self, port: u16) -> Result<Self, InvalidRedirectUri> {
+		let uri = self
+			.redirect_uri
+			.as_mut()
+			.ok_or(InvalidRedirectUri::NotLoopback)?;
+		set_loopback_port(uri, port)?;
+		Ok(self)
+	}
+
 	/// Returns the redirect URI to use, falling back to `default_uri` if
 	/// none was specified in the request.
 	pub fn redirect_url<'a>(&'a self, default_uri: Option<&'a Uri>) -> Option<&'a Uri> {
@@ -158,6 +207,18 @@ impl AuthorizationCodeAuthorizationRequest {
 	}
 }
 
+impl<E> RequestBuilder<E, AuthorizationCodeAuthorizationRequest> {
+	/// Sets the port of the loopback redirect URI.
+	///
+	/// See [`AuthorizationCodeAuthorizationRequest::with_loopback_port`].
+	pub fn with_loopback_port(self, port: u16) -> Result<Self, InvalidRedirectUri> {
+		Ok(Self {
+			endpoint: self.endpoint,
+			request: self.request.with_loopback_port(port)?,
+		})
+	}
+}
+
 impl RedirectRequest for AuthorizationCodeAuthorizationRequest {
 	type RequestBody<'b>
 		= &'b Self
@@ -172,6 +233,7 @@ impl RedirectRequest for AuthorizationCodeAuthorizationRequest {
 /// Authorization Response with the Authorization Code Grant.
 #[skip_serializing_none]
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct AuthorizationCodeAuthorizationResponse {
 	/// Authorization code generated by the authorization server.
 	///
@@ -210,8 +272,15 @@ where
 /// Token Request with the Authorization Code Grant.
 #[skip_serializing_none]
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "grant_type", rename = "authorization_code")]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct AuthorizationCodeTokenRequest {
+	#[serde(with = "grant_type_param")]
+	#[cfg_attr(
+		feature = "schemars",
+		schemars(schema_with = "grant_type_param::json_schema::<AuthorizationCodeTokenRequest>")
+	)]
+	grant_type: PhantomData<Self>,
+
 	/// Client identifier.
 	///
 	/// Required if the client is not authenticating with the authorization
@@ -226,9 +295,14 @@ pub struct AuthorizationCodeTokenRequest {
 	/// Required if the "redirect_uri" parameter was included in the
 	/// authorization request as described in Section 4.1.1, and their values
 	/// *must* be identical.
+	#[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
 	pub redirect_uri: Option<UriBuf>,
 }
 
+impl TokenGrant for AuthorizationCodeTokenRequest {
+	const GRANT_TYPE: GrantType = GrantType::AuthorizationCode;
+}
+
 impl AuthorizationCodeTokenRequest {
 	/// Creates a new token request with the given parameters.
 	pub fn new(
@@ -237,6 +311,7 @@ impl AuthorizationCodeTokenRequest {
 		redirect_uri: Option<UriBuf>,
 	) -> Self {
 		Self {
+			grant_type: PhantomData,
 			client_id,
 			code,
 			redirect_uri,
@@ -270,18 +345,10 @@ where
 
 	fn decode_response(
 		&self,
-		_endpoint: &TokenEndpoint<'a, C>,
+		endpoint: &TokenEndpoint<'a, C>,
 		response: http::Response<Vec<u8>>,
 	) -> Result<http::Response<Self::ResponsePayload>, OAuth2ClientError> {
-		if response.status() != http::StatusCode::OK {
-			return Err(OAuth2ClientError::server(response.status()));
-		}
-
-		expect_content_type(response.headers(), &APPLICATION_JSON)?;
-
-		let body = serde_json::from_slice(response.body()).map_err(OAuth2ClientError::response)?;
-
-		Ok(response.map(|_| body))
+		endpoint.decode_token_response(response)
 	}
 
 	async fn process_response(
@@ -0,0 +1,283 @@
+//! Device Authorization Grant.
+//!
+//! See: <https://datatracker.ietf.org/doc/html/rfc8628>
+use std::marker::PhantomData;
+
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+use crate::{
+	ClientIdBuf,
+	client::{OAuth2Client, OAuth2ClientError},
+	endpoints::{HttpRequest, RequestBuilder, token::TokenEndpoint},
+	grant::{GrantType, TokenGrant, grant_type_param},
+	transport::{HttpClient, WwwFormUrlEncoded},
+};
+
+#[cfg(feature = "tokio")]
+use crate::{
+	endpoints::device_authorization::DeviceAuthorizationResponse,
+	grant::polling::{PollingConfig, PollingError},
+	time::Clock,
+};
+
+impl<'a, C> TokenEndpoint<'a, C>
+where
+	C: OAuth2Client,
+{
+	/// Begins a Device Access Token Request, polling for the result of a
+	/// device authorization.
+	///
+	/// Returns a [`RequestBuilder`] that can be further extended before
+	/// being sent.
+	pub fn exchange_device_code(
+		self,
+		device_code: String,
+	) -> RequestBuilder<Self, DeviceCodeTokenRequest> {
+		let client_id = self.client.client_id().to_owned();
+		RequestBuilder::new(
+			self,
+			DeviceCodeTokenRequest::new(Some(client_id), device_code),
+		)
+	}
+
+	/// Polls the token endpoint until the given device authorization is
+	/// completed by the end user, then returns the token response.
+	///
+	/// Waits for the polling interval before each request, increasing it
+	/// when the server responds with `slow_down`. Stops with
+	/// [`PollingError::Expired`] once the device code expires, or when the
+	/// server responds with any other error (e.g. `access_denied`).
+	#[cfg(feature = "tokio")]
+	pub async fn poll_device_code(
+		self,
+		authorization: &DeviceAuthorizationResponse,
+		config: &PollingConfig,
+		http_client: &impl HttpClient,
+		clock: &impl Clock,
+	) -> Result<C::TokenResponse, PollingError> {
+		let mut state = authorization.polling(config, clock);
+		let mut interval = state.interval();
+
+		loop {
+			tokio::time::sleep(interval).await;
+
+			match self
+				.clone()
+				.exchange_device_code(authorization.device_code.clone())
+				.send(http_client)
+				.await
+			{
+				Ok(response) => break Ok(response),
+				Err(e) => interval = state.on_error(e, clock)?,
+			}
+		}
+	}
+}
+
+/// Device Access Token Request.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc8628#section-3.4>
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DeviceCodeTokenRequest {
+	#[serde(with = "grant_type_param")]
+	#[cfg_attr(
+		feature = "schemars",
+		schemars(schema_with = "grant_type_param::json_schema::<DeviceCodeTokenRequest>")
+	)]
+	grant_type: PhantomData<Self>,
+
+	/// Client identifier, if the client is not authenticating by other
+	/// means.
+	pub client_id: Option<ClientIdBuf>,
+
+	/// The device verification code returned by the device authorization
+	/// endpoint.
+	pub device_code: String,
+}
+
+impl TokenGrant for DeviceCodeTokenRequest {
+	const GRANT_TYPE: GrantType = GrantType::DeviceCode;
+}
+
+impl DeviceCodeTokenRequest {
+	/// Creates a new device access token request.
+	pub fn new(client_id: Option<ClientIdBuf>, device_code: String) -> Self {
+		Self {
+			grant_type: PhantomData,
+			client_id,
+			device_code,
+		}
+	}
+}
+
+impl<'a, C> HttpRequest<TokenEndpoint<'a, C>> for DeviceCodeTokenRequest
+where
+	C: OAuth2Client,
+{
+	type ContentType = WwwFormUrlEncoded;
+	type RequestBody<'b>
+		= &'b Self
+	where
+		Self: 'b;
+	type Response = C::TokenResponse;
+	type ResponsePayload = C::TokenResponse;
+
+	async fn build_request(
+		&self,
+		endpoint: &TokenEndpoint<'a, C>,
+		_http_client: &impl HttpClient,
+	) -> Result<http::Request<Self::RequestBody<'_>>, OAuth2ClientError> {
+		Ok(http::Request::builder()
+			.method(http::Method::POST)
+			.uri(endpoint.uri.as_str())
+			.body(self)
+			.unwrap())
+	}
+
+	fn decode_response(
+		&self,
+		endpoint: &TokenEndpoint<'a, C>,
+		response: http::Response<Vec<u8>>,
+	) -> Result<http::Response<Self::ResponsePayload>, OAuth2ClientError> {
+		endpoint.decode_token_response(response)
+	}
+
+	async fn process_response(
+		&self,
+		_endpoint: &TokenEndpoint<'a, C>,
+		_http_client: &impl HttpClient,
+		response: http::Response<Self::ResponsePayload>,
+	) -> Result<Self::Response, OAuth2ClientError> {
+		Ok(response.into_body())
+	}
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+	use std::{
+		sync::atomic::{AtomicUsize, Ordering},
+		time::Duration,
+	};
+
+	use iref::uri;
+
+	use crate::{
+		ClientId, client_id, endpoints::token::TokenResponse, grant::polling::PollingConfig,
+		time::SystemClock,
+	};
+
+	use super::*;
+
+	struct TestClient;
+
+	impl OAuth2Client for TestClient {
+		type TokenResponse = TokenResponse;
+
+		fn client_id(&self) -> &ClientId {
+			client_id!("s6BhdRkqt3")
+		}
+	}
+
+	/// Token endpoint responding with the given error codes, then issuing
+	/// an access token.
+	struct PollingServer {
+		errors: &'static [&'static str],
+		requests: AtomicUsize,
+	}
+
+	impl HttpClient for PollingServer {
+		async fn send(
+			&self,
+			request: http::Request<Vec<u8>>,
+		) -> Result<http::Response<Vec<u8>>, OAuth2ClientError> {
+			let body = String::from_utf8(request.into_body()).unwrap();
+			assert!(body.contains("device_code=GmRhmhcxhwAzkoEqiMEg_DnyEysNkuNhszIySk9eS"));
+
+			let n = self.requests.fetch_add(1, Ordering::SeqCst);
+			let (status, body) = match self.errors.get(n) {
+				Some(error) => (
+					http::StatusCode::BAD_REQUEST,
+					serde_json::json!({ "error": error }),
+				),
+				None => (
+					http::StatusCode::OK,
+					serde_json::json!({
+						"access_token": "2YotnFZFEjr1zCsicMWpAA",
+						"token_type": "Bearer"
+					}),
+				),
+			};
+
+			Ok(http::Response::builder()
+				.status(status)
+				.header(http::header::CONTENT_TYPE, "application/json")
+				.body(serde_json::to_vec(&body).unwrap())
+				.unwrap())
+		}
+	}
+
+	fn authorization(expires_in: Duration) -> DeviceAuthorizationResponse {
+		DeviceAuthorizationResponse::new(
+			"GmRhmhcxhwAzkoEqiMEg_DnyEysNkuNhszIySk9eS".to_owned(),
+			"WDJB-MJHT".to_owned(),
+			uri!("https://example.com/device").to_owned(),
+			expires_in,
+		)
+	}
+
+	fn endpoint(client: &TestClient) -> TokenEndpoint<'_, TestClient> {
+		TokenEndpoint::new(client, uri!("https://server.example.com/token"))
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn poll() {
+		let server = PollingServer {
+			errors: &[
+				"authorization_pending",
+				"slow_down",
+				"authorization_pending",
+			],
+			requests: AtomicUsize::new(0),
+		};
+
+		let start = tokio::time::Instant::now();
+		let response = endpoint(&TestClient)
+			.poll_device_code(
+				&authorization(Duration::from_secs(1800)),
+				&PollingConfig::new(),
+				&server,
+				&SystemClock,
+			)
+			.await
+			.unwrap();
+
+		assert_eq!(response.access_token.as_str(), "2YotnFZFEjr1zCsicMWpAA");
+		assert_eq!(server.requests.load(Ordering::SeqCst), 4);
+		assert_eq!(start.elapsed(), Duration::from_secs(5 + 5 + 10 + 10));
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn poll_denied() {
+		let server = PollingServer {
+			errors: &["authorization_pending", "access_denied"],
+			requests: AtomicUsize::new(0),
+		};
+
+		let result = endpoint(&TestClient)
+			.poll_device_code(
+				&authorization(Duration::from_secs(1800)),
+				&PollingConfig::new(),
+				&server,
+				&SystemClock,
+			)
+			.await;
+
+		assert!(matches!(
+			result,
+			Err(PollingError::Client(e)) if e.error_code() == Some("access_denied")
+		));
+	}
+}
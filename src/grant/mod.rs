@@ -4,7 +4,289 @@
 //!
 //! - [`authorization_code`] — Authorization Code Grant
 //!   ([RFC 6749 Section 4.1](https://datatracker.ietf.org/doc/html/rfc6749#section-4.1)).
+//! - [`device_code`] — Device Authorization Grant
+//!   ([RFC 8628](https://datatracker.ietf.org/doc/html/rfc8628)).
+//! - [`polling`] — Token endpoint polling, shared by the Device Authorization
+//!   Grant and Client-Initiated Backchannel Authentication.
 //! - [`pre_authorized_code`] — Pre-Authorized Code Grant
 //!   ([OpenID4VCI](https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0.html)).
+//! - [`refresh`] — Refresh Token Grant
+//!   ([RFC 6749 Section 6](https://datatracker.ietf.org/doc/html/rfc6749#section-6)).
 pub mod authorization_code;
+#[cfg(feature = "device")]
+pub mod device_code;
+pub mod polling;
 pub mod pre_authorized_code;
+pub mod refresh;
+
+use std::{convert::Infallible, fmt, marker::PhantomData, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+/// String representation of the Authorization Code Grant type.
+pub const GRANT_TYPE_AUTHORIZATION_CODE: &str = "authorization_code";
+
+/// String representation of the Implicit Grant type.
+pub const GRANT_TYPE_IMPLICIT: &str = "implicit";
+
+/// String representation of the Refresh Token Grant type.
+pub const GRANT_TYPE_REFRESH_TOKEN: &str = "refresh_token";
+
+/// String representation of the Client Credentials Grant type.
+pub const GRANT_TYPE_CLIENT_CREDENTIALS: &str = "client_credentials";
+
+/// String representation of the Resource Owner Password Credentials Grant
+/// type.
+pub const GRANT_TYPE_PASSWORD: &str = "password";
+
+/// String representation of the Device Authorization Grant type.
+pub const GRANT_TYPE_DEVICE_CODE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// String representation of the Pre-Authorized Code Grant type.
+pub const GRANT_TYPE_PRE_AUTHORIZED_CODE: &str =
+	"urn:ietf:params:oauth:grant-type:pre-authorized_code";
+
+/// String representation of the Token Exchange Grant type.
+pub const GRANT_TYPE_TOKEN_EXCHANGE: &str = "urn:ietf:params:oauth:grant-type:token-exchange";
+
+/// String representation of the JWT Bearer Grant type.
+pub const GRANT_TYPE_JWT_BEARER: &str = "urn:ietf:params:oauth:grant-type:jwt-bearer";
+
+/// String representation of the Client-Initiated Backchannel Authentication
+/// Grant type.
+pub const GRANT_TYPE_CIBA: &str = "urn:openid:params:grant-type:ciba";
+
+/// OAuth 2.0 grant type, as used in the `grant_type` parameter of token
+/// requests and the `grant_types_supported` authorization server metadata.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GrantType {
+	/// Authorization Code Grant.
+	///
+	/// See: <https://datatracker.ietf.org/doc/html/rfc6749#section-4.1>
+	AuthorizationCode,
+
+	/// Implicit Grant.
+	///
+	/// See: <https://datatracker.ietf.org/doc/html/rfc6749#section-4.2>
+	Implicit,
+
+	/// Refresh Token Grant.
+	///
+	/// See: <https://datatracker.ietf.org/doc/html/rfc6749#section-6>
+	RefreshToken,
+
+	/// Client Credentials Grant.
+	///
+	/// See: <https://datatracker.ietf.org/doc/html/rfc6749#section-4.4>
+	ClientCredentials,
+
+	/// Resource Owner Password Credentials Grant.
+	///
+	/// See: <https://datatracker.ietf.org/doc/html/rfc6749#section-4.3>
+	Password,
+
+	/// Device Authorization Grant.
+	///
+	/// See: <https://datatracker.ietf.org/doc/html/rfc8628#section-3.4>
+	DeviceCode,
+
+	/// Pre-Authorized Code Grant.
+	///
+	/// See: <https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0.html#name-token-request>
+	PreAuthorizedCode,
+
+	/// Token Exchange.
+	///
+	/// See: <https://datatracker.ietf.org/doc/html/rfc8693#section-2.1>
+	TokenExchange,
+
+	/// JWT Bearer Token Grant.
+	///
+	/// See: <https://datatracker.ietf.org/doc/html/rfc7523#section-2.1>
+	JwtBearer,
+
+	/// Client-Initiated Backchannel Authentication Grant.
+	///
+	/// See: <https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#rfc.section.10.1>
+	Ciba,
+
+	/// Any other grant type.
+	Extension(String),
+}
+
+impl GrantType {
+	/// Returns the string representation of this grant type.
+	pub fn as_str(&self) -> &str {
+		match self {
+			Self::AuthorizationCode => GRANT_TYPE_AUTHORIZATION_CODE,
+			Self::Implicit => GRANT_TYPE_IMPLICIT,
+			Self::RefreshToken => GRANT_TYPE_REFRESH_TOKEN,
+			Self::ClientCredentials => GRANT_TYPE_CLIENT_CREDENTIALS,
+			Self::Password => GRANT_TYPE_PASSWORD,
+			Self::DeviceCode => GRANT_TYPE_DEVICE_CODE,
+			Self::PreAuthorizedCode => GRANT_TYPE_PRE_AUTHORIZED_CODE,
+			Self::TokenExchange => GRANT_TYPE_TOKEN_EXCHANGE,
+			Self::JwtBearer => GRANT_TYPE_JWT_BEARER,
+			Self::Ciba => GRANT_TYPE_CIBA,
+			Self::Extension(s) => s,
+		}
+	}
+}
+
+impl FromStr for GrantType {
+	type Err = Infallible;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Ok(match s {
+			GRANT_TYPE_AUTHORIZATION_CODE => Self::AuthorizationCode,
+			GRANT_TYPE_IMPLICIT => Self::Implicit,
+			GRANT_TYPE_REFRESH_TOKEN => Self::RefreshToken,
+			GRANT_TYPE_CLIENT_CREDENTIALS => Self::ClientCredentials,
+			GRANT_TYPE_PASSWORD => Self::Password,
+			GRANT_TYPE_DEVICE_CODE => Self::DeviceCode,
+			GRANT_TYPE_PRE_AUTHORIZED_CODE => Self::PreAuthorizedCode,
+			GRANT_TYPE_TOKEN_EXCHANGE => Self::TokenExchange,
+			GRANT_TYPE_JWT_BEARER => Self::JwtBearer,
+			GRANT_TYPE_CIBA => Self::Ciba,
+			other => Self::Extension(other.to_owned()),
+		})
+	}
+}
+
+impl fmt::Display for GrantType {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.as_str().fmt(f)
+	}
+}
+
+impl Serialize for GrantType {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: serde::Serializer,
+	{
+		self.as_str().serialize(serializer)
+	}
+}
+
+impl<'de> Deserialize<'de> for GrantType {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: serde::Deserializer<'de>,
+	{
+		let s = String::deserialize(deserializer)?;
+		Ok(s.parse().unwrap_or_else(|e| match e {}))
+	}
+}
+
+#[cfg(feature = "schemars")]
+crate::types::json_schema_string!(GrantType);
+
+/// Token request of a specific grant type.
+pub trait TokenGrant {
+	/// Value of the `grant_type` parameter of the request.
+	const GRANT_TYPE: GrantType;
+}
+
+/// Serializes the `grant_type` parameter of a [`TokenGrant`] request, and
+/// checks it on deserialization.
+///
+/// Meant to be used on a `PhantomData<Self>` field, with
+/// `#[serde(with = "grant_type_param")]`.
+pub(crate) mod grant_type_param {
+	use super::*;
+
+	pub fn serialize<T, S>(_: &PhantomData<T>, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		T: TokenGrant,
+		S: serde::Serializer,
+	{
+		T::GRANT_TYPE.serialize(serializer)
+	}
+
+	pub fn deserialize<'de, T, D>(deserializer: D) -> Result<PhantomData<T>, D::Error>
+	where
+		T: TokenGrant,
+		D: serde::Deserializer<'de>,
+	{
+		let grant_type = GrantType::deserialize(deserializer)?;
+		if grant_type == T::GRANT_TYPE {
+			Ok(PhantomData)
+		} else {
+			Err(serde::de::Error::invalid_value(
+				serde::de::Unexpected::Str(grant_type.as_str()),
+				&T::GRANT_TYPE.as_str(),
+			))
+		}
+	}
+
+	#[cfg(feature = "schemars")]
+	pub fn json_schema<T>(_: &mut schemars::SchemaGenerator) -> schemars::Schema
+	where
+		T: TokenGrant,
+	{
+		schemars::json_schema!({
+			"type": "string",
+			"const": T::GRANT_TYPE.as_str()
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn grant_type_round_trip() {
+		for grant_type in [
+			GrantType::AuthorizationCode,
+			GrantType::Implicit,
+			GrantType::RefreshToken,
+			GrantType::ClientCredentials,
+			GrantType::Password,
+			GrantType::DeviceCode,
+			GrantType::PreAuthorizedCode,
+			GrantType::TokenExchange,
+			GrantType::JwtBearer,
+			GrantType::Ciba,
+			GrantType::Extension("urn:example:custom".to_owned()),
+		] {
+			assert_eq!(grant_type.as_str().parse(), Ok(grant_type.clone()));
+
+			let json = serde_json::to_value(&grant_type).unwrap();
+			assert_eq!(json, grant_type.as_str());
+			assert_eq!(
+				serde_json::from_value::<GrantType>(json).unwrap(),
+				grant_type
+			);
+		}
+	}
+
+	#[test]
+	fn grant_type_param() {
+		let request =
+			refresh::RefreshTokenRequest::new(None, "tGzv3JOkF0XG5Qx2TlKWIA".to_owned(), None);
+		let form = serde_html_form::to_string(&request).unwrap();
+		assert_eq!(
+			form,
+			"grant_type=refresh_token&refresh_token=tGzv3JOkF0XG5Qx2TlKWIA"
+		);
+		assert!(serde_html_form::from_str::<refresh::RefreshTokenRequest>(&form).is_ok());
+
+		assert!(
+			serde_html_form::from_str::<refresh::RefreshTokenRequest>(
+				"grant_type=authorization_code&refresh_token=tGzv3JOkF0XG5Qx2TlKWIA"
+			)
+			.is_err()
+		);
+	}
+
+	#[cfg(feature = "schemars")]
+	#[test]
+	fn grant_type_json_schema() {
+		let schema = schemars::schema_for!(refresh::RefreshTokenRequest);
+		assert_eq!(
+			schema.pointer("/properties/grant_type/const"),
+			Some(&serde_json::json!(GRANT_TYPE_REFRESH_TOKEN))
+		);
+	}
+}
@@ -0,0 +1,259 @@
+//! Polling of the token endpoint.
+//!
+//! Grants whose authorization is performed out of band, such as the Device
+//! Authorization Grant
+//! ([RFC 8628 Section 3.5](https://datatracker.ietf.org/doc/html/rfc8628#section-3.5))
+//! or Client-Initiated Backchannel Authentication in poll mode
+//! ([CIBA Section 7.3](https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#rfc.section.7.3)),
+//! have the client poll the token endpoint until the authorization
+//! completes. Both flows share the same rules:
+//! - the client waits for the interval given by the authorization server, or
+//!   5 seconds by default, between two requests;
+//! - an `authorization_pending` error means the client must poll again;
+//! - a `slow_down` error means the client must poll again, after increasing
+//!   its interval by at least 5 seconds;
+//! - the client stops polling when the authorization request expires.
+//!
+//! A [`PollingConfig`] tunes these rules, and a [`PollingState`] applies them
+//! to the responses of the token endpoint.
+use std::time::{Duration, SystemTime};
+
+use crate::{client::OAuth2ClientError, time::Clock};
+
+/// Default polling interval, when the authorization server does not specify
+/// one.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc8628#section-3.2>
+pub const DEFAULT_POLLING_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Minimum increase of the polling interval after a `slow_down` error.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc8628#section-3.5>
+pub const SLOW_DOWN_INCREMENT: Duration = Duration::from_secs(5);
+
+/// Error code returned while the authorization is pending.
+pub const AUTHORIZATION_PENDING: &str = "authorization_pending";
+
+/// Error code returned when the client polls too frequently.
+pub const SLOW_DOWN: &str = "slow_down";
+
+/// Error code returned when the authorization request expired.
+pub const EXPIRED_TOKEN: &str = "expired_token";
+
+/// Configuration of the polling of the token endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PollingConfig {
+	interval: Duration,
+	slow_down_multiplier: u32,
+	max_duration: Option<Duration>,
+}
+
+impl PollingConfig {
+	/// Creates a new configuration following the rules of the
+	/// specifications.
+	pub fn new() -> Self {
+		Self {
+			interval: DEFAULT_POLLING_INTERVAL,
+			slow_down_multiplier: 1,
+			max_duration: None,
+		}
+	}
+
+	/// Sets the interval used when the authorization server does not specify
+	/// one, or specifies a shorter one.
+	pub fn with_interval(self, interval: Duration) -> Self {
+		Self { interval, ..self }
+	}
+
+	/// Sets the factor applied to the interval after a `slow_down` error.
+	///
+	/// The interval is always increased by at least
+	/// [`SLOW_DOWN_INCREMENT`].
+	pub fn with_slow_down_multiplier(self, slow_down_multiplier: u32) -> Self {
+		Self {
+			slow_down_multiplier,
+			..self
+		}
+	}
+
+	/// Sets the maximum duration of the polling, shortening the lifetime of
+	/// the authorization request given by the server.
+	pub fn with_max_duration(self, max_duration: Option<Duration>) -> Self {
+		Self {
+			max_duration,
+			..self
+		}
+	}
+
+	/// Starts polling for an authorization request expiring in `expires_in`,
+	/// with the `interval` given by the authorization server, if any.
+	pub fn start(
+		&self,
+		interval: Option<Duration>,
+		expires_in: Duration,
+		clock: &impl Clock,
+	) -> PollingState {
+		let interval = interval.map_or(self.interval, |interval| interval.max(self.interval));
+		let duration = self
+			.max_duration
+			.map_or(expires_in, |max_duration| max_duration.min(expires_in));
+
+		PollingState {
+			interval,
+			slow_down_multiplier: self.slow_down_multiplier,
+			deadline: clock.deadline_in(duration),
+		}
+	}
+}
+
+impl Default for PollingConfig {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Error returned when polling stops without the authorization completing.
+#[derive(Debug, thiserror::Error)]
+pub enum PollingError {
+	/// The authorization request expired before being completed.
+	#[error("authorization request expired")]
+	Expired,
+
+	/// The token request failed, e.g. because the user denied the
+	/// authorization (`access_denied`).
+	#[error(transparent)]
+	Client(#[from] OAuth2ClientError),
+}
+
+/// State of the polling of the token endpoint.
+///
+/// Created with [`PollingConfig::start`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PollingState {
+	interval: Duration,
+	slow_down_multiplier: u32,
+	deadline: SystemTime,
+}
+
+impl PollingState {
+	/// Returns the time to wait before the next token request.
+	pub fn interval(&self) -> Duration {
+		self.interval
+	}
+
+	/// Returns the time after which polling stops.
+	pub fn deadline(&self) -> SystemTime {
+		self.deadline
+	}
+
+	/// Checks whether the polling deadline has passed.
+	pub fn is_expired(&self, clock: &impl Clock) -> bool {
+		clock.now() >= self.deadline
+	}
+
+	/// Increases the polling interval after a `slow_down` error.
+	pub fn slow_down(&mut self) {
+		let multiplied = self.interval * self.slow_down_multiplier;
+		self.interval = multiplied.max(self.interval + SLOW_DOWN_INCREMENT);
+	}
+
+	/// Handles an error returned by the token endpoint, returning the time to
+	/// wait before polling again.
+	///
+	/// Polling continues after `authorization_pending` and `slow_down`
+	/// errors, until the deadline. Any other error stops it.
+	pub fn on_error(
+		&mut self,
+		error: OAuth2ClientError,
+		clock: &impl Clock,
+	) -> Result<Duration, PollingError> {
+		match error.error_code() {
+			Some(AUTHORIZATION_PENDING) => (),
+			Some(SLOW_DOWN) => self.slow_down(),
+			Some(EXPIRED_TOKEN) => return Err(PollingError::Expired),
+			_ => return Err(PollingError::Client(error)),
+		}
+
+		if clock.now() + self.interval > self.deadline {
+			return Err(PollingError::Expired);
+		}
+
+		Ok(self.interval)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{server::ErrorResponse, time::MockClock};
+
+	use super::*;
+
+	fn error(code: &str) -> OAuth2ClientError {
+		OAuth2ClientError::ErrorResponse(ErrorResponse::new(code.to_owned(), None, None))
+	}
+
+	#[test]
+	fn intervals() {
+		let clock = MockClock::from_unix_timestamp(0);
+		let config = PollingConfig::new();
+
+		let state = config.start(None, Duration::from_secs(600), &clock);
+		assert_eq!(state.interval(), DEFAULT_POLLING_INTERVAL);
+
+		let state = config.start(
+			Some(Duration::from_secs(1)),
+			Duration::from_secs(600),
+			&clock,
+		);
+		assert_eq!(state.interval(), DEFAULT_POLLING_INTERVAL);
+
+		let mut state = config.start(
+			Some(Duration::from_secs(10)),
+			Duration::from_secs(600),
+			&clock,
+		);
+		assert_eq!(state.interval(), Duration::from_secs(10));
+		state.slow_down();
+		assert_eq!(state.interval(), Duration::from_secs(15));
+
+		let mut state = config.with_slow_down_multiplier(2).start(
+			Some(Duration::from_secs(10)),
+			Duration::from_secs(600),
+			&clock,
+		);
+		state.slow_down();
+		assert_eq!(state.interval(), Duration::from_secs(20));
+	}
+
+	#[test]
+	fn errors() {
+		let clock = MockClock::from_unix_timestamp(0);
+		let mut state = PollingConfig::new()
+			.with_max_duration(Some(Duration::from_secs(12)))
+			.start(None, Duration::from_secs(600), &clock);
+
+		assert_eq!(
+			state
+				.on_error(error("authorization_pending"), &clock)
+				.unwrap(),
+			Duration::from_secs(5)
+		);
+		assert_eq!(
+			state.on_error(error("slow_down"), &clock).unwrap(),
+			Duration::from_secs(10)
+		);
+
+		clock.advance(Duration::from_secs(10));
+		assert!(matches!(
+			state.on_error(error("authorization_pending"), &clock),
+			Err(PollingError::Expired)
+		));
+		assert!(matches!(
+			state.on_error(error("access_denied"), &clock),
+			Err(PollingError::Client(e)) if e.error_code() == Some("access_denied")
+		));
+
+		clock.advance(Duration::from_secs(2));
+		assert!(state.is_expired(&clock));
+	}
+}
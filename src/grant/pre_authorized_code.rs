@@ -1,18 +1,787 @@
 //! Pre-Authorized Code Grant.
 //!
 //! See: <https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0.html#name-credential-offer-parameters>
-use serde::{Deserialize, Serialize};
+//!
+//! Wallets receiving a [`CredentialOffer`] discover the metadata of the
+//! authorization server selected with
+//! [`CredentialOffer::discover_authorization_server`], then build the token
+//! request with [`TokenEndpoint::from_credential_offer`] (or
+//! [`PublicTokenEndpoint::from_credential_offer`] for unregistered wallets),
+//! prompting the end-user for a transaction code if the offer requires one.
+//!
+//! Offers of the Authorization Code Grant may instead carry an
+//! `issuer_state`, which wallets pass back in the authorization request with
+//! [`AddIssuerState::with_offered_issuer_state`]. Wallets starting the
+//! issuance themselves may identify their provider and the end-user with
+//! [`AddWalletParameters`].
+use std::marker::PhantomData;
+
+use iref::{Uri, UriBuf, UriRef, uri_ref};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use serde_with::skip_serializing_none;
 
 use crate::{
-	ClientIdBuf,
+	ClientIdBuf, EndpointUriPolicy, InsecureEndpointUri,
 	client::{OAuth2Client, OAuth2ClientError},
 	endpoints::{
-		HttpRequest, RequestBuilder, authorization::AnyAuthorizationEndpoint, token::TokenEndpoint,
+		HttpRequest, RedirectRequest, RequestBuilder,
+		authorization::AnyAuthorizationEndpoint,
+		token::{PublicTokenEndpoint, TokenEndpoint},
 	},
-	transport::{APPLICATION_JSON, HttpClient, WwwFormUrlEncoded, expect_content_type},
+	grant::{GrantType, TokenGrant, grant_type_param},
+	server::metadata::{AuthorizationServerMetadata, MissingEndpoint},
+	transport::{HttpClient, WwwFormUrlEncoded},
+	util::{Discoverable, NoExtension},
 };
 
+/// Credential Offer.
+///
+/// Only the parameters relevant to the authorization server are
+/// represented.
+///
+/// See: <https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0.html#name-credential-offer-parameters>
+#[skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CredentialOffer {
+	/// URL of the credential issuer.
+	#[cfg_attr(feature = "schemars", schemars(with = "String"))]
+	pub credential_issuer: UriBuf,
+
+	/// Identifiers of the offered credential configurations.
+	pub credential_configuration_ids: Vec<String>,
+
+	/// Grants the wallet may use to obtain an access token.
+	#[serde(default)]
+	pub grants: CredentialOfferGrants,
+}
+
+impl CredentialOffer {
+	/// Returns the Pre-Authorized Code Grant of the offer, if any.
+	pub fn pre_authorized_code(&self) -> Option<&PreAuthorizedCodeGrant> {
+		self.grants.pre_authorized_code.as_ref()
+	}
+
+	/// Returns the Authorization Code Grant of the offer, if any.
+	pub fn authorization_code(&self) -> Option<&AuthorizationCodeGrant> {
+		self.grants.authorization_code.as_ref()
+	}
+
+	/// Returns the `issuer_state` of the Authorization Code Grant of the
+	/// offer, if any.
+	pub fn issuer_state(&self) -> Option<&str> {
+		self.authorization_code()?.issuer_state.as_deref()
+	}
+
+	/// Selects the authorization server issuing tokens for the
+	/// pre-authorized code, among the ones listed by the credential issuer
+	/// metadata.
+	///
+	/// The offer must name the authorization server to use when the
+	/// credential issuer relies on several of them. The credential issuer is
+	/// its own authorization server when it does not list any.
+	///
+	/// See: <https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0.html#name-credential-offer-parameters>
+	pub fn select_authorization_server<'o, E>(
+		&'o self,
+		issuer: &'o CredentialIssuerMetadata<E>,
+	) -> Result<&'o Uri, AuthorizationServerSelectionError> {
+		let grant = self
+			.pre_authorized_code()
+			.ok_or(AuthorizationServerSelectionError::NoPreAuthorizedCode)?;
+
+		if issuer.credential_issuer != self.credential_issuer {
+			return Err(AuthorizationServerSelectionError::IssuerMismatch {
+				expected: self.credential_issuer.clone(),
+				found: issuer.credential_issuer.clone(),
+			});
+		}
+
+		let listed = issuer.authorization_servers.as_deref().unwrap_or_default();
+		match (&grant.authorization_server, listed) {
+			(Some(named), []) if *named == self.credential_issuer => Ok(named),
+			(Some(named), listed) if listed.contains(named) => Ok(named),
+			(Some(named), _) => Err(AuthorizationServerSelectionError::Unlisted(named.clone())),
+			(None, []) => Ok(&self.credential_issuer),
+			(None, [single]) => Ok(single),
+			(None, listed) => Err(AuthorizationServerSelectionError::Ambiguous(
+				listed.to_vec(),
+			)),
+		}
+	}
+
+	/// Selects the authorization server issuing tokens for the
+	/// pre-authorized code with
+	/// [`select_authorization_server`](Self::select_authorization_server),
+	/// and discovers its metadata.
+	pub async fn discover_authorization_server<P, E>(
+		&self,
+		http_client: &impl HttpClient,
+		issuer: &CredentialIssuerMetadata<E>,
+	) -> Result<AuthorizationServerMetadata<P>, AuthorizationServerSelectionError>
+	where
+		P: DeserializeOwned,
+	{
+		let uri = self.select_authorization_server(issuer)?;
+		Ok(AuthorizationServerMetadata::discover(http_client, uri).await?)
+	}
+}
+
+/// Credential Issuer Metadata.
+///
+/// Only the parameters relevant to the authorization server are
+/// represented, other parameters are kept in [`extra`](Self::extra).
+///
+/// See: <https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0.html#name-credential-issuer-metadata>
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CredentialIssuerMetadata<E = NoExtension> {
+	/// Identifier of the credential issuer.
+	#[cfg_attr(feature = "schemars", schemars(with = "String"))]
+	pub credential_issuer: UriBuf,
+
+	/// Issuer identifiers of the authorization servers trusted by the
+	/// credential issuer.
+	#[cfg_attr(feature = "schemars", schemars(with = "Option<Vec<String>>"))]
+	pub authorization_servers: Option<Vec<UriBuf>>,
+
+	/// URL of the credential endpoint.
+	#[cfg_attr(feature = "schemars", schemars(with = "String"))]
+	pub credential_endpoint: UriBuf,
+
+	/// URL of the nonce endpoint, if the credential issuer requires nonces
+	/// in proofs of possession.
+	#[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
+	pub nonce_endpoint: Option<UriBuf>,
+
+	/// Other parameters.
+	#[serde(flatten)]
+	pub extra: E,
+}
+
+impl<E> CredentialIssuerMetadata<E> {
+	/// Fails if an endpoint URI advertised by the credential issuer is not
+	/// accepted by the given policy.
+	pub fn check_endpoint_uris(
+		&self,
+		policy: EndpointUriPolicy,
+	) -> Result<(), InsecureEndpointUri> {
+		policy.check(&self.credential_endpoint)?;
+		self.nonce_endpoint
+			.iter()
+			.try_for_each(|uri| policy.check(uri))
+	}
+}
+
+impl<E> Discoverable for CredentialIssuerMetadata<E>
+where
+	E: DeserializeOwned,
+{
+	const WELL_KNOWN_URI_REF: &UriRef = uri_ref!(".well-known/openid-credential-issuer");
+
+	fn validate(&self, base_url: &Uri) -> Result<(), OAuth2ClientError> {
+		if self.credential_issuer == base_url {
+			Ok(())
+		} else {
+			Err(OAuth2ClientError::response(
+				"invalid credential issuer metadata identifier",
+			))
+		}
+	}
+
+	fn check_endpoint_uris(&self, policy: EndpointUriPolicy) -> Result<(), InsecureEndpointUri> {
+		CredentialIssuerMetadata::check_endpoint_uris(self, policy)
+	}
+}
+
+/// Error returned when the authorization server of a [`CredentialOffer`]
+/// cannot be selected.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum AuthorizationServerSelectionError {
+	/// The offer does not include a Pre-Authorized Code Grant.
+	#[error("credential offer has no pre-authorized code")]
+	NoPreAuthorizedCode,
+
+	/// The metadata is not the one of the credential issuer of the offer.
+	#[error("expected metadata of `{expected}`, found `{found}`")]
+	IssuerMismatch {
+		/// Credential issuer of the offer.
+		expected: UriBuf,
+
+		/// Credential issuer of the metadata.
+		found: UriBuf,
+	},
+
+	/// The credential issuer relies on several authorization servers, and
+	/// the offer does not name the one to use.
+	#[error("ambiguous authorization server, one of {} must be named", .0.len())]
+	Ambiguous(Vec<UriBuf>),
+
+	/// The authorization server named by the offer is not trusted by the
+	/// credential issuer.
+	#[error("authorization server `{0}` is not listed by the credential issuer")]
+	Unlisted(UriBuf),
+
+	/// The metadata of the authorization server could not be discovered.
+	#[error(transparent)]
+	Discovery(#[from] OAuth2ClientError),
+}
+
+/// Grants of a [`CredentialOffer`].
+#[skip_serializing_none]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CredentialOfferGrants {
+	/// Authorization Code Grant parameters.
+	pub authorization_code: Option<AuthorizationCodeGrant>,
+
+	/// Pre-Authorized Code Grant parameters.
+	#[serde(rename = "urn:ietf:params:oauth:grant-type:pre-authorized_code")]
+	pub pre_authorized_code: Option<PreAuthorizedCodeGrant>,
+}
+
+/// Authorization Code Grant parameters of a [`CredentialOffer`].
+#[skip_serializing_none]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct AuthorizationCodeGrant {
+	/// Value binding the authorization request to the offer.
+	pub issuer_state: Option<String>,
+
+	/// Issuer identifier of the authorization server to use.
+	#[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
+	pub authorization_server: Option<UriBuf>,
+}
+
+/// Extension wrapper that attaches the `issuer_state` of a
+/// [`CredentialOffer`] to an authorization request.
+///
+/// See: <https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0.html#name-additional-request-paramete>
+#[skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct WithIssuerState<T> {
+	/// Value binding the authorization request to the offer.
+	pub issuer_state: Option<String>,
+
+	/// The inner request being extended.
+	#[serde(flatten)]
+	pub value: T,
+}
+
+impl<T> WithIssuerState<T> {
+	/// Creates a new [`WithIssuerState`] wrapping the given request.
+	pub fn new(value: T, issuer_state: Option<String>) -> Self {
+		Self {
+			issuer_state,
+			value,
+		}
+	}
+
+	/// Wraps the given request body of the inner request, borrowing the
+	/// issuer state.
+	fn body<B>(&self, value: B) -> WithIssuerStateBody<'_, B> {
+		WithIssuerStateBody {
+			issuer_state: self.issuer_state.as_deref(),
+			value,
+		}
+	}
+}
+
+/// Request body of a [`WithIssuerState`] request, borrowing its issuer
+/// state.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct WithIssuerStateBody<'b, T> {
+	/// Value binding the authorization request to the offer.
+	pub issuer_state: Option<&'b str>,
+
+	/// The inner request body.
+	#[serde(flatten)]
+	pub value: T,
+}
+
+/// Implements [`Deref`](std::ops::Deref), [`Borrow`](std::borrow::Borrow),
+/// [`RedirectRequest`] and [`HttpRequest`] for an authorization request
+/// wrapper with a single parameter field, serialized as the given request
+/// body type.
+///
+/// The wrapper must provide a `body` method borrowing its parameter.
+macro_rules! authorization_parameter_wrapper {
+	($ty:ident, $body:ident) => {
+		impl<T> std::ops::Deref for $ty<T> {
+			type Target = T;
+
+			fn deref(&self) -> &Self::Target {
+				&self.value
+			}
+		}
+
+		impl<T> std::borrow::Borrow<T> for $ty<T> {
+			fn borrow(&self) -> &T {
+				&self.value
+			}
+		}
+
+		impl<T> RedirectRequest for $ty<T>
+		where
+			T: RedirectRequest,
+		{
+			type RequestBody<'b>
+				= $body<'b, T::RequestBody<'b>>
+			where
+				Self: 'b;
+
+			fn build_query(&self) -> Self::RequestBody<'_> {
+				self.body(self.value.build_query())
+			}
+		}
+
+		impl<E, T> HttpRequest<E> for $ty<T>
+		where
+			T: HttpRequest<E>,
+		{
+			type ContentType = T::ContentType;
+			type RequestBody<'b>
+				= $body<'b, T::RequestBody<'b>>
+			where
+				Self: 'b;
+			type Response = T::Response;
+			type ResponsePayload = T::ResponsePayload;
+
+			async fn build_request(
+				&self,
+				endpoint: &E,
+				http_client: &impl HttpClient,
+			) -> Result<http::Request<Self::RequestBody<'_>>, OAuth2ClientError> {
+				self.value
+					.build_request(endpoint, http_client)
+					.await
+					.map(|request| request.map(|value| self.body(value)))
+			}
+
+			fn decode_response(
+				&self,
+				endpoint: &E,
+				response: http::Response<Vec<u8>>,
+			) -> Result<http::Response<Self::ResponsePayload>, OAuth2ClientError> {
+				self.value.decode_response(endpoint, response)
+			}
+
+			async fn process_response(
+				&self,
+				endpoint: &E,
+				http_client: &impl HttpClient,
+				response: http::Response<Self::ResponsePayload>,
+			) -> Result<Self::Response, OAuth2ClientError> {
+				self.value
+					.process_response(endpoint, http_client, response)
+					.await
+			}
+		}
+	};
+}
+
+authorization_parameter_wrapper!(WithIssuerState, WithIssuerStateBody);
+
+/// Extension wrapper that attaches the `wallet_issuer` parameter to an
+/// authorization request.
+///
+/// See: <https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0.html#name-additional-request-paramete>
+///
+/// Identifies the wallet provider, so that the authorization server can
+/// retrieve its metadata, e.g. to request the presentation of a credential
+/// held by the wallet during the authorization.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct WithWalletIssuer<T> {
+	/// Identifier of the wallet provider.
+	pub wallet_issuer: String,
+
+	/// The inner request being extended.
+	#[serde(flatten)]
+	pub value: T,
+}
+
+impl<T> WithWalletIssuer<T> {
+	/// Creates a new [`WithWalletIssuer`] wrapping the given request.
+	pub fn new(value: T, wallet_issuer: String) -> Self {
+		Self {
+			wallet_issuer,
+			value,
+		}
+	}
+
+	/// Wraps the given request body of the inner request, borrowing the
+	/// wallet provider identifier.
+	fn body<B>(&self, value: B) -> WithWalletIssuerBody<'_, B> {
+		WithWalletIssuerBody {
+			wallet_issuer: &self.wallet_issuer,
+			value,
+		}
+	}
+}
+
+/// Request body of a [`WithWalletIssuer`] request, borrowing its wallet
+/// provider identifier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct WithWalletIssuerBody<'b, T> {
+	/// Identifier of the wallet provider.
+	pub wallet_issuer: &'b str,
+
+	/// The inner request body.
+	#[serde(flatten)]
+	pub value: T,
+}
+
+authorization_parameter_wrapper!(WithWalletIssuer, WithWalletIssuerBody);
+
+/// Extension wrapper that attaches the `user_hint` parameter to an
+/// authorization request.
+///
+/// See: <https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0.html#name-additional-request-paramete>
+///
+/// Opaque value the wallet received from the credential issuer, identifying
+/// the end-user to the authorization server.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct WithUserHint<T> {
+	/// Hint about the end-user.
+	pub user_hint: String,
+
+	/// The inner request being extended.
+	#[serde(flatten)]
+	pub value: T,
+}
+
+impl<T> WithUserHint<T> {
+	/// Creates a new [`WithUserHint`] wrapping the given request.
+	pub fn new(value: T, user_hint: String) -> Self {
+		Self { user_hint, value }
+	}
+
+	/// Wraps the given request body of the inner request, borrowing the
+	/// user hint.
+	fn body<B>(&self, value: B) -> WithUserHintBody<'_, B> {
+		WithUserHintBody {
+			user_hint: &self.user_hint,
+			value,
+		}
+	}
+}
+
+/// Request body of a [`WithUserHint`] request, borrowing its user hint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct WithUserHintBody<'b, T> {
+	/// Hint about the end-user.
+	pub user_hint: &'b str,
+
+	/// The inner request body.
+	#[serde(flatten)]
+	pub value: T,
+}
+
+authorization_parameter_wrapper!(WithUserHint, WithUserHintBody);
+
+/// Extension trait for attaching the `issuer_state` of a credential offer to
+/// a [`RequestBuilder`].
+pub trait AddIssuerState: Sized {
+	/// The resulting type after adding the issuer state.
+	type Output;
+
+	/// Wraps the current request in a [`WithIssuerState`] with the given
+	/// issuer state.
+	fn with_issuer_state(self, issuer_state: Option<String>) -> Self::Output;
+
+	/// Wraps the current request in a [`WithIssuerState`] with the
+	/// `issuer_state` of the given offer, if any.
+	fn with_offered_issuer_state(self, offer: &CredentialOffer) -> Self::Output {
+		self.with_issuer_state(offer.issuer_state().map(ToOwned::to_owned))
+	}
+}
+
+impl<E, T> AddIssuerState for RequestBuilder<E, T> {
+	type Output = RequestBuilder<E, WithIssuerState<T>>;
+
+	fn with_issuer_state(self, issuer_state: Option<String>) -> Self::Output {
+		self.map(|value| WithIssuerState::new(value, issuer_state))
+	}
+}
+
+/// Extension trait for attaching the wallet-initiated issuance parameters
+/// of OpenID4VCI to a [`RequestBuilder`].
+pub trait AddWalletParameters: Sized {
+	/// The resulting type after adding `wallet_issuer`.
+	type WithWalletIssuer;
+
+	/// The resulting type after adding `user_hint`.
+	type WithUserHint;
+
+	/// Wraps the current request in a [`WithWalletIssuer`] with the given
+	/// wallet provider identifier.
+	fn with_wallet_issuer(self, wallet_issuer: String) -> Self::WithWalletIssuer;
+
+	/// Wraps the current request in a [`WithUserHint`] with the given hint
+	/// about the end-user.
+	fn with_user_hint(self, user_hint: String) -> Self::WithUserHint;
+}
+
+impl<E, T> AddWalletParameters for RequestBuilder<E, T> {
+	type WithWalletIssuer = RequestBuilder<E, WithWalletIssuer<T>>;
+	type WithUserHint = RequestBuilder<E, WithUserHint<T>>;
+
+	fn with_wallet_issuer(self, wallet_issuer: String) -> Self::WithWalletIssuer {
+		self.map(|value| WithWalletIssuer::new(value, wallet_issuer))
+	}
+
+	fn with_user_hint(self, user_hint: String) -> Self::WithUserHint {
+		self.map(|value| WithUserHint::new(value, user_hint))
+	}
+}
+
+/// Pre-Authorized Code Grant parameters of a [`CredentialOffer`].
+#[skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct PreAuthorizedCodeGrant {
+	/// The pre-authorized code.
+	#[serde(rename = "pre-authorized_code")]
+	pub pre_authorized_code: String,
+
+	/// Transaction code expected by the authorization server, if any.
+	pub tx_code: Option<TxCode>,
+
+	/// Issuer identifier of the authorization server to use.
+	#[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
+	pub authorization_server: Option<UriBuf>,
+}
+
+/// Description of the transaction code the end-user must provide, sent to
+/// them out of band.
+///
+/// See: <https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0.html#name-credential-offer-parameters>
+#[skip_serializing_none]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct TxCode {
+	/// Characters the transaction code is made of.
+	#[serde(default)]
+	pub input_mode: TxCodeInputMode,
+
+	/// Length of the transaction code, if known.
+	pub length: Option<usize>,
+
+	/// Guidance for the end-user, to display when prompting for the code.
+	pub description: Option<String>,
+}
+
+impl TxCode {
+	/// Checks that the given transaction code matches this description.
+	pub fn check(&self, tx_code: &str) -> Result<(), TxCodeError> {
+		if let Some(length) = self.length {
+			let found = tx_code.chars().count();
+			if found != length {
+				return Err(TxCodeError::InvalidLength {
+					expected: length,
+					found,
+				});
+			}
+		}
+
+		if self.input_mode == TxCodeInputMode::Numeric
+			&& !tx_code.bytes().all(|b| b.is_ascii_digit())
+		{
+			return Err(TxCodeError::NotNumeric);
+		}
+
+		Ok(())
+	}
+}
+
+/// Input mode of a [`TxCode`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum TxCodeInputMode {
+	/// Digits only.
+	#[default]
+	Numeric,
+
+	/// Any characters.
+	Text,
+}
+
+/// Error returned when the transaction code provided by the end-user does
+/// not match the [`TxCode`] of the offer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum TxCodeError {
+	/// The offer requires a transaction code.
+	#[error("missing transaction code")]
+	Missing,
+
+	/// The offer does not expect a transaction code.
+	#[error("unexpected transaction code")]
+	Unexpected,
+
+	/// The transaction code does not have the expected length.
+	#[error("transaction code must be {expected} characters long, found {found}")]
+	InvalidLength {
+		/// Expected length.
+		expected: usize,
+
+		/// Length of the provided code.
+		found: usize,
+	},
+
+	/// The transaction code must only contain digits.
+	#[error("transaction code must be numeric")]
+	NotNumeric,
+}
+
+/// Error returned when a token request cannot be built from a
+/// [`CredentialOffer`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CredentialOfferError {
+	/// The offer does not include a Pre-Authorized Code Grant.
+	#[error("credential offer has no pre-authorized code")]
+	NoPreAuthorizedCode,
+
+	/// The metadata is not the one of the authorization server designated by
+	/// the offer.
+	#[error("expected metadata of `{expected}`, found `{found}`")]
+	IssuerMismatch {
+		/// Authorization server designated by the offer.
+		expected: UriBuf,
+
+		/// Issuer of the metadata.
+		found: UriBuf,
+	},
+
+	/// The authorization server has no token endpoint.
+	#[error(transparent)]
+	MissingEndpoint(#[from] MissingEndpoint),
+}
+
+/// Token request for a pre-authorized code of a [`CredentialOffer`], ready
+/// to be sent once the end-user provided the transaction code, if any.
+pub struct PreAuthorizedCodeOffer<E> {
+	/// The token endpoint.
+	pub endpoint: E,
+
+	/// The token request, without transaction code.
+	pub request: PreAuthorizedCodeTokenRequest,
+
+	/// Transaction code to prompt the end-user for, if required.
+	pub tx_code: Option<TxCode>,
+}
+
+impl<E> PreAuthorizedCodeOffer<E> {
+	/// Returns the transaction code to prompt the end-user for, if required.
+	pub fn tx_code(&self) -> Option<&TxCode> {
+		self.tx_code.as_ref()
+	}
+
+	/// Completes the token request with the transaction code provided by the
+	/// end-user, if any.
+	///
+	/// Fails if the transaction code does not match the requirements of the
+	/// offer.
+	pub fn exchange(
+		self,
+		tx_code: Option<String>,
+	) -> Result<RequestBuilder<E, PreAuthorizedCodeTokenRequest>, TxCodeError> {
+		match (&self.tx_code, &tx_code) {
+			(Some(expected), Some(tx_code)) => expected.check(tx_code)?,
+			(Some(_), None) => return Err(TxCodeError::Missing),
+			(None, Some(_)) => return Err(TxCodeError::Unexpected),
+			(None, None) => (),
+		}
+
+		Ok(RequestBuilder::new(
+			self.endpoint,
+			PreAuthorizedCodeTokenRequest {
+				tx_code,
+				..self.request
+			},
+		))
+	}
+}
+
+/// Returns the pre-authorized code grant of `offer`, checking that
+/// `metadata` is the one of the authorization server it names, if any.
+fn offered_grant<'o, P>(
+	offer: &'o CredentialOffer,
+	metadata: &AuthorizationServerMetadata<P>,
+) -> Result<&'o PreAuthorizedCodeGrant, CredentialOfferError> {
+	let grant = offer
+		.pre_authorized_code()
+		.ok_or(CredentialOfferError::NoPreAuthorizedCode)?;
+
+	if let Some(expected) = &grant.authorization_server
+		&& metadata.issuer != **expected
+	{
+		return Err(CredentialOfferError::IssuerMismatch {
+			expected: expected.clone(),
+			found: metadata.issuer.as_uri().to_owned(),
+		});
+	}
+
+	Ok(grant)
+}
+
+impl<'a, C> TokenEndpoint<'a, C>
+where
+	C: OAuth2Client,
+{
+	/// Prepares the token request for the pre-authorized code of a credential
+	/// offer, using the metadata of the authorization server selected by
+	/// [`CredentialOffer::select_authorization_server`].
+	///
+	/// The returned [`PreAuthorizedCodeOffer`] describes the transaction code
+	/// to prompt the end-user for, if any.
+	pub fn from_credential_offer<P>(
+		client: &'a C,
+		offer: &CredentialOffer,
+		metadata: &'a AuthorizationServerMetadata<P>,
+	) -> Result<PreAuthorizedCodeOffer<Self>, CredentialOfferError> {
+		let grant = offered_grant(offer, metadata)?;
+		Ok(PreAuthorizedCodeOffer {
+			endpoint: Self::from_metadata(client, metadata)?,
+			request: PreAuthorizedCodeTokenRequest::new(
+				Some(client.client_id().to_owned()),
+				grant.pre_authorized_code.clone(),
+				None,
+			),
+			tx_code: grant.tx_code.clone(),
+		})
+	}
+}
+
+impl<'a, R> PublicTokenEndpoint<'a, R> {
+	/// Prepares the anonymous token request for the pre-authorized code of a
+	/// credential offer, using the metadata of the authorization server
+	/// selected by [`CredentialOffer::select_authorization_server`].
+	///
+	/// The returned [`PreAuthorizedCodeOffer`] describes the transaction code
+	/// to prompt the end-user for, if any.
+	pub fn from_credential_offer<P>(
+		offer: &CredentialOffer,
+		metadata: &'a AuthorizationServerMetadata<P>,
+	) -> Result<PreAuthorizedCodeOffer<Self>, CredentialOfferError> {
+		let grant = offered_grant(offer, metadata)?;
+		Ok(PreAuthorizedCodeOffer {
+			endpoint: Self::from_metadata(metadata)?,
+			request: PreAuthorizedCodeTokenRequest::new(
+				None,
+				grant.pre_authorized_code.clone(),
+				None,
+			),
+			tx_code: grant.tx_code.clone(),
+		})
+	}
+}
+
 impl<'a, C> TokenEndpoint<'a, C>
 where
 	C: OAuth2Client,
@@ -26,13 +795,29 @@ where
 		pre_authorized_code: String,
 		tx_code: Option<String>,
 	) -> RequestBuilder<Self, PreAuthorizedCodeTokenRequest> {
+		let client_id = self.client.client_id().to_owned();
 		RequestBuilder::new(
 			self,
-			PreAuthorizedCodeTokenRequest::new(
-				Some(self.client.client_id().to_owned()),
-				pre_authorized_code,
-				tx_code,
-			),
+			PreAuthorizedCodeTokenRequest::new(Some(client_id), pre_authorized_code, tx_code),
+		)
+	}
+}
+
+impl<'a, R> PublicTokenEndpoint<'a, R> {
+	/// Begins an anonymous Pre-Authorized Code token exchange request.
+	///
+	/// The request does not include any `client_id`, as expected by
+	/// credential issuers accepting unregistered wallets.
+	///
+	/// See: <https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0.html#name-token-request>
+	pub fn exchange_pre_authorized_code(
+		self,
+		pre_authorized_code: String,
+		tx_code: Option<String>,
+	) -> RequestBuilder<Self, PreAuthorizedCodeTokenRequest> {
+		RequestBuilder::new(
+			self,
+			PreAuthorizedCodeTokenRequest::new(None, pre_authorized_code, tx_code),
 		)
 	}
 }
@@ -70,11 +855,15 @@ where
 /// See: <https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0.html#name-token-request>
 #[skip_serializing_none]
 #[derive(Debug, Serialize, Deserialize)]
-#[serde(
-	tag = "grant_type",
-	rename = "urn:ietf:params:oauth:grant-type:pre-authorized_code"
-)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct PreAuthorizedCodeTokenRequest {
+	#[serde(with = "grant_type_param")]
+	#[cfg_attr(
+		feature = "schemars",
+		schemars(schema_with = "grant_type_param::json_schema::<PreAuthorizedCodeTokenRequest>")
+	)]
+	grant_type: PhantomData<Self>,
+
 	/// Client identifier, if the client is not authenticating by other
 	/// means.
 	pub client_id: Option<ClientIdBuf>,
@@ -88,6 +877,10 @@ pub struct PreAuthorizedCodeTokenRequest {
 	pub tx_code: Option<String>,
 }
 
+impl TokenGrant for PreAuthorizedCodeTokenRequest {
+	const GRANT_TYPE: GrantType = GrantType::PreAuthorizedCode;
+}
+
 impl PreAuthorizedCodeTokenRequest {
 	/// Creates a new pre-authorized code token request.
 	pub fn new(
@@ -96,6 +889,7 @@ impl PreAuthorizedCodeTokenRequest {
 		tx_code: Option<String>,
 	) -> Self {
 		Self {
+			grant_type: PhantomData,
 			client_id,
 			pre_authorized_code,
 			tx_code,
@@ -137,26 +931,296 @@ where
 
 	fn decode_response(
 		&self,
-		_endpoint: &TokenEndpoint<'a, C>,
+		endpoint: &TokenEndpoint<'a, C>,
 		response: http::Response<Vec<u8>>,
 	) -> Result<http::Response<Self::ResponsePayload>, OAuth2ClientError> {
-		if response.status() != http::StatusCode::OK {
-			return Err(OAuth2ClientError::server(response.status()));
-		}
+		endpoint.decode_token_response(response)
+	}
 
-		expect_content_type(response.headers(), &APPLICATION_JSON)?;
+	async fn process_response(
+		&self,
+		_endpoint: &TokenEndpoint<'a, C>,
+		_http_client: &impl HttpClient,
+		response: http::Response<Self::ResponsePayload>,
+	) -> Result<Self::Response, OAuth2ClientError> {
+		Ok(response.into_body())
+	}
+}
 
-		let body = serde_json::from_slice(response.body()).map_err(OAuth2ClientError::response)?;
+impl<'a, R> HttpRequest<PublicTokenEndpoint<'a, R>> for PreAuthorizedCodeTokenRequest
+where
+	R: DeserializeOwned,
+{
+	type ContentType = WwwFormUrlEncoded;
+	type RequestBody<'b>
+		= &'b Self
+	where
+		Self: 'b;
+	type Response = R;
+	type ResponsePayload = R;
 
-		Ok(response.map(|_| body))
+	async fn build_request(
+		&self,
+		endpoint: &PublicTokenEndpoint<'a, R>,
+		_http_client: &impl HttpClient,
+	) -> Result<http::Request<Self::RequestBody<'_>>, OAuth2ClientError> {
+		Ok(http::Request::builder()
+			.method(http::Method::POST)
+			.uri(endpoint.uri.as_str())
+			.body(self)
+			.unwrap())
+	}
+
+	fn decode_response(
+		&self,
+		endpoint: &PublicTokenEndpoint<'a, R>,
+		response: http::Response<Vec<u8>>,
+	) -> Result<http::Response<Self::ResponsePayload>, OAuth2ClientError> {
+		endpoint.decode_token_response(response)
 	}
 
 	async fn process_response(
 		&self,
-		_endpoint: &TokenEndpoint<'a, C>,
+		_endpoint: &PublicTokenEndpoint<'a, R>,
 		_http_client: &impl HttpClient,
 		response: http::Response<Self::ResponsePayload>,
 	) -> Result<Self::Response, OAuth2ClientError> {
 		Ok(response.into_body())
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use iref::uri;
+
+	use crate::{
+		IssuerBuf,
+		endpoints::{authorization::AuthorizationEndpoint, token::TokenResponse},
+		transport::APPLICATION_JSON,
+	};
+
+	use super::*;
+
+	/// Credential issuer accepting anonymous pre-authorized code requests.
+	struct Issuer;
+
+	impl HttpClient for Issuer {
+		async fn send(
+			&self,
+			request: http::Request<Vec<u8>>,
+		) -> Result<http::Response<Vec<u8>>, OAuth2ClientError> {
+			assert_eq!(
+				request.body(),
+				b"grant_type=urn%3Aietf%3Aparams%3Aoauth%3Agrant-type%3Apre-authorized_code&pre-authorized_code=SplxlOBeZQQYbYS6WxSbIA&tx_code=493536"
+			);
+
+			Ok(http::Response::builder()
+				.header(http::header::CONTENT_TYPE, &APPLICATION_JSON)
+				.body(
+					br#"{"access_token":"2YotnFZFEjr1zCsicMWpAA","token_type":"Bearer"}"#.to_vec(),
+				)
+				.unwrap())
+		}
+	}
+
+	#[tokio::test]
+	async fn anonymous_token_request() {
+		let response: TokenResponse =
+			PublicTokenEndpoint::new(uri!("https://issuer.example.com/token"))
+				.exchange_pre_authorized_code(
+					"SplxlOBeZQQYbYS6WxSbIA".to_owned(),
+					Some("493536".to_owned()),
+				)
+				.send(&Issuer)
+				.await
+				.unwrap();
+
+		assert_eq!(response.access_token.as_str(), "2YotnFZFEjr1zCsicMWpAA");
+	}
+
+	#[tokio::test]
+	async fn credential_offer() {
+		let offer: CredentialOffer = serde_json::from_value(serde_json::json!({
+			"credential_issuer": "https://issuer.example.com",
+			"credential_configuration_ids": ["UniversityDegreeCredential"],
+			"grants": {
+				"urn:ietf:params:oauth:grant-type:pre-authorized_code": {
+					"pre-authorized_code": "SplxlOBeZQQYbYS6WxSbIA",
+					"tx_code": {
+						"length": 6,
+						"description": "Please provide the one-time code sent via e-mail"
+					}
+				}
+			}
+		}))
+		.unwrap();
+
+		let metadata: AuthorizationServerMetadata = AuthorizationServerMetadata::new(
+			IssuerBuf::new(offer.credential_issuer.to_string()).unwrap(),
+		)
+		.with_token_endpoint(uri!("https://issuer.example.com/token").to_owned());
+		let offered =
+			|| PublicTokenEndpoint::<TokenResponse>::from_credential_offer(&offer, &metadata);
+
+		let tx_code = offered().unwrap().tx_code;
+		assert_eq!(
+			tx_code.as_ref().unwrap().input_mode,
+			TxCodeInputMode::Numeric
+		);
+		assert_eq!(
+			offered().unwrap().exchange(None).err(),
+			Some(TxCodeError::Missing)
+		);
+		assert_eq!(
+			offered().unwrap().exchange(Some("49353".to_owned())).err(),
+			Some(TxCodeError::InvalidLength {
+				expected: 6,
+				found: 5
+			})
+		);
+		assert_eq!(
+			offered().unwrap().exchange(Some("49353a".to_owned())).err(),
+			Some(TxCodeError::NotNumeric)
+		);
+
+		let response: TokenResponse = offered()
+			.unwrap()
+			.exchange(Some("493536".to_owned()))
+			.unwrap()
+			.send(&Issuer)
+			.await
+			.unwrap();
+		assert_eq!(response.access_token.as_str(), "2YotnFZFEjr1zCsicMWpAA");
+
+		let mut offer = offer;
+		offer
+			.grants
+			.pre_authorized_code
+			.as_mut()
+			.unwrap()
+			.authorization_server = Some(uri!("https://as.example.com").to_owned());
+		assert!(matches!(
+			PublicTokenEndpoint::<TokenResponse>::from_credential_offer(&offer, &metadata),
+			Err(CredentialOfferError::IssuerMismatch { .. })
+		));
+	}
+
+	#[test]
+	fn authorization_server_selection() {
+		let issuer = uri!("https://issuer.example.com");
+		let as1 = uri!("https://as1.example.com").to_owned();
+		let as2 = uri!("https://as2.example.com").to_owned();
+
+		let offer = |authorization_server: Option<&UriBuf>| CredentialOffer {
+			credential_issuer: issuer.to_owned(),
+			credential_configuration_ids: Vec::new(),
+			grants: CredentialOfferGrants {
+				authorization_code: None,
+				pre_authorized_code: Some(PreAuthorizedCodeGrant {
+					pre_authorized_code: "SplxlOBeZQQYbYS6WxSbIA".to_owned(),
+					tx_code: None,
+					authorization_server: authorization_server.cloned(),
+				}),
+			},
+		};
+		let metadata = |authorization_servers: Option<Vec<UriBuf>>| CredentialIssuerMetadata {
+			credential_issuer: issuer.to_owned(),
+			authorization_servers,
+			credential_endpoint: uri!("https://issuer.example.com/credential").to_owned(),
+			nonce_endpoint: None,
+			extra: NoExtension {},
+		};
+
+		// The credential issuer is its own authorization server.
+		let single = metadata(None);
+		assert_eq!(
+			offer(None).select_authorization_server(&single).unwrap(),
+			issuer
+		);
+
+		let one = metadata(Some(vec![as1.clone()]));
+		assert_eq!(offer(None).select_authorization_server(&one).unwrap(), &as1);
+
+		let both = metadata(Some(vec![as1.clone(), as2.clone()]));
+		assert_eq!(
+			offer(Some(&as2))
+				.select_authorization_server(&both)
+				.unwrap(),
+			&as2
+		);
+		assert!(matches!(
+			offer(None).select_authorization_server(&both),
+			Err(AuthorizationServerSelectionError::Ambiguous(listed)) if listed.len() == 2
+		));
+		assert!(matches!(
+			offer(Some(&as2)).select_authorization_server(&one),
+			Err(AuthorizationServerSelectionError::Unlisted(_))
+		));
+	}
+
+	#[test]
+	fn issuer_state() {
+		struct Wallet;
+
+		impl OAuth2Client for Wallet {
+			type TokenResponse = TokenResponse;
+
+			fn client_id(&self) -> &crate::ClientId {
+				crate::client_id!("s6BhdRkqt3")
+			}
+		}
+
+		let offer: CredentialOffer = serde_json::from_value(serde_json::json!({
+			"credential_issuer": "https://issuer.example.com",
+			"credential_configuration_ids": ["UniversityDegreeCredential"],
+			"grants": {
+				"authorization_code": {
+					"issuer_state": "eyJhbGciOiJSU0Et...FYUaBy"
+				}
+			}
+		}))
+		.unwrap();
+		assert_eq!(offer.issuer_state(), Some("eyJhbGciOiJSU0Et...FYUaBy"));
+		assert!(offer.pre_authorized_code().is_none());
+
+		let uri = AuthorizationEndpoint::new(&Wallet, uri!("https://issuer.example.com/authorize"))
+			.authorize_url(None, Some(crate::scope!("openid").to_owned()))
+			.with_offered_issuer_state(&offer)
+			.into_redirect_uri()
+			.unwrap();
+		assert_eq!(
+			uri,
+			"https://issuer.example.com/authorize?issuer_state=eyJhbGciOiJSU0Et...FYUaBy&response_type=code&client_id=s6BhdRkqt3&scope=openid"
+		);
+
+		let decoded: WithIssuerState<
+			crate::grant::authorization_code::AuthorizationCodeAuthorizationRequest,
+		> = serde_html_form::from_str(uri.query().unwrap().as_str()).unwrap();
+		assert_eq!(
+			decoded.issuer_state.as_deref(),
+			Some("eyJhbGciOiJSU0Et...FYUaBy")
+		);
+	}
+
+	#[test]
+	fn wallet_parameters() {
+		let request = WithUserHint::new(
+			WithWalletIssuer::new(
+				crate::util::NoExtension {},
+				"https://wallet-provider.example.org".to_owned(),
+			),
+			"urn:example:user:38cf4a".to_owned(),
+		);
+
+		let query = serde_html_form::to_string(&request).unwrap();
+		assert_eq!(
+			query,
+			"user_hint=urn%3Aexample%3Auser%3A38cf4a&wallet_issuer=https%3A%2F%2Fwallet-provider.example.org"
+		);
+
+		let decoded: WithUserHint<WithWalletIssuer<crate::util::NoExtension>> =
+			serde_html_form::from_str(&query).unwrap();
+		assert_eq!(decoded, request);
+		assert_eq!(decoded.wallet_issuer, "https://wallet-provider.example.org");
+	}
+}
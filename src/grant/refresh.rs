@@ -1,6 +1,127 @@
-#[derive(Debug, Deserialize, Serialize)]
-#[serde(tag = "grant_type", rename = "refresh_token")]
+//! Refresh Token Grant.
+//!
+//! See: <https://datatracker.ietf.org/doc/html/rfc6749#section-6>
+use std::marker::PhantomData;
+
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+use crate::{
+	ClientIdBuf, IntoScope, ScopeBuf,
+	client::{OAuth2Client, OAuth2ClientError},
+	endpoints::{HttpRequest, RequestBuilder, token::TokenEndpoint},
+	grant::{GrantType, TokenGrant, grant_type_param},
+	transport::{HttpClient, WwwFormUrlEncoded},
+};
+
+impl<'a, C> TokenEndpoint<'a, C>
+where
+	C: OAuth2Client,
+{
+	/// Begins a Refresh Token Request, obtaining a new access token.
+	///
+	/// The requested `scope` must not include any scope not originally
+	/// granted by the resource owner. If omitted, it is treated as equal to
+	/// the originally granted scope.
+	///
+	/// Returns a [`RequestBuilder`] that can be further extended before
+	/// being sent.
+	pub fn refresh(
+		self,
+		refresh_token: String,
+		scope: impl IntoScope,
+	) -> RequestBuilder<Self, RefreshTokenRequest> {
+		let client_id = self.client.client_id().to_owned();
+		RequestBuilder::new(
+			self,
+			RefreshTokenRequest::new(Some(client_id), refresh_token, scope),
+		)
+	}
+}
+
+/// Refresh Token Request.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc6749#section-6>
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RefreshTokenRequest {
-	pub client_id: Option<String>,
+	#[serde(with = "grant_type_param")]
+	#[cfg_attr(
+		feature = "schemars",
+		schemars(schema_with = "grant_type_param::json_schema::<RefreshTokenRequest>")
+	)]
+	grant_type: PhantomData<Self>,
+
+	/// Client identifier, if the client is not authenticating by other
+	/// means.
+	pub client_id: Option<ClientIdBuf>,
+
+	/// The refresh token issued to the client.
 	pub refresh_token: String,
+
+	/// The scope of the access request.
+	pub scope: Option<ScopeBuf>,
+}
+
+impl TokenGrant for RefreshTokenRequest {
+	const GRANT_TYPE: GrantType = GrantType::RefreshToken;
+}
+
+impl RefreshTokenRequest {
+	/// Creates a new refresh token request.
+	pub fn new(
+		client_id: Option<ClientIdBuf>,
+		refresh_token: String,
+		scope: impl IntoScope,
+	) -> Self {
+		Self {
+			grant_type: PhantomData,
+			client_id,
+			refresh_token,
+			scope: scope.into_scope(),
+		}
+	}
+}
+
+impl<'a, C> HttpRequest<TokenEndpoint<'a, C>> for RefreshTokenRequest
+where
+	C: OAuth2Client,
+{
+	type ContentType = WwwFormUrlEncoded;
+	type RequestBody<'b>
+		= &'b Self
+	where
+		Self: 'b;
+	type Response = C::TokenResponse;
+	type ResponsePayload = C::TokenResponse;
+
+	async fn build_request(
+		&self,
+		endpoint: &TokenEndpoint<'a, C>,
+		_http_client: &impl HttpClient,
+	) -> Result<http::Request<Self::RequestBody<'_>>, OAuth2ClientError> {
+		Ok(http::Request::builder()
+			.method(http::Method::POST)
+			.uri(endpoint.uri.as_str())
+			.body(self)
+			.unwrap())
+	}
+
+	fn decode_response(
+		&self,
+		endpoint: &TokenEndpoint<'a, C>,
+		response: http::Response<Vec<u8>>,
+	) -> Result<http::Response<Self::ResponsePayload>, OAuth2ClientError> {
+		endpoint.decode_token_response(response)
+	}
+
+	async fn process_response(
+		&self,
+		_endpoint: &TokenEndpoint<'a, C>,
+		_http_client: &impl HttpClient,
+		response: http::Response<Self::ResponsePayload>,
+	) -> Result<Self::Response, OAuth2ClientError> {
+		Ok(response.into_body())
+	}
 }
@@ -2,7 +2,10 @@
 //! servers, following the specifications defined in [RFC 6749][rfc6749] and
 //! related extensions:
 //!
+//! - [RFC 7009][rfc7009] — Token Revocation
 //! - [RFC 7636][rfc7636] — Proof Key for Code Exchange (PKCE)
+//! - [RFC 7662][rfc7662] — Token Introspection
+//! - [RFC 8628][rfc8628] — Device Authorization Grant
 //! - [RFC 9126][rfc9126] — Pushed Authorization Requests (PAR)
 //! - [RFC 9396][rfc9396] — Rich Authorization Requests (RAR)
 //! - [OpenID4VCI][oid4vci] — Pre-Authorized Code Grant
@@ -11,7 +14,10 @@
 //! numerous extensions to the OAuth 2.0 framework.
 //!
 //! [rfc6749]: https://datatracker.ietf.org/doc/html/rfc6749
+//! [rfc7009]: https://datatracker.ietf.org/doc/html/rfc7009
 //! [rfc7636]: https://datatracker.ietf.org/doc/html/rfc7636
+//! [rfc7662]: https://datatracker.ietf.org/doc/html/rfc7662
+//! [rfc8628]: https://datatracker.ietf.org/doc/html/rfc8628
 //! [rfc9126]: https://www.rfc-editor.org/rfc/rfc9126.html
 //! [rfc9396]: https://www.rfc-editor.org/rfc/rfc9396.html
 //! [oid4vci]: https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0.html
@@ -19,9 +25,11 @@
 //! # Client usage
 //!
 //! The following shows how to set up a client, build an authorization URL
-//! with PKCE, and exchange the resulting code for a token.
+//! with PKCE, and exchange the resulting code for a token. It requires the
+//! `pkce` feature.
 //!
-//! ```rust,no_run
+#![cfg_attr(feature = "pkce", doc = "```rust,no_run")]
+#![cfg_attr(not(feature = "pkce"), doc = "```rust,ignore")]
 //! use open_auth2::{
 //!     ClientId, StateBuf,
 //!     AddState,
@@ -57,7 +65,7 @@
 //!         .authorize_url(None, None)
 //!         .with_state(Some(state))
 //!         .with_pkce_challenge(pkce_challenge)
-//!         .into_redirect_uri();
+//!         .into_redirect_uri()?;
 //!
 //!     println!("Open in browser: {authorize_url}");
 //!
@@ -77,24 +85,112 @@
 //!
 //! # Modules
 //!
-//! - [`client`](crate::client) — OAuth 2.0 client trait and types.
-//! - [`endpoints`](crate::endpoints) — Endpoint abstractions (authorization, token, PAR).
-//! - [`ext`](crate::ext) — Built-in protocol extensions (PKCE, RAR).
-//! - [`grant`](crate::grant) — Grant type implementations (authorization code,
-//!   pre-authorized code).
-//! - [`server`](crate::server) — OAuth 2.0 server trait and types.
-//! - [`transport`](crate::transport) — HTTP transport layer and content type encoding.
-//! - [`util`](crate::util) — URI query string utilities.
+//! - [`client`] — OAuth 2.0 client trait, types and token refresh.
+//! - `compliance` — Golden wire formats for regression testing. Requires the
+//!   `test-util` feature.
+//! - `config` — Client configuration loaded from TOML, JSON or environment
+//!   variables. Requires the `config` feature.
+//! - `conformance` — Conformance self-test of authorization servers, also
+//!   available as the `conformance` binary. Requires the `conformance`
+//!   feature.
+//! - [`endpoints`] — Endpoint abstractions (authorization, token, PAR,
+//!   device authorization, introspection, revocation).
+//! - `examples` — Helpers for command-line clients (loopback redirection
+//!   endpoint). Requires the `examples` feature.
+//! - [`ext`] — Built-in protocol extensions (PKCE, RAR,
+//!   OpenID Connect ID token hashes).
+//! - [`grant`] — Grant type implementations (authorization code,
+//!   device code, pre-authorized code, refresh token).
+//! - `roundtrip` — Wire-format compatibility checks between client and
+//!   server types. Requires the `test-util` feature.
+//! - [`server`] — OAuth 2.0 server trait and types.
+//! - `strategies` — Property-based testing strategies. Requires the
+//!   `proptest` feature.
+//! - [`time`] — Clock abstraction and clock skew tolerance.
+//! - [`transport`] — HTTP transport layer and content type encoding.
+//! - [`util`] — URI query string utilities.
+//!
+//! # Features
+//!
+//! Protocol extensions can be disabled to reduce binary size and the number
+//! of dependencies. They are all enabled by default.
+//!
+//! - `pkce` — Proof Key for Code Exchange (`ext::pkce`). Depends on `sha2`
+//!   and enables `rand`.
+//! - `rar` — Rich Authorization Requests (`ext::rar`).
+//! - `par` — Pushed Authorization Requests
+//!   (`endpoints::pushed_authorization`, and `server::par` with `rand`).
+//! - `oidc` — OpenID Connect ID token hashes and authentication request
+//!   parameters (`ext::oidc`), RP-Initiated Logout
+//!   (`endpoints::end_session`) and Back-Channel Logout
+//!   (`ext::backchannel_logout`). Depends on `sha2` and `base64`.
+//! - `device` — Device Authorization Grant
+//!   (`endpoints::device_authorization`, `grant::device_code`).
+//! - `http-sig` — HTTP Message Signatures (`ext::http_sig`). Depends on
+//!   `sha2` and `base64`.
+//! - `client-secret` — Client authentication with a secret resolved at
+//!   send time (`client::SecretProvider`). Depends on `base64`.
+//! - `dpop` — DPoP nonces shared across endpoints (`ext::dpop`).
+//! - `client-attestation` — Attestation-Based Client Authentication, for
+//!   wallets pushing authorization requests (`ext::client_attestation`).
+//! - `jar` — JWT-Secured Authorization Requests, signed and optionally
+//!   encrypted request objects (`ext::jar`). Depends on `base64`.
+//! - `rand` — Random generation of states, codes and access tokens
+//!   (`StateBuf::new_random`, etc.). Depends on `rand` and `base64`.
+//!
+//! The `url` feature, disabled by default, adds conversions from and to
+//! [`url::Url`](https://docs.rs/url) (`util::UrlExt`, `util::UriExt`),
+//! `from_url` endpoint constructors and
+//! `RequestBuilder::into_redirect_url`.
+//!
+//! The `system-browser` feature, disabled by default, adds
+//! `client::SystemBrowser`, opening authorization requests of native apps in
+//! the default browser of the user with the [`open`](https://docs.rs/open)
+//! crate.
+//!
+//! The `tower-sessions` feature, disabled by default, adds
+//! `server::AuthorizationSession`, keeping the pending authorization
+//! request and the authenticated user of the consent and login pages in
+//! [`tower-sessions`](https://docs.rs/tower-sessions) sessions. Enables
+//! `axum`.
+//!
+//! The `compat-oauth2` feature, disabled by default, adds conversions between
+//! the authorization code, state, PKCE and token response types of the
+//! [`oauth2`](https://docs.rs/oauth2) crate and their equivalents in this
+//! crate (see `util::InvalidOAuth2Value`), to migrate from it incrementally.
+//!
+//! The `schemars` feature, disabled by default, implements
+//! [`schemars::JsonSchema`](https://docs.rs/schemars) for the request,
+//! response and metadata types, to generate the OpenAPI documentation of
+//! servers built with this crate (e.g. with `utoipa` or `aide`).
+//!
+//! The `openid-credential` feature, disabled by default, adds the
+//! `openid_credential` authorization details of OpenID4VCI, with presets for
+//! the mDL and EUDI PID credentials (`ext::openid_credential`). Enables
+//! `rar`.
 #[cfg(feature = "reqwest")]
 pub use reqwest;
 
 pub use http;
 
 pub mod client;
+#[cfg(feature = "test-util")]
+pub mod compliance;
+#[cfg(feature = "config")]
+pub mod config;
+#[cfg(feature = "conformance")]
+pub mod conformance;
 pub mod endpoints;
+#[cfg(feature = "examples")]
+pub mod examples;
 pub mod ext;
 pub mod grant;
+#[cfg(feature = "test-util")]
+pub mod roundtrip;
 pub mod server;
+#[cfg(feature = "proptest")]
+pub mod strategies;
+pub mod time;
 pub mod transport;
 mod types;
 pub mod util;
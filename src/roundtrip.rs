@@ -0,0 +1,255 @@
+//! Wire-format compatibility checks between client and server types.
+//!
+//! Requests are serialized with the type used by the client, and
+//! deserialized with the type used by the server, while responses go the
+//! other way. The received value is then serialized again and compared to
+//! the sent wire format, so that parameters renamed on one side only, or
+//! swallowed by a flattened extension colliding with another field, are
+//! reported even when deserialization succeeds.
+//!
+//! The receiving type must therefore implement [`Serialize`] as well as
+//! [`Deserialize`](serde::Deserialize), serializing the parameters it
+//! understands.
+//!
+//! ```
+//! use open_auth2::{compliance, grant::device_code::DeviceCodeTokenRequest, roundtrip};
+//!
+//! roundtrip::assert_form::<_, DeviceCodeTokenRequest>(&compliance::device_code_token_request());
+//! ```
+//!
+//! Requires the `test-util` feature.
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::compliance::form_eq;
+
+/// Wire-format incompatibility between a sent and received type.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RoundtripError {
+	/// The sent value could not be serialized.
+	#[error("unable to serialize the sent value: {0}")]
+	Serialize(String),
+
+	/// The received type could not be deserialized from the wire format.
+	#[error("unable to deserialize `{sent}`: {error}")]
+	Deserialize { sent: String, error: String },
+
+	/// The received value does not serialize back to the sent wire format.
+	#[error("sent `{sent}`, but received `{received}`")]
+	Mismatch { sent: String, received: String },
+}
+
+/// Sends `value` as an `application/x-www-form-urlencoded` body (or query)
+/// and receives it as a `U`.
+///
+/// Parameter order is not significant.
+pub fn form<T, U>(value: &T) -> Result<U, RoundtripError>
+where
+	T: Serialize,
+	U: Serialize + DeserializeOwned,
+{
+	let sent =
+		serde_html_form::to_string(value).map_err(|e| RoundtripError::Serialize(e.to_string()))?;
+
+	let received: U =
+		serde_html_form::from_str(&sent).map_err(|e| RoundtripError::Deserialize {
+			sent: sent.clone(),
+			error: e.to_string(),
+		})?;
+
+	let reserialized = serde_html_form::to_string(&received)
+		.map_err(|e| RoundtripError::Serialize(e.to_string()))?;
+
+	if form_eq(&sent, &reserialized) {
+		Ok(received)
+	} else {
+		Err(RoundtripError::Mismatch {
+			sent,
+			received: reserialized,
+		})
+	}
+}
+
+/// Sends `value` as a JSON body and receives it as a `U`.
+pub fn json<T, U>(value: &T) -> Result<U, RoundtripError>
+where
+	T: Serialize,
+	U: Serialize + DeserializeOwned,
+{
+	let sent = serde_json::to_value(value).map_err(|e| RoundtripError::Serialize(e.to_string()))?;
+
+	let received: U =
+		serde_json::from_value(sent.clone()).map_err(|e| RoundtripError::Deserialize {
+			sent: sent.to_string(),
+			error: e.to_string(),
+		})?;
+
+	let reserialized =
+		serde_json::to_value(&received).map_err(|e| RoundtripError::Serialize(e.to_string()))?;
+
+	if sent == reserialized {
+		Ok(received)
+	} else {
+		Err(RoundtripError::Mismatch {
+			sent: sent.to_string(),
+			received: reserialized.to_string(),
+		})
+	}
+}
+
+/// Asserts that `value` is received as a `U` when sent as a form.
+///
+/// # Panics
+///
+/// Panics if [`form`] fails.
+#[track_caller]
+pub fn assert_form<T, U>(value: &T) -> U
+where
+	T: Serialize,
+	U: Serialize + DeserializeOwned,
+{
+	form(value).unwrap_or_else(|e| panic!("form roundtrip failed: {e}"))
+}
+
+/// Asserts that `value` is received as a `U` when sent as JSON.
+///
+/// # Panics
+///
+/// Panics if [`json`] fails.
+#[track_caller]
+pub fn assert_json<T, U>(value: &T) -> U
+where
+	T: Serialize,
+	U: Serialize + DeserializeOwned,
+{
+	json(value).unwrap_or_else(|e| panic!("JSON roundtrip failed: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+	use serde::Deserialize;
+
+	use crate::{
+		Stateful,
+		compliance::*,
+		endpoints::{
+			device_authorization::{DeviceAuthorizationRequest, DeviceAuthorizationResponse},
+			introspection::IntrospectionRequest,
+			pushed_authorization::{PushedAuthorizationRequest, PushedAuthorizationResponse},
+			revocation::RevocationRequest,
+			token::TokenResponse,
+		},
+		grant::{
+			authorization_code::{
+				AuthorizationCodeAuthorizationRequest, AuthorizationCodeTokenRequest,
+			},
+			device_code::DeviceCodeTokenRequest,
+			pre_authorized_code::PreAuthorizedCodeTokenRequest,
+		},
+		server::ErrorResponse,
+		util::NoExtension,
+	};
+
+	use super::*;
+
+	#[test]
+	fn requests() {
+		assert_form::<_, Stateful<AuthorizationCodeAuthorizationRequest>>(&authorization_request());
+		assert_form::<_, Stateful<AuthorizationCodeAuthorizationRequest>>(&par_request());
+		assert_form::<_, PushedAuthorizationRequest<NoExtension>>(&pushed_authorization_request());
+		assert_form::<_, AuthorizationCodeTokenRequest>(&authorization_code_token_request());
+		assert_form::<_, DeviceCodeTokenRequest>(&device_code_token_request());
+		assert_form::<_, PreAuthorizedCodeTokenRequest>(&pre_authorized_code_token_request());
+		assert_form::<_, DeviceAuthorizationRequest>(&device_authorization_request());
+		assert_form::<_, IntrospectionRequest>(&introspection_request());
+		assert_form::<_, RevocationRequest>(&revocation_request());
+	}
+
+	#[test]
+	fn responses() {
+		assert_json::<_, PushedAuthorizationResponse>(&par_response());
+		assert_json::<_, TokenResponse>(&token_response());
+		assert_json::<_, DeviceAuthorizationResponse>(&device_authorization_response());
+		assert_json::<_, ErrorResponse>(&error_response());
+		assert_form::<_, Stateful<ErrorResponse>>(&authorization_error_response());
+	}
+
+	#[cfg(feature = "pkce")]
+	#[test]
+	fn pkce_extension() {
+		use crate::ext::pkce::{
+			PkceCodeChallengeAndMethod, PkceCodeVerifierBuf, WithPkceChallenge, WithPkceVerifier,
+		};
+
+		#[derive(Serialize, Deserialize)]
+		struct ServerAuthorizationRequest {
+			#[serde(flatten)]
+			request: Stateful<AuthorizationCodeAuthorizationRequest>,
+
+			#[serde(flatten)]
+			pkce: PkceCodeChallengeAndMethod,
+		}
+
+		#[derive(Serialize, Deserialize)]
+		struct ServerTokenRequest {
+			#[serde(flatten)]
+			request: AuthorizationCodeTokenRequest,
+
+			code_verifier: PkceCodeVerifierBuf,
+		}
+
+		let (challenge, verifier) = PkceCodeChallengeAndMethod::new_random_sha256();
+		let request = assert_form::<_, ServerAuthorizationRequest>(&WithPkceChallenge::new(
+			authorization_request(),
+			challenge.clone(),
+		));
+		assert_eq!(request.pkce, challenge);
+
+		let request = assert_form::<_, ServerTokenRequest>(&WithPkceVerifier::new(
+			authorization_code_token_request(),
+			&verifier,
+		));
+		assert_eq!(request.code_verifier, verifier);
+
+		// The server ignores the PKCE parameters.
+		assert!(matches!(
+			form::<_, AuthorizationCodeTokenRequest>(&WithPkceVerifier::new(
+				authorization_code_token_request(),
+				&verifier,
+			)),
+			Err(RoundtripError::Mismatch { .. })
+		));
+	}
+
+	#[test]
+	fn renamed_parameter() {
+		#[derive(Serialize, Deserialize)]
+		struct ServerTokenRequest {
+			grant_type: String,
+			pre_authorized_code: Option<String>,
+			tx_code: Option<String>,
+		}
+
+		assert!(matches!(
+			form::<_, ServerTokenRequest>(&pre_authorized_code_token_request()),
+			Err(RoundtripError::Mismatch { .. })
+		));
+	}
+
+	#[test]
+	fn flattened_extension_collision() {
+		#[derive(Serialize, Deserialize)]
+		struct Extension {
+			token_type: String,
+		}
+
+		let response = TokenResponse::new(
+			token_response().access_token,
+			"Bearer".to_owned(),
+			Extension {
+				token_type: "N_A".to_owned(),
+			},
+		);
+
+		assert!(json::<_, TokenResponse<String, Extension>>(&response).is_err());
+	}
+}
@@ -0,0 +1,641 @@
+use std::{future::Future, sync::Arc};
+
+use axum::{
+	extract::{FromRequest, RawForm, Request},
+	response::{IntoResponse, Response},
+};
+use iref::UriBuf;
+use serde::{Deserialize, de::DeserializeOwned};
+
+use crate::{
+	ClientIdBuf, CodeBuf, RedirectUriPolicy, StateBuf, Stateful,
+	client::ClientType,
+	ext::pkce::PkcePolicy,
+	server::{
+		ErrorResponse, RedirectResponder,
+		validation::{
+			describe_violations, validate_authorization_request, validate_pkce_policy,
+			validate_redirect_uri, validate_redirect_uri_policy,
+		},
+	},
+};
+
+use super::{AuthorizeError, ErrorCode, IntoErrorResponse, OAuth2Server, authorization_redirect};
+
+/// Extractor of authorization requests.
+///
+/// Parameters are read from the query of `GET` requests, or from the
+/// `application/x-www-form-urlencoded` body of `POST` requests, as allowed by
+/// [RFC 6749 Section 3.1](https://datatracker.ietf.org/doc/html/rfc6749#section-3.1).
+///
+/// The request is checked with [`validate_authorization_request`] and
+/// against [`OAuth2Server::PKCE_POLICY`] before being deserialized. Failures
+/// are rejected with an `invalid_request` [`AuthorizeError`] naming the
+/// offending parameters, which redirects the user-agent to the client only if
+/// its redirection URI could be validated against
+/// [`OAuth2Server::redirect_uris`].
+///
+/// Requests referencing a pushed authorization request with `request_uri`
+/// ([RFC 9126 Section 4](https://www.rfc-editor.org/rfc/rfc9126.html#section-4))
+/// are only checked for their `client_id` and `request_uri`. The server
+/// must resolve the pushed request itself, e.g. by taking it out of the
+/// `server::par::PushedRequestStore` it was pushed to.
+pub struct AuthorizeRequest<T>(pub Stateful<T>);
+
+impl<S, T> FromRequest<Arc<S>> for AuthorizeRequest<T>
+where
+	S: OAuth2Server,
+	T: DeserializeOwned,
+{
+	type Rejection = AuthorizeError;
+
+	async fn from_request(request: Request, server: &Arc<S>) -> Result<Self, AuthorizeError> {
+		extract_authorization_request(request, server.as_ref())
+			.await
+			.map(|(_, request)| Self(request))
+	}
+}
+
+/// Client of an authorization request, validated by the authorization
+/// endpoint before calling [`OAuth2Server::authorize`] or
+/// [`OAuth2TenantServer::authorize`](super::OAuth2TenantServer::authorize).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorizationContext {
+	/// Identifier of the client.
+	pub client_id: ClientIdBuf,
+
+	/// Type of the client, as returned by [`OAuth2Server::client_type`].
+	pub client_type: ClientType,
+
+	/// Redirection URI of the request, or the first registered one if the
+	/// request has none, validated against
+	/// [`OAuth2Server::redirect_uris`].
+	///
+	/// This is `None` if the client is unknown, or if the redirection URI
+	/// does not match any registered one, in which case the user-agent must
+	/// not be redirected to it.
+	pub redirect_uri: Option<UriBuf>,
+}
+
+/// Interruption of the authorization flow by one of the authorization hooks
+/// of [`OAuth2Server`].
+#[derive(Debug)]
+pub enum AuthorizeInterrupt {
+	/// Responds to the user-agent directly, e.g. with a login or consent
+	/// page, or a redirection to it.
+	Respond(Response),
+
+	/// Denies the authorization request with the given error, reported to
+	/// the client by redirecting the user-agent if possible.
+	Deny(ErrorResponse<ErrorCode>),
+}
+
+impl AuthorizeInterrupt {
+	/// Interrupts the flow with the given response.
+	pub fn respond(response: impl IntoResponse) -> Self {
+		Self::Respond(response.into_response())
+	}
+
+	/// Denies the authorization request with the given error.
+	pub fn deny(error: impl IntoErrorResponse) -> Self {
+		Self::Deny(error.into_error_response())
+	}
+
+	/// Converts this interruption into a response, reporting errors to the
+	/// client through the given responder.
+	pub(super) fn into_response(self, responder: RedirectResponder) -> Response {
+		match self {
+			Self::Respond(response) => response,
+			Self::Deny(error) => AuthorizeError::from_error(
+				responder.redirect_uri().to_owned(),
+				responder.state().map(ToOwned::to_owned),
+				error,
+			)
+			.into_response(),
+		}
+	}
+}
+
+/// Authorization hooks, either those of an [`OAuth2Server`] or those of a
+/// tenant of an [`OAuth2TenantServer`](super::OAuth2TenantServer).
+pub(super) trait AuthorizationHooks: Send + Sync {
+	type Request: Send + Sync;
+
+	fn authenticate_user(
+		&self,
+		context: &AuthorizationContext,
+		request: &Stateful<Self::Request>,
+	) -> impl Send + Future<Output = Result<String, AuthorizeInterrupt>>;
+
+	fn check_consent(
+		&self,
+		subject: &str,
+		context: &AuthorizationContext,
+		request: &Stateful<Self::Request>,
+	) -> impl Send + Future<Output = Result<(), AuthorizeInterrupt>>;
+
+	fn issue_code(
+		&self,
+		subject: &str,
+		context: &AuthorizationContext,
+		request: &Stateful<Self::Request>,
+	) -> impl Send + Future<Output = Result<CodeBuf, AuthorizeInterrupt>>;
+}
+
+/// Authorization hooks of an [`OAuth2Server`].
+pub(super) struct ServerHooks<'a, S>(pub &'a S);
+
+impl<S: OAuth2Server> AuthorizationHooks for ServerHooks<'_, S> {
+	type Request = S::AuthorizationRequest;
+
+	fn authenticate_user(
+		&self,
+		context: &AuthorizationContext,
+		request: &Stateful<Self::Request>,
+	) -> impl Send + Future<Output = Result<String, AuthorizeInterrupt>> {
+		self.0.authenticate_user(context, request)
+	}
+
+	fn check_consent(
+		&self,
+		subject: &str,
+		context: &AuthorizationContext,
+		request: &Stateful<Self::Request>,
+	) -> impl Send + Future<Output = Result<(), AuthorizeInterrupt>> {
+		self.0.check_consent(subject, context, request)
+	}
+
+	fn issue_code(
+		&self,
+		subject: &str,
+		context: &AuthorizationContext,
+		request: &Stateful<Self::Request>,
+	) -> impl Send + Future<Output = Result<CodeBuf, AuthorizeInterrupt>> {
+		self.0.issue_code(subject, context, request)
+	}
+}
+
+/// Answers an authorization request by calling the `authenticate_user`,
+/// `check_consent` and `issue_code` hooks in this order, and redirecting the
+/// user-agent to the client with the issued code or the error denying the
+/// request.
+///
+/// If the redirection URI of the request could not be validated, the hooks
+/// are not called and the request is rejected directly.
+pub(super) async fn authorize_with_hooks<H: AuthorizationHooks>(
+	hooks: H,
+	context: AuthorizationContext,
+	request: Stateful<H::Request>,
+) -> Response {
+	let Some(redirect_uri) = context.redirect_uri.clone() else {
+		return AuthorizeError::without_redirect(ErrorCode::InvalidRequest)
+			.with_description("unregistered client or redirection URI")
+			.into_response();
+	};
+
+	let code = async {
+		let subject = hooks.authenticate_user(&context, &request).await?;
+		hooks.check_consent(&subject, &context, &request).await?;
+		hooks.issue_code(&subject, &context, &request).await
+	}
+	.await;
+
+	let (responder, _) = RedirectResponder::new(request, redirect_uri);
+	match code {
+		Ok(code) => authorization_redirect(&responder.grant(code)),
+		Err(interrupt) => interrupt.into_response(responder),
+	}
+}
+
+/// Reads, validates and deserializes an authorization request, along with
+/// its [`AuthorizationContext`].
+pub(super) async fn extract_authorization_request<S, T>(
+	request: Request,
+	server: &S,
+) -> Result<(AuthorizationContext, Stateful<T>), AuthorizeError>
+where
+	S: OAuth2Server,
+	T: DeserializeOwned,
+{
+	let parameters = authorization_parameters(request, server).await?;
+
+	let client_id = authorization_client_id(&parameters);
+	let (client_type, redirect_uris, redirect_uri_policy) = match &client_id {
+		Some(client_id) => (
+			server.client_type(client_id).await,
+			server.redirect_uris(client_id).await,
+			server.redirect_uri_policy(client_id).await,
+		),
+		None => (ClientType::Public, None, RedirectUriPolicy::Generic),
+	};
+
+	check_authorization_context(
+		&parameters,
+		client_id,
+		client_type,
+		redirect_uris.as_deref(),
+		redirect_uri_policy,
+		S::PKCE_POLICY,
+	)
+}
+
+/// Validates and deserializes the raw parameters of an authorization
+/// request, from the client information of `client_id`, into the request
+/// and its [`AuthorizationContext`].
+pub(super) fn check_authorization_context<T>(
+	parameters: &str,
+	client_id: Option<ClientIdBuf>,
+	client_type: ClientType,
+	redirect_uris: Option<&[UriBuf]>,
+	redirect_uri_policy: RedirectUriPolicy,
+	pkce_policy: PkcePolicy,
+) -> Result<(AuthorizationContext, Stateful<T>), AuthorizeError>
+where
+	T: DeserializeOwned,
+{
+	let (request, redirect_uri) = check_authorization_request(
+		parameters,
+		redirect_uris,
+		redirect_uri_policy,
+		client_type,
+		pkce_policy,
+	)?;
+
+	let client_id = client_id.ok_or_else(|| {
+		AuthorizeError::without_redirect(ErrorCode::InvalidRequest)
+			.with_description("invalid `client_id` parameter")
+	})?;
+
+	Ok((
+		AuthorizationContext {
+			client_id,
+			client_type,
+			redirect_uri,
+		},
+		request,
+	))
+}
+
+/// Reads the raw parameters of an authorization request, from the query or
+/// form body.
+pub(super) async fn authorization_parameters<S: Send + Sync>(
+	request: Request,
+	state: &S,
+) -> Result<String, AuthorizeError> {
+	let RawForm(form) = RawForm::from_request(request, state).await.map_err(|e| {
+		AuthorizeError::without_redirect(ErrorCode::InvalidRequest).with_description(e.body_text())
+	})?;
+
+	String::from_utf8(form.into()).map_err(|_| {
+		AuthorizeError::without_redirect(ErrorCode::InvalidRequest)
+			.with_description("parameters are not valid UTF-8")
+	})
+}
+
+/// Validates and deserializes the raw parameters of an authorization
+/// request, returning it along with its validated redirection URI.
+///
+/// Errors are reported by redirecting the user-agent only if the client is
+/// known, with its `redirect_uris`, and the `redirect_uri` parameter matches
+/// one of them.
+pub(super) fn check_authorization_request<T>(
+	parameters: &str,
+	redirect_uris: Option<&[UriBuf]>,
+	redirect_uri_policy: RedirectUriPolicy,
+	client_type: ClientType,
+	policy: PkcePolicy,
+) -> Result<(Stateful<T>, Option<UriBuf>), AuthorizeError>
+where
+	T: DeserializeOwned,
+{
+	let mut violations = validate_authorization_request(parameters);
+	violations.extend(validate_redirect_uri_policy(
+		parameters,
+		redirect_uri_policy,
+	));
+
+	let redirect_uri = redirect_uris.and_then(|registered| {
+		let trusted = validate_redirect_uri(parameters, registered).is_none()
+			&& !violations
+				.iter()
+				.any(|v| v.parameter == "client_id" || v.parameter == "redirect_uri");
+
+		trusted.then(|| match parameter(parameters, "redirect_uri") {
+			// UNWRAP SAFETY: the redirect URI was validated.
+			Some(uri) => UriBuf::new(uri.into_bytes()).unwrap(),
+			None => registered[0].clone(),
+		})
+	});
+
+	let error = |description: String| {
+		let error = match &redirect_uri {
+			Some(uri) => AuthorizeError::new(
+				uri.clone(),
+				parameter(parameters, "state").and_then(|s| StateBuf::new(s.to_owned()).ok()),
+				ErrorCode::InvalidRequest,
+			),
+			None => AuthorizeError::without_redirect(ErrorCode::InvalidRequest),
+		};
+
+		error.with_description(description)
+	};
+
+	violations.extend(validate_pkce_policy(parameters, client_type, policy));
+	if !violations.is_empty() {
+		return Err(error(describe_violations(&violations)));
+	}
+
+	let request = serde_html_form::from_str(parameters).map_err(|e| error(e.to_string()))?;
+	Ok((request, redirect_uri))
+}
+
+/// Returns the `client_id` parameter of an authorization request.
+pub(super) fn authorization_client_id(parameters: &str) -> Option<ClientIdBuf> {
+	#[derive(Deserialize)]
+	struct Parameters {
+		client_id: ClientIdBuf,
+	}
+
+	serde_html_form::from_str::<Parameters>(parameters)
+		.ok()
+		.map(|p| p.client_id)
+}
+
+/// Returns the first value of the given parameter.
+fn parameter(parameters: &str, name: &str) -> Option<String> {
+	serde_html_form::from_str::<Vec<(String, String)>>(parameters)
+		.ok()?
+		.into_iter()
+		.find_map(|(n, value)| (n == name).then_some(value))
+}
+
+#[cfg(test)]
+mod tests {
+	use std::borrow::Cow;
+
+	use axum::{
+		body::Body,
+		http::{
+			StatusCode,
+			header::{CONTENT_TYPE, LOCATION},
+		},
+		response::{IntoResponse, Redirect},
+	};
+	use iref::{Uri, uri};
+
+	use crate::{
+		CodeBuf, code,
+		server::{AuthorizationServerMetadata, OAuth2ServerError},
+		util::NoExtension,
+	};
+
+	use super::*;
+
+	#[derive(Debug, Deserialize)]
+	struct Request {
+		client_id: ClientIdBuf,
+	}
+
+	fn check(parameters: &str) -> Result<Stateful<Request>, AuthorizeError> {
+		check_authorization_request(
+			parameters,
+			Some(&[uri!("https://client.example.com/cb").to_owned()]),
+			RedirectUriPolicy::Generic,
+			ClientType::Confidential,
+			PkcePolicy::RequiredForPublicClients,
+		)
+		.map(|(request, _)| request)
+	}
+
+	#[test]
+	fn valid_request() {
+		let request = check("response_type=code&client_id=s6BhdRkqt3&state=xyz").unwrap();
+		assert_eq!(request.value.client_id.as_str(), "s6BhdRkqt3");
+		assert_eq!(request.state.unwrap().as_str(), "xyz");
+	}
+
+	#[test]
+	fn redirected_error() {
+		let error = check("client_id=s6BhdRkqt3&state=xyz").unwrap_err();
+		assert_eq!(
+			error.error.error_description.as_deref(),
+			Some("missing `response_type` parameter")
+		);
+
+		let response = error.into_response();
+		assert_eq!(response.status(), StatusCode::FOUND);
+		assert!(
+			response.headers()[LOCATION]
+				.to_str()
+				.unwrap()
+				.starts_with("https://client.example.com/cb?state=xyz&error=invalid_request")
+		);
+	}
+
+	#[test]
+	fn unverified_redirect_uri() {
+		for parameters in [
+			"client_id=s6BhdRkqt3&redirect_uri=https%3A%2F%2Fattacker.example.com%2Fcb",
+			"client_id=s6BhdRkqt3&client_id=other",
+		] {
+			let error = check(parameters).unwrap_err();
+			assert_eq!(error.redirect_uri, None);
+			assert_eq!(error.into_response().status(), StatusCode::BAD_REQUEST);
+		}
+	}
+
+	#[tokio::test]
+	async fn form_parameters() {
+		let request = axum::http::Request::post("/authorize")
+			.header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+			.body(Body::from("response_type=code&client_id=s6BhdRkqt3"))
+			.unwrap();
+		assert_eq!(
+			authorization_parameters(request, &()).await.unwrap(),
+			"response_type=code&client_id=s6BhdRkqt3"
+		);
+
+		let request = axum::http::Request::post("/authorize")
+			.header(CONTENT_TYPE, "application/json")
+			.body(Body::from("{}"))
+			.unwrap();
+		assert!(authorization_parameters(request, &()).await.is_err());
+	}
+
+	/// Server authenticating and obtaining the consent of the resource owner
+	/// depending on its configuration.
+	struct HookServer {
+		logged_in: bool,
+		consent: bool,
+	}
+
+	impl OAuth2Server for HookServer {
+		type Metadata = NoExtension;
+		type AuthorizationRequest = Request;
+		type TokenRequest = Request;
+		type TokenResponse = ();
+
+		async fn metadata(
+			&self,
+		) -> Result<Cow<'_, AuthorizationServerMetadata>, OAuth2ServerError> {
+			Err(OAuth2ServerError::InvalidRequest)
+		}
+
+		async fn authenticate_user(
+			&self,
+			_context: &AuthorizationContext,
+			_request: &Stateful<Request>,
+		) -> Result<String, AuthorizeInterrupt> {
+			if self.logged_in {
+				Ok("alice".to_owned())
+			} else {
+				Err(AuthorizeInterrupt::respond(Redirect::to("/login")))
+			}
+		}
+
+		async fn check_consent(
+			&self,
+			subject: &str,
+			_context: &AuthorizationContext,
+			_request: &Stateful<Request>,
+		) -> Result<(), AuthorizeInterrupt> {
+			assert_eq!(subject, "alice");
+			if self.consent {
+				Ok(())
+			} else {
+				Err(AuthorizeInterrupt::deny(ErrorCode::AccessDenied))
+			}
+		}
+
+		async fn issue_code(
+			&self,
+			_subject: &str,
+			context: &AuthorizationContext,
+			request: &Stateful<Request>,
+		) -> Result<CodeBuf, AuthorizeInterrupt> {
+			assert_eq!(context.client_id, request.value.client_id);
+			Ok(code!("SplxlOBeZQQYbYS6WxSbIA").to_owned())
+		}
+
+		async fn token(&self, _: Request) -> Result<(), OAuth2ServerError> {
+			Err(OAuth2ServerError::InvalidGrant)
+		}
+	}
+
+	#[tokio::test]
+	async fn authorization_hooks() {
+		let authorize = async |logged_in, consent, redirect_uri: Option<&Uri>| {
+			let context = AuthorizationContext {
+				client_id: ClientIdBuf::new("s6BhdRkqt3".to_owned()).unwrap(),
+				client_type: ClientType::Confidential,
+				redirect_uri: redirect_uri.map(ToOwned::to_owned),
+			};
+			let request = check("response_type=code&client_id=s6BhdRkqt3&state=xyz").unwrap();
+			HookServer { logged_in, consent }
+				.authorize(context, request)
+				.await
+				.into_response()
+		};
+		let redirect_uri = Some(uri!("https://client.example.com/cb"));
+
+		let response = authorize(false, true, redirect_uri).await;
+		assert_eq!(response.status(), StatusCode::SEE_OTHER);
+		assert_eq!(response.headers()[LOCATION], "/login");
+
+		let response = authorize(true, false, redirect_uri).await;
+		assert_eq!(response.status(), StatusCode::FOUND);
+		assert_eq!(
+			response.headers()[LOCATION],
+			"https://client.example.com/cb?state=xyz&error=access_denied"
+		);
+
+		let response = authorize(true, true, redirect_uri).await;
+		assert_eq!(response.status(), StatusCode::FOUND);
+		assert_eq!(
+			response.headers()[LOCATION],
+			"https://client.example.com/cb?state=xyz&code=SplxlOBeZQQYbYS6WxSbIA"
+		);
+
+		let response = authorize(true, true, None).await;
+		assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+	}
+
+	/// Server resolving pushed authorization requests.
+	#[cfg(feature = "par")]
+	struct ParServer {
+		requests: crate::server::par::PushedRequestStore<Stateful<Request>>,
+	}
+
+	#[cfg(feature = "par")]
+	#[derive(Deserialize)]
+	struct PushedRequestReference {
+		client_id: ClientIdBuf,
+		request_uri: UriBuf,
+	}
+
+	#[cfg(feature = "par")]
+	impl OAuth2Server for ParServer {
+		type Metadata = NoExtension;
+		type AuthorizationRequest = PushedRequestReference;
+		type TokenRequest = Request;
+		type TokenResponse = ();
+
+		async fn metadata(
+			&self,
+		) -> Result<Cow<'_, AuthorizationServerMetadata>, OAuth2ServerError> {
+			Err(OAuth2ServerError::InvalidRequest)
+		}
+
+		async fn authorize(
+			&self,
+			context: AuthorizationContext,
+			request: Stateful<PushedRequestReference>,
+		) -> impl IntoResponse {
+			assert_eq!(context.client_id, request.value.client_id);
+			match self
+				.requests
+				.take(&request.value.request_uri, &context.client_id)
+			{
+				Some(request) if request.state.as_deref() == Some(crate::state!("xyz")) => {
+					StatusCode::OK
+				}
+				_ => StatusCode::BAD_REQUEST,
+			}
+		}
+
+		async fn token(&self, _: Request) -> Result<(), OAuth2ServerError> {
+			Err(OAuth2ServerError::InvalidGrant)
+		}
+	}
+
+	#[cfg(feature = "par")]
+	#[tokio::test]
+	async fn pushed_authorization_request() {
+		use tower_service::Service;
+
+		use crate::server::axum::OAuth2Router;
+
+		let server = ParServer {
+			requests: Default::default(),
+		};
+		let client_id = ClientIdBuf::new("s6BhdRkqt3".to_owned()).unwrap();
+		let pushed = check("response_type=code&client_id=s6BhdRkqt3&state=xyz").unwrap();
+		let response = server.requests.push(&client_id, pushed);
+		let mut router = axum::Router::new()
+			.oauth2_routes()
+			.with_state(Arc::new(server));
+
+		let uri = format!(
+			"/authorize?client_id=s6BhdRkqt3&request_uri={}",
+			response.request_uri
+		);
+		let request = |uri: &str| axum::http::Request::get(uri).body(Body::empty()).unwrap();
+
+		let response = router.call(request(&uri)).await.unwrap();
+		assert_eq!(response.status(), StatusCode::OK);
+
+		// Pushed requests can only be used once.
+		let response = router.call(request(&uri)).await.unwrap();
+		assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+	}
+}
@@ -0,0 +1,291 @@
+//! Endpoint-specific error responses.
+//!
+//! Errors of the authorization endpoint are reported to the client by
+//! redirecting the user-agent, while errors of the token endpoint are
+//! reported in a JSON response body. [`AuthorizeError`] and [`TokenError`]
+//! pick the right format.
+use axum::{
+	body::Body,
+	http::{
+		StatusCode,
+		header::{LOCATION, WWW_AUTHENTICATE},
+	},
+	response::{IntoResponse, Response},
+};
+use iref::{Uri, UriBuf};
+
+use crate::{
+	StateBuf, Stateful,
+	server::{ErrorResponse, code::CodeRedemptionError},
+	util::extend_uri_query,
+};
+
+use super::{ErrorCode, OAuth2ServerError, SecureJson, error_response};
+
+/// Error of the authorization endpoint.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc6749#section-4.1.2.1>
+///
+/// The error is reported by redirecting the user-agent to the client
+/// redirection URI, including the `state` of the request. If the redirection
+/// URI is missing or invalid, or the client identifier is invalid, the
+/// user-agent must not be redirected: the error is then rendered directly
+/// with a `400 Bad Request` status.
+#[derive(Debug, Clone)]
+pub struct AuthorizeError {
+	/// Client redirection URI, if it could be validated.
+	pub redirect_uri: Option<UriBuf>,
+
+	/// State of the authorization request.
+	pub state: Option<StateBuf>,
+
+	/// Error response.
+	pub error: ErrorResponse<ErrorCode>,
+}
+
+impl AuthorizeError {
+	/// Creates an error reported by redirecting the user-agent to
+	/// `redirect_uri`.
+	pub fn new(redirect_uri: UriBuf, state: Option<StateBuf>, error: ErrorCode) -> Self {
+		Self::from_error(redirect_uri, state, error)
+	}
+
+	/// Creates an error reported by redirecting the user-agent to
+	/// `redirect_uri`, from any [`IntoErrorResponse`] error.
+	pub fn from_error(
+		redirect_uri: UriBuf,
+		state: Option<StateBuf>,
+		error: impl IntoErrorResponse,
+	) -> Self {
+		Self {
+			redirect_uri: Some(redirect_uri),
+			state,
+			error: error.into_error_response(),
+		}
+	}
+
+	/// Creates an error that must not be reported to the client, because the
+	/// redirection URI or client identifier is missing or invalid.
+	pub fn without_redirect(error: ErrorCode) -> Self {
+		Self {
+			redirect_uri: None,
+			state: None,
+			error: ErrorResponse::new(error, None, None),
+		}
+	}
+
+	/// Sets the human-readable description of the error.
+	pub fn with_description(mut self, description: impl Into<String>) -> Self {
+		self.error.error_description = Some(description.into());
+		self
+	}
+
+	/// Returns the URI the user-agent is redirected to, if any.
+	pub fn redirect_url(&self) -> Option<UriBuf> {
+		let mut uri = self.redirect_uri.clone()?;
+		extend_uri_query(&mut uri, Stateful::new(&self.error, self.state.clone()));
+		Some(uri)
+	}
+}
+
+impl IntoResponse for AuthorizeError {
+	fn into_response(self) -> Response {
+		match self.redirect_url() {
+			Some(uri) => authorization_redirect(&uri),
+			None => error_response(self.error.error, self.error.error_description),
+		}
+	}
+}
+
+/// Redirects the user-agent to the client with an authorization response,
+/// successful or not, using the `302 Found` status of the examples of
+/// [RFC 6749 Section 4.1.2](https://datatracker.ietf.org/doc/html/rfc6749#section-4.1.2).
+pub(super) fn authorization_redirect(uri: &Uri) -> Response {
+	Response::builder()
+		.status(StatusCode::FOUND)
+		.header(LOCATION, uri.as_str())
+		.body(Body::empty())
+		// UNWRAP SAFETY: URIs are valid header values.
+		.unwrap()
+}
+
+/// Error that can be reported in an OAuth 2.0 error response.
+///
+/// Implemented by server-specific error types, so that they can be turned
+/// into an [`AuthorizeError`] or a [`TokenError`], possibly with an
+/// extension [`ErrorCode`].
+pub trait IntoErrorResponse {
+	/// Converts this error into an error response.
+	fn into_error_response(self) -> ErrorResponse<ErrorCode>;
+}
+
+impl IntoErrorResponse for ErrorCode {
+	fn into_error_response(self) -> ErrorResponse<ErrorCode> {
+		ErrorResponse::new(self, None, None)
+	}
+}
+
+impl IntoErrorResponse for ErrorResponse<ErrorCode> {
+	fn into_error_response(self) -> ErrorResponse<ErrorCode> {
+		self
+	}
+}
+
+impl IntoErrorResponse for OAuth2ServerError {
+	fn into_error_response(self) -> ErrorResponse<ErrorCode> {
+		// UNWRAP SAFETY: every server error has an error code.
+		self.as_error_code().unwrap().into_error_response()
+	}
+}
+
+impl IntoErrorResponse for CodeRedemptionError {
+	fn into_error_response(self) -> ErrorResponse<ErrorCode> {
+		ErrorResponse::new(ErrorCode::InvalidGrant, Some(self.to_string()), None)
+	}
+}
+
+/// Error of the token endpoint.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc6749#section-5.2>
+///
+/// The error is reported in a JSON response body with a `400 Bad Request`
+/// status, or `401 Unauthorized` for `invalid_client` errors.
+#[derive(Debug, Clone)]
+pub struct TokenError(pub ErrorResponse<ErrorCode>);
+
+impl TokenError {
+	/// Creates a new token endpoint error.
+	pub fn new(error: ErrorCode) -> Self {
+		Self::from_error(error)
+	}
+
+	/// Creates a token endpoint error from any [`IntoErrorResponse`] error.
+	pub fn from_error(error: impl IntoErrorResponse) -> Self {
+		Self(error.into_error_response())
+	}
+
+	/// Sets the human-readable description of the error.
+	pub fn with_description(mut self, description: impl Into<String>) -> Self {
+		self.0.error_description = Some(description.into());
+		self
+	}
+}
+
+impl From<ErrorCode> for TokenError {
+	fn from(value: ErrorCode) -> Self {
+		Self::new(value)
+	}
+}
+
+impl From<OAuth2ServerError> for TokenError {
+	fn from(value: OAuth2ServerError) -> Self {
+		Self::from_error(value)
+	}
+}
+
+impl From<CodeRedemptionError> for TokenError {
+	fn from(value: CodeRedemptionError) -> Self {
+		Self::from_error(value)
+	}
+}
+
+impl IntoResponse for TokenError {
+	fn into_response(self) -> Response {
+		match self.0.error {
+			ErrorCode::InvalidClient => (
+				StatusCode::UNAUTHORIZED,
+				[(WWW_AUTHENTICATE, "Basic")],
+				SecureJson(self.0),
+			)
+				.into_response(),
+			_ => (StatusCode::BAD_REQUEST, SecureJson(self.0)).into_response(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use axum::http::header::CONTENT_TYPE;
+
+	use crate::transport::APPLICATION_JSON;
+
+	use super::*;
+
+	#[test]
+	fn authorize_error_redirect() {
+		let response = AuthorizeError::new(
+			UriBuf::new(b"https://client.example.com/cb".to_vec()).unwrap(),
+			Some(StateBuf::new("xyz".to_owned()).unwrap()),
+			ErrorCode::AccessDenied,
+		)
+		.into_response();
+
+		assert_eq!(response.status(), StatusCode::FOUND);
+		assert_eq!(
+			response.headers()[LOCATION],
+			"https://client.example.com/cb?state=xyz&error=access_denied"
+		);
+	}
+
+	#[test]
+	fn authorize_error_without_redirect() {
+		let response = AuthorizeError::without_redirect(ErrorCode::InvalidRequest)
+			.with_description("invalid redirect URI")
+			.into_response();
+
+		assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+		assert!(response.headers().get(LOCATION).is_none());
+	}
+
+	#[test]
+	fn token_error() {
+		let response = TokenError::new(ErrorCode::InvalidGrant).into_response();
+		assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+		assert_eq!(response.headers()[CONTENT_TYPE], APPLICATION_JSON);
+
+		let response = TokenError::from(OAuth2ServerError::InvalidClient).into_response();
+		assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+		assert_eq!(response.headers()[WWW_AUTHENTICATE], "Basic");
+
+		let error = TokenError::from(CodeRedemptionError::MissingCodeVerifier);
+		assert_eq!(error.0.error, ErrorCode::InvalidGrant);
+		assert_eq!(
+			error.0.error_description.as_deref(),
+			Some("missing code verifier")
+		);
+	}
+
+	#[test]
+	fn extension_error_code() {
+		struct InvalidTxCode;
+
+		impl IntoErrorResponse for InvalidTxCode {
+			fn into_error_response(self) -> ErrorResponse<ErrorCode> {
+				ErrorResponse::new(
+					ErrorCode::extension("invalid_tx_code"),
+					Some("wrong transaction code".to_owned()),
+					None,
+				)
+			}
+		}
+
+		let error = TokenError::from_error(InvalidTxCode);
+		assert_eq!(
+			serde_json::to_value(&error.0).unwrap(),
+			serde_json::json!({
+				"error": "invalid_tx_code",
+				"error_description": "wrong transaction code"
+			})
+		);
+		assert_eq!(error.into_response().status(), StatusCode::BAD_REQUEST);
+
+		assert_eq!(
+			serde_json::from_str::<ErrorCode>(r#""invalid_grant""#).unwrap(),
+			ErrorCode::InvalidGrant
+		);
+		assert_eq!(
+			serde_json::from_str::<ErrorCode>(r#""invalid_tx_code""#).unwrap(),
+			ErrorCode::extension("invalid_tx_code")
+		);
+	}
+}
@@ -0,0 +1,286 @@
+use std::{future::Future, sync::Arc};
+
+use axum::{
+	extract::State,
+	http::{
+		HeaderValue, StatusCode,
+		header::{CACHE_CONTROL, CONTENT_TYPE},
+	},
+	response::{IntoResponse, Response},
+	routing::get,
+};
+
+use crate::server::jwks::KeyProvider;
+
+use super::{MountError, MountPoint, OAuth2Server, check_mount_point, routes};
+
+/// Default path of the JWKS route.
+pub const DEFAULT_JWKS_PATH: &str = "/jwks.json";
+
+/// Media type of JWK Sets.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc7517#section-8.5.1>
+pub const APPLICATION_JWK_SET_JSON: HeaderValue =
+	HeaderValue::from_static("application/jwk-set+json");
+
+/// Authorization server publishing its public keys.
+///
+/// The keys returned by the [`KeyProvider`] are served at
+/// [`JWKS_PATH`](Self::JWKS_PATH), cached by clients for
+/// [`KeyProvider::max_age`]. Only their public part is served: private key
+/// members are stripped, and symmetric keys are left out.
+pub trait OAuth2JwksServer: OAuth2Server {
+	/// Provider of the published keys.
+	type KeyProvider: KeyProvider;
+
+	/// Path of the JWKS route, relative to the mount point of the server.
+	///
+	/// Defaults to [`DEFAULT_JWKS_PATH`].
+	const JWKS_PATH: &'static str = DEFAULT_JWKS_PATH;
+
+	/// Returns the provider of the published keys.
+	fn key_provider(&self) -> &Self::KeyProvider;
+}
+
+/// Router serving the OAuth 2.0 routes of an [`OAuth2JwksServer`], along
+/// with its JWKS route.
+///
+/// This is used instead of [`OAuth2Router`](super::OAuth2Router): the
+/// metadata advertises the JWKS route as `jwks_uri`, unless the server
+/// metadata already has one.
+pub trait OAuth2JwksRouter<S>: Sized {
+	/// Adds the OAuth 2.0 routes and the JWKS route.
+	fn oauth2_jwks_routes(self) -> Self;
+
+	/// Adds the OAuth 2.0 routes and the JWKS route at the location of the
+	/// given mount point.
+	///
+	/// See [`OAuth2Router::oauth2_routes_at`](super::OAuth2Router::oauth2_routes_at).
+	fn oauth2_jwks_routes_at(
+		self,
+		server: &S,
+		mount_point: &MountPoint,
+	) -> impl Send + Future<Output = Result<Self, MountError>>;
+}
+
+impl<S: OAuth2JwksServer> OAuth2JwksRouter<S> for axum::Router<Arc<S>> {
+	fn oauth2_jwks_routes(self) -> Self {
+		routes(self, None, Some(S::JWKS_PATH)).route(S::JWKS_PATH, get(jwks::<S>))
+	}
+
+	async fn oauth2_jwks_routes_at(
+		self,
+		server: &S,
+		mount_point: &MountPoint,
+	) -> Result<Self, MountError> {
+		check_mount_point(server, mount_point).await?;
+		Ok(routes(self, Some(mount_point), Some(S::JWKS_PATH))
+			.route(&mount_point.path(S::JWKS_PATH), get(jwks::<S>)))
+	}
+}
+
+/// JWKS endpoint.
+async fn jwks<S>(State(server): State<Arc<S>>) -> Response
+where
+	S: OAuth2JwksServer,
+{
+	let provider = server.key_provider();
+	let keys = provider.public_keys().await.to_public();
+	let cache_control = format!("public, max-age={}", provider.max_age().as_secs());
+
+	match serde_json::to_vec(&keys) {
+		Ok(body) => (
+			[
+				(CONTENT_TYPE, APPLICATION_JWK_SET_JSON),
+				// UNWRAP SAFETY: the value only contains ASCII characters.
+				(
+					CACHE_CONTROL,
+					HeaderValue::from_str(&cache_control).unwrap(),
+				),
+			],
+			body,
+		)
+			.into_response(),
+		Err(e) => {
+			log::error!("unable to serialize JWK Set: {e}");
+			StatusCode::INTERNAL_SERVER_ERROR.into_response()
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::borrow::Cow;
+
+	use crate::{
+		IssuerBuf, issuer,
+		server::{AuthorizationServerMetadata, OAuth2ServerError, jwks::KeyRing},
+		util::{JwkSet, NoExtension},
+	};
+
+	use super::{super::metadata, *};
+
+	struct Server {
+		metadata: AuthorizationServerMetadata,
+		keys: KeyRing,
+	}
+
+	impl OAuth2Server for Server {
+		type Metadata = NoExtension;
+		type AuthorizationRequest = NoExtension;
+		type TokenRequest = NoExtension;
+		type TokenResponse = ();
+
+		async fn metadata(
+			&self,
+		) -> Result<Cow<'_, AuthorizationServerMetadata>, OAuth2ServerError> {
+			Ok(Cow::Borrowed(&self.metadata))
+		}
+
+		async fn token(&self, _: NoExtension) -> Result<(), OAuth2ServerError> {
+			Err(OAuth2ServerError::InvalidGrant)
+		}
+	}
+
+	impl OAuth2JwksServer for Server {
+		type KeyProvider = KeyRing;
+
+		fn key_provider(&self) -> &KeyRing {
+			&self.keys
+		}
+	}
+
+	fn server(issuer: IssuerBuf) -> Arc<Server> {
+		let keys = KeyRing::new();
+		keys.add(
+			serde_json::from_value(serde_json::json!({
+				"kty": "OKP",
+				"kid": "1",
+				"crv": "Ed25519",
+				"x": "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"
+			}))
+			.unwrap(),
+		)
+		.unwrap();
+
+		Arc::new(Server {
+			metadata: AuthorizationServerMetadata::new(issuer),
+			keys,
+		})
+	}
+
+	/// Server whose key provider returns private keys.
+	struct LeakyServer(Server);
+
+	impl OAuth2Server for LeakyServer {
+		type Metadata = NoExtension;
+		type AuthorizationRequest = NoExtension;
+		type TokenRequest = NoExtension;
+		type TokenResponse = ();
+
+		async fn metadata(
+			&self,
+		) -> Result<Cow<'_, AuthorizationServerMetadata>, OAuth2ServerError> {
+			self.0.metadata().await
+		}
+
+		async fn token(&self, _: NoExtension) -> Result<(), OAuth2ServerError> {
+			Err(OAuth2ServerError::InvalidGrant)
+		}
+	}
+
+	impl OAuth2JwksServer for LeakyServer {
+		type KeyProvider = Self;
+
+		fn key_provider(&self) -> &Self {
+			self
+		}
+	}
+
+	impl KeyProvider for LeakyServer {
+		async fn public_keys(&self) -> JwkSet {
+			serde_json::from_value(serde_json::json!({
+				"keys": [
+					{
+						"kty": "EC",
+						"kid": "ec",
+						"crv": "P-256",
+						"x": "f83OJ3D2xF1Bg8vub9tLe1gHMzV76e8Tus9uPHvRVEU",
+						"y": "x_FEzRu9m36HLN_tue659LNpXW6pCyStikYjKIWI5a0",
+						"d": "jpsQnnGQmL-YBIffH1136cspYG6-0iY7X1fCE9-E9LI"
+					},
+					{
+						"kty": "RSA",
+						"kid": "rsa",
+						"n": "sXchDaQebHnPiGvy",
+						"e": "AQAB",
+						"d": "VFCWOqXr8nvZNyaa",
+						"p": "9gY2w6I6S6L0juEK",
+						"q": "uKlCKvKv_ZJMVcdI",
+						"dp": "w0kZbV63cVRvVX6y",
+						"dq": "o_8V14SezckO6CNL",
+						"qi": "eNho5yRBEBxhGBtQ"
+					},
+					{ "kty": "oct", "kid": "oct", "k": "c2VjcmV0" }
+				]
+			}))
+			.unwrap()
+		}
+	}
+
+	async fn body(response: Response) -> serde_json::Value {
+		let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+			.await
+			.unwrap();
+		serde_json::from_slice(&body).unwrap()
+	}
+
+	#[tokio::test]
+	async fn jwks_route() {
+		let server = server(issuer!("https://server.example.com").to_owned());
+
+		let response = jwks(State(server.clone())).await;
+		assert_eq!(response.status(), StatusCode::OK);
+		assert_eq!(response.headers()[CONTENT_TYPE], "application/jwk-set+json");
+		assert_eq!(response.headers()[CACHE_CONTROL], "public, max-age=3600");
+		let keys: JwkSet = serde_json::from_value(body(response).await).unwrap();
+		assert!(keys.find("1").is_some());
+	}
+
+	#[tokio::test]
+	async fn private_keys_not_served() {
+		let server = Arc::new(LeakyServer(Server {
+			metadata: AuthorizationServerMetadata::new(
+				issuer!("https://server.example.com").to_owned(),
+			),
+			keys: KeyRing::new(),
+		}));
+
+		let body = body(jwks(State(server)).await).await;
+		let keys = body["keys"].as_array().unwrap();
+		assert_eq!(keys.len(), 2);
+		for key in keys {
+			for member in ["d", "p", "q", "dp", "dq", "qi", "oth", "k"] {
+				assert_eq!(key.get(member), None);
+			}
+		}
+		assert_eq!(keys[0]["x"], "f83OJ3D2xF1Bg8vub9tLe1gHMzV76e8Tus9uPHvRVEU");
+		assert_eq!(keys[1]["e"], "AQAB");
+	}
+
+	#[tokio::test]
+	async fn jwks_uri() {
+		let server = server(issuer!("https://server.example.com/auth").to_owned());
+		let mount_point = Some(Arc::new(MountPoint::new(server.metadata.issuer.clone())));
+
+		let response =
+			metadata::<Server>(State(server.clone()), mount_point, Some(DEFAULT_JWKS_PATH)).await;
+		assert_eq!(
+			body(response).await["jwks_uri"],
+			"https://server.example.com/auth/jwks.json"
+		);
+
+		let response = metadata::<Server>(State(server), None, None).await;
+		assert_eq!(body(response).await.get("jwks_uri"), None);
+	}
+}
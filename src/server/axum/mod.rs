@@ -0,0 +1,755 @@
+use std::{borrow::Cow, future::Future, sync::Arc};
+
+#[cfg(feature = "par")]
+use axum::Form;
+use axum::{
+	body::Body,
+	extract::{RawForm, Request, State},
+	http::{HeaderMap, StatusCode},
+	response::{IntoResponse, Response},
+	routing::{get, post},
+};
+use iref::UriBuf;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::{
+	ClientId, CodeBuf, RedirectUriPolicy, Stateful,
+	client::ClientType,
+	endpoints::{
+		introspection::{IntrospectionRequest, IntrospectionResponse},
+		nonce::NonceResponse,
+		revocation::{RevocationRequest, TokenRevocation},
+	},
+	ext::pkce::PkcePolicy,
+	server::{
+		ErrorResponse,
+		validation::{
+			Violation, describe_violations, validate_introspection_request,
+			validate_revocation_request,
+		},
+	},
+	util::NoExtension,
+};
+
+#[cfg(feature = "par")]
+use crate::endpoints::pushed_authorization::PushedAuthorizationResponse;
+#[cfg(feature = "device")]
+use crate::{
+	endpoints::device_authorization::DeviceAuthorizationResponse,
+	server::validation::validate_device_authorization_request,
+};
+
+use super::AuthorizationServerMetadata;
+
+mod authorize;
+mod error;
+mod jwks;
+mod mount;
+mod response;
+#[cfg(feature = "tower-sessions")]
+mod session;
+mod tenant;
+mod token;
+pub use authorize::*;
+pub use error::*;
+pub use jwks::*;
+pub use mount::*;
+pub use response::*;
+#[cfg(feature = "tower-sessions")]
+pub use session::*;
+pub use tenant::*;
+pub use token::*;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+	InvalidRequest,
+	InvalidClient,
+	InvalidGrant,
+	UnauthorizedClient,
+	UnsupportedGrantType,
+	InvalidScope,
+	AuthorizationPending,
+	SlowDown,
+	AccessDenied,
+	ExpiredToken,
+	UnsupportedTokenType,
+	UnsupportedResponseType,
+	ServerError,
+	TemporarilyUnavailable,
+
+	/// Any other error code, defined by an extension (e.g. OpenID4VCI's
+	/// `invalid_tx_code`).
+	#[serde(untagged)]
+	Extension(String),
+}
+
+impl ErrorCode {
+	/// Creates an extension error code.
+	pub fn extension(code: impl Into<String>) -> Self {
+		Self::Extension(code.into())
+	}
+}
+
+#[derive(Debug)]
+pub enum OAuth2ServerError {
+	InvalidRequest,
+	InvalidClient,
+	InvalidGrant,
+	UnauthorizedClient,
+	UnsupportedGrantType,
+	InvalidScope,
+	AuthorizationPending,
+	SlowDown,
+	AccessDenied,
+	ExpiredToken,
+	UnsupportedTokenType,
+}
+
+impl OAuth2ServerError {
+	pub fn as_error_code(&self) -> Option<ErrorCode> {
+		match self {
+			Self::InvalidRequest => Some(ErrorCode::InvalidRequest),
+			Self::InvalidClient => Some(ErrorCode::InvalidClient),
+			Self::InvalidGrant => Some(ErrorCode::InvalidGrant),
+			Self::UnauthorizedClient => Some(ErrorCode::UnauthorizedClient),
+			Self::UnsupportedGrantType => Some(ErrorCode::UnsupportedGrantType),
+			Self::InvalidScope => Some(ErrorCode::InvalidScope),
+			Self::AuthorizationPending => Some(ErrorCode::AuthorizationPending),
+			Self::SlowDown => Some(ErrorCode::SlowDown),
+			Self::AccessDenied => Some(ErrorCode::AccessDenied),
+			Self::ExpiredToken => Some(ErrorCode::ExpiredToken),
+			Self::UnsupportedTokenType => Some(ErrorCode::UnsupportedTokenType),
+		}
+	}
+}
+
+impl IntoResponse for OAuth2ServerError {
+	fn into_response(self) -> Response {
+		let error = match self.as_error_code() {
+			Some(code) => code,
+			None => {
+				return Response::builder()
+					.status(StatusCode::INTERNAL_SERVER_ERROR)
+					.body(Body::empty())
+					.unwrap();
+			}
+		};
+
+		error_response(error, None)
+	}
+}
+
+/// Builds a `400 Bad Request` JSON error response.
+fn error_response(error: ErrorCode, error_description: Option<String>) -> Response {
+	json_response(
+		Response::builder().status(StatusCode::BAD_REQUEST),
+		&ErrorResponse::<_>::new(error, error_description, None),
+	)
+}
+
+/// Builds an `invalid_request` error response listing the given violations.
+fn violations_response(violations: &[Violation]) -> Response {
+	error_response(
+		ErrorCode::InvalidRequest,
+		Some(describe_violations(violations)),
+	)
+}
+
+pub trait OAuth2Server: Sized + Send + Sync + 'static {
+	type Metadata: Clone + Serialize;
+	type AuthorizationRequest: Send + Sync + DeserializeOwned;
+	type TokenRequest: Send + DeserializeOwned;
+	type TokenResponse: Serialize;
+
+	/// PKCE requirements enforced at the authorization endpoint.
+	///
+	/// Defaults to [`PkcePolicy::RequiredForPublicClients`], following the
+	/// OAuth 2.0 Security Best Current Practice.
+	const PKCE_POLICY: PkcePolicy = PkcePolicy::RequiredForPublicClients;
+
+	/// Returns the type of the given client.
+	///
+	/// Defaults to [`ClientType::Public`]. Servers registering confidential
+	/// clients must override this method for them to be exempted from
+	/// [`PkcePolicy::RequiredForPublicClients`].
+	fn client_type(&self, _client_id: &ClientId) -> impl Send + Future<Output = ClientType> {
+		std::future::ready(ClientType::Public)
+	}
+
+	/// Returns the redirection URIs registered by the given client, or
+	/// `None` if the client is unknown.
+	///
+	/// Invalid authorization requests are reported by redirecting the
+	/// user-agent to the client only if its redirection URI matches one of
+	/// these. Defaults to `None`, so that they are always reported directly
+	/// to the user-agent.
+	fn redirect_uris(
+		&self,
+		_client_id: &ClientId,
+	) -> impl Send + Future<Output = Option<Vec<UriBuf>>> {
+		std::future::ready(None)
+	}
+
+	/// Returns the rules applied to the redirection URIs of the given
+	/// client.
+	///
+	/// Defaults to [`RedirectUriPolicy::Generic`]. Servers registering
+	/// native apps should return [`RedirectUriPolicy::NativeApp`] for them.
+	fn redirect_uri_policy(
+		&self,
+		_client_id: &ClientId,
+	) -> impl Send + Future<Output = RedirectUriPolicy> {
+		std::future::ready(RedirectUriPolicy::Generic)
+	}
+
+	fn metadata(
+		&self,
+	) -> impl Send
+	+ Future<
+		Output = Result<Cow<'_, AuthorizationServerMetadata<Self::Metadata>>, OAuth2ServerError>,
+	>;
+
+	/// Authenticates the resource owner of an authorization request.
+	///
+	/// Returns the subject identifier of the resource owner, or interrupts
+	/// the flow, e.g. to render a login page. Defaults to denying the
+	/// request with `access_denied`.
+	fn authenticate_user(
+		&self,
+		_context: &AuthorizationContext,
+		_request: &Stateful<Self::AuthorizationRequest>,
+	) -> impl Send + Future<Output = Result<String, AuthorizeInterrupt>> {
+		std::future::ready(Err(AuthorizeInterrupt::deny(ErrorCode::AccessDenied)))
+	}
+
+	/// Checks that the resource owner identified by `subject` consents to
+	/// the authorization request, or interrupts the flow, e.g. to render a
+	/// consent page.
+	///
+	/// Defaults to consenting, which is only appropriate for first-party
+	/// clients. See [`ConsentStore`](crate::server::consent::ConsentStore)
+	/// to skip the consent page for previously granted scopes.
+	fn check_consent(
+		&self,
+		_subject: &str,
+		_context: &AuthorizationContext,
+		_request: &Stateful<Self::AuthorizationRequest>,
+	) -> impl Send + Future<Output = Result<(), AuthorizeInterrupt>> {
+		std::future::ready(Ok(()))
+	}
+
+	/// Issues an authorization code for the request, on behalf of the
+	/// resource owner identified by `subject`.
+	///
+	/// The code must be bound to the request, e.g. with a
+	/// [`CodeBinding`](crate::server::code::CodeBinding). Defaults to failing
+	/// with `server_error`.
+	fn issue_code(
+		&self,
+		_subject: &str,
+		_context: &AuthorizationContext,
+		_request: &Stateful<Self::AuthorizationRequest>,
+	) -> impl Send + Future<Output = Result<CodeBuf, AuthorizeInterrupt>> {
+		std::future::ready(Err(AuthorizeInterrupt::deny(ErrorCode::ServerError)))
+	}
+
+	/// Answers an authorization request, checked by the authorization
+	/// endpoint as described by [`AuthorizeRequest`].
+	///
+	/// Defaults to calling [`authenticate_user`](Self::authenticate_user),
+	/// [`check_consent`](Self::check_consent) and
+	/// [`issue_code`](Self::issue_code) in this order, and redirecting the
+	/// user-agent to the client with the issued code. Interruptions denying
+	/// the request are reported to the client, or rendered directly if its
+	/// redirection URI could not be validated.
+	fn authorize(
+		&self,
+		context: AuthorizationContext,
+		request: Stateful<Self::AuthorizationRequest>,
+	) -> impl Send + Future<Output = impl IntoResponse> {
+		authorize_with_hooks(ServerHooks(self), context, request)
+	}
+
+	fn token(
+		&self,
+		token_request: Self::TokenRequest,
+	) -> impl Send + Future<Output = Result<Self::TokenResponse, OAuth2ServerError>>;
+}
+
+pub trait OAuth2Router<S>: Sized {
+	fn oauth2_routes(self) -> Self;
+
+	/// Adds the OAuth 2.0 routes at the location of the given mount point.
+	///
+	/// Fails if the metadata returned by [`OAuth2Server::metadata`] does not
+	/// advertise the issuer identifier of the mount point. Since the
+	/// metadata may change, it is checked again before being served, and
+	/// replaced by a `500 Internal Server Error` response if they disagree.
+	///
+	/// The other routes must be added at the same mount point (e.g. with
+	/// [`OAuth2RevocationRouter::oauth2_revocation_route_at`]).
+	fn oauth2_routes_at(
+		self,
+		server: &S,
+		mount_point: &MountPoint,
+	) -> impl Send + Future<Output = Result<Self, MountError>>;
+}
+
+impl<S: OAuth2Server> OAuth2Router<S> for axum::Router<Arc<S>> {
+	fn oauth2_routes(self) -> Self {
+		routes(self, None, None)
+	}
+
+	async fn oauth2_routes_at(
+		self,
+		server: &S,
+		mount_point: &MountPoint,
+	) -> Result<Self, MountError> {
+		check_mount_point(server, mount_point).await?;
+		Ok(routes(self, Some(mount_point), None))
+	}
+}
+
+/// Checks that the metadata of the given server advertises the issuer
+/// identifier of the mount point.
+async fn check_mount_point<S: OAuth2Server>(
+	server: &S,
+	mount_point: &MountPoint,
+) -> Result<(), MountError> {
+	let metadata = server.metadata().await.map_err(MountError::Metadata)?;
+	mount_point.check(&metadata)?;
+	Ok(())
+}
+
+/// Adds the OAuth 2.0 routes, at the location of the given mount point if
+/// any.
+///
+/// If `jwks_path` is given, the metadata advertises the JWKS route at this
+/// path relative to the mount point, unless it already has a `jwks_uri`.
+fn routes<S: OAuth2Server>(
+	router: axum::Router<Arc<S>>,
+	mount_point: Option<&MountPoint>,
+	jwks_path: Option<&'static str>,
+) -> axum::Router<Arc<S>> {
+	let mount_point = mount_point.cloned().map(Arc::new);
+	let path = |path: &str| match &mount_point {
+		Some(mount_point) => mount_point.path(path),
+		None => path.to_owned(),
+	};
+	let well_known_path = match &mount_point {
+		Some(mount_point) => mount_point.well_known_path(),
+		None => WELL_KNOWN_PATH.to_owned(),
+	};
+
+	router
+		.route(
+			&well_known_path,
+			get({
+				let mount_point = mount_point.clone();
+				move |server| metadata::<S>(server, mount_point, jwks_path)
+			}),
+		)
+		.route(
+			&path("/authorize"),
+			get(authorize::<S>).post(authorize::<S>),
+		)
+		.route(&path("/token"), post(token::<S>))
+}
+
+/// Authorization Server Metadata Endpoint.
+///
+/// If served at a mount point, the metadata returned by
+/// [`OAuth2Server::metadata`] is checked against its issuer identifier.
+async fn metadata<S>(
+	State(server): State<Arc<S>>,
+	mount_point: Option<Arc<MountPoint>>,
+	jwks_path: Option<&'static str>,
+) -> Response
+where
+	S: OAuth2Server,
+{
+	// TODO support `Accept-Language` header.
+	let metadata = match server.metadata().await {
+		Ok(metadata) => metadata,
+		Err(e) => return e.into_response(),
+	};
+
+	if let Some(mount_point) = &mount_point
+		&& let Err(e) = mount_point.check(&metadata)
+	{
+		log::error!("{e}");
+		return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+	}
+
+	match jwks_path {
+		Some(jwks_path) if metadata.jwks_uri.is_none() => {
+			let mut metadata = metadata.into_owned();
+			metadata.jwks_uri =
+				Some(MountPoint::new(metadata.issuer.clone()).endpoint_uri(jwks_path));
+			metadata.into_response()
+		}
+		_ => metadata.as_ref().into_response(),
+	}
+}
+
+/// Authorization Request endpoint.
+///
+/// Requests are checked like with the [`AuthorizeRequest`] extractor.
+async fn authorize<S>(State(server): State<Arc<S>>, request: Request) -> Response
+where
+	S: OAuth2Server,
+{
+	match extract_authorization_request(request, server.as_ref()).await {
+		Ok((context, request)) => server.authorize(context, request).await.into_response(),
+		Err(error) => error.into_response(),
+	}
+}
+
+/// Token Request endpoint.
+async fn token<S>(State(server): State<Arc<S>>, RawForm(body): RawForm) -> Response
+where
+	S: OAuth2Server,
+{
+	match parse_token_request(&body) {
+		Ok(request) => server.token(request).await.map(SecureJson).into_response(),
+		Err(error) => error.into_response(),
+	}
+}
+
+/// Parses the raw body of a token request.
+///
+/// The body is first checked by [`RawTokenRequest::parse`] before being
+/// deserialized.
+fn parse_token_request<T>(body: &[u8]) -> Result<T, TokenError>
+where
+	T: DeserializeOwned,
+{
+	RawTokenRequest::parse(body)?.deserialize()
+}
+
+#[cfg(feature = "par")]
+pub trait OAuth2ParServer: OAuth2Server {
+	type PushedAuthorizationRequest: Send + DeserializeOwned;
+
+	fn par(
+		&self,
+		request: Stateful<Self::PushedAuthorizationRequest>,
+	) -> impl Send + Future<Output = Result<PushedAuthorizationResponse, OAuth2ServerError>>;
+}
+
+#[cfg(feature = "par")]
+pub trait OAuth2ParRouter<S> {
+	fn oauth2_par_route(self) -> Self;
+
+	/// Adds the route at the location of the given mount point.
+	///
+	/// See [`OAuth2Router::oauth2_routes_at`].
+	fn oauth2_par_route_at(self, mount_point: &MountPoint) -> Self;
+}
+
+#[cfg(feature = "par")]
+impl<S: OAuth2ParServer> OAuth2ParRouter<S> for axum::Router<Arc<S>> {
+	fn oauth2_par_route(self) -> Self {
+		self.route("/par", post(par::<S>))
+	}
+
+	fn oauth2_par_route_at(self, mount_point: &MountPoint) -> Self {
+		self.route(&mount_point.path("/par"), post(par::<S>))
+	}
+}
+
+#[cfg(feature = "par")]
+async fn par<S>(
+	State(server): State<Arc<S>>,
+	Form(request): Form<Stateful<S::PushedAuthorizationRequest>>,
+) -> impl IntoResponse
+where
+	S: OAuth2ParServer,
+{
+	server.par(request).await
+}
+
+/// Authorization server supporting the Device Authorization Grant.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc8628>
+///
+/// Device access token requests are handled by
+/// [`OAuth2Server::token`], which should answer with the
+/// [`AuthorizationPending`](OAuth2ServerError::AuthorizationPending),
+/// [`SlowDown`](OAuth2ServerError::SlowDown),
+/// [`AccessDenied`](OAuth2ServerError::AccessDenied) and
+/// [`ExpiredToken`](OAuth2ServerError::ExpiredToken) errors while the
+/// authorization is not granted.
+#[cfg(feature = "device")]
+pub trait OAuth2DeviceServer: OAuth2Server {
+	type DeviceAuthorizationRequest: Send + DeserializeOwned;
+
+	fn device_authorization(
+		&self,
+		request: Self::DeviceAuthorizationRequest,
+	) -> impl Send + Future<Output = Result<DeviceAuthorizationResponse, OAuth2ServerError>>;
+}
+
+#[cfg(feature = "device")]
+pub trait OAuth2DeviceRouter<S> {
+	fn oauth2_device_route(self) -> Self;
+
+	/// Adds the route at the location of the given mount point.
+	///
+	/// See [`OAuth2Router::oauth2_routes_at`].
+	fn oauth2_device_route_at(self, mount_point: &MountPoint) -> Self;
+}
+
+#[cfg(feature = "device")]
+impl<S: OAuth2DeviceServer> OAuth2DeviceRouter<S> for axum::Router<Arc<S>> {
+	fn oauth2_device_route(self) -> Self {
+		self.route("/device_authorization", post(device_authorization::<S>))
+	}
+
+	fn oauth2_device_route_at(self, mount_point: &MountPoint) -> Self {
+		self.route(
+			&mount_point.path("/device_authorization"),
+			post(device_authorization::<S>),
+		)
+	}
+}
+
+/// Device Authorization endpoint.
+///
+/// The raw body is first checked with
+/// [`validate_device_authorization_request`] before being deserialized.
+#[cfg(feature = "device")]
+async fn device_authorization<S>(State(server): State<Arc<S>>, RawForm(body): RawForm) -> Response
+where
+	S: OAuth2DeviceServer,
+{
+	let violations = validate_device_authorization_request(&String::from_utf8_lossy(&body));
+	if !violations.is_empty() {
+		return violations_response(&violations);
+	}
+
+	match serde_html_form::from_bytes::<S::DeviceAuthorizationRequest>(&body) {
+		Ok(request) => server.device_authorization(request).await.into_response(),
+		Err(e) => error_response(ErrorCode::InvalidRequest, Some(e.to_string())),
+	}
+}
+
+/// Authorization server supporting Token Introspection.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc7662>
+pub trait OAuth2IntrospectionServer: OAuth2Server {
+	/// Authenticated protected resource.
+	type Caller: Send;
+
+	/// Extension fields of the introspection response.
+	type IntrospectionExtension: Serialize;
+
+	/// Authenticates the protected resource calling the introspection
+	/// endpoint, from the request headers and parameters.
+	///
+	/// If `None` is returned, the token is reported as inactive, without
+	/// further details, as required by
+	/// [RFC 7662 Section 4](https://datatracker.ietf.org/doc/html/rfc7662#section-4).
+	fn authenticate_introspection_caller(
+		&self,
+		headers: &HeaderMap,
+		request: &IntrospectionRequest,
+	) -> impl Send + Future<Output = Option<Self::Caller>>;
+
+	/// Looks up the token and describes it.
+	///
+	/// Unknown, expired or revoked tokens, as well as tokens the caller is
+	/// not allowed to introspect, must be reported with
+	/// [`IntrospectionResponse::inactive`].
+	fn introspect(
+		&self,
+		caller: Self::Caller,
+		request: IntrospectionRequest,
+	) -> impl Send
+	+ Future<
+		Output = Result<IntrospectionResponse<Self::IntrospectionExtension>, OAuth2ServerError>,
+	>;
+}
+
+pub trait OAuth2IntrospectionRouter<S> {
+	fn oauth2_introspection_route(self) -> Self;
+
+	/// Adds the route at the location of the given mount point.
+	///
+	/// See [`OAuth2Router::oauth2_routes_at`].
+	fn oauth2_introspection_route_at(self, mount_point: &MountPoint) -> Self;
+}
+
+impl<S: OAuth2IntrospectionServer> OAuth2IntrospectionRouter<S> for axum::Router<Arc<S>> {
+	fn oauth2_introspection_route(self) -> Self {
+		self.route("/introspect", post(introspect::<S>))
+	}
+
+	fn oauth2_introspection_route_at(self, mount_point: &MountPoint) -> Self {
+		self.route(&mount_point.path("/introspect"), post(introspect::<S>))
+	}
+}
+
+/// Token Introspection endpoint.
+///
+/// The raw body is first checked with [`validate_introspection_request`]
+/// before being deserialized.
+async fn introspect<S>(
+	State(server): State<Arc<S>>,
+	headers: HeaderMap,
+	RawForm(body): RawForm,
+) -> Response
+where
+	S: OAuth2IntrospectionServer,
+{
+	let violations = validate_introspection_request(&String::from_utf8_lossy(&body));
+	if !violations.is_empty() {
+		return violations_response(&violations);
+	}
+
+	let request = match serde_html_form::from_bytes::<IntrospectionRequest>(&body) {
+		Ok(request) => request,
+		Err(e) => return error_response(ErrorCode::InvalidRequest, Some(e.to_string())),
+	};
+
+	match server
+		.authenticate_introspection_caller(&headers, &request)
+		.await
+	{
+		Some(caller) => server.introspect(caller, request).await.into_response(),
+		None => IntrospectionResponse::<NoExtension>::inactive().into_response(),
+	}
+}
+
+/// Authorization server supporting Token Revocation.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc7009>
+pub trait OAuth2RevocationServer: OAuth2Server {
+	/// Authenticated client.
+	type Caller: Send;
+
+	/// Authenticates the client calling the revocation endpoint, from the
+	/// request headers and parameters.
+	///
+	/// Should fail with [`OAuth2ServerError::InvalidClient`] if the client
+	/// authentication failed.
+	fn authenticate_revocation_client(
+		&self,
+		headers: &HeaderMap,
+		request: &RevocationRequest,
+	) -> impl Send + Future<Output = Result<Self::Caller, OAuth2ServerError>>;
+
+	/// Revokes the token.
+	///
+	/// The server must check that the token was issued to the calling
+	/// client. Tokens that cannot be found, or that were issued to another
+	/// client, must be reported as [`TokenRevocation::Unknown`].
+	///
+	/// Should fail with [`OAuth2ServerError::UnsupportedTokenType`] if the
+	/// server does not support the revocation of this type of token.
+	fn revoke(
+		&self,
+		caller: Self::Caller,
+		request: RevocationRequest,
+	) -> impl Send + Future<Output = Result<TokenRevocation, OAuth2ServerError>>;
+}
+
+pub trait OAuth2RevocationRouter<S> {
+	fn oauth2_revocation_route(self) -> Self;
+
+	/// Adds the route at the location of the given mount point.
+	///
+	/// See [`OAuth2Router::oauth2_routes_at`].
+	fn oauth2_revocation_route_at(self, mount_point: &MountPoint) -> Self;
+}
+
+impl<S: OAuth2RevocationServer> OAuth2RevocationRouter<S> for axum::Router<Arc<S>> {
+	fn oauth2_revocation_route(self) -> Self {
+		self.route("/revoke", post(revoke::<S>))
+	}
+
+	fn oauth2_revocation_route_at(self, mount_point: &MountPoint) -> Self {
+		self.route(&mount_point.path("/revoke"), post(revoke::<S>))
+	}
+}
+
+/// Token Revocation endpoint.
+///
+/// The raw body is first checked with [`validate_revocation_request`]
+/// before being deserialized.
+async fn revoke<S>(
+	State(server): State<Arc<S>>,
+	headers: HeaderMap,
+	RawForm(body): RawForm,
+) -> Response
+where
+	S: OAuth2RevocationServer,
+{
+	let violations = validate_revocation_request(&String::from_utf8_lossy(&body));
+	if !violations.is_empty() {
+		return violations_response(&violations);
+	}
+
+	let request = match serde_html_form::from_bytes::<RevocationRequest>(&body) {
+		Ok(request) => request,
+		Err(e) => return error_response(ErrorCode::InvalidRequest, Some(e.to_string())),
+	};
+
+	let caller = match server
+		.authenticate_revocation_client(&headers, &request)
+		.await
+	{
+		Ok(caller) => caller,
+		Err(e) => return e.into_response(),
+	};
+
+	match server.revoke(caller, request).await {
+		Ok(TokenRevocation::Revoked | TokenRevocation::Unknown) => StatusCode::OK.into_response(),
+		Err(e) => e.into_response(),
+	}
+}
+
+/// Credential issuer providing fresh nonces to wallets, with the OpenID4VCI
+/// nonce endpoint.
+///
+/// See: <https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0.html#name-nonce-endpoint>
+pub trait NonceServer: Send + Sync + 'static {
+	/// Issues a fresh `c_nonce`, to be checked in the proofs of possession
+	/// of the next credential requests.
+	///
+	/// A DPoP nonce can also be provided with
+	/// [`NonceResponse::with_dpop_nonce`].
+	fn issue_nonce(&self) -> impl Send + Future<Output = Result<NonceResponse, OAuth2ServerError>>;
+}
+
+pub trait NonceRouter<S> {
+	fn nonce_route(self) -> Self;
+
+	/// Adds the route at the location of the given mount point.
+	///
+	/// See [`OAuth2Router::oauth2_routes_at`].
+	fn nonce_route_at(self, mount_point: &MountPoint) -> Self;
+}
+
+impl<S: NonceServer> NonceRouter<S> for axum::Router<Arc<S>> {
+	fn nonce_route(self) -> Self {
+		self.route("/nonce", post(nonce::<S>))
+	}
+
+	fn nonce_route_at(self, mount_point: &MountPoint) -> Self {
+		self.route(&mount_point.path("/nonce"), post(nonce::<S>))
+	}
+}
+
+/// Nonce endpoint.
+async fn nonce<S>(State(server): State<Arc<S>>) -> Response
+where
+	S: NonceServer,
+{
+	match server.issue_nonce().await {
+		Ok(response) => response.into_response(),
+		Err(e) => e.into_response(),
+	}
+}
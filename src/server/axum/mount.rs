@@ -0,0 +1,240 @@
+use iref::UriBuf;
+
+use crate::{Issuer, IssuerBuf, server::AuthorizationServerMetadata};
+
+use super::OAuth2ServerError;
+
+/// Well-known path of the authorization server metadata.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc8414#section-3>
+pub(super) const WELL_KNOWN_PATH: &str = "/.well-known/oauth-authorization-server";
+
+/// Error returned when the metadata of an authorization server does not
+/// advertise the issuer identifier of the location it is served from.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("metadata issuer `{found}` does not match the mount point issuer `{expected}`")]
+pub struct IssuerMismatch {
+	/// Issuer identifier of the mount point.
+	pub expected: IssuerBuf,
+
+	/// Issuer identifier of the metadata.
+	pub found: IssuerBuf,
+}
+
+/// Error returned when the OAuth 2.0 routes cannot be added at a mount point.
+#[derive(Debug, thiserror::Error)]
+pub enum MountError {
+	/// The metadata of the server could not be retrieved.
+	#[error("unable to retrieve the authorization server metadata: {0:?}")]
+	Metadata(OAuth2ServerError),
+
+	/// The metadata of the server does not advertise the issuer identifier
+	/// of the mount point.
+	#[error(transparent)]
+	IssuerMismatch(#[from] IssuerMismatch),
+}
+
+/// Location the OAuth 2.0 routes are served from, derived from the issuer
+/// identifier of the authorization server.
+///
+/// Issuers with a path component (e.g. `https://example.com/auth`) serve
+/// their endpoints under this path (`/auth/authorize`, `/auth/token`), and
+/// their metadata with the well-known path inserted before it
+/// (`/.well-known/oauth-authorization-server/auth`), as required by
+/// [RFC 8414 Section 3](https://datatracker.ietf.org/doc/html/rfc8414#section-3).
+///
+/// See [`OAuth2Router::oauth2_routes_at`](super::OAuth2Router::oauth2_routes_at).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MountPoint {
+	issuer: IssuerBuf,
+}
+
+impl MountPoint {
+	/// Creates the mount point of the given issuer identifier.
+	pub fn new(issuer: IssuerBuf) -> Self {
+		Self { issuer }
+	}
+
+	/// Returns the issuer identifier.
+	pub fn issuer(&self) -> &Issuer {
+		&self.issuer
+	}
+
+	/// Returns the path prefix of the routes, without trailing slash.
+	///
+	/// Empty if the issuer has no path component.
+	pub fn path_prefix(&self) -> &str {
+		self.issuer.as_uri().path().as_str().trim_end_matches('/')
+	}
+
+	/// Returns the path of the route with the given path relative to the
+	/// mount point (e.g. `/token`).
+	pub fn path(&self, path: &str) -> String {
+		format!("{}{path}", self.path_prefix())
+	}
+
+	/// Returns the path of the authorization server metadata route.
+	pub fn well_known_path(&self) -> String {
+		format!("{WELL_KNOWN_PATH}{}", self.path_prefix())
+	}
+
+	/// Returns the URI of the endpoint with the given path relative to the
+	/// mount point, to advertise in the metadata (e.g. `token_endpoint`).
+	pub fn endpoint_uri(&self, path: &str) -> UriBuf {
+		let mut uri = self.issuer.as_uri().to_owned();
+		// UNWRAP SAFETY: the path prefix comes from a valid URI.
+		uri.set_path(self.path(path).as_str().try_into().unwrap());
+		uri
+	}
+
+	/// Checks that the given metadata advertises the issuer identifier of
+	/// this mount point.
+	pub fn check<P>(
+		&self,
+		metadata: &AuthorizationServerMetadata<P>,
+	) -> Result<(), IssuerMismatch> {
+		if metadata.issuer == self.issuer {
+			Ok(())
+		} else {
+			Err(IssuerMismatch {
+				expected: self.issuer.clone(),
+				found: metadata.issuer.clone(),
+			})
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{borrow::Cow, sync::Arc};
+
+	use axum::{
+		body::Body,
+		http::{Method, StatusCode},
+	};
+
+	use crate::{
+		endpoints::nonce::NonceResponse,
+		issuer,
+		server::axum::{NonceRouter, NonceServer, OAuth2Router, OAuth2Server},
+		util::NoExtension,
+	};
+
+	use super::*;
+
+	struct Server(AuthorizationServerMetadata);
+
+	impl OAuth2Server for Server {
+		type Metadata = NoExtension;
+		type AuthorizationRequest = NoExtension;
+		type TokenRequest = NoExtension;
+		type TokenResponse = ();
+
+		async fn metadata(
+			&self,
+		) -> Result<Cow<'_, AuthorizationServerMetadata>, OAuth2ServerError> {
+			Ok(Cow::Borrowed(&self.0))
+		}
+
+		async fn token(&self, _: NoExtension) -> Result<(), OAuth2ServerError> {
+			Err(OAuth2ServerError::InvalidGrant)
+		}
+	}
+
+	impl NonceServer for Server {
+		async fn issue_nonce(&self) -> Result<NonceResponse, OAuth2ServerError> {
+			Ok(NonceResponse::new("wKI4LT17ac15ES9bw8ac4".to_owned()))
+		}
+	}
+
+	async fn status(router: &mut axum::Router, method: Method, uri: &str) -> StatusCode {
+		use tower_service::Service;
+
+		let request = axum::http::Request::builder()
+			.method(method)
+			.uri(uri)
+			.body(Body::empty())
+			.unwrap();
+		router.call(request).await.unwrap().status()
+	}
+
+	#[tokio::test]
+	async fn mounted_routes() {
+		let server = Server(AuthorizationServerMetadata::new(
+			issuer!("https://server.example.com/auth").to_owned(),
+		));
+		let mount_point = MountPoint::new(server.0.issuer.clone());
+
+		let mut router = axum::Router::new()
+			.nonce_route_at(&mount_point)
+			.oauth2_routes_at(&server, &mount_point)
+			.await
+			.unwrap()
+			.with_state(Arc::new(server));
+
+		let well_known = "/.well-known/oauth-authorization-server/auth";
+		assert_eq!(
+			status(&mut router, Method::GET, well_known).await,
+			StatusCode::OK
+		);
+		assert_eq!(
+			status(&mut router, Method::POST, "/auth/nonce").await,
+			StatusCode::OK
+		);
+		assert_eq!(
+			status(&mut router, Method::POST, "/nonce").await,
+			StatusCode::NOT_FOUND
+		);
+		assert_eq!(
+			status(&mut router, Method::POST, "/token").await,
+			StatusCode::NOT_FOUND
+		);
+	}
+
+	#[tokio::test]
+	async fn mount_issuer_mismatch() {
+		let server = Server(AuthorizationServerMetadata::new(
+			issuer!("https://server.example.com").to_owned(),
+		));
+		let mount_point = MountPoint::new(issuer!("https://server.example.com/auth").to_owned());
+
+		let result = axum::Router::<Arc<Server>>::new()
+			.oauth2_routes_at(&server, &mount_point)
+			.await;
+		assert!(matches!(result, Err(MountError::IssuerMismatch(_))));
+	}
+
+	#[test]
+	fn paths() {
+		let root = MountPoint::new(issuer!("https://server.example.com").to_owned());
+		assert_eq!(root.path("/token"), "/token");
+		assert_eq!(
+			root.well_known_path(),
+			"/.well-known/oauth-authorization-server"
+		);
+
+		let nested = MountPoint::new(issuer!("https://server.example.com/auth/").to_owned());
+		assert_eq!(nested.path("/token"), "/auth/token");
+		assert_eq!(
+			nested.well_known_path(),
+			"/.well-known/oauth-authorization-server/auth"
+		);
+		assert_eq!(
+			nested.endpoint_uri("/token").as_str(),
+			"https://server.example.com/auth/token"
+		);
+	}
+
+	#[test]
+	fn issuer_mismatch() {
+		let mount_point = MountPoint::new(issuer!("https://server.example.com/auth").to_owned());
+		let metadata =
+			AuthorizationServerMetadata::<NoExtension>::new(mount_point.issuer().to_owned());
+		assert_eq!(mount_point.check(&metadata), Ok(()));
+
+		let metadata = AuthorizationServerMetadata::<NoExtension>::new(
+			issuer!("https://server.example.com").to_owned(),
+		);
+		assert!(mount_point.check(&metadata).is_err());
+	}
+}
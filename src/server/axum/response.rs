@@ -0,0 +1,190 @@
+//! Responses carrying credentials.
+//!
+//! Responses including tokens or other sensitive information, such as those
+//! of the token and introspection endpoints, must not be cached, as required
+//! by [RFC 6749 Section 5.1](https://datatracker.ietf.org/doc/html/rfc6749#section-5.1).
+//! They are built with [`SecureJson`], while public responses, such as the
+//! authorization server metadata, may be cached.
+use axum::{
+	body::Body,
+	http::{
+		StatusCode,
+		header::{CACHE_CONTROL, CONTENT_TYPE, PRAGMA, WWW_AUTHENTICATE},
+		response::Builder,
+	},
+	response::{IntoResponse, Response},
+};
+use serde::Serialize;
+
+use crate::{
+	endpoints::token::{TokenResponse, TokenType},
+	server::ServerResult,
+	transport::APPLICATION_JSON,
+};
+
+/// JSON response that must not be cached.
+///
+/// Responds with a `200 OK` status, which can be overridden by responding
+/// with a `(StatusCode, SecureJson<T>)` pair, and the `Cache-Control:
+/// no-store` and `Pragma: no-cache` headers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SecureJson<T>(pub T);
+
+impl<T> IntoResponse for SecureJson<T>
+where
+	T: Serialize,
+{
+	fn into_response(self) -> Response {
+		json_response(
+			Response::builder()
+				.status(StatusCode::OK)
+				.header(CACHE_CONTROL, "no-store")
+				.header(PRAGMA, "no-cache"),
+			&self.0,
+		)
+	}
+}
+
+/// Body of the responses replacing those whose payload cannot be serialized.
+const SERVER_ERROR_BODY: &str = r#"{"error":"server_error"}"#;
+
+/// Completes the given response with a JSON body.
+///
+/// Payloads that cannot be represented in JSON, such as extensions including
+/// maps with non-string keys, do not panic the handler: the failure is logged
+/// and a `500 Internal Server Error` response with a `server_error` code is
+/// returned instead.
+pub(crate) fn json_response<T>(response: Builder, payload: &T) -> Response
+where
+	T: ?Sized + Serialize,
+{
+	match serde_json::to_vec(payload) {
+		Ok(body) => response
+			.header(CONTENT_TYPE, &APPLICATION_JSON)
+			.body(Body::from(body))
+			.unwrap(),
+		Err(e) => {
+			log::error!(
+				"unable to serialize `{}` response: {e}",
+				std::any::type_name::<T>()
+			);
+
+			Response::builder()
+				.status(StatusCode::INTERNAL_SERVER_ERROR)
+				.header(CONTENT_TYPE, &APPLICATION_JSON)
+				.header(CACHE_CONTROL, "no-store")
+				.body(Body::from(SERVER_ERROR_BODY))
+				.unwrap()
+		}
+	}
+}
+
+impl<T, E> IntoResponse for TokenResponse<T, E>
+where
+	T: TokenType,
+	E: Serialize,
+{
+	fn into_response(self) -> Response {
+		SecureJson(self).into_response()
+	}
+}
+
+/// Responds with the success payload, or with the error response in a JSON
+/// body, with a `400 Bad Request` status, or `401 Unauthorized` for
+/// `invalid_client` errors.
+impl<T, E> IntoResponse for ServerResult<T, E>
+where
+	T: IntoResponse,
+	E: Serialize,
+{
+	fn into_response(self) -> Response {
+		match self {
+			Self::Ok(value) => value.into_response(),
+			Self::Err(error) => {
+				let invalid_client =
+					serde_json::to_value(&error.error).is_ok_and(|code| code == "invalid_client");
+
+				if invalid_client {
+					(
+						StatusCode::UNAUTHORIZED,
+						[(WWW_AUTHENTICATE, "Basic")],
+						SecureJson(error),
+					)
+						.into_response()
+				} else {
+					(StatusCode::BAD_REQUEST, SecureJson(error)).into_response()
+				}
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeMap;
+
+	use crate::{
+		AccessTokenBuf,
+		endpoints::introspection::IntrospectionResponse,
+		server::{ErrorCode, ErrorResponse, TokenError},
+		util::NoExtension,
+	};
+
+	use super::*;
+
+	#[test]
+	fn token_response() {
+		let response = TokenResponse::new(
+			AccessTokenBuf::new("2YotnFZFEjr1zCsicMWpAA".to_owned()).unwrap(),
+			"Bearer".to_owned(),
+			NoExtension {},
+		)
+		.into_response();
+
+		assert_eq!(response.status(), StatusCode::OK);
+		assert_eq!(response.headers()[CONTENT_TYPE], APPLICATION_JSON);
+		assert_eq!(response.headers()[CACHE_CONTROL], "no-store");
+		assert_eq!(response.headers()[PRAGMA], "no-cache");
+	}
+
+	#[test]
+	fn server_result() {
+		let error = |code: &str| -> ServerResult<TokenResponse> {
+			ServerResult::Err(ErrorResponse::new(code.to_owned(), None, None))
+		};
+
+		let response = error("invalid_grant").into_response();
+		assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+		assert_eq!(response.headers()[CACHE_CONTROL], "no-store");
+
+		let response = error("invalid_client").into_response();
+		assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+		assert_eq!(response.headers()[WWW_AUTHENTICATE], "Basic");
+	}
+
+	#[test]
+	fn secure_responses() {
+		for response in [
+			IntrospectionResponse::active(NoExtension {}).into_response(),
+			IntrospectionResponse::<NoExtension>::inactive().into_response(),
+			TokenError::new(ErrorCode::InvalidGrant).into_response(),
+		] {
+			assert_eq!(response.headers()[CACHE_CONTROL], "no-store");
+			assert_eq!(response.headers()[PRAGMA], "no-cache");
+		}
+	}
+
+	#[tokio::test]
+	async fn unserializable_response() {
+		let payload = BTreeMap::from([((0u8, 1u8), "non-string key")]);
+		let response = SecureJson(payload).into_response();
+		assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+		assert_eq!(response.headers()[CONTENT_TYPE], APPLICATION_JSON);
+
+		let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+			.await
+			.unwrap();
+		let error: ErrorResponse<ErrorCode> = serde_json::from_slice(&body).unwrap();
+		assert_eq!(error.error, ErrorCode::ServerError);
+	}
+}
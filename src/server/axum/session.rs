@@ -0,0 +1,321 @@
+//! Authorization flow state stored in [`tower-sessions`] sessions.
+//!
+//! Authorization endpoints usually interrupt the flow to authenticate the
+//! resource owner and ask for their consent, on separate pages. An
+//! [`AuthorizationSession`] keeps the pending authorization request and the
+//! authenticated user in the session of the user-agent, until the consent is
+//! checked against a [`ConsentStore`] and the authorization code issued.
+//!
+//! [`tower-sessions`]: https://docs.rs/tower-sessions
+use std::time::{Duration, SystemTime};
+
+use axum::{extract::FromRequestParts, http::request::Parts};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_with::{TimestampSeconds, serde_as, skip_serializing_none};
+use tower_sessions::{Session, session::Error as SessionError};
+
+use crate::{
+	ClientId, Scope, Stateful,
+	server::consent::{ConsentDiff, ConsentStore},
+	time::Clock,
+};
+
+/// Session key of the pending authorization request.
+pub const PENDING_AUTHORIZATION_KEY: &str = "open_auth2.pending_authorization";
+
+/// Session key of the authenticated user.
+pub const AUTHENTICATED_USER_KEY: &str = "open_auth2.authenticated_user";
+
+/// Default lifetime of pending authorization requests.
+///
+/// Matches the maximum authorization code lifetime of 10 minutes recommended
+/// by [RFC 6749 Section 4.1.2](https://datatracker.ietf.org/doc/html/rfc6749#section-4.1.2).
+pub const DEFAULT_PENDING_AUTHORIZATION_LIFETIME: Duration = Duration::from_secs(600);
+
+/// Authorization request waiting for the resource owner to log in or
+/// consent.
+#[serde_as]
+#[derive(Serialize, Deserialize)]
+struct PendingAuthorization<R> {
+	request: R,
+
+	#[serde_as(as = "TimestampSeconds<i64>")]
+	expires_at: SystemTime,
+}
+
+/// Resource owner authenticated in the session.
+#[serde_as]
+#[skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthenticatedUser {
+	/// Identifier of the resource owner, as used in the [`ConsentStore`].
+	pub subject: String,
+
+	/// Time at which the resource owner authenticated, serialized as a Unix
+	/// timestamp in seconds.
+	#[serde_as(as = "TimestampSeconds<i64>")]
+	pub auth_time: SystemTime,
+
+	/// Authentication Context Class Reference satisfied by the
+	/// authentication, if any.
+	pub acr: Option<String>,
+
+	/// Authentication methods used, if known.
+	pub amr: Option<Vec<String>>,
+}
+
+impl AuthenticatedUser {
+	/// Creates a new user authenticated now.
+	pub fn new(subject: String, clock: &impl Clock) -> Self {
+		Self {
+			subject,
+			auth_time: clock.now(),
+			acr: None,
+			amr: None,
+		}
+	}
+
+	/// Sets the Authentication Context Class Reference.
+	pub fn with_acr(self, acr: String) -> Self {
+		Self {
+			acr: Some(acr),
+			..self
+		}
+	}
+
+	/// Sets the authentication methods used.
+	pub fn with_amr(self, amr: Vec<String>) -> Self {
+		Self {
+			amr: Some(amr),
+			..self
+		}
+	}
+
+	/// Compares the `requested` scope to the scopes previously granted by
+	/// this user to the given client.
+	pub async fn consent<C>(
+		&self,
+		store: &C,
+		client_id: &ClientId,
+		requested: &Scope,
+	) -> Result<ConsentDiff, C::Error>
+	where
+		C: ConsentStore,
+	{
+		store.consent(&self.subject, client_id, requested).await
+	}
+}
+
+/// Authorization flow state stored in a [`Session`].
+///
+/// Extracted from requests going through a `SessionManagerLayer`. The
+/// authorization endpoint saves the request with
+/// [`save_pending`](Self::save_pending) before redirecting to the login or
+/// consent page, which reads it back with [`pending`](Self::pending). Once
+/// the consent is granted, the request is removed with
+/// [`take_pending`](Self::take_pending) so that it cannot be used to issue
+/// another authorization code.
+///
+/// Requires the `tower-sessions` feature.
+#[derive(Debug, Clone)]
+pub struct AuthorizationSession {
+	session: Session,
+	lifetime: Duration,
+}
+
+impl AuthorizationSession {
+	/// Wraps the given session, keeping pending authorization requests for
+	/// [`DEFAULT_PENDING_AUTHORIZATION_LIFETIME`].
+	pub fn new(session: Session) -> Self {
+		Self {
+			session,
+			lifetime: DEFAULT_PENDING_AUTHORIZATION_LIFETIME,
+		}
+	}
+
+	/// Sets the lifetime of pending authorization requests.
+	pub fn with_lifetime(self, lifetime: Duration) -> Self {
+		Self { lifetime, ..self }
+	}
+
+	/// Returns the underlying session.
+	pub fn session(&self) -> &Session {
+		&self.session
+	}
+
+	/// Saves the authorization request, waiting for the resource owner to
+	/// log in or consent.
+	///
+	/// Replaces any previously pending request.
+	pub async fn save_pending<T>(
+		&self,
+		request: &Stateful<T>,
+		clock: &impl Clock,
+	) -> Result<(), SessionError>
+	where
+		T: Serialize,
+	{
+		self.session
+			.insert(
+				PENDING_AUTHORIZATION_KEY,
+				PendingAuthorization {
+					request,
+					expires_at: clock.deadline_in(self.lifetime),
+				},
+			)
+			.await
+	}
+
+	/// Returns the pending authorization request, unless it expired.
+	///
+	/// Expired requests are removed from the session.
+	pub async fn pending<T>(&self, clock: &impl Clock) -> Result<Option<Stateful<T>>, SessionError>
+	where
+		T: DeserializeOwned,
+	{
+		let pending = self
+			.session
+			.get::<PendingAuthorization<Stateful<T>>>(PENDING_AUTHORIZATION_KEY)
+			.await?;
+
+		match pending {
+			Some(pending) if pending.expires_at > clock.now() => Ok(Some(pending.request)),
+			Some(_) => {
+				self.session.remove_value(PENDING_AUTHORIZATION_KEY).await?;
+				Ok(None)
+			}
+			None => Ok(None),
+		}
+	}
+
+	/// Removes and returns the pending authorization request, unless it
+	/// expired.
+	pub async fn take_pending<T>(
+		&self,
+		clock: &impl Clock,
+	) -> Result<Option<Stateful<T>>, SessionError>
+	where
+		T: DeserializeOwned,
+	{
+		let pending = self
+			.session
+			.remove::<PendingAuthorization<Stateful<T>>>(PENDING_AUTHORIZATION_KEY)
+			.await?;
+
+		Ok(pending
+			.filter(|pending| pending.expires_at > clock.now())
+			.map(|pending| pending.request))
+	}
+
+	/// Records the authenticated user in the session.
+	///
+	/// The session identifier is renewed to prevent session fixation.
+	pub async fn log_in(&self, user: &AuthenticatedUser) -> Result<(), SessionError> {
+		self.session.cycle_id().await?;
+		self.session.insert(AUTHENTICATED_USER_KEY, user).await
+	}
+
+	/// Returns the user authenticated in the session, if any.
+	pub async fn user(&self) -> Result<Option<AuthenticatedUser>, SessionError> {
+		self.session.get(AUTHENTICATED_USER_KEY).await
+	}
+
+	/// Deletes the session, including the authenticated user and pending
+	/// authorization request.
+	pub async fn log_out(&self) -> Result<(), SessionError> {
+		self.session.flush().await
+	}
+}
+
+impl<S> FromRequestParts<S> for AuthorizationSession
+where
+	S: Send + Sync,
+{
+	type Rejection = <Session as FromRequestParts<S>>::Rejection;
+
+	async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+		Session::from_request_parts(parts, state)
+			.await
+			.map(Self::new)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+
+	use tower_sessions::MemoryStore;
+
+	use crate::{
+		ClientIdBuf, client_id, scope, server::consent::ConsentMemoryStore, time::MockClock,
+	};
+
+	use super::*;
+
+	#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+	struct Request {
+		client_id: ClientIdBuf,
+		scope: String,
+	}
+
+	fn session() -> AuthorizationSession {
+		AuthorizationSession::new(Session::new(None, Arc::new(MemoryStore::default()), None))
+	}
+
+	#[tokio::test]
+	async fn pending_authorization() {
+		let clock = MockClock::from_unix_timestamp(0);
+		let session = session();
+		let request = Stateful {
+			state: None,
+			value: Request {
+				client_id: client_id!("s6BhdRkqt3").to_owned(),
+				scope: "openid email".to_owned(),
+			},
+		};
+
+		assert_eq!(session.pending::<Request>(&clock).await.unwrap(), None);
+
+		session.save_pending(&request, &clock).await.unwrap();
+		assert_eq!(
+			session.pending(&clock).await.unwrap().as_ref(),
+			Some(&request)
+		);
+		assert_eq!(
+			session.take_pending(&clock).await.unwrap().as_ref(),
+			Some(&request)
+		);
+		assert_eq!(session.pending::<Request>(&clock).await.unwrap(), None);
+
+		session.save_pending(&request, &clock).await.unwrap();
+		clock.advance(DEFAULT_PENDING_AUTHORIZATION_LIFETIME);
+		assert_eq!(session.pending::<Request>(&clock).await.unwrap(), None);
+		assert!(session.session().is_empty().await);
+	}
+
+	#[tokio::test]
+	async fn authenticated_user() {
+		let clock = MockClock::from_unix_timestamp(0);
+		let session = session();
+		assert_eq!(session.user().await.unwrap(), None);
+
+		let user = AuthenticatedUser::new("alice".to_owned(), &clock).with_acr("1".to_owned());
+		session.log_in(&user).await.unwrap();
+		assert_eq!(session.user().await.unwrap().as_ref(), Some(&user));
+
+		let store = ConsentMemoryStore::new();
+		let client_id = client_id!("s6BhdRkqt3");
+		store
+			.grant("alice", client_id, scope!("openid"))
+			.await
+			.unwrap();
+		let diff = user
+			.consent(&store, client_id, scope!("openid email"))
+			.await
+			.unwrap();
+		assert_eq!(diff.required.as_deref(), Some(scope!("email")));
+
+		session.log_out().await.unwrap();
+		assert_eq!(session.user().await.unwrap(), None);
+	}
+}
@@ -0,0 +1,555 @@
+//! Multi-tenant authorization servers.
+//!
+//! A single axum application can host several authorization servers, each
+//! with its own issuer identifier and metadata, called tenants.
+use std::{borrow::Cow, future::Future, sync::Arc};
+
+use axum::{
+	extract::{FromRequestParts, Path, RawForm, Request, State},
+	http::{StatusCode, header::HOST, request::Parts},
+	response::{IntoResponse, Response},
+	routing::{get, post},
+};
+use iref::UriBuf;
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{
+	ClientId, CodeBuf, RedirectUriPolicy, Stateful, client::ClientType, ext::pkce::PkcePolicy,
+	server::AuthorizationServerMetadata,
+};
+
+use super::{
+	AuthorizationContext, AuthorizationHooks, AuthorizeInterrupt, ErrorCode, OAuth2ServerError,
+	SecureJson, authorization_client_id, authorization_parameters, authorize_with_hooks,
+	check_authorization_context, parse_token_request,
+};
+
+/// Location of the tenant identifier in incoming requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TenantSource {
+	/// The tenant identifier is the first path segment of the endpoints
+	/// (`/{tenant}/authorize`, `/{tenant}/token`).
+	///
+	/// Following [RFC 8414 Section 3.1], the metadata of the tenant whose
+	/// issuer is `https://example.com/{tenant}` is served under
+	/// `/.well-known/oauth-authorization-server/{tenant}`.
+	///
+	/// [RFC 8414 Section 3.1]: https://datatracker.ietf.org/doc/html/rfc8414#section-3.1
+	Path,
+
+	/// The tenant identifier is the value of the `Host` header, or the
+	/// authority of the request URI if absent.
+	///
+	/// Endpoints are served at the same paths as
+	/// [`OAuth2Router`](super::OAuth2Router).
+	Host,
+}
+
+/// Authorization server hosting several tenants.
+///
+/// This is the multi-tenant counterpart of
+/// [`OAuth2Server`](super::OAuth2Server), where every operation receives the
+/// tenant resolved from the request.
+pub trait OAuth2TenantServer: Sized + Send + Sync + 'static {
+	/// Location of the tenant identifier in incoming requests.
+	const TENANT_SOURCE: TenantSource;
+
+	type Tenant: Send + Sync;
+	type Metadata: Clone + Serialize;
+	type AuthorizationRequest: Send + Sync + DeserializeOwned;
+	type TokenRequest: Send + DeserializeOwned;
+	type TokenResponse: Serialize;
+
+	/// PKCE requirements enforced at the authorization endpoint.
+	///
+	/// Defaults to [`PkcePolicy::RequiredForPublicClients`], following the
+	/// OAuth 2.0 Security Best Current Practice.
+	const PKCE_POLICY: PkcePolicy = PkcePolicy::RequiredForPublicClients;
+
+	/// Returns the type of the given client of a tenant.
+	///
+	/// Defaults to [`ClientType::Public`].
+	fn client_type(
+		&self,
+		_tenant: &Self::Tenant,
+		_client_id: &ClientId,
+	) -> impl Send + Future<Output = ClientType> {
+		std::future::ready(ClientType::Public)
+	}
+
+	/// Returns the redirection URIs registered by the given client of a
+	/// tenant, or `None` if the client is unknown.
+	///
+	/// Defaults to `None`. See
+	/// [`OAuth2Server::redirect_uris`](super::OAuth2Server::redirect_uris).
+	fn redirect_uris(
+		&self,
+		_tenant: &Self::Tenant,
+		_client_id: &ClientId,
+	) -> impl Send + Future<Output = Option<Vec<UriBuf>>> {
+		std::future::ready(None)
+	}
+
+	/// Returns the rules applied to the redirection URIs of the given client
+	/// of a tenant.
+	///
+	/// Defaults to [`RedirectUriPolicy::Generic`]. See
+	/// [`OAuth2Server::redirect_uri_policy`](super::OAuth2Server::redirect_uri_policy).
+	fn redirect_uri_policy(
+		&self,
+		_tenant: &Self::Tenant,
+		_client_id: &ClientId,
+	) -> impl Send + Future<Output = RedirectUriPolicy> {
+		std::future::ready(RedirectUriPolicy::Generic)
+	}
+
+	/// Resolves the tenant from its identifier.
+	///
+	/// Requests for an unknown tenant are answered with `404 Not Found`.
+	fn tenant(&self, id: &str) -> impl Send + Future<Output = Option<Self::Tenant>>;
+
+	/// Returns the metadata of the given tenant.
+	///
+	/// The `issuer` of the returned metadata must be the issuer identifier
+	/// of the tenant, from which the well-known metadata URI is derived.
+	fn metadata<'a>(
+		&'a self,
+		tenant: &'a Self::Tenant,
+	) -> impl Send
+	+ Future<
+		Output = Result<Cow<'a, AuthorizationServerMetadata<Self::Metadata>>, OAuth2ServerError>,
+	>;
+
+	/// Authenticates the resource owner of an authorization request made to
+	/// a tenant.
+	///
+	/// Defaults to denying the request with `access_denied`. See
+	/// [`OAuth2Server::authenticate_user`](super::OAuth2Server::authenticate_user).
+	fn authenticate_user(
+		&self,
+		_tenant: &Self::Tenant,
+		_context: &AuthorizationContext,
+		_request: &Stateful<Self::AuthorizationRequest>,
+	) -> impl Send + Future<Output = Result<String, AuthorizeInterrupt>> {
+		std::future::ready(Err(AuthorizeInterrupt::deny(ErrorCode::AccessDenied)))
+	}
+
+	/// Checks that the resource owner identified by `subject` consents to
+	/// the authorization request made to a tenant.
+	///
+	/// Defaults to consenting. See
+	/// [`OAuth2Server::check_consent`](super::OAuth2Server::check_consent).
+	fn check_consent(
+		&self,
+		_tenant: &Self::Tenant,
+		_subject: &str,
+		_context: &AuthorizationContext,
+		_request: &Stateful<Self::AuthorizationRequest>,
+	) -> impl Send + Future<Output = Result<(), AuthorizeInterrupt>> {
+		std::future::ready(Ok(()))
+	}
+
+	/// Issues an authorization code for the request made to a tenant, on
+	/// behalf of the resource owner identified by `subject`.
+	///
+	/// Defaults to failing with `server_error`. See
+	/// [`OAuth2Server::issue_code`](super::OAuth2Server::issue_code).
+	fn issue_code(
+		&self,
+		_tenant: &Self::Tenant,
+		_subject: &str,
+		_context: &AuthorizationContext,
+		_request: &Stateful<Self::AuthorizationRequest>,
+	) -> impl Send + Future<Output = Result<CodeBuf, AuthorizeInterrupt>> {
+		std::future::ready(Err(AuthorizeInterrupt::deny(ErrorCode::ServerError)))
+	}
+
+	/// Answers an authorization request made to a tenant.
+	///
+	/// Defaults to calling [`authenticate_user`](Self::authenticate_user),
+	/// [`check_consent`](Self::check_consent) and
+	/// [`issue_code`](Self::issue_code), like
+	/// [`OAuth2Server::authorize`](super::OAuth2Server::authorize).
+	fn authorize(
+		&self,
+		tenant: &Self::Tenant,
+		context: AuthorizationContext,
+		request: Stateful<Self::AuthorizationRequest>,
+	) -> impl Send + Future<Output = impl IntoResponse> {
+		async move { authorize_with_hooks(TenantHooks(self, tenant), context, request).await }
+	}
+
+	fn token(
+		&self,
+		tenant: &Self::Tenant,
+		token_request: Self::TokenRequest,
+	) -> impl Send + Future<Output = Result<Self::TokenResponse, OAuth2ServerError>>;
+}
+
+/// Authorization hooks of a tenant of an [`OAuth2TenantServer`].
+struct TenantHooks<'a, S: OAuth2TenantServer>(&'a S, &'a S::Tenant);
+
+impl<S: OAuth2TenantServer> AuthorizationHooks for TenantHooks<'_, S> {
+	type Request = S::AuthorizationRequest;
+
+	fn authenticate_user(
+		&self,
+		context: &AuthorizationContext,
+		request: &Stateful<Self::Request>,
+	) -> impl Send + Future<Output = Result<String, AuthorizeInterrupt>> {
+		self.0.authenticate_user(self.1, context, request)
+	}
+
+	fn check_consent(
+		&self,
+		subject: &str,
+		context: &AuthorizationContext,
+		request: &Stateful<Self::Request>,
+	) -> impl Send + Future<Output = Result<(), AuthorizeInterrupt>> {
+		self.0.check_consent(self.1, subject, context, request)
+	}
+
+	fn issue_code(
+		&self,
+		subject: &str,
+		context: &AuthorizationContext,
+		request: &Stateful<Self::Request>,
+	) -> impl Send + Future<Output = Result<CodeBuf, AuthorizeInterrupt>> {
+		self.0.issue_code(self.1, subject, context, request)
+	}
+}
+
+pub trait OAuth2TenantRouter<S> {
+	fn oauth2_tenant_routes(self) -> Self;
+}
+
+impl<S: OAuth2TenantServer> OAuth2TenantRouter<S> for axum::Router<Arc<S>> {
+	fn oauth2_tenant_routes(self) -> Self {
+		match S::TENANT_SOURCE {
+			TenantSource::Path => self
+				.route(
+					"/.well-known/oauth-authorization-server/{tenant}",
+					get(metadata::<S>),
+				)
+				.route(
+					"/{tenant}/authorize",
+					get(authorize::<S>).post(authorize::<S>),
+				)
+				.route("/{tenant}/token", post(token::<S>)),
+			TenantSource::Host => self
+				.route(
+					"/.well-known/oauth-authorization-server",
+					get(metadata::<S>),
+				)
+				.route("/authorize", get(authorize::<S>).post(authorize::<S>))
+				.route("/token", post(token::<S>)),
+		}
+	}
+}
+
+/// Tenant resolved from the request, according to
+/// [`OAuth2TenantServer::TENANT_SOURCE`].
+struct Tenant<S: OAuth2TenantServer>(S::Tenant);
+
+impl<S> FromRequestParts<Arc<S>> for Tenant<S>
+where
+	S: OAuth2TenantServer,
+{
+	type Rejection = Response;
+
+	async fn from_request_parts(parts: &mut Parts, server: &Arc<S>) -> Result<Self, Response> {
+		let id = match S::TENANT_SOURCE {
+			TenantSource::Path => {
+				let Path(id) = Path::<String>::from_request_parts(parts, server)
+					.await
+					.map_err(IntoResponse::into_response)?;
+				id
+			}
+			TenantSource::Host => parts
+				.headers
+				.get(HOST)
+				.and_then(|value| value.to_str().ok())
+				.or_else(|| parts.uri.authority().map(|authority| authority.as_str()))
+				.ok_or_else(|| StatusCode::NOT_FOUND.into_response())?
+				.to_owned(),
+		};
+
+		server
+			.tenant(&id)
+			.await
+			.map(Self)
+			.ok_or_else(|| StatusCode::NOT_FOUND.into_response())
+	}
+}
+
+/// Authorization Server Metadata endpoint.
+async fn metadata<S>(State(server): State<Arc<S>>, Tenant(tenant): Tenant<S>) -> Response
+where
+	S: OAuth2TenantServer,
+{
+	server
+		.metadata(&tenant)
+		.await
+		.map(|metadata| metadata.as_ref().into_response())
+		.into_response()
+}
+
+/// Authorization Request endpoint.
+///
+/// Requests are checked like with the
+/// [`AuthorizeRequest`](super::AuthorizeRequest) extractor.
+async fn authorize<S>(
+	State(server): State<Arc<S>>,
+	Tenant(tenant): Tenant<S>,
+	request: Request,
+) -> Response
+where
+	S: OAuth2TenantServer,
+{
+	let parameters = match authorization_parameters(request, &server).await {
+		Ok(parameters) => parameters,
+		Err(error) => return error.into_response(),
+	};
+
+	let client_id = authorization_client_id(&parameters);
+	let (client_type, redirect_uris, redirect_uri_policy) = match &client_id {
+		Some(client_id) => (
+			server.client_type(&tenant, client_id).await,
+			server.redirect_uris(&tenant, client_id).await,
+			server.redirect_uri_policy(&tenant, client_id).await,
+		),
+		None => (ClientType::Public, None, RedirectUriPolicy::Generic),
+	};
+
+	match check_authorization_context(
+		&parameters,
+		client_id,
+		client_type,
+		redirect_uris.as_deref(),
+		redirect_uri_policy,
+		S::PKCE_POLICY,
+	) {
+		Ok((context, request)) => server
+			.authorize(&tenant, context, request)
+			.await
+			.into_response(),
+		Err(error) => error.into_response(),
+	}
+}
+
+/// Token Request endpoint.
+async fn token<S>(
+	State(server): State<Arc<S>>,
+	Tenant(tenant): Tenant<S>,
+	RawForm(body): RawForm,
+) -> Response
+where
+	S: OAuth2TenantServer,
+{
+	match parse_token_request(&body) {
+		Ok(request) => server
+			.token(&tenant, request)
+			.await
+			.map(SecureJson)
+			.into_response(),
+		Err(error) => error.into_response(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use axum::{body::Body, http::header::LOCATION};
+	use serde::Deserialize;
+
+	use crate::{ClientIdBuf, IssuerBuf, code, util::NoExtension};
+
+	use super::*;
+
+	#[derive(Debug, Deserialize)]
+	struct Request {
+		client_id: ClientIdBuf,
+	}
+
+	struct TestTenant {
+		id: String,
+		metadata: AuthorizationServerMetadata,
+	}
+
+	/// Server hosting the `a` and `b` tenants, identified by path or by
+	/// host (`a.example.com` and `b.example.com`), whose resource owner `alice` is always logged in.
+	struct TestServer<const BY_HOST: bool>;
+
+	impl<const BY_HOST: bool> OAuth2TenantServer for TestServer<BY_HOST> {
+		const TENANT_SOURCE: TenantSource = if BY_HOST {
+			TenantSource::Host
+		} else {
+			TenantSource::Path
+		};
+
+		type Tenant = TestTenant;
+		type Metadata = NoExtension;
+		type AuthorizationRequest = Request;
+		type TokenRequest = Request;
+		type TokenResponse = ();
+
+		const PKCE_POLICY: PkcePolicy = PkcePolicy::Optional;
+
+		async fn redirect_uris(
+			&self,
+			_tenant: &TestTenant,
+			_client_id: &ClientId,
+		) -> Option<Vec<UriBuf>> {
+			Some(vec![
+				UriBuf::new(b"https://client.example.com/cb".to_vec()).unwrap(),
+			])
+		}
+
+		async fn tenant(&self, id: &str) -> Option<TestTenant> {
+			let issuer = match (BY_HOST, id) {
+				(false, "a" | "b") => format!("https://server.example.com/{id}"),
+				(true, "a.example.com" | "b.example.com") => format!("https://{id}"),
+				_ => return None,
+			};
+			Some(TestTenant {
+				id: id.to_owned(),
+				metadata: AuthorizationServerMetadata::new(IssuerBuf::new(issuer).unwrap()),
+			})
+		}
+
+		async fn metadata<'a>(
+			&'a self,
+			tenant: &'a TestTenant,
+		) -> Result<Cow<'a, AuthorizationServerMetadata>, OAuth2ServerError> {
+			Ok(Cow::Borrowed(&tenant.metadata))
+		}
+
+		async fn authenticate_user(
+			&self,
+			_tenant: &TestTenant,
+			_context: &AuthorizationContext,
+			_request: &Stateful<Request>,
+		) -> Result<String, AuthorizeInterrupt> {
+			Ok("alice".to_owned())
+		}
+
+		async fn issue_code(
+			&self,
+			tenant: &TestTenant,
+			subject: &str,
+			context: &AuthorizationContext,
+			request: &Stateful<Request>,
+		) -> Result<CodeBuf, AuthorizeInterrupt> {
+			assert_eq!(subject, "alice");
+			assert_eq!(context.client_id, request.value.client_id);
+			if tenant.id.starts_with('a') {
+				Ok(code!("SplxlOBeZQQYbYS6WxSbIA").to_owned())
+			} else {
+				Err(AuthorizeInterrupt::deny(ErrorCode::AccessDenied))
+			}
+		}
+
+		async fn token(&self, _tenant: &TestTenant, _: Request) -> Result<(), OAuth2ServerError> {
+			Err(OAuth2ServerError::InvalidGrant)
+		}
+	}
+
+	async fn call<const BY_HOST: bool>(
+		host: &str,
+		uri: &str,
+	) -> (StatusCode, Option<String>, serde_json::Value) {
+		use tower_service::Service;
+
+		let mut router = axum::Router::new()
+			.oauth2_tenant_routes()
+			.with_state(Arc::new(TestServer::<BY_HOST>));
+		let request = axum::http::Request::get(uri)
+			.header(HOST, host)
+			.body(Body::empty())
+			.unwrap();
+		let response = router.call(request).await.unwrap();
+
+		let status = response.status();
+		let location = response
+			.headers()
+			.get(LOCATION)
+			.map(|value| value.to_str().unwrap().to_owned());
+		let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+			.await
+			.unwrap();
+		let body = serde_json::from_slice(&body).unwrap_or_default();
+		(status, location, body)
+	}
+
+	#[tokio::test]
+	async fn path_tenant() {
+		let (status, _, metadata) = call::<false>(
+			"server.example.com",
+			"/.well-known/oauth-authorization-server/b",
+		)
+		.await;
+		assert_eq!(status, StatusCode::OK);
+		assert_eq!(metadata["issuer"], "https://server.example.com/b");
+
+		let (status, location, _) = call::<false>(
+			"server.example.com",
+			"/a/authorize?response_type=code&client_id=s6BhdRkqt3&state=xyz",
+		)
+		.await;
+		assert_eq!(status, StatusCode::FOUND);
+		assert_eq!(
+			location.as_deref(),
+			Some("https://client.example.com/cb?state=xyz&code=SplxlOBeZQQYbYS6WxSbIA")
+		);
+
+		let (status, location, _) = call::<false>(
+			"server.example.com",
+			"/b/authorize?response_type=code&client_id=s6BhdRkqt3&state=xyz",
+		)
+		.await;
+		assert_eq!(status, StatusCode::FOUND);
+		assert_eq!(
+			location.as_deref(),
+			Some("https://client.example.com/cb?state=xyz&error=access_denied")
+		);
+	}
+
+	#[tokio::test]
+	async fn host_tenant() {
+		let (status, _, metadata) =
+			call::<true>("a.example.com", "/.well-known/oauth-authorization-server").await;
+		assert_eq!(status, StatusCode::OK);
+		assert_eq!(metadata["issuer"], "https://a.example.com");
+
+		let (status, location, _) = call::<true>(
+			"a.example.com",
+			"/authorize?response_type=code&client_id=s6BhdRkqt3&state=xyz",
+		)
+		.await;
+		assert_eq!(status, StatusCode::FOUND);
+		assert_eq!(
+			location.as_deref(),
+			Some("https://client.example.com/cb?state=xyz&code=SplxlOBeZQQYbYS6WxSbIA")
+		);
+	}
+
+	#[tokio::test]
+	async fn unknown_tenant() {
+		let (status, ..) = call::<false>(
+			"server.example.com",
+			"/.well-known/oauth-authorization-server/c",
+		)
+		.await;
+		assert_eq!(status, StatusCode::NOT_FOUND);
+
+		let (status, ..) = call::<false>(
+			"server.example.com",
+			"/c/authorize?response_type=code&client_id=s6BhdRkqt3",
+		)
+		.await;
+		assert_eq!(status, StatusCode::NOT_FOUND);
+
+		let (status, ..) =
+			call::<true>("c.example.com", "/.well-known/oauth-authorization-server").await;
+		assert_eq!(status, StatusCode::NOT_FOUND);
+	}
+}
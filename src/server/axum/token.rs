@@ -0,0 +1,220 @@
+//! Token requests of multiple grant types.
+//!
+//! Token endpoints supporting several grant types must inspect the
+//! `grant_type` parameter before deserializing the request into the type of
+//! the grant. [`RawTokenRequest`] holds a validated token request along with
+//! its grant type, and [`TokenDispatcher`] deserializes it into the first
+//! matching [`TokenGrant`] request, or rejects it with an
+//! `unsupported_grant_type` error.
+use serde::{Deserialize, de::DeserializeOwned};
+
+use crate::{
+	grant::{GrantType, TokenGrant},
+	server::validation::{describe_violations, validate_token_request},
+};
+
+use super::{ErrorCode, TokenError};
+
+/// Token request whose grant type is known, but which is not yet
+/// deserialized.
+///
+/// Can be used as [`OAuth2Server::TokenRequest`](super::OAuth2Server::TokenRequest)
+/// to handle several grant types, with [`RawTokenRequest::dispatch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawTokenRequest {
+	grant_type: GrantType,
+	body: String,
+}
+
+impl RawTokenRequest {
+	/// Parses the raw body of a token request.
+	///
+	/// The body is checked with [`validate_token_request`]. Failures are
+	/// rejected with an `invalid_request` error naming the offending
+	/// parameters.
+	pub fn parse(body: &[u8]) -> Result<Self, TokenError> {
+		let body = std::str::from_utf8(body).map_err(|_| {
+			TokenError::new(ErrorCode::InvalidRequest)
+				.with_description("parameters are not valid UTF-8")
+		})?;
+
+		let violations = validate_token_request(body);
+		if !violations.is_empty() {
+			return Err(TokenError::new(ErrorCode::InvalidRequest)
+				.with_description(describe_violations(&violations)));
+		}
+
+		// UNWRAP SAFETY: the `grant_type` parameter was validated.
+		let grant_type = grant_type(body).unwrap();
+
+		Ok(Self {
+			grant_type,
+			body: body.to_owned(),
+		})
+	}
+
+	/// Returns the value of the `grant_type` parameter.
+	pub fn grant_type(&self) -> &GrantType {
+		&self.grant_type
+	}
+
+	/// Returns the `application/x-www-form-urlencoded` parameters of the
+	/// request.
+	pub fn as_str(&self) -> &str {
+		&self.body
+	}
+
+	/// Deserializes the request.
+	///
+	/// Failures are rejected with an `invalid_request` error.
+	pub fn deserialize<T>(&self) -> Result<T, TokenError>
+	where
+		T: DeserializeOwned,
+	{
+		serde_html_form::from_str(&self.body)
+			.map_err(|e| TokenError::new(ErrorCode::InvalidRequest).with_description(e.to_string()))
+	}
+
+	/// Starts dispatching the request according to its grant type.
+	pub fn dispatch<R>(&self) -> TokenDispatcher<'_, R> {
+		TokenDispatcher {
+			request: self,
+			result: None,
+		}
+	}
+}
+
+impl<'de> Deserialize<'de> for RawTokenRequest {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: serde::Deserializer<'de>,
+	{
+		let parameters = Vec::<(String, String)>::deserialize(deserializer)?;
+
+		let grant_type = parameters
+			.iter()
+			.find_map(|(name, value)| (name == "grant_type").then(|| value.parse()))
+			.ok_or_else(|| serde::de::Error::missing_field("grant_type"))?
+			.unwrap_or_else(|e| match e {});
+
+		let body = serde_html_form::to_string(&parameters).map_err(serde::de::Error::custom)?;
+
+		Ok(Self { grant_type, body })
+	}
+}
+
+/// Returns the grant type of the given token request parameters.
+fn grant_type(parameters: &str) -> Option<GrantType> {
+	serde_html_form::from_str::<Vec<(String, String)>>(parameters)
+		.ok()?
+		.into_iter()
+		.find_map(|(name, value)| (name == "grant_type").then(|| value.parse()))
+		.map(|grant_type| grant_type.unwrap_or_else(|e| match e {}))
+}
+
+/// Dispatcher of a [`RawTokenRequest`] to the request type of its grant.
+///
+/// Each call to [`grant`](Self::grant) registers a supported grant type. The
+/// request is deserialized into the first one matching its `grant_type`
+/// parameter, then mapped into `R`.
+///
+/// ```ignore
+/// enum Grant {
+///     Code(AuthorizationCodeTokenRequest),
+///     Refresh(RefreshTokenRequest),
+/// }
+///
+/// let grant = request
+///     .dispatch()
+///     .grant(Grant::Code)
+///     .grant(Grant::Refresh)
+///     .finish()?;
+/// ```
+pub struct TokenDispatcher<'a, R> {
+	request: &'a RawTokenRequest,
+	result: Option<Result<R, TokenError>>,
+}
+
+impl<R> TokenDispatcher<'_, R> {
+	/// Registers the grant type of `T`, whose requests are mapped into `R`
+	/// with `f`.
+	pub fn grant<T>(mut self, f: impl FnOnce(T) -> R) -> Self
+	where
+		T: TokenGrant + DeserializeOwned,
+	{
+		if self.result.is_none() && self.request.grant_type == T::GRANT_TYPE {
+			self.result = Some(self.request.deserialize().map(f));
+		}
+
+		self
+	}
+
+	/// Returns the dispatched request.
+	///
+	/// Requests of a grant type that was not registered are rejected with an
+	/// `unsupported_grant_type` error.
+	pub fn finish(self) -> Result<R, TokenError> {
+		self.result.unwrap_or_else(|| {
+			let description = format!("unsupported grant type `{}`", self.request.grant_type);
+			Err(TokenError::new(ErrorCode::UnsupportedGrantType).with_description(description))
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::grant::{
+		authorization_code::AuthorizationCodeTokenRequest, refresh::RefreshTokenRequest,
+	};
+
+	use super::*;
+
+	#[derive(Debug)]
+	enum Grant {
+		Code(AuthorizationCodeTokenRequest),
+		Refresh(RefreshTokenRequest),
+	}
+
+	fn dispatch(body: &str) -> Result<Grant, TokenError> {
+		RawTokenRequest::parse(body.as_bytes())?
+			.dispatch()
+			.grant(Grant::Code)
+			.grant(Grant::Refresh)
+			.finish()
+	}
+
+	#[test]
+	fn dispatch_grants() {
+		let grant = dispatch("grant_type=authorization_code&code=SplxlOBeZQQYbYS6WxSbIA").unwrap();
+		assert!(matches!(grant, Grant::Code(r) if r.code.as_str() == "SplxlOBeZQQYbYS6WxSbIA"));
+
+		let grant =
+			dispatch("grant_type=refresh_token&refresh_token=tGzv3JOkF0XG5Qx2TlKWIA").unwrap();
+		assert!(matches!(grant, Grant::Refresh(r) if r.refresh_token == "tGzv3JOkF0XG5Qx2TlKWIA"));
+	}
+
+	#[test]
+	fn rejected_requests() {
+		let error = dispatch("grant_type=client_credentials").unwrap_err();
+		assert_eq!(error.0.error, ErrorCode::UnsupportedGrantType);
+
+		let error = dispatch("grant_type=authorization_code").unwrap_err();
+		assert_eq!(error.0.error, ErrorCode::InvalidRequest);
+
+		let error = dispatch("code=SplxlOBeZQQYbYS6WxSbIA").unwrap_err();
+		assert_eq!(error.0.error, ErrorCode::InvalidRequest);
+	}
+
+	#[test]
+	fn deserialize_raw_request() {
+		let request: RawTokenRequest = serde_html_form::from_str(
+			"grant_type=refresh_token&refresh_token=tGzv3JOkF0XG5Qx2TlKWIA",
+		)
+		.unwrap();
+		assert_eq!(request.grant_type(), &GrantType::RefreshToken);
+		assert!(matches!(
+			request.dispatch().grant(Grant::Refresh).finish(),
+			Ok(Grant::Refresh(_))
+		));
+	}
+}
@@ -0,0 +1,469 @@
+//! Authorization code issuance and redemption.
+//!
+//! See: <https://datatracker.ietf.org/doc/html/rfc6749#section-4.1>
+//!
+//! Authorization servers store the issued codes in an [`AuthorizationCodeStore`],
+//! bound to the client, redirection URI, PKCE challenge and OpenID Connect
+//! nonce of the authorization request. At the token endpoint,
+//! [`AuthorizationCodeStore::redeem`] performs all the checks required to
+//! prevent authorization code injection, as described in
+//! [RFC 9700 Section 4.5](https://www.rfc-editor.org/rfc/rfc9700.html#section-4.5),
+//! and detects codes used more than once.
+use std::{
+	collections::HashMap,
+	sync::Mutex,
+	time::{Duration, SystemTime},
+};
+
+use iref::{Uri, UriBuf};
+
+use crate::{
+	ClientId, ClientIdBuf, Code, CodeBuf,
+	ext::pkce::{PkceCodeChallengeAndMethod, PkceCodeVerifier},
+	grant::authorization_code::AuthorizationCodeTokenRequest,
+	time::{Clock, SystemClock},
+};
+
+/// Default lifetime of authorization codes.
+pub const DEFAULT_CODE_LIFETIME: Duration = Duration::from_secs(60);
+
+/// Default duration during which a redeemed code is remembered to detect
+/// replays.
+///
+/// Matches the maximum authorization code lifetime of 10 minutes recommended
+/// by [RFC 6749 Section 4.1.2](https://datatracker.ietf.org/doc/html/rfc6749#section-4.1.2).
+pub const DEFAULT_REPLAY_WINDOW: Duration = Duration::from_secs(600);
+
+/// Parameters of the authorization request an authorization code is bound
+/// to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeBinding {
+	/// Client the code was issued to.
+	pub client_id: ClientIdBuf,
+
+	/// Redirection URI of the authorization request, if included.
+	pub redirect_uri: Option<UriBuf>,
+
+	/// PKCE challenge of the authorization request, if included.
+	pub pkce: Option<PkceCodeChallengeAndMethod>,
+
+	/// OpenID Connect `nonce` of the authorization request, if included.
+	pub nonce: Option<String>,
+}
+
+impl CodeBinding {
+	/// Creates a binding to the given client only.
+	pub fn new(client_id: ClientIdBuf) -> Self {
+		Self {
+			client_id,
+			redirect_uri: None,
+			pkce: None,
+			nonce: None,
+		}
+	}
+
+	/// Binds the code to the `redirect_uri` of the authorization request.
+	pub fn with_redirect_uri(self, redirect_uri: Option<UriBuf>) -> Self {
+		Self {
+			redirect_uri,
+			..self
+		}
+	}
+
+	/// Binds the code to the PKCE challenge of the authorization request.
+	pub fn with_pkce(self, pkce: Option<PkceCodeChallengeAndMethod>) -> Self {
+		Self { pkce, ..self }
+	}
+
+	/// Binds the code to the OpenID Connect `nonce` of the authorization
+	/// request.
+	pub fn with_nonce(self, nonce: Option<String>) -> Self {
+		Self { nonce, ..self }
+	}
+
+	/// Checks that the given exchange matches this binding.
+	fn verify(&self, exchange: &CodeExchange) -> Result<(), CodeRedemptionError> {
+		if self.client_id != *exchange.client_id {
+			return Err(CodeRedemptionError::ClientMismatch);
+		}
+
+		if self.redirect_uri.as_deref() != exchange.redirect_uri {
+			return Err(CodeRedemptionError::RedirectUriMismatch);
+		}
+
+		match (&self.pkce, exchange.code_verifier) {
+			(None, None) => Ok(()),
+			(None, Some(_)) => Err(CodeRedemptionError::UnexpectedCodeVerifier),
+			(Some(_), None) => Err(CodeRedemptionError::MissingCodeVerifier),
+			(Some(pkce), Some(verifier)) => {
+				if *pkce.method.transform(verifier) == *pkce.challenge {
+					Ok(())
+				} else {
+					Err(CodeRedemptionError::InvalidCodeVerifier)
+				}
+			}
+		}
+	}
+}
+
+/// Parameters of a token request redeeming an authorization code.
+#[derive(Debug, Clone, Copy)]
+pub struct CodeExchange<'a> {
+	/// Authenticated client, or `client_id` of a public client.
+	pub client_id: &'a ClientId,
+
+	/// The authorization code.
+	pub code: &'a Code,
+
+	/// The `redirect_uri` parameter of the token request.
+	pub redirect_uri: Option<&'a Uri>,
+
+	/// The `code_verifier` parameter of the token request.
+	pub code_verifier: Option<&'a PkceCodeVerifier>,
+}
+
+impl<'a> CodeExchange<'a> {
+	/// Creates the exchange of the given token request, made by `client_id`.
+	pub fn new(client_id: &'a ClientId, request: &'a AuthorizationCodeTokenRequest) -> Self {
+		Self {
+			client_id,
+			code: &request.code,
+			redirect_uri: request.redirect_uri.as_deref(),
+			code_verifier: None,
+		}
+	}
+
+	/// Sets the `code_verifier` parameter of the token request.
+	pub fn with_code_verifier(self, code_verifier: Option<&'a PkceCodeVerifier>) -> Self {
+		Self {
+			code_verifier,
+			..self
+		}
+	}
+}
+
+/// Authorization code successfully redeemed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedeemedCode<T> {
+	/// The authorization code, which can be used to revoke the tokens issued
+	/// in exchange if it is later [replayed](CodeRedemptionError::Replayed).
+	pub code: CodeBuf,
+
+	/// OpenID Connect `nonce` to include in the ID token, if any.
+	pub nonce: Option<String>,
+
+	/// The grant stored with the code.
+	pub grant: T,
+}
+
+/// Error returned when an authorization code cannot be redeemed.
+///
+/// All these errors are reported to the client as `invalid_grant`.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CodeRedemptionError {
+	/// The code is unknown or expired.
+	#[error("invalid authorization code")]
+	InvalidCode,
+
+	/// The code was already redeemed.
+	///
+	/// The tokens previously issued in exchange for this code *should* be
+	/// revoked.
+	#[error("authorization code already used")]
+	Replayed(CodeBuf),
+
+	/// The code was issued to another client.
+	#[error("authorization code issued to another client")]
+	ClientMismatch,
+
+	/// The redirection URI differs from the one of the authorization
+	/// request.
+	#[error("redirect URI mismatch")]
+	RedirectUriMismatch,
+
+	/// The authorization request included a PKCE challenge, but the token
+	/// request has no `code_verifier`.
+	#[error("missing code verifier")]
+	MissingCodeVerifier,
+
+	/// The token request includes a `code_verifier`, but the authorization
+	/// request had no PKCE challenge, which may indicate a PKCE downgrade
+	/// attack.
+	#[error("unexpected code verifier")]
+	UnexpectedCodeVerifier,
+
+	/// The code verifier does not match the PKCE challenge.
+	#[error("invalid code verifier")]
+	InvalidCodeVerifier,
+}
+
+/// In-memory store of authorization codes.
+///
+/// Codes expire after the configured lifetime and are removed from the store
+/// when redeemed, successfully or not, so that every code can only be used
+/// once. Successfully redeemed codes are remembered for the replay window,
+/// during which another redemption is reported as
+/// [`Replayed`](CodeRedemptionError::Replayed).
+pub struct AuthorizationCodeStore<T, K = SystemClock> {
+	codes: Mutex<Codes<T>>,
+	lifetime: Duration,
+	replay_window: Duration,
+	clock: K,
+}
+
+struct Codes<T> {
+	issued: HashMap<CodeBuf, IssuedCode<T>>,
+	redeemed: HashMap<CodeBuf, SystemTime>,
+}
+
+struct IssuedCode<T> {
+	binding: CodeBinding,
+	grant: T,
+	expires_at: SystemTime,
+}
+
+impl<T> AuthorizationCodeStore<T> {
+	/// Creates a new store with the default lifetime of
+	/// [`DEFAULT_CODE_LIFETIME`] and replay window of
+	/// [`DEFAULT_REPLAY_WINDOW`].
+	pub fn new() -> Self {
+		Self {
+			codes: Mutex::new(Codes {
+				issued: HashMap::new(),
+				redeemed: HashMap::new(),
+			}),
+			lifetime: DEFAULT_CODE_LIFETIME,
+			replay_window: DEFAULT_REPLAY_WINDOW,
+			clock: SystemClock,
+		}
+	}
+}
+
+impl<T> Default for AuthorizationCodeStore<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T, K> AuthorizationCodeStore<T, K> {
+	/// Sets the lifetime of the authorization codes.
+	pub fn with_lifetime(self, lifetime: Duration) -> Self {
+		Self { lifetime, ..self }
+	}
+
+	/// Sets the duration during which redeemed codes are remembered.
+	pub fn with_replay_window(self, replay_window: Duration) -> Self {
+		Self {
+			replay_window,
+			..self
+		}
+	}
+
+	/// Sets the clock used to expire the authorization codes.
+	pub fn with_clock<L>(self, clock: L) -> AuthorizationCodeStore<T, L> {
+		AuthorizationCodeStore {
+			codes: self.codes,
+			lifetime: self.lifetime,
+			replay_window: self.replay_window,
+			clock,
+		}
+	}
+}
+
+impl<T, K: Clock> AuthorizationCodeStore<T, K> {
+	/// Issues a new authorization code with the given binding, storing
+	/// `grant` until the code is redeemed.
+	///
+	/// Expired codes are purged from the store.
+	pub fn issue(&self, binding: CodeBinding, grant: T) -> CodeBuf {
+		let code = CodeBuf::new_random();
+		let now = self.clock.now();
+
+		let mut codes = self.codes.lock().unwrap();
+		codes.purge(now);
+		codes.issued.insert(
+			code.clone(),
+			IssuedCode {
+				binding,
+				grant,
+				expires_at: now + self.lifetime,
+			},
+		);
+
+		code
+	}
+
+	/// Redeems an authorization code, checking that it was issued to the
+	/// client, for the same redirection URI, and with a PKCE challenge
+	/// matching the code verifier.
+	pub fn redeem(&self, exchange: CodeExchange) -> Result<RedeemedCode<T>, CodeRedemptionError> {
+		let now = self.clock.now();
+		let mut codes = self.codes.lock().unwrap();
+		codes.purge(now);
+
+		let Some(issued) = codes.issued.remove(exchange.code) else {
+			return Err(if codes.redeemed.contains_key(exchange.code) {
+				CodeRedemptionError::Replayed(exchange.code.to_owned())
+			} else {
+				CodeRedemptionError::InvalidCode
+			});
+		};
+
+		issued.binding.verify(&exchange)?;
+		codes
+			.redeemed
+			.insert(exchange.code.to_owned(), now + self.replay_window);
+
+		Ok(RedeemedCode {
+			code: exchange.code.to_owned(),
+			nonce: issued.binding.nonce,
+			grant: issued.grant,
+		})
+	}
+}
+
+impl<T> Codes<T> {
+	fn purge(&mut self, now: SystemTime) {
+		self.issued.retain(|_, issued| issued.expires_at > now);
+		self.redeemed.retain(|_, until| *until > now);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use iref::uri;
+
+	use crate::{client_id, ext::pkce::PkceCodeVerifierBuf, time::MockClock};
+
+	use super::*;
+
+	fn binding() -> (CodeBinding, PkceCodeVerifierBuf) {
+		let (pkce, verifier) = PkceCodeChallengeAndMethod::new_random_sha256();
+		let binding = CodeBinding::new(client_id!("s6BhdRkqt3").to_owned())
+			.with_redirect_uri(Some(uri!("https://client.example.com/cb").to_owned()))
+			.with_pkce(Some(pkce))
+			.with_nonce(Some("n-0S6_WzA2Mj".to_owned()));
+		(binding, verifier)
+	}
+
+	#[test]
+	fn redeem() {
+		let store = AuthorizationCodeStore::new().with_clock(MockClock::from_unix_timestamp(0));
+		let (binding, verifier) = binding();
+		let code = store.issue(binding, "grant");
+
+		let request = AuthorizationCodeTokenRequest::new(
+			None,
+			code.clone(),
+			Some(uri!("https://client.example.com/cb").to_owned()),
+		);
+		let exchange = CodeExchange::new(client_id!("s6BhdRkqt3"), &request)
+			.with_code_verifier(Some(&verifier));
+
+		let redeemed = store.redeem(exchange).unwrap();
+		assert_eq!(redeemed.grant, "grant");
+		assert_eq!(redeemed.nonce.as_deref(), Some("n-0S6_WzA2Mj"));
+
+		// Replay detection.
+		assert_eq!(
+			store.redeem(exchange),
+			Err(CodeRedemptionError::Replayed(code))
+		);
+
+		store.clock.advance(DEFAULT_REPLAY_WINDOW);
+		assert_eq!(
+			store.redeem(exchange),
+			Err(CodeRedemptionError::InvalidCode)
+		);
+	}
+
+	#[test]
+	fn injection() {
+		let store = AuthorizationCodeStore::new().with_clock(MockClock::from_unix_timestamp(0));
+		let redirect_uri = uri!("https://client.example.com/cb");
+		let (_, other_verifier) = PkceCodeChallengeAndMethod::new_random_sha256();
+
+		let cases: [(&ClientId, Option<&Uri>, bool, CodeRedemptionError); 4] = [
+			(
+				client_id!("other"),
+				Some(redirect_uri),
+				true,
+				CodeRedemptionError::ClientMismatch,
+			),
+			(
+				client_id!("s6BhdRkqt3"),
+				None,
+				true,
+				CodeRedemptionError::RedirectUriMismatch,
+			),
+			(
+				client_id!("s6BhdRkqt3"),
+				Some(redirect_uri),
+				false,
+				CodeRedemptionError::MissingCodeVerifier,
+			),
+			(
+				client_id!("s6BhdRkqt3"),
+				Some(redirect_uri),
+				true,
+				CodeRedemptionError::InvalidCodeVerifier,
+			),
+		];
+
+		for (client_id, redirect_uri, with_verifier, error) in cases {
+			let (binding, _) = binding();
+			let code = store.issue(binding, ());
+			let exchange = CodeExchange {
+				client_id,
+				code: &code,
+				redirect_uri,
+				code_verifier: with_verifier.then_some(&*other_verifier),
+			};
+			assert_eq!(store.redeem(exchange), Err(error));
+
+			// Codes are consumed by failed redemptions.
+			assert_eq!(
+				store.redeem(exchange),
+				Err(CodeRedemptionError::InvalidCode)
+			);
+		}
+	}
+
+	#[test]
+	fn pkce_downgrade() {
+		let store = AuthorizationCodeStore::new();
+		let (_, verifier) = PkceCodeChallengeAndMethod::new_random_sha256();
+		let code = store.issue(CodeBinding::new(client_id!("s6BhdRkqt3").to_owned()), ());
+
+		let exchange = CodeExchange {
+			client_id: client_id!("s6BhdRkqt3"),
+			code: &code,
+			redirect_uri: None,
+			code_verifier: Some(&verifier),
+		};
+		assert_eq!(
+			store.redeem(exchange),
+			Err(CodeRedemptionError::UnexpectedCodeVerifier)
+		);
+	}
+
+	#[test]
+	fn expired_code() {
+		let store = AuthorizationCodeStore::new()
+			.with_lifetime(Duration::from_secs(10))
+			.with_clock(MockClock::from_unix_timestamp(0));
+		let code = store.issue(CodeBinding::new(client_id!("s6BhdRkqt3").to_owned()), ());
+
+		store.clock.advance(Duration::from_secs(10));
+		let exchange = CodeExchange {
+			client_id: client_id!("s6BhdRkqt3"),
+			code: &code,
+			redirect_uri: None,
+			code_verifier: None,
+		};
+		assert_eq!(
+			store.redeem(exchange),
+			Err(CodeRedemptionError::InvalidCode)
+		);
+	}
+}
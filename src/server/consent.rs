@@ -0,0 +1,192 @@
+//! User consent to the scopes requested by clients.
+//!
+//! Authorization servers may skip the consent screen when the resource owner
+//! already granted all the requested scopes to the client. A
+//! [`ConsentStore`] remembers the scopes granted to each client, and
+//! [`ConsentStore::consent`] compares them to the requested scope, so that
+//! [`OAuth2Server::check_consent`](crate::server::OAuth2Server::check_consent)
+//! implementations only ask for the scopes requiring new consent.
+use std::{collections::HashMap, convert::Infallible, future::Future, sync::Mutex};
+
+use crate::{ClientId, ClientIdBuf, Scope, ScopeBuf};
+
+/// Requested scopes, split according to the prior consent of the resource
+/// owner.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ConsentDiff {
+	/// Requested scopes already granted to the client.
+	pub granted: Option<ScopeBuf>,
+
+	/// Requested scopes requiring new consent.
+	pub required: Option<ScopeBuf>,
+}
+
+impl ConsentDiff {
+	/// Splits the `requested` scope into the scopes included in `granted`,
+	/// and those that are not.
+	pub fn new(granted: Option<&Scope>, requested: &Scope) -> Self {
+		let (already_granted, required): (Vec<_>, Vec<_>) = requested
+			.iter()
+			.partition(|token| granted.is_some_and(|granted| granted.contains(token)));
+
+		Self {
+			granted: ScopeBuf::from_tokens(already_granted),
+			required: ScopeBuf::from_tokens(required),
+		}
+	}
+
+	/// Checks whether all the requested scopes were already granted, in which
+	/// case the consent screen can be skipped.
+	pub fn is_granted(&self) -> bool {
+		self.required.is_none()
+	}
+}
+
+/// Store of the scopes granted by resource owners to clients.
+pub trait ConsentStore: Send + Sync {
+	/// Error returned by the store.
+	type Error;
+
+	/// Returns the scopes granted by the resource owner identified by
+	/// `subject` to the given client, if any.
+	fn granted_scope(
+		&self,
+		subject: &str,
+		client_id: &ClientId,
+	) -> impl Send + Future<Output = Result<Option<ScopeBuf>, Self::Error>>;
+
+	/// Records the consent of the resource owner identified by `subject` to
+	/// the given scopes, in addition to those previously granted to the
+	/// client.
+	fn grant(
+		&self,
+		subject: &str,
+		client_id: &ClientId,
+		scope: &Scope,
+	) -> impl Send + Future<Output = Result<(), Self::Error>>;
+
+	/// Compares the `requested` scope to the scopes previously granted by the
+	/// resource owner identified by `subject` to the given client.
+	fn consent(
+		&self,
+		subject: &str,
+		client_id: &ClientId,
+		requested: &Scope,
+	) -> impl Send + Future<Output = Result<ConsentDiff, Self::Error>> {
+		async move {
+			let granted = self.granted_scope(subject, client_id).await?;
+			Ok(ConsentDiff::new(granted.as_deref(), requested))
+		}
+	}
+}
+
+/// In-memory store of the scopes granted by resource owners to clients.
+#[derive(Debug, Default)]
+pub struct ConsentMemoryStore {
+	grants: Mutex<HashMap<(String, ClientIdBuf), ScopeBuf>>,
+}
+
+impl ConsentMemoryStore {
+	/// Creates a new empty store.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Forgets the scopes granted by the resource owner identified by
+	/// `subject` to the given client.
+	pub fn revoke(&self, subject: &str, client_id: &ClientId) {
+		self.grants
+			.lock()
+			.unwrap()
+			.remove(&(subject.to_owned(), client_id.to_owned()));
+	}
+}
+
+impl ConsentStore for ConsentMemoryStore {
+	type Error = Infallible;
+
+	async fn granted_scope(
+		&self,
+		subject: &str,
+		client_id: &ClientId,
+	) -> Result<Option<ScopeBuf>, Self::Error> {
+		Ok(self
+			.grants
+			.lock()
+			.unwrap()
+			.get(&(subject.to_owned(), client_id.to_owned()))
+			.cloned())
+	}
+
+	async fn grant(
+		&self,
+		subject: &str,
+		client_id: &ClientId,
+		scope: &Scope,
+	) -> Result<(), Self::Error> {
+		self.grants
+			.lock()
+			.unwrap()
+			.entry((subject.to_owned(), client_id.to_owned()))
+			.and_modify(|granted| granted.extend(scope))
+			.or_insert_with(|| scope.to_owned());
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{client_id, scope};
+
+	use super::*;
+
+	#[test]
+	fn diff() {
+		let diff = ConsentDiff::new(Some(scope!("openid profile")), scope!("openid email"));
+		assert_eq!(diff.granted.as_deref(), Some(scope!("openid")));
+		assert_eq!(diff.required.as_deref(), Some(scope!("email")));
+		assert!(!diff.is_granted());
+
+		let diff = ConsentDiff::new(None, scope!("openid"));
+		assert_eq!(diff.granted, None);
+		assert_eq!(diff.required.as_deref(), Some(scope!("openid")));
+
+		let diff = ConsentDiff::new(Some(scope!("openid email")), scope!("email"));
+		assert!(diff.is_granted());
+	}
+
+	#[tokio::test]
+	async fn memory_store() {
+		let store = ConsentMemoryStore::new();
+		let client_id = client_id!("s6BhdRkqt3");
+
+		store
+			.grant("alice", client_id, scope!("openid"))
+			.await
+			.unwrap();
+		store
+			.grant("alice", client_id, scope!("email"))
+			.await
+			.unwrap();
+
+		let diff = store
+			.consent("alice", client_id, scope!("openid email"))
+			.await
+			.unwrap();
+		assert!(diff.is_granted());
+
+		let diff = store
+			.consent("bob", client_id, scope!("openid"))
+			.await
+			.unwrap();
+		assert_eq!(diff.required.as_deref(), Some(scope!("openid")));
+
+		store.revoke("alice", client_id);
+		let diff = store
+			.consent("alice", client_id, scope!("email"))
+			.await
+			.unwrap();
+		assert!(!diff.is_granted());
+	}
+}
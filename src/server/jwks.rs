@@ -0,0 +1,260 @@
+//! Publication of the public keys of an authorization server.
+//!
+//! See: <https://datatracker.ietf.org/doc/html/rfc8414#section-2>
+//!
+//! Authorization servers signing JWT access tokens, ID tokens or metadata
+//! publish the corresponding public keys as a JWK Set at their `jwks_uri`.
+//! A [`KeyProvider`] returns the keys to publish. To rotate keys without
+//! invalidating the tokens in circulation, the new key is published before
+//! being used, and the retired key is still published until the last token
+//! it signed expires, as done by [`KeyRing`].
+use std::{
+	future::Future,
+	sync::Mutex,
+	time::{Duration, SystemTime},
+};
+
+use crate::{
+	time::{Clock, SystemClock},
+	util::{Jwk, JwkSet},
+};
+
+/// Default duration during which clients may cache the published keys.
+pub const DEFAULT_JWKS_MAX_AGE: Duration = Duration::from_secs(3600);
+
+/// Provider of the public keys published by an authorization server.
+pub trait KeyProvider: Send + Sync {
+	/// Returns the public keys to publish.
+	///
+	/// The set must include the keys currently used for signing, the keys
+	/// about to be used, and the retired keys which signed tokens that have
+	/// not expired yet.
+	fn public_keys(&self) -> impl Send + Future<Output = JwkSet>;
+
+	/// Returns the duration during which clients may cache the published
+	/// keys.
+	///
+	/// New keys must be published at least this long before being used.
+	/// Defaults to [`DEFAULT_JWKS_MAX_AGE`].
+	fn max_age(&self) -> Duration {
+		DEFAULT_JWKS_MAX_AGE
+	}
+}
+
+/// Error returned when trying to publish a private or symmetric key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("private or symmetric keys cannot be published")]
+pub struct PrivateKeyError;
+
+/// In-memory set of public keys supporting rotation.
+///
+/// Keys are published from the moment they are [added](Self::add), and
+/// until the end of the grace period given when they are
+/// [retired](Self::retire).
+pub struct KeyRing<K = SystemClock> {
+	keys: Mutex<Vec<PublishedKey>>,
+	max_age: Duration,
+	clock: K,
+}
+
+struct PublishedKey {
+	jwk: Jwk,
+	retired_until: Option<SystemTime>,
+}
+
+impl KeyRing {
+	/// Creates a new empty key ring, cached by clients for
+	/// [`DEFAULT_JWKS_MAX_AGE`].
+	pub fn new() -> Self {
+		Self {
+			keys: Mutex::new(Vec::new()),
+			max_age: DEFAULT_JWKS_MAX_AGE,
+			clock: SystemClock,
+		}
+	}
+}
+
+impl Default for KeyRing {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<K> KeyRing<K> {
+	/// Sets the duration during which clients may cache the published keys.
+	pub fn with_max_age(self, max_age: Duration) -> Self {
+		Self { max_age, ..self }
+	}
+
+	/// Sets the clock used to expire the retired keys.
+	pub fn with_clock<L>(self, clock: L) -> KeyRing<L> {
+		KeyRing {
+			keys: self.keys,
+			max_age: self.max_age,
+			clock,
+		}
+	}
+
+	/// Publishes the given key, replacing any key with the same identifier.
+	///
+	/// Private and symmetric keys are rejected, since the published keys are
+	/// served to anyone (see [`Jwk::is_public`]).
+	pub fn add(&self, jwk: Jwk) -> Result<(), PrivateKeyError> {
+		if !jwk.is_public() {
+			return Err(PrivateKeyError);
+		}
+
+		let mut keys = self.keys.lock().unwrap();
+		keys.retain(|key| jwk.kid.is_none() || key.jwk.kid != jwk.kid);
+		keys.push(PublishedKey {
+			jwk,
+			retired_until: None,
+		});
+		Ok(())
+	}
+
+	/// Immediately stops publishing the key with the given identifier.
+	pub fn remove(&self, kid: &str) {
+		self.keys
+			.lock()
+			.unwrap()
+			.retain(|key| key.jwk.kid.as_deref() != Some(kid));
+	}
+}
+
+impl<K: Clock> KeyRing<K> {
+	/// Retires the key with the given identifier, which is still published
+	/// during the given grace period.
+	///
+	/// The grace period should cover the lifetime of the last tokens signed
+	/// with the key. Returns `false` if there is no such key.
+	pub fn retire(&self, kid: &str, grace_period: Duration) -> bool {
+		let until = self.clock.deadline_in(grace_period);
+		let mut keys = self.keys.lock().unwrap();
+		match keys
+			.iter_mut()
+			.find(|key| key.jwk.kid.as_deref() == Some(kid))
+		{
+			Some(key) => {
+				key.retired_until = Some(until);
+				true
+			}
+			None => false,
+		}
+	}
+}
+
+impl<K: Clock + Send + Sync> KeyProvider for KeyRing<K> {
+	async fn public_keys(&self) -> JwkSet {
+		let now = self.clock.now();
+		let mut keys = self.keys.lock().unwrap();
+		keys.retain(|key| key.retired_until.is_none_or(|until| until > now));
+		JwkSet::new(keys.iter().map(|key| key.jwk.clone()).collect())
+	}
+
+	fn max_age(&self) -> Duration {
+		self.max_age
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::time::MockClock;
+
+	use super::*;
+
+	fn jwk(kid: &str) -> Jwk {
+		serde_json::from_value(serde_json::json!({
+			"kty": "EC",
+			"kid": kid,
+			"use": "sig",
+			"alg": "ES256",
+			"crv": "P-256",
+			"x": "f83OJ3D2xF1Bg8vub9tLe1gHMzV76e8Tus9uPHvRVEU",
+			"y": "x_FEzRu9m36HLN_tue659LNpXW6pCyStikYjKIWI5a0"
+		}))
+		.unwrap()
+	}
+
+	#[tokio::test]
+	async fn rotation() {
+		let keys = KeyRing::new().with_clock(MockClock::from_unix_timestamp(0));
+		keys.add(jwk("2024-01")).unwrap();
+		keys.add(jwk("2024-02")).unwrap();
+		assert_eq!(keys.public_keys().await.keys.len(), 2);
+
+		assert!(keys.retire("2024-01", Duration::from_secs(600)));
+		assert!(!keys.retire("2023-12", Duration::from_secs(600)));
+		keys.clock.advance(Duration::from_secs(599));
+		assert!(keys.public_keys().await.find("2024-01").is_some());
+
+		keys.clock.advance(Duration::from_secs(1));
+		let published = keys.public_keys().await;
+		assert_eq!(published.find("2024-01"), None);
+		assert_eq!(published.find("2024-02"), Some(&jwk("2024-02")));
+
+		keys.remove("2024-02");
+		assert!(keys.public_keys().await.keys.is_empty());
+	}
+
+	#[tokio::test]
+	async fn private_keys() {
+		let keys = KeyRing::new();
+
+		let mut ec = jwk("ec");
+		ec.params.insert(
+			"d".to_owned(),
+			"jpsQnnGQmL-YBIffH1136cspYG6-0iY7X1fCE9-E9LI".into(),
+		);
+		assert_eq!(keys.add(ec), Err(PrivateKeyError));
+
+		let rsa = serde_json::from_value(serde_json::json!({
+			"kty": "RSA",
+			"kid": "rsa",
+			"n": "0vx7agoebGcQSuuP",
+			"e": "AQAB",
+			"d": "X4cTteJY_gn4FYPs",
+			"p": "83i-7IvMGXoMXCsk",
+			"q": "3dfOR9cuYq-0S-mk",
+			"dp": "G4sPXkc6Ya9y8oJW",
+			"dq": "s9lAH9fggBsoFR8O",
+			"qi": "GyM_p6JrXySiz1to"
+		}))
+		.unwrap();
+		assert_eq!(keys.add(rsa), Err(PrivateKeyError));
+
+		let oct = serde_json::from_value(serde_json::json!({
+			"kty": "oct",
+			"kid": "oct",
+			"k": "AyM1SysPpbyDfgZl"
+		}))
+		.unwrap();
+		assert_eq!(keys.add(oct), Err(PrivateKeyError));
+
+		assert!(keys.public_keys().await.keys.is_empty());
+	}
+
+	#[test]
+	fn public_keys_of_set() {
+		let mut private = jwk("2024-01");
+		private.params.insert(
+			"d".to_owned(),
+			"jpsQnnGQmL-YBIffH1136cspYG6-0iY7X1fCE9-E9LI".into(),
+		);
+		let secret: Jwk =
+			serde_json::from_value(serde_json::json!({ "kty": "oct", "k": "c2VjcmV0" })).unwrap();
+
+		let set = JwkSet::new(vec![private, secret]).to_public();
+		assert_eq!(set, JwkSet::new(vec![jwk("2024-01")]));
+		assert!(set.keys[0].is_public());
+	}
+
+	#[test]
+	fn jwk_set() {
+		let set = JwkSet::new(vec![jwk("2024-01")]);
+		let value = serde_json::to_value(&set).unwrap();
+		assert_eq!(value["keys"][0]["use"], "sig");
+		assert_eq!(value["keys"][0]["crv"], "P-256");
+		assert_eq!(serde_json::from_value::<JwkSet>(value).unwrap(), set);
+	}
+}
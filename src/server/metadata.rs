@@ -1,36 +1,55 @@
 use iref::{Uri, UriBuf, uri_ref};
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_with::skip_serializing_none;
 
 use crate::{
 	// authorization::oauth2::{
 	// 	client_attestation::ClientAttestationServerParams, dpop::DpopServerParams,
 	// },
+	EndpointUriPolicy,
+	InsecureEndpointUri,
+	IssuerBuf,
+	ResponseType,
+	ResponseTypeBuf,
 	ScopeBuf,
 	client::OAuth2ClientError,
-	ext::pkce::PkceCodeChallengeMethod,
-	util::{Discoverable, NoExtension}, // util::discoverable::Discoverable,
+	util::{AnyExtension, Discoverable, NoExtension}, // util::discoverable::Discoverable,
 };
 
+#[cfg(feature = "pkce")]
+use crate::ext::pkce::PkceCodeChallengeMethod;
+
+pub use crate::grant::GrantType;
+
 /// Authorization Server Metadata.
 ///
 /// See: <https://datatracker.ietf.org/doc/html/rfc8414>
+///
+/// Absent optional fields are omitted when serializing. Fields with a
+/// default value are always serialized.
+#[skip_serializing_none]
 #[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct AuthorizationServerMetadata<P = NoExtension> {
-	pub issuer: UriBuf,
+	pub issuer: IssuerBuf,
 
+	#[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
 	pub authorization_endpoint: Option<UriBuf>,
 
+	#[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
 	pub token_endpoint: Option<UriBuf>,
 
+	#[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
 	pub jwks_uri: Option<UriBuf>,
 
+	#[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
 	pub registration_endpoint: Option<UriBuf>,
 
 	pub scopes_supported: Option<Vec<ScopeBuf>>,
 
 	/// Note: this type is required in the core specification, but made optional
 	/// by some extensions.
-	pub response_types_supported: Option<Vec<String>>,
+	pub response_types_supported: Option<Vec<ResponseTypeBuf>>,
 
 	#[serde(default = "default_response_modes_supported")]
 	pub response_modes_supported: Vec<String>,
@@ -38,18 +57,112 @@ pub struct AuthorizationServerMetadata<P = NoExtension> {
 	#[serde(default = "default_grant_types_supported")]
 	pub grant_types_supported: Vec<GrantType>,
 
+	#[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
 	pub revocation_endpoint: Option<UriBuf>,
 
+	#[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
 	pub introspection_endpoint: Option<UriBuf>,
 
+	#[cfg(feature = "pkce")]
 	pub code_challenge_methods_supported: Option<Vec<PkceCodeChallengeMethod>>,
 
+	/// URL of the Pushed Authorization Request endpoint.
+	///
+	/// See: <https://www.rfc-editor.org/rfc/rfc9126.html#section-5>
+	#[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
+	pub pushed_authorization_request_endpoint: Option<UriBuf>,
+
+	/// Whether the authorization server only accepts authorization requests
+	/// made through the Pushed Authorization Request endpoint.
+	///
+	/// See: <https://www.rfc-editor.org/rfc/rfc9126.html#section-5>
+	#[serde(default)]
+	pub require_pushed_authorization_requests: bool,
+
+	/// URL of the Device Authorization endpoint.
+	///
+	/// See: <https://datatracker.ietf.org/doc/html/rfc8628#section-4>
+	#[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
+	pub device_authorization_endpoint: Option<UriBuf>,
+
+	/// URL of the OpenID Connect end session endpoint, to which relying
+	/// parties redirect the end-user to log out.
+	///
+	/// See: <https://openid.net/specs/openid-connect-rpinitiated-1_0.html#OPMetadata>
+	#[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
+	pub end_session_endpoint: Option<UriBuf>,
+
+	/// URL of the OpenID provider iframe polled by relying parties to
+	/// monitor the session of the end-user.
+	///
+	/// See: <https://openid.net/specs/openid-connect-session-1_0.html#OPMetadata>
+	#[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
+	pub check_session_iframe: Option<UriBuf>,
+
+	/// Whether the OpenID provider supports back-channel logout.
+	///
+	/// See: <https://openid.net/specs/openid-connect-backchannel-1_0.html#BCSupport>
+	#[serde(default)]
+	pub backchannel_logout_supported: bool,
+
+	/// Whether the OpenID provider includes the `sid` claim in logout tokens.
+	///
+	/// See: <https://openid.net/specs/openid-connect-backchannel-1_0.html#BCSupport>
+	#[serde(default)]
+	pub backchannel_logout_session_supported: bool,
+
+	/// Whether the authorization server only accepts authorization requests
+	/// passed in a signed request object.
+	///
+	/// See: <https://www.rfc-editor.org/rfc/rfc9101.html#section-10.5>
+	#[serde(default)]
+	pub require_signed_request_object: bool,
+
+	/// JWS algorithms supported for request objects.
+	///
+	/// See: <https://www.rfc-editor.org/rfc/rfc9101.html#section-10.5>
+	pub request_object_signing_alg_values_supported: Option<Vec<String>>,
+
+	/// JWE key management algorithms supported for encrypted request
+	/// objects.
+	///
+	/// See: <https://openid.net/specs/openid-connect-discovery-1_0.html#ProviderMetadata>
+	pub request_object_encryption_alg_values_supported: Option<Vec<String>>,
+
+	/// JWE content encryption algorithms supported for encrypted request
+	/// objects.
+	///
+	/// See: <https://openid.net/specs/openid-connect-discovery-1_0.html#ProviderMetadata>
+	pub request_object_encryption_enc_values_supported: Option<Vec<String>>,
+
+	/// JWS algorithms supported for DPoP proof JWTs.
+	///
+	/// See: <https://www.rfc-editor.org/rfc/rfc9449.html#section-5.1>
+	pub dpop_signing_alg_values_supported: Option<Vec<String>>,
+
+	/// Authorization details types supported in Rich Authorization Requests.
+	///
+	/// See: <https://www.rfc-editor.org/rfc/rfc9396.html#section-10>
+	#[cfg(feature = "rar")]
+	pub authorization_details_types_supported: Option<Vec<String>>,
+
+	/// JWS algorithms supported for client attestation JWTs.
+	///
+	/// See: <https://datatracker.ietf.org/doc/html/draft-ietf-oauth-attestation-based-client-auth#section-10.1>
+	pub client_attestation_signing_alg_values_supported: Option<Vec<String>>,
+
+	/// JWS algorithms supported for client attestation proof of possession
+	/// JWTs.
+	///
+	/// See: <https://datatracker.ietf.org/doc/html/draft-ietf-oauth-attestation-based-client-auth#section-10.1>
+	pub client_attestation_pop_signing_alg_values_supported: Option<Vec<String>>,
+
 	#[serde(flatten)]
 	pub extra: P,
 }
 
 impl<P> AuthorizationServerMetadata<P> {
-	pub fn new(issuer: UriBuf) -> Self
+	pub fn new(issuer: IssuerBuf) -> Self
 	where
 		P: Default,
 	{
@@ -65,7 +178,24 @@ impl<P> AuthorizationServerMetadata<P> {
 			grant_types_supported: default_grant_types_supported(),
 			revocation_endpoint: Default::default(),
 			introspection_endpoint: Default::default(),
+			#[cfg(feature = "pkce")]
 			code_challenge_methods_supported: Default::default(),
+			pushed_authorization_request_endpoint: Default::default(),
+			require_pushed_authorization_requests: false,
+			device_authorization_endpoint: Default::default(),
+			end_session_endpoint: Default::default(),
+			check_session_iframe: Default::default(),
+			backchannel_logout_supported: false,
+			backchannel_logout_session_supported: false,
+			require_signed_request_object: false,
+			request_object_signing_alg_values_supported: Default::default(),
+			request_object_encryption_alg_values_supported: Default::default(),
+			request_object_encryption_enc_values_supported: Default::default(),
+			dpop_signing_alg_values_supported: Default::default(),
+			#[cfg(feature = "rar")]
+			authorization_details_types_supported: Default::default(),
+			client_attestation_signing_alg_values_supported: Default::default(),
+			client_attestation_pop_signing_alg_values_supported: Default::default(),
 			extra: Default::default(),
 		}
 	}
@@ -83,12 +213,243 @@ impl<P> AuthorizationServerMetadata<P> {
 			..self
 		}
 	}
+
+	pub fn with_pushed_authorization_request_endpoint(
+		self,
+		pushed_authorization_request_endpoint: UriBuf,
+	) -> Self {
+		Self {
+			pushed_authorization_request_endpoint: Some(pushed_authorization_request_endpoint),
+			..self
+		}
+	}
+
+	pub fn with_device_authorization_endpoint(self, device_authorization_endpoint: UriBuf) -> Self {
+		Self {
+			device_authorization_endpoint: Some(device_authorization_endpoint),
+			..self
+		}
+	}
+
+	pub fn with_end_session_endpoint(self, end_session_endpoint: UriBuf) -> Self {
+		Self {
+			end_session_endpoint: Some(end_session_endpoint),
+			..self
+		}
+	}
+
+	pub fn with_check_session_iframe(self, check_session_iframe: UriBuf) -> Self {
+		Self {
+			check_session_iframe: Some(check_session_iframe),
+			..self
+		}
+	}
+
+	/// Checks whether the given response type is advertised in
+	/// `response_types_supported`, regardless of the order of its response
+	/// names.
+	///
+	/// Since some extensions make `response_types_supported` optional, every
+	/// response type is assumed to be supported when it is absent.
+	pub fn supports_response_type(&self, response_type: &ResponseType) -> bool {
+		self.response_types_supported
+			.as_ref()
+			.is_none_or(|supported| {
+				supported
+					.iter()
+					.any(|supported| supported.is_equivalent(response_type))
+			})
+	}
+
+	/// Fails if the given response type is not advertised in
+	/// `response_types_supported`.
+	///
+	/// See [`supports_response_type`](Self::supports_response_type).
+	pub fn check_response_type(
+		&self,
+		response_type: &ResponseType,
+	) -> Result<(), UnsupportedResponseType> {
+		if self.supports_response_type(response_type) {
+			Ok(())
+		} else {
+			Err(UnsupportedResponseType(response_type.to_owned()))
+		}
+	}
+
+	/// Fails if an endpoint URI advertised by the authorization server is not
+	/// accepted by the given policy.
+	///
+	/// Metadata are checked with the default, secure, policy upon
+	/// [discovery](Discoverable::discover).
+	pub fn check_endpoint_uris(
+		&self,
+		policy: EndpointUriPolicy,
+	) -> Result<(), InsecureEndpointUri> {
+		[
+			&self.authorization_endpoint,
+			&self.token_endpoint,
+			&self.jwks_uri,
+			&self.registration_endpoint,
+			&self.revocation_endpoint,
+			&self.introspection_endpoint,
+			&self.pushed_authorization_request_endpoint,
+			&self.device_authorization_endpoint,
+			&self.end_session_endpoint,
+			&self.check_session_iframe,
+		]
+		.into_iter()
+		.flatten()
+		.try_for_each(|uri| policy.check(uri))
+	}
+
+	/// Fails if an authorization request sent directly to the authorization
+	/// endpoint is not accepted, because it must be pushed (`pushed`) or
+	/// passed in a signed request object (`signed`).
+	pub fn check_authorization_request(
+		&self,
+		pushed: bool,
+		signed: bool,
+	) -> Result<(), UnsupportedCapability> {
+		if self.require_pushed_authorization_requests && !pushed {
+			return Err(UnsupportedCapability::PushedAuthorizationRequired);
+		}
+
+		if self.require_signed_request_object && !signed {
+			return Err(UnsupportedCapability::SignedRequestObjectRequired);
+		}
+
+		Ok(())
+	}
+
+	/// Fails if the given JWS algorithm is not advertised in
+	/// `request_object_signing_alg_values_supported`.
+	///
+	/// Every algorithm is assumed to be supported when the field is absent.
+	pub fn check_request_object_signing_alg(&self, alg: &str) -> Result<(), UnsupportedCapability> {
+		check_supported(&self.request_object_signing_alg_values_supported, alg)
+			.map_err(UnsupportedCapability::RequestObjectSigningAlg)
+	}
+
+	/// Fails if the given JWS algorithm is not advertised in
+	/// `dpop_signing_alg_values_supported`.
+	///
+	/// Every algorithm is assumed to be supported when the field is absent.
+	pub fn check_dpop_signing_alg(&self, alg: &str) -> Result<(), UnsupportedCapability> {
+		check_supported(&self.dpop_signing_alg_values_supported, alg)
+			.map_err(UnsupportedCapability::DpopSigningAlg)
+	}
+
+	/// Fails if the given JWS algorithms of a client attestation and its
+	/// proof of possession are not advertised in
+	/// `client_attestation_signing_alg_values_supported` and
+	/// `client_attestation_pop_signing_alg_values_supported`.
+	///
+	/// Every algorithm is assumed to be supported when the fields are
+	/// absent.
+	pub fn check_client_attestation_signing_algs(
+		&self,
+		alg: &str,
+		pop_alg: &str,
+	) -> Result<(), UnsupportedCapability> {
+		check_supported(&self.client_attestation_signing_alg_values_supported, alg)
+			.map_err(UnsupportedCapability::ClientAttestationSigningAlg)?;
+		check_supported(
+			&self.client_attestation_pop_signing_alg_values_supported,
+			pop_alg,
+		)
+		.map_err(UnsupportedCapability::ClientAttestationPopSigningAlg)
+	}
+
+	/// Fails if the type of one of the given authorization details is not
+	/// advertised in `authorization_details_types_supported`.
+	///
+	/// Every type is assumed to be supported when the field is absent.
+	#[cfg(feature = "rar")]
+	pub fn check_authorization_details<D>(
+		&self,
+		authorization_details: &[D],
+	) -> Result<(), UnsupportedCapability>
+	where
+		D: crate::ext::rar::AuthorizationDetailsObject,
+	{
+		authorization_details.iter().try_for_each(|details| {
+			check_supported(
+				&self.authorization_details_types_supported,
+				details.r#type(),
+			)
+			.map_err(UnsupportedCapability::AuthorizationDetailsType)
+		})
+	}
 }
 
+fn check_supported(supported: &Option<Vec<String>>, value: &str) -> Result<(), String> {
+	match supported {
+		Some(supported) if !supported.iter().any(|s| s == value) => Err(value.to_owned()),
+		_ => Ok(()),
+	}
+}
+
+/// Authorization Server Metadata capturing every unrecognized field in an
+/// [`AnyExtension`] map.
+///
+/// Unrecognized fields are preserved when documents are round-tripped
+/// through this type, without defining an extension type.
+pub type AnyAuthorizationServerMetadata = AuthorizationServerMetadata<AnyExtension>;
+
 #[derive(Debug, thiserror::Error)]
 #[error("invalid authorization server metadata")]
 pub struct InvalidAuthorizationServerMetadata;
 
+/// Error returned when the authorization server metadata does not advertise
+/// a required endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("authorization server metadata has no `{0}`")]
+pub struct MissingEndpoint(pub &'static str);
+
+/// Error returned when the authorization server metadata does not advertise
+/// the response type of the requested flow.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("unsupported response type `{0}`")]
+pub struct UnsupportedResponseType(pub ResponseTypeBuf);
+
+/// Error returned when the authorization server metadata does not advertise
+/// a capability required by the client.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum UnsupportedCapability {
+	/// Authorization requests must be pushed.
+	#[error("authorization server requires pushed authorization requests")]
+	PushedAuthorizationRequired,
+
+	/// Authorization requests must be passed in a signed request object.
+	#[error("authorization server requires signed request objects")]
+	SignedRequestObjectRequired,
+
+	/// Unsupported request object signing algorithm.
+	#[error("unsupported request object signing algorithm `{0}`")]
+	RequestObjectSigningAlg(String),
+
+	/// No request object encryption algorithm is supported by both the
+	/// authorization server and the client.
+	#[error("no supported request object encryption algorithm")]
+	RequestObjectEncryption,
+
+	/// Unsupported DPoP proof signing algorithm.
+	#[error("unsupported DPoP signing algorithm `{0}`")]
+	DpopSigningAlg(String),
+
+	/// Unsupported authorization details type.
+	#[error("unsupported authorization details type `{0}`")]
+	AuthorizationDetailsType(String),
+
+	/// Unsupported client attestation signing algorithm.
+	#[error("unsupported client attestation signing algorithm `{0}`")]
+	ClientAttestationSigningAlg(String),
+
+	/// Unsupported client attestation proof of possession signing algorithm.
+	#[error("unsupported client attestation PoP signing algorithm `{0}`")]
+	ClientAttestationPopSigningAlg(String),
+}
+
 impl<T> Discoverable for AuthorizationServerMetadata<T>
 where
 	T: DeserializeOwned,
@@ -96,7 +457,7 @@ where
 	const WELL_KNOWN_URI_REF: &iref::UriRef = uri_ref!(".well-known/oauth-authorization-server");
 
 	fn validate(&self, base_url: &Uri) -> Result<(), OAuth2ClientError> {
-		if self.issuer == base_url {
+		if self.issuer == *base_url {
 			Ok(())
 		} else {
 			Err(OAuth2ClientError::response(
@@ -104,17 +465,10 @@ where
 			))
 		}
 	}
-}
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
-#[serde(rename_all = "snake_case")]
-pub enum GrantType {
-	AuthorizationCode,
-	Implicit,
-	#[serde(rename = "urn:ietf:params:oauth:grant-type:pre-authorized_code")]
-	PreAuthorizedCode,
-	#[serde(untagged)]
-	Extension(String),
+	fn check_endpoint_uris(&self, policy: EndpointUriPolicy) -> Result<(), InsecureEndpointUri> {
+		AuthorizationServerMetadata::check_endpoint_uris(self, policy)
+	}
 }
 
 pub fn default_response_modes_supported() -> Vec<String> {
@@ -127,10 +481,9 @@ pub fn default_grant_types_supported() -> Vec<GrantType> {
 
 #[cfg(feature = "axum")]
 mod axum {
-	use crate::transport::APPLICATION_JSON;
+	use crate::server::json_response;
 	use ::axum::{
-		body::Body,
-		http::{StatusCode, header::CONTENT_TYPE},
+		http::StatusCode,
 		response::{IntoResponse, Response},
 	};
 
@@ -150,16 +503,188 @@ mod axum {
 		T: Serialize,
 	{
 		fn into_response(self) -> ::axum::response::Response {
-			Response::builder()
-				.status(StatusCode::OK)
-				.header(CONTENT_TYPE, &APPLICATION_JSON)
-				.body(Body::from(
-					serde_json::to_vec(self)
-						// UNWRAP SAFETY: Authorization Server Metadata is
-						//                always serializable as JSON.
-						.unwrap(),
-				))
-				.unwrap()
+			json_response(Response::builder().status(StatusCode::OK), self)
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use iref::uri;
+
+	use crate::issuer;
+
+	use super::*;
+	#[cfg(feature = "device")]
+	use crate::endpoints::device_authorization::DeviceAuthorizationEndpoint;
+	#[cfg(feature = "par")]
+	use crate::endpoints::pushed_authorization::PushedAuthorizationEndpoint;
+	use crate::endpoints::token::TokenEndpoint;
+
+	#[test]
+	fn unknown_fields_round_trip() {
+		let document = serde_json::json!({
+			"issuer": "https://server.example.com",
+			"token_endpoint": "https://server.example.com/token",
+			"response_modes_supported": ["query", "fragment"],
+			"grant_types_supported": ["authorization_code", "refresh_token"],
+			"require_pushed_authorization_requests": false,
+			"backchannel_logout_supported": false,
+			"backchannel_logout_session_supported": false,
+			"require_signed_request_object": false,
+			"dpop_signing_alg_values_supported": ["ES256"],
+			"x-vendor": { "nested": [1, 2, 3] }
+		});
+
+		let metadata: AnyAuthorizationServerMetadata =
+			serde_json::from_value(document.clone()).unwrap();
+		assert_eq!(metadata.extra["x-vendor"]["nested"][2], 3);
+		assert_eq!(serde_json::to_value(&metadata).unwrap(), document);
+	}
+
+	#[cfg(feature = "par")]
+	#[test]
+	fn pushed_authorization_request_endpoint() {
+		let metadata: AuthorizationServerMetadata = serde_json::from_value(serde_json::json!({
+			"issuer": "https://server.example.com",
+			"pushed_authorization_request_endpoint": "https://server.example.com/as/par",
+			"require_pushed_authorization_requests": true
+		}))
+		.unwrap();
+
+		assert!(metadata.require_pushed_authorization_requests);
+		let endpoint = PushedAuthorizationEndpoint::from_metadata(&(), &metadata).unwrap();
+		assert_eq!(endpoint.uri, uri!("https://server.example.com/as/par"));
+	}
+
+	#[test]
+	fn session_management() {
+		let document = serde_json::json!({
+			"issuer": "https://server.example.com",
+			"end_session_endpoint": "https://server.example.com/logout",
+			"check_session_iframe": "https://server.example.com/check_session",
+			"backchannel_logout_supported": true,
+			"backchannel_logout_session_supported": true
+		});
+		let metadata: AuthorizationServerMetadata =
+			serde_json::from_value(document.clone()).unwrap();
+
+		assert_eq!(
+			metadata.check_session_iframe.as_deref(),
+			Some(uri!("https://server.example.com/check_session"))
+		);
+		assert!(metadata.backchannel_logout_session_supported);
+
+		let serialized = serde_json::to_value(&metadata).unwrap();
+		for (key, value) in document.as_object().unwrap() {
+			assert_eq!(&serialized[key], value);
+		}
+	}
+
+	#[cfg(feature = "device")]
+	#[test]
+	fn device_authorization_endpoint() {
+		let metadata: AuthorizationServerMetadata = serde_json::from_value(serde_json::json!({
+			"issuer": "https://server.example.com",
+			"device_authorization_endpoint": "https://server.example.com/device_authorization",
+			"grant_types_supported": ["urn:ietf:params:oauth:grant-type:device_code"]
+		}))
+		.unwrap();
+
+		assert_eq!(metadata.grant_types_supported, [GrantType::DeviceCode]);
+		let endpoint = DeviceAuthorizationEndpoint::from_metadata(&(), &metadata).unwrap();
+		assert_eq!(
+			endpoint.uri,
+			uri!("https://server.example.com/device_authorization")
+		);
+	}
+
+	#[cfg(feature = "par")]
+	#[test]
+	fn missing_pushed_authorization_request_endpoint() {
+		let metadata = AuthorizationServerMetadata::<NoExtension>::new(
+			issuer!("https://server.example.com").to_owned(),
+		);
+
+		assert!(!metadata.require_pushed_authorization_requests);
+		assert_eq!(
+			PushedAuthorizationEndpoint::from_metadata(&(), &metadata)
+				.err()
+				.unwrap()
+				.to_string(),
+			"authorization server metadata has no `pushed_authorization_request_endpoint`"
+		);
+	}
+
+	#[test]
+	fn token_endpoint() {
+		let mut metadata = AuthorizationServerMetadata::<NoExtension>::new(
+			issuer!("https://server.example.com").to_owned(),
+		);
+		assert_eq!(
+			TokenEndpoint::from_metadata(&(), &metadata)
+				.err()
+				.unwrap()
+				.to_string(),
+			"authorization server metadata has no `token_endpoint`"
+		);
+
+		metadata.token_endpoint = Some(uri!("https://server.example.com/token").to_owned());
+		let endpoint = TokenEndpoint::from_metadata(&(), &metadata).unwrap();
+		assert_eq!(*endpoint.uri, *uri!("https://server.example.com/token"));
+
+		let endpoint =
+			TokenEndpoint::parse(&(), "https://server.example.com/token".to_owned()).unwrap();
+		assert_eq!(*endpoint.uri, *uri!("https://server.example.com/token"));
+	}
+
+	#[test]
+	fn capabilities() {
+		let metadata: AuthorizationServerMetadata = serde_json::from_value(serde_json::json!({
+			"issuer": "https://server.example.com",
+			"require_pushed_authorization_requests": true,
+			"require_signed_request_object": true,
+			"dpop_signing_alg_values_supported": ["ES256"],
+			"authorization_details_types_supported": ["payment_initiation"],
+			"client_attestation_signing_alg_values_supported": ["ES256"],
+			"client_attestation_pop_signing_alg_values_supported": ["ES256", "EdDSA"]
+		}))
+		.unwrap();
+
+		assert_eq!(
+			metadata.check_authorization_request(false, true),
+			Err(UnsupportedCapability::PushedAuthorizationRequired)
+		);
+		assert_eq!(
+			metadata.check_authorization_request(true, false),
+			Err(UnsupportedCapability::SignedRequestObjectRequired)
+		);
+		assert_eq!(metadata.check_authorization_request(true, true), Ok(()));
+
+		assert_eq!(metadata.check_dpop_signing_alg("ES256"), Ok(()));
+		assert_eq!(
+			metadata
+				.check_dpop_signing_alg("RS256")
+				.unwrap_err()
+				.to_string(),
+			"unsupported DPoP signing algorithm `RS256`"
+		);
+
+		assert_eq!(
+			metadata.check_client_attestation_signing_algs("ES256", "EdDSA"),
+			Ok(())
+		);
+		assert_eq!(
+			metadata.check_client_attestation_signing_algs("EdDSA", "ES256"),
+			Err(UnsupportedCapability::ClientAttestationSigningAlg(
+				"EdDSA".to_owned()
+			))
+		);
+
+		let metadata = AuthorizationServerMetadata::<NoExtension>::new(
+			issuer!("https://server.example.com").to_owned(),
+		);
+		assert_eq!(metadata.check_authorization_request(false, false), Ok(()));
+		assert_eq!(metadata.check_dpop_signing_alg("RS256"), Ok(()));
+	}
+}
@@ -3,22 +3,40 @@ use iref::UriBuf;
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 
+use crate::util::NoExtension;
+
 #[cfg(feature = "axum")]
 mod axum;
+#[cfg(feature = "pkce")]
+pub mod code;
+pub mod consent;
+pub mod jwks;
 pub mod metadata;
+#[cfg(all(feature = "par", feature = "rand"))]
+pub mod par;
+pub mod redirect;
+#[cfg(feature = "axum")]
+pub mod resource;
+pub mod validation;
 
 #[cfg(feature = "axum")]
 pub use axum::*;
 pub use metadata::AuthorizationServerMetadata;
+pub use redirect::RedirectResponder;
 
 /// An OAuth 2.0 error response.
 ///
 /// This is the standard error format returned by the authorization server
 /// when a request fails, as defined in
 /// [RFC 6749 Section 5.2](https://datatracker.ietf.org/doc/html/rfc6749#section-5.2).
+///
+/// Extension fields returned by the server are captured by `E`, which can be
+/// set to [`AnyExtension`](crate::util::AnyExtension) to inspect them without
+/// defining a dedicated type.
 #[skip_serializing_none]
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
-pub struct ErrorResponse<T = String> {
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ErrorResponse<T = String, E = NoExtension> {
 	/// A single error code string.
 	pub error: T,
 
@@ -27,16 +45,25 @@ pub struct ErrorResponse<T = String> {
 
 	/// A URI identifying a human-readable web page with information about
 	/// the error.
+	#[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
 	pub error_uri: Option<UriBuf>,
+
+	/// Extension fields.
+	#[serde(flatten)]
+	pub ext: E,
 }
 
-impl<T> ErrorResponse<T> {
+impl<T, E> ErrorResponse<T, E> {
 	/// Creates a new error response.
-	pub fn new(error: T, error_description: Option<String>, error_uri: Option<UriBuf>) -> Self {
+	pub fn new(error: T, error_description: Option<String>, error_uri: Option<UriBuf>) -> Self
+	where
+		E: Default,
+	{
 		Self {
 			error,
 			error_description,
 			error_uri,
+			ext: E::default(),
 		}
 	}
 }
@@ -47,6 +74,7 @@ impl<T> ErrorResponse<T> {
 /// Uses `#[serde(untagged)]` to transparently handle both cases from a
 /// single JSON response body.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(untagged)]
 pub enum ServerResult<T, E = String> {
 	/// The request succeeded.
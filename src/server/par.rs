@@ -0,0 +1,197 @@
+//! Pushed Authorization Request issuance.
+//!
+//! See: <https://www.rfc-editor.org/rfc/rfc9126.html>
+//!
+//! Authorization servers store the pushed requests in a [`PushedRequestStore`]
+//! under a freshly generated `request_uri`, returned to the client in a
+//! [`PushedAuthorizationResponse`]. The request is later retrieved, once,
+//! when the client redirects the user-agent to the authorization endpoint.
+use std::{
+	collections::HashMap,
+	sync::Mutex,
+	time::{Duration, SystemTime},
+};
+
+use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+use iref::{Uri, UriBuf};
+use rand::{RngExt, rng};
+
+use crate::{
+	ClientId, ClientIdBuf,
+	endpoints::pushed_authorization::PushedAuthorizationResponse,
+	time::{Clock, SystemClock},
+};
+
+/// Prefix of the `request_uri` values issued by the PAR endpoint.
+///
+/// See: <https://www.rfc-editor.org/rfc/rfc9126.html#section-2.2>
+pub const REQUEST_URI_PREFIX: &str = "urn:ietf:params:oauth:request_uri:";
+
+/// Default lifetime of pushed authorization requests.
+pub const DEFAULT_PUSHED_REQUEST_LIFETIME: Duration = Duration::from_secs(60);
+
+/// Generates a new `request_uri` with a random reference.
+///
+/// The reference is made of 32 random bytes, base64url-encoded, so it
+/// cannot be guessed.
+pub fn new_request_uri() -> UriBuf {
+	let random_bytes: Vec<u8> = (0..32).map(|_| rng().random::<u8>()).collect();
+	let uri = format!(
+		"{REQUEST_URI_PREFIX}{}",
+		BASE64_URL_SAFE_NO_PAD.encode(random_bytes)
+	);
+
+	// UNWRAP SAFETY: base64url characters are unreserved URI characters.
+	UriBuf::new(uri.into_bytes()).unwrap()
+}
+
+/// Returns the reference part of a `request_uri` issued by
+/// [`new_request_uri`], or `None` if it does not have the expected prefix.
+pub fn request_uri_reference(request_uri: &Uri) -> Option<&str> {
+	request_uri
+		.as_str()
+		.strip_prefix(REQUEST_URI_PREFIX)
+		.filter(|reference| !reference.is_empty())
+}
+
+/// In-memory store of pushed authorization requests.
+///
+/// Each request is bound to the client that pushed it and expires after the
+/// configured lifetime. Requests are removed from the store when taken, so
+/// every `request_uri` can only be used once.
+pub struct PushedRequestStore<T, K = SystemClock> {
+	requests: Mutex<HashMap<String, PushedRequest<T>>>,
+	lifetime: Duration,
+	clock: K,
+}
+
+struct PushedRequest<T> {
+	client_id: ClientIdBuf,
+	request: T,
+	expires_at: SystemTime,
+}
+
+impl<T> PushedRequestStore<T> {
+	/// Creates a new store with the default lifetime of
+	/// [`DEFAULT_PUSHED_REQUEST_LIFETIME`].
+	pub fn new() -> Self {
+		Self {
+			requests: Mutex::new(HashMap::new()),
+			lifetime: DEFAULT_PUSHED_REQUEST_LIFETIME,
+			clock: SystemClock,
+		}
+	}
+}
+
+impl<T> Default for PushedRequestStore<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T, K> PushedRequestStore<T, K> {
+	/// Sets the lifetime of the pushed requests.
+	pub fn with_lifetime(self, lifetime: Duration) -> Self {
+		Self { lifetime, ..self }
+	}
+
+	/// Sets the clock used to expire the pushed requests.
+	pub fn with_clock<L>(self, clock: L) -> PushedRequestStore<T, L> {
+		PushedRequestStore {
+			requests: self.requests,
+			lifetime: self.lifetime,
+			clock,
+		}
+	}
+}
+
+impl<T, K: Clock> PushedRequestStore<T, K> {
+	/// Stores a request pushed by the given client, returning the response of
+	/// the PAR endpoint.
+	///
+	/// Expired requests are purged from the store.
+	pub fn push(&self, client_id: &ClientId, request: T) -> PushedAuthorizationResponse {
+		let request_uri = new_request_uri();
+		let now = self.clock.now();
+
+		// UNWRAP SAFETY: the request URI was generated with the expected
+		//                prefix.
+		let reference = request_uri_reference(&request_uri).unwrap().to_owned();
+
+		let mut requests = self.requests.lock().unwrap();
+		requests.retain(|_, pushed| pushed.expires_at > now);
+		requests.insert(
+			reference,
+			PushedRequest {
+				client_id: client_id.to_owned(),
+				request,
+				expires_at: now + self.lifetime,
+			},
+		);
+
+		PushedAuthorizationResponse::new(request_uri, self.lifetime)
+	}
+
+	/// Takes the request referenced by `request_uri` out of the store.
+	///
+	/// Returns `None` if the request is unknown, expired, already taken, or
+	/// was pushed by another client than `client_id`.
+	pub fn take(&self, request_uri: &Uri, client_id: &ClientId) -> Option<T> {
+		let reference = request_uri_reference(request_uri)?;
+		let mut requests = self.requests.lock().unwrap();
+
+		if requests.get(reference)?.client_id != *client_id {
+			return None;
+		}
+
+		let pushed = requests.remove(reference)?;
+		(pushed.expires_at > self.clock.now()).then_some(pushed.request)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{client_id, time::MockClock};
+
+	use super::*;
+
+	#[test]
+	fn request_uri() {
+		let uri = new_request_uri();
+		assert!(uri.as_str().starts_with(REQUEST_URI_PREFIX));
+		assert_eq!(request_uri_reference(&uri).unwrap().len(), 43);
+		assert_ne!(uri, new_request_uri());
+	}
+
+	#[test]
+	fn push_and_take() {
+		let store = PushedRequestStore::new().with_clock(MockClock::from_unix_timestamp(0));
+		let client_id = client_id!("s6BhdRkqt3");
+
+		let response = store.push(client_id, "request");
+		assert_eq!(response.expires_in(), DEFAULT_PUSHED_REQUEST_LIFETIME);
+
+		// Other client.
+		assert_eq!(store.take(&response.request_uri, client_id!("other")), None);
+
+		assert_eq!(
+			store.take(&response.request_uri, client_id),
+			Some("request")
+		);
+
+		// One-time use.
+		assert_eq!(store.take(&response.request_uri, client_id), None);
+	}
+
+	#[test]
+	fn expired_request() {
+		let store = PushedRequestStore::new()
+			.with_lifetime(Duration::from_secs(10))
+			.with_clock(MockClock::from_unix_timestamp(0));
+		let client_id = client_id!("s6BhdRkqt3");
+
+		let response = store.push(client_id, "request");
+		store.clock.advance(Duration::from_secs(10));
+		assert_eq!(store.take(&response.request_uri, client_id), None);
+	}
+}
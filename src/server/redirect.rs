@@ -0,0 +1,136 @@
+//! Authorization responses delivered by redirection.
+//!
+//! See: <https://datatracker.ietf.org/doc/html/rfc6749#section-4.1.2>
+//!
+//! The authorization server answers an authorization request by redirecting
+//! the user-agent to the client redirection URI. If the request included a
+//! `state` parameter, the exact value received from the client *must* be
+//! included in the response, whether it is a success or an error.
+//! [`RedirectResponder`] takes the state out of the incoming [`Stateful`]
+//! request so that it cannot be dropped or altered by the server.
+use iref::{Uri, UriBuf};
+use serde::Serialize;
+
+use crate::{
+	CodeBuf, State, StateBuf, Stateful,
+	grant::authorization_code::AuthorizationCodeAuthorizationResponse, server::ErrorResponse,
+	util::extend_uri_query,
+};
+
+/// Responder to an authorization request, echoing its `state`.
+///
+/// A responder is created from the incoming [`Stateful`] request once its
+/// redirection URI has been validated. It is then consumed to build the
+/// success or error redirection URI, both including the `state` of the
+/// request if, and only if, it was present.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RedirectResponder {
+	redirect_uri: UriBuf,
+	state: Option<StateBuf>,
+}
+
+impl RedirectResponder {
+	/// Creates a responder redirecting to `redirect_uri`, taking the state
+	/// out of the given request.
+	///
+	/// Returns the responder along with the inner request.
+	pub fn new<T>(request: Stateful<T>, redirect_uri: UriBuf) -> (Self, T) {
+		let Stateful { state, value } = request;
+		(
+			Self {
+				redirect_uri,
+				state,
+			},
+			value,
+		)
+	}
+
+	/// Returns the client redirection URI.
+	pub fn redirect_uri(&self) -> &Uri {
+		&self.redirect_uri
+	}
+
+	/// Returns the state of the authorization request, if any.
+	pub fn state(&self) -> Option<&State> {
+		self.state.as_deref()
+	}
+
+	/// Returns the redirection URI carrying the given response parameters and
+	/// the state of the request.
+	pub fn respond(self, response: impl Serialize) -> UriBuf {
+		let mut uri = self.redirect_uri;
+		extend_uri_query(&mut uri, Stateful::new(response, self.state));
+		uri
+	}
+
+	/// Returns the redirection URI granting the given authorization code.
+	///
+	/// See: <https://datatracker.ietf.org/doc/html/rfc6749#section-4.1.2>
+	pub fn grant(self, code: CodeBuf) -> UriBuf {
+		self.respond(AuthorizationCodeAuthorizationResponse { code })
+	}
+
+	/// Returns the redirection URI reporting the given error.
+	///
+	/// See: <https://datatracker.ietf.org/doc/html/rfc6749#section-4.1.2.1>
+	pub fn deny<T, E>(self, error: ErrorResponse<T, E>) -> UriBuf
+	where
+		T: Serialize,
+		E: Serialize,
+	{
+		self.respond(error)
+	}
+
+	/// Converts this responder into an [`AuthorizeError`](super::AuthorizeError)
+	/// reporting the given error code.
+	#[cfg(feature = "axum")]
+	pub fn into_error(self, error: super::ErrorCode) -> super::AuthorizeError {
+		super::AuthorizeError::new(self.redirect_uri, self.state, error)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use iref::uri;
+
+	use crate::{code, state};
+
+	use super::*;
+
+	#[test]
+	fn state_echo() {
+		let request = Stateful::new((), Some(state!("xyz").to_owned()));
+		let (responder, ()) =
+			RedirectResponder::new(request, uri!("https://client.example.com/cb").to_owned());
+
+		assert_eq!(responder.state(), Some(state!("xyz")));
+		assert_eq!(
+			responder
+				.clone()
+				.grant(code!("SplxlOBeZQQYbYS6WxSbIA").to_owned())
+				.as_str(),
+			"https://client.example.com/cb?state=xyz&code=SplxlOBeZQQYbYS6WxSbIA"
+		);
+		assert_eq!(
+			responder
+				.deny(ErrorResponse::<_>::new("access_denied", None, None))
+				.as_str(),
+			"https://client.example.com/cb?state=xyz&error=access_denied"
+		);
+	}
+
+	#[test]
+	fn no_state() {
+		let (responder, ()) = RedirectResponder::new(
+			Stateful::new((), None),
+			uri!("https://client.example.com/cb?tenant=a").to_owned(),
+		);
+
+		assert_eq!(
+			responder
+				.grant(code!("SplxlOBeZQQYbYS6WxSbIA").to_owned())
+				.as_str(),
+			"https://client.example.com/cb?tenant=a&code=SplxlOBeZQQYbYS6WxSbIA"
+		);
+	}
+}
@@ -0,0 +1,100 @@
+use std::collections::BTreeMap;
+
+use iref::UriBuf;
+
+use http::header::{AUTHORIZATION, CONTENT_TYPE};
+
+use crate::{
+	AccessToken, AccessTokenBuf,
+	client::{OAuth2Client, OAuth2ClientError},
+	endpoints::{
+		HttpRequest,
+		introspection::{IntrospectionEndpoint, IntrospectionRequest},
+		token::TokenTypeHint,
+	},
+	transport::{APPLICATION_X_WWW_FORM_URLENCODED, ContentType, HttpClient, WwwFormUrlEncoded},
+};
+
+use super::{AccessTokenClaims, AccessTokenVerifier, BearerError};
+
+/// Verifier querying the introspection endpoint of the authorization
+/// server.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc7662>
+///
+/// Inactive tokens are rejected with [`BearerError::InvalidToken`], while
+/// introspection failures are reported as [`BearerError::Server`].
+pub struct IntrospectionVerifier<C> {
+	client: C,
+	uri: UriBuf,
+	http_client: reqwest::Client,
+	credentials: Option<AccessTokenBuf>,
+}
+
+impl<C> IntrospectionVerifier<C> {
+	/// Creates a new introspection verifier.
+	///
+	/// The resource server acts as the given `client` of the authorization
+	/// server.
+	pub fn new(client: C, uri: UriBuf, http_client: reqwest::Client) -> Self {
+		Self {
+			client,
+			uri,
+			http_client,
+			credentials: None,
+		}
+	}
+
+	/// Authenticates the introspection requests with the given bearer
+	/// token.
+	pub fn with_bearer_credentials(self, token: AccessTokenBuf) -> Self {
+		Self {
+			credentials: Some(token),
+			..self
+		}
+	}
+}
+
+impl<C> AccessTokenVerifier for IntrospectionVerifier<C>
+where
+	C: Send + Sync + OAuth2Client,
+{
+	type Extension = BTreeMap<String, serde_json::Value>;
+
+	async fn verify(&self, token: &AccessToken) -> Result<AccessTokenClaims, BearerError> {
+		let endpoint = IntrospectionEndpoint::new(&self.client, &self.uri);
+		let request =
+			IntrospectionRequest::new(token.as_str().to_owned(), Some(TokenTypeHint::AccessToken));
+
+		let mut http_request = http::Request::builder()
+			.method(http::Method::POST)
+			.uri(self.uri.as_str())
+			.header(CONTENT_TYPE, APPLICATION_X_WWW_FORM_URLENCODED)
+			.body(WwwFormUrlEncoded::encode(&request))
+			.unwrap();
+
+		if let Some(credentials) = &self.credentials {
+			http_request.headers_mut().insert(
+				AUTHORIZATION,
+				format!("Bearer {credentials}").try_into().unwrap(),
+			);
+		}
+
+		let server_error = |e: OAuth2ClientError| BearerError::Server(e.to_string());
+		let response = self
+			.http_client
+			.send(http_request)
+			.await
+			.map_err(server_error)?;
+		let response = request
+			.decode_response(&endpoint, response)
+			.map_err(server_error)?
+			.into_body();
+
+		if response.active {
+			Ok(response.into())
+		} else {
+			Err(BearerError::InvalidToken("inactive token".to_owned()))
+		}
+	}
+}
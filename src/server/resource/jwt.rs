@@ -0,0 +1,254 @@
+use std::collections::BTreeMap;
+
+use serde::de::DeserializeOwned;
+
+use crate::{
+	AccessToken, AudienceBuf, IssuerBuf,
+	time::{Clock, LeewayConfig, SystemClock},
+	util::{decode_compact_jws, unix_time},
+};
+
+pub use crate::util::{JwsHeader, JwsSignatureVerifier};
+
+use super::{AccessTokenClaims, AccessTokenVerifier, BearerError};
+
+/// Verifier for JWT access tokens.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc9068#section-4>
+///
+/// The `typ` header must be `at+jwt`, and the signature is checked with the
+/// given [`JwsSignatureVerifier`]. The `exp` claim is required and checked,
+/// along with `nbf` if present, tolerating the configured clock skew. The
+/// `iss` claim must be the expected issuer, and the `aud` claim must contain
+/// the expected audience.
+pub struct JwtVerifier<V, E = BTreeMap<String, serde_json::Value>, K = SystemClock> {
+	signature_verifier: V,
+	issuer: IssuerBuf,
+	audience: AudienceBuf,
+	clock: K,
+	leeway: LeewayConfig,
+	extension: std::marker::PhantomData<fn() -> E>,
+}
+
+impl<V, E> JwtVerifier<V, E> {
+	/// Creates a new JWT access token verifier with the given signature
+	/// verifier, accepting tokens issued by `issuer` for `audience`,
+	/// typically the identifier of this resource server.
+	pub fn new(signature_verifier: V, issuer: IssuerBuf, audience: AudienceBuf) -> Self {
+		Self {
+			signature_verifier,
+			issuer,
+			audience,
+			clock: SystemClock,
+			leeway: LeewayConfig::default(),
+			extension: std::marker::PhantomData,
+		}
+	}
+}
+
+impl<V, E, K> JwtVerifier<V, E, K> {
+	/// Sets the clock skew tolerance.
+	pub fn with_leeway(self, leeway: LeewayConfig) -> Self {
+		Self { leeway, ..self }
+	}
+
+	/// Sets the clock used to check the token expiration.
+	pub fn with_clock<L>(self, clock: L) -> JwtVerifier<V, E, L> {
+		JwtVerifier {
+			signature_verifier: self.signature_verifier,
+			issuer: self.issuer,
+			audience: self.audience,
+			clock,
+			leeway: self.leeway,
+			extension: std::marker::PhantomData,
+		}
+	}
+}
+
+impl<V, E, K> AccessTokenVerifier for JwtVerifier<V, E, K>
+where
+	V: JwsSignatureVerifier,
+	E: 'static + Clone + Send + Sync + DeserializeOwned,
+	K: Send + Sync + Clock,
+{
+	type Extension = E;
+
+	async fn verify(&self, token: &AccessToken) -> Result<AccessTokenClaims<E>, BearerError> {
+		let invalid = |description: &str| BearerError::InvalidToken(description.to_owned());
+
+		let jws = decode_compact_jws(token.as_str()).ok_or_else(|| invalid("malformed JWT"))?;
+
+		if !jws.header.typ.as_deref().is_some_and(is_access_token_type) {
+			return Err(invalid("not a JWT access token"));
+		}
+
+		if jws.header.alg == "none" {
+			return Err(invalid("unsecured JWT"));
+		}
+
+		if !jws.verify_signature(&self.signature_verifier).await {
+			return Err(invalid("invalid JWT signature"));
+		}
+
+		let claims: AccessTokenClaims<E> = jws
+			.claims()
+			.ok_or_else(|| invalid("malformed JWT claims"))?;
+
+		if claims.iss.as_deref() != Some(self.issuer.as_str()) {
+			return Err(invalid("unexpected issuer"));
+		}
+
+		if !claims
+			.aud
+			.iter()
+			.flatten()
+			.any(|aud| aud.as_str() == self.audience.as_str())
+		{
+			return Err(invalid("unexpected audience"));
+		}
+
+		let exp = claims
+			.exp
+			.ok_or_else(|| invalid("missing expiration time"))?;
+		let exp = unix_time(exp).ok_or_else(|| invalid("invalid expiration time"))?;
+		if self.leeway.is_expired(&self.clock, exp) {
+			return Err(invalid("token expired"));
+		}
+
+		if let Some(nbf) = claims.nbf {
+			let nbf = unix_time(nbf).ok_or_else(|| invalid("invalid not before time"))?;
+			if self.leeway.is_not_yet_valid(&self.clock, nbf) {
+				return Err(invalid("token not yet valid"));
+			}
+		}
+
+		Ok(claims)
+	}
+}
+
+/// Checks the `typ` header of a JWT access token.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc9068#section-2.1>
+fn is_access_token_type(typ: &str) -> bool {
+	let typ = typ.strip_prefix("application/").unwrap_or(typ);
+	typ.eq_ignore_ascii_case("at+jwt")
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		audience, issuer,
+		time::MockClock,
+		util::tests::{TestVerifier, jws},
+	};
+
+	use super::*;
+
+	fn jwt(header: serde_json::Value, claims: serde_json::Value, signature: &[u8]) -> String {
+		jws(header, claims.to_string().as_bytes(), signature)
+	}
+
+	fn verifier() -> JwtVerifier<TestVerifier, BTreeMap<String, serde_json::Value>, MockClock> {
+		JwtVerifier::new(
+			TestVerifier,
+			issuer!("https://authorization-server.example.com/").to_owned(),
+			audience!("https://rs.example.com/").to_owned(),
+		)
+		.with_clock(MockClock::from_unix_timestamp(1000))
+	}
+
+	async fn verify(token: &str) -> Result<AccessTokenClaims, BearerError> {
+		let token = AccessToken::new(token).unwrap();
+		verifier().verify(token).await
+	}
+
+	fn claims(exp: u64) -> serde_json::Value {
+		serde_json::json!({
+			"iss": "https://authorization-server.example.com/",
+			"sub": "5ba552d67",
+			"aud": "https://rs.example.com/",
+			"exp": exp,
+			"client_id": "s6BhdRkqt3",
+			"scope": "openid profile reademail"
+		})
+	}
+
+	#[tokio::test]
+	async fn valid_jwt() {
+		let token = jwt(
+			serde_json::json!({ "typ": "at+jwt", "alg": "HS256" }),
+			claims(2000),
+			b"signed",
+		);
+		let claims = verify(&token).await.unwrap();
+		assert_eq!(claims.sub.as_deref(), Some("5ba552d67"));
+	}
+
+	#[tokio::test]
+	async fn invalid_jwt() {
+		let header = serde_json::json!({ "typ": "at+jwt", "alg": "HS256" });
+
+		// Wrong signature.
+		assert!(
+			verify(&jwt(header.clone(), claims(2000), b"forged"))
+				.await
+				.is_err()
+		);
+
+		// Expired, beyond leeway.
+		assert!(
+			verify(&jwt(header.clone(), claims(900), b"signed"))
+				.await
+				.is_err()
+		);
+
+		// Expiration time out of range.
+		assert!(
+			verify(&jwt(header.clone(), claims(u64::MAX), b"signed"))
+				.await
+				.is_err()
+		);
+
+		// Unexpected issuer.
+		let mut other_issuer = claims(2000);
+		other_issuer["iss"] = "https://attacker.example.com/".into();
+		assert!(
+			verify(&jwt(header.clone(), other_issuer, b"signed"))
+				.await
+				.is_err()
+		);
+
+		// Unexpected audience.
+		let mut other_audience = claims(2000);
+		other_audience["aud"] = serde_json::json!(["https://other-rs.example.com/"]);
+		assert!(
+			verify(&jwt(header.clone(), other_audience, b"signed"))
+				.await
+				.is_err()
+		);
+
+		// Missing issuer and audience.
+		let mut anonymous = claims(2000);
+		anonymous.as_object_mut().unwrap().remove("iss");
+		anonymous.as_object_mut().unwrap().remove("aud");
+		assert!(
+			verify(&jwt(header.clone(), anonymous, b"signed"))
+				.await
+				.is_err()
+		);
+
+		// ID token instead of access token.
+		assert!(
+			verify(&jwt(
+				serde_json::json!({ "typ": "JWT", "alg": "HS256" }),
+				claims(2000),
+				b"signed"
+			))
+			.await
+			.is_err()
+		);
+
+		// Not a JWT.
+		assert!(verify("2YotnFZFEjr1zCsicMWpAA").await.is_err());
+	}
+}
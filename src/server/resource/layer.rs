@@ -0,0 +1,330 @@
+use std::{
+	borrow::Borrow,
+	future::Future,
+	marker::PhantomData,
+	pin::Pin,
+	sync::Arc,
+	task::{Context, Poll},
+};
+
+use axum::{
+	extract::FromRequestParts,
+	http::{Extensions, Request, request::Parts},
+	response::{IntoResponse, Response},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::{Scope, ScopeBuf};
+
+use super::{AccessTokenVerifier, BearerError, BearerToken};
+
+type BoxFuture<T> = Pin<Box<dyn Send + Future<Output = T>>>;
+
+/// Scope granted to the verified bearer token of a request.
+///
+/// Inserted in the request extensions by [`VerifyBearer`], and checked by
+/// [`RequireScope`] and [`RequiredScope`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrantedScope(pub Option<ScopeBuf>);
+
+/// Layer verifying the bearer token of every request.
+///
+/// Requests without a valid bearer token are rejected with a
+/// [`BearerError`]. Otherwise, the [`BearerToken`], its
+/// [`AccessTokenClaims`](super::AccessTokenClaims) and its [`GrantedScope`]
+/// are inserted in the request extensions, where they are picked up by
+/// [`VerifiedBearerToken`](super::VerifiedBearerToken), [`RequireScope`] and
+/// [`RequiredScope`].
+pub struct VerifyBearer<V> {
+	verifier: Arc<V>,
+}
+
+impl<V> VerifyBearer<V> {
+	/// Creates a new layer verifying bearer tokens with the given verifier.
+	pub fn new(verifier: V) -> Self {
+		Self {
+			verifier: Arc::new(verifier),
+		}
+	}
+}
+
+impl<V> Clone for VerifyBearer<V> {
+	fn clone(&self) -> Self {
+		Self {
+			verifier: self.verifier.clone(),
+		}
+	}
+}
+
+impl<S, V> Layer<S> for VerifyBearer<V> {
+	type Service = VerifyBearerService<S, V>;
+
+	fn layer(&self, inner: S) -> Self::Service {
+		VerifyBearerService {
+			inner,
+			verifier: self.verifier.clone(),
+		}
+	}
+}
+
+/// Service created by the [`VerifyBearer`] layer.
+pub struct VerifyBearerService<S, V> {
+	inner: S,
+	verifier: Arc<V>,
+}
+
+impl<S: Clone, V> Clone for VerifyBearerService<S, V> {
+	fn clone(&self) -> Self {
+		Self {
+			inner: self.inner.clone(),
+			verifier: self.verifier.clone(),
+		}
+	}
+}
+
+impl<S, V, B> Service<Request<B>> for VerifyBearerService<S, V>
+where
+	S: Clone + Send + 'static + Service<Request<B>, Response = Response>,
+	S::Future: Send,
+	V: 'static + AccessTokenVerifier,
+	B: Send + 'static,
+{
+	type Response = Response;
+	type Error = S::Error;
+	type Future = BoxFuture<Result<Response, S::Error>>;
+
+	fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.inner.poll_ready(cx)
+	}
+
+	fn call(&mut self, mut request: Request<B>) -> Self::Future {
+		// The service that was polled ready must be the one called.
+		let clone = self.inner.clone();
+		let mut inner = std::mem::replace(&mut self.inner, clone);
+		let verifier = self.verifier.clone();
+
+		Box::pin(async move {
+			let token = match BearerToken::from_headers(request.headers()) {
+				Ok(token) => token,
+				Err(e) => return Ok(e.into_response()),
+			};
+
+			let claims = match verifier.verify(&token.0).await {
+				Ok(claims) => claims,
+				Err(e) => return Ok(e.into_response()),
+			};
+
+			let extensions = request.extensions_mut();
+			extensions.insert(GrantedScope(claims.scope.clone()));
+			extensions.insert(claims);
+			extensions.insert(token);
+
+			inner.call(request).await
+		})
+	}
+}
+
+/// Layer requiring the verified bearer token to be granted a scope.
+///
+/// The token must first be verified by an outer [`VerifyBearer`] layer.
+/// Requests whose token was not granted every token of the required scope
+/// are rejected with [`BearerError::InsufficientScope`].
+///
+/// ```ignore
+/// Router::new()
+///     .route("/admin", get(admin))
+///     .route_layer(RequireScope(scope!("admin")))
+///     .route_layer(VerifyBearer::new(verifier));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct RequireScope<T = &'static Scope>(pub T);
+
+impl<S, T: Clone> Layer<S> for RequireScope<T> {
+	type Service = RequireScopeService<S, T>;
+
+	fn layer(&self, inner: S) -> Self::Service {
+		RequireScopeService {
+			inner,
+			scope: self.0.clone(),
+		}
+	}
+}
+
+/// Service created by the [`RequireScope`] layer.
+#[derive(Debug, Clone)]
+pub struct RequireScopeService<S, T> {
+	inner: S,
+	scope: T,
+}
+
+impl<S, T, B> Service<Request<B>> for RequireScopeService<S, T>
+where
+	S: Service<Request<B>, Response = Response>,
+	S::Future: Send + 'static,
+	S::Error: Send + 'static,
+	T: Borrow<Scope>,
+{
+	type Response = Response;
+	type Error = S::Error;
+	type Future = BoxFuture<Result<Response, S::Error>>;
+
+	fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.inner.poll_ready(cx)
+	}
+
+	fn call(&mut self, request: Request<B>) -> Self::Future {
+		match check_scope(request.extensions(), self.scope.borrow()) {
+			Ok(_) => Box::pin(self.inner.call(request)),
+			Err(e) => Box::pin(std::future::ready(Ok(e.into_response()))),
+		}
+	}
+}
+
+/// Scope required by the [`RequiredScope`] extractor.
+pub trait ScopeRequirement {
+	/// The required scope.
+	const SCOPE: &'static Scope;
+}
+
+/// Extractor requiring the verified bearer token to be granted the scope of
+/// the [`ScopeRequirement`] `R`, returning the whole granted scope.
+///
+/// This is the per-handler counterpart of the [`RequireScope`] layer: the
+/// token must first be verified by an outer [`VerifyBearer`] layer, and
+/// requests whose token was not granted every token of the required scope
+/// are rejected with [`BearerError::InsufficientScope`].
+///
+/// ```ignore
+/// struct Admin;
+///
+/// impl ScopeRequirement for Admin {
+///     const SCOPE: &'static Scope = scope!("admin");
+/// }
+///
+/// async fn admin(RequiredScope(granted, _): RequiredScope<Admin>) { ... }
+/// ```
+pub struct RequiredScope<R>(pub ScopeBuf, pub PhantomData<R>);
+
+impl<S, R> FromRequestParts<S> for RequiredScope<R>
+where
+	S: Send + Sync,
+	R: ScopeRequirement,
+{
+	type Rejection = BearerError;
+
+	async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, BearerError> {
+		let granted = check_scope(&parts.extensions, R::SCOPE)?;
+		Ok(Self(granted.to_owned(), PhantomData))
+	}
+}
+
+/// Checks that the bearer token verified by [`VerifyBearer`] was granted the
+/// `required` scope, returning the granted scope.
+fn check_scope<'a>(extensions: &'a Extensions, required: &Scope) -> Result<&'a Scope, BearerError> {
+	match extensions.get::<GrantedScope>() {
+		Some(GrantedScope(Some(granted))) if granted.includes(required) => Ok(granted),
+		Some(_) => Err(BearerError::InsufficientScope(required.to_owned())),
+		None => Err(BearerError::MissingToken),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::convert::Infallible;
+
+	use axum::http::StatusCode;
+
+	use super::*;
+
+	#[derive(Clone)]
+	struct Protected;
+
+	impl Service<Request<()>> for Protected {
+		type Response = Response;
+		type Error = Infallible;
+		type Future = std::future::Ready<Result<Response, Infallible>>;
+
+		fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+			Poll::Ready(Ok(()))
+		}
+
+		fn call(&mut self, _request: Request<()>) -> Self::Future {
+			std::future::ready(Ok(StatusCode::OK.into_response()))
+		}
+	}
+
+	fn call(granted: Option<GrantedScope>) -> Response {
+		let mut request = Request::new(());
+		if let Some(granted) = granted {
+			request.extensions_mut().insert(granted);
+		}
+
+		let mut service = RequireScope(crate::scope!("admin")).layer(Protected);
+		let mut future = service.call(request);
+		let waker = std::task::Waker::noop();
+		match future.as_mut().poll(&mut Context::from_waker(waker)) {
+			Poll::Ready(result) => result.unwrap(),
+			Poll::Pending => panic!("unexpected pending future"),
+		}
+	}
+
+	#[test]
+	fn require_scope() {
+		let granted = |scope: &str| GrantedScope(Some(ScopeBuf::new(scope.to_owned()).unwrap()));
+
+		assert_eq!(call(Some(granted("read admin"))).status(), StatusCode::OK);
+
+		let response = call(Some(granted("read")));
+		assert_eq!(response.status(), StatusCode::FORBIDDEN);
+		assert_eq!(
+			response.headers()["www-authenticate"],
+			"Bearer error=\"insufficient_scope\", scope=\"admin\""
+		);
+
+		assert_eq!(
+			call(Some(GrantedScope(None))).status(),
+			StatusCode::FORBIDDEN
+		);
+		assert_eq!(call(None).status(), StatusCode::UNAUTHORIZED);
+	}
+
+	struct Admin;
+
+	impl ScopeRequirement for Admin {
+		const SCOPE: &'static Scope = crate::scope!("admin");
+	}
+
+	#[tokio::test]
+	async fn required_scope() {
+		let extract = async |granted: Option<GrantedScope>| {
+			let (mut parts, ()) = Request::new(()).into_parts();
+			if let Some(granted) = granted {
+				parts.extensions.insert(granted);
+			}
+
+			RequiredScope::<Admin>::from_request_parts(&mut parts, &())
+				.await
+				.map(|RequiredScope(granted, _)| granted)
+		};
+		let granted = |scope: &str| GrantedScope(Some(ScopeBuf::new(scope.to_owned()).unwrap()));
+
+		assert_eq!(
+			extract(Some(granted("read admin"))).await,
+			Ok(ScopeBuf::new("read admin".to_owned()).unwrap())
+		);
+		assert_eq!(
+			extract(Some(granted("read"))).await,
+			Err(BearerError::InsufficientScope(
+				crate::scope!("admin").to_owned()
+			))
+		);
+		assert_eq!(
+			extract(Some(GrantedScope(None))).await,
+			Err(BearerError::InsufficientScope(
+				crate::scope!("admin").to_owned()
+			))
+		);
+		assert_eq!(extract(None).await, Err(BearerError::MissingToken));
+	}
+}
@@ -0,0 +1,411 @@
+//! Resource server support.
+//!
+//! Protected resources served with axum can require a valid bearer token
+//! with the [`VerifiedBearerToken`] extractor, which reads the token from
+//! the `Authorization` header as defined by [RFC 6750] and checks it with an
+//! [`AccessTokenVerifier`]. Two verifiers are provided:
+//! - [`JwtVerifier`], for JWT access tokens ([RFC 9068]), with a pluggable
+//!   signature check;
+//! - [`IntrospectionVerifier`], querying the introspection endpoint of the
+//!   authorization server ([RFC 7662]). Requires the `reqwest` feature.
+//!
+//! Alternatively, whole routers can be protected with the [`VerifyBearer`]
+//! layer, combined with [`RequireScope`] to require specific scopes, or with
+//! the [`RequiredScope`] extractor to require them per handler.
+//!
+//! Failures are reported with [`BearerError`], which is rendered with the
+//! appropriate status code and `WWW-Authenticate` challenge.
+//!
+//! [RFC 6750]: https://datatracker.ietf.org/doc/html/rfc6750
+//! [RFC 9068]: https://datatracker.ietf.org/doc/html/rfc9068
+//! [RFC 7662]: https://datatracker.ietf.org/doc/html/rfc7662
+use std::{collections::BTreeMap, future::Future};
+
+use axum::{
+	extract::{FromRef, FromRequestParts},
+	http::{
+		HeaderValue, StatusCode,
+		header::{AUTHORIZATION, WWW_AUTHENTICATE},
+		request::Parts,
+	},
+	response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use serde_with::{OneOrMany, formats::PreferOne, serde_as, skip_serializing_none};
+
+use crate::{
+	AccessToken, AccessTokenBuf, AudienceBuf, ClientIdBuf, ScopeBuf,
+	endpoints::introspection::IntrospectionResponse,
+};
+
+#[cfg(feature = "reqwest")]
+mod introspection;
+mod jwt;
+mod layer;
+
+#[cfg(feature = "reqwest")]
+pub use introspection::*;
+pub use jwt::*;
+pub use layer::*;
+
+/// Bearer token error.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc6750#section-3.1>
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BearerError {
+	/// The request lacks any authentication information.
+	///
+	/// Rendered as a `401 Unauthorized` response with a bare `Bearer`
+	/// challenge.
+	#[error("missing bearer token")]
+	MissingToken,
+
+	/// The request is malformed, e.g. the `Authorization` header is
+	/// repeated or uses the wrong syntax.
+	#[error("invalid request: {0}")]
+	InvalidRequest(String),
+
+	/// The access token is expired, revoked, malformed, or invalid for
+	/// other reasons.
+	#[error("invalid token: {0}")]
+	InvalidToken(String),
+
+	/// The request requires higher privileges than provided by the access
+	/// token.
+	#[error("insufficient scope, `{0}` required")]
+	InsufficientScope(ScopeBuf),
+
+	/// The token could not be verified because of a server-side error
+	/// (e.g. the introspection endpoint is unreachable).
+	#[error("unable to verify token: {0}")]
+	Server(String),
+}
+
+impl BearerError {
+	/// Returns the error code of this error, if any.
+	pub fn error_code(&self) -> Option<&'static str> {
+		match self {
+			Self::MissingToken | Self::Server(_) => None,
+			Self::InvalidRequest(_) => Some("invalid_request"),
+			Self::InvalidToken(_) => Some("invalid_token"),
+			Self::InsufficientScope(_) => Some("insufficient_scope"),
+		}
+	}
+
+	/// Returns the HTTP status code of the response for this error.
+	pub fn status(&self) -> StatusCode {
+		match self {
+			Self::MissingToken | Self::InvalidToken(_) => StatusCode::UNAUTHORIZED,
+			Self::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+			Self::InsufficientScope(_) => StatusCode::FORBIDDEN,
+			Self::Server(_) => StatusCode::INTERNAL_SERVER_ERROR,
+		}
+	}
+
+	/// Returns the `WWW-Authenticate` challenge for this error, if any.
+	pub fn challenge(&self) -> Option<String> {
+		let mut challenge = "Bearer".to_owned();
+
+		match self {
+			Self::MissingToken => (),
+			Self::InvalidRequest(description) | Self::InvalidToken(description) => {
+				challenge.push_str(&format!(
+					" error=\"{}\", error_description=\"{}\"",
+					self.error_code()?,
+					sanitize_description(description)
+				));
+			}
+			Self::InsufficientScope(scope) => {
+				challenge.push_str(&format!(
+					" error=\"{}\", scope=\"{scope}\"",
+					self.error_code()?
+				));
+			}
+			Self::Server(_) => return None,
+		}
+
+		Some(challenge)
+	}
+}
+
+impl IntoResponse for BearerError {
+	fn into_response(self) -> Response {
+		let mut response = self.status().into_response();
+
+		if let Some(challenge) = self.challenge() {
+			// UNWRAP SAFETY: the challenge only contains visible ASCII
+			//                characters.
+			response
+				.headers_mut()
+				.insert(WWW_AUTHENTICATE, HeaderValue::try_from(challenge).unwrap());
+		}
+
+		response
+	}
+}
+
+/// Removes the characters not allowed in `error_description` attributes.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc6750#section-3>
+fn sanitize_description(description: &str) -> String {
+	description
+		.chars()
+		.filter(|&c| matches!(c, '\x20'..='\x21' | '\x23'..='\x5b' | '\x5d'..='\x7e'))
+		.collect()
+}
+
+/// Checks that the given string matches the `b64token` production.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc6750#section-2.1>
+fn is_b64token(s: &str) -> bool {
+	let value = s.trim_end_matches('=');
+	!value.is_empty()
+		&& value.bytes().all(|b| {
+			b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~' | b'+' | b'/')
+		})
+}
+
+/// Bearer token extracted from the `Authorization` request header.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc6750#section-2.1>
+///
+/// This extractor only parses the token. Use [`VerifiedBearerToken`] to also
+/// verify it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BearerToken(pub AccessTokenBuf);
+
+impl BearerToken {
+	/// Parses the bearer token from the request headers.
+	pub fn from_headers(headers: &axum::http::HeaderMap) -> Result<Self, BearerError> {
+		let mut values = headers.get_all(AUTHORIZATION).iter();
+
+		let value = values.next().ok_or(BearerError::MissingToken)?;
+		if values.next().is_some() {
+			return Err(BearerError::InvalidRequest(
+				"repeated `Authorization` header".to_owned(),
+			));
+		}
+
+		let value = value.to_str().map_err(|_| {
+			BearerError::InvalidRequest("invalid `Authorization` header".to_owned())
+		})?;
+
+		let (scheme, token) = value.split_once(' ').unwrap_or((value, ""));
+		if !scheme.eq_ignore_ascii_case("Bearer") {
+			// Other authentication schemes are ignored.
+			return Err(BearerError::MissingToken);
+		}
+
+		let token = token.trim_start_matches(' ');
+		if !is_b64token(token) {
+			return Err(BearerError::InvalidRequest(
+				"malformed bearer token".to_owned(),
+			));
+		}
+
+		// UNWRAP SAFETY: `b64token` is a subset of `1*VSCHAR`.
+		Ok(Self(AccessTokenBuf::new(token.to_owned()).unwrap()))
+	}
+}
+
+impl<S> FromRequestParts<S> for BearerToken
+where
+	S: Send + Sync,
+{
+	type Rejection = BearerError;
+
+	async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, BearerError> {
+		Self::from_headers(&parts.headers)
+	}
+}
+
+/// Claims of a verified access token.
+///
+/// Those are the claims shared by JWT access tokens
+/// ([RFC 9068 Section 2.2](https://datatracker.ietf.org/doc/html/rfc9068#section-2.2))
+/// and introspection responses
+/// ([RFC 7662 Section 2.2](https://datatracker.ietf.org/doc/html/rfc7662#section-2.2)).
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccessTokenClaims<E = BTreeMap<String, serde_json::Value>> {
+	/// Issuer of the token.
+	pub iss: Option<String>,
+
+	/// Subject of the token.
+	pub sub: Option<String>,
+
+	/// Intended audience of the token.
+	#[serde_as(as = "Option<OneOrMany<_, PreferOne>>")]
+	#[serde(default)]
+	pub aud: Option<Vec<AudienceBuf>>,
+
+	/// Client identifier of the client that requested the token.
+	pub client_id: Option<ClientIdBuf>,
+
+	/// Scope associated with the token.
+	pub scope: Option<ScopeBuf>,
+
+	/// Expiration time of the token, in seconds since the Unix epoch.
+	pub exp: Option<u64>,
+
+	/// Issuance time of the token, in seconds since the Unix epoch.
+	pub iat: Option<u64>,
+
+	/// Time before which the token must not be used, in seconds since the
+	/// Unix epoch.
+	pub nbf: Option<u64>,
+
+	/// Identifier of the token.
+	pub jti: Option<String>,
+
+	/// Extension claims.
+	#[serde(flatten)]
+	pub ext: E,
+}
+
+impl<E> From<IntrospectionResponse<E>> for AccessTokenClaims<E> {
+	fn from(value: IntrospectionResponse<E>) -> Self {
+		Self {
+			iss: value.iss,
+			sub: value.sub,
+			aud: value.aud,
+			client_id: value.client_id,
+			scope: value.scope,
+			exp: value.exp,
+			iat: value.iat,
+			nbf: value.nbf,
+			jti: value.jti,
+			ext: value.ext,
+		}
+	}
+}
+
+/// Access token verifier.
+pub trait AccessTokenVerifier: Send + Sync {
+	/// Extension claims.
+	type Extension: 'static + Clone + Send + Sync;
+
+	/// Verifies the given access token, returning its claims.
+	///
+	/// Fails with [`BearerError::InvalidToken`] if the token is not valid.
+	fn verify(
+		&self,
+		token: &AccessToken,
+	) -> impl Send + Future<Output = Result<AccessTokenClaims<Self::Extension>, BearerError>>;
+}
+
+impl<T: AccessTokenVerifier> AccessTokenVerifier for std::sync::Arc<T> {
+	type Extension = T::Extension;
+
+	fn verify(
+		&self,
+		token: &AccessToken,
+	) -> impl Send + Future<Output = Result<AccessTokenClaims<Self::Extension>, BearerError>> {
+		T::verify(self, token)
+	}
+}
+
+/// Bearer token verified by an [`AccessTokenVerifier`].
+///
+/// The verifier is extracted from the router state using [`FromRef`]. If the
+/// token was already verified by a [`VerifyBearer`] layer, its claims are
+/// reused instead.
+pub struct VerifiedBearerToken<V: AccessTokenVerifier> {
+	/// The access token.
+	pub token: AccessTokenBuf,
+
+	/// The verified claims of the access token.
+	pub claims: AccessTokenClaims<V::Extension>,
+}
+
+impl<S, V> FromRequestParts<S> for VerifiedBearerToken<V>
+where
+	S: Send + Sync,
+	V: AccessTokenVerifier + FromRef<S>,
+{
+	type Rejection = BearerError;
+
+	async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, BearerError> {
+		if let (Some(BearerToken(token)), Some(claims)) = (
+			parts.extensions.get::<BearerToken>(),
+			parts.extensions.get::<AccessTokenClaims<V::Extension>>(),
+		) {
+			return Ok(Self {
+				token: token.clone(),
+				claims: claims.clone(),
+			});
+		}
+
+		let BearerToken(token) = BearerToken::from_headers(&parts.headers)?;
+		let verifier = V::from_ref(state);
+		let claims = verifier.verify(&token).await?;
+		Ok(Self { token, claims })
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use axum::http::HeaderMap;
+
+	use super::*;
+
+	fn headers(values: &[&'static str]) -> HeaderMap {
+		let mut headers = HeaderMap::new();
+		for value in values {
+			headers.append(AUTHORIZATION, HeaderValue::from_static(value));
+		}
+		headers
+	}
+
+	#[test]
+	fn parse_bearer_token() {
+		assert_eq!(
+			BearerToken::from_headers(&headers(&["Bearer mF_9.B5f-4.1JqM"])),
+			Ok(BearerToken(
+				AccessTokenBuf::new("mF_9.B5f-4.1JqM".to_owned()).unwrap()
+			))
+		);
+		assert_eq!(
+			BearerToken::from_headers(&headers(&["bearer abc=="])).map(|t| t.0.as_str().to_owned()),
+			Ok("abc==".to_owned())
+		);
+	}
+
+	#[test]
+	fn invalid_bearer_token() {
+		assert_eq!(
+			BearerToken::from_headers(&headers(&[])),
+			Err(BearerError::MissingToken)
+		);
+		assert_eq!(
+			BearerToken::from_headers(&headers(&["Basic czZCaGRSa3F0Mw=="])),
+			Err(BearerError::MissingToken)
+		);
+		assert!(matches!(
+			BearerToken::from_headers(&headers(&["Bearer a b"])),
+			Err(BearerError::InvalidRequest(_))
+		));
+		assert!(matches!(
+			BearerToken::from_headers(&headers(&["Bearer a", "Bearer b"])),
+			Err(BearerError::InvalidRequest(_))
+		));
+	}
+
+	#[test]
+	fn challenges() {
+		assert_eq!(BearerError::MissingToken.challenge().unwrap(), "Bearer");
+		assert_eq!(
+			BearerError::InvalidToken("token \"expired\"".to_owned())
+				.challenge()
+				.unwrap(),
+			"Bearer error=\"invalid_token\", error_description=\"token expired\""
+		);
+		assert_eq!(
+			BearerError::InsufficientScope(ScopeBuf::new("admin".to_owned()).unwrap())
+				.into_response()
+				.status(),
+			StatusCode::FORBIDDEN
+		);
+	}
+}
@@ -0,0 +1,624 @@
+//! Pre-flight request validation.
+//!
+//! The functions in this module perform the protocol-level checks defined by
+//! [RFC 6749] and its extensions directly on the raw
+//! `application/x-www-form-urlencoded` parameters of a request, before they
+//! are deserialized into typed requests.
+//!
+//! They are used by the axum handlers provided by this crate, but do not
+//! depend on any web framework, so that other servers or gateways embedding
+//! this crate can perform the exact same checks.
+//!
+//! [RFC 6749]: https://datatracker.ietf.org/doc/html/rfc6749
+use std::fmt;
+
+use iref::{Uri, UriBuf};
+
+use crate::{
+	ClientId, Code, RedirectUriKind, RedirectUriPolicy, ResponseType, Scope, State,
+	grant::GrantType, types::is_vschar,
+};
+
+#[cfg(feature = "pkce")]
+use crate::{
+	client::ClientType,
+	ext::pkce::{PkceCodeChallenge, PkceCodeChallengeMethod, PkceCodeVerifier, PkcePolicy},
+};
+
+/// Kind of [`Violation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ViolationKind {
+	/// A required parameter is missing.
+	Missing,
+
+	/// A parameter is included more than once.
+	///
+	/// See: <https://datatracker.ietf.org/doc/html/rfc6749#section-3.1>
+	Repeated,
+
+	/// A parameter value is malformed.
+	Invalid,
+}
+
+impl ViolationKind {
+	/// Returns a lowercase, human-readable name for this kind of violation.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Self::Missing => "missing",
+			Self::Repeated => "repeated",
+			Self::Invalid => "invalid",
+		}
+	}
+}
+
+impl fmt::Display for ViolationKind {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.as_str().fmt(f)
+	}
+}
+
+/// A single specification violation found in a request.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, thiserror::Error)]
+#[error("{kind} `{parameter}` parameter")]
+pub struct Violation {
+	/// Name of the offending parameter.
+	pub parameter: String,
+
+	/// What is wrong with the parameter.
+	pub kind: ViolationKind,
+}
+
+impl Violation {
+	/// Creates a new violation for the given parameter.
+	pub fn new(parameter: impl Into<String>, kind: ViolationKind) -> Self {
+		Self {
+			parameter: parameter.into(),
+			kind,
+		}
+	}
+}
+
+/// Formats a list of violations as a single human-readable sentence,
+/// suitable for the `error_description` field of an error response.
+pub fn describe_violations(violations: &[Violation]) -> String {
+	let mut result = String::new();
+
+	for (i, violation) in violations.iter().enumerate() {
+		if i > 0 {
+			result.push_str(", ");
+		}
+
+		result.push_str(&violation.to_string());
+	}
+
+	result
+}
+
+/// Validates the raw query of an authorization request.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc6749#section-4.1.1>
+///
+/// The following checks are performed:
+/// - no parameter is included more than once;
+/// - `response_type` and `client_id` are present and well-formed;
+/// - `redirect_uri`, if present, is an absolute URI without fragment;
+/// - `scope`, `state`, `code_challenge` and `code_challenge_method`, if
+///   present, are well-formed;
+/// - `code_challenge` is present if `code_challenge_method` is.
+///
+/// The PKCE parameters are only checked with the `pkce` feature.
+///
+/// Requests referencing a pushed authorization request with `request_uri`
+/// only require `client_id` and a well-formed `request_uri`, the other
+/// parameters having been pushed to, and validated by, the PAR endpoint.
+///
+/// See: <https://www.rfc-editor.org/rfc/rfc9126.html#section-4>
+///
+/// Returns the list of violations, empty if the request is valid.
+pub fn validate_authorization_request(query: &str) -> Vec<Violation> {
+	let mut v = Validator::new(query);
+
+	if v.contains("request_uri") {
+		v.require("client_id", ClientId::validate_str);
+		v.require("request_uri", |s| Uri::new(s).is_ok());
+		return v.into_violations();
+	}
+
+	v.require("response_type", ResponseType::validate_str);
+	v.require("client_id", ClientId::validate_str);
+	v.optional("redirect_uri", is_redirect_uri);
+	v.optional("scope", Scope::validate_str);
+	v.optional("state", State::validate_str);
+
+	#[cfg(feature = "pkce")]
+	{
+		v.optional("code_challenge", PkceCodeChallenge::validate_str);
+
+		if v.optional("code_challenge_method", |s| {
+			s.parse::<PkceCodeChallengeMethod>().is_ok()
+		})
+		.is_some() && !v.contains("code_challenge")
+		{
+			v.push("code_challenge", ViolationKind::Missing);
+		}
+	}
+
+	v.into_violations()
+}
+
+/// Checks that the raw query of an authorization request includes a PKCE
+/// `code_challenge` if `policy` requires it for clients of type
+/// `client_type`.
+///
+/// This complements [`validate_authorization_request`] once the client, and
+/// hence its type, is known. Requests referencing a pushed authorization
+/// request with `request_uri` are not checked, since the `code_challenge`
+/// is part of the pushed request.
+///
+/// See: <https://www.rfc-editor.org/rfc/rfc9700.html#section-2.1.1>
+#[cfg(feature = "pkce")]
+pub fn validate_pkce_policy(
+	query: &str,
+	client_type: ClientType,
+	policy: PkcePolicy,
+) -> Option<Violation> {
+	let v = Validator::new(query);
+
+	(policy.is_required_for(client_type)
+		&& !v.contains("code_challenge")
+		&& !v.contains("request_uri"))
+	.then(|| Violation::new("code_challenge", ViolationKind::Missing))
+}
+
+/// Checks that the `redirect_uri` of the raw query of an authorization
+/// request, if any, is accepted by the `policy` applied to the client.
+///
+/// This complements [`validate_authorization_request`] once the client is
+/// known, e.g. to apply the rules of
+/// [RFC 8252](https://www.rfc-editor.org/rfc/rfc8252.html) to native apps
+/// only.
+pub fn validate_redirect_uri_policy(query: &str, policy: RedirectUriPolicy) -> Option<Violation> {
+	let v = Validator::new(query);
+
+	v.get("redirect_uri")
+		.filter(|uri| Uri::new(uri).is_ok_and(|uri| policy.check(uri).is_err()))
+		.map(|_| Violation::new("redirect_uri", ViolationKind::Invalid))
+}
+
+/// Checks that the `redirect_uri` of the raw query of an authorization
+/// request matches one of the URIs `registered` by the client.
+///
+/// The parameter may only be omitted if the client registered exactly one
+/// redirection URI. Loopback URIs match regardless of their port (see
+/// [`RedirectUriKind::matches`]).
+///
+/// This complements [`validate_authorization_request`] once the client, and
+/// hence its registered redirection URIs, is known. If a violation is
+/// returned, the user-agent must not be redirected.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc6749#section-3.1.2.3>
+pub fn validate_redirect_uri(query: &str, registered: &[UriBuf]) -> Option<Violation> {
+	let v = Validator::new(query);
+
+	match v.get("redirect_uri") {
+		Some(requested) => Uri::new(requested)
+			.ok()
+			.filter(|requested| {
+				registered
+					.iter()
+					.any(|uri| RedirectUriKind::matches(uri, requested))
+			})
+			.is_none()
+			.then(|| Violation::new("redirect_uri", ViolationKind::Invalid)),
+		None => {
+			(registered.len() != 1).then(|| Violation::new("redirect_uri", ViolationKind::Missing))
+		}
+	}
+}
+
+/// Validates the raw body of a token request.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc6749#section-3.2>
+///
+/// The following checks are performed:
+/// - no parameter is included more than once;
+/// - `grant_type` is present;
+/// - the parameters required by the grant type are present and
+///   well-formed, for the grant types known to this crate;
+/// - `client_id`, if present, is well-formed.
+///
+/// Unknown grant types are not rejected, since they may be handled by an
+/// extension.
+///
+/// Returns the list of violations, empty if the request is valid.
+pub fn validate_token_request(body: &str) -> Vec<Violation> {
+	let mut v = Validator::new(body);
+
+	v.optional("client_id", ClientId::validate_str);
+
+	let grant_type = v
+		.require("grant_type", |s| !s.is_empty())
+		.map(|s| s.parse().unwrap_or_else(|e| match e {}));
+
+	match grant_type {
+		Some(GrantType::AuthorizationCode) => {
+			v.require("code", Code::validate_str);
+			v.optional("redirect_uri", is_redirect_uri);
+			#[cfg(feature = "pkce")]
+			v.optional("code_verifier", PkceCodeVerifier::validate_str);
+		}
+		Some(GrantType::RefreshToken) => {
+			v.require("refresh_token", is_vschar_string);
+			v.optional("scope", Scope::validate_str);
+		}
+		Some(GrantType::ClientCredentials) => {
+			v.optional("scope", Scope::validate_str);
+		}
+		Some(GrantType::PreAuthorizedCode) => {
+			v.require("pre-authorized_code", |s| !s.is_empty());
+		}
+		Some(GrantType::DeviceCode) => {
+			v.require("device_code", |s| !s.is_empty());
+		}
+		_ => (),
+	}
+
+	v.into_violations()
+}
+
+/// Validates the raw body of a device authorization request.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc8628#section-3.1>
+///
+/// The following checks are performed:
+/// - no parameter is included more than once;
+/// - `client_id` is present;
+/// - `scope`, if present, is well-formed.
+///
+/// Returns the list of violations, empty if the request is valid.
+pub fn validate_device_authorization_request(body: &str) -> Vec<Violation> {
+	let mut v = Validator::new(body);
+
+	v.require("client_id", ClientId::validate_str);
+	v.optional("scope", Scope::validate_str);
+
+	v.into_violations()
+}
+
+/// Validates the raw body of a token introspection request.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc7662#section-2.1>
+///
+/// The following checks are performed:
+/// - no parameter is included more than once;
+/// - `token` is present.
+///
+/// Returns the list of violations, empty if the request is valid.
+pub fn validate_introspection_request(body: &str) -> Vec<Violation> {
+	let mut v = Validator::new(body);
+
+	v.require("token", |s| !s.is_empty());
+	v.optional("token_type_hint", |s| !s.is_empty());
+
+	v.into_violations()
+}
+
+/// Validates the raw body of a token revocation request.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc7009#section-2.1>
+///
+/// The following checks are performed:
+/// - no parameter is included more than once;
+/// - `token` is present.
+///
+/// Returns the list of violations, empty if the request is valid.
+pub fn validate_revocation_request(body: &str) -> Vec<Violation> {
+	validate_introspection_request(body)
+}
+
+/// Checks that the given string is a valid redirection endpoint URI.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc6749#section-3.1.2>
+fn is_redirect_uri(s: &str) -> bool {
+	Uri::new(s).is_ok_and(|uri| RedirectUriPolicy::Generic.check(uri).is_ok())
+}
+
+/// Checks that the given string matches `1*VSCHAR`.
+fn is_vschar_string(s: &str) -> bool {
+	!s.is_empty() && s.bytes().all(is_vschar)
+}
+
+/// Parsed request parameters accumulating violations.
+struct Validator {
+	parameters: Vec<(String, String)>,
+	violations: Vec<Violation>,
+}
+
+impl Validator {
+	fn new(form: &str) -> Self {
+		let parameters: Vec<(String, String)> = serde_html_form::from_str(form).unwrap_or_default();
+
+		let mut violations = Vec::new();
+		for (i, (name, _)) in parameters.iter().enumerate() {
+			let first = parameters.iter().position(|(n, _)| n == name) == Some(i);
+			let repeated = parameters[i + 1..].iter().any(|(n, _)| n == name);
+			if first && repeated {
+				violations.push(Violation::new(name.as_str(), ViolationKind::Repeated));
+			}
+		}
+
+		Self {
+			parameters,
+			violations,
+		}
+	}
+
+	fn get(&self, name: &str) -> Option<&str> {
+		self.parameters
+			.iter()
+			.find(|(n, _)| n == name)
+			.map(|(_, value)| value.as_str())
+	}
+
+	fn contains(&self, name: &str) -> bool {
+		self.get(name).is_some()
+	}
+
+	fn push(&mut self, name: &str, kind: ViolationKind) {
+		self.violations.push(Violation::new(name, kind))
+	}
+
+	/// Checks an optional parameter, returning its value if it is present
+	/// and valid.
+	fn optional(&mut self, name: &str, is_valid: impl FnOnce(&str) -> bool) -> Option<&str> {
+		match self.get(name) {
+			Some(value) if is_valid(value) => (),
+			Some(_) => {
+				self.push(name, ViolationKind::Invalid);
+				return None;
+			}
+			None => return None,
+		}
+
+		self.get(name)
+	}
+
+	/// Checks a required parameter, returning its value if it is present
+	/// and valid.
+	fn require(&mut self, name: &str, is_valid: impl FnOnce(&str) -> bool) -> Option<&str> {
+		if !self.contains(name) {
+			self.push(name, ViolationKind::Missing);
+			return None;
+		}
+
+		self.optional(name, is_valid)
+	}
+
+	fn into_violations(self) -> Vec<Violation> {
+		self.violations
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn valid_authorization_request() {
+		let violations = validate_authorization_request(
+			"response_type=code&client_id=s6BhdRkqt3&state=xyz&redirect_uri=https%3A%2F%2Fclient%2Eexample%2Ecom%2Fcb",
+		);
+		assert!(violations.is_empty());
+	}
+
+	#[test]
+	fn authorization_request_missing_parameters() {
+		let violations = validate_authorization_request("state=xyz");
+		assert_eq!(
+			violations,
+			vec![
+				Violation::new("response_type", ViolationKind::Missing),
+				Violation::new("client_id", ViolationKind::Missing),
+			]
+		);
+	}
+
+	#[test]
+	fn authorization_request_repeated_parameter() {
+		let violations =
+			validate_authorization_request("response_type=code&client_id=a&state=x&state=y");
+		assert_eq!(
+			violations,
+			vec![Violation::new("state", ViolationKind::Repeated)]
+		);
+	}
+
+	#[test]
+	fn authorization_request_redirect_uri_with_fragment() {
+		let violations = validate_authorization_request(
+			"response_type=code&client_id=a&redirect_uri=https%3A%2F%2Fclient.example.com%2Fcb%23frag",
+		);
+		assert_eq!(
+			violations,
+			vec![Violation::new("redirect_uri", ViolationKind::Invalid)]
+		);
+	}
+
+	#[test]
+	fn authorization_request_native_app_redirect_uri() {
+		for query in [
+			"response_type=code&client_id=a&redirect_uri=http%3A%2F%2Fclient.example.com%2Fcb",
+			"response_type=code&client_id=a&redirect_uri=http%3A%2F%2Flocalhost%2Fcb",
+		] {
+			assert!(validate_authorization_request(query).is_empty());
+			assert_eq!(
+				validate_redirect_uri_policy(query, RedirectUriPolicy::Generic),
+				None
+			);
+			assert_eq!(
+				validate_redirect_uri_policy(query, RedirectUriPolicy::NativeApp),
+				Some(Violation::new("redirect_uri", ViolationKind::Invalid))
+			);
+		}
+
+		assert_eq!(
+			validate_redirect_uri_policy(
+				"response_type=code&client_id=a&redirect_uri=http%3A%2F%2F127.0.0.1%3A51004%2Fcb",
+				RedirectUriPolicy::NativeApp
+			),
+			None
+		);
+	}
+
+	#[test]
+	fn pushed_authorization_request_reference() {
+		let query = "client_id=s6BhdRkqt3&request_uri=urn%3Aietf%3Aparams%3Aoauth%3Arequest_uri%3A6esc_11ACC5bwc014ltc14eY22c";
+		assert!(validate_authorization_request(query).is_empty());
+		#[cfg(feature = "pkce")]
+		assert_eq!(
+			validate_pkce_policy(query, ClientType::Public, PkcePolicy::Required),
+			None
+		);
+
+		assert_eq!(
+			validate_authorization_request(
+				"request_uri=urn%3Aexample%3A1&request_uri=urn%3Aexample%3A2"
+			),
+			vec![
+				Violation::new("request_uri", ViolationKind::Repeated),
+				Violation::new("client_id", ViolationKind::Missing),
+			]
+		);
+	}
+
+	#[test]
+	fn registered_redirect_uri() {
+		let registered = [
+			UriBuf::new(b"com.example.app:/cb".to_vec()).unwrap(),
+			UriBuf::new(b"http://127.0.0.1/cb".to_vec()).unwrap(),
+		];
+
+		assert_eq!(
+			validate_redirect_uri(
+				"redirect_uri=http%3A%2F%2F127.0.0.1%3A51004%2Fcb",
+				&registered
+			),
+			None
+		);
+		assert_eq!(
+			validate_redirect_uri("redirect_uri=com.example.app%3A%2Fcb", &registered),
+			None
+		);
+		assert_eq!(
+			validate_redirect_uri("redirect_uri=com.example.app%3A%2Fother", &registered),
+			Some(Violation::new("redirect_uri", ViolationKind::Invalid))
+		);
+		assert_eq!(
+			validate_redirect_uri("client_id=a", &registered),
+			Some(Violation::new("redirect_uri", ViolationKind::Missing))
+		);
+		assert_eq!(validate_redirect_uri("client_id=a", &registered[..1]), None);
+	}
+
+	#[cfg(feature = "pkce")]
+	#[test]
+	fn authorization_request_challenge_method_without_challenge() {
+		let violations = validate_authorization_request(
+			"response_type=code&client_id=a&code_challenge_method=S256",
+		);
+		assert_eq!(
+			violations,
+			vec![Violation::new("code_challenge", ViolationKind::Missing)]
+		);
+	}
+
+	#[cfg(feature = "pkce")]
+	#[test]
+	fn authorization_request_pkce_policy() {
+		let query = "response_type=code&client_id=a";
+		assert_eq!(
+			validate_pkce_policy(query, ClientType::Public, PkcePolicy::default()),
+			Some(Violation::new("code_challenge", ViolationKind::Missing))
+		);
+		assert_eq!(
+			validate_pkce_policy(query, ClientType::Confidential, PkcePolicy::default()),
+			None
+		);
+		assert_eq!(
+			validate_pkce_policy(
+				"response_type=code&client_id=a&code_challenge=E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM",
+				ClientType::Public,
+				PkcePolicy::Required
+			),
+			None
+		);
+	}
+
+	#[test]
+	fn valid_token_request() {
+		let violations = validate_token_request(
+			"grant_type=authorization_code&code=SplxlOBeZQQYbYS6WxSbIA&redirect_uri=https%3A%2F%2Fclient%2Eexample%2Ecom%2Fcb",
+		);
+		assert!(violations.is_empty());
+	}
+
+	#[test]
+	fn token_request_missing_grant_parameters() {
+		assert_eq!(
+			validate_token_request("grant_type=authorization_code"),
+			vec![Violation::new("code", ViolationKind::Missing)]
+		);
+		assert_eq!(
+			validate_token_request("grant_type=refresh_token"),
+			vec![Violation::new("refresh_token", ViolationKind::Missing)]
+		);
+		assert_eq!(
+			validate_token_request("code=abc"),
+			vec![Violation::new("grant_type", ViolationKind::Missing)]
+		);
+	}
+
+	#[cfg(feature = "pkce")]
+	#[test]
+	fn token_request_invalid_code_verifier() {
+		assert_eq!(
+			validate_token_request("grant_type=authorization_code&code=abc&code_verifier=short"),
+			vec![Violation::new("code_verifier", ViolationKind::Invalid)]
+		);
+	}
+
+	#[test]
+	fn token_request_unknown_grant_type() {
+		assert!(validate_token_request("grant_type=urn%3Aexample%3Acustom").is_empty());
+	}
+
+	#[test]
+	fn device_authorization_request() {
+		assert!(
+			validate_device_authorization_request("client_id=1406020730&scope=example_scope")
+				.is_empty()
+		);
+		assert_eq!(
+			validate_device_authorization_request("scope=example_scope"),
+			vec![Violation::new("client_id", ViolationKind::Missing)]
+		);
+		assert_eq!(
+			validate_token_request(
+				"grant_type=urn%3Aietf%3Aparams%3Aoauth%3Agrant-type%3Adevice_code"
+			),
+			vec![Violation::new("device_code", ViolationKind::Missing)]
+		);
+	}
+
+	#[test]
+	fn violations_description() {
+		let violations = vec![
+			Violation::new("code", ViolationKind::Missing),
+			Violation::new("state", ViolationKind::Repeated),
+		];
+		assert_eq!(
+			describe_violations(&violations),
+			"missing `code` parameter, repeated `state` parameter"
+		);
+	}
+}
@@ -0,0 +1,303 @@
+//! Property-based testing strategies.
+//!
+//! This module provides [`proptest`] strategies generating valid and invalid
+//! values for the string types of this crate, along with [`Arbitrary`]
+//! implementations for those types and the request structures built on top
+//! of them. It can be used to fuzz downstream integrations.
+//!
+//! Requires the `proptest` feature.
+use iref::UriBuf;
+use proptest::{
+	arbitrary::Arbitrary,
+	collection::vec,
+	option,
+	prelude::{BoxedStrategy, Just, Strategy},
+	prop_oneof,
+};
+
+use crate::{
+	AccessTokenBuf, ClientIdBuf, CodeBuf, ScopeBuf, ScopeTokenBuf, StateBuf, Stateful,
+	endpoints::{
+		device_authorization::DeviceAuthorizationRequest, introspection::IntrospectionRequest,
+		revocation::RevocationRequest, token::TokenTypeHint,
+	},
+	ext::pkce::{PkceCodeChallengeBuf, PkceCodeVerifierBuf},
+	grant::{
+		authorization_code::{
+			AuthorizationCodeAuthorizationRequest, AuthorizationCodeTokenRequest,
+		},
+		device_code::DeviceCodeTokenRequest,
+		pre_authorized_code::PreAuthorizedCodeTokenRequest,
+	},
+};
+
+/// `NQCHAR` characters.
+const NQCHAR: &str = r"[\x21\x23-\x5B\x5D-\x7E]";
+
+/// `VSCHAR` characters.
+const VSCHAR: &str = r"[\x20-\x7E]";
+
+/// Characters allowed in PKCE code verifiers and challenges.
+const UNRESERVED: &str = r"[A-Za-z0-9._~-]";
+
+fn regex(pattern: &str) -> impl Strategy<Value = String> + use<> {
+	// UNWRAP SAFETY: patterns are defined in this module.
+	proptest::string::string_regex(pattern).unwrap()
+}
+
+/// Strings of 1 to 32 `VSCHAR` characters.
+fn vschars() -> impl Strategy<Value = String> {
+	regex(&format!("{VSCHAR}{{1,32}}"))
+}
+
+/// Generates non-empty strings containing at least one character that is not
+/// a `VSCHAR`.
+///
+/// Those are invalid [`State`](crate::State), [`Code`](crate::Code),
+/// [`AccessToken`](crate::AccessToken) and
+/// [`ClientId`](crate::ClientId) values.
+pub fn invalid_vschars() -> impl Strategy<Value = String> {
+	regex(&format!(
+		r"{VSCHAR}{{0,8}}[\x00-\x1F\x7F-\u{{FF}}]{VSCHAR}{{0,8}}"
+	))
+}
+
+/// Generates valid scope tokens.
+pub fn scope_token() -> impl Strategy<Value = ScopeTokenBuf> {
+	regex(&format!("{NQCHAR}{{1,16}}")).prop_map(|s| ScopeTokenBuf::new(s).unwrap())
+}
+
+/// Generates valid scopes of 1 to 8 tokens.
+pub fn scope() -> impl Strategy<Value = ScopeBuf> {
+	vec(regex(&format!("{NQCHAR}{{1,16}}")), 1..=8)
+		.prop_map(|tokens| ScopeBuf::new(tokens.join(" ")).unwrap())
+}
+
+/// Generates invalid scopes.
+pub fn invalid_scope() -> impl Strategy<Value = String> {
+	let token = || regex(&format!("{NQCHAR}{{1,8}}"));
+	prop_oneof![
+		Just(String::new()),
+		token().prop_map(|t| format!(" {t}")),
+		token().prop_map(|t| format!("{t} ")),
+		(token(), token()).prop_map(|(a, b)| format!("{a}  {b}")),
+		(token(), r#"["\\]"#, token()).prop_map(|(a, c, b)| format!("{a}{c}{b}")),
+	]
+}
+
+/// Generates valid client identifiers, possibly empty.
+pub fn client_id() -> impl Strategy<Value = ClientIdBuf> {
+	regex(&format!("{VSCHAR}{{0,32}}")).prop_map(|s| ClientIdBuf::new(s).unwrap())
+}
+
+/// Generates valid state values.
+pub fn state() -> impl Strategy<Value = StateBuf> {
+	vschars().prop_map(|s| StateBuf::new(s).unwrap())
+}
+
+/// Generates valid authorization codes.
+pub fn code() -> impl Strategy<Value = CodeBuf> {
+	vschars().prop_map(|s| CodeBuf::new(s).unwrap())
+}
+
+/// Generates valid access tokens.
+pub fn access_token() -> impl Strategy<Value = AccessTokenBuf> {
+	vschars().prop_map(|s| AccessTokenBuf::new(s).unwrap())
+}
+
+/// Generates valid PKCE code verifiers.
+pub fn pkce_code_verifier() -> impl Strategy<Value = PkceCodeVerifierBuf> {
+	regex(&format!("{UNRESERVED}{{43,128}}")).prop_map(|s| PkceCodeVerifierBuf::new(s).unwrap())
+}
+
+/// Generates valid PKCE code challenges.
+pub fn pkce_code_challenge() -> impl Strategy<Value = PkceCodeChallengeBuf> {
+	regex(&format!("{UNRESERVED}{{43,128}}")).prop_map(|s| PkceCodeChallengeBuf::new(s).unwrap())
+}
+
+/// Generates invalid PKCE code verifiers and challenges, either too short,
+/// too long, or containing a reserved character.
+pub fn invalid_pkce_code_verifier() -> impl Strategy<Value = String> {
+	prop_oneof![
+		regex(&format!("{UNRESERVED}{{0,42}}")),
+		regex(&format!("{UNRESERVED}{{129,160}}")),
+		regex(&format!(
+			r"{UNRESERVED}{{21}}[^A-Za-z0-9._~-]{UNRESERVED}{{21}}"
+		)),
+	]
+}
+
+/// Generates `https` redirect URIs.
+pub fn redirect_uri() -> impl Strategy<Value = UriBuf> {
+	regex("https://[a-z]{1,12}\\.example(/[a-z0-9]{1,8}){0,3}")
+		.prop_map(|s| UriBuf::new(s.into_bytes()).unwrap())
+}
+
+/// Generates token type hints, including extension values.
+pub fn token_type_hint() -> impl Strategy<Value = TokenTypeHint> {
+	prop_oneof![
+		Just(TokenTypeHint::AccessToken),
+		Just(TokenTypeHint::RefreshToken),
+		regex("[a-z]{1,16}").prop_map(TokenTypeHint::Extension),
+	]
+}
+
+macro_rules! arbitrary {
+	($($ty:ty => $strategy:expr),* $(,)?) => {
+		$(
+			impl Arbitrary for $ty {
+				type Parameters = ();
+				type Strategy = BoxedStrategy<Self>;
+
+				fn arbitrary_with(_: ()) -> Self::Strategy {
+					$strategy.boxed()
+				}
+			}
+		)*
+	};
+}
+
+arbitrary! {
+	ScopeTokenBuf => scope_token(),
+	ScopeBuf => scope(),
+	ClientIdBuf => client_id(),
+	StateBuf => state(),
+	CodeBuf => code(),
+	AccessTokenBuf => access_token(),
+	PkceCodeVerifierBuf => pkce_code_verifier(),
+	PkceCodeChallengeBuf => pkce_code_challenge(),
+	TokenTypeHint => token_type_hint(),
+	AuthorizationCodeAuthorizationRequest => (
+		client_id(),
+		option::of(redirect_uri()),
+		option::of(scope()),
+	)
+		.prop_map(|(client_id, redirect_uri, scope)| {
+			Self::new(client_id, redirect_uri, scope)
+		}),
+	AuthorizationCodeTokenRequest => (
+		option::of(client_id()),
+		code(),
+		option::of(redirect_uri()),
+	)
+		.prop_map(|(client_id, code, redirect_uri)| Self::new(client_id, code, redirect_uri)),
+	DeviceAuthorizationRequest => (client_id(), option::of(scope()))
+		.prop_map(|(client_id, scope)| Self::new(client_id, scope)),
+	DeviceCodeTokenRequest => (option::of(client_id()), vschars())
+		.prop_map(|(client_id, device_code)| Self::new(client_id, device_code)),
+	PreAuthorizedCodeTokenRequest => (
+		option::of(client_id()),
+		vschars(),
+		option::of(vschars()),
+	)
+		.prop_map(|(client_id, code, tx_code)| Self::new(client_id, code, tx_code)),
+	IntrospectionRequest => (vschars(), option::of(token_type_hint()))
+		.prop_map(|(token, hint)| Self::new(token, hint)),
+	RevocationRequest => (vschars(), option::of(token_type_hint()))
+		.prop_map(|(token, hint)| Self::new(token, hint)),
+}
+
+impl<T> Arbitrary for Stateful<T>
+where
+	T: Arbitrary + 'static,
+{
+	type Parameters = T::Parameters;
+	type Strategy = BoxedStrategy<Self>;
+
+	fn arbitrary_with(args: T::Parameters) -> Self::Strategy {
+		(T::arbitrary_with(args), option::of(state()))
+			.prop_map(|(value, state)| Self::new(value, state))
+			.boxed()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use proptest::{arbitrary::any, prop_assert, prop_assert_eq, proptest};
+	use serde::{Serialize, de::DeserializeOwned};
+
+	use crate::{
+		AccessToken, ClientId, Code, Scope, ScopeToken, State,
+		ext::pkce::{PkceCodeChallenge, PkceCodeVerifier},
+	};
+
+	use super::*;
+
+	/// Checks that `value` survives a form serialization round trip.
+	fn form_round_trip<T: Serialize + DeserializeOwned>(value: &T) -> bool {
+		let form = serde_html_form::to_string(value).unwrap();
+		let decoded: T = serde_html_form::from_str(&form).unwrap();
+		serde_html_form::to_string(&decoded).unwrap() == form
+	}
+
+	proptest! {
+		#[test]
+		fn valid_strings(
+			client_id in client_id(),
+			state in state(),
+			code in code(),
+			access_token in access_token(),
+			verifier in pkce_code_verifier(),
+			challenge in pkce_code_challenge(),
+		) {
+			prop_assert!(ClientId::new(client_id.as_str()).is_ok());
+			prop_assert!(State::new(state.as_str()).is_ok());
+			prop_assert!(Code::new(code.as_str()).is_ok());
+			prop_assert!(AccessToken::new(access_token.as_str()).is_ok());
+			prop_assert!(PkceCodeVerifier::new(verifier.as_str()).is_ok());
+			prop_assert!(PkceCodeChallenge::new(challenge.as_str()).is_ok());
+		}
+
+		#[test]
+		fn invalid_strings(s in invalid_vschars(), pkce in invalid_pkce_code_verifier()) {
+			prop_assert!(ClientId::new(&s).is_err());
+			prop_assert!(State::new(&s).is_err());
+			prop_assert!(Code::new(&s).is_err());
+			prop_assert!(AccessToken::new(&s).is_err());
+			prop_assert!(PkceCodeVerifier::new(&pkce).is_err());
+			prop_assert!(PkceCodeChallenge::new(&pkce).is_err());
+		}
+
+		#[test]
+		fn invalid_scopes(s in invalid_scope()) {
+			prop_assert!(Scope::new(&s).is_err());
+		}
+
+		#[test]
+		fn scope_tokens(token in scope_token(), scope in scope()) {
+			prop_assert!(ScopeToken::new(token.as_str()).is_ok());
+			for token in &scope {
+				prop_assert!(ScopeToken::new(token.as_str()).is_ok());
+			}
+		}
+
+		#[test]
+		fn request_round_trip(
+			authorization in any::<Stateful<AuthorizationCodeAuthorizationRequest>>(),
+			token in any::<AuthorizationCodeTokenRequest>(),
+			device in any::<DeviceAuthorizationRequest>(),
+			device_code in any::<DeviceCodeTokenRequest>(),
+			pre_authorized_code in any::<PreAuthorizedCodeTokenRequest>(),
+			introspection in any::<IntrospectionRequest>(),
+			revocation in any::<RevocationRequest>(),
+		) {
+			prop_assert!(form_round_trip(&authorization));
+			prop_assert!(form_round_trip(&token));
+			prop_assert!(form_round_trip(&device));
+			prop_assert!(form_round_trip(&device_code));
+			prop_assert!(form_round_trip(&pre_authorized_code));
+			prop_assert_eq!(
+				serde_html_form::from_str::<IntrospectionRequest>(
+					&serde_html_form::to_string(&introspection).unwrap()
+				).unwrap(),
+				introspection
+			);
+			prop_assert_eq!(
+				serde_html_form::from_str::<RevocationRequest>(
+					&serde_html_form::to_string(&revocation).unwrap()
+				).unwrap(),
+				revocation
+			);
+		}
+	}
+}
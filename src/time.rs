@@ -0,0 +1,213 @@
+//! Time sources and clock skew tolerance.
+//!
+//! Every expiry check performed by this crate goes through a [`Clock`],
+//! making time-dependent flows deterministically testable with a
+//! [`MockClock`], and tolerates clock skew between parties according to a
+//! [`LeewayConfig`].
+use std::{
+	sync::{Arc, Mutex},
+	time::{Duration, SystemTime},
+};
+
+/// Source of the current time.
+pub trait Clock {
+	/// Returns the current time.
+	fn now(&self) -> SystemTime;
+
+	/// Returns the point in time `duration` from now.
+	///
+	/// This is typically used to turn a relative `expires_in` value into an
+	/// absolute expiration time upon receipt.
+	fn deadline_in(&self, duration: Duration) -> SystemTime {
+		self.now() + duration
+	}
+}
+
+impl<T: ?Sized + Clock> Clock for &T {
+	fn now(&self) -> SystemTime {
+		T::now(*self)
+	}
+}
+
+impl<T: ?Sized + Clock> Clock for Arc<T> {
+	fn now(&self) -> SystemTime {
+		T::now(self)
+	}
+}
+
+/// System clock, backed by [`SystemTime::now`].
+///
+/// On `wasm32-unknown-unknown`, where [`SystemTime::now`] panics, the time is
+/// read from the JavaScript `Date.now()` function instead.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+	#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+	fn now(&self) -> SystemTime {
+		SystemTime::now()
+	}
+
+	#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+	fn now(&self) -> SystemTime {
+		// `Date.now()` returns the number of milliseconds since the Unix epoch.
+		SystemTime::UNIX_EPOCH + Duration::from_millis(js_sys::Date::now() as u64)
+	}
+}
+
+/// Manually driven clock, for tests.
+///
+/// The time only changes when [`set`](Self::set) or
+/// [`advance`](Self::advance) is called.
+#[derive(Debug)]
+pub struct MockClock {
+	now: Mutex<SystemTime>,
+}
+
+impl MockClock {
+	/// Creates a new mock clock starting at the given time.
+	pub fn new(now: SystemTime) -> Self {
+		Self {
+			now: Mutex::new(now),
+		}
+	}
+
+	/// Creates a new mock clock starting `secs` seconds after the Unix epoch.
+	pub fn from_unix_timestamp(secs: u64) -> Self {
+		Self::new(SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+	}
+
+	/// Sets the current time.
+	pub fn set(&self, now: SystemTime) {
+		*self.now.lock().unwrap() = now
+	}
+
+	/// Moves the current time forward by the given duration.
+	pub fn advance(&self, duration: Duration) {
+		*self.now.lock().unwrap() += duration
+	}
+}
+
+impl Default for MockClock {
+	fn default() -> Self {
+		Self::new(SystemTime::UNIX_EPOCH)
+	}
+}
+
+impl Clock for MockClock {
+	fn now(&self) -> SystemTime {
+		*self.now.lock().unwrap()
+	}
+}
+
+/// Clock skew tolerance applied to expiry checks.
+///
+/// The leeway is applied conservatively depending on who set the deadline:
+/// - deadlines set by a remote party and checked locally (e.g. the `exp`
+///   claim of a received JWT) are checked with
+///   [`is_expired`](Self::is_expired), which accepts values that expired less
+///   than `leeway` ago;
+/// - deadlines of credentials held locally and presented to a remote party
+///   (e.g. an access token or a PAR `request_uri`) are checked with
+///   [`expires_soon`](Self::expires_soon), which treats values expiring in
+///   less than `leeway` as already expired.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LeewayConfig {
+	/// Maximum tolerated clock skew.
+	pub leeway: Duration,
+}
+
+impl LeewayConfig {
+	/// Default leeway of 60 seconds.
+	pub const DEFAULT_LEEWAY: Duration = Duration::from_secs(60);
+
+	/// No leeway at all.
+	pub const NONE: Self = Self::new(Duration::ZERO);
+
+	/// Creates a new leeway configuration.
+	pub const fn new(leeway: Duration) -> Self {
+		Self { leeway }
+	}
+
+	/// Checks whether a deadline set by a remote party has passed, tolerating
+	/// up to `leeway` of clock skew.
+	pub fn is_expired(&self, clock: &(impl ?Sized + Clock), expires_at: SystemTime) -> bool {
+		clock.now() > expires_at + self.leeway
+	}
+
+	/// Checks whether a "not before" time set by a remote party is still in
+	/// the future, tolerating up to `leeway` of clock skew.
+	pub fn is_not_yet_valid(&self, clock: &(impl ?Sized + Clock), not_before: SystemTime) -> bool {
+		clock.now() + self.leeway < not_before
+	}
+
+	/// Checks whether a locally held credential expires within `leeway`, in
+	/// which case it should be considered expired already.
+	pub fn expires_soon(&self, clock: &(impl ?Sized + Clock), expires_at: SystemTime) -> bool {
+		clock.now() + self.leeway >= expires_at
+	}
+}
+
+impl Default for LeewayConfig {
+	fn default() -> Self {
+		Self::new(Self::DEFAULT_LEEWAY)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn mock_clock_advance() {
+		let clock = MockClock::from_unix_timestamp(1000);
+		clock.advance(Duration::from_secs(30));
+		assert_eq!(
+			clock.now(),
+			SystemTime::UNIX_EPOCH + Duration::from_secs(1030)
+		);
+	}
+
+	#[test]
+	fn remote_deadline_tolerates_skew() {
+		let clock = MockClock::from_unix_timestamp(1000);
+		let leeway = LeewayConfig::new(Duration::from_secs(30));
+		let expires_at = SystemTime::UNIX_EPOCH + Duration::from_secs(990);
+
+		assert!(!leeway.is_expired(&clock, expires_at));
+		clock.advance(Duration::from_secs(21));
+		assert!(leeway.is_expired(&clock, expires_at));
+	}
+
+	#[test]
+	fn not_before_tolerates_skew() {
+		let clock = MockClock::from_unix_timestamp(1000);
+		let leeway = LeewayConfig::new(Duration::from_secs(30));
+
+		let not_before = SystemTime::UNIX_EPOCH + Duration::from_secs(1020);
+		assert!(!leeway.is_not_yet_valid(&clock, not_before));
+
+		let not_before = SystemTime::UNIX_EPOCH + Duration::from_secs(1031);
+		assert!(leeway.is_not_yet_valid(&clock, not_before));
+	}
+
+	#[test]
+	fn local_credential_expires_early() {
+		let clock = MockClock::from_unix_timestamp(1000);
+		let leeway = LeewayConfig::new(Duration::from_secs(30));
+		let expires_at = clock.deadline_in(Duration::from_secs(60));
+
+		assert!(!leeway.expires_soon(&clock, expires_at));
+		clock.advance(Duration::from_secs(30));
+		assert!(leeway.expires_soon(&clock, expires_at));
+	}
+
+	#[test]
+	fn no_leeway() {
+		let clock = MockClock::from_unix_timestamp(1000);
+		let expires_at = clock.now();
+
+		assert!(!LeewayConfig::NONE.is_expired(&clock, expires_at));
+		assert!(LeewayConfig::NONE.expires_soon(&clock, expires_at));
+	}
+}
@@ -0,0 +1,128 @@
+use std::{collections::BTreeMap, net::SocketAddr, time::Duration};
+
+/// Connection options of the provided [`HttpClient`](super::HttpClient)
+/// implementations.
+///
+/// High-volume token clients, such as gateways exchanging or introspecting
+/// tokens for every request, benefit from reusing connections to the
+/// authorization server. Unset options keep the defaults of the underlying
+/// HTTP client.
+///
+/// With the `reqwest` feature, a [`reqwest::Client`](::reqwest::Client) is
+/// built from this configuration with `TransportConfig::build_reqwest`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TransportConfig {
+	/// Maximum number of idle connections kept per host.
+	pub pool_max_idle_per_host: Option<usize>,
+
+	/// Duration after which idle connections are closed.
+	pub pool_idle_timeout: Option<Duration>,
+
+	/// Whether to only use HTTP/2, without protocol negotiation.
+	pub http2_prior_knowledge: bool,
+
+	/// Interval between HTTP/2 `PING` frames keeping connections alive.
+	pub http2_keep_alive_interval: Option<Duration>,
+
+	/// Duration after which a connection is closed if an HTTP/2 `PING` is
+	/// not acknowledged.
+	pub http2_keep_alive_timeout: Option<Duration>,
+
+	/// Whether to send HTTP/2 `PING` frames on idle connections.
+	pub http2_keep_alive_while_idle: bool,
+
+	/// Idle time after which TCP keepalive probes are sent.
+	pub tcp_keepalive: Option<Duration>,
+
+	/// Timeout of connection establishment.
+	pub connect_timeout: Option<Duration>,
+
+	/// Timeout of whole requests, from connection to the end of the
+	/// response body.
+	pub timeout: Option<Duration>,
+
+	/// Addresses used for the given domains instead of resolving them, to
+	/// pin the authorization servers of known issuers.
+	pub resolve: BTreeMap<String, Vec<SocketAddr>>,
+}
+
+impl TransportConfig {
+	/// Creates a new configuration keeping the defaults of the HTTP client.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Sets the maximum number of idle connections kept per host.
+	pub fn with_pool_max_idle_per_host(self, pool_max_idle_per_host: usize) -> Self {
+		Self {
+			pool_max_idle_per_host: Some(pool_max_idle_per_host),
+			..self
+		}
+	}
+
+	/// Sets the duration after which idle connections are closed.
+	pub fn with_pool_idle_timeout(self, pool_idle_timeout: Duration) -> Self {
+		Self {
+			pool_idle_timeout: Some(pool_idle_timeout),
+			..self
+		}
+	}
+
+	/// Only uses HTTP/2, without protocol negotiation.
+	///
+	/// Requests to servers that do not support HTTP/2 fail.
+	pub fn with_http2_prior_knowledge(self) -> Self {
+		Self {
+			http2_prior_knowledge: true,
+			..self
+		}
+	}
+
+	/// Keeps HTTP/2 connections alive by sending a `PING` frame every
+	/// `interval`, closing them if it is not acknowledged within `timeout`.
+	///
+	/// Idle connections are also kept alive if `while_idle` is `true`.
+	pub fn with_http2_keep_alive(
+		self,
+		interval: Duration,
+		timeout: Duration,
+		while_idle: bool,
+	) -> Self {
+		Self {
+			http2_keep_alive_interval: Some(interval),
+			http2_keep_alive_timeout: Some(timeout),
+			http2_keep_alive_while_idle: while_idle,
+			..self
+		}
+	}
+
+	/// Sets the idle time after which TCP keepalive probes are sent.
+	pub fn with_tcp_keepalive(self, tcp_keepalive: Duration) -> Self {
+		Self {
+			tcp_keepalive: Some(tcp_keepalive),
+			..self
+		}
+	}
+
+	/// Sets the timeout of connection establishment.
+	pub fn with_connect_timeout(self, connect_timeout: Duration) -> Self {
+		Self {
+			connect_timeout: Some(connect_timeout),
+			..self
+		}
+	}
+
+	/// Sets the timeout of whole requests.
+	pub fn with_timeout(self, timeout: Duration) -> Self {
+		Self {
+			timeout: Some(timeout),
+			..self
+		}
+	}
+
+	/// Connects to the given addresses instead of resolving `domain`.
+	pub fn with_resolve(mut self, domain: impl Into<String>, addrs: Vec<SocketAddr>) -> Self {
+		self.resolve.insert(domain.into(), addrs);
+		self
+	}
+}
@@ -0,0 +1,462 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use http::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+use super::{RecordedExchange, RecordingClient};
+
+/// Version of the HAR format produced by [`Har::from_exchanges`].
+pub const HAR_VERSION: &str = "1.2";
+
+/// HTTP Archive (HAR) of recorded exchanges.
+///
+/// See: <http://www.softwareishard.com/blog/har-12-spec/>
+///
+/// HAR files can be opened by the developer tools of web browsers and most
+/// HTTP debugging tools, which makes them a convenient format to share the
+/// exchanges of a [`RecordingClient`] when reporting interoperability issues.
+/// Since the exchanges are recorded with sensitive values masked, so is the
+/// archive.
+///
+/// ```
+/// # use open_auth2::transport::RecordingClient;
+/// # fn dump<H>(client: &RecordingClient<H>) -> serde_json::Result<String> {
+/// serde_json::to_string_pretty(&client.to_har())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Har {
+	/// The archive.
+	pub log: HarLog,
+}
+
+impl Har {
+	/// Creates an archive of the given exchanges.
+	pub fn from_exchanges<'a>(exchanges: impl IntoIterator<Item = &'a RecordedExchange>) -> Self {
+		Self {
+			log: HarLog {
+				version: HAR_VERSION.to_owned(),
+				creator: HarCreator {
+					name: env!("CARGO_PKG_NAME").to_owned(),
+					version: env!("CARGO_PKG_VERSION").to_owned(),
+				},
+				entries: exchanges.into_iter().map(HarEntry::new).collect(),
+			},
+		}
+	}
+}
+
+impl<H> RecordingClient<H> {
+	/// Returns an archive of the recorded exchanges.
+	pub fn to_har(&self) -> Har {
+		Har::from_exchanges(&self.exchanges())
+	}
+}
+
+/// Root of an HTTP Archive.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HarLog {
+	/// Version of the format.
+	pub version: String,
+
+	/// Application that created the archive.
+	pub creator: HarCreator,
+
+	/// Exchanges, from the oldest to the most recent.
+	pub entries: Vec<HarEntry>,
+}
+
+/// Application that created an HTTP Archive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HarCreator {
+	/// Name of the application.
+	pub name: String,
+
+	/// Version of the application.
+	pub version: String,
+}
+
+/// Exchange of an HTTP Archive.
+#[skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarEntry {
+	/// Date and time at which the request was sent, in the RFC 3339 format.
+	pub started_date_time: String,
+
+	/// Time elapsed until the response was received, in milliseconds.
+	pub time: f64,
+
+	/// The request.
+	pub request: HarRequest,
+
+	/// The response, with a `0` status if the request failed.
+	pub response: HarResponse,
+
+	/// Cache usage, always empty.
+	pub cache: serde_json::Map<String, serde_json::Value>,
+
+	/// Timings of the exchange, in milliseconds.
+	pub timings: HarTimings,
+
+	/// Error returned by the HTTP client, if the request failed.
+	pub comment: Option<String>,
+}
+
+impl HarEntry {
+	/// Creates an entry from the given exchange.
+	pub fn new(exchange: &RecordedExchange) -> Self {
+		let time = exchange.time.as_secs_f64() * 1000.0;
+		let request = &exchange.request;
+		let query = request
+			.uri
+			.split_once('?')
+			.map(|(_, query)| query)
+			.unwrap_or_default();
+
+		Self {
+			started_date_time: format_date_time(exchange.started_at),
+			time,
+			request: HarRequest {
+				method: request.method.clone(),
+				url: request.uri.clone(),
+				http_version: HTTP_VERSION.to_owned(),
+				cookies: Vec::new(),
+				headers: name_values(&request.headers),
+				query_string: serde_html_form::from_str::<Vec<(String, String)>>(query)
+					.unwrap_or_default()
+					.into_iter()
+					.map(|(name, value)| HarNameValue { name, value })
+					.collect(),
+				post_data: (!request.body.is_empty()).then(|| HarPostData {
+					mime_type: header(&request.headers, "content-type"),
+					text: request.body.clone(),
+				}),
+				headers_size: -1,
+				body_size: -1,
+			},
+			response: match &exchange.response {
+				Some(response) => HarResponse {
+					status: response.status,
+					status_text: StatusCode::from_u16(response.status)
+						.ok()
+						.and_then(|status| status.canonical_reason())
+						.unwrap_or_default()
+						.to_owned(),
+					http_version: HTTP_VERSION.to_owned(),
+					cookies: Vec::new(),
+					headers: name_values(&response.headers),
+					content: HarContent {
+						size: -1,
+						mime_type: header(&response.headers, "content-type"),
+						text: Some(response.body.clone()),
+					},
+					redirect_url: header(&response.headers, "location"),
+					headers_size: -1,
+					body_size: -1,
+				},
+				None => HarResponse {
+					status: 0,
+					status_text: String::new(),
+					http_version: String::new(),
+					cookies: Vec::new(),
+					headers: Vec::new(),
+					content: HarContent {
+						size: 0,
+						mime_type: String::new(),
+						text: None,
+					},
+					redirect_url: String::new(),
+					headers_size: -1,
+					body_size: -1,
+				},
+			},
+			cache: serde_json::Map::new(),
+			timings: HarTimings {
+				send: 0.0,
+				wait: time,
+				receive: 0.0,
+			},
+			comment: exchange.error.clone(),
+		}
+	}
+}
+
+/// HTTP version of the recorded exchanges, which is not known.
+const HTTP_VERSION: &str = "HTTP/1.1";
+
+/// Request of an HTTP Archive.
+#[skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarRequest {
+	/// The request method.
+	pub method: String,
+
+	/// The request URI.
+	pub url: String,
+
+	/// The HTTP version.
+	pub http_version: String,
+
+	/// Cookies, always empty since the `Cookie` header is masked.
+	pub cookies: Vec<HarNameValue>,
+
+	/// The request headers.
+	pub headers: Vec<HarNameValue>,
+
+	/// The query parameters of the request URI.
+	pub query_string: Vec<HarNameValue>,
+
+	/// The request body, if any.
+	pub post_data: Option<HarPostData>,
+
+	/// Size of the request headers, `-1` if unknown.
+	pub headers_size: i64,
+
+	/// Size of the request body, `-1` if unknown.
+	pub body_size: i64,
+}
+
+/// Body of a request of an HTTP Archive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarPostData {
+	/// Media type of the body.
+	pub mime_type: String,
+
+	/// The body.
+	pub text: String,
+}
+
+/// Response of an HTTP Archive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarResponse {
+	/// The response status code.
+	pub status: u16,
+
+	/// The reason phrase of the status code.
+	pub status_text: String,
+
+	/// The HTTP version.
+	pub http_version: String,
+
+	/// Cookies, always empty since the `Set-Cookie` header is masked.
+	pub cookies: Vec<HarNameValue>,
+
+	/// The response headers.
+	pub headers: Vec<HarNameValue>,
+
+	/// The response body.
+	pub content: HarContent,
+
+	/// Target of the `Location` header.
+	#[serde(rename = "redirectURL")]
+	pub redirect_url: String,
+
+	/// Size of the response headers, `-1` if unknown.
+	pub headers_size: i64,
+
+	/// Size of the response body, `-1` if unknown.
+	pub body_size: i64,
+}
+
+/// Body of a response of an HTTP Archive.
+#[skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarContent {
+	/// Size of the body, `-1` if unknown.
+	pub size: i64,
+
+	/// Media type of the body.
+	pub mime_type: String,
+
+	/// The body.
+	pub text: Option<String>,
+}
+
+/// Header, cookie or query parameter of an HTTP Archive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HarNameValue {
+	/// Name.
+	pub name: String,
+
+	/// Value.
+	pub value: String,
+}
+
+/// Timings of an exchange of an HTTP Archive, in milliseconds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HarTimings {
+	/// Time spent sending the request.
+	pub send: f64,
+
+	/// Time spent waiting for the response.
+	pub wait: f64,
+
+	/// Time spent receiving the response.
+	pub receive: f64,
+}
+
+fn name_values(headers: &[(String, String)]) -> Vec<HarNameValue> {
+	headers
+		.iter()
+		.map(|(name, value)| HarNameValue {
+			name: name.clone(),
+			value: value.clone(),
+		})
+		.collect()
+}
+
+fn header(headers: &[(String, String)], name: &str) -> String {
+	headers
+		.iter()
+		.find(|(n, _)| n == name)
+		.map(|(_, value)| value.clone())
+		.unwrap_or_default()
+}
+
+/// Formats the given time in the RFC 3339 format, in UTC with millisecond
+/// precision.
+fn format_date_time(time: SystemTime) -> String {
+	let duration = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+	let secs = duration.as_secs();
+	let (days, secs) = (secs / 86400, secs % 86400);
+
+	// Civil date from the number of days since the Unix epoch.
+	// See: <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+	let z = days + 719468;
+	let era = z / 146097;
+	let doe = z % 146097;
+	let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+	let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+	let mp = (5 * doy + 2) / 153;
+	let day = doy - (153 * mp + 2) / 5 + 1;
+	let month = if mp < 10 { mp + 3 } else { mp - 9 };
+	let year = yoe + era * 400 + u64::from(month <= 2);
+
+	format!(
+		"{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+		secs / 3600,
+		secs / 60 % 60,
+		secs % 60,
+		duration.subsec_millis()
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+
+	use crate::transport::{RecordedRequest, RecordedResponse};
+
+	use super::*;
+
+	#[test]
+	fn date_time() {
+		assert_eq!(format_date_time(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+		assert_eq!(
+			format_date_time(UNIX_EPOCH + Duration::from_millis(951_782_400_123)),
+			"2000-02-29T00:00:00.123Z"
+		);
+		assert_eq!(
+			format_date_time(UNIX_EPOCH + Duration::from_secs(1_792_152_000)),
+			"2026-10-16T12:00:00.000Z"
+		);
+	}
+
+	#[test]
+	fn har() {
+		let exchanges = [
+			RecordedExchange {
+				started_at: UNIX_EPOCH + Duration::from_secs(1_792_152_000),
+				request: RecordedRequest {
+					method: "POST".to_owned(),
+					uri: "https://server.example.com/token".to_owned(),
+					headers: vec![
+						(
+							"content-type".to_owned(),
+							"application/x-www-form-urlencoded".to_owned(),
+						),
+						("authorization".to_owned(), "<redacted>".to_owned()),
+					],
+					body: "grant_type=authorization_code&code=%3Credacted%3E".to_owned(),
+				},
+				response: Some(RecordedResponse {
+					status: 200,
+					headers: vec![("content-type".to_owned(), "application/json".to_owned())],
+					body: r#"{"access_token":"<redacted>","token_type":"Bearer"}"#.to_owned(),
+				}),
+				error: None,
+				time: Duration::from_millis(42),
+			},
+			RecordedExchange {
+				started_at: UNIX_EPOCH + Duration::from_secs(1_792_152_001),
+				request: RecordedRequest {
+					method: "GET".to_owned(),
+					uri: "https://server.example.com/userinfo?schema=openid".to_owned(),
+					headers: Vec::new(),
+					body: String::new(),
+				},
+				response: None,
+				error: Some("request error: connection refused".to_owned()),
+				time: Duration::from_millis(3),
+			},
+		];
+
+		let har = serde_json::to_value(Har::from_exchanges(&exchanges)).unwrap();
+		assert_eq!(har["log"]["version"], "1.2");
+		assert_eq!(
+			har["log"]["entries"][0],
+			serde_json::json!({
+				"startedDateTime": "2026-10-16T12:00:00.000Z",
+				"time": 42.0,
+				"request": {
+					"method": "POST",
+					"url": "https://server.example.com/token",
+					"httpVersion": "HTTP/1.1",
+					"cookies": [],
+					"headers": [
+						{ "name": "content-type", "value": "application/x-www-form-urlencoded" },
+						{ "name": "authorization", "value": "<redacted>" }
+					],
+					"queryString": [],
+					"postData": {
+						"mimeType": "application/x-www-form-urlencoded",
+						"text": "grant_type=authorization_code&code=%3Credacted%3E"
+					},
+					"headersSize": -1,
+					"bodySize": -1
+				},
+				"response": {
+					"status": 200,
+					"statusText": "OK",
+					"httpVersion": "HTTP/1.1",
+					"cookies": [],
+					"headers": [{ "name": "content-type", "value": "application/json" }],
+					"content": {
+						"size": -1,
+						"mimeType": "application/json",
+						"text": r#"{"access_token":"<redacted>","token_type":"Bearer"}"#
+					},
+					"redirectURL": "",
+					"headersSize": -1,
+					"bodySize": -1
+				},
+				"cache": {},
+				"timings": { "send": 0.0, "wait": 42.0, "receive": 0.0 }
+			})
+		);
+
+		let entry = &har["log"]["entries"][1];
+		assert_eq!(
+			entry["request"]["queryString"],
+			serde_json::json!([{ "name": "schema", "value": "openid" }])
+		);
+		assert_eq!(entry["response"]["status"], 0);
+		assert_eq!(entry["comment"], "request error: connection refused");
+	}
+}
@@ -1,8 +1,17 @@
 use crate::client::OAuth2ClientError;
 
+mod config;
+mod har;
+mod recording;
+mod redirect;
 #[cfg(feature = "reqwest")]
 mod reqwest;
 
+pub use config::*;
+pub use har::*;
+pub use recording::*;
+pub use redirect::*;
+
 /// An asynchronous HTTP client capable of sending raw requests.
 ///
 /// This trait abstracts over the actual HTTP implementation, allowing the
@@ -0,0 +1,194 @@
+use http::{StatusCode, Uri, header::LOCATION};
+use iref::UriRef;
+
+use crate::client::OAuth2ClientError;
+
+use super::HttpClient;
+
+/// HTTP client following scheme-upgrade redirects only.
+///
+/// Some misconfigured authorization servers redirect their endpoints from
+/// `http` to `https`. Blindly following redirects on token or pushed
+/// authorization requests is dangerous, since the request carries client
+/// credentials and grants. This client follows a single `307 Temporary
+/// Redirect` or `308 Permanent Redirect` response, and only if it points to
+/// the same origin, upgraded from `http` to `https`. Any other redirect is
+/// reported as an error describing it.
+///
+/// The inner client must not follow redirects itself. For instance, a
+/// [`reqwest::Client`](::reqwest::Client) must be built with
+/// `redirect::Policy::none()`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SafeRedirects<H>(pub H);
+
+impl<H: HttpClient> HttpClient for SafeRedirects<H> {
+	async fn send(
+		&self,
+		request: http::Request<Vec<u8>>,
+	) -> Result<http::Response<Vec<u8>>, OAuth2ClientError> {
+		let method = request.method().clone();
+		let uri = request.uri().clone();
+		let headers = request.headers().clone();
+		let body = request.body().clone();
+
+		let response = self.0.send(request).await?;
+		if !response.status().is_redirection() || response.status() == StatusCode::NOT_MODIFIED {
+			return Ok(response);
+		}
+
+		let target = upgrade_target(&uri, &response)?;
+		log::warn!("following scheme upgrade redirect from {uri} to {target}");
+
+		let mut request = http::Request::builder()
+			.method(method)
+			.uri(target)
+			.body(body)
+			.unwrap();
+		*request.headers_mut() = headers;
+
+		self.0.send(request).await
+	}
+}
+
+/// Returns the target of a redirect response, if it is a same-origin scheme
+/// upgrade preserving the request method and body.
+fn upgrade_target(uri: &Uri, response: &http::Response<Vec<u8>>) -> Result<Uri, OAuth2ClientError> {
+	let status = response.status();
+	let location = response
+		.headers()
+		.get(LOCATION)
+		.and_then(|value| value.to_str().ok())
+		.ok_or_else(|| {
+			OAuth2ClientError::response(format!("{status} redirect without valid location"))
+		})?;
+
+	let refuse = |reason: &str| {
+		OAuth2ClientError::response(format!(
+			"refusing to follow {status} redirect from {uri} to {location}: {reason}"
+		))
+	};
+
+	if status != StatusCode::TEMPORARY_REDIRECT && status != StatusCode::PERMANENT_REDIRECT {
+		return Err(refuse(
+			"only 307 and 308 redirects preserve the request method and body",
+		));
+	}
+
+	let target = iref::Uri::new(uri.to_string().as_bytes())
+		.ok()
+		.zip(UriRef::new(location.as_bytes()).ok())
+		.and_then(|(base, location)| location.resolved(base).as_str().parse::<Uri>().ok())
+		.ok_or_else(|| refuse("invalid location"))?;
+
+	let is_upgrade = uri.scheme_str() == Some("http")
+		&& target.scheme_str() == Some("https")
+		&& uri.host() == target.host()
+		&& uri.port_u16().is_none_or(|port| port == 80)
+		&& target.port_u16().is_none_or(|port| port == 443)
+		&& uri.path_and_query() == target.path_and_query();
+
+	if is_upgrade {
+		Ok(target)
+	} else {
+		Err(refuse(
+			"only same-origin http to https redirects are allowed",
+		))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Mutex;
+
+	use super::*;
+
+	/// Server redirecting every `http` request to `location`.
+	struct RedirectingServer {
+		status: StatusCode,
+		location: &'static str,
+		requests: Mutex<Vec<(http::Method, Uri, Vec<u8>)>>,
+	}
+
+	impl RedirectingServer {
+		fn new(status: StatusCode, location: &'static str) -> Self {
+			Self {
+				status,
+				location,
+				requests: Mutex::new(Vec::new()),
+			}
+		}
+	}
+
+	impl HttpClient for RedirectingServer {
+		async fn send(
+			&self,
+			request: http::Request<Vec<u8>>,
+		) -> Result<http::Response<Vec<u8>>, OAuth2ClientError> {
+			let (parts, body) = request.into_parts();
+			let redirect = parts.uri.scheme_str() == Some("http");
+			self.requests
+				.lock()
+				.unwrap()
+				.push((parts.method, parts.uri, body));
+
+			let response = if redirect {
+				http::Response::builder()
+					.status(self.status)
+					.header(LOCATION, self.location)
+			} else {
+				http::Response::builder()
+			};
+
+			Ok(response.body(Vec::new()).unwrap())
+		}
+	}
+
+	fn token_request() -> http::Request<Vec<u8>> {
+		http::Request::builder()
+			.method(http::Method::POST)
+			.uri("http://server.example.com/token")
+			.body(b"grant_type=client_credentials".to_vec())
+			.unwrap()
+	}
+
+	#[tokio::test]
+	async fn scheme_upgrade() {
+		let server = RedirectingServer::new(
+			StatusCode::PERMANENT_REDIRECT,
+			"https://server.example.com/token",
+		);
+
+		let response = SafeRedirects(&server).send(token_request()).await.unwrap();
+		assert_eq!(response.status(), StatusCode::OK);
+
+		let requests = server.requests.into_inner().unwrap();
+		assert_eq!(requests.len(), 2);
+		assert_eq!(requests[1].0, http::Method::POST);
+		assert_eq!(requests[1].1, "https://server.example.com/token");
+		assert_eq!(requests[1].2, b"grant_type=client_credentials");
+	}
+
+	#[tokio::test]
+	async fn unsafe_redirects() {
+		for (status, location) in [
+			(StatusCode::FOUND, "https://server.example.com/token"),
+			(
+				StatusCode::TEMPORARY_REDIRECT,
+				"https://evil.example.com/token",
+			),
+			(
+				StatusCode::TEMPORARY_REDIRECT,
+				"https://server.example.com/other",
+			),
+			(StatusCode::TEMPORARY_REDIRECT, "/token"),
+		] {
+			let server = RedirectingServer::new(status, location);
+			let error = SafeRedirects(&server)
+				.send(token_request())
+				.await
+				.unwrap_err();
+			assert!(matches!(error, OAuth2ClientError::Response(_)));
+			assert_eq!(server.requests.into_inner().unwrap().len(), 1);
+		}
+	}
+}
@@ -1,14 +1,73 @@
-use crate::client::OAuth2ClientError;
+use crate::{client::OAuth2ClientError, transport::Redaction};
 
 use super::HttpClient;
 
+#[cfg(not(target_arch = "wasm32"))]
+use super::TransportConfig;
+
+#[cfg(not(target_arch = "wasm32"))]
+impl TransportConfig {
+	/// Applies this configuration to the given `reqwest` client builder.
+	pub fn apply_reqwest(&self, mut builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+		if let Some(max) = self.pool_max_idle_per_host {
+			builder = builder.pool_max_idle_per_host(max);
+		}
+
+		if let Some(timeout) = self.pool_idle_timeout {
+			builder = builder.pool_idle_timeout(timeout);
+		}
+
+		if self.http2_prior_knowledge {
+			builder = builder.http2_prior_knowledge();
+		}
+
+		if let Some(interval) = self.http2_keep_alive_interval {
+			builder = builder.http2_keep_alive_interval(interval);
+		}
+
+		if let Some(timeout) = self.http2_keep_alive_timeout {
+			builder = builder.http2_keep_alive_timeout(timeout);
+		}
+
+		if let Some(keepalive) = self.tcp_keepalive {
+			builder = builder.tcp_keepalive(keepalive);
+		}
+
+		if let Some(timeout) = self.connect_timeout {
+			builder = builder.connect_timeout(timeout);
+		}
+
+		if let Some(timeout) = self.timeout {
+			builder = builder.timeout(timeout);
+		}
+
+		for (domain, addrs) in &self.resolve {
+			builder = builder.resolve_to_addrs(domain, addrs);
+		}
+
+		builder.http2_keep_alive_while_idle(self.http2_keep_alive_while_idle)
+	}
+
+	/// Builds a `reqwest` client with this configuration.
+	///
+	/// The client does not follow redirects, which would forward client
+	/// credentials and grants to another server. Wrap it in
+	/// [`SafeRedirects`](super::SafeRedirects) to follow scheme upgrades.
+	pub fn build_reqwest(&self) -> Result<reqwest::Client, OAuth2ClientError> {
+		self.apply_reqwest(reqwest::Client::builder().redirect(reqwest::redirect::Policy::none()))
+			.build()
+			.map_err(OAuth2ClientError::request)
+	}
+}
+
 impl HttpClient for reqwest::Client {
 	async fn send(
 		&self,
 		request: http::Request<Vec<u8>>,
 	) -> Result<http::Response<Vec<u8>>, OAuth2ClientError> {
-		log::debug!("HTTP request to: {}", request.uri());
-		log::trace!("HTTP request: {request:?}");
+		let redaction = Redaction::global();
+		log::debug!("HTTP request to: {}", redaction.redact_uri(request.uri()));
+		log::trace!("HTTP request: {:?}", redaction.request(&request));
 
 		let response = self
 			.execute(request.try_into().map_err(OAuth2ClientError::request)?)
@@ -36,8 +95,58 @@ impl HttpClient for reqwest::Client {
 			)
 			.map_err(OAuth2ClientError::response)?;
 
-		log::trace!("HTTP response: {response:?}");
+		log::trace!("HTTP response: {:?}", redaction.response(&response));
 
 		Ok(response)
 	}
 }
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+	use std::{
+		io::{Read, Write},
+		net::TcpListener,
+		time::Duration,
+	};
+
+	use super::*;
+
+	#[tokio::test]
+	async fn pinned_issuer() {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let addr = listener.local_addr().unwrap();
+		let server = std::thread::spawn(move || {
+			let (mut stream, _) = listener.accept().unwrap();
+			let mut request = [0; 1024];
+			let len = stream.read(&mut request).unwrap();
+			stream
+				.write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\n{}")
+				.unwrap();
+			String::from_utf8_lossy(&request[..len]).into_owned()
+		});
+
+		let client = TransportConfig::new()
+			.with_pool_max_idle_per_host(8)
+			.with_pool_idle_timeout(Duration::from_secs(30))
+			.with_http2_keep_alive(Duration::from_secs(10), Duration::from_secs(5), true)
+			.with_tcp_keepalive(Duration::from_secs(60))
+			.with_connect_timeout(Duration::from_secs(5))
+			.with_resolve("server.example.com", vec![addr])
+			.build_reqwest()
+			.unwrap();
+
+		let response = client
+			.send(
+				http::Request::get(format!("http://server.example.com:{}/token", addr.port()))
+					.body(Vec::new())
+					.unwrap(),
+			)
+			.await
+			.unwrap();
+		assert_eq!(response.body(), b"{}");
+
+		let request = server.join().unwrap();
+		assert!(request.starts_with("GET /token HTTP/1.1\r\n"));
+		assert!(request.contains(&format!("host: server.example.com:{}", addr.port())));
+	}
+}
@@ -0,0 +1,225 @@
+use http::{HeaderValue, StatusCode};
+use serde::de::DeserializeOwned;
+
+use crate::client::OAuth2ClientError;
+
+use super::{APPLICATION_JSON, expect_content_type};
+
+/// Maximum size, in bytes, of a response body.
+///
+/// Endpoint responses are small JSON documents. Larger bodies are rejected
+/// before being parsed, to bound the work done on behalf of a misbehaving or
+/// compromised server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ResponseBodyLimit(pub usize);
+
+impl ResponseBodyLimit {
+	/// Default limit of 1 MiB.
+	pub const DEFAULT: Self = Self(1024 * 1024);
+
+	/// Checks that the given body does not exceed the limit.
+	pub fn check(self, body: &[u8]) -> Result<(), OAuth2ClientError> {
+		if body.len() > self.0 {
+			Err(OAuth2ClientError::response(format!(
+				"response body is {} bytes long, exceeding the limit of {} bytes",
+				body.len(),
+				self.0
+			)))
+		} else {
+			Ok(())
+		}
+	}
+}
+
+impl Default for ResponseBodyLimit {
+	fn default() -> Self {
+		Self::DEFAULT
+	}
+}
+
+/// How unsuccessful responses are reported by a [`ResponseDecoder`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ErrorMapping {
+	/// Reported with [`OAuth2ClientError::unexpected_status`].
+	#[default]
+	Status,
+
+	/// Parsed as an OAuth 2.0 error response if possible, with
+	/// [`OAuth2ClientError::token_error`].
+	///
+	/// See: <https://datatracker.ietf.org/doc/html/rfc6749#section-5.2>
+	ErrorResponse,
+}
+
+/// Decoder of endpoint responses.
+///
+/// Shared by the requests of this crate to check, in order:
+///
+/// - the response status, mapping unexpected ones to an error;
+/// - the body size, against a [`ResponseBodyLimit`];
+/// - the `Content-Type` header, ignoring parameters other than `charset`,
+///   which must be UTF-8 if present;
+/// - the body encoding, which must be UTF-8;
+///
+/// before deserializing the JSON body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResponseDecoder {
+	/// Expected status of successful responses.
+	pub status: StatusCode,
+
+	/// Expected content type.
+	pub content_type: HeaderValue,
+
+	/// Maximum size of the body.
+	pub body_limit: ResponseBodyLimit,
+
+	/// How unsuccessful responses are reported.
+	pub errors: ErrorMapping,
+}
+
+impl ResponseDecoder {
+	/// Creates a decoder of JSON responses with the given status.
+	pub fn json(status: StatusCode) -> Self {
+		Self {
+			status,
+			content_type: APPLICATION_JSON,
+			body_limit: ResponseBodyLimit::DEFAULT,
+			errors: ErrorMapping::Status,
+		}
+	}
+
+	/// Parses unsuccessful responses as OAuth 2.0 error responses.
+	pub fn with_error_responses(self) -> Self {
+		Self {
+			errors: ErrorMapping::ErrorResponse,
+			..self
+		}
+	}
+
+	/// Sets the maximum size of the body.
+	pub fn with_body_limit(self, body_limit: ResponseBodyLimit) -> Self {
+		Self { body_limit, ..self }
+	}
+
+	/// Checks the status, body size and content type of the given response.
+	pub fn check(&self, response: &http::Response<Vec<u8>>) -> Result<(), OAuth2ClientError> {
+		if response.status() != self.status {
+			return Err(match self.errors {
+				ErrorMapping::Status => OAuth2ClientError::unexpected_status(response),
+				ErrorMapping::ErrorResponse => OAuth2ClientError::token_error(response),
+			});
+		}
+
+		self.body_limit.check(response.body())?;
+		expect_content_type(response.headers(), &self.content_type)
+	}
+
+	/// Checks the given response and deserializes its JSON body.
+	pub fn decode<T: DeserializeOwned>(
+		&self,
+		response: http::Response<Vec<u8>>,
+	) -> Result<http::Response<T>, OAuth2ClientError> {
+		self.check(&response)?;
+
+		let body = std::str::from_utf8(response.body()).map_err(OAuth2ClientError::response)?;
+		let body = serde_json::from_str(body).map_err(OAuth2ClientError::response)?;
+
+		Ok(response.map(|_| body))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use http::header::CONTENT_TYPE;
+
+	use super::*;
+
+	fn response(status: StatusCode, content_type: &str, body: &[u8]) -> http::Response<Vec<u8>> {
+		http::Response::builder()
+			.status(status)
+			.header(CONTENT_TYPE, content_type)
+			.body(body.to_vec())
+			.unwrap()
+	}
+
+	#[test]
+	fn decode() {
+		let decoder = ResponseDecoder::json(StatusCode::OK);
+
+		let value: serde_json::Value = decoder
+			.decode(response(
+				StatusCode::OK,
+				"application/json; charset=UTF-8",
+				br#"{"active":true}"#,
+			))
+			.unwrap()
+			.into_body();
+		assert_eq!(value, serde_json::json!({ "active": true }));
+
+		for content_type in [
+			"application/jsonp",
+			"text/html",
+			"application/json; charset=iso-8859-1",
+		] {
+			assert!(
+				decoder
+					.decode::<serde_json::Value>(response(StatusCode::OK, content_type, b"{}"))
+					.is_err()
+			);
+		}
+
+		assert!(
+			decoder
+				.decode::<serde_json::Value>(response(
+					StatusCode::OK,
+					"application/json",
+					b"\"\xff\""
+				))
+				.is_err()
+		);
+	}
+
+	#[test]
+	fn body_limit() {
+		let decoder = ResponseDecoder::json(StatusCode::OK).with_body_limit(ResponseBodyLimit(4));
+
+		assert!(
+			decoder
+				.decode::<serde_json::Value>(response(StatusCode::OK, "application/json", b"{}"))
+				.is_ok()
+		);
+		assert!(
+			decoder
+				.decode::<serde_json::Value>(response(
+					StatusCode::OK,
+					"application/json",
+					b"[1, 2]"
+				))
+				.is_err()
+		);
+	}
+
+	#[test]
+	fn error_mapping() {
+		let error = || {
+			response(
+				StatusCode::BAD_REQUEST,
+				"application/json",
+				br#"{"error":"invalid_request"}"#,
+			)
+		};
+
+		assert!(matches!(
+			ResponseDecoder::json(StatusCode::CREATED).decode::<serde_json::Value>(error()),
+			Err(OAuth2ClientError::ServerError(StatusCode::BAD_REQUEST))
+		));
+		assert_eq!(
+			ResponseDecoder::json(StatusCode::CREATED)
+				.with_error_responses()
+				.decode::<serde_json::Value>(error())
+				.unwrap_err()
+				.error_code(),
+			Some("invalid_request")
+		);
+	}
+}
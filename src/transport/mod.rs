@@ -1,12 +1,16 @@
 //! HTTP transport layer, content type encoding, and client abstraction.
 use http::{HeaderMap, HeaderValue, header};
-use serde::Serialize;
+use serde::{Serialize, de::DeserializeOwned};
 
 use crate::client::OAuth2ClientError;
 
 mod client;
+mod decode;
+mod redact;
 
 pub use client::*;
+pub use decode::*;
+pub use redact::*;
 
 /// `Content-Type: application/json` header value.
 pub const APPLICATION_JSON: HeaderValue = HeaderValue::from_static("application/json");
@@ -15,10 +19,16 @@ pub const APPLICATION_JSON: HeaderValue = HeaderValue::from_static("application/
 pub const APPLICATION_X_WWW_FORM_URLENCODED: HeaderValue =
 	HeaderValue::from_static("application/x-www-form-urlencoded");
 
+/// `Content-Type: application/cbor` header value.
+#[cfg(feature = "cbor")]
+pub const APPLICATION_CBOR: HeaderValue = HeaderValue::from_static("application/cbor");
+
 /// Validates that the response `Content-Type` header matches the expected
 /// value.
 ///
-/// Returns an error if the header is missing or does not match.
+/// The media types are compared case-insensitively, ignoring parameters,
+/// except for `charset` which must be UTF-8 if present. Returns an error if
+/// the header is missing or does not match.
 pub fn expect_content_type(
 	headers: &HeaderMap,
 	expected_value: &HeaderValue,
@@ -27,16 +37,30 @@ pub fn expect_content_type(
 		.get(header::CONTENT_TYPE)
 		.ok_or_else(|| OAuth2ClientError::response("missing content type"))?;
 
-	if !content_type
-		.as_bytes()
-		.starts_with(expected_value.as_bytes())
-	{
-		Err(OAuth2ClientError::response("unexpected content type"))
-	} else {
+	if media_type_matches(content_type, expected_value) {
 		Ok(())
+	} else {
+		Err(OAuth2ClientError::response("unexpected content type"))
 	}
 }
 
+fn media_type_matches(value: &HeaderValue, expected: &HeaderValue) -> bool {
+	let Ok(value) = value.to_str() else {
+		return false;
+	};
+
+	let mut parts = value.split(';');
+	let essence = parts.next().unwrap_or_default().trim();
+
+	essence.as_bytes().eq_ignore_ascii_case(expected.as_bytes())
+		&& parts.all(|param| match param.split_once('=') {
+			Some((name, value)) if name.trim().eq_ignore_ascii_case("charset") => {
+				value.trim().trim_matches('"').eq_ignore_ascii_case("utf-8")
+			}
+			_ => true,
+		})
+}
+
 /// Trait for encoding request bodies with a specific content type.
 pub trait ContentType {
 	/// The `Content-Type` header value, or `None` for requests with no body.
@@ -81,3 +105,71 @@ impl ContentType for WwwFormUrlEncoded {
 		serde_html_form::to_string(value).unwrap().into_bytes()
 	}
 }
+
+/// Trait for decoding response bodies with a specific content type.
+///
+/// Token endpoint responses are JSON encoded, but some authorization
+/// servers, or constrained environments, use other encodings. Those can be
+/// handled by implementing this trait and calling
+/// [`RequestBuilder::with_response_content_type`](crate::endpoints::RequestBuilder::with_response_content_type).
+pub trait ResponseContentType {
+	/// The expected `Content-Type` header value.
+	const VALUE: HeaderValue;
+
+	/// Deserializes the given response body using this content type's
+	/// encoding.
+	fn decode<T: DeserializeOwned>(body: &[u8]) -> Result<T, OAuth2ClientError>;
+}
+
+impl ResponseContentType for Json {
+	const VALUE: HeaderValue = APPLICATION_JSON;
+
+	fn decode<T: DeserializeOwned>(body: &[u8]) -> Result<T, OAuth2ClientError> {
+		serde_json::from_slice(body).map_err(OAuth2ClientError::response)
+	}
+}
+
+impl ResponseContentType for WwwFormUrlEncoded {
+	const VALUE: HeaderValue = APPLICATION_X_WWW_FORM_URLENCODED;
+
+	fn decode<T: DeserializeOwned>(body: &[u8]) -> Result<T, OAuth2ClientError> {
+		serde_html_form::from_bytes(body).map_err(OAuth2ClientError::response)
+	}
+}
+
+/// CBOR (`application/cbor`) content type encoding.
+///
+/// See: <https://www.rfc-editor.org/rfc/rfc8949.html>
+///
+/// Meant for constrained environments, such as those of the
+/// [ACE framework](https://www.rfc-editor.org/rfc/rfc9200.html). Requests can
+/// be sent and decoded in CBOR with
+/// [`RequestBuilder::with_content_type`](crate::endpoints::RequestBuilder::with_content_type)
+/// and
+/// [`RequestBuilder::with_response_content_type`](crate::endpoints::RequestBuilder::with_response_content_type).
+///
+/// Requires the `cbor` feature.
+#[cfg(feature = "cbor")]
+pub struct Cbor;
+
+#[cfg(feature = "cbor")]
+impl ContentType for Cbor {
+	const VALUE: Option<HeaderValue> = Some(APPLICATION_CBOR);
+
+	fn encode<T: Serialize>(value: &T) -> Vec<u8> {
+		let mut bytes = Vec::new();
+		// UNWRAP SAFETY: writing to a vector cannot fail, and request bodies
+		//                are always serializable.
+		ciborium::into_writer(value, &mut bytes).unwrap();
+		bytes
+	}
+}
+
+#[cfg(feature = "cbor")]
+impl ResponseContentType for Cbor {
+	const VALUE: HeaderValue = APPLICATION_CBOR;
+
+	fn decode<T: DeserializeOwned>(body: &[u8]) -> Result<T, OAuth2ClientError> {
+		ciborium::from_reader(body).map_err(OAuth2ClientError::response)
+	}
+}
@@ -1,3 +1,7 @@
+#[cfg(feature = "rand")]
+use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+#[cfg(feature = "rand")]
+use rand::{RngExt, rng};
 use str_newtype::StrNewType;
 
 use crate::{
@@ -47,6 +51,15 @@ impl AccessToken {
 	}
 }
 
+#[cfg(feature = "rand")]
+impl AccessTokenBuf {
+	/// Generates a new random, base64url-encoded 256-bit access token.
+	pub fn new_random() -> Self {
+		let random_bytes: Vec<u8> = (0..32).map(|_| rng().random::<u8>()).collect();
+		unsafe { Self::new_unchecked(BASE64_URL_SAFE_NO_PAD.encode(random_bytes)) }
+	}
+}
+
 #[macro_export]
 macro_rules! access_token {
 	($value:literal) => {{
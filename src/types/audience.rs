@@ -0,0 +1,240 @@
+use std::ops::{Deref, DerefMut};
+
+use serde::{Deserialize, Serialize};
+use str_newtype::StrNewType;
+
+use crate::{
+	endpoints::{HttpRequest, RedirectRequest, RequestBuilder},
+	transport::HttpClient,
+};
+
+use super::is_vschar;
+
+/// An audience (borrowed).
+///
+/// Logical name or URI of the target service where a token is intended to be
+/// used. The `audience` request parameter is not part of the core OAuth 2.0
+/// framework, but is defined by
+/// [RFC 8693 Section 2.1](https://datatracker.ietf.org/doc/html/rfc8693#section-2.1)
+/// for token exchange, and widely used by authorization servers such as
+/// Auth0 to select the API an access token is issued for. It is also the
+/// value of the `aud` claim of tokens.
+///
+/// # Grammar
+///
+/// ```abnf
+/// audience = 1*VSCHAR
+/// ```
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, StrNewType)]
+#[newtype(
+	serde,
+	owned(AudienceBuf, derive(PartialEq, Eq, PartialOrd, Ord, Hash))
+)]
+pub struct Audience(str);
+
+impl Audience {
+	/// Validates that the given string is a well-formed audience.
+	pub const fn validate_str(s: &str) -> bool {
+		Self::validate_bytes(s.as_bytes())
+	}
+
+	/// Validates that the given byte slice is a well-formed audience.
+	pub const fn validate_bytes(bytes: &[u8]) -> bool {
+		let mut i = 0;
+
+		while i < bytes.len() {
+			if !is_vschar(bytes[i]) {
+				return false;
+			}
+
+			i += 1
+		}
+
+		i > 0
+	}
+}
+
+#[macro_export]
+macro_rules! audience {
+	($value:literal) => {{
+		match $crate::Audience::new($value) {
+			Ok(value) => value,
+			Err(_) => panic!("invalid audience"),
+		}
+	}};
+}
+
+/// Wrapper that attaches `audience` parameters to a request.
+///
+/// Each audience is serialized as a separate `audience` parameter, as
+/// required by
+/// [RFC 8693 Section 2.1](https://datatracker.ietf.org/doc/html/rfc8693#section-2.1)
+/// when requesting a token for multiple audiences.
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct WithAudience<T> {
+	/// Target services where the requested token is intended to be used.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub audience: Vec<AudienceBuf>,
+
+	/// The inner request being extended.
+	#[serde(flatten)]
+	pub value: T,
+}
+
+impl<T> WithAudience<T> {
+	/// Creates a new [`WithAudience`] wrapping the given request.
+	pub fn new(value: T, audience: Vec<AudienceBuf>) -> Self {
+		Self { audience, value }
+	}
+
+	/// Wraps the given request body of the inner request, borrowing the
+	/// audiences.
+	fn body<B>(&self, value: B) -> WithAudienceBody<'_, B> {
+		WithAudienceBody {
+			audience: &self.audience,
+			value,
+		}
+	}
+}
+
+/// Request body of a [`WithAudience`] request, borrowing its audiences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct WithAudienceBody<'b, T> {
+	/// Target services where the requested token is intended to be used.
+	#[serde(skip_serializing_if = "<[_]>::is_empty")]
+	pub audience: &'b [AudienceBuf],
+
+	/// The inner request body.
+	#[serde(flatten)]
+	pub value: T,
+}
+
+impl<T> Deref for WithAudience<T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		&self.value
+	}
+}
+
+impl<T> DerefMut for WithAudience<T> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.value
+	}
+}
+
+impl<T> RedirectRequest for WithAudience<T>
+where
+	T: RedirectRequest,
+{
+	type RequestBody<'b>
+		= WithAudienceBody<'b, T::RequestBody<'b>>
+	where
+		Self: 'b;
+
+	fn build_query(&self) -> Self::RequestBody<'_> {
+		self.body(self.value.build_query())
+	}
+}
+
+impl<E, T> HttpRequest<E> for WithAudience<T>
+where
+	T: HttpRequest<E>,
+{
+	type ContentType = T::ContentType;
+	type RequestBody<'b>
+		= WithAudienceBody<'b, T::RequestBody<'b>>
+	where
+		Self: 'b;
+	type Response = T::Response;
+	type ResponsePayload = T::ResponsePayload;
+
+	async fn build_request(
+		&self,
+		endpoint: &E,
+		http_client: &impl HttpClient,
+	) -> Result<http::Request<Self::RequestBody<'_>>, crate::client::OAuth2ClientError> {
+		self.value
+			.build_request(endpoint, http_client)
+			.await
+			.map(|request| request.map(|value| self.body(value)))
+	}
+
+	fn decode_response(
+		&self,
+		endpoint: &E,
+		response: http::Response<Vec<u8>>,
+	) -> Result<http::Response<Self::ResponsePayload>, crate::client::OAuth2ClientError> {
+		self.value.decode_response(endpoint, response)
+	}
+
+	async fn process_response(
+		&self,
+		endpoint: &E,
+		http_client: &impl HttpClient,
+		response: http::Response<Self::ResponsePayload>,
+	) -> Result<Self::Response, crate::client::OAuth2ClientError> {
+		self.value
+			.process_response(endpoint, http_client, response)
+			.await
+	}
+}
+
+/// Extension trait for attaching `audience` parameters to a
+/// [`RequestBuilder`].
+pub trait AddAudience {
+	/// The resulting type after adding the audience.
+	type Output;
+
+	/// Wraps the current request in a [`WithAudience`] with the given
+	/// audiences.
+	fn with_audience(self, audience: impl IntoIterator<Item = AudienceBuf>) -> Self::Output;
+}
+
+impl<E, T> AddAudience for RequestBuilder<E, T> {
+	type Output = RequestBuilder<E, WithAudience<T>>;
+
+	fn with_audience(self, audience: impl IntoIterator<Item = AudienceBuf>) -> Self::Output {
+		self.map(|value| WithAudience::new(value, audience.into_iter().collect()))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::util::NoExtension;
+
+	use super::*;
+
+	#[test]
+	fn valid_audience() {
+		assert!(Audience::new("https://api.example.com/").is_ok());
+		assert!(Audience::new("urn:example:cooperation-context").is_ok());
+		assert!(Audience::new("").is_err());
+		assert!(Audience::new("abc\n").is_err());
+	}
+
+	#[test]
+	fn multiple_audiences() {
+		let request = WithAudience::new(
+			NoExtension {},
+			vec![
+				audience!("https://a.example.com/").to_owned(),
+				audience!("https://b.example.com/").to_owned(),
+			],
+		);
+
+		let query = serde_html_form::to_string(&request).unwrap();
+		assert_eq!(
+			query,
+			"audience=https%3A%2F%2Fa.example.com%2F&audience=https%3A%2F%2Fb.example.com%2F"
+		);
+
+		let decoded: WithAudience<NoExtension> = serde_html_form::from_str(&query).unwrap();
+		assert_eq!(decoded, request);
+
+		let empty: WithAudience<NoExtension> = serde_html_form::from_str("").unwrap();
+		assert!(empty.audience.is_empty());
+		assert_eq!(serde_html_form::to_string(&empty).unwrap(), "");
+	}
+}
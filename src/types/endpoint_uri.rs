@@ -0,0 +1,111 @@
+use iref::{Uri, UriBuf};
+
+use super::is_loopback_ip;
+
+/// Policy applied to the scheme of endpoint URIs.
+///
+/// Endpoints receive credentials, grants and tokens, and must only be
+/// reached over TLS. By default, plain `http` URIs are only accepted on the
+/// `127.0.0.1` and `[::1]` loopback addresses, which is convenient for local
+/// development servers.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EndpointUriPolicy {
+	/// Only accept `https` URIs, and `http` URIs on a loopback address.
+	#[default]
+	Secure,
+
+	/// Also accept `http` URIs on any host.
+	///
+	/// This must never be used in production.
+	AllowInsecureHttp,
+}
+
+impl EndpointUriPolicy {
+	/// Returns the policy allowing insecure `http` URIs if
+	/// `allow_insecure_http` is `true`, and the secure policy otherwise.
+	pub fn new(allow_insecure_http: bool) -> Self {
+		if allow_insecure_http {
+			Self::AllowInsecureHttp
+		} else {
+			Self::Secure
+		}
+	}
+
+	/// Checks that the given endpoint URI is accepted by this policy.
+	pub fn check(self, uri: &Uri) -> Result<(), InsecureEndpointUri> {
+		let scheme = uri.scheme().as_str();
+		if scheme.eq_ignore_ascii_case("https") {
+			return Ok(());
+		}
+
+		if scheme.eq_ignore_ascii_case("http") {
+			let host = uri.authority().map(|authority| authority.host().as_str());
+			if host.is_some_and(is_loopback_ip) {
+				return Ok(());
+			}
+
+			if self == Self::AllowInsecureHttp {
+				log::warn!("using insecure endpoint URI: {uri}");
+				return Ok(());
+			}
+		}
+
+		Err(InsecureEndpointUri(uri.to_owned()))
+	}
+}
+
+/// Error returned when an endpoint URI is rejected by an
+/// [`EndpointUriPolicy`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error(
+	"insecure endpoint URI `{0}`: only https URIs and http URIs on a loopback address are allowed"
+)]
+pub struct InsecureEndpointUri(pub UriBuf);
+
+#[cfg(test)]
+mod tests {
+	use iref::uri;
+
+	use super::*;
+
+	#[test]
+	fn endpoint_uri_policy() {
+		let policy = EndpointUriPolicy::default();
+		assert!(
+			policy
+				.check(uri!("https://server.example.com/token"))
+				.is_ok()
+		);
+		assert!(
+			policy
+				.check(uri!("HTTPS://server.example.com/token"))
+				.is_ok()
+		);
+		assert!(policy.check(uri!("http://127.0.0.1:8080/token")).is_ok());
+		assert!(policy.check(uri!("http://[::1]/token")).is_ok());
+		assert_eq!(
+			policy.check(uri!("http://server.example.com/token")),
+			Err(InsecureEndpointUri(
+				uri!("http://server.example.com/token").to_owned()
+			))
+		);
+		assert!(policy.check(uri!("http://localhost/token")).is_err());
+		assert!(
+			policy
+				.check(uri!("ftp://server.example.com/token"))
+				.is_err()
+		);
+
+		let policy = EndpointUriPolicy::new(true);
+		assert!(
+			policy
+				.check(uri!("http://server.example.com/token"))
+				.is_ok()
+		);
+		assert!(
+			policy
+				.check(uri!("ftp://server.example.com/token"))
+				.is_err()
+		);
+	}
+}
@@ -0,0 +1,193 @@
+use iref::Uri;
+use str_newtype::StrNewType;
+
+/// An issuer identifier (borrowed).
+///
+/// Identifier of an authorization server or OpenID provider, used as the
+/// `issuer` of its metadata and the `iss` claim of the tokens it issues.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc8414#section-2>
+///
+/// It is an `https` URI with a host, and without query or fragment
+/// component. Plain `http` URIs are only accepted on the `127.0.0.1` and
+/// `[::1]` loopback addresses, for local development.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, StrNewType)]
+#[newtype(serde, owned(IssuerBuf, derive(PartialEq, Eq, PartialOrd, Ord, Hash)))]
+pub struct Issuer(str);
+
+impl Issuer {
+	/// Validates that the given string is a well-formed issuer identifier.
+	pub const fn validate_str(s: &str) -> bool {
+		Self::validate_bytes(s.as_bytes())
+	}
+
+	/// Validates that the given byte slice is a well-formed issuer
+	/// identifier.
+	pub const fn validate_bytes(bytes: &[u8]) -> bool {
+		let mut i = if starts_with_ignore_case(bytes, b"https://") {
+			let start = 8;
+			let mut i = start;
+
+			while i < bytes.len() && bytes[i] != b'/' {
+				if !is_authority_char(bytes[i]) {
+					return false;
+				}
+
+				i += 1
+			}
+
+			if i == start || bytes[start] == b':' {
+				return false;
+			}
+
+			i
+		} else if starts_with_ignore_case(bytes, b"http://127.0.0.1") {
+			16
+		} else if starts_with_ignore_case(bytes, b"http://[::1]") {
+			12
+		} else {
+			return false;
+		};
+
+		if i < bytes.len() && bytes[i] == b':' {
+			i += 1;
+			while i < bytes.len() && bytes[i].is_ascii_digit() {
+				i += 1
+			}
+		}
+
+		if i < bytes.len() && bytes[i] != b'/' {
+			return false;
+		}
+
+		while i < bytes.len() {
+			match bytes[i] {
+				b'%' => {
+					if i + 2 >= bytes.len()
+						|| !bytes[i + 1].is_ascii_hexdigit()
+						|| !bytes[i + 2].is_ascii_hexdigit()
+					{
+						return false;
+					}
+
+					i += 3
+				}
+				c if is_path_char(c) => i += 1,
+				_ => return false,
+			}
+		}
+
+		true
+	}
+
+	/// Returns the issuer identifier as a URI.
+	pub fn as_uri(&self) -> &Uri {
+		// UNWRAP SAFETY: issuer identifiers are valid URIs.
+		Uri::new(self.as_bytes()).unwrap()
+	}
+}
+
+impl IssuerBuf {
+	/// Returns the issuer identifier as a URI.
+	pub fn as_uri(&self) -> &Uri {
+		self.as_issuer().as_uri()
+	}
+}
+
+impl PartialEq<Uri> for Issuer {
+	fn eq(&self, other: &Uri) -> bool {
+		self.as_bytes() == other.as_bytes()
+	}
+}
+
+impl PartialEq<Uri> for IssuerBuf {
+	fn eq(&self, other: &Uri) -> bool {
+		self.as_issuer() == other
+	}
+}
+
+const fn starts_with_ignore_case(bytes: &[u8], prefix: &[u8]) -> bool {
+	if bytes.len() < prefix.len() {
+		return false;
+	}
+
+	let mut i = 0;
+	while i < prefix.len() {
+		if bytes[i].to_ascii_lowercase() != prefix[i] {
+			return false;
+		}
+
+		i += 1
+	}
+
+	true
+}
+
+/// Checks that the given byte can appear in the host and port of an issuer
+/// identifier.
+const fn is_authority_char(c: u8) -> bool {
+	c.is_ascii_alphanumeric() || matches!(c, b'-' | b'.' | b'_' | b'~' | b':')
+}
+
+/// Checks that the given byte, other than `%`, can appear in the path of an
+/// issuer identifier.
+const fn is_path_char(c: u8) -> bool {
+	c.is_ascii_alphanumeric()
+		|| matches!(
+			c,
+			b'-' | b'.'
+				| b'_' | b'~'
+				| b'!' | b'$'
+				| b'&' | b'\''
+				| b'(' | b')'
+				| b'*' | b'+'
+				| b',' | b';'
+				| b'=' | b':'
+				| b'@' | b'/'
+		)
+}
+
+#[macro_export]
+macro_rules! issuer {
+	($value:literal) => {{
+		match $crate::Issuer::new($value) {
+			Ok(value) => value,
+			Err(_) => panic!("invalid issuer identifier"),
+		}
+	}};
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn valid_issuer() {
+		for issuer in [
+			"https://server.example.com",
+			"https://server.example.com:8443/tenant/",
+			"https://server.example.com/realms/a%20b",
+			"http://127.0.0.1:3000",
+			"http://[::1]/auth",
+		] {
+			let issuer = Issuer::new(issuer).unwrap();
+			assert_eq!(issuer.as_uri().as_str(), issuer.as_str());
+		}
+
+		for issuer in [
+			"",
+			"https://",
+			"https://:443/",
+			"https://server.example.com?tenant=a",
+			"https://server.example.com/#top",
+			"https://server.example.com/a b",
+			"https://server.example.com/%2",
+			"http://server.example.com",
+			"http://localhost:3000",
+			"http://127.0.0.1.example.com",
+			"urn:example:issuer",
+		] {
+			assert!(Issuer::new(issuer).is_err(), "{issuer}");
+		}
+	}
+}
@@ -7,20 +7,78 @@
 //! All types come in borrowed/owned pairs (e.g. [`AccessToken`] /
 //! [`AccessTokenBuf`]) following the same pattern as [`str`] / [`String`].
 mod access_token;
+mod audience;
 mod client_id;
 mod code;
+mod endpoint_uri;
+mod issuer;
+mod redirect_uri;
+mod response_type;
 mod scope;
 mod state;
 
 pub use access_token::*;
+pub use audience::*;
 pub use client_id::*;
 pub use code::*;
+pub use endpoint_uri::*;
+pub use issuer::*;
+pub use redirect_uri::*;
+pub use response_type::*;
 pub use scope::*;
 pub use state::*;
 
+/// Implements [`schemars::JsonSchema`] for the given string types, as plain
+/// strings.
+#[cfg(feature = "schemars")]
+macro_rules! json_schema_string {
+	($($ty:ident),* $(,)?) => {
+		$(
+			impl schemars::JsonSchema for $ty {
+				fn inline_schema() -> bool {
+					true
+				}
+
+				fn schema_name() -> std::borrow::Cow<'static, str> {
+					stringify!($ty).into()
+				}
+
+				fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+					schemars::json_schema!({ "type": "string" })
+				}
+			}
+		)*
+	};
+}
+
+#[cfg(feature = "schemars")]
+pub(crate) use json_schema_string;
+
+#[cfg(feature = "schemars")]
+json_schema_string!(
+	AccessToken,
+	AccessTokenBuf,
+	Audience,
+	AudienceBuf,
+	ClientId,
+	ClientIdBuf,
+	Code,
+	CodeBuf,
+	Issuer,
+	IssuerBuf,
+	ResponseType,
+	ResponseTypeBuf,
+	ScopeToken,
+	ScopeTokenBuf,
+	Scope,
+	ScopeBuf,
+	State,
+	StateBuf,
+);
+
 /// Returns `true` if the byte is a VSCHAR (visible ASCII character plus
 /// space), i.e. in the range `0x20..=0x7E`.
-const fn is_vschar(c: u8) -> bool {
+pub(crate) const fn is_vschar(c: u8) -> bool {
 	c >= 0x20 && c <= 0x7e
 }
 
@@ -0,0 +1,257 @@
+use iref::{Uri, UriBuf, uri::Port};
+
+/// Kind of redirection endpoint URI.
+///
+/// See: <https://www.rfc-editor.org/rfc/rfc8252.html#section-7>
+///
+/// Native apps cannot always receive redirects on an `https` URI, and may
+/// instead use a private-use URI scheme or a loopback interface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RedirectUriKind {
+	/// Claimed `https` URI, e.g. `https://app.example.com/callback`.
+	///
+	/// See: <https://www.rfc-editor.org/rfc/rfc8252.html#section-7.2>
+	Https,
+
+	/// Private-use URI scheme, e.g. `com.example.app:/callback`.
+	///
+	/// The scheme must be a reverse domain name controlled by the app.
+	///
+	/// See: <https://www.rfc-editor.org/rfc/rfc8252.html#section-7.1>
+	PrivateUse,
+
+	/// Loopback interface, e.g. `http://127.0.0.1:51004/callback`.
+	///
+	/// The port is chosen by the app at request time, and must be ignored
+	/// when comparing the URI with the registered one.
+	///
+	/// See: <https://www.rfc-editor.org/rfc/rfc8252.html#section-7.3>
+	Loopback,
+}
+
+impl RedirectUriKind {
+	/// Classifies the given redirection URI, checking it against the rules of
+	/// [RFC 8252](https://www.rfc-editor.org/rfc/rfc8252.html).
+	///
+	/// Plain `http` URIs are only accepted on the `127.0.0.1` and `[::1]`
+	/// loopback addresses. The `localhost` name is rejected, since it may
+	/// resolve to a non-loopback interface.
+	pub fn of(uri: &Uri) -> Result<Self, InvalidRedirectUri> {
+		if uri.fragment().is_some() {
+			return Err(InvalidRedirectUri::Fragment);
+		}
+
+		let scheme = uri.scheme().as_str();
+		let host = uri.authority().map(|authority| authority.host().as_str());
+
+		if scheme.eq_ignore_ascii_case("https") {
+			match host {
+				Some(host) if !host.is_empty() => Ok(Self::Https),
+				_ => Err(InvalidRedirectUri::MissingHost),
+			}
+		} else if scheme.eq_ignore_ascii_case("http") {
+			match host {
+				Some(host) if is_loopback_ip(host) => Ok(Self::Loopback),
+				_ => Err(InvalidRedirectUri::NotLoopback),
+			}
+		} else if scheme.contains('.') {
+			Ok(Self::PrivateUse)
+		} else {
+			Err(InvalidRedirectUri::PrivateUseScheme(scheme.to_owned()))
+		}
+	}
+
+	/// Checks whether the `requested` redirection URI matches the
+	/// `registered` one.
+	///
+	/// URIs are compared exactly, except for the port of loopback URIs.
+	///
+	/// See: <https://www.rfc-editor.org/rfc/rfc8252.html#section-7.3>
+	pub fn matches(registered: &Uri, requested: &Uri) -> bool {
+		if registered == requested {
+			return true;
+		}
+
+		match (Self::of(registered), Self::of(requested)) {
+			(Ok(Self::Loopback), Ok(Self::Loopback)) => {
+				without_port(registered) == without_port(requested)
+			}
+			_ => false,
+		}
+	}
+}
+
+/// Rules applied to the redirection URIs of a client.
+///
+/// By default, redirection URIs only need to be absolute URIs without
+/// fragment, as required by
+/// [RFC 6749 Section 3.1.2](https://datatracker.ietf.org/doc/html/rfc6749#section-3.1.2).
+/// The stricter rules of
+/// [RFC 8252](https://www.rfc-editor.org/rfc/rfc8252.html) are only applied
+/// to native apps.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RedirectUriPolicy {
+	/// Accept any absolute URI without fragment.
+	#[default]
+	Generic,
+
+	/// Only accept the redirection URIs of native apps (see
+	/// [`RedirectUriKind::of`]).
+	NativeApp,
+}
+
+impl RedirectUriPolicy {
+	/// Checks that the given redirection URI is accepted by this policy.
+	pub fn check(self, uri: &Uri) -> Result<(), InvalidRedirectUri> {
+		match self {
+			Self::Generic if uri.fragment().is_some() => Err(InvalidRedirectUri::Fragment),
+			Self::Generic => Ok(()),
+			Self::NativeApp => RedirectUriKind::of(uri).map(|_| ()),
+		}
+	}
+}
+
+/// Sets the port of a loopback redirection URI, typically once the app has
+/// bound its local HTTP listener.
+///
+/// See: <https://www.rfc-editor.org/rfc/rfc8252.html#section-7.3>
+pub fn set_loopback_port(uri: &mut UriBuf, port: u16) -> Result<(), InvalidRedirectUri> {
+	if RedirectUriKind::of(uri)? != RedirectUriKind::Loopback {
+		return Err(InvalidRedirectUri::NotLoopback);
+	}
+
+	let port = port.to_string();
+	// UNWRAP SAFETY: loopback URIs have an authority, and a decimal number
+	//                is a valid port.
+	uri.authority_mut()
+		.unwrap()
+		.set_port(Some(Port::new(port.as_bytes()).unwrap()));
+
+	Ok(())
+}
+
+/// Checks whether the given URI host is a loopback IP address literal.
+pub(crate) fn is_loopback_ip(host: &str) -> bool {
+	host == "127.0.0.1" || host == "[::1]"
+}
+
+fn without_port(uri: &Uri) -> UriBuf {
+	let mut uri = uri.to_owned();
+	if let Some(mut authority) = uri.authority_mut() {
+		authority.set_port(None);
+	}
+
+	uri
+}
+
+/// Invalid redirection URI.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum InvalidRedirectUri {
+	/// The URI includes a fragment component.
+	#[error("redirect URI must not include a fragment")]
+	Fragment,
+
+	/// The `https` URI has no host.
+	#[error("https redirect URI must include a host")]
+	MissingHost,
+
+	/// The URI is not an `http` URI on a loopback IP address.
+	#[error("http redirect URI must use the 127.0.0.1 or [::1] loopback address")]
+	NotLoopback,
+
+	/// The URI scheme is not a reverse domain name.
+	#[error("private-use URI scheme `{0}` is not a reverse domain name")]
+	PrivateUseScheme(String),
+}
+
+#[cfg(test)]
+mod tests {
+	use iref::uri;
+
+	use super::*;
+
+	#[test]
+	fn kinds() {
+		assert_eq!(
+			RedirectUriKind::of(uri!("https://app.example.com/cb")),
+			Ok(RedirectUriKind::Https)
+		);
+		assert_eq!(
+			RedirectUriKind::of(uri!("com.example.app:/cb")),
+			Ok(RedirectUriKind::PrivateUse)
+		);
+		assert_eq!(
+			RedirectUriKind::of(uri!("http://127.0.0.1:51004/cb")),
+			Ok(RedirectUriKind::Loopback)
+		);
+		assert_eq!(
+			RedirectUriKind::of(uri!("http://[::1]/cb")),
+			Ok(RedirectUriKind::Loopback)
+		);
+	}
+
+	#[test]
+	fn invalid() {
+		assert_eq!(
+			RedirectUriKind::of(uri!("https://app.example.com/cb#frag")),
+			Err(InvalidRedirectUri::Fragment)
+		);
+		assert_eq!(
+			RedirectUriKind::of(uri!("http://localhost/cb")),
+			Err(InvalidRedirectUri::NotLoopback)
+		);
+		assert_eq!(
+			RedirectUriKind::of(uri!("http://app.example.com/cb")),
+			Err(InvalidRedirectUri::NotLoopback)
+		);
+		assert_eq!(
+			RedirectUriKind::of(uri!("myapp:/cb")),
+			Err(InvalidRedirectUri::PrivateUseScheme("myapp".to_owned()))
+		);
+	}
+
+	#[test]
+	fn policy() {
+		for uri in [
+			uri!("http://localhost/cb"),
+			uri!("http://client.example.com/cb"),
+			uri!("myapp:/cb"),
+		] {
+			assert_eq!(RedirectUriPolicy::Generic.check(uri), Ok(()));
+			assert!(RedirectUriPolicy::NativeApp.check(uri).is_err());
+		}
+
+		assert_eq!(
+			RedirectUriPolicy::Generic.check(uri!("https://client.example.com/cb#frag")),
+			Err(InvalidRedirectUri::Fragment)
+		);
+		assert_eq!(
+			RedirectUriPolicy::NativeApp.check(uri!("http://127.0.0.1/cb")),
+			Ok(())
+		);
+	}
+
+	#[test]
+	fn loopback_port() {
+		let registered = uri!("http://127.0.0.1/cb");
+		let mut requested = registered.to_owned();
+		set_loopback_port(&mut requested, 51004).unwrap();
+		assert_eq!(requested.as_str(), "http://127.0.0.1:51004/cb");
+
+		assert!(RedirectUriKind::matches(registered, &requested));
+		assert!(!RedirectUriKind::matches(
+			registered,
+			uri!("http://127.0.0.1:51004/other")
+		));
+		assert!(!RedirectUriKind::matches(
+			uri!("https://app.example.com/cb"),
+			uri!("https://app.example.com:8443/cb")
+		));
+
+		let mut https = UriBuf::new(b"https://app.example.com/cb".to_vec()).unwrap();
+		assert_eq!(
+			set_loopback_port(&mut https, 51004),
+			Err(InvalidRedirectUri::NotLoopback)
+		);
+	}
+}
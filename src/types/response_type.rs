@@ -0,0 +1,116 @@
+use str_newtype::StrNewType;
+
+/// An OAuth 2.0 response type (borrowed).
+///
+/// A response type is a space-separated list of response names, such as
+/// `code` or `code id_token`. The order of the names is not significant,
+/// hence response types should be compared with
+/// [`is_equivalent`](Self::is_equivalent).
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc6749#section-3.1.1>
+/// See: <https://openid.net/specs/oauth-v2-multiple-response-types-1_0.html#ResponseTypesAndModes>
+///
+/// # Grammar
+///
+/// ```abnf
+/// response-type = response-name *( SP response-name )
+/// response-name = 1*response-char
+/// response-char = "_" / DIGIT / ALPHA
+/// ```
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, StrNewType)]
+#[newtype(
+	serde,
+	owned(ResponseTypeBuf, derive(PartialEq, Eq, PartialOrd, Ord, Hash))
+)]
+pub struct ResponseType(str);
+
+impl ResponseType {
+	/// The `code` response type, requesting an authorization code.
+	pub const CODE: &Self = unsafe { Self::new_unchecked("code") };
+
+	/// The `token` response type, requesting an access token with the
+	/// Implicit Grant.
+	pub const TOKEN: &Self = unsafe { Self::new_unchecked("token") };
+
+	/// Validates that the given string is a well-formed response type.
+	pub const fn validate_str(s: &str) -> bool {
+		Self::validate_bytes(s.as_bytes())
+	}
+
+	/// Validates that the given byte slice is a well-formed response type.
+	pub const fn validate_bytes(bytes: &[u8]) -> bool {
+		let mut i = 0;
+		let mut name_len = 0;
+
+		while i < bytes.len() {
+			let c = bytes[i];
+			if c == b' ' {
+				if name_len == 0 {
+					return false;
+				}
+
+				name_len = 0;
+			} else if c == b'_' || c.is_ascii_alphanumeric() {
+				name_len += 1;
+			} else {
+				return false;
+			}
+
+			i += 1
+		}
+
+		name_len > 0
+	}
+
+	/// Returns an iterator over the response names of this response type.
+	pub fn names(&self) -> std::str::Split<'_, char> {
+		self.0.split(' ')
+	}
+
+	/// Returns `true` if this response type contains the given response name.
+	pub fn contains(&self, name: &str) -> bool {
+		self.names().any(|n| n == name)
+	}
+
+	/// Returns `true` if both response types are made of the same response
+	/// names, regardless of their order.
+	pub fn is_equivalent(&self, other: &Self) -> bool {
+		self.names().all(|n| other.contains(n)) && other.names().all(|n| self.contains(n))
+	}
+}
+
+#[macro_export]
+macro_rules! response_type {
+	($value:literal) => {{
+		match $crate::ResponseType::new($value) {
+			Ok(value) => value,
+			Err(_) => panic!("invalid response type"),
+		}
+	}};
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn valid_response_type() {
+		assert!(ResponseType::new("code").is_ok());
+		assert!(ResponseType::new("code id_token").is_ok());
+		assert!(ResponseType::new("").is_err());
+		assert!(ResponseType::new("code  token").is_err());
+		assert!(ResponseType::new(" code").is_err());
+		assert!(ResponseType::new("code ").is_err());
+		assert!(ResponseType::new("code-token").is_err());
+	}
+
+	#[test]
+	fn equivalence() {
+		let a = ResponseType::new("code id_token").unwrap();
+		let b = ResponseType::new("id_token code").unwrap();
+		assert_eq!(a.names().collect::<Vec<_>>(), ["code", "id_token"]);
+		assert!(a.is_equivalent(b));
+		assert!(!a.is_equivalent(ResponseType::CODE));
+		assert!(!ResponseType::CODE.is_equivalent(a));
+	}
+}
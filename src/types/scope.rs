@@ -29,28 +29,64 @@ impl ScopeToken {
 
 	/// Validates that the given byte slice is a well-formed scope token.
 	pub const fn validate_bytes(bytes: &[u8]) -> bool {
-		let mut i = 0;
+		let end = scope_token_end(bytes, 0);
+		end > 0 && end == bytes.len()
+	}
+}
 
-		while i < bytes.len() {
-			if !is_nqchar(bytes[i]) {
-				return false;
-			}
+/// Returns the end offset of the scope token starting at offset `start` in
+/// `bytes`, that is the offset of the first byte that is not a NQCHAR.
+///
+/// The token is empty if the returned offset is `start`. This is the only
+/// tokenizer for scopes, shared by [`ScopeToken`] and [`Scope`] validation,
+/// and by [`ScopeIter`].
+const fn scope_token_end(bytes: &[u8], start: usize) -> usize {
+	let mut i = start;
+
+	while i < bytes.len() && is_nqchar(bytes[i]) {
+		i += 1;
+	}
+
+	i
+}
 
-			i += 1;
+/// Checks whether `bytes[a..a_end]` and `bytes[b..b_end]` are equal.
+const fn bytes_eq(bytes: &[u8], a: usize, a_end: usize, b: usize, b_end: usize) -> bool {
+	if a_end - a != b_end - b {
+		return false;
+	}
+
+	let mut i = 0;
+	while a + i < a_end {
+		if bytes[a + i] != bytes[b + i] {
+			return false;
 		}
 
-		i > 1
+		i += 1;
 	}
+
+	true
 }
 
+/// Creates a `&'static ScopeToken`, validated at compile time.
+///
+/// Can be used to define scope token constants:
+///
+/// ```
+/// use open_auth2::{ScopeToken, scope_token};
+///
+/// const READ: &ScopeToken = scope_token!("read");
+/// ```
 #[macro_export]
 macro_rules! scope_token {
-	($value:literal) => {{
-		match $crate::ScopeToken::new($value) {
-			Ok(value) => value,
-			Err(_) => panic!("invalid scope token"),
+	($value:literal) => {
+		const {
+			match $crate::ScopeToken::from_str($value) {
+				Ok(value) => value,
+				Err(_) => panic!("invalid scope token"),
+			}
 		}
-	}};
+	};
 }
 
 /// Conversion trait for types that can be turned into an optional [`ScopeBuf`].
@@ -104,34 +140,50 @@ impl Scope {
 
 	/// Validates that the given byte slice is a well-formed scope.
 	pub const fn validate_bytes(bytes: &[u8]) -> bool {
-		let mut i = 0;
-
-		let mut expect_token = true;
-		while expect_token {
-			expect_token = false;
-			let mut scope_token_empty = true;
-
-			while i < bytes.len() {
-				match bytes[i] {
-					c if is_nqchar(c) => {
-						scope_token_empty = false;
-						i += 1;
-					}
-					b' ' => {
-						expect_token = true;
-						i += 1;
-						break;
-					}
-					_ => return false,
-				}
+		let mut start = 0;
+
+		loop {
+			let end = scope_token_end(bytes, start);
+
+			if end == start {
+				// Empty scope token.
+				return false;
 			}
 
-			if scope_token_empty {
+			if end == bytes.len() {
+				return true;
+			}
+
+			if bytes[end] != b' ' {
 				return false;
 			}
+
+			start = end + 1;
 		}
+	}
+
+	/// Returns `true` if a token appears more than once in this scope.
+	pub const fn has_duplicates(&self) -> bool {
+		let bytes = self.0.as_bytes();
+		let mut start = 0;
 
-		true
+		while start < bytes.len() {
+			let end = scope_token_end(bytes, start);
+			let mut other = end + 1;
+
+			while other < bytes.len() {
+				let other_end = scope_token_end(bytes, other);
+				if bytes_eq(bytes, start, end, other, other_end) {
+					return true;
+				}
+
+				other = other_end + 1;
+			}
+
+			start = end + 1;
+		}
+
+		false
 	}
 
 	/// Returns `true` if this scope contains the given token.
@@ -139,9 +191,14 @@ impl Scope {
 		self.iter().any(|t| t == token)
 	}
 
+	/// Returns `true` if this scope contains every token of `other`.
+	pub fn includes(&self, other: &Scope) -> bool {
+		other.iter().all(|t| self.contains(t))
+	}
+
 	/// Returns an iterator over the individual scope tokens.
 	pub fn iter(&self) -> ScopeIter<'_> {
-		ScopeIter(self.0.split(' '))
+		ScopeIter(&self.0)
 	}
 }
 
@@ -154,26 +211,70 @@ impl<'a> IntoIterator for &'a Scope {
 	}
 }
 
+/// Creates a `&'static Scope`, validated at compile time.
+///
+/// See [`scopes!`](crate::scopes) to build a scope from individual tokens.
 #[macro_export]
 macro_rules! scope {
-	($value:literal) => {{
-		match $crate::Scope::new($value) {
-			Ok(value) => value,
-			Err(_) => panic!("invalid scope"),
+	($value:literal) => {
+		const {
+			match $crate::Scope::from_str($value) {
+				Ok(value) => value,
+				Err(_) => panic!("invalid scope"),
+			}
 		}
-	}};
+	};
+}
+
+/// Creates a `&'static Scope` from the given scope tokens, validated at
+/// compile time.
+///
+/// Each literal must be a single scope token, appearing only once:
+///
+/// ```
+/// use open_auth2::{Scope, scopes};
+///
+/// const LOGIN: &Scope = scopes!["openid", "profile", "email"];
+/// assert_eq!(LOGIN.as_str(), "openid profile email");
+/// ```
+///
+/// ```compile_fail
+/// open_auth2::scopes!["openid", "openid"];
+/// ```
+#[macro_export]
+macro_rules! scopes {
+	($first:literal $(, $rest:literal)* $(,)?) => {
+		const {
+			$crate::scope_token!($first);
+			$($crate::scope_token!($rest);)*
+
+			match $crate::Scope::from_str(concat!($first $(, " ", $rest)*)) {
+				Ok(scope) if !scope.has_duplicates() => scope,
+				Ok(_) => panic!("duplicate scope token"),
+				Err(_) => panic!("invalid scope"),
+			}
+		}
+	};
 }
 
 /// Iterator over the individual [`ScopeToken`]s in a [`Scope`].
-pub struct ScopeIter<'a>(std::str::Split<'a, char>);
+pub struct ScopeIter<'a>(&'a str);
 
 impl<'a> Iterator for ScopeIter<'a> {
 	type Item = &'a ScopeToken;
 
 	fn next(&mut self) -> Option<Self::Item> {
-		self.0
-			.next()
-			.map(|t| unsafe { ScopeToken::new_unchecked(t) })
+		if self.0.is_empty() {
+			return None;
+		}
+
+		let end = scope_token_end(self.0.as_bytes(), 0);
+		let (token, rest) = self.0.split_at(end);
+		self.0 = rest.strip_prefix(' ').unwrap_or(rest);
+
+		// SAFETY: the iterated scope is valid, hence made of non-empty scope
+		//         tokens separated by single spaces.
+		Some(unsafe { ScopeToken::new_unchecked(token) })
 	}
 }
 
@@ -247,6 +348,14 @@ mod tests {
 		assert!(ScopeToken::new("!!").is_ok());
 	}
 
+	#[test]
+	fn single_char_scope_token() {
+		assert!(ScopeToken::new("a").is_ok());
+		assert!(ScopeToken::new("!").is_ok());
+		assert!(Scope::new("a").is_ok());
+		assert!(Scope::new("a b c").is_ok());
+	}
+
 	#[test]
 	fn empty_scope_token_is_invalid() {
 		assert!(ScopeToken::new("").is_err());
@@ -321,6 +430,10 @@ mod tests {
 		let scope = Scope::new("openid profile email").unwrap();
 		let tokens: Vec<&str> = scope.iter().map(|t| t.as_str()).collect();
 		assert_eq!(tokens, vec!["openid", "profile", "email"]);
+
+		let scope = Scope::new("a bc d").unwrap();
+		let tokens: Vec<&str> = scope.iter().map(|t| t.as_str()).collect();
+		assert_eq!(tokens, vec!["a", "bc", "d"]);
 	}
 
 	#[test]
@@ -330,6 +443,36 @@ mod tests {
 		assert!(!scope.contains(ScopeToken::new("email").unwrap()));
 	}
 
+	#[test]
+	fn scope_includes() {
+		let scope = Scope::new("openid profile").unwrap();
+		assert!(scope.includes(Scope::new("profile openid").unwrap()));
+		assert!(scope.includes(Scope::new("openid").unwrap()));
+		assert!(!scope.includes(Scope::new("openid email").unwrap()));
+	}
+
+	#[test]
+	fn scope_has_duplicates() {
+		assert!(!Scope::new("openid profile").unwrap().has_duplicates());
+		assert!(!Scope::new("read read:user").unwrap().has_duplicates());
+		assert!(
+			Scope::new("openid profile openid")
+				.unwrap()
+				.has_duplicates()
+		);
+		assert!(Scope::new("a b b").unwrap().has_duplicates());
+	}
+
+	#[test]
+	fn scope_macros() {
+		const OPENID: &ScopeToken = crate::scope_token!("openid");
+		const LOGIN: &Scope = crate::scopes!["openid", "profile",];
+
+		assert_eq!(LOGIN, crate::scope!("openid profile"));
+		assert!(LOGIN.contains(OPENID));
+		assert_eq!(crate::scopes!["email"].as_str(), "email");
+	}
+
 	#[test]
 	fn scope_buf_insert() {
 		let mut scope = ScopeBuf::new("openid".to_owned()).unwrap();
@@ -1,6 +1,8 @@
 use std::ops::{Deref, DerefMut};
 
+#[cfg(feature = "rand")]
 use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+#[cfg(feature = "rand")]
 use rand::{RngExt, rng};
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
@@ -61,6 +63,7 @@ macro_rules! state {
 	}};
 }
 
+#[cfg(feature = "rand")]
 impl StateBuf {
 	/// Generates a new random, base64url-encoded 128-bit CSRF token.
 	pub fn new_random() -> Self {
@@ -81,6 +84,7 @@ impl StateBuf {
 /// callback, preventing cross-site request forgery attacks.
 #[skip_serializing_none]
 #[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Stateful<T> {
 	/// Opaque value used by the client to maintain state between the request
 	/// and callback.
@@ -102,6 +106,15 @@ impl<T> Stateful<T> {
 	pub fn new(value: T, state: Option<StateBuf>) -> Self {
 		Self { state, value }
 	}
+
+	/// Wraps the given request body of the inner request, borrowing the
+	/// state.
+	fn body<B>(&self, value: B) -> StatefulBody<'_, B> {
+		StatefulBody {
+			state: self.state.as_deref(),
+			value,
+		}
+	}
 }
 
 impl<T> Deref for Stateful<T> {
@@ -118,17 +131,29 @@ impl<T> DerefMut for Stateful<T> {
 	}
 }
 
+/// Request body of a [`Stateful`] request, borrowing its state.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct StatefulBody<'b, T> {
+	/// The state token, if any.
+	pub state: Option<&'b State>,
+
+	/// The inner request body.
+	#[serde(flatten)]
+	pub value: T,
+}
+
 impl<T> RedirectRequest for Stateful<T>
 where
 	T: RedirectRequest,
 {
 	type RequestBody<'b>
-		= Stateful<T::RequestBody<'b>>
+		= StatefulBody<'b, T::RequestBody<'b>>
 	where
 		Self: 'b;
 
 	fn build_query(&self) -> Self::RequestBody<'_> {
-		Stateful::new(self.value.build_query(), self.state.clone())
+		self.body(self.value.build_query())
 	}
 }
 
@@ -138,7 +163,7 @@ where
 {
 	type ContentType = T::ContentType;
 	type RequestBody<'b>
-		= Stateful<T::RequestBody<'b>>
+		= StatefulBody<'b, T::RequestBody<'b>>
 	where
 		Self: 'b;
 	type Response = T::Response;
@@ -152,7 +177,7 @@ where
 		self.value
 			.build_request(endpoint, http_client)
 			.await
-			.map(|request| request.map(|value| Stateful::new(value, self.state.clone())))
+			.map(|request| request.map(|value| self.body(value)))
 	}
 
 	fn decode_response(
@@ -219,12 +244,14 @@ mod tests {
 		assert!(State::new("abc\x7f").is_err());
 	}
 
+	#[cfg(feature = "rand")]
 	#[test]
 	fn random_state_is_valid() {
 		let state = StateBuf::new_random();
 		assert!(State::new(state.as_str()).is_ok());
 	}
 
+	#[cfg(feature = "rand")]
 	#[test]
 	fn random_state_len_is_valid() {
 		let state = StateBuf::new_random_len(32);
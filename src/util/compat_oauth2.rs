@@ -0,0 +1,212 @@
+use oauth2::{
+	AuthorizationCode, CsrfToken, ExtraTokenFields, PkceCodeChallenge, PkceCodeVerifier,
+	RefreshToken, Scope, StandardTokenResponse, TokenResponse as _, basic::BasicTokenType,
+};
+
+use crate::{
+	AccessTokenBuf, CodeBuf, ScopeBuf, StateBuf,
+	endpoints::token::TokenResponse,
+	ext::pkce::{PkceCodeChallengeAndMethod, PkceCodeChallengeBuf, PkceCodeVerifierBuf},
+};
+
+/// Error returned when a value of the [`oauth2`] crate is not valid for the
+/// equivalent type of this crate.
+///
+/// The [`oauth2`] crate does not validate its values, while this crate
+/// checks them against the syntax of the specifications.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("invalid `{0}` value")]
+pub struct InvalidOAuth2Value(pub &'static str);
+
+impl TryFrom<AuthorizationCode> for CodeBuf {
+	type Error = InvalidOAuth2Value;
+
+	fn try_from(value: AuthorizationCode) -> Result<Self, Self::Error> {
+		CodeBuf::new(value.into_secret()).map_err(|_| InvalidOAuth2Value("code"))
+	}
+}
+
+impl From<CodeBuf> for AuthorizationCode {
+	fn from(value: CodeBuf) -> Self {
+		AuthorizationCode::new(value.into_string())
+	}
+}
+
+impl TryFrom<CsrfToken> for StateBuf {
+	type Error = InvalidOAuth2Value;
+
+	fn try_from(value: CsrfToken) -> Result<Self, Self::Error> {
+		StateBuf::new(value.into_secret()).map_err(|_| InvalidOAuth2Value("state"))
+	}
+}
+
+impl From<StateBuf> for CsrfToken {
+	fn from(value: StateBuf) -> Self {
+		CsrfToken::new(value.into_string())
+	}
+}
+
+impl TryFrom<PkceCodeVerifier> for PkceCodeVerifierBuf {
+	type Error = InvalidOAuth2Value;
+
+	fn try_from(value: PkceCodeVerifier) -> Result<Self, Self::Error> {
+		PkceCodeVerifierBuf::new(value.into_secret())
+			.map_err(|_| InvalidOAuth2Value("code_verifier"))
+	}
+}
+
+impl From<PkceCodeVerifierBuf> for PkceCodeVerifier {
+	fn from(value: PkceCodeVerifierBuf) -> Self {
+		PkceCodeVerifier::new(value.into_string())
+	}
+}
+
+impl TryFrom<PkceCodeChallenge> for PkceCodeChallengeAndMethod {
+	type Error = InvalidOAuth2Value;
+
+	fn try_from(value: PkceCodeChallenge) -> Result<Self, Self::Error> {
+		Ok(Self {
+			challenge: PkceCodeChallengeBuf::new(value.as_str().to_owned())
+				.map_err(|_| InvalidOAuth2Value("code_challenge"))?,
+			method: value
+				.method()
+				.parse()
+				.map_err(|_| InvalidOAuth2Value("code_challenge_method"))?,
+		})
+	}
+}
+
+impl From<PkceCodeChallengeAndMethod> for PkceCodeChallenge {
+	fn from(value: PkceCodeChallengeAndMethod) -> Self {
+		// `PkceCodeChallenge` has no public constructor, but serializes with
+		// the same parameter names.
+		// UNWRAP SAFETY: both fields are strings.
+		serde_json::from_value(serde_json::to_value(value).unwrap()).unwrap()
+	}
+}
+
+impl<E> TryFrom<StandardTokenResponse<E, BasicTokenType>> for TokenResponse<String, E>
+where
+	E: ExtraTokenFields + Clone,
+{
+	type Error = InvalidOAuth2Value;
+
+	fn try_from(value: StandardTokenResponse<E, BasicTokenType>) -> Result<Self, Self::Error> {
+		let access_token = AccessTokenBuf::new(value.access_token().secret().clone())
+			.map_err(|_| InvalidOAuth2Value("access_token"))?;
+		let scope = value
+			.scopes()
+			.map(|scopes| {
+				let scopes: Vec<&str> = scopes.iter().map(|s| s.as_str()).collect();
+				ScopeBuf::new(scopes.join(" ")).map_err(|_| InvalidOAuth2Value("scope"))
+			})
+			.transpose()?;
+
+		Ok(TokenResponse {
+			access_token,
+			token_type: value.token_type().as_ref().to_owned(),
+			expires_in: value.expires_in().map(|d| d.as_secs()),
+			refresh_token: value.refresh_token().map(|t| t.secret().clone()),
+			scope,
+			ext: value.extra_fields().clone(),
+		})
+	}
+}
+
+impl<E> From<TokenResponse<String, E>> for StandardTokenResponse<E, BasicTokenType>
+where
+	E: ExtraTokenFields,
+{
+	fn from(value: TokenResponse<String, E>) -> Self {
+		let token_type = match value.token_type.as_str() {
+			t if t.eq_ignore_ascii_case("bearer") => BasicTokenType::Bearer,
+			t if t.eq_ignore_ascii_case("mac") => BasicTokenType::Mac,
+			t => BasicTokenType::Extension(t.to_owned()),
+		};
+
+		let mut response = StandardTokenResponse::new(
+			oauth2::AccessToken::new(value.access_token.into_string()),
+			token_type,
+			value.ext,
+		);
+		response.set_expires_in(
+			value
+				.expires_in
+				.map(std::time::Duration::from_secs)
+				.as_ref(),
+		);
+		response.set_refresh_token(value.refresh_token.map(RefreshToken::new));
+		response.set_scopes(
+			value
+				.scope
+				.map(|scope| scope.iter().map(|t| Scope::new(t.to_string())).collect()),
+		);
+		response
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+
+	use oauth2::{EmptyExtraTokenFields, basic::BasicTokenResponse};
+
+	use crate::ext::pkce::PkceCodeChallengeMethod;
+
+	use super::*;
+
+	#[test]
+	fn pkce() {
+		let (challenge, verifier) = PkceCodeChallenge::new_random_sha256();
+		let verifier = PkceCodeVerifierBuf::try_from(verifier).unwrap();
+		let converted = PkceCodeChallengeAndMethod::try_from(challenge.clone()).unwrap();
+		assert_eq!(converted.method, PkceCodeChallengeMethod::S256);
+		assert_eq!(
+			converted,
+			PkceCodeChallengeAndMethod::from_code_verifier_sha256(&verifier)
+		);
+		assert_eq!(PkceCodeChallenge::from(converted), challenge);
+	}
+
+	#[test]
+	fn invalid_values() {
+		assert_eq!(
+			CodeBuf::try_from(AuthorizationCode::new(String::new())),
+			Err(InvalidOAuth2Value("code"))
+		);
+		assert_eq!(
+			PkceCodeVerifierBuf::try_from(PkceCodeVerifier::new("short".to_owned())),
+			Err(InvalidOAuth2Value("code_verifier"))
+		);
+	}
+
+	#[test]
+	fn token_response() {
+		let mut response = BasicTokenResponse::new(
+			oauth2::AccessToken::new("2YotnFZFEjr1zCsicMWpAA".to_owned()),
+			BasicTokenType::Bearer,
+			EmptyExtraTokenFields {},
+		);
+		response.set_expires_in(Some(&Duration::from_secs(3600)));
+		response.set_refresh_token(Some(RefreshToken::new("tGzv3JOkF0XG5Qx2TlKWIA".to_owned())));
+		response.set_scopes(Some(vec![
+			Scope::new("read".to_owned()),
+			Scope::new("write".to_owned()),
+		]));
+
+		let converted = TokenResponse::try_from(response.clone()).unwrap();
+		assert_eq!(converted.access_token.as_str(), "2YotnFZFEjr1zCsicMWpAA");
+		assert_eq!(converted.token_type, "bearer");
+		assert_eq!(converted.expires_in, Some(3600));
+		assert_eq!(
+			converted.scope.as_deref().map(|s| s.as_str()),
+			Some("read write")
+		);
+
+		let back = BasicTokenResponse::from(converted);
+		assert_eq!(
+			serde_json::to_value(&back).unwrap(),
+			serde_json::to_value(&response).unwrap()
+		);
+	}
+}
@@ -2,9 +2,10 @@ use iref::{Uri, UriBuf, UriRef};
 use serde::de::DeserializeOwned;
 
 use crate::{
+	EndpointUriPolicy, InsecureEndpointUri,
 	client::OAuth2ClientError,
 	http,
-	transport::{APPLICATION_JSON, HttpClient, expect_content_type},
+	transport::{APPLICATION_JSON, HttpClient, ResponseDecoder},
 };
 
 pub trait Discoverable: DeserializeOwned {
@@ -12,15 +13,37 @@ pub trait Discoverable: DeserializeOwned {
 
 	fn validate(&self, base_url: &Uri) -> Result<(), OAuth2ClientError>;
 
+	/// Fails if an advertised endpoint URI is not accepted by the given
+	/// policy.
+	fn check_endpoint_uris(&self, policy: EndpointUriPolicy) -> Result<(), InsecureEndpointUri>;
+
+	/// Discovers the metadata published at the well-known URI of `base_url`.
+	///
+	/// Fails if `base_url` or an advertised endpoint is not an `https` URI,
+	/// except on a loopback address. See
+	/// [`discover_with_policy`](Self::discover_with_policy).
 	#[allow(async_fn_in_trait)]
 	async fn discover(
 		http_client: &impl HttpClient,
 		base_url: &Uri,
 	) -> Result<Self, OAuth2ClientError> {
+		Self::discover_with_policy(http_client, base_url, EndpointUriPolicy::default()).await
+	}
+
+	/// Discovers the metadata published at the well-known URI of `base_url`,
+	/// checking `base_url` and the advertised endpoints against the given
+	/// policy.
+	#[allow(async_fn_in_trait)]
+	async fn discover_with_policy(
+		http_client: &impl HttpClient,
+		base_url: &Uri,
+		policy: EndpointUriPolicy,
+	) -> Result<Self, OAuth2ClientError> {
+		policy.check(base_url).map_err(OAuth2ClientError::request)?;
 		let discovery_url = well_known_uri(base_url, Self::WELL_KNOWN_URI_REF);
 		let discovery_request = discovery_request(&discovery_url);
 		let http_response = http_client.send(discovery_request).await?;
-		discovery_response(base_url, http_response)
+		discovery_response(base_url, policy, http_response)
 	}
 }
 
@@ -55,18 +78,16 @@ fn discovery_request(discovery_url: &Uri) -> http::Request<Vec<u8>> {
 
 fn discovery_response<T: Discoverable>(
 	base_url: &Uri,
+	policy: EndpointUriPolicy,
 	discovery_response: http::Response<Vec<u8>>,
 ) -> Result<T, OAuth2ClientError> {
-	let status = discovery_response.status();
-	if status != http::StatusCode::OK {
-		return Err(OAuth2ClientError::ServerError(status));
-	}
-
-	expect_content_type(discovery_response.headers(), &APPLICATION_JSON)?;
-
-	let metadata: T =
-		serde_json::from_slice(discovery_response.body()).map_err(OAuth2ClientError::response)?;
+	let metadata: T = ResponseDecoder::json(http::StatusCode::OK)
+		.decode(discovery_response)?
+		.into_body();
 	metadata.validate(base_url)?;
+	metadata
+		.check_endpoint_uris(policy)
+		.map_err(OAuth2ClientError::response)?;
 
 	Ok(metadata)
 }
@@ -75,8 +96,57 @@ fn discovery_response<T: Discoverable>(
 mod tests {
 	use iref::{uri, uri_ref};
 
+	use crate::server::AuthorizationServerMetadata;
+
 	use super::*;
 
+	/// Authorization server advertising a plain `http` token endpoint.
+	struct InsecureServer;
+
+	impl HttpClient for InsecureServer {
+		async fn send(
+			&self,
+			request: http::Request<Vec<u8>>,
+		) -> Result<http::Response<Vec<u8>>, OAuth2ClientError> {
+			assert_eq!(
+				request.uri(),
+				"https://server.example.com/.well-known/oauth-authorization-server"
+			);
+
+			Ok(http::Response::builder()
+				.header(http::header::CONTENT_TYPE, APPLICATION_JSON)
+				.body(
+					serde_json::to_vec(&serde_json::json!({
+						"issuer": "https://server.example.com",
+						"token_endpoint": "http://server.example.com/token"
+					}))
+					.unwrap(),
+				)
+				.unwrap())
+		}
+	}
+
+	#[tokio::test]
+	async fn insecure_discovery() {
+		let issuer = uri!("https://server.example.com");
+		assert!(matches!(
+			<AuthorizationServerMetadata>::discover(&InsecureServer, issuer).await,
+			Err(OAuth2ClientError::Response(_))
+		));
+
+		let metadata = <AuthorizationServerMetadata>::discover_with_policy(
+			&InsecureServer,
+			issuer,
+			EndpointUriPolicy::AllowInsecureHttp,
+		)
+		.await
+		.unwrap();
+		assert_eq!(
+			metadata.token_endpoint.as_deref(),
+			Some(uri!("http://server.example.com/token"))
+		);
+	}
+
 	#[test]
 	fn test_well_known_uri1() {
 		let result = well_known_uri(
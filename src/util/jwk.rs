@@ -0,0 +1,94 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+/// JSON Web Key.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc7517#section-4>
+///
+/// Only the common parameters are typed: the key material (e.g. `crv`, `x`,
+/// `y` for elliptic curve keys) is kept in [`params`](Self::params), since
+/// this crate does not implement any cryptographic algorithm.
+#[skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Jwk {
+	/// Key type (e.g. `EC`, `RSA`, `OKP`).
+	pub kty: String,
+
+	/// Key identifier.
+	pub kid: Option<String>,
+
+	/// Intended use of the public key (`sig` or `enc`).
+	#[serde(rename = "use")]
+	pub use_: Option<String>,
+
+	/// Algorithm intended for use with the key.
+	pub alg: Option<String>,
+
+	/// Other key parameters.
+	#[serde(flatten)]
+	pub params: BTreeMap<String, serde_json::Value>,
+}
+
+/// Members holding private or symmetric key material.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc7518#section-6>
+const PRIVATE_KEY_PARAMS: [&str; 8] = ["d", "p", "q", "dp", "dq", "qi", "oth", "k"];
+
+impl Jwk {
+	/// Checks that this key can be published, i.e. that it is not a
+	/// symmetric (`oct`) key and has no private key members.
+	pub fn is_public(&self) -> bool {
+		self.kty != "oct"
+			&& !PRIVATE_KEY_PARAMS
+				.iter()
+				.any(|name| self.params.contains_key(*name))
+	}
+
+	/// Returns the public part of this key, without its private key members.
+	///
+	/// Returns `None` for symmetric (`oct`) keys, which have no public part.
+	pub fn to_public(&self) -> Option<Self> {
+		if self.kty == "oct" {
+			return None;
+		}
+
+		let mut public = self.clone();
+		public
+			.params
+			.retain(|name, _| !PRIVATE_KEY_PARAMS.contains(&name.as_str()));
+		Some(public)
+	}
+}
+
+/// JSON Web Key Set.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc7517#section-5>
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct JwkSet {
+	/// The keys of the set.
+	pub keys: Vec<Jwk>,
+}
+
+impl JwkSet {
+	/// Creates a new key set.
+	pub fn new(keys: Vec<Jwk>) -> Self {
+		Self { keys }
+	}
+
+	/// Returns the public part of the keys of this set, leaving out the
+	/// symmetric keys.
+	///
+	/// See [`Jwk::to_public`].
+	pub fn to_public(&self) -> Self {
+		Self::new(self.keys.iter().filter_map(Jwk::to_public).collect())
+	}
+
+	/// Returns the key with the given identifier, if any.
+	pub fn find(&self, kid: &str) -> Option<&Jwk> {
+		self.keys.iter().find(|key| key.kid.as_deref() == Some(kid))
+	}
+}
@@ -0,0 +1,181 @@
+#[cfg(any(feature = "oidc", feature = "jar", feature = "axum"))]
+use std::time::{Duration, SystemTime};
+use std::{collections::BTreeMap, future::Future};
+
+#[cfg(any(feature = "oidc", feature = "jar", feature = "axum"))]
+use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+#[cfg(any(feature = "oidc", feature = "jar", feature = "axum"))]
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+/// JOSE header of a JWS.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc7515#section-4>
+#[skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JwsHeader {
+	/// Signature algorithm.
+	pub alg: String,
+
+	/// Key identifier.
+	pub kid: Option<String>,
+
+	/// Media type of the JWS.
+	pub typ: Option<String>,
+
+	/// Media type of the payload, `JWT` for nested JWTs.
+	pub cty: Option<String>,
+
+	/// Other header parameters.
+	#[serde(flatten)]
+	pub ext: BTreeMap<String, serde_json::Value>,
+}
+
+/// JWS signature verifier.
+///
+/// This crate does not implement any cryptographic algorithm: the signature
+/// check is delegated to this trait, typically implemented with the keys
+/// published by the issuer of the JWS.
+pub trait JwsSignatureVerifier: Send + Sync {
+	/// Verifies the `signature` of the `signing_input` of a JWS with the
+	/// given header.
+	///
+	/// Implementations must reject the algorithms they do not expect.
+	fn verify_signature(
+		&self,
+		header: &JwsHeader,
+		signing_input: &[u8],
+		signature: &[u8],
+	) -> impl Send + Future<Output = bool>;
+}
+
+/// Compact JWS, with its header and signature decoded.
+#[cfg(any(feature = "oidc", feature = "jar", feature = "axum"))]
+pub(crate) struct CompactJws<'a> {
+	/// JOSE header.
+	pub header: JwsHeader,
+
+	/// Encoded `header.payload` prefix, input of the signature.
+	pub signing_input: &'a str,
+
+	/// Encoded payload.
+	pub payload: &'a str,
+
+	/// Signature.
+	pub signature: Vec<u8>,
+}
+
+#[cfg(any(feature = "oidc", feature = "jar", feature = "axum"))]
+impl CompactJws<'_> {
+	/// Verifies the signature with the given verifier.
+	pub async fn verify_signature(&self, verifier: &impl JwsSignatureVerifier) -> bool {
+		verifier
+			.verify_signature(&self.header, self.signing_input.as_bytes(), &self.signature)
+			.await
+	}
+
+	/// Decodes the JSON payload.
+	pub fn claims<T: DeserializeOwned>(&self) -> Option<T> {
+		decode_json(self.payload)
+	}
+}
+
+/// Splits the given compact JWS into its parts, decoding its header and
+/// signature.
+///
+/// Returns `None` if the JWS is malformed. The signature is not checked.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc7515#section-7.1>
+#[cfg(any(feature = "oidc", feature = "jar", feature = "axum"))]
+pub(crate) fn decode_compact_jws(jws: &str) -> Option<CompactJws<'_>> {
+	let [header, payload, signature] = jws.split('.').collect::<Vec<_>>()[..] else {
+		return None;
+	};
+
+	Some(CompactJws {
+		header: decode_json(header)?,
+		signing_input: &jws[..header.len() + 1 + payload.len()],
+		payload,
+		signature: BASE64_URL_SAFE_NO_PAD.decode(signature).ok()?,
+	})
+}
+
+/// Decodes a base64url-encoded JSON part of a JOSE object.
+#[cfg(any(feature = "oidc", feature = "jar", feature = "axum"))]
+pub(crate) fn decode_json<T: DeserializeOwned>(part: &str) -> Option<T> {
+	let bytes = BASE64_URL_SAFE_NO_PAD.decode(part).ok()?;
+	serde_json::from_slice(&bytes).ok()
+}
+
+/// Converts a NumericDate claim into a point in time, if representable.
+///
+/// See: <https://datatracker.ietf.org/doc/html/rfc7519#section-2>
+#[cfg(any(feature = "oidc", feature = "jar", feature = "axum"))]
+pub(crate) fn unix_time(secs: u64) -> Option<SystemTime> {
+	SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(secs))
+}
+
+/// Converts a point in time into a NumericDate claim.
+#[cfg(feature = "oidc")]
+pub(crate) fn unix_timestamp(time: SystemTime) -> u64 {
+	time.duration_since(SystemTime::UNIX_EPOCH)
+		.unwrap_or_default()
+		.as_secs()
+}
+
+#[cfg(all(test, any(feature = "oidc", feature = "jar", feature = "axum")))]
+pub(crate) mod tests {
+	use super::*;
+
+	/// Accepts the `HS256` signatures equal to `signed`.
+	pub struct TestVerifier;
+
+	impl JwsSignatureVerifier for TestVerifier {
+		async fn verify_signature(
+			&self,
+			header: &JwsHeader,
+			_signing_input: &[u8],
+			signature: &[u8],
+		) -> bool {
+			header.alg == "HS256" && signature == b"signed"
+		}
+	}
+
+	/// Builds a compact JWS from its parts.
+	pub fn jws(header: serde_json::Value, payload: &[u8], signature: &[u8]) -> String {
+		format!(
+			"{}.{}.{}",
+			BASE64_URL_SAFE_NO_PAD.encode(header.to_string()),
+			BASE64_URL_SAFE_NO_PAD.encode(payload),
+			BASE64_URL_SAFE_NO_PAD.encode(signature)
+		)
+	}
+
+	#[test]
+	fn compact_jws() {
+		let token = jws(
+			serde_json::json!({ "typ": "JWT", "alg": "HS256" }),
+			br#"{"sub":"alice"}"#,
+			b"signed",
+		);
+		let jws = decode_compact_jws(&token).unwrap();
+		assert_eq!(jws.header.alg, "HS256");
+		assert_eq!(jws.signing_input, &token[..token.rfind('.').unwrap()]);
+		assert_eq!(jws.signature, b"signed");
+		assert_eq!(jws.claims::<serde_json::Value>().unwrap()["sub"], "alice");
+
+		assert!(decode_compact_jws("a.b").is_none());
+		assert!(decode_compact_jws(&format!("{token}.")).is_none());
+		assert!(decode_compact_jws("e30.e30.!").is_none());
+	}
+
+	#[test]
+	fn numeric_date() {
+		assert_eq!(
+			unix_time(1000),
+			Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1000))
+		);
+		assert_eq!(unix_time(u64::MAX), None);
+	}
+}
@@ -1,18 +1,250 @@
 //! URI query string utilities.
+use std::fmt;
+
 use iref::{
-	UriBuf,
+	Uri, UriBuf,
 	uri::{Query, QueryBuf},
 };
-use serde::{Deserialize, Serialize};
+use serde::{
+	Deserialize, Serialize,
+	de::{DeserializeOwned, MapAccess, Visitor},
+	ser::SerializeMap,
+};
 
+#[cfg(feature = "compat-oauth2")]
+mod compat_oauth2;
 mod discoverable;
+mod jwk;
+mod jws;
+mod raw_json;
+#[cfg(feature = "url")]
+mod url;
 
+#[cfg(feature = "url")]
+pub use self::url::*;
+#[cfg(feature = "compat-oauth2")]
+pub use compat_oauth2::*;
 pub use discoverable::*;
+pub use jwk::*;
+pub use jws::*;
+pub use raw_json::*;
 
 /// Placeholder type for structs that carry no extension fields.
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[derive(
+	Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct NoExtension {}
 
+/// Extension type holding arbitrary JSON fields.
+///
+/// Used in place of a typed extension (e.g. in
+/// [`TokenResponse`](crate::endpoints::token::TokenResponse) or
+/// [`ErrorResponse`](crate::server::ErrorResponse)) to inspect
+/// provider-specific fields without defining a dedicated type. Fields are
+/// serialized back unchanged, and can later be parsed into a typed extension
+/// with [`parse`](Self::parse).
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(transparent)]
+pub struct AnyExtension(pub serde_json::Map<String, serde_json::Value>);
+
+impl AnyExtension {
+	/// Deserializes the field with the given name.
+	///
+	/// Returns `Ok(None)` if the field is absent.
+	pub fn get_as<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, serde_json::Error> {
+		self.0
+			.get(key)
+			.map(|value| T::deserialize(value))
+			.transpose()
+	}
+
+	/// Parses all the fields into a typed extension.
+	pub fn parse<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+		T::deserialize(&serde_json::Value::Object(self.0.clone()))
+	}
+}
+
+impl std::ops::Deref for AnyExtension {
+	type Target = serde_json::Map<String, serde_json::Value>;
+
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl std::ops::DerefMut for AnyExtension {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.0
+	}
+}
+
+impl From<serde_json::Map<String, serde_json::Value>> for AnyExtension {
+	fn from(value: serde_json::Map<String, serde_json::Value>) -> Self {
+		Self(value)
+	}
+}
+
+/// Extension type whose decoding failure does not fail the enclosing
+/// document.
+///
+/// Providers sometimes omit or malform fields expected by a typed extension
+/// `E`. Flattened in a document such as a
+/// [`TokenResponse`](crate::endpoints::token::TokenResponse), `E` would then
+/// fail the whole document, losing the core fields (e.g. the access token).
+/// `TryExt<E>` instead captures the failure, along with the raw extension
+/// fields, so that callers can log the mismatch and carry on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TryExt<E>(pub Result<E, InvalidExtension>);
+
+impl<E> TryExt<E> {
+	/// Returns the extension, if it was successfully decoded.
+	pub fn ok(&self) -> Option<&E> {
+		self.0.as_ref().ok()
+	}
+
+	/// Returns the decoding failure, if any.
+	pub fn error(&self) -> Option<&InvalidExtension> {
+		self.0.as_ref().err()
+	}
+
+	/// Returns the decoding result.
+	pub fn into_result(self) -> Result<E, InvalidExtension> {
+		self.0
+	}
+}
+
+impl<E> From<E> for TryExt<E> {
+	fn from(value: E) -> Self {
+		Self(Ok(value))
+	}
+}
+
+impl<E: Serialize> Serialize for TryExt<E> {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		match &self.0 {
+			Ok(ext) => ext.serialize(serializer),
+			Err(e) => e.fields.serialize(serializer),
+		}
+	}
+}
+
+impl<'de, E: DeserializeOwned> Deserialize<'de> for TryExt<E> {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let fields = AnyExtension::deserialize(deserializer)?;
+		Ok(Self(fields.parse().map_err(|e| InvalidExtension {
+			message: e.to_string(),
+			fields,
+		})))
+	}
+}
+
+/// Extension fields that could not be decoded by a [`TryExt`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid extension fields: {message}")]
+pub struct InvalidExtension {
+	/// Decoding error message.
+	pub message: String,
+
+	/// Raw extension fields.
+	pub fields: AnyExtension,
+}
+
+/// Query parameters already present in an endpoint URI.
+///
+/// Unlike a map, the parameters keep their original order, and parameters
+/// included more than once are all preserved. The parameters serialize as a
+/// map, so they can be [flattened](https://serde.rs/attr-flatten.html) into
+/// a request.
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ExistingQuery(pub Vec<(String, String)>);
+
+impl ExistingQuery {
+	/// Parses the query parameters of the given URI.
+	///
+	/// Malformed parameters are ignored.
+	pub fn of(uri: &Uri) -> Self {
+		uri.query().map(Self::parse).unwrap_or_default()
+	}
+
+	/// Parses the given query.
+	///
+	/// Malformed parameters are ignored.
+	pub fn parse(query: &Query) -> Self {
+		Self(serde_html_form::from_str(query.as_str()).unwrap_or_default())
+	}
+
+	/// Checks whether there are no parameters.
+	pub fn is_empty(&self) -> bool {
+		self.0.is_empty()
+	}
+
+	/// Returns the value of the first parameter with the given name.
+	pub fn get(&self, name: &str) -> Option<&str> {
+		self.0
+			.iter()
+			.find(|(n, _)| n == name)
+			.map(|(_, value)| value.as_str())
+	}
+
+	/// Returns an iterator over the parameters, in their original order.
+	pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+		self.0
+			.iter()
+			.map(|(name, value)| (name.as_str(), value.as_str()))
+	}
+}
+
+impl Serialize for ExistingQuery {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		let mut map = serializer.serialize_map(Some(self.0.len()))?;
+		for (name, value) in &self.0 {
+			map.serialize_entry(name, value)?;
+		}
+		map.end()
+	}
+}
+
+impl<'de> Deserialize<'de> for ExistingQuery {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		/// Value of a parameter, repeated parameters being grouped together
+		/// by some deserializers.
+		#[derive(Deserialize)]
+		#[serde(untagged)]
+		enum Values {
+			One(String),
+			Many(Vec<String>),
+		}
+
+		struct ExistingQueryVisitor;
+
+		impl<'de> Visitor<'de> for ExistingQueryVisitor {
+			type Value = ExistingQuery;
+
+			fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+				f.write_str("query parameters")
+			}
+
+			fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+				let mut parameters = Vec::new();
+				while let Some((name, values)) = map.next_entry::<String, Values>()? {
+					match values {
+						Values::One(value) => parameters.push((name, value)),
+						Values::Many(values) => {
+							parameters.extend(values.into_iter().map(|value| (name.clone(), value)))
+						}
+					}
+				}
+
+				Ok(ExistingQuery(parameters))
+			}
+		}
+
+		deserializer.deserialize_map(ExistingQueryVisitor)
+	}
+}
+
 /// Extends the query parameters of a URI by serializing `value` as
 /// `application/x-www-form-urlencoded` and appending the result.
 ///
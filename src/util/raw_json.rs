@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::value::RawValue;
+
+/// JSON document decoded as `T`, keeping its raw body alive.
+///
+/// Extension types such as [`AnyExtension`](super::AnyExtension) decode
+/// every unrecognized field into a [`serde_json::Value`] tree, which is
+/// costly for large payloads (e.g. the `authorization_details` of a
+/// [`TokenResponse`](crate::endpoints::token::TokenResponse)). Decoding the
+/// response as `RawJson<TokenResponse>` instead only decodes the core
+/// fields, while the other fields can be read from the raw body when needed,
+/// either as [`RawValue`]s with [`raw_field`](Self::raw_field), or with
+/// [`field`](Self::field) into types borrowing from the body.
+///
+/// It can be used as the
+/// [`OAuth2Client::TokenResponse`](crate::client::OAuth2Client::TokenResponse)
+/// type, but only with JSON responses.
+#[derive(Debug, Clone)]
+pub struct RawJson<T> {
+	value: T,
+	raw: Box<RawValue>,
+}
+
+impl<T> RawJson<T> {
+	/// Decodes the given raw JSON document.
+	pub fn new(raw: Box<RawValue>) -> Result<Self, serde_json::Error>
+	where
+		T: DeserializeOwned,
+	{
+		Ok(Self {
+			value: serde_json::from_str(raw.get())?,
+			raw,
+		})
+	}
+
+	/// Returns the decoded value.
+	pub fn value(&self) -> &T {
+		&self.value
+	}
+
+	/// Returns the decoded value, dropping the raw body.
+	pub fn into_value(self) -> T {
+		self.value
+	}
+
+	/// Returns the raw body.
+	pub fn raw(&self) -> &RawValue {
+		&self.raw
+	}
+
+	/// Returns the raw value of the top-level field with the given name,
+	/// without decoding it.
+	///
+	/// Returns `Ok(None)` if the field is absent, and fails if the document
+	/// is not an object.
+	pub fn raw_field(&self, name: &str) -> Result<Option<&RawValue>, serde_json::Error> {
+		let mut fields: BTreeMap<String, &RawValue> = serde_json::from_str(self.raw.get())?;
+		Ok(fields.remove(name))
+	}
+
+	/// Deserializes the top-level field with the given name, borrowing from
+	/// the raw body.
+	///
+	/// Returns `Ok(None)` if the field is absent.
+	pub fn field<'a, U>(&'a self, name: &str) -> Result<Option<U>, serde_json::Error>
+	where
+		U: Deserialize<'a>,
+	{
+		self.raw_field(name)?
+			.map(|value| serde_json::from_str(value.get()))
+			.transpose()
+	}
+}
+
+impl<T> std::ops::Deref for RawJson<T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		&self.value
+	}
+}
+
+impl<T: PartialEq> PartialEq for RawJson<T> {
+	fn eq(&self, other: &Self) -> bool {
+		self.value == other.value
+	}
+}
+
+impl<T> Serialize for RawJson<T> {
+	/// Serializes the raw body, unchanged.
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		self.raw.serialize(serializer)
+	}
+}
+
+impl<'de, T: DeserializeOwned> Deserialize<'de> for RawJson<T> {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let raw = Box::<RawValue>::deserialize(deserializer)?;
+		Self::new(raw).map_err(serde::de::Error::custom)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::endpoints::token::TokenResponse;
+
+	use super::*;
+
+	#[derive(Debug, PartialEq, Deserialize)]
+	struct AuthorizationDetail<'a> {
+		#[serde(rename = "type")]
+		type_: &'a str,
+
+		#[serde(borrow)]
+		locations: Vec<&'a str>,
+	}
+
+	const TOKEN_RESPONSE: &str = r#"{
+		"access_token": "2YotnFZFEjr1zCsicMWpAA",
+		"token_type": "Bearer",
+		"expires_in": 3600,
+		"authorization_details": [{
+			"type": "payment_initiation",
+			"locations": ["https://example.com/payments"],
+			"instructedAmount": { "currency": "EUR", "amount": "123.50" }
+		}]
+	}"#;
+
+	#[test]
+	fn token_response() {
+		let response: RawJson<TokenResponse> = serde_json::from_str(TOKEN_RESPONSE).unwrap();
+		assert_eq!(response.access_token.as_str(), "2YotnFZFEjr1zCsicMWpAA");
+		assert_eq!(response.expires_in, Some(3600));
+
+		let details: Vec<AuthorizationDetail> =
+			response.field("authorization_details").unwrap().unwrap();
+		assert_eq!(
+			details,
+			[AuthorizationDetail {
+				type_: "payment_initiation",
+				locations: vec!["https://example.com/payments"],
+			}]
+		);
+
+		assert!(
+			response
+				.raw_field("authorization_details")
+				.unwrap()
+				.unwrap()
+				.get()
+				.contains("instructedAmount")
+		);
+		assert!(response.raw_field("id_token").unwrap().is_none());
+		assert_eq!(
+			serde_json::to_string(&response).unwrap(),
+			response.raw().get()
+		);
+	}
+
+	#[test]
+	fn invalid() {
+		assert!(
+			serde_json::from_str::<RawJson<TokenResponse>>(r#"{"token_type":"Bearer"}"#).is_err()
+		);
+		assert!(
+			RawJson::<serde_json::Value>::new(RawValue::from_string("[]".to_owned()).unwrap())
+				.unwrap()
+				.raw_field("access_token")
+				.is_err()
+		);
+	}
+}
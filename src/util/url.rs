@@ -0,0 +1,77 @@
+use iref::{Uri, UriBuf};
+use url::Url;
+
+/// Error returned when converting between a [`Url`] and a [`Uri`].
+///
+/// The WHATWG URL Standard implemented by [`url`] and RFC 3986 disagree on a
+/// few edge cases, such as unencoded `|` characters in paths, so the
+/// conversion can fail in both directions.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum UrlConversionError {
+	/// The URL is not a valid RFC 3986 URI.
+	#[error("invalid URI `{0}`")]
+	InvalidUri(String),
+
+	/// The URI is not a valid WHATWG URL.
+	#[error(transparent)]
+	InvalidUrl(#[from] url::ParseError),
+}
+
+/// Extension trait converting a [`Url`] into a [`Uri`].
+///
+/// Conversions are provided as extension traits rather than `From`
+/// implementations, since neither type is defined by this crate.
+pub trait UrlExt {
+	/// Borrows this URL as a URI.
+	fn as_uri(&self) -> Result<&Uri, UrlConversionError>;
+
+	/// Converts this URL into an owned URI.
+	fn to_uri_buf(&self) -> Result<UriBuf, UrlConversionError> {
+		self.as_uri().map(ToOwned::to_owned)
+	}
+}
+
+impl UrlExt for Url {
+	fn as_uri(&self) -> Result<&Uri, UrlConversionError> {
+		Uri::new(self.as_str()).map_err(|_| UrlConversionError::InvalidUri(self.to_string()))
+	}
+}
+
+/// Extension trait converting a [`Uri`] into a [`Url`].
+pub trait UriExt {
+	/// Parses this URI as a URL.
+	fn to_url(&self) -> Result<Url, UrlConversionError>;
+}
+
+impl UriExt for Uri {
+	fn to_url(&self) -> Result<Url, UrlConversionError> {
+		Ok(Url::parse(self.as_str())?)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use iref::uri;
+
+	use super::*;
+
+	#[test]
+	fn round_trip() {
+		let url = Url::parse("https://server.example.com/authorize?tenant=a#frag").unwrap();
+		let uri = url.as_uri().unwrap();
+		assert_eq!(
+			uri,
+			uri!("https://server.example.com/authorize?tenant=a#frag")
+		);
+		assert_eq!(uri.to_url().unwrap(), url);
+	}
+
+	#[test]
+	fn invalid_uri() {
+		let url = Url::parse("https://server.example.com/a|b").unwrap();
+		assert!(matches!(
+			url.as_uri(),
+			Err(UrlConversionError::InvalidUri(_))
+		));
+	}
+}